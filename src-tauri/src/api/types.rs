@@ -18,6 +18,9 @@ pub type AgentId = String;
 /// Unique identifier for an ACP session (UUID v4 string)
 pub type SessionId = String;
 
+/// Identifier for a prompt turn: the client-assigned `session/prompt` request id
+pub type TurnId = String;
+
 /// Unique identifier for a terminal run (UUID v4 string)
 pub type TerminalId = String;
 
@@ -29,11 +32,139 @@ pub struct WorkspaceSummary {
     pub workspace_id: WorkspaceId,
     /// Canonicalized absolute path to workspace root
     pub root_dir: String,
+    /// Display name: the user's chosen name, else the root directory's name
+    pub name: Option<String>,
     /// Timestamp when workspace was created (milliseconds since epoch)
     /// Using f64 for JavaScript number compatibility
     pub created_at_ms: f64,
 }
 
+/// One page of a list, plus the length of the whole list
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct Page<T> {
+    /// Items on this page, in list order
+    pub items: Vec<T>,
+    /// Number of items in the whole list
+    pub total: u32,
+}
+
+impl<T> Page<T> {
+    /// Cut the page starting at `offset` (default 0) holding at most `limit`
+    /// items (default: all remaining) out of `items`.
+    ///
+    /// An offset past the end yields an empty page.
+    pub fn slice(items: Vec<T>, offset: Option<u32>, limit: Option<u32>) -> Self {
+        let total = u32::try_from(items.len()).unwrap_or(u32::MAX);
+        let offset = offset.unwrap_or(0) as usize;
+        let limit = limit.map_or(usize::MAX, |limit| limit as usize);
+        Self {
+            items: items.into_iter().skip(offset).take(limit).collect(),
+            total,
+        }
+    }
+
+    /// Convert every item on the page, keeping the total.
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            total: self.total,
+        }
+    }
+}
+
+/// Size limits applied to agent file reads and command output in a workspace
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceLimits {
+    /// Largest file (in bytes) an agent may read
+    pub max_read_bytes: u32,
+    /// Bytes of `terminal/run` output kept per stream for the agent
+    pub output_capture_limit: u32,
+    /// Terminal commands that may run at once
+    pub max_terminals: u32,
+    /// Prompts an agent may have outstanding per session: the running turn
+    /// plus those queued behind it
+    pub max_outstanding_prompts: u32,
+}
+
+/// Shell used to run terminal commands in a workspace
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminalShell {
+    /// Shell executable: a path, or a name looked up on `PATH`
+    pub program: String,
+    /// Arguments for the shell; an argument equal to `{command}` is replaced
+    /// by the command line, which is otherwise appended last (e.g. `["-c"]`)
+    pub args: Vec<String>,
+}
+
+/// One entry of a workspace directory listing
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct DirEntryInfo {
+    /// File or directory name (no path)
+    pub name: String,
+    /// Whether the entry is a directory (symlinks report their target)
+    pub is_dir: bool,
+    /// Size in bytes (0 for directories)
+    pub size: f64,
+}
+
+/// Size, modification time and type of a workspace path
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct FileMetadata {
+    /// The entry is a regular file (false for a symlink itself)
+    pub is_file: bool,
+    /// The entry is a directory (false for a symlink itself)
+    pub is_dir: bool,
+    /// The entry is a symlink; size and time then describe its target
+    pub is_symlink: bool,
+    /// Size in bytes
+    pub len: f64,
+    /// Last modification (milliseconds since epoch), if the platform reports it
+    pub modified_ms: Option<f64>,
+}
+
+/// An adapter process spawned by this app that hasn't been released yet
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct AdapterProcessInfo {
+    /// OS process id
+    pub pid: u32,
+    /// Executable the adapter was launched from
+    pub program: String,
+    /// Working directory (workspace root) it was launched in
+    pub cwd: String,
+    /// Spawn time (milliseconds since epoch)
+    pub started_at_ms: f64,
+}
+
+/// A problem found while parsing a workspace `.env` file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvFileWarning {
+    /// 1-based line number
+    pub line: u32,
+    /// Description of the problem
+    pub message: String,
+}
+
+/// Preview of the variables a workspace `.env` would pass to adapters.
+///
+/// Values are never included; only variable names are exposed.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvPreview {
+    /// Whether a `.env` file exists in the workspace root
+    pub exists: bool,
+    /// Variable names that would be injected, in file order
+    pub keys: Vec<String>,
+    /// Lines that were skipped or overridden
+    pub warnings: Vec<EnvFileWarning>,
+}
+
 /// Summary of an agent returned to the frontend
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 #[serde(rename_all = "camelCase")]
@@ -46,6 +177,48 @@ pub struct AgentSummary {
     pub plugin_id: String,
     /// Optional display name for the agent
     pub display_name: Option<String>,
+    /// Whether the agent is restarted automatically after an unexpected exit
+    pub auto_restart: bool,
+    /// What happens to a prompt sent while a turn is in progress
+    pub busy_prompt_policy: BusyPromptPolicy,
+}
+
+/// How an agent handles a prompt sent while it is still working on a turn
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum BusyPromptPolicy {
+    /// Hold the prompt and send it once the current turn completes
+    #[default]
+    Queue,
+    /// Refuse the prompt with `ApiError::TurnInProgress`
+    Reject,
+}
+
+/// Environment variable passed to an MCP server process
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct McpEnvVariable {
+    pub name: String,
+    pub value: String,
+}
+
+/// Stdio MCP server an agent's adapter should connect to.
+///
+/// Sent with `session/new` and `session/load` so the agent can use the
+/// server's tools.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct McpServerConfig {
+    /// Human-readable server name (e.g., "filesystem")
+    pub name: String,
+    /// Executable that launches the server
+    pub command: String,
+    /// Command-line arguments for the server
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Environment variables for the server process
+    #[serde(default)]
+    pub env: Vec<McpEnvVariable>,
 }
 
 /// Plugin installation and update status returned to the frontend
@@ -66,6 +239,81 @@ pub struct PluginStatus {
     pub bin_path: Option<String>,
 }
 
+/// Disk usage of the app cache returned to the frontend
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheInfo {
+    /// Absolute path to the plugins cache directory
+    pub plugins_root: String,
+    /// Installed plugin IDs found in the cache
+    pub plugin_ids: Vec<String>,
+    /// Total size of the cache in bytes
+    /// Using f64 for JavaScript number compatibility
+    pub total_bytes: f64,
+}
+
+/// Installed plugin and its version, as reported by `diagnostics`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct InstalledPluginVersion {
+    pub plugin_id: String,
+    /// None if the install metadata is missing or unreadable
+    pub installed_version: Option<String>,
+}
+
+/// Snapshot of app and runtime state for bug reports
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostics {
+    /// Version of the app backend (`CARGO_PKG_VERSION`)
+    pub app_version: String,
+    pub workspace_count: u32,
+    /// Agents across all workspaces, running or not
+    pub agent_count: u32,
+    /// Agents whose adapter process is running
+    pub running_agent_count: u32,
+    /// Terminal commands still running
+    pub active_terminal_count: u32,
+    /// Permission requests awaiting a decision
+    pub pending_permission_count: u32,
+    /// Plugins in the local cache, sorted by ID
+    pub plugins: Vec<InstalledPluginVersion>,
+}
+
+/// What `cache_clear` removed
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheClearResult {
+    /// Plugin IDs whose installation was removed
+    pub removed_plugins: Vec<String>,
+    /// Temp files of interrupted agent writes removed from workspaces
+    pub removed_temp_files: u32,
+    /// Exited terminals whose retained output was dropped
+    pub released_terminals: u32,
+    /// Bytes freed on disk
+    /// Using f64 for JavaScript number compatibility
+    pub freed_bytes: f64,
+}
+
+/// Availability of the Node.js toolchain needed to install plugins.
+///
+/// Returned by the install preflight so the UI can explain what is missing
+/// before any permission prompt or filesystem work happens.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolchainInfo {
+    /// Detected Node.js version without the leading "v" (None if `node` is missing)
+    pub node_version: Option<String>,
+    /// Detected npm version (None if `npm` is missing)
+    pub npm_version: Option<String>,
+    /// Minimum Node.js version the adapters require
+    pub min_node_version: String,
+    /// Whether plugins can be installed with the detected toolchain
+    pub ready: bool,
+    /// Human-readable explanation when `ready` is false
+    pub message: Option<String>,
+}
+
 // ============================================================================
 // Permission System Types (US-04+)
 // ============================================================================
@@ -84,10 +332,77 @@ pub struct OperationStarted {
 pub enum PermissionDecision {
     /// Allow this operation once
     AllowOnce,
+    /// Allow this operation and remember the grant for matching requests
+    /// from the same workspace/agent
+    AllowAlways,
     /// Deny this operation
     Deny,
 }
 
+impl PermissionDecision {
+    /// Whether the operation may proceed.
+    pub fn is_allowed(self) -> bool {
+        matches!(self, Self::AllowOnce | Self::AllowAlways)
+    }
+}
+
+/// What choosing a permission option means (mirrors ACP `PermissionOptionKind`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum PermissionOptionKind {
+    /// Allow this operation only this time
+    AllowOnce,
+    /// Allow this operation and remember the choice
+    AllowAlways,
+    /// Reject this operation only this time
+    RejectOnce,
+    /// Reject this operation and remember the choice
+    RejectAlways,
+}
+
+impl PermissionOptionKind {
+    /// Parse ACP's snake_case wire name (e.g. "allow_always").
+    pub fn from_acp(kind: &str) -> Option<Self> {
+        match kind {
+            "allow_once" => Some(Self::AllowOnce),
+            "allow_always" => Some(Self::AllowAlways),
+            "reject_once" => Some(Self::RejectOnce),
+            "reject_always" => Some(Self::RejectAlways),
+            _ => None,
+        }
+    }
+
+    /// The allow/deny decision this kind implies.
+    pub fn decision(self) -> PermissionDecision {
+        match self {
+            Self::AllowOnce => PermissionDecision::AllowOnce,
+            Self::AllowAlways => PermissionDecision::AllowAlways,
+            Self::RejectOnce | Self::RejectAlways => PermissionDecision::Deny,
+        }
+    }
+}
+
+/// A choice offered by the agent for a permission request
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionOption {
+    /// Agent-defined identifier echoed back when chosen
+    pub option_id: String,
+    /// Label to show the user (e.g., "Always Allow")
+    pub name: String,
+    /// What choosing this option means
+    pub kind: PermissionOptionKind,
+}
+
+/// The user's answer to a permission request.
+///
+/// `option_id` is set when the request offered agent-defined options.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PermissionOutcome {
+    pub decision: PermissionDecision,
+    pub option_id: Option<String>,
+}
+
 /// Source of a permission request
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 #[serde(rename_all = "camelCase", tag = "type")]
@@ -100,13 +415,133 @@ pub enum PermissionSource {
     /// Agent-requested terminal execution
     TerminalRun { command: String },
     /// Agent-requested file read
-    FsReadTextFile { path: String },
+    FsReadTextFile {
+        /// Path exactly as supplied by the agent
+        path: String,
+        /// Canonical path relative to the workspace root (what will be read)
+        resolved_path: String,
+    },
     /// Agent-requested file write
     FsWriteTextFile {
+        /// Path exactly as supplied by the agent
         path: String,
+        /// Canonical path relative to the workspace root (what will be written)
+        resolved_path: String,
         content_preview: String,
         content_truncated: bool,
+        /// Content is appended rather than replacing the file
+        append: bool,
     },
+    /// Agent-requested file (or recursive directory) delete
+    FsDelete {
+        /// Path exactly as supplied by the agent
+        path: String,
+        /// Canonical path relative to the workspace root (what will be deleted)
+        resolved_path: String,
+        recursive: bool,
+    },
+    /// Agent-requested file rename (move)
+    FsRename {
+        /// Paths exactly as supplied by the agent
+        from: String,
+        to: String,
+        /// Canonical paths relative to the workspace root
+        resolved_from: String,
+        resolved_to: String,
+    },
+}
+
+/// The variant of a `PermissionSource`, without its details
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum PermissionSourceKind {
+    InstallPlugin,
+    TerminalRun,
+    FsReadTextFile,
+    FsWriteTextFile,
+    FsDelete,
+    FsRename,
+}
+
+impl PermissionSource {
+    /// Which kind of request this is.
+    pub fn kind(&self) -> PermissionSourceKind {
+        match self {
+            Self::InstallPlugin { .. } => PermissionSourceKind::InstallPlugin,
+            Self::TerminalRun { .. } => PermissionSourceKind::TerminalRun,
+            Self::FsReadTextFile { .. } => PermissionSourceKind::FsReadTextFile,
+            Self::FsWriteTextFile { .. } => PermissionSourceKind::FsWriteTextFile,
+            Self::FsDelete { .. } => PermissionSourceKind::FsDelete,
+            Self::FsRename { .. } => PermissionSourceKind::FsRename,
+        }
+    }
+}
+
+/// How a permission request was resolved, as recorded in the audit log
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum PermissionAuditDecision {
+    AllowOnce,
+    AllowAlways,
+    Deny,
+    /// Approved without asking by a remembered "Allow Always" grant
+    Remembered,
+    /// Approved without asking by a terminal command or file path rule from preferences
+    AllowedByRule,
+    /// Refused without asking by a terminal command or file path rule from preferences
+    DeniedByRule,
+    /// Nobody answered before the request timed out
+    TimedOut,
+    /// The operation was abandoned before anyone answered
+    Cancelled,
+}
+
+impl From<PermissionDecision> for PermissionAuditDecision {
+    fn from(decision: PermissionDecision) -> Self {
+        match decision {
+            PermissionDecision::AllowOnce => Self::AllowOnce,
+            PermissionDecision::AllowAlways => Self::AllowAlways,
+            PermissionDecision::Deny => Self::Deny,
+        }
+    }
+}
+
+/// One resolved permission request in the audit log
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionAuditEntry {
+    pub operation_id: OperationId,
+    pub source: PermissionSource,
+    pub origin: Option<PermissionOrigin>,
+    pub decision: PermissionAuditDecision,
+    pub decided_at_ms: f64,
+}
+
+/// What a remembered permission grant covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum PermissionRuleKind {
+    /// Terminal commands starting with the pattern
+    TerminalCommand,
+    /// File reads inside the pattern directory
+    FsRead,
+    /// File writes inside the pattern directory
+    FsWrite,
+}
+
+/// A remembered "Allow Always" grant, scoped to the workspace (and agent)
+/// that requested it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionRule {
+    pub rule_id: String,
+    pub kind: PermissionRuleKind,
+    /// Command prefix, or directory relative to the workspace root (`.`: all)
+    pub pattern: String,
+    pub workspace_id: WorkspaceId,
+    /// Agent the grant applies to (`None`: any agent in the workspace)
+    pub agent_id: Option<AgentId>,
+    pub created_at_ms: f64,
 }
 
 /// Origin context for a permission request (optional scoping)
@@ -139,10 +574,43 @@ pub struct AcpPermissionRequestedEvent {
     pub requested_at_ms: f64,
     /// Optional origin context
     pub origin: Option<PermissionOrigin>,
+    /// Agent-defined choices; empty means a plain allow/deny prompt
+    pub options: Vec<PermissionOption>,
+}
+
+/// Why a permission request ended without an answer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum PermissionExpiryReason {
+    /// Nobody answered before the request's timeout
+    TimedOut,
+    /// The operation that asked was abandoned
+    Cancelled,
+}
+
+/// Event payload: a permission request can no longer be answered
+/// (acp/permission_timed_out); the frontend should dismiss its prompt
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct AcpPermissionTimedOutEvent {
+    pub operation_id: OperationId,
+    pub reason: PermissionExpiryReason,
+}
+
+/// A terminal command that is still running
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminalInfo {
+    /// Terminal identifier
+    pub terminal_id: TerminalId,
+    /// Shell command line being run
+    pub command: String,
+    /// When the command was started (ms since epoch)
+    pub started_at_ms: f64,
 }
 
 /// Stream identifier for terminal output
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
 #[serde(rename_all = "camelCase")]
 pub enum TerminalStream {
     Stdout,
@@ -181,8 +649,32 @@ pub struct TerminalExitedEvent {
     pub terminal_id: TerminalId,
     /// Exit code (None if unavailable)
     pub exit_code: Option<i32>,
+    /// Signal that terminated the process (Unix only; None for normal exits)
+    pub signal: Option<i32>,
     /// Whether the user explicitly stopped the process
     pub user_stopped: bool,
+    /// Whether the process was killed for exceeding its timeout
+    pub timed_out: bool,
+}
+
+/// Kind of change reported by the workspace file watcher
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum FsChangeKind {
+    Created,
+    Modified,
+    Removed,
+}
+
+/// Event payload: workspace file changed (fs/changed)
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct FsChangedEvent {
+    /// Workspace being watched
+    pub workspace_id: WorkspaceId,
+    /// Changed path relative to the workspace root (`/` separators)
+    pub path: String,
+    pub kind: FsChangeKind,
 }
 
 /// Event payload: plugin status changed (acp/plugin_status_changed)
@@ -197,6 +689,28 @@ pub struct AcpPluginStatusChangedEvent {
     pub error: Option<String>,
 }
 
+/// Severity of a user-facing notification
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum NotificationLevel {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+/// Event payload: user-facing alert (notification/show)
+///
+/// Emitted when a native notification can't be shown, so the frontend can
+/// render it instead.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationShowEvent {
+    pub title: String,
+    pub body: Option<String>,
+    pub level: NotificationLevel,
+}
+
 // ============================================================================
 // Agent Runtime Types (US-06+)
 // ============================================================================
@@ -213,21 +727,197 @@ pub struct AcpSessionUpdateEvent {
     pub workspace_id: WorkspaceId,
     /// Agent identifier
     pub agent_id: AgentId,
+    /// Plugin the agent runs on, for labels (`agent_id` identifies the agent)
+    #[serde(default)]
+    pub plugin_id: String,
+    /// Agent's display name, if it has one, for labels
+    #[serde(default)]
+    pub display_name: Option<String>,
     /// Session identifier
     pub session_id: SessionId,
     /// Monotonic sequence number (per agent host) for deterministic ordering
+    #[specta(type = f64)]
     pub seq: u64,
     /// Timestamp when the host emitted this update (ms since UNIX epoch)
     pub emitted_at_ms: f64,
+    /// Prompt turn this update belongs to, when the adapter identifies it
+    pub turn_id: Option<TurnId>,
     /// The update payload
     pub update: AcpSessionUpdate,
 }
 
+/// Direction of a traced protocol frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum ProtocolTraceDirection {
+    /// Adapter → app
+    Inbound,
+    /// App → adapter
+    Outbound,
+}
+
+/// Event payload: one raw JSON-RPC frame (acp/protocol_trace)
+///
+/// Only emitted for agents with protocol tracing turned on. Large content
+/// (e.g. base64 image data) is redacted from `raw`.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct AcpProtocolTraceEvent {
+    /// Workspace this agent belongs to
+    pub workspace_id: WorkspaceId,
+    /// Agent identifier
+    pub agent_id: AgentId,
+    /// Whether the frame was received or sent
+    pub direction: ProtocolTraceDirection,
+    /// The frame as JSON text
+    pub raw: String,
+}
+
+/// What kind of tool a tool call runs (ACP `ToolKind`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolCallKind {
+    Read,
+    Edit,
+    Delete,
+    Move,
+    Search,
+    Execute,
+    Think,
+    Fetch,
+    SwitchMode,
+    /// Anything else, including kinds added to ACP later
+    #[default]
+    #[serde(other)]
+    Other,
+}
+
+/// Progress of a tool call (ACP `ToolCallStatus`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolCallStatus {
+    #[default]
+    Pending,
+    InProgress,
+    Completed,
+    Failed,
+}
+
+/// A file a tool call is working on
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolCallLocation {
+    pub path: String,
+    /// 1-based line, when the tool targets a specific spot
+    #[serde(default)]
+    pub line: Option<u32>,
+}
+
+/// Output produced by a tool call
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ToolCallContent {
+    /// A regular content block (text, image, resource...)
+    Content { content: serde_json::Value },
+    /// A file modification
+    #[serde(rename_all = "camelCase")]
+    Diff {
+        path: String,
+        /// Previous text (`None` for a new file)
+        #[serde(default)]
+        old_text: Option<String>,
+        new_text: String,
+    },
+    /// Live output of a terminal started by the agent
+    #[serde(rename_all = "camelCase")]
+    Terminal { terminal_id: TerminalId },
+}
+
+/// A tool call the agent started
+///
+/// Mirrors the ACP `ToolCall` schema. `raw` keeps the payload exactly as
+/// the adapter sent it, for fields this type doesn't know about yet.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct AcpToolCall {
+    #[serde(alias = "id")]
+    pub tool_call_id: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub kind: ToolCallKind,
+    #[serde(default)]
+    pub status: ToolCallStatus,
+    #[serde(default)]
+    pub content: Vec<ToolCallContent>,
+    #[serde(default)]
+    pub locations: Vec<ToolCallLocation>,
+    #[serde(default)]
+    pub raw_input: Option<serde_json::Value>,
+    #[serde(default)]
+    pub raw_output: Option<serde_json::Value>,
+    /// The payload as received
+    #[serde(default)]
+    pub raw: serde_json::Value,
+}
+
+/// Changes to a tool call; absent fields are unchanged
+///
+/// Mirrors the ACP `ToolCallUpdate` schema, with the same `raw` escape
+/// hatch as [`AcpToolCall`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct AcpToolCallUpdate {
+    #[serde(alias = "id")]
+    pub tool_call_id: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub kind: Option<ToolCallKind>,
+    #[serde(default)]
+    pub status: Option<ToolCallStatus>,
+    #[serde(default)]
+    pub content: Option<Vec<ToolCallContent>>,
+    #[serde(default)]
+    pub locations: Option<Vec<ToolCallLocation>>,
+    #[serde(default)]
+    pub raw_input: Option<serde_json::Value>,
+    #[serde(default)]
+    pub raw_output: Option<serde_json::Value>,
+    /// The payload as received
+    #[serde(default)]
+    pub raw: serde_json::Value,
+}
+
+/// Why the agent ended a turn (ACP `stopReason`)
+///
+/// Serialized as the ACP string (`"end_turn"`, `"cancelled"`, ...). Reasons
+/// this app doesn't know yet are kept verbatim in `Other`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum StopReason {
+    /// The model finished its response
+    EndTurn,
+    /// The token limit was reached
+    MaxTokens,
+    /// The limit on model requests per turn was reached
+    MaxTurnRequests,
+    /// The turn was cancelled by the client
+    Cancelled,
+    /// The agent refused to continue
+    Refusal,
+    /// The turn ended because of an error
+    Error,
+    /// Any reason not listed above
+    #[serde(untagged)]
+    Other(String),
+}
+
 /// ACP session update types
 ///
 /// US-07: Represents different types of updates that can be received from
-/// an ACP adapter during a session. Uses `serde_json::Value` for payload
-/// flexibility (can be strongly typed later without breaking the contract).
+/// an ACP adapter during a session. Tool calls are typed; other payloads
+/// use `serde_json::Value` for flexibility.
 ///
 /// Variants are inspired by Zed's SessionUpdate enum but kept flexible
 /// for compatibility with various ACP adapters.
@@ -249,13 +939,13 @@ pub enum AcpSessionUpdate {
     #[serde(rename_all = "camelCase")]
     ToolCall {
         #[serde(alias = "data", alias = "toolCall")]
-        tool_call: serde_json::Value,
+        tool_call: AcpToolCall,
     },
     /// Tool call progress update
     #[serde(rename_all = "camelCase")]
     ToolCallUpdate {
         #[serde(alias = "data", alias = "toolCallUpdate")]
-        tool_call_update: serde_json::Value,
+        tool_call_update: AcpToolCallUpdate,
     },
     /// Implementation plan
     Plan {
@@ -280,11 +970,22 @@ pub enum AcpSessionUpdate {
         #[serde(alias = "data")]
         config_options: serde_json::Value,
     },
+    /// Turn start signal, sent once the adapter accepts a prompt
+    #[serde(rename_all = "camelCase")]
+    TurnStarted {
+        /// Request ID of the prompt, which is also the turn ID
+        prompt_id: String,
+    },
     /// Turn completion signal with stop reason
     #[serde(rename_all = "camelCase")]
     TurnComplete {
         #[serde(alias = "stopReason")]
-        stop_reason: serde_json::Value,
+        #[specta(type = String)]
+        stop_reason: StopReason,
+        /// Why the turn failed, if the adapter rejected its prompt after the
+        /// turn had started or it timed out
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
     },
     /// Raw/unknown update (fallback for unrecognized formats)
     Raw { json: serde_json::Value },
@@ -300,6 +1001,37 @@ pub enum AcpSessionUpdate {
 pub struct SendPromptAck {
     /// Session identifier for tracking responses
     pub session_id: SessionId,
+    /// Turn started by this prompt; matches `turnId` on its session updates.
+    /// `None` while the prompt is queued behind the active turn.
+    pub turn_id: Option<TurnId>,
+    /// Whether the prompt waits for the active turn to complete
+    pub queued: bool,
+}
+
+/// One block of prompt content sent to an agent.
+///
+/// Mirrors the subset of ACP `ContentBlock`s the app can produce; the
+/// protocol layer converts each variant to the adapter's wire shape.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum PromptContentBlock {
+    /// Plain text
+    Text { text: String },
+    /// Base64-encoded image (e.g. a screenshot)
+    Image {
+        #[serde(rename = "mimeType")]
+        mime_type: String,
+        data: String,
+    },
+    /// Reference to a file or other resource by URI
+    Resource { uri: String },
+}
+
+impl PromptContentBlock {
+    /// Plain-text block (the common case).
+    pub fn text(text: impl Into<String>) -> Self {
+        PromptContentBlock::Text { text: text.into() }
+    }
 }
 
 /// Runtime status of an agent
@@ -327,10 +1059,62 @@ pub struct AgentStatusChangedEvent {
     pub workspace_id: WorkspaceId,
     /// Agent identifier
     pub agent_id: AgentId,
+    /// Plugin the agent runs on, for labels (`agent_id` identifies the agent)
+    pub plugin_id: String,
+    /// Agent's display name, if it has one, for labels
+    pub display_name: Option<String>,
     /// Current runtime status
     pub status: AgentRuntimeStatus,
 }
 
+/// What went wrong in an ACP protocol error, so callers can react without
+/// parsing the message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum ProtocolErrorKind {
+    /// The adapter didn't answer initialize or session setup in time
+    HandshakeTimeout,
+    /// The adapter rejected initialize or speaks an unsupported version
+    InitializeRejected,
+    /// session/new failed or returned no session
+    SessionCreateFailed,
+    /// session/load failed
+    SessionLoadFailed,
+    /// The adapter process or its stdio pipes went away
+    ConnectionClosed,
+    /// A JSON-RPC message couldn't be serialized
+    SerializationFailed,
+    /// The adapter sent a message larger than the frame limit
+    FrameTooLarge,
+    /// The adapter answered a request with a JSON-RPC error
+    RequestFailed,
+    /// A request wasn't answered within its timeout
+    RequestTimeout,
+    /// The agent isn't running or has no connection
+    NotConnected,
+    /// The adapter doesn't support the requested operation
+    Unsupported,
+}
+
+impl std::fmt::Display for ProtocolErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            ProtocolErrorKind::HandshakeTimeout => "handshake timeout",
+            ProtocolErrorKind::InitializeRejected => "initialize rejected",
+            ProtocolErrorKind::SessionCreateFailed => "session create failed",
+            ProtocolErrorKind::SessionLoadFailed => "session load failed",
+            ProtocolErrorKind::ConnectionClosed => "connection closed",
+            ProtocolErrorKind::SerializationFailed => "serialization failed",
+            ProtocolErrorKind::FrameTooLarge => "frame too large",
+            ProtocolErrorKind::RequestFailed => "request failed",
+            ProtocolErrorKind::RequestTimeout => "request timeout",
+            ProtocolErrorKind::NotConnected => "not connected",
+            ProtocolErrorKind::Unsupported => "unsupported",
+        };
+        f.write_str(text)
+    }
+}
+
 /// API errors for frontend consumption
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 #[serde(rename_all = "camelCase", tag = "type")]
@@ -378,8 +1162,40 @@ pub enum ApiError {
         #[serde(rename = "pluginId")]
         plugin_id: String,
     },
+    /// Plugin files can't be changed while an agent is running on it
+    PluginInUse {
+        #[serde(rename = "pluginId")]
+        plugin_id: String,
+    },
     /// Protocol error during ACP communication
-    ProtocolError { message: String },
+    ProtocolError {
+        kind: ProtocolErrorKind,
+        message: String,
+    },
+    /// The adapter no longer knows the session (e.g., it restarted)
+    SessionNotFound {
+        #[serde(rename = "sessionId")]
+        session_id: SessionId,
+    },
+    /// The operation was aborted because its turn was cancelled
+    Cancelled { message: String },
+    /// The agent is still working on a turn and won't take another prompt
+    TurnInProgress {
+        #[serde(rename = "sessionId")]
+        session_id: SessionId,
+    },
+    /// The workspace already runs as many terminal commands as it allows
+    TooManyTerminals { limit: u32 },
+    /// As many agents are running (across all workspaces) as the app allows
+    TooManyAgents { limit: u32 },
+    /// The session already has as many prompts outstanding as allowed
+    TooManyPrompts {
+        #[serde(rename = "sessionId")]
+        session_id: SessionId,
+        limit: u32,
+    },
+    /// The action needs a focused workspace, but none is focused
+    NoFocusedWorkspace,
 }
 
 impl std::fmt::Display for ApiError {
@@ -410,9 +1226,35 @@ impl std::fmt::Display for ApiError {
             ApiError::PluginMissingBinPath { plugin_id } => {
                 write!(f, "Plugin missing binary path: {plugin_id}")
             }
-            ApiError::ProtocolError { message } => {
-                write!(f, "Protocol error: {message}")
+            ApiError::PluginInUse { plugin_id } => {
+                write!(f, "Plugin is in use by a running agent: {plugin_id}")
+            }
+            ApiError::ProtocolError { kind, message } => {
+                write!(f, "Protocol error ({kind}): {message}")
             }
+            ApiError::SessionNotFound { session_id } => {
+                write!(f, "Session not found: {session_id}")
+            }
+            ApiError::Cancelled { message } => write!(f, "Cancelled: {message}"),
+            ApiError::TurnInProgress { session_id } => {
+                write!(f, "A turn is already in progress: {session_id}")
+            }
+            ApiError::TooManyTerminals { limit } => {
+                write!(f, "Too many running terminal commands (limit {limit})")
+            }
+            ApiError::TooManyAgents { limit } => {
+                write!(
+                    f,
+                    "Too many running agents (limit {limit}); stop one to start another"
+                )
+            }
+            ApiError::TooManyPrompts { session_id, limit } => {
+                write!(
+                    f,
+                    "Too many prompts outstanding for session {session_id} (limit {limit}); wait for the current turn to finish"
+                )
+            }
+            ApiError::NoFocusedWorkspace => write!(f, "No workspace is focused"),
         }
     }
 }
@@ -421,6 +1263,34 @@ impl std::fmt::Display for ApiError {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_page_slice_boundaries() {
+        let items: Vec<u32> = (0..5).collect();
+
+        let all = Page::slice(items.clone(), None, None);
+        assert_eq!(all.items, vec![0, 1, 2, 3, 4]);
+        assert_eq!(all.total, 5);
+
+        assert_eq!(
+            Page::slice(items.clone(), Some(1), Some(2)).items,
+            vec![1, 2]
+        );
+        assert_eq!(
+            Page::slice(items.clone(), Some(3), Some(10)).items,
+            vec![3, 4]
+        );
+        assert_eq!(Page::slice(items.clone(), Some(4), None).items, vec![4]);
+        assert!(Page::slice(items.clone(), None, Some(0)).items.is_empty());
+
+        // Past the end: empty page, total still reported
+        let beyond = Page::slice(items.clone(), Some(5), Some(2));
+        assert!(beyond.items.is_empty());
+        assert_eq!(beyond.total, 5);
+        assert!(Page::slice(items, Some(u32::MAX), Some(u32::MAX))
+            .items
+            .is_empty());
+    }
+
     #[test]
     fn test_acp_session_update_deserialize_agent_message_chunk() {
         let json = serde_json::json!({
@@ -474,4 +1344,206 @@ mod tests {
             panic!("Expected Raw variant");
         }
     }
+
+    #[test]
+    fn test_stop_reason_deserializes_known_reasons() {
+        for (wire, expected) in [
+            ("end_turn", StopReason::EndTurn),
+            ("max_tokens", StopReason::MaxTokens),
+            ("max_turn_requests", StopReason::MaxTurnRequests),
+            ("cancelled", StopReason::Cancelled),
+            ("refusal", StopReason::Refusal),
+            ("error", StopReason::Error),
+        ] {
+            let parsed: StopReason = serde_json::from_value(serde_json::json!(wire)).unwrap();
+            assert_eq!(parsed, expected, "{wire}");
+            assert_eq!(serde_json::to_value(&parsed).unwrap(), wire);
+        }
+    }
+
+    #[test]
+    fn test_stop_reason_keeps_unknown_reasons() {
+        let parsed: StopReason =
+            serde_json::from_value(serde_json::json!("context_exhausted")).unwrap();
+        assert_eq!(parsed, StopReason::Other("context_exhausted".to_string()));
+        assert_eq!(
+            serde_json::to_value(&parsed).unwrap(),
+            serde_json::json!("context_exhausted")
+        );
+
+        let update: AcpSessionUpdate = serde_json::from_value(serde_json::json!({
+            "type": "turnComplete",
+            "stopReason": "end_turn"
+        }))
+        .unwrap();
+        assert!(matches!(
+            update,
+            AcpSessionUpdate::TurnComplete {
+                stop_reason: StopReason::EndTurn,
+                error: None,
+            }
+        ));
+    }
+
+    #[test]
+    fn test_prompt_content_block_frontend_shape() {
+        let block: PromptContentBlock = serde_json::from_value(serde_json::json!({
+            "type": "image",
+            "mimeType": "image/png",
+            "data": "iVBORw0KGgo="
+        }))
+        .unwrap();
+
+        assert_eq!(
+            block,
+            PromptContentBlock::Image {
+                mime_type: "image/png".to_string(),
+                data: "iVBORw0KGgo=".to_string(),
+            }
+        );
+        assert_eq!(
+            serde_json::to_value(PromptContentBlock::text("hi")).unwrap(),
+            serde_json::json!({ "type": "text", "text": "hi" })
+        );
+    }
+
+    #[test]
+    fn test_protocol_error_frontend_shape() {
+        let error = ApiError::ProtocolError {
+            kind: ProtocolErrorKind::HandshakeTimeout,
+            message: "initialize timed out after 100ms".to_string(),
+        };
+
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({
+                "type": "protocolError",
+                "kind": "handshakeTimeout",
+                "message": "initialize timed out after 100ms"
+            })
+        );
+        assert_eq!(
+            error.to_string(),
+            "Protocol error (handshake timeout): initialize timed out after 100ms"
+        );
+    }
+
+    #[test]
+    fn test_notification_event_frontend_shape() {
+        let event = NotificationShowEvent {
+            title: "claude-code installed".to_string(),
+            body: None,
+            level: NotificationLevel::Success,
+        };
+
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            serde_json::json!({
+                "title": "claude-code installed",
+                "body": null,
+                "level": "success"
+            })
+        );
+    }
+
+    #[test]
+    fn test_tool_call_deserializes_claude_code_payloads() {
+        let tool_call: AcpToolCall = serde_json::from_value(serde_json::json!({
+            "sessionUpdate": "tool_call",
+            "toolCallId": "toolu_01HxRk2cJ4V9bU7yqRk5T3mL",
+            "title": "Read src/main.rs",
+            "kind": "read",
+            "status": "pending",
+            "locations": [{ "path": "/repo/src/main.rs", "line": 0 }],
+            "rawInput": { "file_path": "/repo/src/main.rs" },
+            "content": []
+        }))
+        .unwrap();
+        assert_eq!(tool_call.tool_call_id, "toolu_01HxRk2cJ4V9bU7yqRk5T3mL");
+        assert_eq!(tool_call.kind, ToolCallKind::Read);
+        assert_eq!(tool_call.status, ToolCallStatus::Pending);
+        assert_eq!(
+            tool_call.locations,
+            vec![ToolCallLocation {
+                path: "/repo/src/main.rs".to_string(),
+                line: Some(0),
+            }]
+        );
+        assert_eq!(
+            tool_call.raw_input,
+            Some(serde_json::json!({ "file_path": "/repo/src/main.rs" }))
+        );
+
+        let update: AcpToolCallUpdate = serde_json::from_value(serde_json::json!({
+            "sessionUpdate": "tool_call_update",
+            "toolCallId": "toolu_01HxRk2cJ4V9bU7yqRk5T3mL",
+            "status": "completed",
+            "content": [
+                { "type": "content", "content": { "type": "text", "text": "fn main() {}" } },
+                {
+                    "type": "diff",
+                    "path": "/repo/src/main.rs",
+                    "oldText": "fn main() {}",
+                    "newText": "fn main() { run() }"
+                }
+            ]
+        }))
+        .unwrap();
+        assert_eq!(update.status, Some(ToolCallStatus::Completed));
+        assert_eq!(update.title, None);
+        assert_eq!(
+            update.content.unwrap()[1],
+            ToolCallContent::Diff {
+                path: "/repo/src/main.rs".to_string(),
+                old_text: Some("fn main() {}".to_string()),
+                new_text: "fn main() { run() }".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_tool_call_deserializes_codex_payloads() {
+        let tool_call: AcpToolCall = serde_json::from_value(serde_json::json!({
+            "sessionUpdate": "tool_call",
+            "toolCallId": "call_8fK2mQ",
+            "title": "Run cargo test",
+            "kind": "execute",
+            "status": "in_progress",
+            "rawInput": { "command": ["bash", "-lc", "cargo test"], "cwd": "/repo" },
+            "content": [{ "type": "terminal", "terminalId": "term-1" }]
+        }))
+        .unwrap();
+        assert_eq!(tool_call.kind, ToolCallKind::Execute);
+        assert_eq!(tool_call.status, ToolCallStatus::InProgress);
+        assert!(tool_call.locations.is_empty());
+        assert_eq!(
+            tool_call.content,
+            vec![ToolCallContent::Terminal {
+                terminal_id: "term-1".to_string(),
+            }]
+        );
+
+        let update: AcpToolCallUpdate = serde_json::from_value(serde_json::json!({
+            "sessionUpdate": "tool_call_update",
+            "toolCallId": "call_8fK2mQ",
+            "status": "failed",
+            "rawOutput": { "exit_code": 101, "stdout": "", "stderr": "error" }
+        }))
+        .unwrap();
+        assert_eq!(update.status, Some(ToolCallStatus::Failed));
+        assert_eq!(update.content, None);
+        assert_eq!(
+            update.raw_output,
+            Some(serde_json::json!({ "exit_code": 101, "stdout": "", "stderr": "error" }))
+        );
+
+        // Kinds added to ACP later don't break parsing
+        let future: AcpToolCall = serde_json::from_value(serde_json::json!({
+            "toolCallId": "call_1",
+            "title": "Browse",
+            "kind": "browse"
+        }))
+        .unwrap();
+        assert_eq!(future.kind, ToolCallKind::Other);
+    }
 }
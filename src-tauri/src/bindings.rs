@@ -2,8 +2,8 @@ use tauri_specta::{collect_commands, Builder};
 
 pub fn generate_bindings() -> Builder<tauri::Wry> {
     use crate::commands::{
-        agents, chat, notifications, permissions, plugins, preferences, quick_pane, recovery,
-        terminal, workspaces,
+        agents, cache, chat, diagnostics, fs, notifications, permissions, plugins, preferences,
+        quick_pane, recovery, terminal, workspaces,
     };
 
     Builder::<tauri::Wry>::new().commands(collect_commands![
@@ -11,39 +11,88 @@ pub fn generate_bindings() -> Builder<tauri::Wry> {
         preferences::load_preferences,
         preferences::save_preferences,
         notifications::send_native_notification,
+        notifications::notify,
         recovery::save_emergency_data,
         recovery::load_emergency_data,
         recovery::cleanup_old_recovery_files,
+        recovery::recovery_list_adapter_processes,
+        recovery::recovery_kill_adapter_process,
         quick_pane::show_quick_pane,
         quick_pane::dismiss_quick_pane,
         quick_pane::toggle_quick_pane,
         quick_pane::get_default_quick_pane_shortcut,
         quick_pane::update_quick_pane_shortcut,
+        quick_pane::quick_pane_send,
         workspaces::workspace_create,
         workspaces::workspace_list,
         workspaces::workspace_delete,
+        workspaces::workspace_rename,
+        workspaces::workspace_set_limits,
+        workspaces::workspace_set_shell,
+        workspaces::workspace_start_watch,
+        workspaces::workspace_stop_watch,
         workspaces::workspace_set_focus,
         workspaces::workspace_get_focus,
+        workspaces::workspace_preview_env,
         plugins::plugin_get_status,
         plugins::plugin_install,
+        plugins::plugin_check_toolchain,
+        plugins::plugin_repair,
+        plugins::plugin_is_in_use,
+        cache::cache_info,
+        cache::cache_clear,
+        diagnostics::diagnostics,
         permissions::permission_respond,
+        permissions::permission_cancel,
+        permissions::permission_list_pending,
+        permissions::permission_rules_list,
+        permissions::permission_rule_revoke,
+        permissions::permission_history,
+        permissions::permission_set_timeout,
         agents::agent_create,
         agents::agent_list,
+        agents::agent_set_auto_restart,
+        agents::agent_set_protocol_trace,
+        agents::agent_set_busy_prompt_policy,
+        agents::agent_status,
+        agents::agent_available_commands,
+        agents::agent_current_mode,
+        agents::agent_set_mode,
+        agents::agent_new_session,
+        agents::agent_close_session,
+        agents::agent_delete,
+        agents::agent_set_mcp_servers,
+        agents::agent_stop,
+        agents::agent_restart,
         chat::chat_send_prompt,
+        chat::session_transcript_read,
         chat::chat_stop_turn,
         terminal::terminal_kill,
+        terminal::terminal_list,
+        terminal::terminal_kill_all,
+        fs::fs_list_dir,
+        fs::fs_metadata,
     ])
 }
 
+/// Committed TypeScript bindings, relative to `src-tauri`
+#[cfg(any(test, debug_assertions))]
+const BINDINGS_PATH: &str = "../src/lib/bindings.ts";
+
 /// Export TypeScript bindings to the frontend.
 /// Run with: cargo test export_bindings -- --ignored
 #[cfg(any(test, debug_assertions))]
 pub fn export_ts_bindings() {
+    export_ts_bindings_to(BINDINGS_PATH);
+}
+
+#[cfg(any(test, debug_assertions))]
+fn export_ts_bindings_to(path: impl AsRef<std::path::Path>) {
     generate_bindings()
         .export(
             specta_typescript::Typescript::default()
                 .header("// @ts-nocheck\n// Auto-generated by tauri-specta. DO NOT EDIT.\n\n"),
-            "../src/lib/bindings.ts",
+            path,
         )
         .expect("Failed to export TypeScript bindings");
 }
@@ -61,4 +110,24 @@ mod tests {
         export_ts_bindings();
         println!("✓ TypeScript bindings exported to ../src/lib/bindings.ts");
     }
+
+    /// Fail if the committed bindings drifted from the Rust commands/types.
+    /// Only runs with the `bindings-check` feature (e.g. in CI):
+    /// cargo test --features bindings-check bindings_up_to_date
+    #[test]
+    #[cfg_attr(not(feature = "bindings-check"), ignore)]
+    fn bindings_up_to_date() {
+        let generated = std::env::temp_dir().join(format!("bindings_{}.ts", uuid::Uuid::new_v4()));
+        export_ts_bindings_to(&generated);
+        let expected =
+            std::fs::read_to_string(&generated).expect("Failed to read generated bindings");
+        let _ = std::fs::remove_file(&generated);
+
+        let committed =
+            std::fs::read_to_string(BINDINGS_PATH).expect("Failed to read committed bindings");
+        assert!(
+            committed == expected,
+            "{BINDINGS_PATH} is out of date; regenerate it with `npm run rust:bindings`"
+        );
+    }
 }
@@ -4,9 +4,14 @@
 
 use std::sync::Arc;
 
-use tauri::State;
+use tauri::{Manager, State};
 
-use crate::api::types::{AgentSummary, ApiError, WorkspaceId};
+use crate::api::types::{
+    AgentId, AgentRuntimeStatus, AgentSummary, ApiError, BusyPromptPolicy, McpServerConfig, Page,
+    SessionId, WorkspaceId,
+};
+use crate::plugins::manager::PluginManager;
+use crate::runtime::permissions::PermissionHub;
 use crate::runtime::workspace_manager::WorkspaceManager;
 
 async fn agent_create_inner(
@@ -56,8 +61,10 @@ pub async fn agent_create(
 async fn agent_list_inner(
     workspace_manager: &WorkspaceManager,
     workspace_id: WorkspaceId,
-) -> Result<Vec<AgentSummary>, ApiError> {
-    log::info!("agent_list: workspace={workspace_id}");
+    offset: Option<u32>,
+    limit: Option<u32>,
+) -> Result<Page<AgentSummary>, ApiError> {
+    log::info!("agent_list: workspace={workspace_id}, offset={offset:?}, limit={limit:?}");
 
     // Validate workspace_id is not empty
     if workspace_id.trim().is_empty() {
@@ -66,16 +73,21 @@ async fn agent_list_inner(
         });
     }
 
-    workspace_manager.list_agents(workspace_id).await
+    workspace_manager
+        .list_agents(workspace_id, offset, limit)
+        .await
 }
 
-/// Lists all agents within a workspace.
+/// Lists agents within a workspace, one page at a time.
 ///
 /// # Arguments
 /// * `workspace_id` - ID of the workspace to list agents from
+/// * `offset` - Agents to skip (default 0)
+/// * `limit` - Most agents to return (default: all)
 ///
 /// # Returns
-/// * `Vec<AgentSummary>` - List of all agent summaries
+/// * `Page<AgentSummary>` - The requested agent summaries, oldest first, and
+///   the total number of agents
 ///
 /// # Errors
 /// * `ApiError::WorkspaceNotFound` - If the workspace does not exist
@@ -84,8 +96,430 @@ async fn agent_list_inner(
 pub async fn agent_list(
     workspace_manager: State<'_, Arc<WorkspaceManager>>,
     workspace_id: WorkspaceId,
-) -> Result<Vec<AgentSummary>, ApiError> {
-    agent_list_inner(&workspace_manager, workspace_id).await
+    offset: Option<u32>,
+    limit: Option<u32>,
+) -> Result<Page<AgentSummary>, ApiError> {
+    agent_list_inner(&workspace_manager, workspace_id, offset, limit).await
+}
+
+/// Configures the MCP servers passed to an agent's adapter.
+///
+/// The servers are sent with `session/new` (and `session/load`) the next
+/// time the agent starts; a running adapter keeps its current servers.
+///
+/// # Arguments
+/// * `workspace_id` - ID of the workspace containing the agent
+/// * `agent_id` - ID of the agent to configure
+/// * `mcp_servers` - Stdio MCP servers (name, command, args, env)
+///
+/// # Errors
+/// * `ApiError::WorkspaceNotFound` - If the workspace does not exist
+/// * `ApiError::AgentNotFound` - If the agent does not exist
+/// * `ApiError::InvalidInput` - If a server has an empty name or command
+#[tauri::command]
+#[specta::specta]
+pub async fn agent_set_mcp_servers(
+    workspace_manager: State<'_, Arc<WorkspaceManager>>,
+    workspace_id: WorkspaceId,
+    agent_id: AgentId,
+    mcp_servers: Vec<McpServerConfig>,
+) -> Result<(), ApiError> {
+    log::info!(
+        "agent_set_mcp_servers: workspace={workspace_id}, agent={agent_id}, servers={}",
+        mcp_servers.len()
+    );
+
+    workspace_manager
+        .set_agent_mcp_servers(workspace_id, agent_id, mcp_servers)
+        .await
+}
+
+/// Enables or disables automatic restarts for an agent.
+///
+/// When enabled, an adapter that exits unexpectedly is restarted with
+/// exponential backoff (a limited number of attempts), resuming its session.
+/// `agent_stop` never triggers a restart.
+///
+/// # Arguments
+/// * `workspace_id` - ID of the workspace containing the agent
+/// * `agent_id` - ID of the agent to configure
+/// * `enabled` - Whether to restart automatically
+///
+/// # Errors
+/// * `ApiError::WorkspaceNotFound` - If the workspace does not exist
+/// * `ApiError::AgentNotFound` - If the agent does not exist
+#[tauri::command]
+#[specta::specta]
+pub async fn agent_set_auto_restart(
+    workspace_manager: State<'_, Arc<WorkspaceManager>>,
+    workspace_id: WorkspaceId,
+    agent_id: AgentId,
+    enabled: bool,
+) -> Result<(), ApiError> {
+    log::info!(
+        "agent_set_auto_restart: workspace={workspace_id}, agent={agent_id}, enabled={enabled}"
+    );
+
+    workspace_manager
+        .set_agent_auto_restart(workspace_id, agent_id, enabled)
+        .await
+}
+
+/// Enables or disables protocol tracing for an agent.
+///
+/// While on, every JSON-RPC frame exchanged with the adapter is emitted as an
+/// `acp/protocol_trace` event, with large content such as image data
+/// redacted. Off by default since frames include prompt content; the
+/// setting isn't saved.
+///
+/// # Arguments
+/// * `workspace_id` - ID of the workspace containing the agent
+/// * `agent_id` - ID of the agent to trace
+/// * `enabled` - Whether to emit trace events
+///
+/// # Errors
+/// * `ApiError::WorkspaceNotFound` - If the workspace does not exist
+/// * `ApiError::AgentNotFound` - If the agent does not exist
+#[tauri::command]
+#[specta::specta]
+pub async fn agent_set_protocol_trace(
+    workspace_manager: State<'_, Arc<WorkspaceManager>>,
+    workspace_id: WorkspaceId,
+    agent_id: AgentId,
+    enabled: bool,
+) -> Result<(), ApiError> {
+    log::info!(
+        "agent_set_protocol_trace: workspace={workspace_id}, agent={agent_id}, enabled={enabled}"
+    );
+
+    workspace_manager
+        .set_agent_protocol_trace(workspace_id, agent_id, enabled)
+        .await
+}
+
+/// Chooses what happens to prompts sent while an agent is mid-turn.
+///
+/// `queue` (the default) holds them and sends each once the previous turn
+/// completes; `reject` fails `chat_send_prompt` with `TurnInProgress`.
+///
+/// # Arguments
+/// * `workspace_id` - ID of the workspace containing the agent
+/// * `agent_id` - ID of the agent to configure
+/// * `policy` - Queue or reject busy prompts
+///
+/// # Errors
+/// * `ApiError::WorkspaceNotFound` - If the workspace does not exist
+/// * `ApiError::AgentNotFound` - If the agent does not exist
+#[tauri::command]
+#[specta::specta]
+pub async fn agent_set_busy_prompt_policy(
+    workspace_manager: State<'_, Arc<WorkspaceManager>>,
+    workspace_id: WorkspaceId,
+    agent_id: AgentId,
+    policy: BusyPromptPolicy,
+) -> Result<(), ApiError> {
+    log::info!(
+        "agent_set_busy_prompt_policy: workspace={workspace_id}, agent={agent_id}, policy={policy:?}"
+    );
+
+    workspace_manager
+        .set_agent_busy_prompt_policy(workspace_id, agent_id, policy)
+        .await
+}
+
+/// Returns an agent's current runtime status.
+///
+/// Lets the UI recover the status after a reload or a missed
+/// `agent/status_changed` event. Agents that were never started are `stopped`.
+///
+/// # Arguments
+/// * `workspace_id` - ID of the workspace containing the agent
+/// * `agent_id` - ID of the agent to query
+///
+/// # Errors
+/// * `ApiError::WorkspaceNotFound` - If the workspace does not exist
+/// * `ApiError::AgentNotFound` - If the agent does not exist
+#[tauri::command]
+#[specta::specta]
+pub async fn agent_status(
+    workspace_manager: State<'_, Arc<WorkspaceManager>>,
+    workspace_id: WorkspaceId,
+    agent_id: AgentId,
+) -> Result<AgentRuntimeStatus, ApiError> {
+    log::debug!("agent_status: workspace={workspace_id}, agent={agent_id}");
+
+    workspace_manager.agent_status(workspace_id, agent_id).await
+}
+
+/// Returns the slash commands an agent's session last announced.
+///
+/// Adapters send `availableCommandsUpdate` session updates as their command
+/// list changes; this lets the UI recover the latest list after a reload.
+///
+/// # Arguments
+/// * `workspace_id` - ID of the workspace containing the agent
+/// * `agent_id` - ID of the agent to query
+/// * `session_id` - Session whose commands to return
+///
+/// # Returns
+/// * The commands as the adapter sent them (empty if none were received yet)
+///
+/// # Errors
+/// * `ApiError::WorkspaceNotFound` - If the workspace does not exist
+/// * `ApiError::AgentNotFound` - If the agent does not exist
+#[tauri::command]
+#[specta::specta]
+pub async fn agent_available_commands(
+    workspace_manager: State<'_, Arc<WorkspaceManager>>,
+    workspace_id: WorkspaceId,
+    agent_id: AgentId,
+    session_id: SessionId,
+) -> Result<Vec<serde_json::Value>, ApiError> {
+    log::debug!(
+        "agent_available_commands: workspace={workspace_id}, agent={agent_id}, session={session_id}"
+    );
+
+    workspace_manager
+        .available_commands(workspace_id, agent_id, session_id)
+        .await
+}
+
+/// Returns the mode an agent's session was last reported in.
+///
+/// Sessions report their mode when created and on every
+/// `currentModeUpdate`; this lets the UI recover it after a reload.
+///
+/// # Arguments
+/// * `workspace_id` - ID of the workspace containing the agent
+/// * `agent_id` - ID of the agent to query
+/// * `session_id` - Session whose mode to return
+///
+/// # Returns
+/// * The mode ID, or `null` if the adapter hasn't reported one
+///
+/// # Errors
+/// * `ApiError::WorkspaceNotFound` - If the workspace does not exist
+/// * `ApiError::AgentNotFound` - If the agent does not exist
+#[tauri::command]
+#[specta::specta]
+pub async fn agent_current_mode(
+    workspace_manager: State<'_, Arc<WorkspaceManager>>,
+    workspace_id: WorkspaceId,
+    agent_id: AgentId,
+    session_id: SessionId,
+) -> Result<Option<String>, ApiError> {
+    log::debug!(
+        "agent_current_mode: workspace={workspace_id}, agent={agent_id}, session={session_id}"
+    );
+
+    workspace_manager
+        .current_mode(workspace_id, agent_id, session_id)
+        .await
+}
+
+/// Switches an agent's session to another mode via ACP `session/set_mode`.
+///
+/// Once the adapter accepts, the change is emitted as a `currentModeUpdate`
+/// session update.
+///
+/// # Arguments
+/// * `workspace_id` - ID of the workspace containing the agent
+/// * `agent_id` - ID of the agent
+/// * `session_id` - Session to switch
+/// * `mode_id` - One of the modes the adapter offers for the session
+///
+/// # Errors
+/// * `ApiError::WorkspaceNotFound` - If the workspace does not exist
+/// * `ApiError::AgentNotFound` - If the agent does not exist
+/// * `ApiError::InvalidInput` - If the session isn't open or the mode is unknown
+/// * `ApiError::ProtocolError` - If the agent isn't running, has no modes, or
+///   the adapter rejects the change
+#[tauri::command]
+#[specta::specta]
+pub async fn agent_set_mode(
+    workspace_manager: State<'_, Arc<WorkspaceManager>>,
+    workspace_id: WorkspaceId,
+    agent_id: AgentId,
+    session_id: SessionId,
+    mode_id: String,
+) -> Result<(), ApiError> {
+    log::info!(
+        "agent_set_mode: workspace={workspace_id}, agent={agent_id}, session={session_id}, mode={mode_id}"
+    );
+
+    if mode_id.trim().is_empty() {
+        return Err(ApiError::InvalidInput {
+            message: "Mode ID cannot be empty".to_string(),
+        });
+    }
+
+    workspace_manager
+        .set_mode(workspace_id, agent_id, session_id, mode_id)
+        .await
+}
+
+/// Opens another session on an agent, starting the agent if needed.
+///
+/// The session has its own turn and prompt queue; pass its ID to
+/// `chat_send_prompt` to prompt it. The agent's primary session is unchanged.
+///
+/// # Arguments
+/// * `workspace_id` - ID of the workspace containing the agent
+/// * `agent_id` - ID of the agent
+///
+/// # Returns
+/// * `Ok(SessionId)` - The new session
+///
+/// # Errors
+/// * `ApiError::WorkspaceNotFound` - If the workspace does not exist
+/// * `ApiError::AgentNotFound` - If the agent does not exist
+/// * `ApiError::ProtocolError` - If the adapter can't create the session
+#[tauri::command]
+#[specta::specta]
+pub async fn agent_new_session(
+    workspace_manager: State<'_, Arc<WorkspaceManager>>,
+    workspace_id: WorkspaceId,
+    agent_id: AgentId,
+) -> Result<SessionId, ApiError> {
+    log::info!("agent_new_session: workspace={workspace_id}, agent={agent_id}");
+
+    workspace_manager.new_session(workspace_id, agent_id).await
+}
+
+/// Closes a session opened with `agent_new_session`.
+///
+/// Its running turn is cancelled and queued prompts are dropped.
+///
+/// # Arguments
+/// * `workspace_id` - ID of the workspace containing the agent
+/// * `agent_id` - ID of the agent
+/// * `session_id` - Session to close
+///
+/// # Errors
+/// * `ApiError::WorkspaceNotFound` - If the workspace does not exist
+/// * `ApiError::AgentNotFound` - If the agent does not exist
+/// * `ApiError::InvalidInput` - If the session is the agent's primary one or
+///   isn't open
+/// * `ApiError::ProtocolError` - If the adapter fails to close the session
+#[tauri::command]
+#[specta::specta]
+pub async fn agent_close_session(
+    workspace_manager: State<'_, Arc<WorkspaceManager>>,
+    workspace_id: WorkspaceId,
+    agent_id: AgentId,
+    session_id: SessionId,
+) -> Result<(), ApiError> {
+    log::info!(
+        "agent_close_session: workspace={workspace_id}, agent={agent_id}, session={session_id}"
+    );
+
+    workspace_manager
+        .close_session(workspace_id, agent_id, session_id)
+        .await
+}
+
+/// Deletes an agent from a workspace.
+///
+/// A running agent is stopped first so its adapter process doesn't outlive it.
+///
+/// # Arguments
+/// * `workspace_id` - ID of the workspace containing the agent
+/// * `agent_id` - ID of the agent to delete
+///
+/// # Errors
+/// * `ApiError::WorkspaceNotFound` - If the workspace does not exist
+/// * `ApiError::AgentNotFound` - If the agent does not exist
+#[tauri::command]
+#[specta::specta]
+pub async fn agent_delete(
+    workspace_manager: State<'_, Arc<WorkspaceManager>>,
+    workspace_id: WorkspaceId,
+    agent_id: AgentId,
+) -> Result<(), ApiError> {
+    log::info!("agent_delete: workspace={workspace_id}, agent={agent_id}");
+
+    workspace_manager.delete_agent(workspace_id, agent_id).await
+}
+
+/// Stops a running agent, shutting down its adapter process.
+///
+/// Unlike `chat_stop_turn`, this ends the agent itself; the next prompt
+/// starts it again. Stopping an agent that isn't running succeeds.
+///
+/// # Arguments
+/// * `workspace_id` - ID of the workspace containing the agent
+/// * `agent_id` - ID of the agent to stop
+///
+/// # Errors
+/// * `ApiError::WorkspaceNotFound` - If the workspace does not exist
+/// * `ApiError::AgentNotFound` - If the agent does not exist
+#[tauri::command]
+#[specta::specta]
+pub async fn agent_stop(
+    workspace_manager: State<'_, Arc<WorkspaceManager>>,
+    workspace_id: WorkspaceId,
+    agent_id: AgentId,
+) -> Result<(), ApiError> {
+    log::info!("agent_stop: workspace={workspace_id}, agent={agent_id}");
+
+    workspace_manager.stop_agent(workspace_id, agent_id).await
+}
+
+/// Restarts an agent, shutting down its adapter and starting a fresh one.
+///
+/// The new adapter resumes the agent's session where the adapter supports
+/// `session/load`, so the conversation survives; otherwise a new session is
+/// created. Restarting a stopped agent starts it.
+///
+/// # Arguments
+/// * `workspace_id` - ID of the workspace containing the agent
+/// * `agent_id` - ID of the agent to restart
+///
+/// # Returns
+/// * `SessionId` - The resumed or new session
+///
+/// # Events Emitted
+/// * `agent/status_changed` - Stopped, then Starting and Running (or Errored)
+///
+/// # Errors
+/// * `ApiError::WorkspaceNotFound` - If the workspace does not exist
+/// * `ApiError::AgentNotFound` - If the agent does not exist
+/// * `ApiError::PluginNotInstalled` - If the agent's plugin is not installed
+/// * `ApiError::ProtocolError` - If the new adapter fails to start
+#[tauri::command]
+#[specta::specta]
+pub async fn agent_restart(
+    app: tauri::AppHandle,
+    workspace_manager: State<'_, Arc<WorkspaceManager>>,
+    plugin_manager: State<'_, Arc<PluginManager>>,
+    workspace_id: WorkspaceId,
+    agent_id: AgentId,
+) -> Result<SessionId, ApiError> {
+    log::info!("agent_restart: workspace={workspace_id}, agent={agent_id}");
+
+    let workspace = workspace_manager.get_workspace(&workspace_id).await?;
+    let agent_runtime = workspace.ensure_agent_runtime(agent_id.clone()).await?;
+    let resume_session_id = workspace.agent_session_id(&agent_id).await?;
+    let mcp_servers = workspace.agent_mcp_servers(&agent_id).await?;
+    let permission_hub = app.state::<Arc<PermissionHub>>().inner().clone();
+
+    let session_id = agent_runtime
+        .restart(
+            app,
+            workspace.root_dir().clone(),
+            plugin_manager.inner().clone(),
+            permission_hub,
+            workspace.terminal_manager(),
+            workspace.fs_manager(),
+            resume_session_id,
+            mcp_servers,
+        )
+        .await?;
+    workspace_manager
+        .record_agent_session(workspace_id, agent_id, session_id.clone())
+        .await?;
+
+    Ok(session_id)
 }
 
 #[cfg(test)]
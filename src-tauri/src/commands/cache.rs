@@ -0,0 +1,314 @@
+//! App cache commands.
+//!
+//! Gives users a single troubleshooting action to see how much space the
+//! plugin cache uses and wipe it, along with transient data agents left
+//! behind. Cleared plugins are reinstalled on demand.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use tauri::State;
+
+use crate::api::types::{ApiError, CacheClearResult, CacheInfo};
+use crate::plugins::cache;
+use crate::plugins::manager::PluginManager;
+use crate::runtime::fs::remove_temp_write_files;
+use crate::runtime::plugin_installer::{InstallingSet, PluginInstaller};
+use crate::runtime::workspace_manager::WorkspaceManager;
+use crate::utils::blocking::BlockingFsLimiter;
+
+async fn cache_info_inner(plugins_root: PathBuf) -> Result<CacheInfo, ApiError> {
+    let root_for_scan = plugins_root.clone();
+    let (plugin_ids, total_bytes) = BlockingFsLimiter::shared()
+        .run(move || -> Result<_, ApiError> {
+            Ok((
+                cache::cached_plugin_ids(&root_for_scan)?,
+                cache::dir_size(&root_for_scan),
+            ))
+        })
+        .await
+        .map_err(|e| ApiError::IoError {
+            message: format!("Failed to spawn blocking task: {e}"),
+        })??;
+
+    Ok(CacheInfo {
+        plugins_root: plugins_root.to_string_lossy().to_string(),
+        plugin_ids,
+        total_bytes: total_bytes as f64,
+    })
+}
+
+async fn cache_clear_inner(
+    workspace_manager: &WorkspaceManager,
+    installing: &InstallingSet,
+    plugins_root: PathBuf,
+    confirm: bool,
+) -> Result<CacheClearResult, ApiError> {
+    log::info!("cache_clear: confirm={confirm}");
+
+    if !confirm {
+        return Err(ApiError::InvalidInput {
+            message: "Clearing the cache requires confirm=true".to_string(),
+        });
+    }
+
+    let limiter = BlockingFsLimiter::shared();
+    let root_for_scan = plugins_root.clone();
+    let plugin_ids = limiter
+        .run(move || cache::cached_plugin_ids(&root_for_scan))
+        .await
+        .map_err(|e| ApiError::IoError {
+            message: format!("Failed to spawn blocking task: {e}"),
+        })??;
+
+    // All or nothing: don't leave a half-cleared cache behind. The install
+    // slots are held until the plugins are gone so no install starts meanwhile.
+    let mut install_slots = Vec::with_capacity(plugin_ids.len());
+    for plugin_id in &plugin_ids {
+        workspace_manager
+            .ensure_plugin_not_in_use(plugin_id)
+            .await?;
+        let slot = installing.try_acquire(plugin_id).ok_or_else(|| {
+            log::warn!("Refusing to clear cache during install: plugin_id={plugin_id}");
+            ApiError::PluginInstallInProgress {
+                plugin_id: plugin_id.clone(),
+            }
+        })?;
+        install_slots.push(slot);
+    }
+
+    let ids_for_remove = plugin_ids.clone();
+    let plugin_bytes = limiter
+        .run(move || cache::remove_plugins(&plugins_root, &ids_for_remove))
+        .await
+        .map_err(|e| ApiError::IoError {
+            message: format!("Failed to spawn blocking task: {e}"),
+        })??;
+    drop(install_slots);
+
+    // Transient data: output of exited terminals, and temp files of writes
+    // that were interrupted before they could clean up
+    let released_terminals = workspace_manager.release_exited_terminals().await;
+    let roots = workspace_manager.workspace_roots().await;
+    let (removed_temp_files, temp_bytes) = limiter
+        .run(move || {
+            roots.iter().fold((0, 0), |(count, bytes), root| {
+                let (removed, freed) = remove_temp_write_files(root);
+                (count + removed, bytes + freed)
+            })
+        })
+        .await
+        .map_err(|e| ApiError::IoError {
+            message: format!("Failed to spawn blocking task: {e}"),
+        })?;
+    let freed_bytes = plugin_bytes + temp_bytes;
+
+    log::info!(
+        "Cache cleared: plugins={}, temp_files={removed_temp_files}, terminals={released_terminals}, freed_bytes={freed_bytes}",
+        plugin_ids.len()
+    );
+
+    Ok(CacheClearResult {
+        removed_plugins: plugin_ids,
+        removed_temp_files,
+        released_terminals: released_terminals as u32,
+        freed_bytes: freed_bytes as f64,
+    })
+}
+
+/// Report where the plugin cache lives and how much space it uses.
+///
+/// # Returns
+///
+/// Returns `CacheInfo` with:
+/// - `pluginsRoot`: Absolute path to the plugins cache directory
+/// - `pluginIds`: Plugins currently installed in the cache
+/// - `totalBytes`: Total size on disk
+///
+/// # Errors
+///
+/// Returns `ApiError::IoError` if the cache directory can't be resolved or read.
+#[tauri::command]
+#[specta::specta]
+pub async fn cache_info(
+    plugin_manager: State<'_, Arc<PluginManager>>,
+) -> Result<CacheInfo, ApiError> {
+    log::debug!("cache_info called");
+
+    cache_info_inner(plugin_manager.plugins_root()?).await
+}
+
+/// Remove every installed plugin from the cache, plus transient data: the
+/// retained output of exited terminals and temp files of interrupted writes
+/// in workspaces.
+///
+/// Plugins are reinstalled through the usual install flow when needed.
+/// Nothing is removed if any cached plugin has a running agent or an install
+/// in progress.
+///
+/// # Arguments
+///
+/// * `confirm` - Must be `true`; guards against accidental wipes
+///
+/// # Returns
+///
+/// Returns `CacheClearResult` listing the removed plugins and bytes freed.
+///
+/// # Errors
+///
+/// Returns `ApiError::InvalidInput` if `confirm` is not `true`.
+/// Returns `ApiError::PluginInUse` if a running agent uses a cached plugin.
+/// Returns `ApiError::PluginInstallInProgress` if a cached plugin is being
+/// installed or repaired.
+/// Returns `ApiError::IoError` if a plugin directory can't be removed.
+#[tauri::command]
+#[specta::specta]
+pub async fn cache_clear(
+    plugin_manager: State<'_, Arc<PluginManager>>,
+    plugin_installer: State<'_, Arc<PluginInstaller>>,
+    workspace_manager: State<'_, Arc<WorkspaceManager>>,
+    confirm: bool,
+) -> Result<CacheClearResult, ApiError> {
+    cache_clear_inner(
+        &workspace_manager,
+        plugin_installer.installing(),
+        plugin_manager.plugins_root()?,
+        confirm,
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::test_support::IdleConnection;
+
+    fn plugins_fixture() -> PathBuf {
+        let root = std::env::temp_dir().join(format!("plugins_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("claude-code/node_modules")).unwrap();
+        std::fs::write(root.join("claude-code/install.json"), "{}").unwrap();
+        root
+    }
+
+    #[tokio::test]
+    async fn test_cache_clear_requires_confirm() {
+        let manager = WorkspaceManager::new();
+        let root = plugins_fixture();
+
+        let result =
+            cache_clear_inner(&manager, &InstallingSet::default(), root.clone(), false).await;
+
+        assert!(matches!(result, Err(ApiError::InvalidInput { .. })));
+        assert!(root.join("claude-code").exists());
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[tokio::test]
+    async fn test_cache_clear_removes_plugins_unless_in_use() {
+        let manager = WorkspaceManager::new();
+        let root = plugins_fixture();
+        let ws_summary = manager
+            .create_workspace(std::env::temp_dir().to_str().unwrap())
+            .await
+            .unwrap();
+        let agent = manager
+            .create_agent(
+                ws_summary.workspace_id.clone(),
+                "claude-code".to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+        let runtime = manager
+            .ensure_agent_runtime(ws_summary.workspace_id, agent.agent_id)
+            .await
+            .unwrap();
+        runtime
+            .attach_connection_for_test("session-1".to_string(), Arc::new(IdleConnection))
+            .await;
+
+        let refused =
+            cache_clear_inner(&manager, &InstallingSet::default(), root.clone(), true).await;
+        assert!(matches!(
+            refused,
+            Err(ApiError::PluginInUse { plugin_id }) if plugin_id == "claude-code"
+        ));
+        assert!(root.join("claude-code/install.json").exists());
+
+        // Once nothing runs on the plugin, the cache can be cleared
+        let idle_manager = WorkspaceManager::new();
+        let cleared =
+            cache_clear_inner(&idle_manager, &InstallingSet::default(), root.clone(), true)
+                .await
+                .unwrap();
+        assert_eq!(cleared.removed_plugins, vec!["claude-code".to_string()]);
+        assert_eq!(cleared.freed_bytes, 2.0);
+        assert!(!root.join("claude-code").exists());
+
+        let info = cache_info_inner(root.clone()).await.unwrap();
+        assert!(info.plugin_ids.is_empty());
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[tokio::test]
+    async fn test_cache_clear_refuses_during_install() {
+        let manager = WorkspaceManager::new();
+        let root = plugins_fixture();
+        let installing = InstallingSet::default();
+        let slot = installing.try_acquire("claude-code").unwrap();
+
+        let refused = cache_clear_inner(&manager, &installing, root.clone(), true).await;
+        assert!(matches!(
+            refused,
+            Err(ApiError::PluginInstallInProgress { plugin_id }) if plugin_id == "claude-code"
+        ));
+        assert!(root.join("claude-code/install.json").exists());
+
+        drop(slot);
+        let cleared = cache_clear_inner(&manager, &installing, root.clone(), true)
+            .await
+            .unwrap();
+        assert_eq!(cleared.removed_plugins, vec!["claude-code".to_string()]);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_cache_clear_removes_transient_data() {
+        let manager = WorkspaceManager::new();
+        let root = plugins_fixture();
+        let workspace_root =
+            std::env::temp_dir().join(format!("cache_ws_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(workspace_root.join("src")).unwrap();
+        std::fs::write(workspace_root.join("src/.tmp_write_1"), "abc").unwrap();
+        std::fs::write(workspace_root.join("src/lib.rs"), "").unwrap();
+        let ws_summary = manager
+            .create_workspace(workspace_root.to_str().unwrap())
+            .await
+            .unwrap();
+        let terminals = manager
+            .get_workspace(&ws_summary.workspace_id)
+            .await
+            .unwrap()
+            .terminal_manager();
+        let terminal_id = terminals
+            .create("true".to_string(), None, None, None, None)
+            .await
+            .unwrap();
+        terminals.wait_for_exit(&terminal_id).await.unwrap();
+
+        let cleared = cache_clear_inner(&manager, &InstallingSet::default(), root.clone(), true)
+            .await
+            .unwrap();
+
+        assert_eq!(cleared.removed_temp_files, 1);
+        assert_eq!(cleared.released_terminals, 1);
+        // install.json (2 bytes) and the temp file (3 bytes)
+        assert_eq!(cleared.freed_bytes, 5.0);
+        assert!(!workspace_root.join("src/.tmp_write_1").exists());
+        assert!(workspace_root.join("src/lib.rs").exists());
+        assert!(terminals.output(&terminal_id).await.is_err());
+        let _ = std::fs::remove_dir_all(&root);
+        let _ = std::fs::remove_dir_all(&workspace_root);
+    }
+}
@@ -5,24 +5,41 @@
 //! and sends the user's prompt to the agent.
 
 use std::sync::Arc;
+use std::time::Duration;
 
 use tauri::{Manager, State};
 
-use crate::api::types::{AgentId, ApiError, SendPromptAck, SessionId, WorkspaceId};
+use crate::api::types::{
+    AcpSessionUpdateEvent, AgentId, ApiError, PromptContentBlock, SendPromptAck, SessionId,
+    WorkspaceId,
+};
 use crate::plugins::manager::PluginManager;
 use crate::runtime::permissions::PermissionHub;
+use crate::runtime::transcripts::SessionTranscripts;
 use crate::runtime::workspace_manager::WorkspaceManager;
 
+/// Longest per-call turn timeout `chat_send_prompt` accepts (one hour).
+const MAX_PROMPT_TIMEOUT_MS: u32 = 60 * 60 * 1000;
+
 /// Inner function for testing without Tauri State wrapper.
-async fn chat_send_prompt_inner(
+/// Also used by `quick_pane_send`.
+pub(crate) async fn chat_send_prompt_inner(
     app: tauri::AppHandle,
     workspace_manager: &WorkspaceManager,
     plugin_manager: Arc<PluginManager>,
     workspace_id: WorkspaceId,
     agent_id: AgentId,
+    session_id: Option<SessionId>,
     prompt: String,
+    attachments: Option<Vec<PromptContentBlock>>,
+    timeout_ms: Option<u32>,
 ) -> Result<SendPromptAck, ApiError> {
-    log::info!("chat_send_prompt: workspace={workspace_id}, agent={agent_id}");
+    log::info!(
+        "chat_send_prompt: workspace={workspace_id}, agent={agent_id}, session={session_id:?}"
+    );
+
+    let timeout = prompt_timeout(timeout_ms)?;
+    let prompt = prompt_blocks(prompt, attachments.unwrap_or_default());
 
     // Get workspace to access root_dir and agent registry
     let workspace = workspace_manager.get_workspace(&workspace_id).await?;
@@ -34,8 +51,12 @@ async fn chat_send_prompt_inner(
     // Ensure agent runtime exists (use workspace directly to avoid redundant lookup)
     let agent_runtime = workspace.ensure_agent_runtime(agent_id.clone()).await?;
 
+    // Resume the agent's previous session if the process has to be (re)started
+    let resume_session_id = workspace.agent_session_id(&agent_id).await?;
+    let mcp_servers = workspace.agent_mcp_servers(&agent_id).await?;
+
     // Ensure agent is started (lazy startup on first prompt)
-    let session_id = agent_runtime
+    let started_session_id = agent_runtime
         .ensure_started(
             app,
             workspace_root,
@@ -43,17 +64,66 @@ async fn chat_send_prompt_inner(
             permission_hub,
             terminal_manager,
             fs_manager,
+            resume_session_id,
+            mcp_servers,
         )
         .await?;
 
-    log::info!("Agent started: workspace={workspace_id}, agent={agent_id}, session={session_id}");
+    log::info!(
+        "Agent started: workspace={workspace_id}, agent={agent_id}, session={started_session_id}"
+    );
+
+    // US-07: Send the prompt to the agent (may move to a fresh session if the
+    // adapter no longer knows the current one)
+    let to_primary = session_id.is_none();
+    let ack = agent_runtime
+        .send_prompt_with_timeout(session_id, prompt, timeout)
+        .await?;
+    // Only the primary session is resumed when the agent restarts
+    if to_primary {
+        workspace_manager
+            .record_agent_session(
+                workspace_id.clone(),
+                agent_id.clone(),
+                ack.session_id.clone(),
+            )
+            .await?;
+    }
+
+    log::debug!(
+        "Prompt sent: workspace={workspace_id}, agent={agent_id}, session={}, turn={:?}, queued={}",
+        ack.session_id,
+        ack.turn_id,
+        ack.queued
+    );
 
-    // US-07: Send the prompt to the agent
-    agent_runtime.send_prompt(prompt).await?;
+    Ok(ack)
+}
 
-    log::debug!("Prompt sent: workspace={workspace_id}, agent={agent_id}, session={session_id}");
+/// Combine the prompt text and attachments into ACP content blocks.
+///
+/// The text comes first; it is omitted only when empty and attachments exist.
+fn prompt_blocks(prompt: String, attachments: Vec<PromptContentBlock>) -> Vec<PromptContentBlock> {
+    let mut blocks = Vec::with_capacity(attachments.len() + 1);
+    if !prompt.is_empty() || attachments.is_empty() {
+        blocks.push(PromptContentBlock::text(prompt));
+    }
+    blocks.extend(attachments);
+    blocks
+}
 
-    Ok(SendPromptAck { session_id })
+/// Validate a per-call turn timeout; `None` keeps the default (no limit).
+fn prompt_timeout(timeout_ms: Option<u32>) -> Result<Option<Duration>, ApiError> {
+    match timeout_ms {
+        None => Ok(None),
+        Some(0) => Err(ApiError::InvalidInput {
+            message: "Prompt timeout must be positive".to_string(),
+        }),
+        Some(ms) if ms > MAX_PROMPT_TIMEOUT_MS => Err(ApiError::InvalidInput {
+            message: format!("Prompt timeout cannot exceed {MAX_PROMPT_TIMEOUT_MS}ms"),
+        }),
+        Some(ms) => Ok(Some(Duration::from_millis(u64::from(ms)))),
+    }
 }
 
 /// Inner function for testing without Tauri State wrapper.
@@ -99,10 +169,15 @@ async fn chat_stop_turn_inner(
 /// # Arguments
 /// * `workspace_id` - ID of the workspace containing the agent
 /// * `agent_id` - ID of the agent to send the prompt to
+/// * `session_id` - Open session to send to; `None` for the agent's primary session
 /// * `prompt` - The user's prompt text
+/// * `attachments` - Optional images or resource links sent after the text
+/// * `timeout_ms` - Optional limit on how long this turn may run once started;
+///   a turn still running then is cancelled and completes with an error
 ///
 /// # Returns
-/// * `SendPromptAck` - Contains the session ID for tracking responses
+/// * `SendPromptAck` - Contains the session ID and turn ID for tracking responses,
+///   or `queued: true` if the prompt waits for the active turn to complete
 ///
 /// # Events Emitted
 /// * `agent/status_changed` - When agent starts (Starting → Running) or errors
@@ -111,9 +186,14 @@ async fn chat_stop_turn_inner(
 /// # Errors
 /// * `ApiError::WorkspaceNotFound` - If workspace doesn't exist
 /// * `ApiError::AgentNotFound` - If agent doesn't exist in workspace
+/// * `ApiError::InvalidInput` - If `session_id` isn't an open session of the agent,
+///   or `timeout_ms` is zero or above one hour
 /// * `ApiError::PluginNotInstalled` - If the agent's plugin is not installed
 /// * `ApiError::PluginMissingBinPath` - If plugin has no binary path
-/// * `ApiError::ProtocolError` - If ACP communication fails
+/// * `ApiError::TurnInProgress` - If a turn is active and the agent rejects busy prompts
+/// * `ApiError::TooManyPrompts` - If the session already has as many prompts outstanding as allowed
+/// * `ApiError::ProtocolError` - If ACP communication fails, or the adapter
+///   didn't accept the prompt within `timeout_ms`
 /// * `ApiError::IoError` - If writing to stdin fails
 #[tauri::command]
 #[specta::specta]
@@ -123,7 +203,10 @@ pub async fn chat_send_prompt(
     plugin_manager: State<'_, Arc<PluginManager>>,
     workspace_id: WorkspaceId,
     agent_id: AgentId,
+    session_id: Option<SessionId>,
     prompt: String,
+    attachments: Option<Vec<PromptContentBlock>>,
+    timeout_ms: Option<u32>,
 ) -> Result<SendPromptAck, ApiError> {
     chat_send_prompt_inner(
         app,
@@ -131,7 +214,10 @@ pub async fn chat_send_prompt(
         plugin_manager.inner().clone(),
         workspace_id,
         agent_id,
+        session_id,
         prompt,
+        attachments,
+        timeout_ms,
     )
     .await
 }
@@ -164,6 +250,39 @@ pub async fn chat_stop_turn(
     chat_stop_turn_inner(&workspace_manager, workspace_id, agent_id, session_id).await
 }
 
+/// Load the recorded transcript of a session, oldest update first.
+///
+/// Transcripts are only written while the `sessionTranscripts` preference is
+/// on; each entry is an `acp/session_update` event as it was emitted.
+///
+/// # Arguments
+/// * `workspace_id` - ID of the workspace the session belongs to
+/// * `session_id` - Session whose transcript to load
+///
+/// # Returns
+/// * The recorded updates (empty if none were recorded)
+///
+/// # Errors
+/// * `ApiError::InvalidInput` - If an ID is empty
+/// * `ApiError::IoError` - If the transcript can't be read
+#[tauri::command]
+#[specta::specta]
+pub async fn session_transcript_read(
+    transcripts: State<'_, Arc<SessionTranscripts>>,
+    workspace_id: WorkspaceId,
+    session_id: SessionId,
+) -> Result<Vec<AcpSessionUpdateEvent>, ApiError> {
+    log::debug!("session_transcript_read: workspace={workspace_id}, session={session_id}");
+
+    if workspace_id.trim().is_empty() || session_id.trim().is_empty() {
+        return Err(ApiError::InvalidInput {
+            message: "Workspace ID and session ID cannot be empty".to_string(),
+        });
+    }
+
+    transcripts.read(&workspace_id, &session_id).await
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -310,4 +429,48 @@ mod tests {
 
         assert!(matches!(result, Err(ApiError::ProtocolError { .. })));
     }
+
+    #[test]
+    fn test_prompt_blocks_text_then_attachments() {
+        let image = PromptContentBlock::Image {
+            mime_type: "image/png".to_string(),
+            data: "AAAA".to_string(),
+        };
+
+        assert_eq!(
+            prompt_blocks("look".to_string(), vec![image.clone()]),
+            vec![PromptContentBlock::text("look"), image.clone()]
+        );
+        // Attachment-only prompts skip the empty text block
+        assert_eq!(
+            prompt_blocks(String::new(), vec![image.clone()]),
+            vec![image]
+        );
+        // Plain text behaves as before
+        assert_eq!(
+            prompt_blocks("hi".to_string(), Vec::new()),
+            vec![PromptContentBlock::text("hi")]
+        );
+    }
+
+    #[test]
+    fn test_prompt_timeout_bounds() {
+        assert_eq!(prompt_timeout(None).unwrap(), None);
+        assert_eq!(
+            prompt_timeout(Some(1500)).unwrap(),
+            Some(Duration::from_millis(1500))
+        );
+        assert_eq!(
+            prompt_timeout(Some(MAX_PROMPT_TIMEOUT_MS)).unwrap(),
+            Some(Duration::from_millis(u64::from(MAX_PROMPT_TIMEOUT_MS)))
+        );
+        assert!(matches!(
+            prompt_timeout(Some(0)),
+            Err(ApiError::InvalidInput { .. })
+        ));
+        assert!(matches!(
+            prompt_timeout(Some(MAX_PROMPT_TIMEOUT_MS + 1)),
+            Err(ApiError::InvalidInput { .. })
+        ));
+    }
 }
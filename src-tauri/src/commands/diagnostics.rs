@@ -0,0 +1,185 @@
+//! Diagnostics command.
+//!
+//! Collects versions and counts from the runtime in one call so users can
+//! paste a single snapshot into bug reports.
+
+use std::sync::Arc;
+
+use tauri::State;
+
+use crate::api::types::{ApiError, Diagnostics, InstalledPluginVersion};
+use crate::plugins::cache;
+use crate::plugins::manager::PluginManager;
+use crate::runtime::permissions::PermissionHub;
+use crate::runtime::workspace_manager::WorkspaceManager;
+use crate::utils::blocking::BlockingFsLimiter;
+
+/// Clamp a count to the `u32` sent to the frontend.
+fn count(value: usize) -> u32 {
+    value.min(u32::MAX as usize) as u32
+}
+
+async fn diagnostics_inner(
+    workspace_manager: &WorkspaceManager,
+    pending_permissions: usize,
+    plugins: Vec<InstalledPluginVersion>,
+) -> Diagnostics {
+    let counts = workspace_manager.runtime_counts().await;
+    Diagnostics {
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        workspace_count: count(counts.workspaces),
+        agent_count: count(counts.agents),
+        running_agent_count: count(counts.running_agents),
+        active_terminal_count: count(counts.active_terminals),
+        pending_permission_count: count(pending_permissions),
+        plugins,
+    }
+}
+
+/// Versions of the plugins in the local cache, sorted by ID.
+async fn installed_plugins(
+    plugin_manager: &PluginManager,
+) -> Result<Vec<InstalledPluginVersion>, ApiError> {
+    let plugins_root = plugin_manager.plugins_root()?;
+    let plugin_ids = BlockingFsLimiter::shared()
+        .run(move || cache::cached_plugin_ids(&plugins_root))
+        .await
+        .map_err(|e| ApiError::IoError {
+            message: format!("Failed to spawn blocking task: {e}"),
+        })??;
+
+    let mut plugins = Vec::with_capacity(plugin_ids.len());
+    for plugin_id in plugin_ids {
+        // Stray directories that aren't valid plugin IDs are reported without a version
+        let installed_version = match plugin_manager.get_status(plugin_id.clone(), false).await {
+            Ok(status) => status.installed_version,
+            Err(e) => {
+                log::warn!(
+                    "Failed to read plugin status for diagnostics: plugin_id={plugin_id}, error={e}"
+                );
+                None
+            }
+        };
+        plugins.push(InstalledPluginVersion {
+            plugin_id,
+            installed_version,
+        });
+    }
+    Ok(plugins)
+}
+
+/// Report app version, runtime counts and installed plugin versions.
+///
+/// Cheap to compute; meant to be attached to bug reports.
+///
+/// # Returns
+///
+/// Returns `Diagnostics` with:
+/// - `appVersion`: Backend version
+/// - `workspaceCount`, `agentCount`, `runningAgentCount`: Across all workspaces
+/// - `activeTerminalCount`: Terminal commands still running
+/// - `pendingPermissionCount`: Permission requests awaiting a decision
+/// - `plugins`: Cached plugins and their installed versions
+///
+/// # Errors
+///
+/// Returns `ApiError::IoError` if the plugin cache can't be read.
+#[tauri::command]
+#[specta::specta]
+pub async fn diagnostics(
+    workspace_manager: State<'_, Arc<WorkspaceManager>>,
+    plugin_manager: State<'_, Arc<PluginManager>>,
+    permission_hub: State<'_, Arc<PermissionHub>>,
+) -> Result<Diagnostics, ApiError> {
+    log::debug!("diagnostics called");
+
+    let plugins = installed_plugins(&plugin_manager).await?;
+    let pending_permissions = permission_hub.list_pending().await.len();
+    Ok(diagnostics_inner(&workspace_manager, pending_permissions, plugins).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::test_support::IdleConnection;
+
+    #[tokio::test]
+    async fn test_diagnostics_with_no_workspaces() {
+        let manager = WorkspaceManager::new();
+
+        let diagnostics = diagnostics_inner(&manager, 0, Vec::new()).await;
+
+        assert_eq!(diagnostics.app_version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(diagnostics.workspace_count, 0);
+        assert_eq!(diagnostics.agent_count, 0);
+        assert_eq!(diagnostics.running_agent_count, 0);
+        assert_eq!(diagnostics.active_terminal_count, 0);
+        assert!(diagnostics.plugins.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_diagnostics_aggregates_across_workspaces() {
+        let manager = WorkspaceManager::new();
+        let roots: Vec<_> = (0..2)
+            .map(|_| std::env::temp_dir().join(format!("ws_diag_{}", uuid::Uuid::new_v4())))
+            .collect();
+        let mut workspaces = Vec::new();
+        for root in &roots {
+            std::fs::create_dir_all(root).unwrap();
+            workspaces.push(
+                manager
+                    .create_workspace(root.to_str().unwrap())
+                    .await
+                    .unwrap(),
+            );
+        }
+        let (first, second) = (&workspaces[0], &workspaces[1]);
+
+        let running = manager
+            .create_agent(first.workspace_id.clone(), "claude-code".to_string(), None)
+            .await
+            .unwrap();
+        manager
+            .create_agent(first.workspace_id.clone(), "codex".to_string(), None)
+            .await
+            .unwrap();
+        manager
+            .create_agent(second.workspace_id.clone(), "gemini".to_string(), None)
+            .await
+            .unwrap();
+        manager
+            .ensure_agent_runtime(first.workspace_id.clone(), running.agent_id)
+            .await
+            .unwrap()
+            .attach_connection_for_test("session-1".to_string(), Arc::new(IdleConnection))
+            .await;
+
+        let terminals = manager
+            .get_workspace(&second.workspace_id)
+            .await
+            .unwrap()
+            .terminal_manager();
+        let _run = terminals
+            .spawn_run("sleep 5".to_string(), None, None)
+            .await
+            .unwrap();
+
+        let plugins = vec![InstalledPluginVersion {
+            plugin_id: "claude-code".to_string(),
+            installed_version: Some("1.2.3".to_string()),
+        }];
+        let diagnostics = diagnostics_inner(&manager, 2, plugins.clone()).await;
+        terminals.kill_all().await;
+
+        assert_eq!(diagnostics.workspace_count, 2);
+        assert_eq!(diagnostics.agent_count, 3);
+        assert_eq!(diagnostics.running_agent_count, 1);
+        assert_eq!(diagnostics.active_terminal_count, 1);
+        assert_eq!(diagnostics.pending_permission_count, 2);
+        assert_eq!(diagnostics.plugins, plugins);
+        for root in roots {
+            let _ = std::fs::remove_dir_all(root);
+        }
+    }
+}
@@ -0,0 +1,66 @@
+//! Workspace file system commands.
+
+use std::sync::Arc;
+
+use tauri::State;
+
+use crate::api::types::{ApiError, DirEntryInfo, FileMetadata, WorkspaceId};
+use crate::runtime::workspace_manager::WorkspaceManager;
+
+/// List a directory inside a workspace.
+///
+/// # Arguments
+/// * `workspace_id` - ID of the workspace to browse
+/// * `path` - Directory path relative to the workspace root (empty: the root)
+///
+/// # Returns
+/// * `Ok(Vec<DirEntryInfo>)` - Entries, directories first, then by name
+/// * `Err(ApiError)` - If the workspace is missing, the path escapes the
+///   workspace, or the path is not a directory
+#[tauri::command]
+#[specta::specta]
+pub async fn fs_list_dir(
+    workspace_manager: State<'_, Arc<WorkspaceManager>>,
+    workspace_id: WorkspaceId,
+    path: String,
+) -> Result<Vec<DirEntryInfo>, ApiError> {
+    log::info!("fs_list_dir: workspace={workspace_id}, path={path}");
+
+    if workspace_id.trim().is_empty() {
+        return Err(ApiError::InvalidInput {
+            message: "Workspace ID cannot be empty".to_string(),
+        });
+    }
+
+    let workspace = workspace_manager.get_workspace(&workspace_id).await?;
+    workspace.fs_manager().list_dir(path).await
+}
+
+/// Size, modification time and type of a path inside a workspace.
+///
+/// # Arguments
+/// * `workspace_id` - ID of the workspace the path belongs to
+/// * `path` - File or directory path (relative to the workspace root or absolute)
+///
+/// # Returns
+/// * `Ok(FileMetadata)` - Entry info; symlinks are reported as such
+/// * `Err(ApiError)` - If the workspace is missing, the path escapes the
+///   workspace, or it doesn't exist
+#[tauri::command]
+#[specta::specta]
+pub async fn fs_metadata(
+    workspace_manager: State<'_, Arc<WorkspaceManager>>,
+    workspace_id: WorkspaceId,
+    path: String,
+) -> Result<FileMetadata, ApiError> {
+    log::info!("fs_metadata: workspace={workspace_id}, path={path}");
+
+    if workspace_id.trim().is_empty() {
+        return Err(ApiError::InvalidInput {
+            message: "Workspace ID cannot be empty".to_string(),
+        });
+    }
+
+    let workspace = workspace_manager.get_workspace(&workspace_id).await?;
+    workspace.fs_manager().metadata(path).await
+}
@@ -4,7 +4,10 @@
 //! Import specific commands via their submodule (e.g., `commands::preferences::greet`).
 
 pub mod agents;
+pub mod cache;
 pub mod chat;
+pub mod diagnostics;
+pub mod fs;
 pub mod notifications;
 pub mod permissions;
 pub mod plugins;
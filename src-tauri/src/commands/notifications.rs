@@ -2,7 +2,12 @@
 //!
 //! Provides cross-platform native notification support using the Tauri notification plugin.
 
-use tauri::AppHandle;
+use std::sync::Arc;
+
+use tauri::{AppHandle, Manager};
+
+use crate::api::types::NotificationLevel;
+use crate::runtime::notifier::Notifier;
 
 /// Sends a native system notification.
 /// On mobile platforms, returns an error as notifications are not yet supported.
@@ -44,3 +49,18 @@ pub async fn send_native_notification(
         Err("Native notifications not supported on mobile".to_string())
     }
 }
+
+/// Shows a user-facing alert unless notifications are disabled in preferences.
+/// Falls back to a `notification/show` event where native notifications are
+/// unavailable. Returns whether the alert was shown.
+#[tauri::command]
+#[specta::specta]
+pub async fn notify(
+    app: AppHandle,
+    title: String,
+    body: Option<String>,
+    level: NotificationLevel,
+) -> Result<bool, String> {
+    let notifier = app.state::<Arc<Notifier>>();
+    Ok(notifier.notify(&title, body.as_deref(), level))
+}
@@ -4,10 +4,14 @@
 //! from background operations (like plugin installation).
 
 use std::sync::Arc;
+use std::time::Duration;
 
 use tauri::State;
 
-use crate::api::types::{ApiError, OperationId, PermissionDecision};
+use crate::api::types::{
+    AcpPermissionRequestedEvent, ApiError, OperationId, PermissionAuditEntry, PermissionDecision,
+    PermissionRule, PermissionSourceKind,
+};
 use crate::runtime::permissions::PermissionHub;
 
 /// Respond to a pending permission request.
@@ -18,7 +22,10 @@ use crate::runtime::permissions::PermissionHub;
 /// # Arguments
 ///
 /// * `operation_id` - The operation ID from the permission request event
-/// * `decision` - The user's decision (AllowOnce or Deny)
+/// * `decision` - The user's decision (AllowOnce, AllowAlways or Deny);
+///   AllowAlways also remembers the grant for matching requests
+/// * `option_id` - The agent-defined option the user picked, when the request
+///   event listed `options`; if omitted, the closest option to `decision` is used
 ///
 /// # Returns
 ///
@@ -29,7 +36,130 @@ pub async fn permission_respond(
     permission_hub: State<'_, Arc<PermissionHub>>,
     operation_id: OperationId,
     decision: PermissionDecision,
+    option_id: Option<String>,
 ) -> Result<(), ApiError> {
-    log::info!("Permission response: operation_id={operation_id}, decision={decision:?}");
-    permission_hub.respond(operation_id, decision).await
+    log::info!(
+        "Permission response: operation_id={operation_id}, decision={decision:?}, option_id={option_id:?}"
+    );
+    permission_hub
+        .respond(operation_id, decision, option_id)
+        .await
+}
+
+/// List permission requests still awaiting a decision, oldest first.
+///
+/// Each entry has the same shape as the `acp/permission_requested` event, so
+/// the frontend can re-render prompts it missed (e.g. after a reload).
+#[tauri::command]
+#[specta::specta]
+pub async fn permission_list_pending(
+    permission_hub: State<'_, Arc<PermissionHub>>,
+) -> Result<Vec<AcpPermissionRequestedEvent>, ApiError> {
+    Ok(permission_hub.list_pending().await)
+}
+
+/// Cancel a pending permission request.
+///
+/// Called when the operation that asked for permission is abandoned; the
+/// waiting operation fails with a cancelled error and the prompt can be
+/// dismissed.
+///
+/// # Arguments
+///
+/// * `operation_id` - The operation ID from the permission request event
+///
+/// # Returns
+///
+/// Ok(()) on success, or `OperationNotFound` if it was already answered,
+/// cancelled or timed out.
+#[tauri::command]
+#[specta::specta]
+pub async fn permission_cancel(
+    permission_hub: State<'_, Arc<PermissionHub>>,
+    operation_id: OperationId,
+) -> Result<(), ApiError> {
+    log::info!("Permission cancel: operation_id={operation_id}");
+    permission_hub.cancel(operation_id).await
+}
+
+/// List remembered "Allow Always" grants.
+///
+/// # Returns
+///
+/// Every remembered rule with the workspace (and agent) it is scoped to.
+#[tauri::command]
+#[specta::specta]
+pub async fn permission_rules_list(
+    permission_hub: State<'_, Arc<PermissionHub>>,
+) -> Result<Vec<PermissionRule>, ApiError> {
+    Ok(permission_hub.list_rules())
+}
+
+/// Revoke a remembered grant so matching requests prompt again.
+///
+/// # Arguments
+///
+/// * `rule_id` - The rule ID from `permission_rules_list`
+///
+/// # Returns
+///
+/// Ok(()) on success, or `InvalidInput` if no rule has this ID.
+#[tauri::command]
+#[specta::specta]
+pub async fn permission_rule_revoke(
+    permission_hub: State<'_, Arc<PermissionHub>>,
+    rule_id: String,
+) -> Result<(), ApiError> {
+    log::info!("Permission rule revoke: rule_id={rule_id}");
+    permission_hub.revoke_rule(&rule_id)
+}
+
+/// Recent permission decisions for display, newest first.
+///
+/// # Arguments
+///
+/// * `limit` - Maximum number of entries (defaults to everything kept in memory)
+///
+/// # Returns
+///
+/// Each resolved request with what was asked, by whom, and how it was resolved.
+#[tauri::command]
+#[specta::specta]
+pub async fn permission_history(
+    permission_hub: State<'_, Arc<PermissionHub>>,
+    limit: Option<u32>,
+) -> Result<Vec<PermissionAuditEntry>, ApiError> {
+    let limit = limit.map_or(usize::MAX, |limit| limit as usize);
+    Ok(permission_hub.history(limit))
+}
+
+/// Set how long permission requests wait for an answer before being denied.
+///
+/// # Arguments
+///
+/// * `source_kind` - Only this kind of request (omit to change the default,
+///   which is 5 minutes out of the box)
+/// * `timeout_secs` - Seconds to wait; omit to wait indefinitely
+///
+/// # Returns
+///
+/// Ok(()) on success, or `InvalidInput` if `timeout_secs` is zero.
+#[tauri::command]
+#[specta::specta]
+pub async fn permission_set_timeout(
+    permission_hub: State<'_, Arc<PermissionHub>>,
+    source_kind: Option<PermissionSourceKind>,
+    timeout_secs: Option<u32>,
+) -> Result<(), ApiError> {
+    log::info!("Permission timeout: source_kind={source_kind:?}, timeout_secs={timeout_secs:?}");
+    if timeout_secs == Some(0) {
+        return Err(ApiError::InvalidInput {
+            message: "timeoutSecs must be at least 1 (omit it to wait indefinitely)".to_string(),
+        });
+    }
+    permission_hub.set_timeout(
+        source_kind,
+        timeout_secs.map(|secs| Duration::from_secs(secs as u64)),
+    );
+    Ok(())
 }
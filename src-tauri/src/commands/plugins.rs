@@ -6,9 +6,11 @@ use std::sync::Arc;
 
 use tauri::State;
 
-use crate::api::types::{ApiError, OperationStarted, PluginStatus};
+use crate::api::types::{ApiError, OperationStarted, PluginStatus, ToolchainInfo};
 use crate::plugins::manager::PluginManager;
+use crate::plugins::package_manager::PackageManager;
 use crate::runtime::plugin_installer::PluginInstaller;
+use crate::runtime::workspace_manager::WorkspaceManager;
 
 /// Get the installation and update status of a plugin.
 ///
@@ -54,6 +56,51 @@ pub async fn plugin_get_status(
     Ok(status)
 }
 
+/// Check whether the Node.js toolchain needed for plugin installs is available.
+///
+/// Lets the frontend disable the install button and explain what is missing
+/// before any permission prompt or filesystem work begins.
+///
+/// # Returns
+///
+/// Returns `ToolchainInfo` with:
+/// - `nodeVersion` / `npmVersion`: Detected versions, or null if missing
+/// - `minNodeVersion`: Minimum Node.js version the adapters require
+/// - `ready`: Whether installation can proceed
+/// - `message`: Explanation when `ready` is false
+#[tauri::command]
+#[specta::specta]
+pub async fn plugin_check_toolchain(
+    plugin_manager: State<'_, Arc<PluginManager>>,
+) -> Result<ToolchainInfo, ApiError> {
+    log::debug!("plugin_check_toolchain called");
+
+    plugin_manager.check_toolchain().await
+}
+
+/// Check whether any running agent uses a plugin.
+///
+/// The frontend uses this to disable reinstall/repair while an adapter
+/// process for the plugin is alive.
+///
+/// # Arguments
+///
+/// * `plugin_id` - Plugin identifier (e.g., "claude-code")
+///
+/// # Returns
+///
+/// Returns `true` if an agent in any workspace is running on the plugin.
+#[tauri::command]
+#[specta::specta]
+pub async fn plugin_is_in_use(
+    workspace_manager: State<'_, Arc<WorkspaceManager>>,
+    plugin_id: String,
+) -> Result<bool, ApiError> {
+    log::debug!("plugin_is_in_use called: plugin_id={plugin_id}");
+
+    Ok(workspace_manager.is_plugin_in_use(&plugin_id).await)
+}
+
 /// Start a plugin installation operation.
 ///
 /// This command initiates an async installation process:
@@ -66,6 +113,7 @@ pub async fn plugin_get_status(
 ///
 /// * `plugin_id` - Plugin identifier (e.g., "claude-code", "codex", "gemini")
 /// * `version` - Optional version to install
+/// * `package_manager` - Optional package manager (npm, pnpm, yarn); auto-detected if omitted
 ///
 /// # Returns
 ///
@@ -75,18 +123,63 @@ pub async fn plugin_get_status(
 ///
 /// Returns `ApiError::InvalidInput` if the plugin ID is invalid.
 /// Returns `ApiError::PluginInstallInProgress` if the plugin is already being installed.
+/// Returns `ApiError::PluginInUse` if a running agent uses the plugin.
 #[tauri::command]
 #[specta::specta]
 pub async fn plugin_install(
     plugin_installer: State<'_, Arc<PluginInstaller>>,
+    workspace_manager: State<'_, Arc<WorkspaceManager>>,
     plugin_id: String,
     version: Option<String>,
+    package_manager: Option<PackageManager>,
 ) -> Result<OperationStarted, ApiError> {
-    log::info!("plugin_install called: plugin_id={plugin_id}, version={version:?}");
+    log::info!(
+        "plugin_install called: plugin_id={plugin_id}, version={version:?}, package_manager={package_manager:?}"
+    );
+
+    workspace_manager
+        .ensure_plugin_not_in_use(&plugin_id)
+        .await?;
 
     // Use .inner() to get &Arc<PluginInstaller> for the arbitrary self type receiver
     plugin_installer
         .inner()
-        .start_install(plugin_id, version)
+        .start_install(plugin_id, version, package_manager)
         .await
 }
+
+/// Repair a plugin whose installation is broken or incomplete.
+///
+/// Like `plugin_install`, this returns immediately and goes through the
+/// permission prompt. Once approved, `node_modules`, `package.json`, and
+/// `install.json` are removed and the previously recorded version is
+/// reinstalled, emitting `acp/plugin_status_changed` when done.
+///
+/// # Arguments
+///
+/// * `plugin_id` - Plugin identifier (e.g., "claude-code")
+///
+/// # Returns
+///
+/// Returns `OperationStarted` with the operation ID for tracking.
+///
+/// # Errors
+///
+/// Returns `ApiError::InvalidInput` if the plugin ID is invalid.
+/// Returns `ApiError::PluginInstallInProgress` if the plugin is already being installed.
+/// Returns `ApiError::PluginInUse` if a running agent uses the plugin.
+#[tauri::command]
+#[specta::specta]
+pub async fn plugin_repair(
+    plugin_installer: State<'_, Arc<PluginInstaller>>,
+    workspace_manager: State<'_, Arc<WorkspaceManager>>,
+    plugin_id: String,
+) -> Result<OperationStarted, ApiError> {
+    log::info!("plugin_repair called: plugin_id={plugin_id}");
+
+    workspace_manager
+        .ensure_plugin_not_in_use(&plugin_id)
+        .await?;
+
+    plugin_installer.inner().start_repair(plugin_id).await
+}
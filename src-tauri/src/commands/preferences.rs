@@ -2,10 +2,24 @@
 //!
 //! Handles loading and saving user preferences to disk.
 
+use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::Arc;
 use tauri::{AppHandle, Manager};
 
-use crate::types::{validate_color_theme, validate_string_input, validate_theme, AppPreferences};
+use crate::protocols::acp::AcpKeepalive;
+use crate::runtime::agents::{AdapterKeepalive, DEFAULT_KEEPALIVE_TIMEOUT};
+use crate::runtime::env_file;
+use crate::runtime::notifier::Notifier;
+use crate::runtime::permissions::PermissionHub;
+use crate::runtime::session_updates::ChunkCoalescing;
+use crate::runtime::transcripts::SessionTranscripts;
+
+use crate::types::{
+    validate_adapter_keepalive, validate_color_theme, validate_fs_path_rules,
+    validate_string_input, validate_terminal_command_rules, validate_theme, AppPreferences,
+    FsPathRule, TerminalCommandRule, DEFAULT_QUICK_PANE_PLUGIN,
+};
 
 /// Gets the path to the preferences file.
 fn get_preferences_path(app: &AppHandle) -> Result<PathBuf, String> {
@@ -21,9 +35,8 @@ fn get_preferences_path(app: &AppHandle) -> Result<PathBuf, String> {
     Ok(app_data_dir.join("preferences.json"))
 }
 
-/// Load the saved quick pane shortcut from preferences, returning None on any failure.
-/// Used at startup before the full preferences system is available.
-pub fn load_quick_pane_shortcut(app: &AppHandle) -> Option<String> {
+/// Load the saved preferences, returning None on any failure.
+fn load_saved_preferences(app: &AppHandle) -> Option<AppPreferences> {
     let path = get_preferences_path(app).ok()?;
     if !path.exists() {
         return None;
@@ -31,10 +44,99 @@ pub fn load_quick_pane_shortcut(app: &AppHandle) -> Option<String> {
     let contents = std::fs::read_to_string(&path)
         .inspect_err(|e| log::warn!("Failed to read preferences: {e}"))
         .ok()?;
-    let prefs: AppPreferences = serde_json::from_str(&contents)
+    serde_json::from_str(&contents)
         .inspect_err(|e| log::warn!("Failed to parse preferences: {e}"))
-        .ok()?;
-    prefs.quick_pane_shortcut
+        .ok()
+}
+
+/// Load the saved quick pane shortcut from preferences, returning None on any failure.
+/// Used at startup before the full preferences system is available.
+pub fn load_quick_pane_shortcut(app: &AppHandle) -> Option<String> {
+    load_saved_preferences(app)?.quick_pane_shortcut
+}
+
+/// Load the plugin for quick pane prompts, falling back to the default.
+pub fn load_quick_pane_plugin_id(app: &AppHandle) -> String {
+    load_saved_preferences(app)
+        .and_then(|prefs| prefs.quick_pane_plugin_id)
+        .unwrap_or_else(|| DEFAULT_QUICK_PANE_PLUGIN.to_string())
+}
+
+/// Whether the user opted in to the permission audit file (false on any failure).
+/// Used at startup before the full preferences system is available.
+pub fn load_permission_audit_log(app: &AppHandle) -> bool {
+    load_saved_preferences(app).is_some_and(|prefs| prefs.permission_audit_log)
+}
+
+/// Whether alerts are enabled (true, the default, on any failure).
+/// Used at startup before the full preferences system is available.
+pub fn load_notifications_enabled(app: &AppHandle) -> bool {
+    load_saved_preferences(app).is_none_or(|prefs| prefs.notifications_enabled)
+}
+
+/// Whether the user opted in to session transcripts (false on any failure).
+/// Used at startup before the full preferences system is available.
+pub fn load_session_transcripts(app: &AppHandle) -> bool {
+    load_saved_preferences(app).is_some_and(|prefs| prefs.session_transcripts)
+}
+
+/// Whether the user opted in to workspace `.env` variables (false on any failure).
+/// Used at startup before the full preferences system is available.
+pub fn load_workspace_env_file(app: &AppHandle) -> bool {
+    load_saved_preferences(app).is_some_and(|prefs| prefs.workspace_env_file)
+}
+
+/// Window for merging message chunks (off on any failure).
+/// Used at startup before the full preferences system is available.
+pub fn load_message_chunk_coalesce_window(app: &AppHandle) -> Option<std::time::Duration> {
+    load_saved_preferences(app).map(|prefs| coalesce_window(prefs.message_chunk_coalesce_ms))
+}
+
+fn coalesce_window(millis: u32) -> std::time::Duration {
+    std::time::Duration::from_millis(u64::from(millis))
+}
+
+/// Adapter keepalive settings (off on any failure).
+/// Used at startup before the full preferences system is available.
+pub fn load_adapter_keepalive(app: &AppHandle) -> Option<AcpKeepalive> {
+    load_saved_preferences(app).and_then(|prefs| adapter_keepalive(&prefs))
+}
+
+fn adapter_keepalive(prefs: &AppPreferences) -> Option<AcpKeepalive> {
+    if prefs.adapter_keepalive_interval_ms == 0 {
+        return None;
+    }
+    let timeout = match prefs.adapter_keepalive_timeout_ms {
+        0 => DEFAULT_KEEPALIVE_TIMEOUT,
+        millis => std::time::Duration::from_millis(u64::from(millis)),
+    };
+    Some(AcpKeepalive {
+        interval: std::time::Duration::from_millis(u64::from(prefs.adapter_keepalive_interval_ms)),
+        timeout,
+    })
+}
+
+/// Terminal command rules per workspace (none on any failure).
+/// Used at startup before the full preferences system is available.
+pub fn load_terminal_command_rules(app: &AppHandle) -> HashMap<String, Vec<TerminalCommandRule>> {
+    load_saved_preferences(app)
+        .map(|prefs| prefs.terminal_command_rules)
+        .unwrap_or_default()
+}
+
+/// File path rules per workspace (none on any failure).
+/// Used at startup before the full preferences system is available.
+pub fn load_fs_path_rules(app: &AppHandle) -> HashMap<String, Vec<FsPathRule>> {
+    load_saved_preferences(app)
+        .map(|prefs| prefs.fs_path_rules)
+        .unwrap_or_default()
+}
+
+/// Whether the default file deny rules apply (true, the default, on any
+/// failure).
+/// Used at startup before the full preferences system is available.
+pub fn load_fs_default_deny_rules(app: &AppHandle) -> bool {
+    load_saved_preferences(app).is_none_or(|prefs| prefs.fs_default_deny_rules)
 }
 
 /// Simple greeting command for demonstration purposes.
@@ -86,6 +188,9 @@ pub async fn save_preferences(app: AppHandle, preferences: AppPreferences) -> Re
     // Validate theme value
     validate_theme(&preferences.theme)?;
     validate_color_theme(&preferences.color_theme)?;
+    validate_terminal_command_rules(&preferences.terminal_command_rules)?;
+    validate_fs_path_rules(&preferences.fs_path_rules)?;
+    validate_adapter_keepalive(preferences.adapter_keepalive_interval_ms)?;
 
     log::debug!("Saving preferences to disk: {preferences:?}");
     let prefs_path = get_preferences_path(&app)?;
@@ -112,6 +217,27 @@ pub async fn save_preferences(app: AppHandle, preferences: AppPreferences) -> Re
         return Err(format!("Failed to finalize preferences file: {rename_err}"));
     }
 
+    // Apply the audit file preference right away
+    if let Some(permission_hub) = app.try_state::<Arc<PermissionHub>>() {
+        permission_hub.set_audit_file_logging(preferences.permission_audit_log);
+        permission_hub.set_command_rules(preferences.terminal_command_rules.clone());
+        permission_hub.set_path_rules(preferences.fs_path_rules.clone());
+        permission_hub.set_default_path_denies(preferences.fs_default_deny_rules);
+    }
+    if let Some(notifier) = app.try_state::<Arc<Notifier>>() {
+        notifier.set_enabled(preferences.notifications_enabled);
+    }
+    if let Some(transcripts) = app.try_state::<Arc<SessionTranscripts>>() {
+        transcripts.set_enabled(preferences.session_transcripts);
+    }
+    env_file::set_env_file_enabled(preferences.workspace_env_file);
+    if let Some(coalescing) = app.try_state::<Arc<ChunkCoalescing>>() {
+        coalescing.set_window(Some(coalesce_window(preferences.message_chunk_coalesce_ms)));
+    }
+    if let Some(keepalive) = app.try_state::<Arc<AdapterKeepalive>>() {
+        keepalive.set(adapter_keepalive(&preferences));
+    }
+
     log::info!("Successfully saved preferences to {prefs_path:?}");
     Ok(())
 }
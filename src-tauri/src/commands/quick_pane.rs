@@ -2,10 +2,16 @@
 //!
 //! The quick pane is a floating panel (NSPanel on macOS, standard window elsewhere)
 //! that provides quick entry functionality accessible via global shortcut.
+//! Prompts typed there go to a dedicated agent in the focused workspace.
 
-use std::sync::Mutex;
-use tauri::{AppHandle, Manager, WebviewUrl};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager, State, WebviewUrl};
 
+use crate::api::types::{AgentId, ApiError, SendPromptAck, WorkspaceId};
+use crate::commands::chat::chat_send_prompt_inner;
+use crate::commands::preferences::load_quick_pane_plugin_id;
+use crate::plugins::manager::PluginManager;
+use crate::runtime::workspace_manager::WorkspaceManager;
 use crate::types::DEFAULT_QUICK_PANE_SHORTCUT;
 
 // ============================================================================
@@ -15,6 +21,9 @@ use crate::types::DEFAULT_QUICK_PANE_SHORTCUT;
 /// Window label for the quick pane
 const QUICK_PANE_LABEL: &str = "quick-pane";
 
+/// Display name of the agent that answers quick pane prompts
+const QUICK_PANE_AGENT_NAME: &str = "Quick Pane";
+
 /// Quick pane window dimensions
 const QUICK_PANE_WIDTH: f64 = 500.0;
 const QUICK_PANE_HEIGHT: f64 = 72.0;
@@ -401,3 +410,146 @@ pub fn update_quick_pane_shortcut(app: AppHandle, shortcut: Option<String>) -> R
 
     Ok(())
 }
+
+// ============================================================================
+// Quick Prompts
+// ============================================================================
+
+/// Find the quick pane agent of the focused workspace, creating it on first use.
+///
+/// The quick pane agent is the one named [`QUICK_PANE_AGENT_NAME`] running
+/// `plugin_id`, so changing the preferred plugin gets a fresh agent.
+///
+/// # Errors
+/// * `ApiError::NoFocusedWorkspace` - If no workspace is focused
+async fn quick_pane_agent(
+    workspace_manager: &WorkspaceManager,
+    plugin_id: String,
+) -> Result<(WorkspaceId, AgentId), ApiError> {
+    let workspace_id = workspace_manager
+        .get_focus()
+        .await
+        .ok_or(ApiError::NoFocusedWorkspace)?;
+
+    let existing = workspace_manager
+        .list_agents(workspace_id.clone(), None, None)
+        .await?
+        .items
+        .into_iter()
+        .find(|agent| {
+            agent.plugin_id == plugin_id
+                && agent.display_name.as_deref() == Some(QUICK_PANE_AGENT_NAME)
+        });
+    let agent = match existing {
+        Some(agent) => agent,
+        None => {
+            log::info!("Creating quick pane agent: workspace={workspace_id}, plugin={plugin_id}");
+            workspace_manager
+                .create_agent(
+                    workspace_id.clone(),
+                    plugin_id,
+                    Some(QUICK_PANE_AGENT_NAME.to_string()),
+                )
+                .await?
+        }
+    };
+
+    Ok((workspace_id, agent.agent_id))
+}
+
+/// Sends a one-off prompt from the quick pane to the focused workspace.
+///
+/// The prompt goes to the workspace's quick pane agent (created on first use)
+/// using the plugin chosen in preferences, and otherwise behaves like
+/// `chat_send_prompt`.
+///
+/// # Errors
+/// * `ApiError::NoFocusedWorkspace` - If no workspace is focused
+/// * Any error of `chat_send_prompt`
+#[tauri::command]
+#[specta::specta]
+pub async fn quick_pane_send(
+    app: AppHandle,
+    workspace_manager: State<'_, Arc<WorkspaceManager>>,
+    plugin_manager: State<'_, Arc<PluginManager>>,
+    prompt: String,
+) -> Result<SendPromptAck, ApiError> {
+    let plugin_id = load_quick_pane_plugin_id(&app);
+    let (workspace_id, agent_id) = quick_pane_agent(&workspace_manager, plugin_id).await?;
+    log::info!("quick_pane_send: workspace={workspace_id}, agent={agent_id}");
+
+    chat_send_prompt_inner(
+        app,
+        &workspace_manager,
+        plugin_manager.inner().clone(),
+        workspace_id,
+        agent_id,
+        None,
+        prompt,
+        None,
+        None,
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_quick_pane_agent_requires_focus() {
+        let workspace_manager = WorkspaceManager::new();
+        workspace_manager
+            .create_workspace(std::env::temp_dir().to_str().unwrap())
+            .await
+            .unwrap();
+
+        let result = quick_pane_agent(&workspace_manager, "claude-code".to_string()).await;
+
+        assert!(matches!(result, Err(ApiError::NoFocusedWorkspace)));
+    }
+
+    #[tokio::test]
+    async fn test_quick_pane_agent_is_created_once_per_plugin() {
+        let workspace_manager = WorkspaceManager::new();
+        let workspace = workspace_manager
+            .create_workspace(std::env::temp_dir().to_str().unwrap())
+            .await
+            .unwrap();
+        let other = workspace_manager
+            .create_agent(
+                workspace.workspace_id.clone(),
+                "claude-code".to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+        workspace_manager
+            .set_focus(workspace.workspace_id.clone())
+            .await
+            .unwrap();
+
+        let (workspace_id, agent_id) =
+            quick_pane_agent(&workspace_manager, "claude-code".to_string())
+                .await
+                .unwrap();
+        assert_eq!(workspace_id, workspace.workspace_id);
+        assert_ne!(agent_id, other.agent_id);
+
+        let (_, again) = quick_pane_agent(&workspace_manager, "claude-code".to_string())
+            .await
+            .unwrap();
+        assert_eq!(again, agent_id);
+
+        let (_, codex) = quick_pane_agent(&workspace_manager, "codex".to_string())
+            .await
+            .unwrap();
+        assert_ne!(codex, agent_id);
+
+        let agents = workspace_manager
+            .list_agents(workspace.workspace_id, None, None)
+            .await
+            .unwrap();
+        assert_eq!(agents.total, 3);
+    }
+}
@@ -1,14 +1,18 @@
 //! Emergency data recovery commands.
 //!
 //! Provides a simple pattern for saving JSON data to disk for crash recovery
-//! or session persistence.
+//! or session persistence, plus access to the adapter processes this app has
+//! spawned so stragglers can be killed.
 
 use serde_json::Value;
 use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 use tauri::{AppHandle, Manager};
 
+use crate::api::types::AdapterProcessInfo;
 use crate::types::{validate_filename, RecoveryError, MAX_RECOVERY_DATA_BYTES};
+use crate::utils::adapter_pids::AdapterPids;
 
 /// Gets the path to the recovery directory, creating it if necessary.
 fn get_recovery_dir(app: &AppHandle) -> Result<PathBuf, String> {
@@ -204,3 +208,34 @@ pub async fn cleanup_old_recovery_files(app: AppHandle) -> Result<u32, RecoveryE
     log::info!("Cleanup complete. Removed {removed_count} old recovery files");
     Ok(removed_count)
 }
+
+/// Lists the adapter processes this app has spawned and not yet released.
+#[tauri::command]
+#[specta::specta]
+pub async fn recovery_list_adapter_processes(
+    app: AppHandle,
+) -> Result<Vec<AdapterProcessInfo>, RecoveryError> {
+    Ok(app.state::<Arc<AdapterPids>>().list())
+}
+
+/// Kills an adapter process spawned by this app.
+/// Refuses pids this app did not create.
+#[tauri::command]
+#[specta::specta]
+pub async fn recovery_kill_adapter_process(app: AppHandle, pid: u32) -> Result<(), RecoveryError> {
+    log::info!("Killing adapter process: pid={pid}");
+
+    let adapter_pids = app.state::<Arc<AdapterPids>>().inner().clone();
+    if !adapter_pids.contains(pid) {
+        return Err(RecoveryError::ValidationError {
+            message: format!("Process {pid} is not an adapter started by this app"),
+        });
+    }
+
+    tauri::async_runtime::spawn_blocking(move || adapter_pids.kill(pid))
+        .await
+        .map_err(|e| RecoveryError::IoError {
+            message: e.to_string(),
+        })?
+        .map_err(|message| RecoveryError::IoError { message })
+}
@@ -1,12 +1,13 @@
 //! Terminal-related commands.
 //!
 //! US-09: Allow the user to stop a running terminal command.
+//! The active terminals of a workspace can be listed and stopped together.
 
 use std::sync::Arc;
 
 use tauri::State;
 
-use crate::api::types::{ApiError, TerminalId, WorkspaceId};
+use crate::api::types::{ApiError, TerminalId, TerminalInfo, WorkspaceId};
 use crate::runtime::workspace_manager::WorkspaceManager;
 
 /// Stop a running terminal command by ID.
@@ -37,3 +38,56 @@ pub async fn terminal_kill(
     let terminal_manager = workspace.terminal_manager();
     terminal_manager.kill(terminal_id).await
 }
+
+/// List the terminal commands still running in a workspace.
+///
+/// # Arguments
+/// * `workspace_id` - ID of the workspace to list
+///
+/// # Returns
+/// * `Ok(Vec<TerminalInfo>)` - Running terminals, oldest first
+/// * `Err(ApiError)` - If workspace is missing or inputs are invalid
+#[tauri::command]
+#[specta::specta]
+pub async fn terminal_list(
+    workspace_manager: State<'_, Arc<WorkspaceManager>>,
+    workspace_id: WorkspaceId,
+) -> Result<Vec<TerminalInfo>, ApiError> {
+    log::debug!("terminal_list: workspace={workspace_id}");
+
+    if workspace_id.trim().is_empty() {
+        return Err(ApiError::InvalidInput {
+            message: "Workspace ID cannot be empty".to_string(),
+        });
+    }
+
+    let workspace = workspace_manager.get_workspace(&workspace_id).await?;
+    Ok(workspace.terminal_manager().list_active().await)
+}
+
+/// Stop every running terminal command in a workspace.
+///
+/// # Arguments
+/// * `workspace_id` - ID of the workspace whose terminals to stop
+///
+/// # Returns
+/// * `Ok(())` - Stop requests sent (idempotent)
+/// * `Err(ApiError)` - If workspace is missing or inputs are invalid
+#[tauri::command]
+#[specta::specta]
+pub async fn terminal_kill_all(
+    workspace_manager: State<'_, Arc<WorkspaceManager>>,
+    workspace_id: WorkspaceId,
+) -> Result<(), ApiError> {
+    log::info!("terminal_kill_all: workspace={workspace_id}");
+
+    if workspace_id.trim().is_empty() {
+        return Err(ApiError::InvalidInput {
+            message: "Workspace ID cannot be empty".to_string(),
+        });
+    }
+
+    let workspace = workspace_manager.get_workspace(&workspace_id).await?;
+    workspace.terminal_manager().kill_all().await;
+    Ok(())
+}
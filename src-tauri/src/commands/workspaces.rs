@@ -6,7 +6,9 @@ use std::sync::Arc;
 
 use tauri::State;
 
-use crate::api::types::{ApiError, WorkspaceId, WorkspaceSummary};
+use crate::api::types::{
+    ApiError, EnvPreview, Page, TerminalShell, WorkspaceId, WorkspaceLimits, WorkspaceSummary,
+};
 use crate::runtime::workspace_manager::WorkspaceManager;
 
 async fn workspace_create_inner(
@@ -52,21 +54,30 @@ pub async fn workspace_create(
 
 async fn workspace_list_inner(
     workspace_manager: &WorkspaceManager,
-) -> Result<Vec<WorkspaceSummary>, ApiError> {
-    log::debug!("workspace_list called");
-    Ok(workspace_manager.list_workspaces().await)
+    offset: Option<u32>,
+    limit: Option<u32>,
+) -> Result<Page<WorkspaceSummary>, ApiError> {
+    log::debug!("workspace_list called: offset={offset:?}, limit={limit:?}");
+    Ok(workspace_manager.list_workspaces(offset, limit).await)
 }
 
-/// Lists all workspaces.
+/// Lists workspaces, one page at a time.
+///
+/// # Arguments
+/// * `offset` - Workspaces to skip (default 0)
+/// * `limit` - Most workspaces to return (default: all)
 ///
 /// # Returns
-/// * `Vec<WorkspaceSummary>` - List of all workspace summaries, sorted by creation time (newest first)
+/// * `Page<WorkspaceSummary>` - The requested workspace summaries, sorted by
+///   creation time (newest first), and the total number of workspaces
 #[tauri::command]
 #[specta::specta]
 pub async fn workspace_list(
     workspace_manager: State<'_, Arc<WorkspaceManager>>,
-) -> Result<Vec<WorkspaceSummary>, ApiError> {
-    workspace_list_inner(&workspace_manager).await
+    offset: Option<u32>,
+    limit: Option<u32>,
+) -> Result<Page<WorkspaceSummary>, ApiError> {
+    workspace_list_inner(&workspace_manager, offset, limit).await
 }
 
 // --- Delete command ---
@@ -99,6 +110,154 @@ pub async fn workspace_delete(
     workspace_delete_inner(&workspace_manager, workspace_id).await
 }
 
+// --- Rename command ---
+
+async fn workspace_rename_inner(
+    workspace_manager: &WorkspaceManager,
+    workspace_id: WorkspaceId,
+    name: String,
+) -> Result<WorkspaceSummary, ApiError> {
+    log::info!("workspace_rename called with workspace_id: {workspace_id}");
+    workspace_manager
+        .rename_workspace(&workspace_id, &name)
+        .await
+}
+
+/// Renames a workspace.
+///
+/// The name is shown instead of the full root path and survives restarts.
+///
+/// # Arguments
+/// * `workspace_id` - ID of the workspace to rename
+/// * `name` - New display name (trimmed)
+///
+/// # Returns
+/// * `WorkspaceSummary` - The workspace with its new name
+///
+/// # Errors
+/// * `ApiError::InvalidInput` - If the name is empty after trimming
+/// * `ApiError::WorkspaceNotFound` - If the workspace does not exist
+#[tauri::command]
+#[specta::specta]
+pub async fn workspace_rename(
+    workspace_manager: State<'_, Arc<WorkspaceManager>>,
+    workspace_id: WorkspaceId,
+    name: String,
+) -> Result<WorkspaceSummary, ApiError> {
+    workspace_rename_inner(&workspace_manager, workspace_id, name).await
+}
+
+// --- Limits command ---
+
+async fn workspace_set_limits_inner(
+    workspace_manager: &WorkspaceManager,
+    workspace_id: WorkspaceId,
+    limits: WorkspaceLimits,
+) -> Result<WorkspaceLimits, ApiError> {
+    log::info!("workspace_set_limits called with workspace_id: {workspace_id}");
+    workspace_manager
+        .set_workspace_limits(&workspace_id, limits)
+        .await
+}
+
+/// Sets how large a file agents may read, how much command output they get back
+/// and how many commands may run at once.
+///
+/// Limits have fixed upper bounds (64 MiB reads, 16 MiB output per stream,
+/// 256 concurrent terminal commands) so a workspace can't be configured to
+/// exhaust memory or processes.
+///
+/// # Arguments
+/// * `workspace_id` - ID of the workspace to configure
+/// * `limits` - New `maxReadBytes`, `outputCaptureLimit` and `maxTerminals`
+///
+/// # Returns
+/// * `WorkspaceLimits` - The limits now in effect
+///
+/// # Errors
+/// * `ApiError::InvalidInput` - If a limit is zero or above its upper bound
+/// * `ApiError::WorkspaceNotFound` - If the workspace does not exist
+#[tauri::command]
+#[specta::specta]
+pub async fn workspace_set_limits(
+    workspace_manager: State<'_, Arc<WorkspaceManager>>,
+    workspace_id: WorkspaceId,
+    limits: WorkspaceLimits,
+) -> Result<WorkspaceLimits, ApiError> {
+    workspace_set_limits_inner(&workspace_manager, workspace_id, limits).await
+}
+
+// --- Shell command ---
+
+/// Sets the shell terminal commands in a workspace run under.
+///
+/// A shell that can't be found when a command starts is skipped (with a
+/// logged warning) in favor of the default `sh -c` / `cmd /C`.
+///
+/// # Arguments
+/// * `workspace_id` - ID of the workspace to configure
+/// * `shell` - Shell program and arguments, or `None` for the default
+///
+/// # Errors
+/// * `ApiError::InvalidInput` - If the shell program is empty
+/// * `ApiError::WorkspaceNotFound` - If the workspace does not exist
+#[tauri::command]
+#[specta::specta]
+pub async fn workspace_set_shell(
+    workspace_manager: State<'_, Arc<WorkspaceManager>>,
+    workspace_id: WorkspaceId,
+    shell: Option<TerminalShell>,
+) -> Result<(), ApiError> {
+    log::info!("workspace_set_shell called with workspace_id: {workspace_id}");
+    workspace_manager
+        .set_workspace_shell(&workspace_id, shell)
+        .await
+}
+
+// --- Watch commands ---
+
+/// Starts emitting `fs/changed` events for files under the workspace root.
+///
+/// Watching is opt-in so idle workspaces don't hold OS watch handles.
+/// Calling it again while already watching is a no-op.
+///
+/// # Arguments
+/// * `workspace_id` - ID of the workspace to watch
+///
+/// # Errors
+/// * `ApiError::WorkspaceNotFound` - If the workspace does not exist
+/// * `ApiError::IoError` - If the OS watcher can't be set up
+#[tauri::command]
+#[specta::specta]
+pub async fn workspace_start_watch(
+    app: tauri::AppHandle,
+    workspace_manager: State<'_, Arc<WorkspaceManager>>,
+    workspace_id: WorkspaceId,
+) -> Result<(), ApiError> {
+    log::info!("workspace_start_watch called with workspace_id: {workspace_id}");
+    let workspace = workspace_manager.get_workspace(&workspace_id).await?;
+    workspace.start_watch(app)
+}
+
+/// Stops emitting `fs/changed` events for the workspace (no-op if not watching).
+///
+/// # Arguments
+/// * `workspace_id` - ID of the workspace to stop watching
+///
+/// # Errors
+/// * `ApiError::WorkspaceNotFound` - If the workspace does not exist
+#[tauri::command]
+#[specta::specta]
+pub async fn workspace_stop_watch(
+    workspace_manager: State<'_, Arc<WorkspaceManager>>,
+    workspace_id: WorkspaceId,
+) -> Result<(), ApiError> {
+    log::info!("workspace_stop_watch called with workspace_id: {workspace_id}");
+    let workspace = workspace_manager.get_workspace(&workspace_id).await?;
+    workspace.stop_watch();
+    Ok(())
+}
+
 // --- Focus commands ---
 
 async fn workspace_set_focus_inner(
@@ -149,6 +308,40 @@ pub async fn workspace_get_focus(
     workspace_get_focus_inner(&workspace_manager).await
 }
 
+// --- Env preview command ---
+
+async fn workspace_preview_env_inner(
+    workspace_manager: &WorkspaceManager,
+    workspace_id: WorkspaceId,
+) -> Result<EnvPreview, ApiError> {
+    log::debug!("workspace_preview_env called with workspace_id: {workspace_id}");
+    let workspace = workspace_manager.get_workspace(&workspace_id).await?;
+    workspace.preview_env().await
+}
+
+/// Previews the variables a workspace `.env` file would pass to adapters.
+///
+/// Parses `<root>/.env` without injecting anything. Values are redacted:
+/// only variable names and parse warnings are returned.
+///
+/// # Arguments
+/// * `workspace_id` - ID of the workspace whose `.env` to preview
+///
+/// # Returns
+/// * `EnvPreview` - Whether the file exists, its variable names, and any warnings
+///
+/// # Errors
+/// * `ApiError::WorkspaceNotFound` - If the workspace does not exist
+/// * `ApiError::IoError` - If the file exists but cannot be read
+#[tauri::command]
+#[specta::specta]
+pub async fn workspace_preview_env(
+    workspace_manager: State<'_, Arc<WorkspaceManager>>,
+    workspace_id: WorkspaceId,
+) -> Result<EnvPreview, ApiError> {
+    workspace_preview_env_inner(&workspace_manager, workspace_id).await
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -206,9 +399,9 @@ mod tests {
     #[tokio::test]
     async fn test_workspace_list_empty() {
         let workspace_manager = WorkspaceManager::new();
-        let result = workspace_list_inner(&workspace_manager).await;
+        let result = workspace_list_inner(&workspace_manager, None, None).await;
         assert!(result.is_ok());
-        assert!(result.unwrap().is_empty());
+        assert!(result.unwrap().items.is_empty());
     }
 
     #[tokio::test]
@@ -221,9 +414,16 @@ mod tests {
             .await
             .unwrap();
 
-        let result = workspace_list_inner(&workspace_manager).await;
+        let result = workspace_list_inner(&workspace_manager, None, None).await;
         assert!(result.is_ok());
-        assert_eq!(result.unwrap().len(), 1);
+        assert_eq!(result.unwrap().items.len(), 1);
+
+        // Past the end
+        let page = workspace_list_inner(&workspace_manager, Some(1), Some(10))
+            .await
+            .unwrap();
+        assert!(page.items.is_empty());
+        assert_eq!(page.total, 1);
     }
 
     #[tokio::test]
@@ -242,8 +442,10 @@ mod tests {
         assert!(result.is_ok());
 
         // Verify it's gone
-        let list = workspace_list_inner(&workspace_manager).await.unwrap();
-        assert!(list.is_empty());
+        let list = workspace_list_inner(&workspace_manager, None, None)
+            .await
+            .unwrap();
+        assert!(list.items.is_empty());
     }
 
     #[tokio::test]
@@ -252,4 +454,138 @@ mod tests {
         let result = workspace_delete_inner(&workspace_manager, "unknown-id".to_string()).await;
         assert!(matches!(result, Err(ApiError::WorkspaceNotFound { .. })));
     }
+
+    #[tokio::test]
+    async fn test_workspace_rename() {
+        let workspace_manager = WorkspaceManager::new();
+        let root = std::env::temp_dir().join(format!("ws_rename_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+
+        let summary =
+            workspace_create_inner(&workspace_manager, root.to_str().unwrap().to_string())
+                .await
+                .unwrap();
+        // Defaults to the directory name
+        let dir_name = root.file_name().unwrap().to_str().unwrap();
+        assert_eq!(summary.name.as_deref(), Some(dir_name));
+
+        let blank = workspace_rename_inner(
+            &workspace_manager,
+            summary.workspace_id.clone(),
+            "  ".into(),
+        )
+        .await;
+        assert!(matches!(blank, Err(ApiError::InvalidInput { .. })));
+
+        let renamed = workspace_rename_inner(
+            &workspace_manager,
+            summary.workspace_id.clone(),
+            "  My Project ".to_string(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(renamed.name.as_deref(), Some("My Project"));
+        let list = workspace_list_inner(&workspace_manager, None, None)
+            .await
+            .unwrap();
+        assert_eq!(list.items[0].name.as_deref(), Some("My Project"));
+
+        let unknown =
+            workspace_rename_inner(&workspace_manager, "unknown-id".to_string(), "x".into()).await;
+        assert!(matches!(unknown, Err(ApiError::WorkspaceNotFound { .. })));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[tokio::test]
+    async fn test_workspace_set_limits() {
+        let workspace_manager = WorkspaceManager::new();
+        let temp_dir = std::env::temp_dir();
+        let summary =
+            workspace_create_inner(&workspace_manager, temp_dir.to_str().unwrap().to_string())
+                .await
+                .unwrap();
+
+        let limits = WorkspaceLimits {
+            max_read_bytes: 8 * 1024 * 1024,
+            output_capture_limit: 256 * 1024,
+            max_terminals: 4,
+            max_outstanding_prompts: 2,
+        };
+        let applied =
+            workspace_set_limits_inner(&workspace_manager, summary.workspace_id.clone(), limits)
+                .await
+                .unwrap();
+        assert_eq!(applied, limits);
+
+        // Above the safety ceiling: rejected, previous limits kept
+        let unbounded = WorkspaceLimits {
+            max_read_bytes: u32::MAX,
+            ..limits
+        };
+        let result =
+            workspace_set_limits_inner(&workspace_manager, summary.workspace_id.clone(), unbounded)
+                .await;
+        assert!(matches!(result, Err(ApiError::InvalidInput { .. })));
+        let workspace = workspace_manager
+            .get_workspace(&summary.workspace_id)
+            .await
+            .unwrap();
+        assert_eq!(workspace.limits(), limits);
+        assert_eq!(
+            workspace.terminal_manager().output_capture_limit(),
+            256 * 1024
+        );
+        assert_eq!(workspace.terminal_manager().max_terminals(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_workspace_preview_env_reports_keys_and_warnings() {
+        let workspace_manager = WorkspaceManager::new();
+        let root = std::env::temp_dir().join(format!("ws_env_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(
+            root.join(".env"),
+            "# comment\n\nAPI_KEY=secret-value\nthis line is malformed\nDEBUG=1\n",
+        )
+        .unwrap();
+
+        let summary =
+            workspace_create_inner(&workspace_manager, root.to_str().unwrap().to_string())
+                .await
+                .unwrap();
+
+        let preview = workspace_preview_env_inner(&workspace_manager, summary.workspace_id)
+            .await
+            .unwrap();
+
+        assert!(preview.exists);
+        assert_eq!(preview.keys, vec!["API_KEY", "DEBUG"]);
+        assert_eq!(preview.warnings.len(), 1);
+        assert_eq!(preview.warnings[0].line, 4);
+        // Values are never exposed
+        assert!(!format!("{preview:?}").contains("secret-value"));
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_workspace_preview_env_missing_file() {
+        let workspace_manager = WorkspaceManager::new();
+        let root = std::env::temp_dir().join(format!("ws_env_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+
+        let summary =
+            workspace_create_inner(&workspace_manager, root.to_str().unwrap().to_string())
+                .await
+                .unwrap();
+
+        let preview = workspace_preview_env_inner(&workspace_manager, summary.workspace_id)
+            .await
+            .unwrap();
+
+        assert!(!preview.exists);
+        assert!(preview.keys.is_empty());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
 }
@@ -106,18 +106,89 @@ pub fn run() {
                 app.package_info().name
             );
 
-            // Initialize workspace manager for ACP support (wrapped in Arc for background task support)
-            app.manage(Arc::new(runtime::workspace_manager::WorkspaceManager::new()));
+            // Initialize workspace manager for ACP support (wrapped in Arc for background task support),
+            // restoring the workspaces saved by the previous session
+            let workspace_file = app.path().app_config_dir()?.join("workspaces.json");
+            let workspace_manager =
+                Arc::new(runtime::workspace_manager::WorkspaceManager::with_store(
+                    runtime::workspace_store::WorkspaceStore::new(workspace_file),
+                ));
+            if let Err(e) = tauri::async_runtime::block_on(workspace_manager.load_from_disk()) {
+                log::error!("Failed to load saved workspaces: {e}");
+            }
+            app.manage(workspace_manager);
+
+            // Cap how many adapter processes run at once across all workspaces
+            app.manage(Arc::new(runtime::agent_slots::AgentSlots::new(
+                runtime::agent_slots::DEFAULT_MAX_RUNNING_AGENTS,
+            )));
+
+            // Track spawned adapter pids, first killing any a crashed previous
+            // run left behind
+            let adapter_pids = Arc::new(utils::adapter_pids::AdapterPids::with_file(
+                app.path().app_data_dir()?.join("adapter_pids.json"),
+            ));
+            let swept = adapter_pids.sweep_previous_run();
+            if !swept.is_empty() {
+                log::warn!("Killed {} leftover adapter processes", swept.len());
+            }
+            app.manage(adapter_pids);
+
+            // Adapter keepalive pings, off unless the user configured them
+            app.manage(Arc::new(runtime::agents::AdapterKeepalive::new(
+                commands::preferences::load_adapter_keepalive(app.handle()),
+            )));
+
+            // User-facing alerts, honoring the saved notifications preference
+            app.manage(Arc::new(runtime::notifier::Notifier::new(
+                app.handle().clone(),
+                commands::preferences::load_notifications_enabled(app.handle()),
+            )));
+
+            // Session transcripts, written only if the user opted in
+            let transcripts = runtime::transcripts::SessionTranscripts::new(
+                app.path().app_data_dir()?.join("transcripts"),
+                runtime::transcripts::DEFAULT_MAX_TRANSCRIPT_BYTES,
+            );
+            transcripts.set_enabled(commands::preferences::load_session_transcripts(
+                app.handle(),
+            ));
+            app.manage(Arc::new(transcripts));
+
+            // Workspace `.env` variables, passed on only if the user opted in
+            runtime::env_file::set_env_file_enabled(
+                commands::preferences::load_workspace_env_file(app.handle()),
+            );
+
+            // Optional merging of rapid message chunks into fewer events
+            app.manage(Arc::new(runtime::session_updates::ChunkCoalescing::new(
+                commands::preferences::load_message_chunk_coalesce_window(app.handle()),
+            )));
 
             // Initialize plugin manager for plugin installation status (wrapped in Arc for sharing)
             let plugin_manager =
                 Arc::new(plugins::manager::PluginManager::new(app.handle().clone()));
             app.manage(plugin_manager.clone());
 
-            // Initialize permission hub for permission request/response flow
-            let permission_hub = Arc::new(runtime::permissions::PermissionHub::new(
-                app.handle().clone(),
+            // Initialize permission hub for permission request/response flow,
+            // restoring the "Allow Always" grants saved by earlier sessions
+            let rules_file = app.path().app_config_dir()?.join("permission_rules.json");
+            let audit_file = app.path().app_data_dir()?.join("permission_audit.jsonl");
+            let permission_hub = Arc::new(
+                runtime::permissions::PermissionHub::new(app.handle().clone())
+                    .with_rule_store(rules_file)
+                    .with_audit_file(audit_file),
+            );
+            permission_hub.set_audit_file_logging(
+                commands::preferences::load_permission_audit_log(app.handle()),
+            );
+            permission_hub.set_command_rules(commands::preferences::load_terminal_command_rules(
+                app.handle(),
             ));
+            permission_hub.set_path_rules(commands::preferences::load_fs_path_rules(app.handle()));
+            permission_hub.set_default_path_denies(
+                commands::preferences::load_fs_default_deny_rules(app.handle()),
+            );
             app.manage(permission_hub.clone());
 
             // Initialize plugin installer (needs both permission_hub and plugin_manager)
@@ -162,6 +233,28 @@ pub fn run() {
             Ok(())
         })
         .invoke_handler(builder.invoke_handler())
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::ExitRequested { .. } = event {
+                shutdown_workspaces(app);
+            }
+        });
+}
+
+/// Stop agents and terminals before exiting, waiting at most
+/// `APP_SHUTDOWN_TIMEOUT`.
+fn shutdown_workspaces(app: &tauri::AppHandle) {
+    use runtime::workspace_manager::{WorkspaceManager, APP_SHUTDOWN_TIMEOUT};
+
+    let Some(workspace_manager) = app.try_state::<Arc<WorkspaceManager>>() else {
+        return;
+    };
+    let workspace_manager = workspace_manager.inner().clone();
+    let finished = tauri::async_runtime::block_on(async move {
+        tokio::time::timeout(APP_SHUTDOWN_TIMEOUT, workspace_manager.shutdown_all()).await
+    });
+    if finished.is_err() {
+        log::warn!("Shutdown did not finish within {APP_SHUTDOWN_TIMEOUT:?}, exiting anyway");
+    }
 }
@@ -0,0 +1,93 @@
+//! Plugin cache inspection and cleanup.
+//!
+//! Everything under `app_cache_dir()/plugins/` can be rebuilt by reinstalling,
+//! so the "clear cache" action removes each plugin directory wholesale
+//! (including stray `install.json.tmp` files from interrupted installs).
+//!
+//! These are blocking helpers; callers run them through a
+//! [`BlockingFsLimiter`](crate::utils::blocking::BlockingFsLimiter).
+
+use std::path::Path;
+
+use crate::api::types::ApiError;
+
+/// Total size in bytes of the files under `path`.
+///
+/// Symlinks are counted as links, not followed. Unreadable entries are skipped.
+pub fn dir_size(path: &Path) -> u64 {
+    let Ok(metadata) = std::fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return 0;
+    };
+    entries.flatten().map(|entry| dir_size(&entry.path())).sum()
+}
+
+/// Plugin IDs with a directory in the plugins root, sorted.
+pub fn cached_plugin_ids(plugins_root: &Path) -> Result<Vec<String>, ApiError> {
+    let entries = match std::fs::read_dir(plugins_root) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            return Err(ApiError::IoError {
+                message: format!("Failed to read plugins directory {plugins_root:?}: {e}"),
+            });
+        }
+    };
+
+    let mut plugin_ids: Vec<String> = entries
+        .flatten()
+        .filter(|entry| entry.file_type().map(|t| t.is_dir()).unwrap_or(false))
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect();
+    plugin_ids.sort();
+    Ok(plugin_ids)
+}
+
+/// Remove the given plugin directories, returning the number of bytes freed.
+pub fn remove_plugins(plugins_root: &Path, plugin_ids: &[String]) -> Result<u64, ApiError> {
+    let mut freed = 0;
+    for plugin_id in plugin_ids {
+        let plugin_dir = plugins_root.join(plugin_id);
+        let size = dir_size(&plugin_dir);
+        match std::fs::remove_dir_all(&plugin_dir) {
+            Ok(()) => freed += size,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => {
+                return Err(ApiError::IoError {
+                    message: format!("Failed to remove {plugin_dir:?}: {e}"),
+                });
+            }
+        }
+        log::info!("Removed cached plugin: plugin_id={plugin_id}, bytes={size}");
+    }
+    Ok(freed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remove_plugins_reports_freed_bytes() {
+        let root = std::env::temp_dir().join(format!("plugins_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("codex/node_modules")).unwrap();
+        std::fs::write(root.join("codex/install.json"), "{}").unwrap();
+        std::fs::write(root.join("codex/node_modules/index.js"), "1234").unwrap();
+        std::fs::write(root.join("stray-file"), "x").unwrap();
+
+        assert_eq!(cached_plugin_ids(&root).unwrap(), vec!["codex".to_string()]);
+        assert_eq!(dir_size(&root.join("codex")), 6);
+
+        let freed = remove_plugins(&root, &["codex".to_string()]).unwrap();
+
+        assert_eq!(freed, 6);
+        assert!(cached_plugin_ids(&root).unwrap().is_empty());
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
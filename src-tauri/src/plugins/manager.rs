@@ -17,14 +17,29 @@
 //! ```
 
 use std::path::{Path, PathBuf};
+use std::process::Output;
 use std::sync::OnceLock;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use serde::{Deserialize, Serialize};
 use tauri::Manager;
 use tokio::process::Command;
 
-use crate::api::types::{ApiError, PluginStatus};
+use crate::api::types::{ApiError, PluginStatus, ToolchainInfo};
+use crate::plugins::package_manager::PackageManager;
+use crate::utils::blocking::BlockingFsLimiter;
+
+/// Upper bound for a single `npm install` run (3 minutes).
+///
+/// A stalled registry would otherwise keep the install task (and the plugin's
+/// slot in the installer's in-progress set) alive forever.
+const INSTALL_TIMEOUT: Duration = Duration::from_secs(180);
+
+/// Minimum Node.js version required by the ACP adapters.
+const MIN_NODE_VERSION: &str = "18.0.0";
+
+/// Upper bound for `node --version` / `npm --version` probes.
+const TOOLCHAIN_PROBE_TIMEOUT: Duration = Duration::from_secs(10);
 
 /// Plugin descriptor containing npm package information.
 ///
@@ -93,6 +108,9 @@ pub struct PluginInstallMetadata {
     /// Installation timestamp in milliseconds since Unix epoch
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub installed_at_ms: Option<f64>,
+    /// Package manager used for the install (reused for updates)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package_manager: Option<PackageManager>,
 }
 
 /// Command specification for launching a plugin adapter.
@@ -116,6 +134,8 @@ pub struct PluginManager {
     app: tauri::AppHandle,
     /// Lazily initialized plugins root directory
     plugins_root: OnceLock<PathBuf>,
+    /// Bounds concurrent blocking filesystem work (metadata reads, cleanup)
+    blocking_fs: BlockingFsLimiter,
 }
 
 impl PluginManager {
@@ -128,6 +148,7 @@ impl PluginManager {
         Self {
             app,
             plugins_root: OnceLock::new(),
+            blocking_fs: BlockingFsLimiter::shared(),
         }
     }
 
@@ -159,6 +180,11 @@ impl PluginManager {
         Ok(self.plugins_root.get().cloned().unwrap_or(plugins_root))
     }
 
+    /// The plugins cache directory (`app_cache_dir()/plugins/`), created if needed.
+    pub fn plugins_root(&self) -> Result<PathBuf, ApiError> {
+        self.plugins_root_dir()
+    }
+
     /// Validate a plugin ID to prevent path traversal attacks.
     ///
     /// Valid plugin IDs:
@@ -210,6 +236,95 @@ impl PluginManager {
         Ok(())
     }
 
+    /// Check that `node` and `npm` are available before starting an install.
+    ///
+    /// Runs `node --version` and `npm --version` and reports the detected
+    /// versions, so the UI can disable installation with a helpful message
+    /// instead of failing after the permission prompt.
+    ///
+    /// # Returns
+    ///
+    /// `ToolchainInfo` describing what was found. Missing tools are reported
+    /// through `ready`/`message` rather than as an error.
+    pub async fn check_toolchain(&self) -> Result<ToolchainInfo, ApiError> {
+        let node_version = Self::probe_version("node").await;
+        let npm_version = Self::probe_version(PackageManager::Npm.program()).await;
+
+        log::debug!("Toolchain check: node={node_version:?}, npm={npm_version:?}");
+
+        Ok(Self::evaluate_toolchain(node_version, npm_version))
+    }
+
+    /// Run `<program> --version` and return the trimmed version string.
+    ///
+    /// Returns `None` if the program is missing, fails, or times out.
+    async fn probe_version(program: &str) -> Option<String> {
+        let mut command = Command::new(program);
+        command.arg("--version");
+
+        let output = Self::output_with_timeout(&mut command, TOOLCHAIN_PROBE_TIMEOUT)
+            .await?
+            .ok()?;
+
+        if !output.status.success() {
+            return None;
+        }
+
+        let version = String::from_utf8_lossy(&output.stdout)
+            .trim()
+            .trim_start_matches('v')
+            .to_string();
+
+        (!version.is_empty()).then_some(version)
+    }
+
+    /// Build a `ToolchainInfo` from the probed versions.
+    fn evaluate_toolchain(
+        node_version: Option<String>,
+        npm_version: Option<String>,
+    ) -> ToolchainInfo {
+        let message = match (&node_version, &npm_version) {
+            (None, _) => Some(format!(
+                "Node.js is not installed or not in PATH. Install Node.js {MIN_NODE_VERSION} or newer."
+            )),
+            (Some(node), _) if !Self::version_at_least(node, MIN_NODE_VERSION) => Some(format!(
+                "Node.js {node} is too old. Install Node.js {MIN_NODE_VERSION} or newer."
+            )),
+            (Some(_), None) => {
+                Some("npm is not installed or not in PATH. Install npm first.".to_string())
+            }
+            (Some(_), Some(_)) => None,
+        };
+
+        ToolchainInfo {
+            node_version,
+            npm_version,
+            min_node_version: MIN_NODE_VERSION.to_string(),
+            ready: message.is_none(),
+            message,
+        }
+    }
+
+    /// Compare dotted numeric versions (missing components count as 0).
+    ///
+    /// Returns false if `version` cannot be parsed.
+    fn version_at_least(version: &str, minimum: &str) -> bool {
+        fn parse(version: &str) -> Option<Vec<u64>> {
+            version
+                .split(['.', '-', '+'])
+                .take(3)
+                .map(|part| part.parse::<u64>().ok())
+                .collect()
+        }
+
+        let (Some(mut actual), Some(mut required)) = (parse(version), parse(minimum)) else {
+            return false;
+        };
+        actual.resize(3, 0);
+        required.resize(3, 0);
+        actual >= required
+    }
+
     /// Get the status of a plugin.
     ///
     /// # Arguments
@@ -241,12 +356,13 @@ impl PluginManager {
         let (installed_version, bin_path) = if installed {
             let metadata_path = plugin_dir.join("install.json");
             if metadata_path.exists() {
-                // Read metadata file - use spawn_blocking to avoid blocking async runtime
+                // Read metadata file on the (bounded) blocking pool to avoid blocking async runtime
                 let metadata_path_clone = metadata_path.clone();
                 let plugin_id_clone = plugin_id.clone();
-                let metadata: Option<PluginInstallMetadata> =
-                    tokio::task::spawn_blocking(move || {
-                        match std::fs::read_to_string(&metadata_path_clone) {
+                let metadata: Option<PluginInstallMetadata> = self
+                    .blocking_fs
+                    .run(
+                        move || match std::fs::read_to_string(&metadata_path_clone) {
                             Ok(content) => match serde_json::from_str(&content) {
                                 Ok(metadata) => Some(metadata),
                                 Err(e) => {
@@ -266,8 +382,8 @@ impl PluginManager {
                                 );
                                 None
                             }
-                        }
-                    })
+                        },
+                    )
                     .await
                     .ok()
                     .flatten();
@@ -341,39 +457,98 @@ impl PluginManager {
         Ok(())
     }
 
-    /// Execute npm install in the plugin directory.
+    /// Pick the package manager for an install.
     ///
-    /// Uses `npm.cmd` on Windows, `npm` on other platforms.
-    async fn run_npm_install(plugin_dir: &Path) -> Result<(), ApiError> {
-        // Determine npm executable based on platform
-        #[cfg(windows)]
-        let npm_cmd = "npm.cmd";
-        #[cfg(not(windows))]
-        let npm_cmd = "npm";
-
-        log::info!("Running npm install in {plugin_dir:?}");
-
-        let output = Command::new(npm_cmd)
-            .args([
-                "install",
-                "--no-audit",
-                "--no-fund",
-                "--loglevel=error",
-                "--omit=dev",
-            ])
-            .current_dir(plugin_dir)
-            .output()
+    /// Preference order: the explicit choice, the one recorded in an existing
+    /// `install.json` (so updates use the same tool), then the first of
+    /// npm/pnpm/yarn found in PATH.
+    async fn resolve_package_manager(
+        blocking_fs: &BlockingFsLimiter,
+        plugin_dir: &Path,
+        explicit: Option<PackageManager>,
+    ) -> Result<PackageManager, ApiError> {
+        if let Some(manager) = explicit {
+            return Ok(manager);
+        }
+
+        let plugin_dir_for_metadata = plugin_dir.to_path_buf();
+        let recorded = blocking_fs
+            .run(move || Self::read_install_metadata(&plugin_dir_for_metadata))
             .await
+            .ok()
+            .flatten()
+            .and_then(|metadata| metadata.package_manager);
+        if let Some(manager) = recorded {
+            return Ok(manager);
+        }
+
+        for manager in PackageManager::DETECTION_ORDER {
+            if Self::probe_version(manager.program()).await.is_some() {
+                log::debug!("Detected package manager: {}", manager.name());
+                return Ok(manager);
+            }
+        }
+
+        Err(ApiError::InvalidInput {
+            message: "No supported package manager (npm, pnpm, yarn) found in PATH. Please install Node.js and npm first.".to_string(),
+        })
+    }
+
+    /// Execute the package install in the plugin directory.
+    ///
+    /// The process is killed and an `IoError` returned if it runs longer
+    /// than `INSTALL_TIMEOUT`.
+    async fn run_package_install(
+        manager: PackageManager,
+        plugin_dir: &Path,
+    ) -> Result<(), ApiError> {
+        let name = manager.name();
+        log::info!("Running {name} install in {plugin_dir:?}");
+
+        let mut command = Command::new(manager.program());
+        command
+            .args(manager.install_args())
+            .envs(manager.install_env().iter().copied())
+            .current_dir(plugin_dir);
+
+        Self::run_install_command(name, command, plugin_dir, INSTALL_TIMEOUT).await?;
+
+        log::info!("{name} install completed successfully");
+        Ok(())
+    }
+
+    /// Run an install `command`, mapping a timeout, spawn failure, or failed
+    /// exit to an error that names the package manager.
+    async fn run_install_command(
+        name: &str,
+        mut command: Command,
+        plugin_dir: &Path,
+        install_timeout: Duration,
+    ) -> Result<(), ApiError> {
+        let output = Self::output_with_timeout(&mut command, install_timeout)
+            .await
+            .ok_or_else(|| {
+                log::error!(
+                    "{name} install timed out after {}s in {plugin_dir:?}",
+                    install_timeout.as_secs()
+                );
+                ApiError::IoError {
+                    message: format!(
+                        "{name} install timed out after {}s",
+                        install_timeout.as_secs()
+                    ),
+                }
+            })?
             .map_err(|e| {
                 if e.kind() == std::io::ErrorKind::NotFound {
                     ApiError::InvalidInput {
                         message: format!(
-                            "npm is not installed or not in PATH. Please install Node.js and npm first. Error: {e}"
+                            "{name} is not installed or not in PATH. Please install it first. Error: {e}"
                         ),
                     }
                 } else {
                     ApiError::IoError {
-                        message: format!("Failed to execute npm install: {e}"),
+                        message: format!("Failed to execute {name} install: {e}"),
                     }
                 }
             })?;
@@ -392,18 +567,30 @@ impl PluginManager {
             };
 
             log::error!(
-                "npm install failed with exit code {exit_code}:\nstderr: {stderr}\nstdout: {stdout}"
+                "{name} install failed with exit code {exit_code}:\nstderr: {stderr}\nstdout: {stdout}"
             );
 
             return Err(ApiError::IoError {
-                message: format!("npm install failed (exit code {exit_code}): {stderr_truncated}"),
+                message: format!(
+                    "{name} install failed (exit code {exit_code}): {stderr_truncated}"
+                ),
             });
         }
-
-        log::info!("npm install completed successfully");
         Ok(())
     }
 
+    /// Run a command to completion, returning `None` if it exceeds `limit`.
+    ///
+    /// The child is spawned with `kill_on_drop`, so when the timeout elapses the
+    /// dropped `output()` future kills the process rather than leaving it behind.
+    async fn output_with_timeout(
+        command: &mut Command,
+        limit: Duration,
+    ) -> Option<std::io::Result<Output>> {
+        command.kill_on_drop(true);
+        tokio::time::timeout(limit, command.output()).await.ok()
+    }
+
     /// Read the installed package version from node_modules.
     ///
     /// Parses the package.json in node_modules to get the actual installed version.
@@ -475,6 +662,12 @@ impl PluginManager {
         })
     }
 
+    /// Read `install.json`, returning `None` if it is missing or invalid.
+    fn read_install_metadata(plugin_dir: &Path) -> Option<PluginInstallMetadata> {
+        let content = std::fs::read_to_string(plugin_dir.join("install.json")).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
     /// Write install metadata atomically.
     ///
     /// Writes to a temp file first, then renames to install.json.
@@ -507,13 +700,13 @@ impl PluginManager {
     // Main installation method
     // =========================================================================
 
-    /// Install or upgrade a plugin via npm.
+    /// Install or upgrade a plugin via npm, pnpm, or yarn.
     ///
     /// This method:
     /// 1. Validates the plugin ID and looks up the npm package
     /// 2. Creates the plugin directory
     /// 3. Writes a package.json with the dependency
-    /// 4. Runs `npm install`
+    /// 4. Runs the package manager's install
     /// 5. Resolves the installed version and binary path
     /// 6. Writes install.json with the metadata
     ///
@@ -521,6 +714,7 @@ impl PluginManager {
     ///
     /// * `plugin_id` - Plugin identifier (e.g., "claude-code")
     /// * `version` - Optional version to install (defaults to "latest")
+    /// * `package_manager` - Optional explicit package manager (auto-detected if None)
     ///
     /// # Returns
     ///
@@ -529,6 +723,7 @@ impl PluginManager {
         &self,
         plugin_id: String,
         version: Option<String>,
+        package_manager: Option<PackageManager>,
     ) -> Result<(), ApiError> {
         // 1. Validate plugin ID
         Self::validate_plugin_id(&plugin_id)?;
@@ -547,7 +742,8 @@ impl PluginManager {
 
         // 4. Create plugin directory (blocking operation)
         let plugin_dir_clone = plugin_dir.clone();
-        tokio::task::spawn_blocking(move || std::fs::create_dir_all(&plugin_dir_clone))
+        self.blocking_fs
+            .run(move || std::fs::create_dir_all(&plugin_dir_clone))
             .await
             .map_err(|e| ApiError::IoError {
                 message: format!("Failed to spawn blocking task: {e}"),
@@ -562,42 +758,49 @@ impl PluginManager {
         let desc_npm_package = desc.npm_package.to_string();
         let desc_plugin_id = desc.plugin_id.to_string();
 
-        tokio::task::spawn_blocking(move || {
-            Self::write_plugin_package_json(
-                &plugin_dir_for_package,
-                &desc_plugin_id,
-                &desc_npm_package,
-                version_for_package.as_deref(),
-            )
-        })
-        .await
-        .map_err(|e| ApiError::IoError {
-            message: format!("Failed to spawn blocking task: {e}"),
-        })??;
+        self.blocking_fs
+            .run(move || {
+                Self::write_plugin_package_json(
+                    &plugin_dir_for_package,
+                    &desc_plugin_id,
+                    &desc_npm_package,
+                    version_for_package.as_deref(),
+                )
+            })
+            .await
+            .map_err(|e| ApiError::IoError {
+                message: format!("Failed to spawn blocking task: {e}"),
+            })??;
 
-        // 6. Run npm install (async operation)
-        Self::run_npm_install(&plugin_dir).await?;
+        // 6. Run the install with the chosen package manager (async operation)
+        let package_manager =
+            Self::resolve_package_manager(&self.blocking_fs, &plugin_dir, package_manager).await?;
+        Self::run_package_install(package_manager, &plugin_dir).await?;
 
         // 7. Read installed version and resolve bin path (blocking operations)
         let plugin_dir_for_version = plugin_dir.clone();
         let npm_package_for_version = desc.npm_package.to_string();
-        let installed_version = tokio::task::spawn_blocking(move || {
-            Self::read_installed_npm_version(&plugin_dir_for_version, &npm_package_for_version)
-        })
-        .await
-        .map_err(|e| ApiError::IoError {
-            message: format!("Failed to spawn blocking task: {e}"),
-        })??;
+        let installed_version = self
+            .blocking_fs
+            .run(move || {
+                Self::read_installed_npm_version(&plugin_dir_for_version, &npm_package_for_version)
+            })
+            .await
+            .map_err(|e| ApiError::IoError {
+                message: format!("Failed to spawn blocking task: {e}"),
+            })??;
 
         let plugin_dir_for_bin = plugin_dir.clone();
         let bin_name_for_resolve = desc.bin_name.to_string();
-        let bin_path = tokio::task::spawn_blocking(move || {
-            Self::resolve_npm_bin_relative_path(&plugin_dir_for_bin, &bin_name_for_resolve)
-        })
-        .await
-        .map_err(|e| ApiError::IoError {
-            message: format!("Failed to spawn blocking task: {e}"),
-        })??;
+        let bin_path = self
+            .blocking_fs
+            .run(move || {
+                Self::resolve_npm_bin_relative_path(&plugin_dir_for_bin, &bin_name_for_resolve)
+            })
+            .await
+            .map_err(|e| ApiError::IoError {
+                message: format!("Failed to spawn blocking task: {e}"),
+            })??;
 
         // 8. Get current timestamp
         let installed_at_ms = SystemTime::now()
@@ -612,16 +815,16 @@ impl PluginManager {
             npm_package: Some(desc.npm_package.to_string()),
             bin_name: Some(desc.bin_name.to_string()),
             installed_at_ms,
+            package_manager: Some(package_manager),
         };
 
         let plugin_dir_for_metadata = plugin_dir.clone();
-        tokio::task::spawn_blocking(move || {
-            Self::write_install_metadata(&plugin_dir_for_metadata, &metadata)
-        })
-        .await
-        .map_err(|e| ApiError::IoError {
-            message: format!("Failed to spawn blocking task: {e}"),
-        })??;
+        self.blocking_fs
+            .run(move || Self::write_install_metadata(&plugin_dir_for_metadata, &metadata))
+            .await
+            .map_err(|e| ApiError::IoError {
+                message: format!("Failed to spawn blocking task: {e}"),
+            })??;
 
         log::info!(
             "Plugin installed successfully: plugin_id={plugin_id}, version={installed_version}, bin_path={bin_path}"
@@ -630,6 +833,78 @@ impl PluginManager {
         Ok(())
     }
 
+    /// Repair a plugin by wiping its install artifacts and reinstalling.
+    ///
+    /// Removes `node_modules`, `package.json`, and `install.json` (which an
+    /// interrupted install can leave partial or stale), then runs the full
+    /// `install` flow for the previously recorded version and package manager.
+    ///
+    /// # Arguments
+    ///
+    /// * `plugin_id` - Plugin identifier (e.g., "claude-code")
+    ///
+    /// # Returns
+    ///
+    /// Ok(()) on success, or an error if cleanup or reinstallation fails.
+    pub async fn repair(&self, plugin_id: String) -> Result<(), ApiError> {
+        Self::validate_plugin_id(&plugin_id)?;
+        plugin_descriptor(&plugin_id)?;
+
+        let plugin_dir = self.plugins_root_dir()?.join(&plugin_id);
+
+        let plugin_dir_for_clear = plugin_dir.clone();
+        let previous = self
+            .blocking_fs
+            .run(move || Self::clear_install_artifacts(&plugin_dir_for_clear))
+            .await
+            .map_err(|e| ApiError::IoError {
+                message: format!("Failed to spawn blocking task: {e}"),
+            })??;
+
+        let (version, package_manager) = previous
+            .map(|metadata| (metadata.installed_version, metadata.package_manager))
+            .unwrap_or_default();
+
+        log::info!(
+            "Repairing plugin: plugin_id={plugin_id}, version={version:?}, package_manager={package_manager:?}"
+        );
+
+        self.install(plugin_id, version, package_manager).await
+    }
+
+    /// Remove install artifacts from a plugin directory.
+    ///
+    /// Returns the previous install metadata (if it was readable) so the
+    /// caller can reinstall the same version with the same package manager.
+    fn clear_install_artifacts(
+        plugin_dir: &Path,
+    ) -> Result<Option<PluginInstallMetadata>, ApiError> {
+        let previous = Self::read_install_metadata(plugin_dir);
+
+        let node_modules = plugin_dir.join("node_modules");
+        if node_modules.exists() {
+            std::fs::remove_dir_all(&node_modules).map_err(|e| ApiError::IoError {
+                message: format!("Failed to remove {node_modules:?}: {e}"),
+            })?;
+        }
+
+        for file_name in ["package.json", "install.json", "install.json.tmp"] {
+            let path = plugin_dir.join(file_name);
+            match std::fs::remove_file(&path) {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => {
+                    return Err(ApiError::IoError {
+                        message: format!("Failed to remove {path:?}: {e}"),
+                    });
+                }
+            }
+        }
+
+        log::debug!("Cleared install artifacts in {plugin_dir:?}");
+        Ok(previous)
+    }
+
     /// Resolve the binary command for a plugin.
     ///
     /// Used by AgentRuntime during lazy startup to find the plugin executable.
@@ -669,19 +944,22 @@ impl PluginManager {
 
         let metadata_path_clone = metadata_path.clone();
         let plugin_id_for_error = plugin_id.clone();
-        let metadata: PluginInstallMetadata = tokio::task::spawn_blocking(move || {
-            let content =
-                std::fs::read_to_string(&metadata_path_clone).map_err(|e| ApiError::IoError {
-                    message: format!("Failed to read install.json: {e}"),
+        let metadata: PluginInstallMetadata = self
+            .blocking_fs
+            .run(move || {
+                let content = std::fs::read_to_string(&metadata_path_clone).map_err(|e| {
+                    ApiError::IoError {
+                        message: format!("Failed to read install.json: {e}"),
+                    }
                 })?;
-            serde_json::from_str(&content).map_err(|e| ApiError::IoError {
-                message: format!("Failed to parse install.json: {e}"),
+                serde_json::from_str(&content).map_err(|e| ApiError::IoError {
+                    message: format!("Failed to parse install.json: {e}"),
+                })
             })
-        })
-        .await
-        .map_err(|e| ApiError::IoError {
-            message: format!("Failed to spawn blocking task: {e}"),
-        })??;
+            .await
+            .map_err(|e| ApiError::IoError {
+                message: format!("Failed to spawn blocking task: {e}"),
+            })??;
 
         // Check if bin_path is present
         let bin_path_str = metadata
@@ -833,4 +1111,213 @@ mod tests {
         assert!(PluginManager::validate_plugin_id("plugin-").is_err());
         assert!(PluginManager::validate_plugin_id("my-plugin").is_ok());
     }
+
+    #[tokio::test]
+    async fn test_output_with_timeout_kills_long_running_command() {
+        #[cfg(windows)]
+        let mut command = {
+            let mut cmd = Command::new("ping");
+            cmd.args(["-n", "10", "127.0.0.1"]);
+            cmd
+        };
+        #[cfg(not(windows))]
+        let mut command = {
+            let mut cmd = Command::new("sleep");
+            cmd.arg("5");
+            cmd
+        };
+
+        let started = std::time::Instant::now();
+        let result =
+            PluginManager::output_with_timeout(&mut command, Duration::from_millis(200)).await;
+
+        assert!(result.is_none());
+        assert!(started.elapsed() < Duration::from_secs(4));
+    }
+
+    #[tokio::test]
+    async fn test_install_that_times_out_is_an_io_error() {
+        #[cfg(windows)]
+        let command = {
+            let mut cmd = Command::new("ping");
+            cmd.args(["-n", "10", "127.0.0.1"]);
+            cmd
+        };
+        #[cfg(not(windows))]
+        let command = {
+            let mut cmd = Command::new("sleep");
+            cmd.arg("5");
+            cmd
+        };
+
+        let started = std::time::Instant::now();
+        let result = PluginManager::run_install_command(
+            "npm",
+            command,
+            &std::env::temp_dir(),
+            Duration::from_millis(200),
+        )
+        .await;
+
+        match result {
+            Err(ApiError::IoError { message }) => {
+                assert!(message.starts_with("npm install timed out"), "{message}")
+            }
+            other => panic!("expected a timeout IoError, got {other:?}"),
+        }
+        assert!(started.elapsed() < Duration::from_secs(4));
+    }
+
+    #[tokio::test]
+    async fn test_output_with_timeout_returns_output_when_fast() {
+        #[cfg(windows)]
+        let mut command = {
+            let mut cmd = Command::new("cmd");
+            cmd.args(["/C", "echo ok"]);
+            cmd
+        };
+        #[cfg(not(windows))]
+        let mut command = {
+            let mut cmd = Command::new("sh");
+            cmd.args(["-c", "printf ok"]);
+            cmd
+        };
+
+        let output = PluginManager::output_with_timeout(&mut command, Duration::from_secs(5))
+            .await
+            .expect("command should finish before the timeout")
+            .expect("command should spawn");
+
+        assert!(output.status.success());
+        assert!(String::from_utf8_lossy(&output.stdout).contains("ok"));
+    }
+
+    #[test]
+    fn test_version_at_least() {
+        assert!(PluginManager::version_at_least("20.11.1", "18.0.0"));
+        assert!(PluginManager::version_at_least("18.0.0", "18.0.0"));
+        assert!(PluginManager::version_at_least("18", "18.0.0"));
+        assert!(!PluginManager::version_at_least("16.20.2", "18.0.0"));
+        assert!(!PluginManager::version_at_least("garbage", "18.0.0"));
+    }
+
+    #[test]
+    fn test_evaluate_toolchain() {
+        let ready = PluginManager::evaluate_toolchain(
+            Some("20.11.1".to_string()),
+            Some("10.2.4".to_string()),
+        );
+        assert!(ready.ready);
+        assert!(ready.message.is_none());
+        assert_eq!(ready.min_node_version, MIN_NODE_VERSION);
+
+        let no_node = PluginManager::evaluate_toolchain(None, Some("10.2.4".to_string()));
+        assert!(!no_node.ready);
+        assert!(no_node
+            .message
+            .unwrap()
+            .contains("Node.js is not installed"));
+
+        let old_node = PluginManager::evaluate_toolchain(
+            Some("16.20.2".to_string()),
+            Some("8.19.4".to_string()),
+        );
+        assert!(!old_node.ready);
+        assert!(old_node.message.unwrap().contains("too old"));
+
+        let no_npm = PluginManager::evaluate_toolchain(Some("20.11.1".to_string()), None);
+        assert!(!no_npm.ready);
+        assert!(no_npm.message.unwrap().contains("npm is not installed"));
+    }
+
+    #[test]
+    fn test_install_metadata_package_manager_roundtrip() {
+        let metadata = PluginInstallMetadata {
+            installed_version: Some("1.0.0".to_string()),
+            bin_path: Some("node_modules/.bin/claude-code-acp".to_string()),
+            npm_package: None,
+            bin_name: None,
+            installed_at_ms: None,
+            package_manager: Some(PackageManager::Pnpm),
+        };
+        let json = serde_json::to_value(&metadata).unwrap();
+        assert_eq!(json["packageManager"], "pnpm");
+
+        // Metadata written before the field existed still parses
+        let legacy: PluginInstallMetadata =
+            serde_json::from_str(r#"{"installedVersion":"1.0.0","binPath":null}"#).unwrap();
+        assert!(legacy.package_manager.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_package_manager_prefers_explicit_then_recorded() {
+        let plugin_dir = std::env::temp_dir().join(format!("plugin_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&plugin_dir).unwrap();
+
+        let explicit = PluginManager::resolve_package_manager(
+            &BlockingFsLimiter::shared(),
+            &plugin_dir,
+            Some(PackageManager::Yarn),
+        )
+        .await
+        .unwrap();
+        assert_eq!(explicit, PackageManager::Yarn);
+
+        std::fs::write(
+            plugin_dir.join("install.json"),
+            r#"{"installedVersion":"1.0.0","binPath":null,"packageManager":"pnpm"}"#,
+        )
+        .unwrap();
+        let recorded =
+            PluginManager::resolve_package_manager(&BlockingFsLimiter::shared(), &plugin_dir, None)
+                .await
+                .unwrap();
+        assert_eq!(recorded, PackageManager::Pnpm);
+
+        std::fs::remove_dir_all(&plugin_dir).unwrap();
+    }
+
+    #[test]
+    fn test_clear_install_artifacts_resets_corrupted_dir() {
+        let plugin_dir = std::env::temp_dir().join(format!("plugin_{}", uuid::Uuid::new_v4()));
+        let bin_dir = plugin_dir.join("node_modules").join(".bin");
+        std::fs::create_dir_all(&bin_dir).unwrap();
+
+        // Partial install: stale metadata pointing at a missing binary
+        std::fs::write(plugin_dir.join("package.json"), "{}").unwrap();
+        std::fs::write(
+            plugin_dir.join("install.json"),
+            r#"{"installedVersion":"0.4.2","binPath":"node_modules/.bin/claude-code-acp","packageManager":"yarn"}"#,
+        )
+        .unwrap();
+        assert!(
+            PluginManager::resolve_npm_bin_relative_path(&plugin_dir, "claude-code-acp").is_err()
+        );
+
+        let previous = PluginManager::clear_install_artifacts(&plugin_dir)
+            .unwrap()
+            .expect("previous metadata should be returned");
+        assert_eq!(previous.installed_version.as_deref(), Some("0.4.2"));
+        assert_eq!(previous.package_manager, Some(PackageManager::Yarn));
+
+        assert!(!plugin_dir.join("node_modules").exists());
+        assert!(!plugin_dir.join("package.json").exists());
+        assert!(!plugin_dir.join("install.json").exists());
+
+        // Reinstall can now lay down a fresh tree; simulate the package manager's output
+        std::fs::create_dir_all(&bin_dir).unwrap();
+        std::fs::write(bin_dir.join("claude-code-acp"), "#!/bin/sh\n").unwrap();
+        assert_eq!(
+            PluginManager::resolve_npm_bin_relative_path(&plugin_dir, "claude-code-acp").unwrap(),
+            "node_modules/.bin/claude-code-acp"
+        );
+
+        // Clearing an already-clean directory is a no-op
+        std::fs::remove_dir_all(plugin_dir.join("node_modules")).unwrap();
+        assert!(PluginManager::clear_install_artifacts(&plugin_dir)
+            .unwrap()
+            .is_none());
+
+        std::fs::remove_dir_all(&plugin_dir).unwrap();
+    }
 }
@@ -3,4 +3,6 @@
 //! Handles plugin installation, status checking, and version management.
 //! Plugins are stored in `app_cache_dir()/plugins/<pluginId>/`.
 
+pub mod cache;
 pub mod manager;
+pub mod package_manager;
@@ -0,0 +1,115 @@
+//! Package manager selection for plugin installs.
+//!
+//! Plugins are npm packages, but users may only have pnpm or yarn in PATH.
+//! All three populate `node_modules/.bin`, so bin resolution is unchanged;
+//! only the install command differs.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+/// Package manager used to install a plugin.
+///
+/// Persisted in `install.json` so later updates use the same tool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum PackageManager {
+    Npm,
+    Pnpm,
+    Yarn,
+}
+
+impl PackageManager {
+    /// Order in which package managers are probed during auto-detection.
+    pub const DETECTION_ORDER: [PackageManager; 3] = [
+        PackageManager::Npm,
+        PackageManager::Pnpm,
+        PackageManager::Yarn,
+    ];
+
+    /// Human-readable name (also the executable name on Unix).
+    pub fn name(self) -> &'static str {
+        match self {
+            PackageManager::Npm => "npm",
+            PackageManager::Pnpm => "pnpm",
+            PackageManager::Yarn => "yarn",
+        }
+    }
+
+    /// Executable to invoke (`.cmd` shims on Windows).
+    pub fn program(self) -> &'static str {
+        #[cfg(windows)]
+        {
+            match self {
+                PackageManager::Npm => "npm.cmd",
+                PackageManager::Pnpm => "pnpm.cmd",
+                PackageManager::Yarn => "yarn.cmd",
+            }
+        }
+        #[cfg(not(windows))]
+        {
+            self.name()
+        }
+    }
+
+    /// Arguments for a quiet, production-only install.
+    pub fn install_args(self) -> &'static [&'static str] {
+        match self {
+            PackageManager::Npm => &[
+                "install",
+                "--no-audit",
+                "--no-fund",
+                "--loglevel=error",
+                "--omit=dev",
+            ],
+            PackageManager::Pnpm => &["install", "--prod", "--reporter=append-only"],
+            PackageManager::Yarn => &["install", "--production", "--non-interactive", "--silent"],
+        }
+    }
+
+    /// Extra environment for the install command.
+    ///
+    /// Yarn Berry defaults to Plug'n'Play, which produces no `node_modules`;
+    /// force the node-modules linker so the bin shim exists.
+    pub fn install_env(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            PackageManager::Yarn => &[("YARN_NODE_LINKER", "node-modules")],
+            PackageManager::Npm | PackageManager::Pnpm => &[],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_install_args_are_production_installs() {
+        assert!(PackageManager::Npm.install_args().contains(&"--omit=dev"));
+        assert!(PackageManager::Pnpm.install_args().contains(&"--prod"));
+        assert!(PackageManager::Yarn
+            .install_args()
+            .contains(&"--production"));
+        for manager in PackageManager::DETECTION_ORDER {
+            assert_eq!(manager.install_args()[0], "install");
+        }
+    }
+
+    #[test]
+    fn test_yarn_forces_node_modules_linker() {
+        assert_eq!(
+            PackageManager::Yarn.install_env(),
+            &[("YARN_NODE_LINKER", "node-modules")]
+        );
+        assert!(PackageManager::Npm.install_env().is_empty());
+    }
+
+    #[test]
+    fn test_serde_names() {
+        assert_eq!(
+            serde_json::to_value(PackageManager::Pnpm).unwrap(),
+            serde_json::json!("pnpm")
+        );
+        let parsed: PackageManager = serde_json::from_value(serde_json::json!("yarn")).unwrap();
+        assert_eq!(parsed, PackageManager::Yarn);
+    }
+}
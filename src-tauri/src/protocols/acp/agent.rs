@@ -5,6 +5,7 @@
 //! - Spawning the adapter process with STDIO pipes
 //! - ACP protocol lifecycle: initialize → session/new → session/prompt
 //! - Streaming session updates via notifications
+//! - Routing responses to their requests by JSON-RPC id (single stdout reader)
 //!
 //! US-06: Connection establishment with ACP handshake
 //! US-07: JSON-RPC prompt sending and session update streaming
@@ -12,24 +13,36 @@
 //! US-10/11: File system read/write operations
 //! US-12: Turn cancellation
 
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
 use async_trait::async_trait;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::io::{AsyncWriteExt, BufReader};
 use tokio::process::{Child, Command};
-use tokio::sync::{Mutex, Semaphore};
+use tokio::sync::{oneshot, Mutex, Notify, Semaphore};
+use tokio::task::JoinSet;
 use uuid::Uuid;
 
+use super::framing::FrameReader;
 use super::update_mapping::{map_acp_update_to_api_update, parse_acp_session_notification_params};
-use crate::api::types::{ApiError, PermissionSource, SessionId};
+use crate::api::types::{
+    ApiError, McpServerConfig, PermissionDecision, PermissionOption, PermissionOptionKind,
+    PermissionOutcome, PermissionSource, PromptContentBlock, ProtocolErrorKind,
+    ProtocolTraceDirection, SessionId, StopReason, TurnId,
+};
 use crate::plugins::manager::PluginCommand;
 use crate::protocols::agent_connection::AgentConnection;
 use crate::protocols::host::{
-    AgentHost, FsReadTextFileRequest, FsWriteTextFileRequest, PermissionRequest, TerminalRunRequest,
+    AgentHost, FsContentEncoding, FsDeleteRequest, FsReadEncoding, FsReadTextFileRequest,
+    FsReadTextFileResult, FsRenameRequest, FsWriteTextFileRequest, FsWriteTextFileResult,
+    PermissionRequest, TerminalCreateRequest, TerminalExitStatus, TerminalRunRequest,
 };
-use agent_client_protocol as acp;
+use crate::utils::adapter_pids::{AdapterPidGuard, AdapterPids};
+use crate::utils::text::truncate_on_char_boundary;
 
 /// JSON-RPC method name for initialize handshake
 /// Per ACP protocol schema: agent-client-protocol-schema/src/agent.rs
@@ -37,12 +50,21 @@ const METHOD_INITIALIZE: &str = "initialize";
 /// JSON-RPC method name for session creation
 /// Per ACP protocol schema: agent-client-protocol-schema/src/agent.rs
 const METHOD_SESSION_NEW: &str = "session/new";
+/// JSON-RPC method name for resuming a previous session
+/// Per ACP protocol schema: agent-client-protocol-schema/src/agent.rs
+const METHOD_SESSION_LOAD: &str = "session/load";
 /// JSON-RPC method name for sending prompts (US-07)
 /// Per ACP protocol schema: agent-client-protocol-schema/src/agent.rs
 const METHOD_SEND_PROMPT: &str = "session/prompt";
 /// JSON-RPC method name for canceling the current turn (US-12)
 /// Per ACP protocol schema: agent-client-protocol-schema/src/agent.rs
 const METHOD_CANCEL_TURN: &str = "session/cancel";
+/// JSON-RPC method name for closing a session (unstable in ACP; adapters
+/// without it answer "method not found")
+const METHOD_SESSION_CLOSE: &str = "session/close";
+/// JSON-RPC method name for switching a session's mode
+/// Per ACP protocol schema: agent-client-protocol-schema/src/agent.rs
+const METHOD_SESSION_SET_MODE: &str = "session/set_mode";
 
 /// JSON-RPC method name for session notifications (US-07)
 const METHOD_SESSION_NOTIFICATION: &str = "session/notification";
@@ -54,15 +76,214 @@ const METHOD_REQUEST_PERMISSION: &str = "request_permission";
 
 /// JSON-RPC method name for terminal run requests (US-08)
 const METHOD_TERMINAL_RUN: &str = "terminal/run";
+/// JSON-RPC method names for retained terminals the agent polls
+const METHOD_TERMINAL_CREATE: &str = "terminal/create";
+const METHOD_TERMINAL_OUTPUT: &str = "terminal/output";
+const METHOD_TERMINAL_WAIT_FOR_EXIT: &str = "terminal/wait_for_exit";
+const METHOD_TERMINAL_KILL: &str = "terminal/kill";
+const METHOD_TERMINAL_RELEASE: &str = "terminal/release";
 /// JSON-RPC method name for file read requests (US-10)
 const METHOD_FS_READ_TEXT_FILE: &str = "fs.read_text_file";
 const METHOD_FS_READ_TEXT_FILE_ALIAS: &str = "read_text_file";
 /// JSON-RPC method name for file write requests (US-11)
 const METHOD_FS_WRITE_TEXT_FILE: &str = "fs.write_text_file";
 const METHOD_FS_WRITE_TEXT_FILE_ALIAS: &str = "write_text_file";
+/// JSON-RPC method name for appending to a file (same permission flow as writes)
+const METHOD_FS_APPEND_TEXT_FILE: &str = "fs.append_text_file";
+/// JSON-RPC method names for file delete/rename requests
+const METHOD_FS_DELETE: &str = "fs.delete";
+const METHOD_FS_RENAME: &str = "fs.rename";
+/// Keepalive probe. Not part of ACP: any response, even "method not found",
+/// shows the adapter is still reading and writing
+const METHOD_PING: &str = "$/ping";
 
 const MAX_INFLIGHT_REQUESTS: usize = 8;
 
+/// ACP protocol version this client requests in `initialize`
+const PROTOCOL_VERSION: u64 = 1;
+/// Oldest ACP protocol version this client can still speak
+const MIN_PROTOCOL_VERSION: u64 = 1;
+
+/// How long `send_prompt` waits for an early error (e.g. unknown session)
+/// before treating the prompt as accepted.
+///
+/// ACP has no explicit acceptance message, so this is a heuristic: the
+/// prompt counts as accepted on its first `session/update`, its response, or
+/// when the window passes without an error, whichever comes first. An error
+/// arriving later (including an unknown session, which is then no longer
+/// retried on a fresh one) ends the started turn with a `TurnComplete`
+/// carrying the error instead of failing the send.
+const PROMPT_ACCEPT_WINDOW: Duration = Duration::from_millis(500);
+/// Default time allowed for each handshake step (`initialize`, `session/new`)
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(30);
+/// Upper bound for request/response round trips such as `session/new`
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+/// Default time `shutdown` gives active turns to finish after `session/cancel`,
+/// and then the adapter to exit after its stdin is closed, before killing it
+pub const SHUTDOWN_GRACE_PERIOD: Duration = Duration::from_secs(3);
+/// Largest single JSON-RPC message accepted from an adapter
+const MAX_FRAME_BYTES: usize = 16 * 1024 * 1024;
+/// Leading stderr lines kept for reporting startup failures
+const STARTUP_STDERR_LINES: usize = 20;
+/// Upper bound on stderr attached to a startup error message
+const STARTUP_STDERR_MAX_BYTES: usize = 2048;
+/// How long a failed startup waits for the adapter's remaining stderr
+const STARTUP_STDERR_TIMEOUT: Duration = Duration::from_millis(500);
+/// Attempts made to spawn an adapter when spawning fails transiently
+const SPAWN_ATTEMPTS: u32 = 3;
+/// Delay before the first spawn retry; doubles with each failure
+const SPAWN_RETRY_BASE_DELAY: Duration = Duration::from_millis(100);
+/// Longest string kept verbatim in a protocol trace frame
+const TRACE_STRING_LIMIT: usize = 2048;
+/// JSON-RPC error code ACP adapters use for unknown resources (e.g. sessions)
+const JSONRPC_RESOURCE_NOT_FOUND: i64 = -32002;
+/// JSON-RPC error code for methods the peer doesn't implement
+const JSONRPC_METHOD_NOT_FOUND: i64 = -32601;
+
+/// Periodic liveness check for adapters that may hang without exiting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AcpKeepalive {
+    /// Time between pings
+    pub interval: Duration,
+    /// How long to wait for a ping's response before giving up on the adapter
+    pub timeout: Duration,
+}
+
+/// Connection tuning for [`AcpAgent::connect_with_options`].
+#[derive(Debug, Clone)]
+pub struct AcpConnectOptions {
+    /// Time allowed for each handshake step (`initialize`, `session/new`)
+    pub handshake_timeout: Duration,
+    /// Time `shutdown` lets the adapter wind down before killing it
+    pub shutdown_grace: Duration,
+    /// Ping the adapter periodically; off by default since adapters may not
+    /// answer unknown methods
+    pub keepalive: Option<AcpKeepalive>,
+    /// Registry the spawned adapter's pid is recorded in while connected
+    pub adapter_pids: Option<Arc<AdapterPids>>,
+}
+
+impl Default for AcpConnectOptions {
+    fn default() -> Self {
+        Self {
+            handshake_timeout: HANDSHAKE_TIMEOUT,
+            shutdown_grace: SHUTDOWN_GRACE_PERIOD,
+            keepalive: None,
+            adapter_pids: None,
+        }
+    }
+}
+
+/// A request awaiting its JSON-RPC response.
+struct PendingRequest {
+    /// Session the request targets (used to attribute `TurnComplete`)
+    session_id: Option<SessionId>,
+    /// Resolved by the stdout reader when the matching response arrives
+    tx: oneshot::Sender<serde_json::Value>,
+}
+
+/// In-flight requests keyed by JSON-RPC id.
+type PendingRequests = Arc<std::sync::Mutex<HashMap<String, PendingRequest>>>;
+
+/// Handlers of adapter-initiated requests (`terminal/run`, permissions, ...).
+type RequestTasks = Arc<std::sync::Mutex<JoinSet<()>>>;
+
+/// Prompt turns that announced `TurnStarted` and haven't ended yet.
+///
+/// Start and end signals are emitted while holding its lock, so a turn's
+/// `TurnComplete` never overtakes its `TurnStarted`.
+#[derive(Clone, Default)]
+struct StartedTurns(Arc<std::sync::Mutex<HashSet<TurnId>>>);
+
+impl StartedTurns {
+    /// Announce that the adapter accepted the prompt in flight on
+    /// `session_id`, unless it was announced already.
+    ///
+    /// Does nothing once the turn ended, i.e. its request left `pending`
+    /// (whoever ended it announced the start as well).
+    fn start(&self, host: &dyn AgentHost, pending: &PendingRequests, session_id: &SessionId) {
+        let mut started = lock_unpoisoned(&self.0);
+        let turn_id = lock_unpoisoned(pending)
+            .iter()
+            .find(|(id, request)| {
+                request.session_id.as_ref() == Some(session_id) && !started.contains(*id)
+            })
+            .map(|(id, _)| id.clone());
+        let Some(turn_id) = turn_id else {
+            return;
+        };
+        started.insert(turn_id.clone());
+        host.on_session_update(
+            session_id.clone(),
+            Some(turn_id.clone()),
+            crate::api::types::AcpSessionUpdate::TurnStarted {
+                prompt_id: turn_id.clone(),
+            },
+        );
+    }
+
+    /// End `turn_id`, announcing its start first if that hadn't happened.
+    fn complete(
+        &self,
+        host: &dyn AgentHost,
+        session_id: &SessionId,
+        turn_id: &TurnId,
+        stop_reason: StopReason,
+    ) {
+        let mut started = lock_unpoisoned(&self.0);
+        if !started.remove(turn_id) {
+            host.on_session_update(
+                session_id.clone(),
+                Some(turn_id.clone()),
+                crate::api::types::AcpSessionUpdate::TurnStarted {
+                    prompt_id: turn_id.clone(),
+                },
+            );
+        }
+        host.on_session_update(
+            session_id.clone(),
+            Some(turn_id.clone()),
+            crate::api::types::AcpSessionUpdate::TurnComplete {
+                stop_reason,
+                error: None,
+            },
+        );
+    }
+
+    /// End `turn_id` with `error` after it failed. A turn that was never
+    /// announced stays silent; its send reports the error instead.
+    fn fail(&self, host: &dyn AgentHost, session_id: &SessionId, turn_id: &TurnId, error: String) {
+        let mut started = lock_unpoisoned(&self.0);
+        if started.remove(turn_id) {
+            host.on_session_update(
+                session_id.clone(),
+                Some(turn_id.clone()),
+                crate::api::types::AcpSessionUpdate::TurnComplete {
+                    stop_reason: StopReason::Error,
+                    error: Some(error),
+                },
+            );
+        }
+    }
+}
+
+/// Outcome of a successful handshake.
+struct Handshake {
+    /// Adapter-issued (or resumed) session
+    session_id: SessionId,
+    /// Protocol version agreed in `initialize`
+    protocol_version: u64,
+    /// Modes the session was created (or resumed) with, if the adapter has any
+    modes: Option<SessionModes>,
+}
+
+/// A session's modes as announced in a `session/new` or `session/load` result.
+#[derive(Debug, Clone, PartialEq)]
+struct SessionModes {
+    current_mode_id: Option<String>,
+    available_mode_ids: Vec<String>,
+}
+
 /// ACP protocol implementation using STDIO subprocess.
 pub struct AcpAgent {
     /// The spawned child process (used by shutdown)
@@ -70,11 +291,39 @@ pub struct AcpAgent {
     child: Mutex<Option<Child>>,
     /// Standard input handle for sending prompts (US-07)
     stdin: Arc<Mutex<Option<tokio::process::ChildStdin>>>,
-    /// Session ID assigned during new_session
-    session_id: SessionId,
-    /// Host for callbacks (status updates, used by stdout reader task)
+    /// Current session ID (assigned during handshake, replaced by new_session)
+    session_id: Arc<std::sync::Mutex<SessionId>>,
+    /// ACP protocol version negotiated during `initialize`
     #[allow(dead_code)]
+    protocol_version: u64,
+    /// Working directory passed to `session/new`
+    cwd: PathBuf,
+    /// MCP servers passed to `session/new`
+    mcp_servers: Vec<McpServerConfig>,
+    /// Requests awaiting a response, resolved by the stdout reader task
+    pending: PendingRequests,
+    /// Request id of the most recent prompt sent on each session
+    prompt_ids: std::sync::Mutex<HashMap<SessionId, String>>,
+    /// Mode IDs each session accepts, for sessions whose adapter announced modes
+    available_modes: std::sync::Mutex<HashMap<SessionId, Vec<String>>>,
+    /// Signaled by the stdout reader whenever a prompt (turn) response arrives
+    turn_finished: Arc<Notify>,
+    /// Turns announced to the host, shared with the stdout reader
+    started_turns: StartedTurns,
+    /// Tasks handling adapter-initiated requests, aborted on shutdown
+    request_tasks: RequestTasks,
+    /// Time allowed for cancelled turns to finish, and then for the process
+    /// to exit on its own, before it is killed
+    shutdown_grace: Duration,
+    /// Cleared when the adapter goes away (stdout EOF or a broken stdin pipe)
+    connected: Arc<AtomicBool>,
+    /// Host for callbacks (status updates, used by stdout reader task)
     host: Arc<dyn AgentHost>,
+    /// Keeps the child's pid in the adapter registry while connected
+    #[allow(dead_code)]
+    pid_guard: Option<AdapterPidGuard>,
+    /// This agent, for tasks that outlive a call (turn timeouts)
+    this: std::sync::Weak<AcpAgent>,
 }
 
 impl AcpAgent {
@@ -90,6 +339,8 @@ impl AcpAgent {
     /// * `cmd` - The plugin command specification (path, args, env)
     /// * `cwd` - Working directory for the adapter (workspace root)
     /// * `host` - Callback interface for events
+    /// * `resume_session_id` - Previous session to resume via `session/load`, if any
+    /// * `mcp_servers` - MCP servers passed with `session/new` and `session/load`
     ///
     /// # Returns
     /// * `Ok((Arc<dyn AgentConnection>, SessionId))` - Connection and session
@@ -98,7 +349,37 @@ impl AcpAgent {
         cmd: PluginCommand,
         cwd: PathBuf,
         host: Arc<dyn AgentHost>,
+        resume_session_id: Option<SessionId>,
+        mcp_servers: Vec<McpServerConfig>,
+    ) -> Result<(Arc<dyn AgentConnection>, SessionId), ApiError> {
+        Self::connect_with_options(
+            cmd,
+            cwd,
+            host,
+            resume_session_id,
+            mcp_servers,
+            AcpConnectOptions::default(),
+        )
+        .await
+    }
+
+    /// Like [`AcpAgent::connect`], but with custom timeouts and an optional
+    /// keepalive (see [`AcpConnectOptions`]).
+    pub async fn connect_with_options(
+        cmd: PluginCommand,
+        cwd: PathBuf,
+        host: Arc<dyn AgentHost>,
+        resume_session_id: Option<SessionId>,
+        mcp_servers: Vec<McpServerConfig>,
+        options: AcpConnectOptions,
     ) -> Result<(Arc<dyn AgentConnection>, SessionId), ApiError> {
+        let AcpConnectOptions {
+            handshake_timeout,
+            shutdown_grace,
+            keepalive,
+            adapter_pids,
+        } = options;
+
         log::info!(
             "Connecting to ACP adapter: bin={:?}, cwd={:?}",
             cmd.path,
@@ -120,11 +401,36 @@ impl AcpAgent {
             command.env(key, value);
         }
 
-        let mut child = command.spawn().map_err(|e| ApiError::IoError {
-            message: format!("Failed to spawn adapter process: {e}"),
-        })?;
+        let mut child = spawn_adapter(&mut command)
+            .await
+            .map_err(|e| ApiError::IoError {
+                message: format!("Failed to spawn adapter process: {e}"),
+            })?;
 
         log::debug!("Adapter process spawned: pid={:?}", child.id());
+        let pid_guard = adapter_pids
+            .zip(child.id())
+            .map(|(pids, pid)| pids.register(pid, &cmd.path, &cwd));
+
+        // Spawn stderr reader task for logging. The first lines are kept so
+        // a crash during startup can report what the adapter printed.
+        let stderr = child.stderr.take().ok_or_else(|| ApiError::ProtocolError {
+            kind: ProtocolErrorKind::ConnectionClosed,
+            message: "Failed to get stderr handle".to_string(),
+        })?;
+        let startup_stderr = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let startup_stderr_for_task = startup_stderr.clone();
+        let mut stderr_task = tokio::spawn(async move {
+            let mut frames = FrameReader::new(BufReader::new(stderr), MAX_FRAME_BYTES);
+            while let Ok(Some(line)) = frames.next_frame().await {
+                log::debug!("[adapter stderr] {line}");
+                let mut captured = lock_unpoisoned(&startup_stderr_for_task);
+                if captured.len() < STARTUP_STDERR_LINES {
+                    captured.push(line);
+                }
+            }
+            log::debug!("Adapter stderr closed");
+        });
 
         // Security/Robustness: Check if process exited immediately (crash on startup)
         match child.try_wait() {
@@ -134,10 +440,15 @@ impl AcpAgent {
                     "Adapter process exited immediately: exit_status={:?}",
                     status
                 );
+                let _ = tokio::time::timeout(STARTUP_STDERR_TIMEOUT, &mut stderr_task).await;
                 return Err(ApiError::ProtocolError {
-                    message: format!(
-                        "Adapter process exited immediately with status: {:?}",
-                        status.code()
+                    kind: ProtocolErrorKind::ConnectionClosed,
+                    message: with_startup_stderr(
+                        format!(
+                            "Adapter process exited immediately with status: {:?}",
+                            status.code()
+                        ),
+                        &lock_unpoisoned(&startup_stderr),
                     ),
                 });
             }
@@ -154,51 +465,59 @@ impl AcpAgent {
         // Take ownership of stdio handles
         // US-07: Store stdin for sending prompts
         let stdin = child.stdin.take().ok_or_else(|| ApiError::ProtocolError {
+            kind: ProtocolErrorKind::ConnectionClosed,
             message: "Failed to get stdin handle".to_string(),
         })?;
 
         let stdout = child.stdout.take().ok_or_else(|| ApiError::ProtocolError {
+            kind: ProtocolErrorKind::ConnectionClosed,
             message: "Failed to get stdout handle".to_string(),
         })?;
 
-        let stderr = child.stderr.take().ok_or_else(|| ApiError::ProtocolError {
-            message: "Failed to get stderr handle".to_string(),
-        })?;
-
-        // Spawn stderr reader task for logging
-        let _stderr_task = tokio::spawn(async move {
-            let reader = BufReader::new(stderr);
-            let mut lines = reader.lines();
-            while let Ok(Some(line)) = lines.next_line().await {
-                log::debug!("[adapter stderr] {line}");
-            }
-            log::debug!("Adapter stderr closed");
-        });
-
         let stdin = Arc::new(Mutex::new(Some(stdin)));
 
-        // Perform ACP handshake: initialize → session/new
-        // This must happen before spawning the stdout reader task
-        let mut stdout_reader = BufReader::new(stdout);
-        let session_id = perform_acp_handshake(&stdin, &mut stdout_reader, &cwd).await?;
-
-        log::info!("ACP handshake completed: session={session_id}");
-
-        // Now spawn stdout reader task for ongoing notifications/requests
+        // Spawn the stdout reader before the handshake so every response,
+        // including `initialize` and `session/new`, is routed through `pending`
         let request_semaphore = Arc::new(Semaphore::new(MAX_INFLIGHT_REQUESTS));
+        let pending: PendingRequests = Arc::new(std::sync::Mutex::new(HashMap::new()));
+        let current_session = Arc::new(std::sync::Mutex::new(SessionId::new()));
+        let connected = Arc::new(AtomicBool::new(false));
         let host_for_stdout = host.clone();
-        let session_id_for_stdout = session_id.clone();
+        let session_for_stdout = current_session.clone();
+        let pending_for_stdout = pending.clone();
+        let connected_for_stdout = connected.clone();
+        let turn_finished = Arc::new(Notify::new());
+        let turn_finished_for_stdout = turn_finished.clone();
+        let started_turns = StartedTurns::default();
+        let started_turns_for_stdout = started_turns.clone();
         let stdin_for_stdout = stdin.clone();
         let semaphore_for_stdout = request_semaphore.clone();
+        let request_tasks: RequestTasks = Arc::new(std::sync::Mutex::new(JoinSet::new()));
+        let request_tasks_for_stdout = request_tasks.clone();
 
-        // Convert stdout_reader back to lines iterator for the task
         let _stdout_task = tokio::spawn(async move {
-            let mut lines = stdout_reader.lines();
+            let mut frames = FrameReader::new(BufReader::new(stdout), MAX_FRAME_BYTES);
+
+            loop {
+                let line = match frames.next_frame().await {
+                    Ok(Some(line)) => line,
+                    Ok(None) => break,
+                    Err(e) => {
+                        // Oversized or unreadable output: treat the adapter as gone
+                        log::error!("Stopped reading adapter stdout: error={e}");
+                        break;
+                    }
+                };
+                let session_id_for_stdout = lock_unpoisoned(&session_for_stdout).clone();
 
-            while let Ok(Some(line)) = lines.next_line().await {
                 // Try to parse as JSON
                 match serde_json::from_str::<serde_json::Value>(&line) {
                     Ok(json) => {
+                        trace_frame(
+                            host_for_stdout.as_ref(),
+                            ProtocolTraceDirection::Inbound,
+                            &json,
+                        );
                         let method = json
                             .get("method")
                             .and_then(|m| m.as_str())
@@ -212,15 +531,23 @@ impl AcpAgent {
                                     || method == METHOD_SESSION_UPDATE
                                 {
                                     if let Some(params) = json.get("params").cloned() {
+                                        let turn_id = extract_turn_id(&params);
                                         match parse_acp_session_notification_params(
                                             params.clone(),
                                             &session_id_for_stdout,
                                         ) {
                                             Ok((notification_session_id, update)) => {
+                                                // Streamed output means the prompt was accepted
+                                                started_turns_for_stdout.start(
+                                                    host_for_stdout.as_ref(),
+                                                    &pending_for_stdout,
+                                                    &notification_session_id,
+                                                );
                                                 let api_update =
                                                     map_acp_update_to_api_update(update);
                                                 host_for_stdout.on_session_update(
                                                     notification_session_id,
+                                                    turn_id,
                                                     api_update,
                                                 );
                                             }
@@ -237,8 +564,11 @@ impl AcpAgent {
                                                     crate::api::types::AcpSessionUpdate::Raw {
                                                         json: params,
                                                     };
-                                                host_for_stdout
-                                                    .on_session_update(raw_session_id, raw_update);
+                                                host_for_stdout.on_session_update(
+                                                    raw_session_id,
+                                                    turn_id,
+                                                    raw_update,
+                                                );
                                             }
                                         }
                                     } else {
@@ -262,7 +592,10 @@ impl AcpAgent {
                                     Err(_) => break,
                                 };
 
-                                tokio::spawn(async move {
+                                let mut request_tasks = lock_unpoisoned(&request_tasks_for_stdout);
+                                // Drop finished handlers so the set only holds live ones
+                                while request_tasks.try_join_next().is_some() {}
+                                request_tasks.spawn(async move {
                                     let _permit = permit;
                                     handle_request(
                                         host_for_request,
@@ -275,38 +608,69 @@ impl AcpAgent {
                                     .await;
                                 });
                             }
-                        } else if json.get("id").is_some() {
-                            if let Some(result) = json.get("result") {
-                                let stop_reason_value = result
-                                    .get("stopReason")
-                                    .or_else(|| result.get("stop_reason"))
-                                    .cloned();
-                                if let Some(stop_reason_value) = stop_reason_value {
-                                    let stop_reason =
-                                        match serde_json::from_value::<acp::PromptResponse>(
-                                            result.clone(),
-                                        ) {
-                                            Ok(prompt_response) => {
-                                                serde_json::to_value(prompt_response.stop_reason)
-                                                    .unwrap_or(stop_reason_value.clone())
-                                            }
-                                            Err(e) => {
-                                                log::debug!(
-                                                "Failed to parse prompt response stopReason: {e}"
-                                            );
-                                                stop_reason_value
-                                            }
-                                        };
-                                    let update =
-                                        crate::api::types::AcpSessionUpdate::TurnComplete {
-                                            stop_reason,
-                                        };
-                                    host_for_stdout
-                                        .on_session_update(session_id_for_stdout.clone(), update);
+                        } else if let Some(id) = json.get("id") {
+                            let id_key = jsonrpc_id_key(id);
+                            let pending_request = id_key
+                                .as_ref()
+                                .and_then(|key| lock_unpoisoned(&pending_for_stdout).remove(key));
+                            let (response_session_id, response_turn_id, response_tx) =
+                                match pending_request {
+                                    Some(PendingRequest {
+                                        session_id: Some(session_id),
+                                        tx,
+                                    }) => {
+                                        // A prompt finished; wake any shutdown waiting on it.
+                                        // Its request id is the turn id.
+                                        turn_finished_for_stdout.notify_waiters();
+                                        (session_id, id_key, Some(tx))
+                                    }
+                                    Some(PendingRequest {
+                                        session_id: None,
+                                        tx,
+                                    }) => (session_id_for_stdout.clone(), None, Some(tx)),
+                                    None => (session_id_for_stdout.clone(), None, None),
+                                };
+
+                            if response_turn_id.is_none()
+                                && json.get("result").is_some_and(|result| {
+                                    result.get("stopReason").is_some()
+                                        || result.get("stop_reason").is_some()
+                                })
+                            {
+                                // e.g. the late response of a turn cancel_turn already ended
+                                log::debug!(
+                                    "[acp] Ignoring prompt response for unknown request: {line}"
+                                );
+                            } else if let Some(turn_id) = &response_turn_id {
+                                if let Some(stop_reason) =
+                                    json.get("result").and_then(parse_stop_reason)
+                                {
+                                    started_turns_for_stdout.complete(
+                                        host_for_stdout.as_ref(),
+                                        &response_session_id,
+                                        turn_id,
+                                        stop_reason,
+                                    );
+                                } else if let Some(error) = response_error(
+                                    &json,
+                                    &response_session_id,
+                                    "Prompt",
+                                    ProtocolErrorKind::RequestFailed,
+                                ) {
+                                    // Rejected after the accept window: the turn already started
+                                    started_turns_for_stdout.fail(
+                                        host_for_stdout.as_ref(),
+                                        &response_session_id,
+                                        turn_id,
+                                        error.to_string(),
+                                    );
                                 }
                             }
-                            // It's a response (has "id" field), log for now
                             log::debug!("[acp stdout response] {line}");
+                            if let Some(tx) = response_tx {
+                                // The waiter may have given up already; that's fine
+                                let _ = tx.send(json);
+                            }
                         } else {
                             log::debug!("[acp stdout] {line}");
                         }
@@ -319,136 +683,373 @@ impl AcpAgent {
             }
 
             // Stdout closed - process likely exited
+            let session_id_for_stdout = lock_unpoisoned(&session_for_stdout).clone();
             log::info!(
                 "Adapter stdout closed, process may have exited: session={session_id_for_stdout}"
             );
-            // Notify host that the connection has been lost and agent has stopped
-            host_for_stdout.on_connection_lost();
+            // Drop pending senders so waiters fail fast instead of timing out
+            lock_unpoisoned(&pending_for_stdout).clear();
+            // Notify host that the connection has been lost and agent has stopped.
+            // During the handshake, connect() reports the failure itself.
+            if connected_for_stdout.swap(false, Ordering::SeqCst) {
+                host_for_stdout.on_connection_lost();
+            }
         });
 
+        // Perform ACP handshake: initialize → session/new
+        let handshake = match perform_acp_handshake(
+            host.as_ref(),
+            &stdin,
+            &pending,
+            &cwd,
+            resume_session_id,
+            &mcp_servers,
+            handshake_timeout,
+        )
+        .await
+        {
+            Ok(handshake) => handshake,
+            Err(e) => {
+                log::error!("ACP handshake failed, killing adapter: error={e}");
+                if let Err(kill_err) = child.kill().await {
+                    log::warn!("Failed to kill adapter process: {kill_err}");
+                }
+                // Let the reader collect whatever the adapter printed before dying
+                let _ = tokio::time::timeout(STARTUP_STDERR_TIMEOUT, &mut stderr_task).await;
+                return Err(match e {
+                    ApiError::ProtocolError { kind, message } => ApiError::ProtocolError {
+                        kind,
+                        message: with_startup_stderr(message, &lock_unpoisoned(&startup_stderr)),
+                    },
+                    other => other,
+                });
+            }
+        };
+        let session_id = handshake.session_id;
+        *lock_unpoisoned(&current_session) = session_id.clone();
+        let mut available_modes = HashMap::new();
+        if let Some(modes) = handshake.modes {
+            report_current_mode(host.as_ref(), &session_id, &modes);
+            available_modes.insert(session_id.clone(), modes.available_mode_ids);
+        }
+        connected.store(true, Ordering::SeqCst);
+
         log::info!("ACP connection established: session={session_id}");
 
-        let agent = Arc::new(Self {
+        let agent = Arc::new_cyclic(|this| Self {
             child: Mutex::new(Some(child)),
             stdin,
-            session_id: session_id.clone(),
+            session_id: current_session,
+            protocol_version: handshake.protocol_version,
+            cwd,
+            mcp_servers,
+            pending,
+            prompt_ids: std::sync::Mutex::new(HashMap::new()),
+            available_modes: std::sync::Mutex::new(available_modes),
+            turn_finished,
+            started_turns,
+            request_tasks,
+            shutdown_grace,
+            connected,
             host,
+            pid_guard,
+            this: this.clone(),
         });
 
+        if let Some(keepalive) = keepalive {
+            spawn_keepalive(Arc::downgrade(&agent), keepalive);
+        }
+
         Ok((agent, session_id))
     }
 }
 
 #[async_trait]
 impl AgentConnection for AcpAgent {
-    async fn send_prompt(&self, session_id: SessionId, prompt: String) -> Result<(), ApiError> {
+    async fn send_prompt(
+        &self,
+        session_id: SessionId,
+        prompt: Vec<PromptContentBlock>,
+    ) -> Result<TurnId, ApiError> {
+        self.send_prompt_with_timeout(session_id, prompt, None)
+            .await
+    }
+
+    async fn send_prompt_with_timeout(
+        &self,
+        session_id: SessionId,
+        prompt: Vec<PromptContentBlock>,
+        timeout: Option<Duration>,
+    ) -> Result<TurnId, ApiError> {
         log::info!(
-            "Sending prompt to ACP agent: session={}, prompt_len={}",
+            "Sending prompt to ACP agent: session={}, blocks={}",
             session_id,
             prompt.len()
         );
 
-        // Generate a unique request ID for JSON-RPC
+        // Generate a unique request ID for JSON-RPC; it doubles as the turn id
         let request_id = Uuid::new_v4().to_string();
 
         // Construct JSON-RPC request with proper ACP PromptRequest structure
         // ACP expects: { sessionId: string, prompt: ContentBlock[] }
         // where ContentBlock can be { type: "text", text: string } etc.
+        let content: Vec<serde_json::Value> = prompt.iter().map(acp_content_block).collect();
         let request = serde_json::json!({
             "jsonrpc": "2.0",
             "id": request_id,
             "method": METHOD_SEND_PROMPT,
             "params": {
                 "sessionId": session_id,
-                "prompt": [{
-                    "type": "text",
-                    "text": prompt
-                }]
+                "prompt": content
             }
         });
 
-        let message = serde_json::to_string(&request).map_err(|e| ApiError::ProtocolError {
-            message: format!("Failed to serialize prompt: {e}"),
-        })?;
-
-        // Write to stdin (newline-delimited JSON)
-        let mut stdin_guard = self.stdin.lock().await;
-        if let Some(stdin) = stdin_guard.as_mut() {
-            stdin
-                .write_all(message.as_bytes())
-                .await
-                .map_err(|e| ApiError::IoError {
-                    message: format!("Failed to write to stdin: {e}"),
-                })?;
-
-            stdin
-                .write_all(b"\n")
-                .await
-                .map_err(|e| ApiError::IoError {
-                    message: format!("Failed to write newline: {e}"),
-                })?;
-
-            stdin.flush().await.map_err(|e| ApiError::IoError {
-                message: format!("Failed to flush stdin: {e}"),
-            })?;
+        let response_rx = self
+            .send_request(&request_id, Some(session_id.clone()), &request)
+            .await?;
+        lock_unpoisoned(&self.prompt_ids).insert(session_id.clone(), request_id.clone());
+        let sent_at = tokio::time::Instant::now();
 
-            log::debug!("Prompt sent successfully: session={session_id}");
-            Ok(())
-        } else {
-            Err(ApiError::ProtocolError {
-                message: "stdin not available".to_string(),
-            })
+        // Wait briefly for an early rejection; a turn in progress keeps its
+        // pending entry so the eventual response still yields TurnComplete.
+        let accept_window = timeout.map_or(PROMPT_ACCEPT_WINDOW, |timeout| {
+            timeout.min(PROMPT_ACCEPT_WINDOW)
+        });
+        match tokio::time::timeout(accept_window, response_rx).await {
+            Ok(Ok(response)) => {
+                if let Some(error) = response_error(
+                    &response,
+                    &session_id,
+                    "Prompt",
+                    ProtocolErrorKind::RequestFailed,
+                ) {
+                    log::warn!("Prompt rejected by adapter: session={session_id}, error={error}");
+                    return Err(error);
+                }
+            }
+            Ok(Err(_)) => {
+                return Err(ApiError::ProtocolError {
+                    kind: ProtocolErrorKind::ConnectionClosed,
+                    message: "Adapter connection closed before the prompt was accepted".to_string(),
+                });
+            }
+            // The whole timeout went by before the prompt was accepted
+            Err(_) if timeout.is_some_and(|timeout| timeout <= PROMPT_ACCEPT_WINDOW) => {
+                self.end_timed_out_prompt(&session_id, &request_id).await;
+                return Err(ApiError::ProtocolError {
+                    kind: ProtocolErrorKind::RequestTimeout,
+                    message: format!("Prompt timed out after {}ms", accept_window.as_millis()),
+                });
+            }
+            // Still running: the adapter accepted the prompt
+            Err(_) => {
+                self.started_turns
+                    .start(self.host.as_ref(), &self.pending, &session_id);
+                if let Some(timeout) = timeout {
+                    self.spawn_turn_timeout(
+                        session_id.clone(),
+                        request_id.clone(),
+                        sent_at + timeout,
+                        timeout,
+                    );
+                }
+            }
         }
+
+        log::debug!("Prompt sent successfully: session={session_id}, turn={request_id}");
+        Ok(request_id)
     }
 
     async fn cancel_turn(&self, session_id: SessionId) -> Result<(), ApiError> {
         log::info!("Canceling turn for ACP session: {}", session_id);
 
-        let request = serde_json::json!({
+        let Some(request_id) = self.send_cancel(&session_id).await? else {
+            return Ok(());
+        };
+
+        // End the turn now rather than waiting for the adapter's (possibly
+        // late) response; that response is dropped as unknown when it arrives.
+        let Some(pending) = lock_unpoisoned(&self.pending).remove(&request_id) else {
+            return Ok(());
+        };
+        let _ = pending.tx.send(serde_json::json!({
             "jsonrpc": "2.0",
-            "method": METHOD_CANCEL_TURN,
-            "params": {
-                "sessionId": session_id
+            "id": request_id,
+            "result": { "stopReason": "cancelled" }
+        }));
+        self.turn_finished.notify_waiters();
+        self.started_turns.complete(
+            self.host.as_ref(),
+            &session_id,
+            &request_id,
+            StopReason::Cancelled,
+        );
+
+        log::debug!("Cancelled turn resolved: session={session_id}, turn={request_id}");
+        Ok(())
+    }
+
+    async fn new_session(&self) -> Result<SessionId, ApiError> {
+        let session_id = self.open_session().await?;
+        log::info!("ACP session opened: session={session_id}");
+        Ok(session_id)
+    }
+
+    async fn replace_session(&self, lost: SessionId) -> Result<SessionId, ApiError> {
+        let session_id = self.open_session().await?;
+        {
+            // Notifications without a session ID belong to the replacement
+            let mut current = lock_unpoisoned(&self.session_id);
+            if *current == lost {
+                *current = session_id.clone();
             }
+        }
+        log::info!("ACP session replaced: previous={lost}, session={session_id}");
+        Ok(session_id)
+    }
+
+    async fn close_session(&self, session_id: SessionId) -> Result<(), ApiError> {
+        log::info!("Closing ACP session: {session_id}");
+
+        // End the session's turn first so its TurnComplete isn't lost
+        if self.active_turn_sessions().contains(&session_id) {
+            self.cancel_turn(session_id.clone()).await?;
+        }
+        lock_unpoisoned(&self.prompt_ids).remove(&session_id);
+        lock_unpoisoned(&self.available_modes).remove(&session_id);
+
+        let request_id = Uuid::new_v4().to_string();
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": request_id,
+            "method": METHOD_SESSION_CLOSE,
+            "params": { "sessionId": session_id }
         });
 
-        let message = serde_json::to_string(&request).map_err(|e| ApiError::ProtocolError {
-            message: format!("Failed to serialize cancel request: {e}"),
-        })?;
+        let response_rx = self.send_request(&request_id, None, &request).await?;
+        let response = match tokio::time::timeout(REQUEST_TIMEOUT, response_rx).await {
+            Ok(Ok(response)) => response,
+            Ok(Err(_)) => {
+                return Err(ApiError::ProtocolError {
+                    kind: ProtocolErrorKind::ConnectionClosed,
+                    message: "Adapter connection closed during session/close".to_string(),
+                });
+            }
+            Err(_) => {
+                lock_unpoisoned(&self.pending).remove(&request_id);
+                return Err(ApiError::ProtocolError {
+                    kind: ProtocolErrorKind::RequestFailed,
+                    message: "Timed out waiting for session/close response".to_string(),
+                });
+            }
+        };
 
-        let mut stdin_guard = self.stdin.lock().await;
-        if let Some(stdin) = stdin_guard.as_mut() {
-            stdin
-                .write_all(message.as_bytes())
-                .await
-                .map_err(|e| ApiError::IoError {
-                    message: format!("Failed to write to stdin: {e}"),
-                })?;
-
-            stdin
-                .write_all(b"\n")
-                .await
-                .map_err(|e| ApiError::IoError {
-                    message: format!("Failed to write newline: {e}"),
-                })?;
-
-            stdin.flush().await.map_err(|e| ApiError::IoError {
-                message: format!("Failed to flush stdin: {e}"),
-            })?;
+        let code = response
+            .get("error")
+            .and_then(|error| error.get("code"))
+            .and_then(|code| code.as_i64());
+        if code == Some(JSONRPC_METHOD_NOT_FOUND) {
+            // Nothing to release on the adapter side; the session is just unused
+            log::debug!("Adapter does not support session/close: session={session_id}");
+            return Ok(());
+        }
+        match response_error(
+            &response,
+            &session_id,
+            "Session close",
+            ProtocolErrorKind::RequestFailed,
+        ) {
+            // Already gone is as good as closed
+            None | Some(ApiError::SessionNotFound { .. }) => Ok(()),
+            Some(error) => Err(error),
+        }
+    }
 
-            log::debug!("Cancel request sent successfully: session={session_id}");
-            Ok(())
-        } else {
-            Err(ApiError::ProtocolError {
-                message: "stdin not available".to_string(),
-            })
+    async fn set_mode(&self, session_id: SessionId, mode_id: String) -> Result<(), ApiError> {
+        log::info!("Setting ACP session mode: session={session_id}, mode={mode_id}");
+
+        if let Some(available) = lock_unpoisoned(&self.available_modes).get(&session_id) {
+            if !available.contains(&mode_id) {
+                return Err(ApiError::InvalidInput {
+                    message: format!(
+                        "Unknown mode '{mode_id}'; available modes: {}",
+                        available.join(", ")
+                    ),
+                });
+            }
+        }
+
+        let request_id = Uuid::new_v4().to_string();
+        let request = set_mode_request(&request_id, &session_id, &mode_id);
+
+        let response_rx = self.send_request(&request_id, None, &request).await?;
+        let response = match tokio::time::timeout(REQUEST_TIMEOUT, response_rx).await {
+            Ok(Ok(response)) => response,
+            Ok(Err(_)) => {
+                return Err(ApiError::ProtocolError {
+                    kind: ProtocolErrorKind::ConnectionClosed,
+                    message: "Adapter connection closed during session/set_mode".to_string(),
+                });
+            }
+            Err(_) => {
+                lock_unpoisoned(&self.pending).remove(&request_id);
+                return Err(ApiError::ProtocolError {
+                    kind: ProtocolErrorKind::RequestFailed,
+                    message: "Timed out waiting for session/set_mode response".to_string(),
+                });
+            }
+        };
+
+        let code = response
+            .get("error")
+            .and_then(|error| error.get("code"))
+            .and_then(|code| code.as_i64());
+        if code == Some(JSONRPC_METHOD_NOT_FOUND) {
+            return Err(ApiError::ProtocolError {
+                kind: ProtocolErrorKind::Unsupported,
+                message: "Adapter does not support session modes".to_string(),
+            });
         }
+        if let Some(error) = response_error(
+            &response,
+            &session_id,
+            "Set mode",
+            ProtocolErrorKind::RequestFailed,
+        ) {
+            return Err(error);
+        }
+
+        // Adapters only announce mode changes they make themselves
+        self.host.on_session_update(
+            session_id,
+            None,
+            crate::api::types::AcpSessionUpdate::CurrentModeUpdate {
+                current_mode_id: serde_json::json!(mode_id),
+            },
+        );
+        Ok(())
     }
 
     async fn shutdown(&self) -> Result<(), ApiError> {
-        log::info!("Shutting down ACP connection: session={}", self.session_id);
+        log::info!(
+            "Shutting down ACP connection: session={}",
+            self.current_session_id()
+        );
+
+        // Let adapters that honor cancellation wind down before being killed
+        self.cancel_active_turns().await;
+
+        // ACP has no shutdown/exit message; EOF on stdin is the adapter's cue
+        // to flush its state and exit
+        drop(self.stdin.lock().await.take());
+
+        // Requests still being handled can no longer be answered; stop them
+        // and clean up the terminals and prompts they left behind
+        let mut request_tasks = std::mem::take(&mut *lock_unpoisoned(&self.request_tasks));
+        request_tasks.abort_all();
+        while request_tasks.join_next().await.is_some() {}
+        self.host.cancel_in_flight_requests().await;
 
-        // Kill the child process
         // Take the child out of the mutex BEFORE awaiting to avoid holding lock across await
         let child_opt = {
             let mut child_guard = self.child.lock().await;
@@ -456,6 +1057,17 @@ impl AgentConnection for AcpAgent {
         };
 
         if let Some(mut child) = child_opt {
+            match tokio::time::timeout(self.shutdown_grace, child.wait()).await {
+                Ok(Ok(status)) => {
+                    log::debug!("Adapter exited after stdin was closed: status={status}");
+                    return Ok(());
+                }
+                Ok(Err(e)) => log::warn!("Failed to wait for adapter process: {e}"),
+                Err(_) => log::warn!(
+                    "Adapter still running {}ms after stdin was closed, killing it",
+                    self.shutdown_grace.as_millis()
+                ),
+            }
             if let Err(e) = child.kill().await {
                 log::warn!("Failed to kill adapter process: {e}");
             }
@@ -463,66 +1075,514 @@ impl AgentConnection for AcpAgent {
 
         Ok(())
     }
-}
 
-impl Drop for AcpAgent {
-    fn drop(&mut self) {
-        log::debug!("AcpAgent dropped: session={}", self.session_id);
+    fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::SeqCst)
     }
 }
 
-async fn handle_request(
-    host: Arc<dyn AgentHost>,
-    stdin: Arc<Mutex<Option<tokio::process::ChildStdin>>>,
-    method: String,
-    id: serde_json::Value,
-    params: Option<serde_json::Value>,
-    fallback_session_id: SessionId,
-) {
-    let params = params.unwrap_or(serde_json::Value::Null);
-
-    let response = match method.as_str() {
-        METHOD_REQUEST_PERMISSION => {
-            let session_id = extract_string(&params, &["sessionId", "session_id"])
-                .or(Some(fallback_session_id.clone()));
-            let tool_call_id = extract_string(&params, &["toolCallId", "tool_call_id"]);
-            let operation_id = extract_string(&params, &["operationId", "operation_id"]);
-            let command = extract_command(&params)
-                .or_else(|| extract_string(&params, &["summary"]))
-                .unwrap_or_else(|| "terminal command".to_string());
+impl AcpAgent {
+    /// The session currently associated with this connection.
+    fn current_session_id(&self) -> SessionId {
+        lock_unpoisoned(&self.session_id).clone()
+    }
 
-            let request = PermissionRequest {
-                source: PermissionSource::TerminalRun { command },
-                session_id,
-                tool_call_id,
-                operation_id,
-            };
+    /// Create a session with `session/new`, recording the modes it offers.
+    async fn open_session(&self) -> Result<SessionId, ApiError> {
+        let request_id = Uuid::new_v4().to_string();
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": request_id,
+            "method": METHOD_SESSION_NEW,
+            "params": session_params(&self.cwd, &self.mcp_servers)
+        });
 
-            match host.request_permission(request).await {
-                Ok(decision) => {
-                    let decision_value = serde_json::Value::String(match decision {
-                        crate::api::types::PermissionDecision::AllowOnce => "AllowOnce".to_string(),
-                        crate::api::types::PermissionDecision::Deny => "Deny".to_string(),
-                    });
-                    serde_json::json!({
-                        "jsonrpc": "2.0",
-                        "id": id,
-                        "result": decision_value
-                    })
-                }
-                Err(e) => jsonrpc_error(id, -32000, &e.to_string()),
+        let response_rx = self.send_request(&request_id, None, &request).await?;
+        let response = match tokio::time::timeout(REQUEST_TIMEOUT, response_rx).await {
+            Ok(Ok(response)) => response,
+            Ok(Err(_)) => {
+                return Err(ApiError::ProtocolError {
+                    kind: ProtocolErrorKind::ConnectionClosed,
+                    message: "Adapter connection closed during session/new".to_string(),
+                });
+            }
+            Err(_) => {
+                lock_unpoisoned(&self.pending).remove(&request_id);
+                return Err(ApiError::ProtocolError {
+                    kind: ProtocolErrorKind::SessionCreateFailed,
+                    message: "Timed out waiting for session/new response".to_string(),
+                });
             }
+        };
+
+        if let Some(error) = response_error(
+            &response,
+            &self.current_session_id(),
+            "Session creation",
+            ProtocolErrorKind::SessionCreateFailed,
+        ) {
+            return Err(error);
         }
-        METHOD_TERMINAL_RUN => {
-            let command = extract_command(&params);
-            if command.is_none() {
-                jsonrpc_error(id, -32602, "Missing command")
-            } else {
-                let operation_id = extract_string(&params, &["operationId", "operation_id"]);
 
-                let request = TerminalRunRequest {
+        let session_id = response
+            .get("result")
+            .and_then(|r| r.get("sessionId"))
+            .and_then(|s| s.as_str())
+            .map(|s| s.to_string())
+            .ok_or_else(|| ApiError::ProtocolError {
+                kind: ProtocolErrorKind::SessionCreateFailed,
+                message: "session/new response missing sessionId".to_string(),
+            })?;
+
+        if let Some(modes) = session_modes(&response) {
+            report_current_mode(self.host.as_ref(), &session_id, &modes);
+            lock_unpoisoned(&self.available_modes)
+                .insert(session_id.clone(), modes.available_mode_ids);
+        }
+
+        Ok(session_id)
+    }
+
+    /// Sessions with a prompt still awaiting its response.
+    fn active_turn_sessions(&self) -> HashSet<SessionId> {
+        lock_unpoisoned(&self.pending)
+            .values()
+            .filter_map(|request| request.session_id.clone())
+            .collect()
+    }
+
+    /// Send `session/cancel` for a session, naming its in-flight prompt.
+    ///
+    /// `session/cancel` is a notification (no id), so there is no response to
+    /// correlate. Returns the request id of the prompt being cancelled, if one
+    /// is still awaiting its response; it is sent as `requestId` for adapters
+    /// that cancel by request.
+    async fn send_cancel(&self, session_id: &SessionId) -> Result<Option<String>, ApiError> {
+        let request_id = lock_unpoisoned(&self.prompt_ids)
+            .get(session_id)
+            .cloned()
+            .filter(|request_id| lock_unpoisoned(&self.pending).contains_key(request_id));
+
+        let mut params = serde_json::json!({ "sessionId": session_id });
+        if let Some(request_id) = &request_id {
+            params["requestId"] = serde_json::json!(request_id);
+        }
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": METHOD_CANCEL_TURN,
+            "params": params
+        });
+
+        self.write_message(&request).await?;
+
+        log::debug!("Cancel request sent successfully: session={session_id}");
+        Ok(request_id)
+    }
+
+    /// Cancel the prompt `request_id` on `session_id` once `deadline` passes,
+    /// if it's still running then. The turn ends with a `TurnComplete` error.
+    fn spawn_turn_timeout(
+        &self,
+        session_id: SessionId,
+        request_id: String,
+        deadline: tokio::time::Instant,
+        timeout: Duration,
+    ) {
+        let agent = self.this.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep_until(deadline).await;
+            let Some(agent) = agent.upgrade() else {
+                return;
+            };
+            if agent.end_timed_out_prompt(&session_id, &request_id).await {
+                agent.started_turns.fail(
+                    agent.host.as_ref(),
+                    &session_id,
+                    &request_id,
+                    format!("Prompt timed out after {}ms", timeout.as_millis()),
+                );
+            }
+        });
+    }
+
+    /// Ask the adapter to stop a prompt that ran out of time and stop
+    /// waiting for its response. Returns `false` if it had already ended.
+    async fn end_timed_out_prompt(&self, session_id: &SessionId, request_id: &str) -> bool {
+        if !lock_unpoisoned(&self.pending).contains_key(request_id) {
+            return false;
+        }
+        log::warn!("Prompt timed out, cancelling: session={session_id}, turn={request_id}");
+        if let Err(e) = self.send_cancel(session_id).await {
+            log::warn!("Failed to cancel timed out prompt: session={session_id}, error={e}");
+        }
+        // Its late response is dropped as unknown when it arrives
+        if lock_unpoisoned(&self.pending).remove(request_id).is_none() {
+            return false;
+        }
+        self.turn_finished.notify_waiters();
+        true
+    }
+
+    /// Send `session/cancel` for every active turn and wait (up to the grace
+    /// period) for their prompt responses to arrive.
+    async fn cancel_active_turns(&self) {
+        let sessions = self.active_turn_sessions();
+        if sessions.is_empty() {
+            return;
+        }
+
+        for session_id in sessions {
+            // Leave the prompts pending so the grace period below waits for
+            // the adapter to actually wind them down
+            if let Err(e) = self.send_cancel(&session_id).await {
+                log::warn!(
+                    "Failed to cancel turn before shutdown: session={session_id}, error={e}"
+                );
+                return;
+            }
+        }
+
+        let deadline = tokio::time::Instant::now() + self.shutdown_grace;
+        loop {
+            // Register interest before checking so a wakeup can't be missed
+            let notified = self.turn_finished.notified();
+            if self.active_turn_sessions().is_empty() {
+                log::debug!("Active turns finished after cancel, proceeding with shutdown");
+                return;
+            }
+            if tokio::time::timeout_at(deadline, notified).await.is_err() {
+                log::warn!(
+                    "Turns still active after {}ms grace period, killing adapter",
+                    self.shutdown_grace.as_millis()
+                );
+                return;
+            }
+        }
+    }
+
+    /// Send a request on this connection; see [`send_jsonrpc_request`].
+    async fn send_request(
+        &self,
+        request_id: &str,
+        session_id: Option<SessionId>,
+        request: &serde_json::Value,
+    ) -> Result<oneshot::Receiver<serde_json::Value>, ApiError> {
+        trace_frame(
+            self.host.as_ref(),
+            ProtocolTraceDirection::Outbound,
+            request,
+        );
+        let result =
+            send_jsonrpc_request(&self.stdin, &self.pending, request_id, session_id, request).await;
+        self.check_stdin_after_write(result).await
+    }
+
+    /// Write a notification (no response expected) to the adapter.
+    async fn write_message(&self, message: &serde_json::Value) -> Result<(), ApiError> {
+        trace_frame(
+            self.host.as_ref(),
+            ProtocolTraceDirection::Outbound,
+            message,
+        );
+        let result = write_jsonrpc_request(&self.stdin, message).await;
+        self.check_stdin_after_write(result).await
+    }
+
+    /// Flag the connection as lost if a failed write found the adapter's
+    /// stdin closed, so the runtime restarts it instead of retrying writes.
+    async fn check_stdin_after_write<T>(&self, result: Result<T, ApiError>) -> Result<T, ApiError> {
+        if result.is_err() && self.stdin.lock().await.is_none() {
+            self.mark_connection_lost("Adapter closed its stdin");
+        }
+        result
+    }
+
+    /// Treat the adapter as gone: fail pending requests and notify the host once.
+    fn mark_connection_lost(&self, reason: &str) {
+        if self.connected.swap(false, Ordering::SeqCst) {
+            log::warn!(
+                "{reason}, marking connection lost: session={}",
+                self.current_session_id()
+            );
+            lock_unpoisoned(&self.pending).clear();
+            self.host.on_connection_lost();
+        }
+    }
+}
+
+impl AcpAgent {
+    /// Send a keepalive ping and wait for any response.
+    ///
+    /// # Returns
+    /// * `true` if the adapter answered within `timeout`
+    async fn ping(&self, timeout: Duration) -> bool {
+        let request_id = Uuid::new_v4().to_string();
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": request_id,
+            "method": METHOD_PING,
+            "params": {}
+        });
+
+        let Ok(response_rx) = self.send_request(&request_id, None, &request).await else {
+            return false;
+        };
+        match tokio::time::timeout(timeout, response_rx).await {
+            Ok(response) => response.is_ok(),
+            Err(_) => {
+                lock_unpoisoned(&self.pending).remove(&request_id);
+                false
+            }
+        }
+    }
+}
+
+/// Spawn the adapter, retrying with backoff while spawning fails transiently.
+async fn spawn_adapter(command: &mut Command) -> std::io::Result<Child> {
+    let mut attempt = 1;
+    loop {
+        match command.spawn() {
+            Ok(child) => return Ok(child),
+            Err(e) if attempt < SPAWN_ATTEMPTS && is_transient_spawn_error(&e) => {
+                let delay = SPAWN_RETRY_BASE_DELAY * 2u32.pow(attempt - 1);
+                log::warn!(
+                    "Adapter spawn failed, retrying: attempt={attempt}, delay={delay:?}, error={e}"
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Whether a spawn failure may succeed when retried: the OS is briefly out of
+/// processes (`EAGAIN`), the call was interrupted, or the binary was just
+/// written and is still open for writing (`ETXTBSY`, e.g. mid-install).
+fn is_transient_spawn_error(error: &std::io::Error) -> bool {
+    #[cfg(unix)]
+    const ETXTBSY: i32 = 26;
+
+    if matches!(
+        error.kind(),
+        std::io::ErrorKind::WouldBlock | std::io::ErrorKind::Interrupted
+    ) {
+        return true;
+    }
+    #[cfg(unix)]
+    if error.raw_os_error() == Some(ETXTBSY) {
+        return true;
+    }
+    false
+}
+
+/// Ping the adapter every `keepalive.interval` until the agent is dropped,
+/// shut down or disconnected. An unanswered ping marks the connection lost
+/// and kills the (presumably wedged) adapter.
+fn spawn_keepalive(agent: std::sync::Weak<AcpAgent>, keepalive: AcpKeepalive) {
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(keepalive.interval).await;
+            let Some(agent) = agent.upgrade() else {
+                break;
+            };
+            if !agent.is_connected() || agent.child.lock().await.is_none() {
+                break;
+            }
+            if agent.ping(keepalive.timeout).await {
+                continue;
+            }
+
+            agent.mark_connection_lost(&format!(
+                "Adapter did not answer a keepalive ping within {}ms",
+                keepalive.timeout.as_millis()
+            ));
+            let child = agent.child.lock().await.take();
+            if let Some(mut child) = child {
+                if let Err(e) = child.kill().await {
+                    log::warn!("Failed to kill unresponsive adapter: {e}");
+                }
+            }
+            break;
+        }
+    });
+}
+
+impl Drop for AcpAgent {
+    fn drop(&mut self) {
+        log::debug!("AcpAgent dropped: session={}", self.current_session_id());
+    }
+}
+
+/// Convert a prompt block to the ACP `ContentBlock` wire shape.
+///
+/// Resources are sent as `resource_link` blocks, which only need a URI and a
+/// display name (the adapter reads the content itself).
+fn acp_content_block(block: &PromptContentBlock) -> serde_json::Value {
+    match block {
+        PromptContentBlock::Text { text } => serde_json::json!({
+            "type": "text",
+            "text": text
+        }),
+        PromptContentBlock::Image { mime_type, data } => serde_json::json!({
+            "type": "image",
+            "mimeType": mime_type,
+            "data": data
+        }),
+        PromptContentBlock::Resource { uri } => serde_json::json!({
+            "type": "resource_link",
+            "uri": uri,
+            "name": resource_name(uri)
+        }),
+    }
+}
+
+/// Last path segment of a URI, used as the resource link's display name.
+fn resource_name(uri: &str) -> &str {
+    uri.trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .filter(|name| !name.is_empty())
+        .unwrap_or(uri)
+}
+
+/// Lock a std mutex, recovering the data if a previous holder panicked.
+fn lock_unpoisoned<T>(mutex: &std::sync::Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Normalize a JSON-RPC id (string or number) into a map key.
+fn jsonrpc_id_key(id: &serde_json::Value) -> Option<String> {
+    match id {
+        serde_json::Value::String(s) => Some(s.clone()),
+        serde_json::Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// Convert the `error` member of a JSON-RPC response into an `ApiError`.
+///
+/// Returns `None` for successful responses. Errors that reference an unknown
+/// session become `SessionNotFound` so the runtime can recover.
+fn response_error(
+    response: &serde_json::Value,
+    session_id: &SessionId,
+    context: &str,
+    kind: ProtocolErrorKind,
+) -> Option<ApiError> {
+    let error = response.get("error")?;
+    let code = error.get("code").and_then(|c| c.as_i64()).unwrap_or(-1);
+    let message = error
+        .get("message")
+        .and_then(|m| m.as_str())
+        .unwrap_or("Unknown error");
+
+    let lowered = message.to_lowercase();
+    let mentions_session = lowered.contains("session");
+    let session_missing = mentions_session
+        && (code == JSONRPC_RESOURCE_NOT_FOUND
+            || lowered.contains("not found")
+            || lowered.contains("unknown"));
+
+    if session_missing {
+        return Some(ApiError::SessionNotFound {
+            session_id: session_id.clone(),
+        });
+    }
+
+    Some(ApiError::ProtocolError {
+        kind,
+        message: format!("{context} failed: code={code}, message={message}"),
+    })
+}
+
+/// Append the adapter's captured stderr (truncated) to a startup error message.
+fn with_startup_stderr(message: String, stderr_lines: &[String]) -> String {
+    let stderr = stderr_lines.join("\n");
+    let stderr = stderr.trim();
+    if stderr.is_empty() {
+        return message;
+    }
+
+    let kept = truncate_on_char_boundary(stderr, STARTUP_STDERR_MAX_BYTES);
+    let ellipsis = if kept.len() < stderr.len() { "…" } else { "" };
+    format!("{message}; adapter stderr:\n{kept}{ellipsis}")
+}
+
+/// The `stopReason` of a prompt response, or `None` for other responses.
+/// Values that aren't strings are kept as their JSON text in `Other`.
+fn parse_stop_reason(result: &serde_json::Value) -> Option<StopReason> {
+    let value = result
+        .get("stopReason")
+        .or_else(|| result.get("stop_reason"))?;
+    match serde_json::from_value(value.clone()) {
+        Ok(stop_reason) => Some(stop_reason),
+        Err(e) => {
+            log::debug!("Failed to parse prompt response stopReason: {e}");
+            Some(StopReason::Other(value.to_string()))
+        }
+    }
+}
+
+async fn handle_request(
+    host: Arc<dyn AgentHost>,
+    stdin: Arc<Mutex<Option<tokio::process::ChildStdin>>>,
+    method: String,
+    id: serde_json::Value,
+    params: Option<serde_json::Value>,
+    fallback_session_id: SessionId,
+) {
+    let params = params.unwrap_or(serde_json::Value::Null);
+
+    let response = match method.as_str() {
+        METHOD_REQUEST_PERMISSION => {
+            let session_id = extract_string(&params, &["sessionId", "session_id"])
+                .or(Some(fallback_session_id.clone()));
+            let tool_call_id = extract_string(&params, &["toolCallId", "tool_call_id"]);
+            let operation_id = extract_string(&params, &["operationId", "operation_id"]);
+            let command = extract_command(&params)
+                .or_else(|| extract_string(&params, &["summary"]))
+                .unwrap_or_else(|| "terminal command".to_string());
+
+            let options = extract_permission_options(&params);
+
+            let request = PermissionRequest {
+                source: PermissionSource::TerminalRun { command },
+                session_id,
+                tool_call_id,
+                operation_id,
+                options,
+            };
+
+            match host.request_permission(request).await {
+                Ok(outcome) => serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "result": permission_result(&outcome)
+                }),
+                Err(e) => jsonrpc_error(id, -32000, &e.to_string()),
+            }
+        }
+        METHOD_TERMINAL_RUN => {
+            let command = extract_command(&params);
+            if command.is_none() {
+                jsonrpc_error(id, -32602, "Missing command")
+            } else {
+                let operation_id = extract_string(&params, &["operationId", "operation_id"]);
+
+                let request = TerminalRunRequest {
                     command: command.unwrap_or_default(),
+                    env: extract_env(&params),
+                    timeout_ms: params
+                        .get("timeoutMs")
+                        .or_else(|| params.get("timeout_ms"))
+                        .and_then(|timeout| timeout.as_u64()),
                     operation_id,
+                    capture_output: params
+                        .get("captureOutput")
+                        .or_else(|| params.get("capture_output"))
+                        .and_then(|capture| capture.as_bool())
+                        .unwrap_or(true),
                 };
 
                 match host.terminal_run(request).await {
@@ -532,6 +1592,7 @@ async fn handle_request(
                         "result": {
                             "terminalId": result.terminal_id,
                             "exitCode": result.exit_code,
+                            "timedOut": result.timed_out,
                             "stdout": result.stdout,
                             "stderr": result.stderr
                         }
@@ -540,18 +1601,88 @@ async fn handle_request(
                 }
             }
         }
+        METHOD_TERMINAL_CREATE => match extract_terminal_create(&params) {
+            None => jsonrpc_error(id, -32602, "Missing command"),
+            Some(request) => match host.terminal_create(request).await {
+                Ok(terminal_id) => serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "result": { "terminalId": terminal_id }
+                }),
+                Err(e) => jsonrpc_error(id, -32000, &e.to_string()),
+            },
+        },
+        METHOD_TERMINAL_OUTPUT
+        | METHOD_TERMINAL_WAIT_FOR_EXIT
+        | METHOD_TERMINAL_KILL
+        | METHOD_TERMINAL_RELEASE => {
+            match extract_string(&params, &["terminalId", "terminal_id"]) {
+                None => jsonrpc_error(id, -32602, "Missing terminalId"),
+                Some(terminal_id) => {
+                    let result = match method.as_str() {
+                        METHOD_TERMINAL_OUTPUT => {
+                            host.terminal_output(terminal_id).await.map(|output| {
+                                serde_json::json!({
+                                    "output": output.output,
+                                    "truncated": output.truncated,
+                                    "exitStatus": output.exit_status.as_ref().map(exit_status_json)
+                                })
+                            })
+                        }
+                        METHOD_TERMINAL_WAIT_FOR_EXIT => host
+                            .terminal_wait_for_exit(terminal_id)
+                            .await
+                            .map(|status| exit_status_json(&status)),
+                        METHOD_TERMINAL_KILL => host
+                            .terminal_kill(terminal_id)
+                            .await
+                            .map(|()| serde_json::json!({})),
+                        _ => host
+                            .terminal_release(terminal_id)
+                            .await
+                            .map(|()| serde_json::json!({})),
+                    };
+
+                    match result {
+                        Ok(result) => serde_json::json!({
+                            "jsonrpc": "2.0",
+                            "id": id,
+                            "result": result
+                        }),
+                        Err(e) => jsonrpc_error(id, -32000, &e.to_string()),
+                    }
+                }
+            }
+        }
         METHOD_FS_READ_TEXT_FILE | METHOD_FS_READ_TEXT_FILE_ALIAS => {
             let path = extract_path(&params);
+            let encoding_hint = extract_string(&params, &["encoding"]);
+            let encoding = read_encoding(encoding_hint.as_deref());
             if path.is_none() {
                 jsonrpc_error(id, -32602, "Missing path")
+            } else if encoding.is_none() {
+                jsonrpc_error(
+                    id,
+                    -32602,
+                    &format!(
+                        "Unsupported encoding: {}",
+                        encoding_hint.unwrap_or_default()
+                    ),
+                )
             } else {
                 let session_id = extract_string(&params, &["sessionId", "session_id"])
                     .or(Some(fallback_session_id.clone()));
                 let tool_call_id = extract_string(&params, &["toolCallId", "tool_call_id"]);
                 let operation_id = extract_string(&params, &["operationId", "operation_id"]);
 
+                let line = extract_u32(&params, "line");
+                let limit = extract_u32(&params, "limit");
+
                 let request = FsReadTextFileRequest {
                     path: path.unwrap_or_default(),
+                    line,
+                    limit,
+                    encoding: encoding.unwrap_or_default(),
                     session_id,
                     tool_call_id,
                     operation_id,
@@ -561,15 +1692,15 @@ async fn handle_request(
                     Ok(result) => serde_json::json!({
                         "jsonrpc": "2.0",
                         "id": id,
-                        "result": {
-                            "content": result.content
-                        }
+                        "result": fs_read_result_json(&result)
                     }),
                     Err(e) => jsonrpc_error(id, -32000, &e.to_string()),
                 }
             }
         }
-        METHOD_FS_WRITE_TEXT_FILE | METHOD_FS_WRITE_TEXT_FILE_ALIAS => {
+        METHOD_FS_WRITE_TEXT_FILE
+        | METHOD_FS_WRITE_TEXT_FILE_ALIAS
+        | METHOD_FS_APPEND_TEXT_FILE => {
             let path = extract_path(&params);
             let content = extract_content(&params);
             if path.is_none() {
@@ -585,13 +1716,36 @@ async fn handle_request(
                 let request = FsWriteTextFileRequest {
                     path: path.unwrap_or_default(),
                     content: content.unwrap_or_default(),
+                    append: method == METHOD_FS_APPEND_TEXT_FILE,
                     session_id,
                     tool_call_id,
                     operation_id,
                 };
 
                 match host.fs_write_text_file(request).await {
-                    Ok(_) => serde_json::json!({
+                    Ok(result) => serde_json::json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": fs_write_result_json(&result)
+                    }),
+                    Err(e) => jsonrpc_error(id, -32000, &e.to_string()),
+                }
+            }
+        }
+        METHOD_FS_DELETE => match extract_path(&params) {
+            None => jsonrpc_error(id, -32602, "Missing path"),
+            Some(path) => {
+                let request = FsDeleteRequest {
+                    path,
+                    recursive: extract_bool(&params, "recursive"),
+                    session_id: extract_string(&params, &["sessionId", "session_id"])
+                        .or(Some(fallback_session_id.clone())),
+                    tool_call_id: extract_string(&params, &["toolCallId", "tool_call_id"]),
+                    operation_id: extract_string(&params, &["operationId", "operation_id"]),
+                };
+
+                match host.fs_delete(request).await {
+                    Ok(()) => serde_json::json!({
                         "jsonrpc": "2.0",
                         "id": id,
                         "result": {}
@@ -599,10 +1753,38 @@ async fn handle_request(
                     Err(e) => jsonrpc_error(id, -32000, &e.to_string()),
                 }
             }
+        },
+        METHOD_FS_RENAME => {
+            let from = extract_string(&params, &["from", "oldPath", "old_path"]);
+            let to = extract_string(&params, &["to", "newPath", "new_path"]);
+            match (from, to) {
+                (Some(from), Some(to)) => {
+                    let request = FsRenameRequest {
+                        from,
+                        to,
+                        recursive: extract_bool(&params, "recursive"),
+                        session_id: extract_string(&params, &["sessionId", "session_id"])
+                            .or(Some(fallback_session_id.clone())),
+                        tool_call_id: extract_string(&params, &["toolCallId", "tool_call_id"]),
+                        operation_id: extract_string(&params, &["operationId", "operation_id"]),
+                    };
+
+                    match host.fs_rename(request).await {
+                        Ok(()) => serde_json::json!({
+                            "jsonrpc": "2.0",
+                            "id": id,
+                            "result": {}
+                        }),
+                        Err(e) => jsonrpc_error(id, -32000, &e.to_string()),
+                    }
+                }
+                _ => jsonrpc_error(id, -32602, "Missing from or to"),
+            }
         }
-        _ => jsonrpc_error(id, -32601, "Method not found"),
+        _ => jsonrpc_error(id, JSONRPC_METHOD_NOT_FOUND, "Method not found"),
     };
 
+    trace_frame(host.as_ref(), ProtocolTraceDirection::Outbound, &response);
     if let Err(e) = send_jsonrpc_response(&stdin, response).await {
         log::warn!("Failed to send JSON-RPC response: {e}");
     }
@@ -616,6 +1798,163 @@ fn extract_command(params: &serde_json::Value) -> Option<String> {
     })
 }
 
+/// Parse `terminal/create` params (`command`, `args`, `outputByteLimit`).
+fn extract_terminal_create(params: &serde_json::Value) -> Option<TerminalCreateRequest> {
+    let command = extract_command(params)?;
+    let args = params
+        .get("args")
+        .and_then(|args| args.as_array())
+        .map(|args| {
+            args.iter()
+                .filter_map(|arg| arg.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default();
+    let output_byte_limit = params
+        .get("outputByteLimit")
+        .or_else(|| params.get("output_byte_limit"))
+        .and_then(|limit| limit.as_u64())
+        .map(|limit| limit as usize);
+
+    Some(TerminalCreateRequest {
+        command,
+        args,
+        env: extract_env(params),
+        cwd: extract_string(params, &["cwd"]).map(PathBuf::from),
+        output_byte_limit,
+        operation_id: extract_string(params, &["operationId", "operation_id"]),
+    })
+}
+
+/// Parse an `env` param, given either as an object map or as ACP's
+/// `[{name, value}]` array. Entries without a string value are dropped.
+fn extract_env(params: &serde_json::Value) -> Option<HashMap<String, String>> {
+    let env = params.get("env")?;
+    let vars = if let Some(map) = env.as_object() {
+        map.iter()
+            .filter_map(|(name, value)| Some((name.clone(), value.as_str()?.to_string())))
+            .collect()
+    } else {
+        env.as_array()?
+            .iter()
+            .filter_map(|var| {
+                let name = var.get("name")?.as_str()?;
+                let value = var.get("value")?.as_str()?;
+                Some((name.to_string(), value.to_string()))
+            })
+            .collect()
+    };
+    Some(vars)
+}
+
+/// ACP exit status object; `signal` is the signal's name (e.g. `SIGKILL`)
+/// when the command was terminated by one.
+fn exit_status_json(status: &TerminalExitStatus) -> serde_json::Value {
+    serde_json::json!({
+        "exitCode": status.exit_code,
+        "signal": status.signal.map(signal_name)
+    })
+}
+
+/// `fs/read_text_file` result, citing the file that was actually read.
+fn fs_read_result_json(result: &FsReadTextFileResult) -> serde_json::Value {
+    serde_json::json!({
+        "content": result.content,
+        "encoding": match result.encoding {
+            FsContentEncoding::Text => "utf8",
+            FsContentEncoding::Base64 => "base64",
+        },
+        "path": result.path,
+        "absolutePath": result.absolute_path.to_string_lossy(),
+    })
+}
+
+/// `fs/write_text_file` result, citing the file that was actually written.
+fn fs_write_result_json(result: &FsWriteTextFileResult) -> serde_json::Value {
+    serde_json::json!({
+        "bytesWritten": result.bytes_written,
+        "path": result.path,
+        "absolutePath": result.absolute_path.to_string_lossy(),
+    })
+}
+
+fn signal_name(signal: i32) -> String {
+    match signal {
+        1 => "SIGHUP".to_string(),
+        2 => "SIGINT".to_string(),
+        3 => "SIGQUIT".to_string(),
+        6 => "SIGABRT".to_string(),
+        9 => "SIGKILL".to_string(),
+        11 => "SIGSEGV".to_string(),
+        13 => "SIGPIPE".to_string(),
+        15 => "SIGTERM".to_string(),
+        other => format!("SIG{other}"),
+    }
+}
+
+/// Parse the adapter's `options` array (`{optionId, name, kind}`).
+///
+/// Entries with a missing id or unknown kind are dropped; an empty result
+/// means the request falls back to a plain allow/deny prompt.
+fn extract_permission_options(params: &serde_json::Value) -> Vec<PermissionOption> {
+    let Some(options) = params.get("options").and_then(|o| o.as_array()) else {
+        return Vec::new();
+    };
+
+    options
+        .iter()
+        .filter_map(|option| {
+            let option_id = extract_string(option, &["optionId", "option_id", "id"])?;
+            let kind = option
+                .get("kind")
+                .and_then(|k| k.as_str())
+                .and_then(PermissionOptionKind::from_acp)?;
+            let name = extract_string(option, &["name"]).unwrap_or_else(|| option_id.clone());
+            Some(PermissionOption {
+                option_id,
+                name,
+                kind,
+            })
+        })
+        .collect()
+}
+
+/// JSON-RPC result for a permission decision.
+///
+/// When the adapter offered options, the chosen one is echoed back as an ACP
+/// `selected` outcome; otherwise the legacy "AllowOnce"/"Deny" string is sent.
+fn permission_result(outcome: &PermissionOutcome) -> serde_json::Value {
+    match &outcome.option_id {
+        Some(option_id) => serde_json::json!({
+            "outcome": {
+                "outcome": "selected",
+                "optionId": option_id
+            }
+        }),
+        None => serde_json::Value::String(
+            match outcome.decision {
+                // Remembering is our side's job; legacy agents only know once/deny
+                PermissionDecision::AllowOnce | PermissionDecision::AllowAlways => "AllowOnce",
+                PermissionDecision::Deny => "Deny",
+            }
+            .to_string(),
+        ),
+    }
+}
+
+/// The prompt turn an adapter attributes a session update to, if it says.
+///
+/// Adapters that track turns echo our `session/prompt` request id, either on
+/// the notification params or on the update itself.
+fn extract_turn_id(params: &serde_json::Value) -> Option<TurnId> {
+    const KEYS: &[&str] = &["turnId", "turn_id"];
+    extract_string(params, KEYS).or_else(|| {
+        params
+            .get("update")
+            .and_then(|update| extract_string(update, KEYS))
+    })
+}
+
 fn extract_path(params: &serde_json::Value) -> Option<String> {
     extract_string(params, &["path", "filePath", "file_path"]).or_else(|| {
         params
@@ -641,6 +1980,32 @@ fn extract_string(params: &serde_json::Value, keys: &[&str]) -> Option<String> {
     })
 }
 
+/// Map a read's `encoding` hint (`utf8`, the default, or `base64`), or
+/// `None` if the hint is neither.
+///
+/// A `utf8` read of a binary file still comes back as base64.
+fn read_encoding(hint: Option<&str>) -> Option<FsReadEncoding> {
+    match hint {
+        None | Some("utf8") => Some(FsReadEncoding::Text),
+        Some("base64") => Some(FsReadEncoding::Base64),
+        Some(_) => None,
+    }
+}
+
+fn extract_u32(params: &serde_json::Value, key: &str) -> Option<u32> {
+    params
+        .get(key)
+        .and_then(|value| value.as_u64())
+        .map(|value| value.min(u32::MAX as u64) as u32)
+}
+
+fn extract_bool(params: &serde_json::Value, key: &str) -> bool {
+    params
+        .get(key)
+        .and_then(|value| value.as_bool())
+        .unwrap_or(false)
+}
+
 fn jsonrpc_error(id: serde_json::Value, code: i64, message: &str) -> serde_json::Value {
     serde_json::json!({
         "jsonrpc": "2.0",
@@ -657,6 +2022,7 @@ async fn send_jsonrpc_response(
     response: serde_json::Value,
 ) -> Result<(), ApiError> {
     let message = serde_json::to_string(&response).map_err(|e| ApiError::ProtocolError {
+        kind: ProtocolErrorKind::SerializationFailed,
         message: format!("Failed to serialize JSON-RPC response: {e}"),
     })?;
 
@@ -680,23 +2046,31 @@ async fn send_jsonrpc_response(
         Ok(())
     } else {
         Err(ApiError::ProtocolError {
+            kind: ProtocolErrorKind::ConnectionClosed,
             message: "stdin not available".to_string(),
         })
     }
 }
 
-/// Perform ACP handshake: initialize → session/new
+/// Perform ACP handshake: initialize → session/load or session/new
 ///
 /// This sends the required ACP protocol messages to establish a session:
 /// 1. `initialize` - Handshake with protocol version and capabilities
-/// 2. `session/new` - Create a new session with workspace cwd
+/// 2. `session/load` - Resume `resume_session_id`, if given and the adapter
+///    advertises `loadSession`
+/// 3. `session/new` - Create a new session with workspace cwd (when not resumed)
 ///
-/// Returns the adapter-issued session ID.
+/// Returns the adapter-issued (or resumed) session ID and the negotiated
+/// protocol version.
 async fn perform_acp_handshake(
+    host: &dyn AgentHost,
     stdin: &Arc<Mutex<Option<tokio::process::ChildStdin>>>,
-    stdout: &mut BufReader<tokio::process::ChildStdout>,
+    pending: &PendingRequests,
     cwd: &std::path::Path,
-) -> Result<SessionId, ApiError> {
+    resume_session_id: Option<SessionId>,
+    mcp_servers: &[McpServerConfig],
+    step_timeout: Duration,
+) -> Result<Handshake, ApiError> {
     // Step 1: Send initialize request
     let init_id = Uuid::new_v4().to_string();
     let init_request = serde_json::json!({
@@ -704,13 +2078,14 @@ async fn perform_acp_handshake(
         "id": init_id,
         "method": METHOD_INITIALIZE,
         "params": {
-            "protocolVersion": 1,
+            "protocolVersion": PROTOCOL_VERSION,
             "clientCapabilities": {
                 "sampling": {},
                 "roots": { "listChanged": false },
                 "prompts": {},
                 "tools": {},
-                "logging": {}
+                "logging": {},
+                "terminal": true
             },
             "clientInfo": {
                 "name": "tauri-acp-template",
@@ -719,11 +2094,14 @@ async fn perform_acp_handshake(
         }
     });
 
-    write_jsonrpc_request(stdin, &init_request).await?;
+    trace_frame(host, ProtocolTraceDirection::Outbound, &init_request);
+    let init_rx = send_jsonrpc_request(stdin, pending, &init_id, None, &init_request).await?;
     log::debug!("Sent initialize request: id={init_id}");
 
     // Wait for initialize response
-    let init_response = read_jsonrpc_response(stdout, &init_id).await?;
+    let init_response =
+        await_handshake_response(pending, &init_id, init_rx, METHOD_INITIALIZE, step_timeout)
+            .await?;
     log::debug!("Received initialize response: {init_response}");
 
     // Check for error in response
@@ -734,28 +2112,79 @@ async fn perform_acp_handshake(
             .and_then(|m| m.as_str())
             .unwrap_or("Unknown error");
         return Err(ApiError::ProtocolError {
+            kind: ProtocolErrorKind::InitializeRejected,
             message: format!("Initialize failed: code={code}, message={message}"),
         });
     }
 
-    // Step 2: Send session/new request
+    let protocol_version = negotiate_protocol_version(&init_response)?;
+    log::info!("Negotiated ACP protocol version: version={protocol_version}");
+
+    // Step 2: Try to resume the previous session
+    if let Some(resume_session_id) = resume_session_id {
+        let supports_load = init_response
+            .get("result")
+            .and_then(|r| r.get("agentCapabilities"))
+            .and_then(|c| c.get("loadSession"))
+            .and_then(|l| l.as_bool())
+            .unwrap_or(false);
+
+        if !supports_load {
+            log::info!(
+                "Adapter does not support session/load, starting a new session: previous={resume_session_id}"
+            );
+        } else if let Some(load_response) = load_session(
+            host,
+            stdin,
+            pending,
+            cwd,
+            &resume_session_id,
+            mcp_servers,
+            step_timeout,
+        )
+        .await?
+        {
+            return Ok(Handshake {
+                session_id: resume_session_id,
+                protocol_version,
+                modes: session_modes(&load_response),
+            });
+        }
+    }
+
+    // Step 3: Send session/new request
     let session_id_request = Uuid::new_v4().to_string();
-    let cwd_str = cwd.to_string_lossy().to_string();
     let session_new_request = serde_json::json!({
         "jsonrpc": "2.0",
         "id": session_id_request,
         "method": METHOD_SESSION_NEW,
-        "params": {
-            "cwd": cwd_str,
-            "mcpServers": []
-        }
+        "params": session_params(cwd, mcp_servers)
     });
 
-    write_jsonrpc_request(stdin, &session_new_request).await?;
-    log::debug!("Sent session/new request: id={session_id_request}, cwd={cwd_str}");
+    trace_frame(host, ProtocolTraceDirection::Outbound, &session_new_request);
+    let session_rx = send_jsonrpc_request(
+        stdin,
+        pending,
+        &session_id_request,
+        None,
+        &session_new_request,
+    )
+    .await?;
+    log::debug!(
+        "Sent session/new request: id={session_id_request}, cwd={}, mcp_servers={}",
+        cwd.display(),
+        mcp_servers.len()
+    );
 
     // Wait for session/new response
-    let session_response = read_jsonrpc_response(stdout, &session_id_request).await?;
+    let session_response = await_handshake_response(
+        pending,
+        &session_id_request,
+        session_rx,
+        METHOD_SESSION_NEW,
+        step_timeout,
+    )
+    .await?;
     log::debug!("Received session/new response: {session_response}");
 
     // Check for error in response
@@ -766,6 +2195,7 @@ async fn perform_acp_handshake(
             .and_then(|m| m.as_str())
             .unwrap_or("Unknown error");
         return Err(ApiError::ProtocolError {
+            kind: ProtocolErrorKind::SessionCreateFailed,
             message: format!("Session creation failed: code={code}, message={message}"),
         });
     }
@@ -777,94 +2207,2018 @@ async fn perform_acp_handshake(
         .and_then(|s| s.as_str())
         .map(|s| s.to_string())
         .ok_or_else(|| ApiError::ProtocolError {
+            kind: ProtocolErrorKind::SessionCreateFailed,
             message: "session/new response missing sessionId".to_string(),
         })?;
 
-    Ok(session_id)
+    Ok(Handshake {
+        session_id,
+        protocol_version,
+        modes: session_modes(&session_response),
+    })
+}
+
+/// Read the `modes` of a `session/new` or `session/load` response.
+///
+/// Returns `None` for adapters without modes.
+fn session_modes(response: &serde_json::Value) -> Option<SessionModes> {
+    let modes = response.get("result")?.get("modes")?;
+    let available_mode_ids = modes
+        .get("availableModes")?
+        .as_array()?
+        .iter()
+        .filter_map(|mode| mode.get("id").and_then(|id| id.as_str()))
+        .map(str::to_string)
+        .collect();
+    Some(SessionModes {
+        current_mode_id: modes
+            .get("currentModeId")
+            .and_then(|id| id.as_str())
+            .map(str::to_string),
+        available_mode_ids,
+    })
+}
+
+/// Tell the host which mode a new or resumed session starts in.
+fn report_current_mode(host: &dyn AgentHost, session_id: &SessionId, modes: &SessionModes) {
+    if let Some(mode_id) = &modes.current_mode_id {
+        host.on_session_update(
+            session_id.clone(),
+            None,
+            crate::api::types::AcpSessionUpdate::CurrentModeUpdate {
+                current_mode_id: serde_json::json!(mode_id),
+            },
+        );
+    }
+}
+
+/// Build the `session/set_mode` request switching `session_id` to `mode_id`.
+fn set_mode_request(request_id: &str, session_id: &SessionId, mode_id: &str) -> serde_json::Value {
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": request_id,
+        "method": METHOD_SESSION_SET_MODE,
+        "params": { "sessionId": session_id, "modeId": mode_id }
+    })
+}
+
+/// Shared `session/new` / `session/load` params: working directory and MCP servers.
+fn session_params(cwd: &std::path::Path, mcp_servers: &[McpServerConfig]) -> serde_json::Value {
+    let servers: Vec<serde_json::Value> = mcp_servers.iter().map(acp_mcp_server).collect();
+    serde_json::json!({
+        "cwd": cwd.to_string_lossy(),
+        "mcpServers": servers
+    })
+}
+
+/// Convert an MCP server config into ACP's stdio `McpServer` shape.
+fn acp_mcp_server(server: &McpServerConfig) -> serde_json::Value {
+    let env: Vec<serde_json::Value> = server
+        .env
+        .iter()
+        .map(|var| serde_json::json!({ "name": var.name, "value": var.value }))
+        .collect();
+    serde_json::json!({
+        "name": server.name,
+        "command": server.command,
+        "args": server.args,
+        "env": env
+    })
+}
+
+/// Read the protocol version the adapter chose from its `initialize` response.
+///
+/// The adapter answers with the version it will speak (ours, or an older one
+/// it prefers); anything outside the range this client implements is an error.
+fn negotiate_protocol_version(init_response: &serde_json::Value) -> Result<u64, ApiError> {
+    let version = init_response
+        .get("result")
+        .and_then(|r| r.get("protocolVersion"))
+        .and_then(|v| v.as_u64())
+        .ok_or_else(|| ApiError::ProtocolError {
+            kind: ProtocolErrorKind::InitializeRejected,
+            message: "initialize response missing protocolVersion".to_string(),
+        })?;
+
+    if !(MIN_PROTOCOL_VERSION..=PROTOCOL_VERSION).contains(&version) {
+        return Err(ApiError::ProtocolError {
+            kind: ProtocolErrorKind::InitializeRejected,
+            message: format!(
+                "Adapter requires unsupported ACP protocol version {version} (supported: {MIN_PROTOCOL_VERSION}-{PROTOCOL_VERSION})"
+            ),
+        });
+    }
+
+    Ok(version)
+}
+
+/// Ask the adapter to resume `session_id` via `session/load`.
+///
+/// Returns the adapter's response once the session is resumed, or `Ok(None)`
+/// if the adapter rejects the request (e.g. the session is gone), so the
+/// caller can fall back to `session/new`.
+async fn load_session(
+    host: &dyn AgentHost,
+    stdin: &Arc<Mutex<Option<tokio::process::ChildStdin>>>,
+    pending: &PendingRequests,
+    cwd: &std::path::Path,
+    session_id: &SessionId,
+    mcp_servers: &[McpServerConfig],
+    step_timeout: Duration,
+) -> Result<Option<serde_json::Value>, ApiError> {
+    let mut params = session_params(cwd, mcp_servers);
+    params["sessionId"] = serde_json::json!(session_id);
+    let request_id = Uuid::new_v4().to_string();
+    let request = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": request_id,
+        "method": METHOD_SESSION_LOAD,
+        "params": params
+    });
+
+    trace_frame(host, ProtocolTraceDirection::Outbound, &request);
+    let response_rx = send_jsonrpc_request(stdin, pending, &request_id, None, &request).await?;
+    log::debug!("Sent session/load request: id={request_id}, session={session_id}");
+
+    // The adapter replays the conversation as session/update notifications
+    // before answering, which the stdout reader forwards to the host
+    let response = await_handshake_response(
+        pending,
+        &request_id,
+        response_rx,
+        METHOD_SESSION_LOAD,
+        step_timeout,
+    )
+    .await?;
+
+    if let Some(error) = response_error(
+        &response,
+        session_id,
+        "Session load",
+        ProtocolErrorKind::SessionLoadFailed,
+    ) {
+        log::warn!(
+            "session/load rejected, starting a new session: session={session_id}, error={error}"
+        );
+        return Ok(None);
+    }
+
+    log::info!("Resumed ACP session: session={session_id}");
+    Ok(Some(response))
+}
+
+/// Pass a frame to the host if it has protocol tracing turned on.
+fn trace_frame(host: &dyn AgentHost, direction: ProtocolTraceDirection, frame: &serde_json::Value) {
+    if host.protocol_trace_enabled() {
+        host.on_protocol_trace(direction, redact_frame(frame).to_string());
+    }
+}
+
+/// Copy of a frame with bulky content replaced by a size note: binary
+/// payloads (`data`, `blob`) always, other strings beyond
+/// `TRACE_STRING_LIMIT` bytes.
+fn redact_frame(frame: &serde_json::Value) -> serde_json::Value {
+    match frame {
+        serde_json::Value::Object(map) => map
+            .iter()
+            .map(|(key, value)| {
+                let value = match value {
+                    serde_json::Value::String(s) if key == "data" || key == "blob" => {
+                        serde_json::json!(format!("[{} bytes redacted]", s.len()))
+                    }
+                    other => redact_frame(other),
+                };
+                (key.clone(), value)
+            })
+            .collect(),
+        serde_json::Value::Array(items) => items.iter().map(redact_frame).collect(),
+        serde_json::Value::String(s) if s.len() > TRACE_STRING_LIMIT => {
+            let kept = truncate_on_char_boundary(s, TRACE_STRING_LIMIT);
+            serde_json::json!(format!(
+                "{kept}… [{} more bytes redacted]",
+                s.len() - kept.len()
+            ))
+        }
+        other => other.clone(),
+    }
 }
 
 /// Write a JSON-RPC request to stdin (newline-delimited JSON)
+///
+/// If the adapter has closed its end of the pipe, the stdin handle is dropped
+/// (so later writes fail fast) and a `ProtocolError` is returned.
 async fn write_jsonrpc_request(
     stdin: &Arc<Mutex<Option<tokio::process::ChildStdin>>>,
     request: &serde_json::Value,
 ) -> Result<(), ApiError> {
-    let message = serde_json::to_string(request).map_err(|e| ApiError::ProtocolError {
+    let mut message = serde_json::to_string(request).map_err(|e| ApiError::ProtocolError {
+        kind: ProtocolErrorKind::SerializationFailed,
         message: format!("Failed to serialize JSON-RPC request: {e}"),
     })?;
+    message.push('\n');
 
     let mut stdin_guard = stdin.lock().await;
-    if let Some(stdin) = stdin_guard.as_mut() {
-        stdin
-            .write_all(message.as_bytes())
-            .await
-            .map_err(|e| ApiError::IoError {
-                message: format!("Failed to write to stdin: {e}"),
-            })?;
-        stdin
-            .write_all(b"\n")
-            .await
-            .map_err(|e| ApiError::IoError {
-                message: format!("Failed to write newline: {e}"),
-            })?;
-        stdin.flush().await.map_err(|e| ApiError::IoError {
-            message: format!("Failed to flush stdin: {e}"),
-        })?;
-        Ok(())
-    } else {
-        Err(ApiError::ProtocolError {
-            message: "stdin not available".to_string(),
-        })
+    let Some(stdin_handle) = stdin_guard.as_mut() else {
+        return Err(ApiError::ProtocolError {
+            kind: ProtocolErrorKind::ConnectionClosed,
+            message: "Adapter connection closed (stdin not available)".to_string(),
+        });
+    };
+
+    let result = match stdin_handle.write_all(message.as_bytes()).await {
+        Ok(()) => stdin_handle.flush().await,
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Ok(()) => Ok(()),
+        Err(e) if is_pipe_closed(&e) => {
+            *stdin_guard = None;
+            Err(ApiError::ProtocolError {
+                kind: ProtocolErrorKind::ConnectionClosed,
+                message: format!("Adapter closed its input; restart required: {e}"),
+            })
+        }
+        Err(e) => Err(ApiError::IoError {
+            message: format!("Failed to write to stdin: {e}"),
+        }),
     }
 }
 
-/// Read JSON-RPC responses from stdout until we find one with the expected ID.
-/// Non-matching lines (notifications, other responses) are logged and skipped.
-async fn read_jsonrpc_response(
-    stdout: &mut BufReader<tokio::process::ChildStdout>,
-    expected_id: &str,
-) -> Result<serde_json::Value, ApiError> {
-    let mut lines = stdout.lines();
+/// Whether a write error means the reader end of the pipe is gone.
+fn is_pipe_closed(error: &std::io::Error) -> bool {
+    matches!(
+        error.kind(),
+        std::io::ErrorKind::BrokenPipe | std::io::ErrorKind::ConnectionReset
+    )
+}
 
-    loop {
-        let line = lines.next_line().await.map_err(|e| ApiError::IoError {
-            message: format!("Failed to read from stdout: {e}"),
-        })?;
+/// Register a pending response for `request_id`, then write the request.
+///
+/// The returned receiver resolves when the stdout reader sees the matching
+/// response, or errors if the connection closes first.
+async fn send_jsonrpc_request(
+    stdin: &Arc<Mutex<Option<tokio::process::ChildStdin>>>,
+    pending: &PendingRequests,
+    request_id: &str,
+    session_id: Option<SessionId>,
+    request: &serde_json::Value,
+) -> Result<oneshot::Receiver<serde_json::Value>, ApiError> {
+    let (tx, rx) = oneshot::channel();
+    lock_unpoisoned(pending).insert(request_id.to_string(), PendingRequest { session_id, tx });
 
-        let line = line.ok_or_else(|| ApiError::ProtocolError {
+    if let Err(e) = write_jsonrpc_request(stdin, request).await {
+        lock_unpoisoned(pending).remove(request_id);
+        return Err(e);
+    }
+
+    Ok(rx)
+}
+
+/// Wait (up to `step_timeout`) for a handshake response routed by the stdout reader.
+async fn await_handshake_response(
+    pending: &PendingRequests,
+    request_id: &str,
+    response_rx: oneshot::Receiver<serde_json::Value>,
+    method: &str,
+    step_timeout: Duration,
+) -> Result<serde_json::Value, ApiError> {
+    match tokio::time::timeout(step_timeout, response_rx).await {
+        Ok(Ok(response)) => Ok(response),
+        Ok(Err(_)) => Err(ApiError::ProtocolError {
+            kind: ProtocolErrorKind::ConnectionClosed,
             message: "Adapter stdout closed unexpectedly during handshake".to_string(),
-        })?;
+        }),
+        Err(_) => {
+            lock_unpoisoned(pending).remove(request_id);
+            Err(ApiError::ProtocolError {
+                kind: ProtocolErrorKind::HandshakeTimeout,
+                message: format!(
+                    "{method} timed out after {}ms waiting for the adapter",
+                    step_timeout.as_millis()
+                ),
+            })
+        }
+    }
+}
 
-        // Try to parse as JSON
-        let json: serde_json::Value = match serde_json::from_str(&line) {
-            Ok(j) => j,
-            Err(_) => {
-                // Not JSON, likely debug output from adapter
-                log::debug!("[handshake stdout] {line}");
-                continue;
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::api::types::{AcpSessionUpdate, AgentRuntimeStatus, McpEnvVariable, TerminalId};
+    use crate::protocols::host::{
+        FsReadTextFileResult, FsWriteTextFileResult, TerminalOutputResult, TerminalRunResult,
+    };
+
+    /// Host that ignores callbacks and refuses every request
+    pub(crate) struct NoopHost;
+
+    #[async_trait]
+    impl AgentHost for NoopHost {
+        fn set_status(&self, _status: AgentRuntimeStatus) {}
+
+        fn on_session_update(
+            &self,
+            _session_id: SessionId,
+            _turn_id: Option<TurnId>,
+            _update: AcpSessionUpdate,
+        ) {
+        }
+
+        fn on_connection_lost(&self) {}
+
+        async fn request_permission(
+            &self,
+            _request: PermissionRequest,
+        ) -> Result<PermissionOutcome, ApiError> {
+            Ok(PermissionOutcome {
+                decision: PermissionDecision::Deny,
+                option_id: None,
+            })
+        }
+
+        async fn terminal_run(
+            &self,
+            _request: TerminalRunRequest,
+        ) -> Result<TerminalRunResult, ApiError> {
+            Err(ApiError::ProtocolError {
+                kind: ProtocolErrorKind::Unsupported,
+                message: "unsupported".to_string(),
+            })
+        }
+
+        async fn terminal_create(
+            &self,
+            _request: TerminalCreateRequest,
+        ) -> Result<TerminalId, ApiError> {
+            Err(ApiError::ProtocolError {
+                kind: ProtocolErrorKind::Unsupported,
+                message: "unsupported".to_string(),
+            })
+        }
+
+        async fn terminal_output(
+            &self,
+            _terminal_id: TerminalId,
+        ) -> Result<TerminalOutputResult, ApiError> {
+            Err(ApiError::ProtocolError {
+                kind: ProtocolErrorKind::Unsupported,
+                message: "unsupported".to_string(),
+            })
+        }
+
+        async fn terminal_wait_for_exit(
+            &self,
+            _terminal_id: TerminalId,
+        ) -> Result<TerminalExitStatus, ApiError> {
+            Err(ApiError::ProtocolError {
+                kind: ProtocolErrorKind::Unsupported,
+                message: "unsupported".to_string(),
+            })
+        }
+
+        async fn terminal_kill(&self, _terminal_id: TerminalId) -> Result<(), ApiError> {
+            Err(ApiError::ProtocolError {
+                kind: ProtocolErrorKind::Unsupported,
+                message: "unsupported".to_string(),
+            })
+        }
+
+        async fn terminal_release(&self, _terminal_id: TerminalId) -> Result<(), ApiError> {
+            Err(ApiError::ProtocolError {
+                kind: ProtocolErrorKind::Unsupported,
+                message: "unsupported".to_string(),
+            })
+        }
+
+        async fn fs_read_text_file(
+            &self,
+            _request: FsReadTextFileRequest,
+        ) -> Result<FsReadTextFileResult, ApiError> {
+            Err(ApiError::ProtocolError {
+                kind: ProtocolErrorKind::Unsupported,
+                message: "unsupported".to_string(),
+            })
+        }
+
+        async fn fs_write_text_file(
+            &self,
+            _request: FsWriteTextFileRequest,
+        ) -> Result<FsWriteTextFileResult, ApiError> {
+            Err(ApiError::ProtocolError {
+                kind: ProtocolErrorKind::Unsupported,
+                message: "unsupported".to_string(),
+            })
+        }
+
+        async fn fs_delete(&self, _request: FsDeleteRequest) -> Result<(), ApiError> {
+            Err(ApiError::ProtocolError {
+                kind: ProtocolErrorKind::Unsupported,
+                message: "unsupported".to_string(),
+            })
+        }
+
+        async fn fs_rename(&self, _request: FsRenameRequest) -> Result<(), ApiError> {
+            Err(ApiError::ProtocolError {
+                kind: ProtocolErrorKind::Unsupported,
+                message: "unsupported".to_string(),
+            })
+        }
+    }
+
+    /// Host that records session updates and otherwise behaves like `NoopHost`.
+    #[derive(Default)]
+    struct RecordingHost {
+        updates: std::sync::Mutex<Vec<(SessionId, Option<TurnId>, AcpSessionUpdate)>>,
+        connection_lost: AtomicBool,
+        trace: AtomicBool,
+        frames: std::sync::Mutex<Vec<(ProtocolTraceDirection, String)>>,
+    }
+
+    #[async_trait]
+    impl AgentHost for RecordingHost {
+        fn set_status(&self, _status: AgentRuntimeStatus) {}
+
+        fn on_session_update(
+            &self,
+            session_id: SessionId,
+            turn_id: Option<TurnId>,
+            update: AcpSessionUpdate,
+        ) {
+            self.updates
+                .lock()
+                .unwrap()
+                .push((session_id, turn_id, update));
+        }
+
+        fn on_connection_lost(&self) {
+            self.connection_lost.store(true, Ordering::SeqCst);
+        }
+
+        fn protocol_trace_enabled(&self) -> bool {
+            self.trace.load(Ordering::SeqCst)
+        }
+
+        fn on_protocol_trace(&self, direction: ProtocolTraceDirection, raw: String) {
+            self.frames.lock().unwrap().push((direction, raw));
+        }
+
+        async fn request_permission(
+            &self,
+            request: PermissionRequest,
+        ) -> Result<PermissionOutcome, ApiError> {
+            NoopHost.request_permission(request).await
+        }
+
+        async fn terminal_run(
+            &self,
+            request: TerminalRunRequest,
+        ) -> Result<TerminalRunResult, ApiError> {
+            NoopHost.terminal_run(request).await
+        }
+
+        async fn terminal_create(
+            &self,
+            request: TerminalCreateRequest,
+        ) -> Result<TerminalId, ApiError> {
+            NoopHost.terminal_create(request).await
+        }
+
+        async fn terminal_output(
+            &self,
+            terminal_id: TerminalId,
+        ) -> Result<TerminalOutputResult, ApiError> {
+            NoopHost.terminal_output(terminal_id).await
+        }
+
+        async fn terminal_wait_for_exit(
+            &self,
+            terminal_id: TerminalId,
+        ) -> Result<TerminalExitStatus, ApiError> {
+            NoopHost.terminal_wait_for_exit(terminal_id).await
+        }
+
+        async fn terminal_kill(&self, terminal_id: TerminalId) -> Result<(), ApiError> {
+            NoopHost.terminal_kill(terminal_id).await
+        }
+
+        async fn terminal_release(&self, terminal_id: TerminalId) -> Result<(), ApiError> {
+            NoopHost.terminal_release(terminal_id).await
+        }
+
+        async fn fs_read_text_file(
+            &self,
+            request: FsReadTextFileRequest,
+        ) -> Result<FsReadTextFileResult, ApiError> {
+            NoopHost.fs_read_text_file(request).await
+        }
+
+        async fn fs_write_text_file(
+            &self,
+            request: FsWriteTextFileRequest,
+        ) -> Result<FsWriteTextFileResult, ApiError> {
+            NoopHost.fs_write_text_file(request).await
+        }
+
+        async fn fs_delete(&self, request: FsDeleteRequest) -> Result<(), ApiError> {
+            NoopHost.fs_delete(request).await
+        }
+
+        async fn fs_rename(&self, request: FsRenameRequest) -> Result<(), ApiError> {
+            NoopHost.fs_rename(request).await
+        }
+    }
+
+    /// Build an agent around a `cat` process that records everything written
+    /// to its stdin into `log_path`, without spawning a stdout reader.
+    #[cfg(unix)]
+    fn recording_agent(log_path: &std::path::Path) -> AcpAgent {
+        script_agent(
+            &format!("cat > '{}'", log_path.display()),
+            Duration::from_millis(200),
+        )
+    }
+
+    /// Agent whose adapter process runs `script` under `sh`.
+    #[cfg(unix)]
+    fn script_agent(script: &str, shutdown_grace: Duration) -> AcpAgent {
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(script)
+            .stdin(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .expect("failed to spawn sh");
+        let stdin = child.stdin.take();
+
+        AcpAgent {
+            child: Mutex::new(Some(child)),
+            stdin: Arc::new(Mutex::new(stdin)),
+            session_id: Arc::new(std::sync::Mutex::new("session-1".to_string())),
+            protocol_version: PROTOCOL_VERSION,
+            cwd: std::env::temp_dir(),
+            mcp_servers: Vec::new(),
+            pending: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            prompt_ids: std::sync::Mutex::new(HashMap::new()),
+            available_modes: std::sync::Mutex::new(HashMap::new()),
+            turn_finished: Arc::new(Notify::new()),
+            started_turns: StartedTurns::default(),
+            request_tasks: Arc::new(std::sync::Mutex::new(JoinSet::new())),
+            shutdown_grace,
+            connected: Arc::new(AtomicBool::new(true)),
+            host: Arc::new(NoopHost),
+            pid_guard: None,
+        }
+    }
+
+    #[test]
+    fn test_permission_options_parsed_and_echoed() {
+        let params = serde_json::json!({
+            "sessionId": "session-1",
+            "options": [
+                { "optionId": "allow", "name": "Allow", "kind": "allow_once" },
+                { "optionId": "always", "name": "Always Allow", "kind": "allow_always" },
+                { "optionId": "reject", "name": "Reject", "kind": "reject_once" },
+                { "optionId": "weird", "name": "Weird", "kind": "sometimes" }
+            ]
+        });
+
+        let options = extract_permission_options(&params);
+        assert_eq!(options.len(), 3);
+        assert_eq!(options[1].option_id, "always");
+        assert_eq!(options[1].name, "Always Allow");
+        assert_eq!(options[1].kind, PermissionOptionKind::AllowAlways);
+
+        let selected = permission_result(&PermissionOutcome {
+            decision: PermissionDecision::AllowOnce,
+            option_id: Some("always".to_string()),
+        });
+        assert_eq!(
+            selected,
+            serde_json::json!({ "outcome": { "outcome": "selected", "optionId": "always" } })
+        );
+
+        // No options offered: legacy two-choice result
+        assert!(extract_permission_options(&serde_json::json!({})).is_empty());
+        let legacy = permission_result(&PermissionOutcome {
+            decision: PermissionDecision::Deny,
+            option_id: None,
+        });
+        assert_eq!(legacy, serde_json::json!("Deny"));
+    }
+
+    #[test]
+    fn test_read_encoding_hint() {
+        assert_eq!(read_encoding(Some("base64")), Some(FsReadEncoding::Base64));
+        assert_eq!(read_encoding(Some("utf8")), Some(FsReadEncoding::Text));
+        assert_eq!(read_encoding(None), Some(FsReadEncoding::Text));
+        // Anything else is rejected as an invalid param
+        for hint in ["utf-16", "text", "auto", ""] {
+            assert_eq!(read_encoding(Some(hint)), None, "{hint}");
+        }
+    }
+
+    #[test]
+    fn test_terminal_create_params_and_exit_status() {
+        let request = extract_terminal_create(&serde_json::json!({
+            "sessionId": "session-1",
+            "command": "cargo",
+            "args": ["build", "--release"],
+            "env": [{ "name": "RUST_LOG", "value": "debug" }],
+            "cwd": "/work/project",
+            "outputByteLimit": 1024
+        }))
+        .unwrap();
+        assert_eq!(request.command, "cargo");
+        assert_eq!(request.args, vec!["build", "--release"]);
+        assert_eq!(
+            request.env,
+            Some(HashMap::from([(
+                "RUST_LOG".to_string(),
+                "debug".to_string()
+            )]))
+        );
+        assert_eq!(request.cwd, Some(PathBuf::from("/work/project")));
+        assert_eq!(request.output_byte_limit, Some(1024));
+
+        assert!(extract_terminal_create(&serde_json::json!({ "args": ["x"] })).is_none());
+
+        let status = exit_status_json(&TerminalExitStatus {
+            exit_code: Some(2),
+            signal: None,
+            user_stopped: false,
+        });
+        assert_eq!(status, serde_json::json!({ "exitCode": 2, "signal": null }));
+
+        let status = exit_status_json(&TerminalExitStatus {
+            exit_code: None,
+            signal: Some(9),
+            user_stopped: true,
+        });
+        assert_eq!(
+            status,
+            serde_json::json!({ "exitCode": null, "signal": "SIGKILL" })
+        );
+    }
+
+    #[test]
+    fn test_terminal_run_env_param() {
+        let expected = HashMap::from([("FOO".to_string(), "bar".to_string())]);
+        assert_eq!(
+            extract_env(&serde_json::json!({ "env": { "FOO": "bar", "N": 1 } })),
+            Some(expected.clone())
+        );
+        assert_eq!(
+            extract_env(&serde_json::json!({ "env": [{ "name": "FOO", "value": "bar" }] })),
+            Some(expected)
+        );
+        assert_eq!(extract_env(&serde_json::json!({ "command": "ls" })), None);
+    }
+
+    #[test]
+    fn test_negotiate_protocol_version() {
+        let response = |result: serde_json::Value| serde_json::json!({ "jsonrpc": "2.0", "id": "init", "result": result });
+
+        assert_eq!(
+            negotiate_protocol_version(&response(serde_json::json!({ "protocolVersion": 1 })))
+                .unwrap(),
+            1
+        );
+
+        let newer = negotiate_protocol_version(&response(serde_json::json!({
+            "protocolVersion": PROTOCOL_VERSION + 1
+        })));
+        match newer {
+            Err(ApiError::ProtocolError {
+                kind: ProtocolErrorKind::InitializeRejected,
+                message,
+            }) => {
+                assert!(
+                    message.contains("unsupported ACP protocol version"),
+                    "{message}"
+                );
             }
+            other => panic!("expected ProtocolError, got {other:?}"),
+        }
+
+        assert!(matches!(
+            negotiate_protocol_version(&response(serde_json::json!({}))),
+            Err(ApiError::ProtocolError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_session_params_include_mcp_servers() {
+        let servers = vec![McpServerConfig {
+            name: "filesystem".to_string(),
+            command: "/usr/local/bin/mcp-fs".to_string(),
+            args: vec!["--root".to_string(), "/tmp".to_string()],
+            env: vec![McpEnvVariable {
+                name: "LOG_LEVEL".to_string(),
+                value: "debug".to_string(),
+            }],
+        }];
+
+        let params = session_params(std::path::Path::new("/workspace"), &servers);
+
+        assert_eq!(
+            params,
+            serde_json::json!({
+                "cwd": "/workspace",
+                "mcpServers": [{
+                    "name": "filesystem",
+                    "command": "/usr/local/bin/mcp-fs",
+                    "args": ["--root", "/tmp"],
+                    "env": [{ "name": "LOG_LEVEL", "value": "debug" }]
+                }]
+            })
+        );
+        assert_eq!(
+            session_params(std::path::Path::new("/workspace"), &[])["mcpServers"],
+            serde_json::json!([])
+        );
+    }
+
+    #[test]
+    fn test_acp_content_block_text() {
+        assert_eq!(
+            acp_content_block(&PromptContentBlock::text("hello")),
+            serde_json::json!({ "type": "text", "text": "hello" })
+        );
+    }
+
+    #[test]
+    fn test_acp_content_block_image() {
+        let block = PromptContentBlock::Image {
+            mime_type: "image/png".to_string(),
+            data: "iVBORw0KGgo=".to_string(),
         };
 
-        // Check if this is the response we're waiting for
-        if let Some(id) = json.get("id") {
-            let id_str = match id {
-                serde_json::Value::String(s) => s.clone(),
-                serde_json::Value::Number(n) => n.to_string(),
-                _ => continue,
-            };
+        assert_eq!(
+            acp_content_block(&block),
+            serde_json::json!({
+                "type": "image",
+                "mimeType": "image/png",
+                "data": "iVBORw0KGgo="
+            })
+        );
+    }
 
-            if id_str == expected_id {
-                return Ok(json);
-            }
+    #[test]
+    fn test_acp_content_block_resource() {
+        let block = PromptContentBlock::Resource {
+            uri: "file:///workspace/src/main.rs".to_string(),
+        };
 
-            // Different response ID - log and continue
-            log::debug!("[handshake] Unexpected response id={id_str}, waiting for {expected_id}");
-        } else {
-            // No ID - probably a notification, log and continue
-            let method = json
-                .get("method")
-                .and_then(|m| m.as_str())
-                .unwrap_or("unknown");
-            log::debug!("[handshake] Received notification: method={method}");
-        }
+        assert_eq!(
+            acp_content_block(&block),
+            serde_json::json!({
+                "type": "resource_link",
+                "uri": "file:///workspace/src/main.rs",
+                "name": "main.rs"
+            })
+        );
+        assert_eq!(resource_name("https://example.com/"), "example.com");
+    }
+
+    /// Build a plugin command that runs `script` under `sh`.
+    #[cfg(unix)]
+    fn stub_adapter(script: &str) -> PluginCommand {
+        PluginCommand {
+            path: PathBuf::from("sh"),
+            args: vec!["-c".to_string(), script.to_string()],
+            env: Vec::new(),
+        }
+    }
+
+    /// Shell snippet that reads one request and stores its id in `$id`.
+    #[cfg(unix)]
+    const READ_REQUEST_ID: &str =
+        r#"read -r line; id=$(printf '%s\n' "$line" | sed 's/.*"id":"\([^"]*\)".*/\1/');"#;
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_handshake_routes_responses_by_id() {
+        let script = format!(
+            r#"{READ_REQUEST_ID}
+echo 'adapter starting'
+echo '{{"jsonrpc":"2.0","id":"unrelated","result":{{}}}}'
+echo "{{\"jsonrpc\":\"2.0\",\"id\":\"$id\",\"result\":{{\"protocolVersion\":1}}}}"
+{READ_REQUEST_ID}
+echo '{{"jsonrpc":"2.0","method":"session/update","params":{{}}}}'
+echo "{{\"jsonrpc\":\"2.0\",\"id\":\"$id\",\"result\":{{\"sessionId\":\"stub-session\"}}}}"
+cat > /dev/null"#
+        );
+
+        let (connection, session_id) = AcpAgent::connect(
+            stub_adapter(&script),
+            std::env::temp_dir(),
+            Arc::new(NoopHost),
+            None,
+            Vec::new(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(session_id, "stub-session");
+        connection.shutdown().await.unwrap();
+    }
+
+    /// Sets its flag when dropped, e.g. with the future holding it.
+    struct DropFlag(Arc<AtomicBool>);
+
+    impl Drop for DropFlag {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    /// Host whose `terminal/run` never finishes, recording what shutdown
+    /// cleans up.
+    #[derive(Default)]
+    struct HangingTerminalHost {
+        run_started: AtomicBool,
+        run_dropped: Arc<AtomicBool>,
+        in_flight_cancelled: AtomicBool,
+    }
+
+    #[async_trait]
+    impl AgentHost for HangingTerminalHost {
+        fn set_status(&self, _status: AgentRuntimeStatus) {}
+
+        fn on_session_update(
+            &self,
+            _session_id: SessionId,
+            _turn_id: Option<TurnId>,
+            _update: AcpSessionUpdate,
+        ) {
+        }
+
+        fn on_connection_lost(&self) {}
+
+        async fn cancel_in_flight_requests(&self) {
+            self.in_flight_cancelled.store(true, Ordering::SeqCst);
+        }
+
+        async fn request_permission(
+            &self,
+            request: PermissionRequest,
+        ) -> Result<PermissionOutcome, ApiError> {
+            NoopHost.request_permission(request).await
+        }
+
+        async fn terminal_run(
+            &self,
+            _request: TerminalRunRequest,
+        ) -> Result<TerminalRunResult, ApiError> {
+            let _dropped = DropFlag(self.run_dropped.clone());
+            self.run_started.store(true, Ordering::SeqCst);
+            std::future::pending().await
+        }
+
+        async fn terminal_create(
+            &self,
+            request: TerminalCreateRequest,
+        ) -> Result<TerminalId, ApiError> {
+            NoopHost.terminal_create(request).await
+        }
+
+        async fn terminal_output(
+            &self,
+            terminal_id: TerminalId,
+        ) -> Result<TerminalOutputResult, ApiError> {
+            NoopHost.terminal_output(terminal_id).await
+        }
+
+        async fn terminal_wait_for_exit(
+            &self,
+            terminal_id: TerminalId,
+        ) -> Result<TerminalExitStatus, ApiError> {
+            NoopHost.terminal_wait_for_exit(terminal_id).await
+        }
+
+        async fn terminal_kill(&self, terminal_id: TerminalId) -> Result<(), ApiError> {
+            NoopHost.terminal_kill(terminal_id).await
+        }
+
+        async fn terminal_release(&self, terminal_id: TerminalId) -> Result<(), ApiError> {
+            NoopHost.terminal_release(terminal_id).await
+        }
+
+        async fn fs_read_text_file(
+            &self,
+            request: FsReadTextFileRequest,
+        ) -> Result<FsReadTextFileResult, ApiError> {
+            NoopHost.fs_read_text_file(request).await
+        }
+
+        async fn fs_write_text_file(
+            &self,
+            request: FsWriteTextFileRequest,
+        ) -> Result<FsWriteTextFileResult, ApiError> {
+            NoopHost.fs_write_text_file(request).await
+        }
+
+        async fn fs_delete(&self, request: FsDeleteRequest) -> Result<(), ApiError> {
+            NoopHost.fs_delete(request).await
+        }
+
+        async fn fs_rename(&self, request: FsRenameRequest) -> Result<(), ApiError> {
+            NoopHost.fs_rename(request).await
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_shutdown_aborts_in_flight_terminal_run() {
+        let script = format!(
+            r#"{READ_REQUEST_ID}
+echo "{{\"jsonrpc\":\"2.0\",\"id\":\"$id\",\"result\":{{\"protocolVersion\":1}}}}"
+{READ_REQUEST_ID}
+echo "{{\"jsonrpc\":\"2.0\",\"id\":\"$id\",\"result\":{{\"sessionId\":\"stub-session\"}}}}"
+echo '{{"jsonrpc":"2.0","id":"run-1","method":"terminal/run","params":{{"command":"sleep 30"}}}}'
+cat > /dev/null"#
+        );
+        let host = Arc::new(HangingTerminalHost::default());
+
+        let (connection, _session_id) = AcpAgent::connect(
+            stub_adapter(&script),
+            std::env::temp_dir(),
+            host.clone(),
+            None,
+            Vec::new(),
+        )
+        .await
+        .unwrap();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !host.run_started.load(Ordering::SeqCst) {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("terminal/run was not handled");
+
+        connection.shutdown().await.unwrap();
+
+        assert!(host.run_dropped.load(Ordering::SeqCst));
+        assert!(host.in_flight_cancelled.load(Ordering::SeqCst));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_handshake_fails_when_adapter_exits() {
+        let script = format!("{READ_REQUEST_ID} exit 0");
+
+        let result = AcpAgent::connect(
+            stub_adapter(&script),
+            std::env::temp_dir(),
+            Arc::new(NoopHost),
+            None,
+            Vec::new(),
+        )
+        .await;
+
+        match result {
+            Err(ApiError::ProtocolError {
+                kind: ProtocolErrorKind::ConnectionClosed,
+                message,
+            }) => {
+                assert!(message.contains("closed unexpectedly during handshake"));
+            }
+            Err(other) => panic!("unexpected error: {other}"),
+            Ok(_) => panic!("handshake should fail"),
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_startup_failure_includes_adapter_stderr() {
+        let script = format!("echo 'ANTHROPIC_API_KEY is not set' >&2; {READ_REQUEST_ID} exit 1");
+
+        let result = AcpAgent::connect(
+            stub_adapter(&script),
+            std::env::temp_dir(),
+            Arc::new(NoopHost),
+            None,
+            Vec::new(),
+        )
+        .await;
+
+        match result {
+            Err(ApiError::ProtocolError {
+                kind: ProtocolErrorKind::ConnectionClosed,
+                message,
+            }) => {
+                assert!(
+                    message.contains("ANTHROPIC_API_KEY is not set"),
+                    "{message}"
+                );
+            }
+            Err(other) => panic!("unexpected error: {other}"),
+            Ok(_) => panic!("handshake should fail"),
+        }
+
+        let long_line = "x".repeat(STARTUP_STDERR_MAX_BYTES * 2);
+        let truncated = with_startup_stderr("failed".to_string(), &[long_line]);
+        assert!(truncated.len() < STARTUP_STDERR_MAX_BYTES + 64);
+        assert_eq!(
+            with_startup_stderr("failed".to_string(), &[]),
+            "failed".to_string()
+        );
+    }
+
+    /// Stub that advertises `loadSession`, answers `session/load` with
+    /// `load_reply` (a JSON-RPC `result`/`error` member, shell-escaped) and
+    /// `session/new` with "fresh-session".
+    #[cfg(unix)]
+    fn resumable_adapter(load_reply: &str) -> PluginCommand {
+        stub_adapter(&format!(
+            r#"while read -r line; do
+  id=$(printf '%s\n' "$line" | sed 's/.*"id":"\([^"]*\)".*/\1/')
+  case "$line" in
+    *'"initialize"'*) echo "{{\"jsonrpc\":\"2.0\",\"id\":\"$id\",\"result\":{{\"protocolVersion\":1,\"agentCapabilities\":{{\"loadSession\":true}}}}}}" ;;
+    *session/load*) echo "{{\"jsonrpc\":\"2.0\",\"id\":\"$id\",{load_reply}}}" ;;
+    *) echo "{{\"jsonrpc\":\"2.0\",\"id\":\"$id\",\"result\":{{\"sessionId\":\"fresh-session\"}}}}" ;;
+  esac
+done"#
+        ))
+    }
+
+    /// Session that ID-less updates are attributed to, found by prompting an
+    /// adapter that sends one.
+    #[cfg(unix)]
+    async fn current_session(
+        connection: &dyn AgentConnection,
+        host: &RecordingHost,
+        session_id: &SessionId,
+    ) -> SessionId {
+        connection
+            .send_prompt(session_id.clone(), vec![PromptContentBlock::text("hi")])
+            .await
+            .unwrap();
+        let updates = host.updates.lock().unwrap();
+        updates
+            .iter()
+            .rev()
+            .find(|(_, _, update)| matches!(update, AcpSessionUpdate::Raw { .. }))
+            .map(|(session_id, _, _)| session_id.clone())
+            .unwrap()
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_only_replacing_a_session_moves_the_current_one() {
+        // Numbers the sessions it creates; each prompt yields an update
+        // without a session ID, which lands on the current session
+        let script = r#"n=0
+while read -r line; do
+  id=$(printf '%s\n' "$line" | sed 's/.*"id":"\([^"]*\)".*/\1/')
+  case "$line" in
+    *session/prompt*)
+      echo '{"jsonrpc":"2.0","method":"session/update","params":{"update":{"sessionUpdate":"agent_message_chunk"}}}'
+      echo "{\"jsonrpc\":\"2.0\",\"id\":\"$id\",\"result\":{\"stopReason\":\"end_turn\"}}" ;;
+    *'"initialize"'*) echo "{\"jsonrpc\":\"2.0\",\"id\":\"$id\",\"result\":{\"protocolVersion\":1}}" ;;
+    *) n=$((n+1)); echo "{\"jsonrpc\":\"2.0\",\"id\":\"$id\",\"result\":{\"sessionId\":\"session-$n\"}}" ;;
+  esac
+done"#;
+        let host = Arc::new(RecordingHost::default());
+        let (connection, session_id) = AcpAgent::connect(
+            stub_adapter(script),
+            std::env::temp_dir(),
+            host.clone(),
+            None,
+            Vec::new(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(session_id, "session-1");
+
+        // An extra session leaves the connection's default alone, and so
+        // does replacing some other session
+        assert_eq!(connection.new_session().await.unwrap(), "session-2");
+        assert_eq!(
+            current_session(connection.as_ref(), &host, &session_id).await,
+            "session-1"
+        );
+        let replacement = connection
+            .replace_session("session-2".to_string())
+            .await
+            .unwrap();
+        assert_eq!(replacement, "session-3");
+        assert_eq!(
+            current_session(connection.as_ref(), &host, &session_id).await,
+            "session-1"
+        );
+
+        let replacement = connection
+            .replace_session("session-1".to_string())
+            .await
+            .unwrap();
+        assert_eq!(replacement, "session-4");
+        assert_eq!(
+            current_session(connection.as_ref(), &host, &session_id).await,
+            "session-4"
+        );
+        connection.shutdown().await.unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_handshake_resumes_stored_session() {
+        let (connection, session_id) = AcpAgent::connect(
+            resumable_adapter(r#"\"result\":null"#),
+            std::env::temp_dir(),
+            Arc::new(NoopHost),
+            Some("stored-session".to_string()),
+            Vec::new(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(session_id, "stored-session");
+        connection.shutdown().await.unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_handshake_falls_back_to_new_session_when_load_rejected() {
+        let (connection, session_id) = AcpAgent::connect(
+            resumable_adapter(r#"\"error\":{\"code\":-32002,\"message\":\"Session not found\"}"#),
+            std::env::temp_dir(),
+            Arc::new(NoopHost),
+            Some("stored-session".to_string()),
+            Vec::new(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(session_id, "fresh-session");
+        connection.shutdown().await.unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_prompt_turn_id_tags_correlated_updates() {
+        // Echoes the prompt's request id as `turnId` on an update, then ends
+        // the turn; a second update carries no turn id
+        let script = r#"while read -r line; do
+  id=$(printf '%s\n' "$line" | sed 's/.*"id":"\([^"]*\)".*/\1/')
+  case "$line" in
+    *session/prompt*)
+      echo "{\"jsonrpc\":\"2.0\",\"method\":\"session/update\",\"params\":{\"sessionId\":\"stub-session\",\"turnId\":\"$id\",\"update\":{\"sessionUpdate\":\"agent_message_chunk\",\"content\":{\"type\":\"text\",\"text\":\"hi\"}}}}"
+      echo '{"jsonrpc":"2.0","method":"session/update","params":{"sessionId":"stub-session","update":{"sessionUpdate":"agent_message_chunk","content":{"type":"text","text":"untagged"}}}}'
+      echo "{\"jsonrpc\":\"2.0\",\"id\":\"$id\",\"result\":{\"stopReason\":\"end_turn\"}}" ;;
+    *'"initialize"'*) echo "{\"jsonrpc\":\"2.0\",\"id\":\"$id\",\"result\":{\"protocolVersion\":1}}" ;;
+    *) echo "{\"jsonrpc\":\"2.0\",\"id\":\"$id\",\"result\":{\"sessionId\":\"stub-session\"}}" ;;
+  esac
+done"#;
+        let host = Arc::new(RecordingHost::default());
+
+        let (connection, session_id) = AcpAgent::connect(
+            stub_adapter(script),
+            std::env::temp_dir(),
+            host.clone(),
+            None,
+            Vec::new(),
+        )
+        .await
+        .unwrap();
+        let turn_id = connection
+            .send_prompt(session_id, vec![PromptContentBlock::text("hello")])
+            .await
+            .unwrap();
+
+        assert!(!turn_id.is_empty());
+        let updates = host.updates.lock().unwrap().clone();
+        assert_eq!(updates.len(), 4, "{updates:?}");
+        assert_eq!(updates[0].1.as_deref(), Some(turn_id.as_str()));
+        assert!(matches!(updates[0].2, AcpSessionUpdate::TurnStarted { .. }));
+        assert_eq!(updates[1].1.as_deref(), Some(turn_id.as_str()));
+        assert_eq!(updates[2].1, None);
+        assert_eq!(updates[3].1.as_deref(), Some(turn_id.as_str()));
+        assert!(matches!(
+            updates[3].2,
+            AcpSessionUpdate::TurnComplete { .. }
+        ));
+        connection.shutdown().await.unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_prompt_yields_turn_started_then_turn_complete() {
+        // Answers the prompt only after the accept window, without
+        // streaming any output first
+        let script = r#"while read -r line; do
+  id=$(printf '%s\n' "$line" | sed 's/.*"id":"\([^"]*\)".*/\1/')
+  case "$line" in
+    *session/prompt*)
+      (sleep 1; echo "{\"jsonrpc\":\"2.0\",\"id\":\"$id\",\"result\":{\"stopReason\":\"end_turn\"}}") & ;;
+    *'"initialize"'*) echo "{\"jsonrpc\":\"2.0\",\"id\":\"$id\",\"result\":{\"protocolVersion\":1}}" ;;
+    *) echo "{\"jsonrpc\":\"2.0\",\"id\":\"$id\",\"result\":{\"sessionId\":\"stub-session\"}}" ;;
+  esac
+done"#;
+        let host = Arc::new(RecordingHost::default());
+        let (connection, session_id) = AcpAgent::connect(
+            stub_adapter(script),
+            std::env::temp_dir(),
+            host.clone(),
+            None,
+            Vec::new(),
+        )
+        .await
+        .unwrap();
+
+        let turn_id = connection
+            .send_prompt(session_id.clone(), vec![PromptContentBlock::text("hello")])
+            .await
+            .unwrap();
+
+        // Accepted but still running
+        {
+            let updates = host.updates.lock().unwrap();
+            assert_eq!(updates.len(), 1, "{updates:?}");
+            assert_eq!(updates[0].0, session_id);
+            assert_eq!(updates[0].1.as_deref(), Some(turn_id.as_str()));
+            assert!(matches!(
+                &updates[0].2,
+                AcpSessionUpdate::TurnStarted { prompt_id } if *prompt_id == turn_id
+            ));
+        }
+
+        for _ in 0..100 {
+            if host.updates.lock().unwrap().len() > 1 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        let updates = host.updates.lock().unwrap().clone();
+        assert_eq!(updates.len(), 2, "{updates:?}");
+        assert_eq!(updates[1].1.as_deref(), Some(turn_id.as_str()));
+        assert!(matches!(
+            updates[1].2,
+            AcpSessionUpdate::TurnComplete {
+                stop_reason: StopReason::EndTurn,
+                error: None,
+            }
+        ));
+        connection.shutdown().await.unwrap();
+    }
+
+    /// Adapter that answers every prompt with `end_turn` after one second.
+    #[cfg(unix)]
+    const SLOW_PROMPT_SCRIPT: &str = r#"while read -r line; do
+  id=$(printf '%s\n' "$line" | sed 's/.*"id":"\([^"]*\)".*/\1/')
+  case "$line" in
+    *session/prompt*)
+      (sleep 1; echo "{\"jsonrpc\":\"2.0\",\"id\":\"$id\",\"result\":{\"stopReason\":\"end_turn\"}}") & ;;
+    *'"initialize"'*) echo "{\"jsonrpc\":\"2.0\",\"id\":\"$id\",\"result\":{\"protocolVersion\":1}}" ;;
+    *) echo "{\"jsonrpc\":\"2.0\",\"id\":\"$id\",\"result\":{\"sessionId\":\"stub-session\"}}" ;;
+  esac
+done"#;
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_prompt_timeout_ends_a_turn_the_default_would_let_finish() {
+        let host = Arc::new(RecordingHost::default());
+        let (connection, session_id) = AcpAgent::connect(
+            stub_adapter(SLOW_PROMPT_SCRIPT),
+            std::env::temp_dir(),
+            host.clone(),
+            None,
+            Vec::new(),
+        )
+        .await
+        .unwrap();
+
+        let turn_id = connection
+            .send_prompt_with_timeout(
+                session_id.clone(),
+                vec![PromptContentBlock::text("hello")],
+                Some(Duration::from_millis(700)),
+            )
+            .await
+            .unwrap();
+
+        // Past the adapter's answer: only the timeout's TurnComplete counts
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        let updates = host.updates.lock().unwrap().clone();
+        assert_eq!(updates.len(), 2, "{updates:?}");
+        assert!(matches!(updates[0].2, AcpSessionUpdate::TurnStarted { .. }));
+        assert_eq!(updates[1].1.as_deref(), Some(turn_id.as_str()));
+        assert!(matches!(
+            &updates[1].2,
+            AcpSessionUpdate::TurnComplete {
+                stop_reason: StopReason::Error,
+                error: Some(error),
+            } if error == "Prompt timed out after 700ms"
+        ));
+
+        // Without a timeout the same turn runs to completion
+        let turn_id = connection
+            .send_prompt(session_id, vec![PromptContentBlock::text("hello")])
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        let updates = host.updates.lock().unwrap().clone();
+        assert_eq!(updates.len(), 4, "{updates:?}");
+        assert_eq!(updates[3].1.as_deref(), Some(turn_id.as_str()));
+        assert!(matches!(
+            updates[3].2,
+            AcpSessionUpdate::TurnComplete {
+                stop_reason: StopReason::EndTurn,
+                error: None,
+            }
+        ));
+        connection.shutdown().await.unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_prompt_timeout_within_accept_window_fails_send() {
+        let host = Arc::new(RecordingHost::default());
+        let (connection, session_id) = AcpAgent::connect(
+            stub_adapter(SLOW_PROMPT_SCRIPT),
+            std::env::temp_dir(),
+            host.clone(),
+            None,
+            Vec::new(),
+        )
+        .await
+        .unwrap();
+
+        let result = connection
+            .send_prompt_with_timeout(
+                session_id,
+                vec![PromptContentBlock::text("hello")],
+                Some(Duration::from_millis(100)),
+            )
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(ApiError::ProtocolError {
+                kind: ProtocolErrorKind::RequestTimeout,
+                ..
+            })
+        ));
+        // Neither the timeout nor the late answer starts a turn
+        tokio::time::sleep(Duration::from_millis(1200)).await;
+        assert!(host.updates.lock().unwrap().is_empty());
+        connection.shutdown().await.unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_prompt_rejected_by_adapter_fails_send() {
+        let host = Arc::new(RecordingHost::default());
+        let (connection, session_id) = AcpAgent::connect(
+            stub_adapter(&handshake_then_answer(1)),
+            std::env::temp_dir(),
+            host.clone(),
+            None,
+            Vec::new(),
+        )
+        .await
+        .unwrap();
+
+        let result = connection
+            .send_prompt(session_id, vec![PromptContentBlock::text("hello")])
+            .await;
+
+        match result {
+            Err(ApiError::ProtocolError {
+                kind: ProtocolErrorKind::RequestFailed,
+                message,
+            }) => assert_eq!(
+                message,
+                "Prompt failed: code=-32601, message=Method not found"
+            ),
+            Err(other) => panic!("unexpected error: {other}"),
+            Ok(turn_id) => panic!("prompt should be rejected, got turn {turn_id}"),
+        }
+        // A rejected prompt never started a turn
+        assert!(host.updates.lock().unwrap().is_empty());
+        connection.shutdown().await.unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_prompt_rejected_after_accept_window_fails_the_turn() {
+        // Rejects the prompt only after the accept window
+        let script = r#"while read -r line; do
+  id=$(printf '%s\n' "$line" | sed 's/.*"id":"\([^"]*\)".*/\1/')
+  case "$line" in
+    *session/prompt*)
+      (sleep 1; echo "{\"jsonrpc\":\"2.0\",\"id\":\"$id\",\"error\":{\"code\":-32603,\"message\":\"Internal error\"}}") & ;;
+    *'"initialize"'*) echo "{\"jsonrpc\":\"2.0\",\"id\":\"$id\",\"result\":{\"protocolVersion\":1}}" ;;
+    *) echo "{\"jsonrpc\":\"2.0\",\"id\":\"$id\",\"result\":{\"sessionId\":\"stub-session\"}}" ;;
+  esac
+done"#;
+        let host = Arc::new(RecordingHost::default());
+        let (connection, session_id) = AcpAgent::connect(
+            stub_adapter(script),
+            std::env::temp_dir(),
+            host.clone(),
+            None,
+            Vec::new(),
+        )
+        .await
+        .unwrap();
+
+        // Looks accepted once the window passes without an error
+        let turn_id = connection
+            .send_prompt(session_id, vec![PromptContentBlock::text("hello")])
+            .await
+            .unwrap();
+
+        for _ in 0..100 {
+            if host.updates.lock().unwrap().len() > 1 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        let updates = host.updates.lock().unwrap().clone();
+        assert_eq!(updates.len(), 2, "{updates:?}");
+        assert!(matches!(updates[0].2, AcpSessionUpdate::TurnStarted { .. }));
+        assert_eq!(updates[1].1.as_deref(), Some(turn_id.as_str()));
+        assert!(matches!(
+            &updates[1].2,
+            AcpSessionUpdate::TurnComplete {
+                stop_reason: StopReason::Error,
+                error: Some(error),
+            } if error == "Protocol error (request failed): Prompt failed: code=-32603, message=Internal error"
+        ));
+        connection.shutdown().await.unwrap();
+    }
+
+    /// Adapter that completes the handshake, then answers `n` more requests
+    /// before going silent while keeping its stdin open.
+    #[cfg(unix)]
+    fn handshake_then_answer(n: usize) -> String {
+        let answer = format!(
+            r#"{READ_REQUEST_ID}
+echo "{{\"jsonrpc\":\"2.0\",\"id\":\"$id\",\"error\":{{\"code\":-32601,\"message\":\"Method not found\"}}}}"
+"#
+        );
+        format!(
+            r#"{READ_REQUEST_ID}
+echo "{{\"jsonrpc\":\"2.0\",\"id\":\"$id\",\"result\":{{\"protocolVersion\":1}}}}"
+{READ_REQUEST_ID}
+echo "{{\"jsonrpc\":\"2.0\",\"id\":\"$id\",\"result\":{{\"sessionId\":\"stub-session\"}}}}"
+{}exec sleep 30"#,
+            answer.repeat(n)
+        )
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_keepalive_detects_wedged_adapter() {
+        let host = Arc::new(RecordingHost::default());
+        let (connection, _) = AcpAgent::connect_with_options(
+            stub_adapter(&handshake_then_answer(1)),
+            std::env::temp_dir(),
+            host.clone(),
+            None,
+            Vec::new(),
+            AcpConnectOptions {
+                keepalive: Some(AcpKeepalive {
+                    interval: Duration::from_millis(50),
+                    timeout: Duration::from_millis(200),
+                }),
+                ..AcpConnectOptions::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        // The first ping is answered (with "method not found"), the second isn't
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while connection.is_connected() {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        })
+        .await
+        .expect("keepalive should mark the connection lost");
+        assert!(host.connection_lost.load(Ordering::SeqCst));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_keepalive_is_off_by_default() {
+        let host = Arc::new(RecordingHost::default());
+        let (connection, _) = AcpAgent::connect_with_options(
+            stub_adapter(&handshake_then_answer(0)),
+            std::env::temp_dir(),
+            host.clone(),
+            None,
+            Vec::new(),
+            AcpConnectOptions {
+                shutdown_grace: Duration::from_millis(100),
+                ..AcpConnectOptions::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert!(connection.is_connected());
+        assert!(!host.connection_lost.load(Ordering::SeqCst));
+        connection.shutdown().await.unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_protocol_trace_reports_frames_when_enabled() {
+        let host = Arc::new(RecordingHost::default());
+        host.trace.store(true, Ordering::SeqCst);
+        let (connection, _) = AcpAgent::connect_with_options(
+            stub_adapter(&handshake_then_answer(0)),
+            std::env::temp_dir(),
+            host.clone(),
+            None,
+            Vec::new(),
+            AcpConnectOptions {
+                shutdown_grace: Duration::from_millis(100),
+                ..AcpConnectOptions::default()
+            },
+        )
+        .await
+        .unwrap();
+        connection.shutdown().await.unwrap();
+
+        let frames = host.frames.lock().unwrap().clone();
+        let (direction, raw) = &frames[0];
+        assert_eq!(*direction, ProtocolTraceDirection::Outbound);
+        assert!(raw.contains(METHOD_INITIALIZE), "{raw}");
+        assert!(frames.iter().any(|(direction, raw)| {
+            *direction == ProtocolTraceDirection::Inbound && raw.contains("stub-session")
+        }));
+
+        // Off by default: nothing is reported
+        let quiet = Arc::new(RecordingHost::default());
+        trace_frame(
+            quiet.as_ref(),
+            ProtocolTraceDirection::Inbound,
+            &serde_json::json!({}),
+        );
+        assert!(quiet.frames.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_redact_frame_hides_large_content() {
+        let long_text = "é".repeat(TRACE_STRING_LIMIT);
+        let frame = serde_json::json!({
+            "method": "session/prompt",
+            "params": {
+                "prompt": [
+                    { "type": "image", "mimeType": "image/png", "data": "iVBORw0KGgo=" },
+                    { "type": "text", "text": long_text }
+                ]
+            }
+        });
+
+        let redacted = redact_frame(&frame);
+        let blocks = &redacted["params"]["prompt"];
+        assert_eq!(blocks[0]["data"], "[12 bytes redacted]");
+        assert_eq!(blocks[0]["mimeType"], "image/png");
+        let text = blocks[1]["text"].as_str().unwrap();
+        assert!(text.ends_with(&format!("… [{} more bytes redacted]", TRACE_STRING_LIMIT)));
+        assert_eq!(redacted["method"], "session/prompt");
+    }
+
+    #[test]
+    fn test_transient_spawn_errors_are_retried_but_missing_binaries_are_not() {
+        use std::io::{Error, ErrorKind};
+
+        assert!(is_transient_spawn_error(&Error::from(
+            ErrorKind::WouldBlock
+        )));
+        assert!(is_transient_spawn_error(&Error::from(
+            ErrorKind::Interrupted
+        )));
+        #[cfg(unix)]
+        assert!(is_transient_spawn_error(&Error::from_raw_os_error(26)));
+        assert!(!is_transient_spawn_error(&Error::from(ErrorKind::NotFound)));
+        assert!(!is_transient_spawn_error(&Error::from(
+            ErrorKind::PermissionDenied
+        )));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_handshake_times_out_when_adapter_never_replies() {
+        // Reads requests but never writes a response
+        let result = AcpAgent::connect_with_options(
+            stub_adapter("cat > /dev/null"),
+            std::env::temp_dir(),
+            Arc::new(NoopHost),
+            None,
+            Vec::new(),
+            AcpConnectOptions {
+                handshake_timeout: Duration::from_millis(200),
+                ..AcpConnectOptions::default()
+            },
+        )
+        .await;
+
+        match result {
+            Err(ApiError::ProtocolError {
+                kind: ProtocolErrorKind::HandshakeTimeout,
+                message,
+            }) => {
+                assert!(message.starts_with("initialize timed out"), "{message}");
+            }
+            Err(other) => panic!("unexpected error: {other}"),
+            Ok(_) => panic!("handshake should time out"),
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_write_to_closed_stdin_marks_connection_lost() {
+        // Adapter that exits immediately, closing the read end of its stdin
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg("exit 0")
+            .stdin(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .expect("failed to spawn sh");
+        let stdin = child.stdin.take();
+        child.wait().await.expect("failed to wait for sh");
+
+        let agent = AcpAgent {
+            child: Mutex::new(Some(child)),
+            stdin: Arc::new(Mutex::new(stdin)),
+            session_id: Arc::new(std::sync::Mutex::new("session-1".to_string())),
+            protocol_version: PROTOCOL_VERSION,
+            cwd: std::env::temp_dir(),
+            mcp_servers: Vec::new(),
+            pending: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            prompt_ids: std::sync::Mutex::new(HashMap::new()),
+            available_modes: std::sync::Mutex::new(HashMap::new()),
+            turn_finished: Arc::new(Notify::new()),
+            started_turns: StartedTurns::default(),
+            request_tasks: Arc::new(std::sync::Mutex::new(JoinSet::new())),
+            shutdown_grace: Duration::from_millis(200),
+            connected: Arc::new(AtomicBool::new(true)),
+            host: Arc::new(NoopHost),
+            pid_guard: None,
+        };
+
+        let result = agent
+            .send_prompt(
+                "session-1".to_string(),
+                vec![PromptContentBlock::text("hi")],
+            )
+            .await;
+
+        match result {
+            Err(ApiError::ProtocolError {
+                kind: ProtocolErrorKind::ConnectionClosed,
+                message,
+            }) => {
+                assert!(message.contains("restart required"), "{message}");
+            }
+            other => panic!("expected ProtocolError, got {other:?}"),
+        }
+        assert!(!agent.is_connected());
+        assert!(agent.pending.lock().unwrap().is_empty());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_shutdown_cancels_active_turn_before_kill() {
+        let log_path = std::env::temp_dir().join(format!("acp_stdin_{}.log", Uuid::new_v4()));
+        let agent = recording_agent(&log_path);
+
+        let (tx, _rx) = oneshot::channel();
+        lock_unpoisoned(&agent.pending).insert(
+            "prompt-1".to_string(),
+            PendingRequest {
+                session_id: Some("session-1".to_string()),
+                tx,
+            },
+        );
+
+        agent.shutdown().await.unwrap();
+        assert!(agent.child.lock().await.is_none());
+
+        let written = std::fs::read_to_string(&log_path).unwrap_or_default();
+        let message: serde_json::Value =
+            serde_json::from_str(written.lines().next().expect("cancel was not written")).unwrap();
+        assert_eq!(message["method"], METHOD_CANCEL_TURN);
+        assert_eq!(message["params"]["sessionId"], "session-1");
+
+        let _ = std::fs::remove_file(&log_path);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_cancel_turn_names_and_resolves_in_flight_prompt() {
+        let log_path = std::env::temp_dir().join(format!("acp_stdin_{}.log", Uuid::new_v4()));
+        let agent = recording_agent(&log_path);
+
+        let (tx, rx) = oneshot::channel();
+        lock_unpoisoned(&agent.pending).insert(
+            "prompt-1".to_string(),
+            PendingRequest {
+                session_id: Some("session-1".to_string()),
+                tx,
+            },
+        );
+        lock_unpoisoned(&agent.prompt_ids).insert("session-1".to_string(), "prompt-1".to_string());
+
+        agent.cancel_turn("session-1".to_string()).await.unwrap();
+
+        // The pending prompt is resolved right away as cancelled
+        let response = rx.await.unwrap();
+        assert_eq!(response["result"]["stopReason"], "cancelled");
+        assert!(agent.active_turn_sessions().is_empty());
+
+        let mut written = String::new();
+        for _ in 0..50 {
+            written = std::fs::read_to_string(&log_path).unwrap_or_default();
+            if !written.is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        let message: serde_json::Value =
+            serde_json::from_str(written.lines().next().expect("cancel was not written")).unwrap();
+        assert_eq!(message["method"], METHOD_CANCEL_TURN);
+        assert_eq!(message["params"]["sessionId"], "session-1");
+        assert_eq!(message["params"]["requestId"], "prompt-1");
+
+        agent.shutdown().await.unwrap();
+        let _ = std::fs::remove_file(&log_path);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_shutdown_without_active_turn_sends_no_cancel() {
+        let log_path = std::env::temp_dir().join(format!("acp_stdin_{}.log", Uuid::new_v4()));
+        let agent = recording_agent(&log_path);
+
+        agent.shutdown().await.unwrap();
+
+        let written = std::fs::read_to_string(&log_path).unwrap_or_default();
+        assert!(written.is_empty());
+
+        let _ = std::fs::remove_file(&log_path);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_shutdown_lets_adapter_exit_on_stdin_close() {
+        let marker = std::env::temp_dir().join(format!("acp_exit_{}", Uuid::new_v4()));
+        // Writes the marker only once stdin hits EOF; a kill would skip it
+        let agent = script_agent(
+            &format!("cat > /dev/null; printf done > '{}'", marker.display()),
+            Duration::from_secs(5),
+        );
+
+        agent.shutdown().await.unwrap();
+
+        assert!(agent.child.lock().await.is_none());
+        assert_eq!(std::fs::read_to_string(&marker).unwrap(), "done");
+        let _ = std::fs::remove_file(&marker);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_shutdown_kills_adapter_ignoring_stdin_close() {
+        let agent = script_agent("exec sleep 30", Duration::from_millis(100));
+
+        tokio::time::timeout(Duration::from_secs(5), agent.shutdown())
+            .await
+            .expect("shutdown should kill the adapter after the grace period")
+            .unwrap();
+        assert!(agent.child.lock().await.is_none());
+    }
+
+    #[test]
+    fn test_set_mode_request_params() {
+        let request = set_mode_request("req-1", &"session-1".to_string(), "code");
+
+        assert_eq!(
+            request,
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": "req-1",
+                "method": "session/set_mode",
+                "params": { "sessionId": "session-1", "modeId": "code" }
+            })
+        );
+    }
+
+    #[test]
+    fn test_session_modes_read_from_session_result() {
+        let response = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": "1",
+            "result": {
+                "sessionId": "session-1",
+                "modes": {
+                    "currentModeId": "ask",
+                    "availableModes": [
+                        { "id": "ask", "name": "Ask" },
+                        { "id": "code", "name": "Code", "description": "Edit files" }
+                    ]
+                }
+            }
+        });
+
+        assert_eq!(
+            session_modes(&response),
+            Some(SessionModes {
+                current_mode_id: Some("ask".to_string()),
+                available_mode_ids: vec!["ask".to_string(), "code".to_string()],
+            })
+        );
+        let without_modes = serde_json::json!({ "result": { "sessionId": "session-1" } });
+        assert_eq!(session_modes(&without_modes), None);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_set_mode_validates_and_reports_the_new_mode() {
+        let script = r#"while read -r line; do
+  id=$(printf '%s\n' "$line" | sed 's/.*"id":"\([^"]*\)".*/\1/')
+  case "$line" in
+    *session/set_mode*) echo "{\"jsonrpc\":\"2.0\",\"id\":\"$id\",\"result\":{}}" ;;
+    *'"initialize"'*) echo "{\"jsonrpc\":\"2.0\",\"id\":\"$id\",\"result\":{\"protocolVersion\":1}}" ;;
+    *) echo "{\"jsonrpc\":\"2.0\",\"id\":\"$id\",\"result\":{\"sessionId\":\"stub-session\",\"modes\":{\"currentModeId\":\"ask\",\"availableModes\":[{\"id\":\"ask\",\"name\":\"Ask\"},{\"id\":\"code\",\"name\":\"Code\"}]}}}" ;;
+  esac
+done"#;
+        let host = Arc::new(RecordingHost::default());
+        let (connection, session_id) = AcpAgent::connect(
+            stub_adapter(script),
+            std::env::temp_dir(),
+            host.clone(),
+            None,
+            Vec::new(),
+        )
+        .await
+        .unwrap();
+
+        let unknown = connection
+            .set_mode(session_id.clone(), "deploy".to_string())
+            .await;
+        assert!(matches!(unknown, Err(ApiError::InvalidInput { .. })));
+        connection
+            .set_mode(session_id.clone(), "code".to_string())
+            .await
+            .unwrap();
+
+        let modes: Vec<serde_json::Value> = host
+            .updates
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|(session, _, update)| match update {
+                AcpSessionUpdate::CurrentModeUpdate { current_mode_id } => {
+                    assert_eq!(session, &session_id);
+                    Some(current_mode_id.clone())
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            modes,
+            vec![serde_json::json!("ask"), serde_json::json!("code")]
+        );
+        connection.shutdown().await.unwrap();
+    }
+
+    #[test]
+    fn test_fs_results_cite_resolved_paths() {
+        let read = fs_read_result_json(&FsReadTextFileResult {
+            content: "//4AAQ==".to_string(),
+            encoding: FsContentEncoding::Base64,
+            path: "assets/logo.png".to_string(),
+            absolute_path: PathBuf::from("/work/project/assets/logo.png"),
+        });
+        assert_eq!(
+            read,
+            serde_json::json!({
+                "content": "//4AAQ==",
+                "encoding": "base64",
+                "path": "assets/logo.png",
+                "absolutePath": "/work/project/assets/logo.png"
+            })
+        );
+        let text = fs_read_result_json(&FsReadTextFileResult {
+            content: "fn main() {}".to_string(),
+            encoding: FsContentEncoding::Text,
+            path: "src/main.rs".to_string(),
+            absolute_path: PathBuf::from("/work/project/src/main.rs"),
+        });
+        assert_eq!(text["encoding"], "utf8");
+
+        let written = fs_write_result_json(&FsWriteTextFileResult {
+            bytes_written: 11,
+            path: "src/main.rs".to_string(),
+            absolute_path: PathBuf::from("/work/project/src/main.rs"),
+        });
+        assert_eq!(
+            written,
+            serde_json::json!({
+                "bytesWritten": 11,
+                "path": "src/main.rs",
+                "absolutePath": "/work/project/src/main.rs"
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_stop_reason_from_prompt_response() {
+        assert_eq!(
+            parse_stop_reason(&serde_json::json!({"stopReason": "max_tokens"})),
+            Some(StopReason::MaxTokens)
+        );
+        assert_eq!(
+            parse_stop_reason(&serde_json::json!({"stop_reason": "refusal"})),
+            Some(StopReason::Refusal)
+        );
+        assert_eq!(
+            parse_stop_reason(&serde_json::json!({"stopReason": "paused"})),
+            Some(StopReason::Other("paused".to_string()))
+        );
+        assert_eq!(
+            parse_stop_reason(&serde_json::json!({"stopReason": {"type": "odd"}})),
+            Some(StopReason::Other(r#"{"type":"odd"}"#.to_string()))
+        );
+        // Responses to other requests have no stop reason
+        assert_eq!(
+            parse_stop_reason(&serde_json::json!({"sessionId": "s1"})),
+            None
+        );
+    }
+
+    #[test]
+    fn test_response_error_none_on_success() {
+        let response = serde_json::json!({"jsonrpc": "2.0", "id": "1", "result": {}});
+        assert!(response_error(
+            &response,
+            &"s1".to_string(),
+            "Prompt",
+            ProtocolErrorKind::RequestFailed
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn test_response_error_detects_session_not_found() {
+        let by_code = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": "1",
+            "error": {"code": -32002, "message": "Session abc not found"}
+        });
+        let by_message = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": "1",
+            "error": {"code": -32603, "message": "Unknown session: abc"}
+        });
+
+        for response in [by_code, by_message] {
+            match response_error(
+                &response,
+                &"abc".to_string(),
+                "Prompt",
+                ProtocolErrorKind::RequestFailed,
+            ) {
+                Some(ApiError::SessionNotFound { session_id }) => assert_eq!(session_id, "abc"),
+                other => panic!("expected SessionNotFound, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_response_error_other_errors_are_protocol_errors() {
+        let response = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": "1",
+            "error": {"code": -32603, "message": "Internal error"}
+        });
+        assert!(matches!(
+            response_error(
+                &response,
+                &"abc".to_string(),
+                "Prompt",
+                ProtocolErrorKind::RequestFailed
+            ),
+            Some(ApiError::ProtocolError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_jsonrpc_id_key() {
+        assert_eq!(
+            jsonrpc_id_key(&serde_json::json!("abc")),
+            Some("abc".to_string())
+        );
+        assert_eq!(jsonrpc_id_key(&serde_json::json!(7)), Some("7".to_string()));
+        assert_eq!(jsonrpc_id_key(&serde_json::Value::Null), None);
     }
 }
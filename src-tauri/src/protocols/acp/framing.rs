@@ -0,0 +1,132 @@
+//! Newline-delimited framing for adapter output.
+//!
+//! ACP messages are JSON-RPC objects terminated by `\n`. `FrameReader`
+//! accumulates bytes until a newline, caps how large a single frame may grow
+//! so a misbehaving adapter can't exhaust memory, and yields a trailing
+//! unterminated frame at EOF.
+
+use tokio::io::{AsyncBufRead, AsyncBufReadExt};
+
+use crate::api::types::{ApiError, ProtocolErrorKind};
+
+/// Reads `\n`-terminated frames from an adapter stream.
+pub struct FrameReader<R> {
+    reader: R,
+    buf: Vec<u8>,
+    max_frame_bytes: usize,
+}
+
+impl<R: AsyncBufRead + Unpin> FrameReader<R> {
+    /// Create a reader that rejects frames longer than `max_frame_bytes`.
+    pub fn new(reader: R, max_frame_bytes: usize) -> Self {
+        Self {
+            reader,
+            buf: Vec::new(),
+            max_frame_bytes,
+        }
+    }
+
+    /// Read the next frame, without its line terminator.
+    ///
+    /// Returns `Ok(None)` at EOF. Invalid UTF-8 is replaced rather than
+    /// rejected so one bad byte doesn't end the stream.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ApiError::ProtocolError` if a frame exceeds the size limit,
+    /// and `ApiError::IoError` if the stream fails. The stream is not usable
+    /// after an error.
+    pub async fn next_frame(&mut self) -> Result<Option<String>, ApiError> {
+        self.buf.clear();
+
+        loop {
+            let available = self
+                .reader
+                .fill_buf()
+                .await
+                .map_err(|e| ApiError::IoError {
+                    message: format!("Failed to read adapter output: {e}"),
+                })?;
+
+            if available.is_empty() {
+                // EOF: hand out a final line that lacked its newline
+                if self.buf.is_empty() {
+                    return Ok(None);
+                }
+                return Ok(Some(self.take_frame()));
+            }
+
+            let (consumed, complete) = match available.iter().position(|&b| b == b'\n') {
+                Some(newline) => {
+                    self.buf.extend_from_slice(&available[..newline]);
+                    (newline + 1, true)
+                }
+                None => {
+                    self.buf.extend_from_slice(available);
+                    (available.len(), false)
+                }
+            };
+            self.reader.consume(consumed);
+
+            if self.buf.len() > self.max_frame_bytes {
+                let size = self.buf.len();
+                self.buf = Vec::new();
+                return Err(ApiError::ProtocolError {
+                    kind: ProtocolErrorKind::FrameTooLarge,
+                    message: format!(
+                        "Adapter message exceeds {} bytes (got at least {size})",
+                        self.max_frame_bytes
+                    ),
+                });
+            }
+
+            if complete {
+                return Ok(Some(self.take_frame()));
+            }
+        }
+    }
+
+    fn take_frame(&mut self) -> String {
+        if self.buf.last() == Some(&b'\r') {
+            self.buf.pop();
+        }
+        let frame = String::from_utf8_lossy(&self.buf).into_owned();
+        self.buf.clear();
+        frame
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::BufReader;
+
+    #[tokio::test]
+    async fn test_frames_split_across_reads_and_trailing_partial_line() {
+        // A tiny buffer forces frames to be assembled from several reads
+        let input: &[u8] = b"{\"id\":1}\r\n{\"id\":2}\n{\"id\":3}";
+        let mut reader = FrameReader::new(BufReader::with_capacity(4, input), 64);
+
+        assert_eq!(reader.next_frame().await.unwrap().unwrap(), r#"{"id":1}"#);
+        assert_eq!(reader.next_frame().await.unwrap().unwrap(), r#"{"id":2}"#);
+        assert_eq!(reader.next_frame().await.unwrap().unwrap(), r#"{"id":3}"#);
+        assert!(reader.next_frame().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_oversized_frame_is_rejected() {
+        let mut input = vec![b'x'; 100];
+        input.push(b'\n');
+        let mut reader = FrameReader::new(BufReader::with_capacity(16, &input[..]), 32);
+
+        match reader.next_frame().await {
+            Err(ApiError::ProtocolError {
+                kind: ProtocolErrorKind::FrameTooLarge,
+                message,
+            }) => {
+                assert!(message.contains("exceeds 32 bytes"), "{message}");
+            }
+            other => panic!("expected ProtocolError, got {other:?}"),
+        }
+    }
+}
@@ -4,6 +4,10 @@
 //! to communicate with adapter binaries (claude-code, codex, gemini, etc.).
 
 mod agent;
+mod framing;
 mod update_mapping;
 
-pub use agent::AcpAgent;
+pub use agent::{AcpAgent, AcpConnectOptions, AcpKeepalive};
+
+#[cfg(test)]
+pub(crate) use agent::tests::NoopHost;
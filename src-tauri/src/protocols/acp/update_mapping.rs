@@ -1,7 +1,8 @@
 use agent_client_protocol as acp;
+use serde::de::DeserializeOwned;
 use serde::Serialize;
 
-use crate::api::types::{AcpSessionUpdate, SessionId};
+use crate::api::types::{AcpSessionUpdate, AcpToolCall, AcpToolCallUpdate, SessionId};
 
 pub fn parse_acp_session_notification_params(
     params: serde_json::Value,
@@ -25,12 +26,27 @@ pub fn map_acp_update_to_api_update(update: acp::SessionUpdate) -> AcpSessionUpd
         acp::SessionUpdate::AgentThoughtChunk(chunk) => AcpSessionUpdate::AgentThoughtChunk {
             content: to_value_or_null(chunk.content),
         },
-        acp::SessionUpdate::ToolCall(tool_call) => AcpSessionUpdate::ToolCall {
-            tool_call: to_value_or_null(tool_call),
-        },
-        acp::SessionUpdate::ToolCallUpdate(tool_call_update) => AcpSessionUpdate::ToolCallUpdate {
-            tool_call_update: to_value_or_null(tool_call_update),
-        },
+        acp::SessionUpdate::ToolCall(tool_call) => {
+            match typed_with_raw(to_value_or_null(&tool_call), |t: &mut AcpToolCall| {
+                &mut t.raw
+            }) {
+                Some(tool_call) => AcpSessionUpdate::ToolCall { tool_call },
+                None => AcpSessionUpdate::Raw {
+                    json: to_value_or_null(acp::SessionUpdate::ToolCall(tool_call)),
+                },
+            }
+        }
+        acp::SessionUpdate::ToolCallUpdate(tool_call_update) => {
+            match typed_with_raw(
+                to_value_or_null(&tool_call_update),
+                |t: &mut AcpToolCallUpdate| &mut t.raw,
+            ) {
+                Some(tool_call_update) => AcpSessionUpdate::ToolCallUpdate { tool_call_update },
+                None => AcpSessionUpdate::Raw {
+                    json: to_value_or_null(acp::SessionUpdate::ToolCallUpdate(tool_call_update)),
+                },
+            }
+        }
         acp::SessionUpdate::Plan(plan) => AcpSessionUpdate::Plan {
             plan: to_value_or_null(plan),
         },
@@ -51,6 +67,25 @@ pub fn map_acp_update_to_api_update(update: acp::SessionUpdate) -> AcpSessionUpd
     }
 }
 
+/// Parse `json` into a typed payload, keeping the original in its `raw` field.
+///
+/// Returns `None` when the shape isn't recognized.
+fn typed_with_raw<T: DeserializeOwned>(
+    json: serde_json::Value,
+    raw: impl FnOnce(&mut T) -> &mut serde_json::Value,
+) -> Option<T> {
+    match serde_json::from_value::<T>(json.clone()) {
+        Ok(mut typed) => {
+            *raw(&mut typed) = json;
+            Some(typed)
+        }
+        Err(e) => {
+            log::debug!("Unrecognized tool call shape, using Raw: {e}");
+            None
+        }
+    }
+}
+
 fn to_value_or_null<T: Serialize>(value: T) -> serde_json::Value {
     serde_json::to_value(value).unwrap_or(serde_json::Value::Null)
 }
@@ -114,4 +149,41 @@ mod tests {
             _ => panic!("Expected AgentMessageChunk"),
         }
     }
+
+    #[test]
+    fn test_map_tool_call_is_typed_and_keeps_raw() {
+        let params = serde_json::json!({
+            "sessionId": "session-1",
+            "update": {
+                "sessionUpdate": "tool_call",
+                "toolCallId": "toolu_01",
+                "title": "Edit src/lib.rs",
+                "kind": "edit",
+                "status": "pending",
+                "locations": [{ "path": "/repo/src/lib.rs" }],
+                "rawInput": { "file_path": "/repo/src/lib.rs" }
+            }
+        });
+        let (_, update) = parse_acp_session_notification_params(params, &"fallback".to_string())
+            .expect("parse tool call");
+
+        match map_acp_update_to_api_update(update) {
+            AcpSessionUpdate::ToolCall { tool_call } => {
+                assert_eq!(tool_call.tool_call_id, "toolu_01");
+                assert_eq!(tool_call.kind, crate::api::types::ToolCallKind::Edit);
+                assert_eq!(tool_call.locations[0].path, "/repo/src/lib.rs");
+                assert_eq!(tool_call.raw["toolCallId"], "toolu_01");
+            }
+            other => panic!("Expected ToolCall, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_typed_with_raw_rejects_unrecognized_shape() {
+        let parsed = typed_with_raw(
+            serde_json::json!({ "title": "no id" }),
+            |t: &mut AcpToolCall| &mut t.raw,
+        );
+        assert!(parsed.is_none());
+    }
 }
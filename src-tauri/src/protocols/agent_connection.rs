@@ -3,7 +3,9 @@
 //! This trait defines how the runtime layer interacts with agent connections
 //! without knowing protocol-specific details (ACP, MCP, etc.).
 
-use crate::api::types::{ApiError, SessionId};
+use std::time::Duration;
+
+use crate::api::types::{ApiError, PromptContentBlock, ProtocolErrorKind, SessionId, TurnId};
 use async_trait::async_trait;
 
 /// Abstract interface for agent protocol connections.
@@ -13,25 +15,53 @@ use async_trait::async_trait;
 /// without coupling to any specific protocol implementation.
 ///
 /// US-06: Only `shutdown()` is needed for lazy startup.
-/// US-07: Adds `send_prompt()` method for sending user prompts.
+/// US-07: Adds `send_prompt()` method for sending user prompts (text and attachments);
+/// `send_prompt_with_timeout()` bounds how long the turn may run.
 /// US-12: Adds `cancel_turn()` method.
+/// `new_session()` lets the runtime recover when the adapter forgets a session,
+/// and open further sessions on the same connection; `close_session()` ends one.
+/// `set_mode()` switches a session between the modes its adapter offers.
+/// `is_connected()` lets the runtime notice a dead adapter after a failed call.
 #[async_trait]
 #[allow(dead_code)]
 pub trait AgentConnection: Send + Sync {
     /// Send a prompt to the agent.
     ///
-    /// US-07: Sends the user's prompt text to the agent process via the
+    /// US-07: Sends the user's prompt to the agent process via the
     /// protocol-specific transport (e.g., JSON-RPC over stdin for ACP).
     /// Streaming responses arrive asynchronously via AgentHost callbacks.
     ///
     /// # Arguments
     /// * `session_id` - The session to send the prompt to
-    /// * `prompt` - The user's prompt text
+    /// * `prompt` - The prompt content (text, images, resource links)
     ///
     /// # Returns
-    /// * `Ok(())` - Prompt sent successfully
+    /// * `Ok(TurnId)` - Prompt accepted; the id tags this turn's session updates.
+    ///   Acceptance may be inferred (ACP has no explicit signal), so a later
+    ///   rejection ends the turn with a `TurnComplete` error instead
+    /// * `Err(ApiError::SessionNotFound)` - The adapter does not know the session
     /// * `Err(ApiError)` - Protocol error, connection closed, etc.
-    async fn send_prompt(&self, session_id: SessionId, prompt: String) -> Result<(), ApiError>;
+    async fn send_prompt(
+        &self,
+        session_id: SessionId,
+        prompt: Vec<PromptContentBlock>,
+    ) -> Result<TurnId, ApiError>;
+
+    /// Like [`Self::send_prompt`], but the turn may run for at most `timeout`
+    /// (no limit if `None`).
+    ///
+    /// A prompt the adapter hasn't accepted by then fails with a
+    /// `RequestTimeout` protocol error; an accepted turn still running is
+    /// cancelled and ends with a `TurnComplete` error. Connections that
+    /// can't enforce a timeout ignore it.
+    async fn send_prompt_with_timeout(
+        &self,
+        session_id: SessionId,
+        prompt: Vec<PromptContentBlock>,
+        _timeout: Option<Duration>,
+    ) -> Result<TurnId, ApiError> {
+        self.send_prompt(session_id, prompt).await
+    }
 
     /// Cancel the current turn for the given session.
     ///
@@ -45,6 +75,61 @@ pub trait AgentConnection: Send + Sync {
     /// * `Err(ApiError)` - Protocol error, connection closed, etc.
     async fn cancel_turn(&self, session_id: SessionId) -> Result<(), ApiError>;
 
+    /// Create a fresh session on the existing connection.
+    ///
+    /// Used to hold several conversations with one adapter process; see
+    /// [`Self::replace_session`] for recovering a lost one.
+    ///
+    /// # Returns
+    /// * `Ok(SessionId)` - The adapter-issued ID of the new session
+    /// * `Err(ApiError)` - Protocol error, connection closed, etc.
+    async fn new_session(&self) -> Result<SessionId, ApiError>;
+
+    /// Create a fresh session to take over from `lost`, which the adapter
+    /// no longer knows (e.g., it restarted without the client noticing).
+    ///
+    /// Unlike [`Self::new_session`], connections that track a default
+    /// session move it to the replacement.
+    async fn replace_session(&self, _lost: SessionId) -> Result<SessionId, ApiError> {
+        self.new_session().await
+    }
+
+    /// Close a session created on this connection.
+    ///
+    /// Its active turn (if any) is cancelled first. Adapters that can't close
+    /// sessions simply stop hearing about it, so the default does nothing.
+    ///
+    /// # Returns
+    /// * `Ok(())` - Session closed (or already unknown to the adapter)
+    /// * `Err(ApiError)` - Protocol error, connection closed, etc.
+    async fn close_session(&self, _session_id: SessionId) -> Result<(), ApiError> {
+        Ok(())
+    }
+
+    /// Switch a session to one of the modes its adapter offers.
+    ///
+    /// # Returns
+    /// * `Ok(())` - Mode changed
+    /// * `Err(ApiError::InvalidInput)` - The adapter announced its modes and
+    ///   `mode_id` isn't one of them
+    /// * `Err(ApiError::ProtocolError)` - The adapter has no modes, rejected
+    ///   the change, or the connection closed
+    async fn set_mode(&self, _session_id: SessionId, _mode_id: String) -> Result<(), ApiError> {
+        Err(ApiError::ProtocolError {
+            kind: ProtocolErrorKind::Unsupported,
+            message: "Agent does not support session modes".to_string(),
+        })
+    }
+
+    /// Whether the underlying transport is still usable.
+    ///
+    /// Returns `false` once the adapter is known to be gone (e.g. it closed
+    /// its stdin); the runtime then drops the connection and restarts the
+    /// agent on the next prompt.
+    fn is_connected(&self) -> bool {
+        true
+    }
+
     /// Shutdown the agent connection gracefully.
     ///
     /// This should terminate the child process and clean up resources.
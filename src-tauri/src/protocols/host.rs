@@ -7,11 +7,14 @@
 //! These are captured by the runtime's AgentHost implementation and attached
 //! to events when emitting to the frontend.
 
+use std::collections::HashMap;
+use std::path::PathBuf;
+
 use async_trait::async_trait;
 
 use crate::api::types::{
-    AcpSessionUpdate, AgentRuntimeStatus, ApiError, OperationId, PermissionDecision,
-    PermissionSource, SessionId, TerminalId,
+    AcpSessionUpdate, AgentRuntimeStatus, ApiError, OperationId, PermissionOption,
+    PermissionOutcome, PermissionSource, ProtocolTraceDirection, SessionId, TerminalId, TurnId,
 };
 
 /// Permission request from a protocol adapter.
@@ -21,13 +24,22 @@ pub struct PermissionRequest {
     pub session_id: Option<SessionId>,
     pub tool_call_id: Option<String>,
     pub operation_id: Option<OperationId>,
+    /// Choices offered by the adapter; empty for a plain allow/deny prompt
+    pub options: Vec<PermissionOption>,
 }
 
 /// Terminal run request from a protocol adapter.
 #[derive(Debug, Clone)]
 pub struct TerminalRunRequest {
     pub command: String,
+    /// Extra environment variables; they override inherited values
+    pub env: Option<HashMap<String, String>>,
+    /// Kill the command if it runs longer than this
+    pub timeout_ms: Option<u64>,
     pub operation_id: Option<OperationId>,
+    /// Return the command's output in the result; when false it is only
+    /// streamed to the UI and the result's `stdout`/`stderr` are empty
+    pub capture_output: bool,
 }
 
 /// Terminal run result returned to the protocol adapter.
@@ -35,23 +47,85 @@ pub struct TerminalRunRequest {
 pub struct TerminalRunResult {
     pub terminal_id: TerminalId,
     pub exit_code: Option<i32>,
+    /// Whether the command was killed for exceeding `timeout_ms`
+    pub timed_out: bool,
     pub stdout: String,
     pub stderr: String,
 }
 
+/// Request to start a retained terminal (ACP `terminal/create`).
+#[derive(Debug, Clone)]
+pub struct TerminalCreateRequest {
+    pub command: String,
+    pub args: Vec<String>,
+    /// Extra environment variables; they override inherited values
+    pub env: Option<HashMap<String, String>>,
+    /// Working directory; only the workspace root is accepted
+    pub cwd: Option<PathBuf>,
+    /// Maximum bytes of output to retain; older output is dropped first
+    pub output_byte_limit: Option<usize>,
+    pub operation_id: Option<OperationId>,
+}
+
+/// Exit status of a retained terminal.
+#[derive(Debug, Clone)]
+pub struct TerminalExitStatus {
+    pub exit_code: Option<i32>,
+    /// Signal that terminated the process (Unix only)
+    pub signal: Option<i32>,
+    pub user_stopped: bool,
+}
+
+/// Output of a retained terminal (ACP `terminal/output`).
+#[derive(Debug, Clone)]
+pub struct TerminalOutputResult {
+    pub output: String,
+    pub truncated: bool,
+    /// Present once the command has exited
+    pub exit_status: Option<TerminalExitStatus>,
+}
+
 /// File read request from a protocol adapter.
 #[derive(Debug, Clone)]
 pub struct FsReadTextFileRequest {
     pub path: String,
+    /// First line to read (1-based); `None` reads from the start
+    pub line: Option<u32>,
+    /// Maximum number of lines to read; `None` reads to the end
+    pub limit: Option<u32>,
+    /// Text (with base64 fallback) or base64
+    pub encoding: FsReadEncoding,
     pub session_id: Option<SessionId>,
     pub tool_call_id: Option<String>,
     pub operation_id: Option<OperationId>,
 }
 
+/// How a file read wants its content encoded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FsReadEncoding {
+    /// UTF-8 text; files that aren't valid UTF-8 come back as base64
+    #[default]
+    Text,
+    /// Raw bytes as base64
+    Base64,
+}
+
+/// Encoding of the content in a file read result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsContentEncoding {
+    Text,
+    Base64,
+}
+
 /// File read result returned to the protocol adapter.
 #[derive(Debug, Clone)]
 pub struct FsReadTextFileResult {
     pub content: String,
+    pub encoding: FsContentEncoding,
+    /// The file that was read, relative to the workspace root
+    pub path: String,
+    /// The file that was read, as an absolute path
+    pub absolute_path: PathBuf,
 }
 
 /// File write request from a protocol adapter.
@@ -59,6 +133,8 @@ pub struct FsReadTextFileResult {
 pub struct FsWriteTextFileRequest {
     pub path: String,
     pub content: String,
+    /// Append to the file instead of replacing it
+    pub append: bool,
     pub session_id: Option<SessionId>,
     pub tool_call_id: Option<String>,
     pub operation_id: Option<OperationId>,
@@ -66,7 +142,37 @@ pub struct FsWriteTextFileRequest {
 
 /// File write result returned to the protocol adapter.
 #[derive(Debug, Clone)]
-pub struct FsWriteTextFileResult;
+pub struct FsWriteTextFileResult {
+    /// Bytes written (or appended)
+    pub bytes_written: u64,
+    /// The file that was written, relative to the workspace root
+    pub path: String,
+    /// The file that was written, as an absolute path
+    pub absolute_path: PathBuf,
+}
+
+/// File delete request from a protocol adapter.
+#[derive(Debug, Clone)]
+pub struct FsDeleteRequest {
+    pub path: String,
+    /// Allow deleting a directory and everything in it
+    pub recursive: bool,
+    pub session_id: Option<SessionId>,
+    pub tool_call_id: Option<String>,
+    pub operation_id: Option<OperationId>,
+}
+
+/// File rename (move) request from a protocol adapter.
+#[derive(Debug, Clone)]
+pub struct FsRenameRequest {
+    pub from: String,
+    pub to: String,
+    /// Allow moving a directory
+    pub recursive: bool,
+    pub session_id: Option<SessionId>,
+    pub tool_call_id: Option<String>,
+    pub operation_id: Option<OperationId>,
+}
 
 /// Callback interface for protocol implementations to interact with runtime.
 ///
@@ -94,8 +200,14 @@ pub trait AgentHost: Send + Sync {
     ///
     /// # Arguments
     /// * `session_id` - The session this update belongs to
+    /// * `turn_id` - The prompt turn it belongs to, if the adapter identified it
     /// * `update` - The session update payload
-    fn on_session_update(&self, session_id: SessionId, update: AcpSessionUpdate);
+    fn on_session_update(
+        &self,
+        session_id: SessionId,
+        turn_id: Option<TurnId>,
+        update: AcpSessionUpdate,
+    );
 
     /// Notify that the connection has been lost (process exited).
     ///
@@ -104,11 +216,29 @@ pub trait AgentHost: Send + Sync {
     /// This is advisory - the runtime may choose to keep state for debugging.
     fn on_connection_lost(&self);
 
+    /// Whether raw protocol frames should be passed to `on_protocol_trace`.
+    ///
+    /// Off unless the host opts in, so prompt content isn't copied around.
+    fn protocol_trace_enabled(&self) -> bool {
+        false
+    }
+
+    /// Report a raw (redacted) protocol frame; only called while
+    /// `protocol_trace_enabled()` is true.
+    fn on_protocol_trace(&self, _direction: ProtocolTraceDirection, _raw: String) {}
+
+    /// Abandon work started for the adapter's requests that is still
+    /// outstanding: kill its terminals and cancel its permission prompts.
+    ///
+    /// Called when the connection shuts down, after the tasks handling those
+    /// requests have been aborted.
+    async fn cancel_in_flight_requests(&self) {}
+
     /// Request permission from the user and await decision (US-08).
     async fn request_permission(
         &self,
         request: PermissionRequest,
-    ) -> Result<PermissionDecision, ApiError>;
+    ) -> Result<PermissionOutcome, ApiError>;
 
     /// Execute a terminal command (US-08).
     async fn terminal_run(
@@ -116,6 +246,28 @@ pub trait AgentHost: Send + Sync {
         request: TerminalRunRequest,
     ) -> Result<TerminalRunResult, ApiError>;
 
+    /// Start a terminal command and return its ID without waiting for exit.
+    async fn terminal_create(&self, request: TerminalCreateRequest)
+        -> Result<TerminalId, ApiError>;
+
+    /// Output captured so far for a terminal started with `terminal_create`.
+    async fn terminal_output(
+        &self,
+        terminal_id: TerminalId,
+    ) -> Result<TerminalOutputResult, ApiError>;
+
+    /// Wait for a terminal started with `terminal_create` to exit.
+    async fn terminal_wait_for_exit(
+        &self,
+        terminal_id: TerminalId,
+    ) -> Result<TerminalExitStatus, ApiError>;
+
+    /// Kill a terminal's command, keeping its output until released.
+    async fn terminal_kill(&self, terminal_id: TerminalId) -> Result<(), ApiError>;
+
+    /// Kill a terminal (if still running) and free its resources.
+    async fn terminal_release(&self, terminal_id: TerminalId) -> Result<(), ApiError>;
+
     /// Read a text file (US-10).
     async fn fs_read_text_file(
         &self,
@@ -127,4 +279,10 @@ pub trait AgentHost: Send + Sync {
         &self,
         request: FsWriteTextFileRequest,
     ) -> Result<FsWriteTextFileResult, ApiError>;
+
+    /// Delete a file (or, when recursive, a directory).
+    async fn fs_delete(&self, request: FsDeleteRequest) -> Result<(), ApiError>;
+
+    /// Rename (move) a file within the workspace.
+    async fn fs_rename(&self, request: FsRenameRequest) -> Result<(), ApiError>;
 }
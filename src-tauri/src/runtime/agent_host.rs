@@ -7,27 +7,37 @@
 //! Key design principle: The AgentHost implementation holds workspace/agent context,
 //! so the protocol layer never needs to know about these business concepts.
 
-use std::collections::HashMap;
-use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::Arc;
-use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
 
-use tauri::Emitter;
+use tauri::{Emitter, Manager};
 use tokio::sync::Mutex;
 use uuid::Uuid;
 
 use crate::api::types::{
-    AcpSessionUpdate, AcpSessionUpdateEvent, AgentId, AgentRuntimeStatus, AgentStatusChangedEvent,
-    ApiError, OperationId, PermissionDecision, PermissionOrigin, PermissionSource, SessionId,
-    TerminalExitedEvent, TerminalOutputEvent, TerminalStream, WorkspaceId,
+    AcpProtocolTraceEvent, AcpSessionUpdate, AcpSessionUpdateEvent, AgentId, AgentRuntimeStatus,
+    AgentStatusChangedEvent, ApiError, NotificationLevel, OperationId, PermissionDecision,
+    PermissionOrigin, PermissionOutcome, PermissionSource, ProtocolTraceDirection, SessionId,
+    TerminalExitedEvent, TerminalId, TerminalOutputEvent, TerminalStream, TurnId, WorkspaceId,
 };
 use crate::protocols::host::{
-    AgentHost, FsReadTextFileRequest, FsReadTextFileResult, FsWriteTextFileRequest,
-    FsWriteTextFileResult, PermissionRequest, TerminalRunRequest, TerminalRunResult,
+    AgentHost, FsContentEncoding, FsDeleteRequest, FsReadEncoding, FsReadTextFileRequest,
+    FsReadTextFileResult, FsRenameRequest, FsWriteTextFileRequest, FsWriteTextFileResult,
+    PermissionRequest, TerminalCreateRequest, TerminalExitStatus, TerminalOutputResult,
+    TerminalRunRequest, TerminalRunResult,
 };
-use crate::runtime::fs::FsManager;
+use crate::runtime::agents::AgentRuntime;
+use crate::runtime::cancellation::{CancellationToken, TurnCancellations};
+use crate::runtime::fs::{FileContent, FileRead, FileWrite, FsManager};
+use crate::runtime::notifier::Notifier;
 use crate::runtime::permissions::PermissionHub;
-use crate::runtime::terminal::{TerminalExit, TerminalManager, TerminalRunHandle};
+use crate::runtime::session_updates::{AgentLabels, ChunkCoalescing, SessionUpdateEmitter};
+use crate::runtime::terminal::{
+    command_line, TerminalExit, TerminalManager, TerminalObserver, TerminalRunHandle,
+};
+use crate::runtime::transcripts::SessionTranscripts;
+use crate::utils::text::truncate_on_char_boundary;
 
 /// Event name for agent status changes
 pub const EVENT_AGENT_STATUS_CHANGED: &str = "agent/status_changed";
@@ -41,6 +51,9 @@ pub const EVENT_TERMINAL_OUTPUT: &str = "terminal/output";
 /// Event name for terminal exit (US-08)
 pub const EVENT_TERMINAL_EXITED: &str = "terminal/exited";
 
+/// Event name for raw protocol frames of agents with tracing on
+pub const EVENT_ACP_PROTOCOL_TRACE: &str = "acp/protocol_trace";
+
 /// Runtime implementation of AgentHost trait.
 ///
 /// Holds workspace and agent context, enabling protocol implementations
@@ -51,20 +64,26 @@ pub const EVENT_TERMINAL_EXITED: &str = "terminal/exited";
 pub struct RuntimeAgentHost {
     /// Tauri application handle for emitting events
     app: tauri::AppHandle,
-    /// Workspace ID for context
-    workspace_id: WorkspaceId,
-    /// Agent ID for context
-    agent_id: AgentId,
+    /// Workspace, agent and the agent's labels, for context and events
+    labels: AgentLabels,
     /// Permission hub for approval flow
     permission_hub: Arc<PermissionHub>,
     /// Terminal manager for command execution
     terminal_manager: Arc<TerminalManager>,
     /// File system manager for workspace-scoped reads
     fs_manager: Arc<FsManager>,
+    /// Per-session tokens that abort file operations when a turn is cancelled
+    turn_cancellations: Arc<TurnCancellations>,
     /// Pre-approved operation IDs from ACP request_permission
     preapproved_ops: Mutex<HashMap<OperationId, Instant>>,
-    /// Monotonic sequence for ACP session updates (for deterministic ordering)
-    session_update_seq: AtomicU64,
+    /// Terminals and permission prompts still outstanding for the adapter
+    in_flight: InFlightOperations,
+    /// Numbers ACP session updates and emits them in order
+    session_updates: SessionUpdateEmitter,
+    /// Runtime that owns this host's connection (weak to avoid an Arc cycle)
+    runtime: Weak<AgentRuntime>,
+    /// Connection generation of `runtime` this host was created for
+    generation: u64,
 }
 
 impl RuntimeAgentHost {
@@ -72,85 +91,224 @@ impl RuntimeAgentHost {
     ///
     /// # Arguments
     /// * `app` - Tauri application handle
-    /// * `workspace_id` - The workspace this agent belongs to
-    /// * `agent_id` - The agent this host is for
+    /// * `labels` - The agent this host is for, its workspace and its labels
     /// * `permission_hub` - Permission hub for approval flow
     /// * `terminal_manager` - Terminal manager scoped to the workspace
+    /// * `fs_manager` - File system manager scoped to the workspace
+    /// * `turn_cancellations` - Cancellation registry shared with the agent runtime
+    /// * `runtime` - The agent runtime, reset when the connection is lost
+    /// * `generation` - The runtime's connection generation this host serves
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         app: tauri::AppHandle,
-        workspace_id: WorkspaceId,
-        agent_id: AgentId,
+        labels: AgentLabels,
         permission_hub: Arc<PermissionHub>,
         terminal_manager: Arc<TerminalManager>,
         fs_manager: Arc<FsManager>,
+        turn_cancellations: Arc<TurnCancellations>,
+        runtime: Weak<AgentRuntime>,
+        generation: u64,
     ) -> Arc<Self> {
+        let session_updates = session_update_emitter(app.clone(), labels.clone());
         Arc::new(Self {
             app,
-            workspace_id,
-            agent_id,
+            labels,
             permission_hub,
             terminal_manager,
             fs_manager,
+            turn_cancellations,
             preapproved_ops: Mutex::new(HashMap::new()),
-            session_update_seq: AtomicU64::new(0),
+            in_flight: InFlightOperations::default(),
+            session_updates,
+            runtime,
+            generation,
         })
     }
+
+    /// Cancellation token for the turn a host request belongs to.
+    fn turn_token(&self, session_id: Option<&str>) -> CancellationToken {
+        session_id
+            .map(|session_id| self.turn_cancellations.token(session_id))
+            .unwrap_or_default()
+    }
+}
+
+/// Terminals and permission prompts started for the adapter's requests that
+/// haven't finished yet, so a shutdown can clean up after aborted handlers.
+#[derive(Default)]
+struct InFlightOperations {
+    terminals: std::sync::Mutex<HashSet<TerminalId>>,
+    permissions: std::sync::Mutex<HashSet<OperationId>>,
+}
+
+impl InFlightOperations {
+    fn track_terminal(&self, terminal_id: &TerminalId) {
+        lock_unpoisoned(&self.terminals).insert(terminal_id.clone());
+    }
+
+    fn finish_terminal(&self, terminal_id: &TerminalId) {
+        lock_unpoisoned(&self.terminals).remove(terminal_id);
+    }
+
+    fn track_permission(&self, operation_id: &OperationId) {
+        lock_unpoisoned(&self.permissions).insert(operation_id.clone());
+    }
+
+    fn finish_permission(&self, operation_id: &OperationId) {
+        lock_unpoisoned(&self.permissions).remove(operation_id);
+    }
+
+    /// Kill every tracked terminal and forget it.
+    async fn kill_terminals(&self, terminal_manager: &TerminalManager) {
+        let terminals: Vec<TerminalId> = lock_unpoisoned(&self.terminals).drain().collect();
+        for terminal_id in terminals {
+            if let Err(e) = terminal_manager.kill(terminal_id.clone()).await {
+                log::warn!("Failed to kill abandoned terminal {terminal_id}: {e}");
+            }
+        }
+    }
+
+    /// Forget and return every tracked permission prompt.
+    fn take_permissions(&self) -> Vec<OperationId> {
+        lock_unpoisoned(&self.permissions).drain().collect()
+    }
+}
+
+fn lock_unpoisoned<T>(mutex: &std::sync::Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Mirrors a created terminal's output and exit to the frontend.
+struct TerminalEventEmitter {
+    app: tauri::AppHandle,
+    workspace_id: WorkspaceId,
+    agent_id: AgentId,
+    operation_id: Option<OperationId>,
+}
+
+impl TerminalObserver for TerminalEventEmitter {
+    fn on_output(&self, terminal_id: &TerminalId, stream: TerminalStream, chunk: &str) {
+        let event = TerminalOutputEvent {
+            workspace_id: self.workspace_id.clone(),
+            agent_id: self.agent_id.clone(),
+            operation_id: self.operation_id.clone(),
+            terminal_id: terminal_id.clone(),
+            stream,
+            chunk: chunk.to_string(),
+        };
+        if let Err(e) = self.app.emit(EVENT_TERMINAL_OUTPUT, &event) {
+            log::error!(
+                "Failed to emit terminal/output: {e} (workspace={}, agent={}, terminal={terminal_id})",
+                self.workspace_id,
+                self.agent_id
+            );
+        }
+    }
+
+    fn on_exit(&self, terminal_id: &TerminalId, exit: &TerminalExit) {
+        let event = TerminalExitedEvent {
+            workspace_id: self.workspace_id.clone(),
+            agent_id: self.agent_id.clone(),
+            operation_id: self.operation_id.clone(),
+            terminal_id: terminal_id.clone(),
+            exit_code: exit.exit_code,
+            signal: exit.signal,
+            user_stopped: exit.user_stopped,
+            timed_out: exit.timed_out,
+        };
+        if let Err(e) = self.app.emit(EVENT_TERMINAL_EXITED, &event) {
+            log::error!(
+                "Failed to emit terminal/exited: {e} (workspace={}, agent={}, terminal={terminal_id})",
+                self.workspace_id,
+                self.agent_id
+            );
+        }
+    }
+}
+
+fn exit_status(exit: TerminalExit) -> TerminalExitStatus {
+    TerminalExitStatus {
+        exit_code: exit.exit_code,
+        signal: exit.signal,
+        user_stopped: exit.user_stopped,
+    }
 }
 
-const OUTPUT_CAPTURE_LIMIT: usize = 64 * 1024;
 const CONTENT_PREVIEW_LIMIT: usize = 4 * 1024;
 const PREAPPROVAL_TTL: Duration = Duration::from_secs(10 * 60);
 
 #[async_trait::async_trait]
 impl AgentHost for RuntimeAgentHost {
     fn set_status(&self, status: AgentRuntimeStatus) {
-        let event = AgentStatusChangedEvent {
-            workspace_id: self.workspace_id.clone(),
-            agent_id: self.agent_id.clone(),
-            status,
-        };
+        if let AgentRuntimeStatus::Errored { message } = &status {
+            if let Some(notifier) = self.app.try_state::<Arc<Notifier>>() {
+                notifier.notify("Agent failed", Some(message), NotificationLevel::Error);
+            }
+        }
+
+        let event = status_changed_event(&self.labels, status);
 
         if let Err(e) = self.app.emit(EVENT_AGENT_STATUS_CHANGED, &event) {
             log::error!(
                 "Failed to emit agent/status_changed event: {} (workspace={}, agent={})",
                 e,
-                self.workspace_id,
-                self.agent_id
+                self.labels.workspace_id,
+                self.labels.agent_id
             );
         } else {
             log::debug!(
                 "Emitted agent/status_changed: workspace={}, agent={}",
-                self.workspace_id,
-                self.agent_id
+                self.labels.workspace_id,
+                self.labels.agent_id
             );
         }
     }
 
-    fn on_session_update(&self, session_id: SessionId, update: AcpSessionUpdate) {
-        let seq = self.session_update_seq.fetch_add(1, Ordering::Relaxed);
-        let emitted_at_ms = now_ms();
+    fn on_session_update(
+        &self,
+        session_id: SessionId,
+        turn_id: Option<TurnId>,
+        update: AcpSessionUpdate,
+    ) {
+        if let Some(runtime) = self.runtime.upgrade() {
+            cache_session_update(&runtime, &session_id, &update);
+        }
 
-        let event = AcpSessionUpdateEvent {
-            workspace_id: self.workspace_id.clone(),
-            agent_id: self.agent_id.clone(),
-            session_id,
-            seq,
-            emitted_at_ms,
-            update,
-        };
+        // A finished turn frees its session to send the next queued prompt
+        if matches!(update, AcpSessionUpdate::TurnComplete { .. }) {
+            if let Some(runtime) = self.runtime.upgrade() {
+                let generation = self.generation;
+                let session_id = session_id.clone();
+                let turn_id = turn_id.clone();
+                tokio::spawn(async move {
+                    runtime
+                        .handle_turn_complete(generation, session_id, turn_id)
+                        .await;
+                });
+            }
+        }
+
+        self.session_updates.push(session_id, turn_id, update);
+    }
 
-        if let Err(e) = self.app.emit(EVENT_ACP_SESSION_UPDATE, &event) {
+    fn protocol_trace_enabled(&self) -> bool {
+        self.runtime
+            .upgrade()
+            .is_some_and(|runtime| runtime.protocol_trace_enabled())
+    }
+
+    fn on_protocol_trace(&self, direction: ProtocolTraceDirection, raw: String) {
+        let event = AcpProtocolTraceEvent {
+            workspace_id: self.labels.workspace_id.clone(),
+            agent_id: self.labels.agent_id.clone(),
+            direction,
+            raw,
+        };
+        if let Err(e) = self.app.emit(EVENT_ACP_PROTOCOL_TRACE, &event) {
             log::error!(
-                "Failed to emit acp/session_update event: {} (workspace={}, agent={})",
-                e,
-                self.workspace_id,
-                self.agent_id
-            );
-        } else {
-            log::trace!(
-                "Emitted acp/session_update: workspace={}, agent={}",
-                self.workspace_id,
-                self.agent_id
+                "Failed to emit acp/protocol_trace: {e} (workspace={}, agent={})",
+                self.labels.workspace_id,
+                self.labels.agent_id
             );
         }
     }
@@ -158,20 +316,36 @@ impl AgentHost for RuntimeAgentHost {
     fn on_connection_lost(&self) {
         log::warn!(
             "Agent connection lost: workspace={}, agent={}",
-            self.workspace_id,
-            self.agent_id
+            self.labels.workspace_id,
+            self.labels.agent_id
         );
 
-        // Emit Stopped status to notify frontend
-        // Note: AgentRuntime state (connection, session_id) is not automatically cleared
-        // to avoid circular references. It will be detected on next operation attempt.
-        self.set_status(AgentRuntimeStatus::Stopped);
+        // Reset the runtime so the next prompt restarts the adapter; the
+        // runtime emits the Stopped status once its state is cleared
+        match self.runtime.upgrade() {
+            Some(runtime) => {
+                let generation = self.generation;
+                tokio::spawn(async move {
+                    runtime.handle_connection_lost(generation).await;
+                });
+            }
+            None => self.set_status(AgentRuntimeStatus::Stopped),
+        }
+    }
+
+    async fn cancel_in_flight_requests(&self) {
+        self.in_flight.kill_terminals(&self.terminal_manager).await;
+        for operation_id in self.in_flight.take_permissions() {
+            if let Err(e) = self.permission_hub.cancel(operation_id.clone()).await {
+                log::debug!("Abandoned permission already settled: {operation_id} ({e})");
+            }
+        }
     }
 
     async fn request_permission(
         &self,
         request: PermissionRequest,
-    ) -> Result<PermissionDecision, ApiError> {
+    ) -> Result<PermissionOutcome, ApiError> {
         let has_request_operation_id = request.operation_id.is_some();
         let operation_id = request
             .operation_id
@@ -179,24 +353,44 @@ impl AgentHost for RuntimeAgentHost {
             .unwrap_or_else(|| Uuid::new_v4().to_string());
 
         let origin = PermissionOrigin {
-            workspace_id: Some(self.workspace_id.clone()),
-            agent_id: Some(self.agent_id.clone()),
+            workspace_id: Some(self.labels.workspace_id.clone()),
+            agent_id: Some(self.labels.agent_id.clone()),
             session_id: request.session_id.clone(),
             tool_call_id: request.tool_call_id.clone(),
         };
 
-        let decision = self
-            .permission_hub
-            .request(operation_id.clone(), request.source, Some(origin))
-            .await?;
+        // The workspace's command and path rules answer without prompting
+        let policy_outcome = self.permission_hub.apply_policy(
+            &operation_id,
+            &request.source,
+            &origin,
+            &request.options,
+        );
+        let outcome = match policy_outcome {
+            Some(outcome) => outcome,
+            None => {
+                self.in_flight.track_permission(&operation_id);
+                let outcome = self
+                    .permission_hub
+                    .request_with_options(
+                        operation_id.clone(),
+                        request.source,
+                        Some(origin),
+                        request.options,
+                    )
+                    .await;
+                self.in_flight.finish_permission(&operation_id);
+                outcome?
+            }
+        };
 
-        if decision == PermissionDecision::AllowOnce && has_request_operation_id {
+        if outcome.decision.is_allowed() && has_request_operation_id {
             let mut preapproved = self.preapproved_ops.lock().await;
             prune_preapprovals(&mut preapproved);
             preapproved.insert(operation_id, Instant::now());
         }
 
-        Ok(decision)
+        Ok(outcome)
     }
 
     async fn terminal_run(
@@ -204,115 +398,146 @@ impl AgentHost for RuntimeAgentHost {
         request: TerminalRunRequest,
     ) -> Result<TerminalRunResult, ApiError> {
         let operation_id = request.operation_id.clone();
+        self.check_command_rules(operation_id.clone(), request.command.clone())?;
 
         let handle = self
             .terminal_manager
-            .spawn_run(request.command.clone())
+            .spawn_run(
+                request.command.clone(),
+                request.env,
+                request.timeout_ms.map(Duration::from_millis),
+            )
             .await?;
 
-        let TerminalRunHandle {
-            terminal_id,
-            mut stdout_rx,
-            mut stderr_rx,
-            mut exit_rx,
-        } = handle;
-        let mut stdout_buffer = String::new();
-        let mut stderr_buffer = String::new();
-        let mut stdout_closed = false;
-        let mut stderr_closed = false;
-        let mut exit_received = false;
-        let mut exit_code: Option<i32> = None;
-        let mut user_stopped = false;
-
-        while !(stdout_closed && stderr_closed && exit_received) {
-            tokio::select! {
-                stdout = stdout_rx.recv(), if !stdout_closed => {
-                    match stdout {
-                        Some(chunk) => {
-                            let event = TerminalOutputEvent {
-                                workspace_id: self.workspace_id.clone(),
-                                agent_id: self.agent_id.clone(),
-                                operation_id: operation_id.clone(),
-                                terminal_id: terminal_id.clone(),
-                                stream: TerminalStream::Stdout,
-                                chunk: chunk.clone(),
-                            };
-                            if let Err(e) = self.app.emit(EVENT_TERMINAL_OUTPUT, &event) {
-                                log::error!(
-                                    "Failed to emit terminal/output: {e} (workspace={}, agent={}, terminal={terminal_id})",
-                                    self.workspace_id,
-                                    self.agent_id
-                                );
-                            }
-                            append_capped(&mut stdout_buffer, &chunk, OUTPUT_CAPTURE_LIMIT);
-                        }
-                        None => stdout_closed = true,
-                    }
-                }
-                stderr = stderr_rx.recv(), if !stderr_closed => {
-                    match stderr {
-                        Some(chunk) => {
-                            let event = TerminalOutputEvent {
-                                workspace_id: self.workspace_id.clone(),
-                                agent_id: self.agent_id.clone(),
-                                operation_id: operation_id.clone(),
-                                terminal_id: terminal_id.clone(),
-                                stream: TerminalStream::Stderr,
-                                chunk: chunk.clone(),
-                            };
-                            if let Err(e) = self.app.emit(EVENT_TERMINAL_OUTPUT, &event) {
-                                log::error!(
-                                    "Failed to emit terminal/output: {e} (workspace={}, agent={}, terminal={terminal_id})",
-                                    self.workspace_id,
-                                    self.agent_id
-                                );
-                            }
-                            append_capped(&mut stderr_buffer, &chunk, OUTPUT_CAPTURE_LIMIT);
-                        }
-                        None => stderr_closed = true,
-                    }
-                }
-                exit = &mut exit_rx, if !exit_received => {
-                    exit_received = true;
-                    match exit {
-                        Ok(TerminalExit { exit_code: code, user_stopped: stopped }) => {
-                            exit_code = code;
-                            user_stopped = stopped;
-                        }
-                        Err(_) => {
-                            exit_code = None;
-                            user_stopped = false;
-                        }
-                    }
-                }
+        let terminal_id = handle.terminal_id.clone();
+        self.in_flight.track_terminal(&terminal_id);
+        // Without capture, output is only streamed to the UI
+        let capture_limit = if request.capture_output {
+            self.terminal_manager.output_capture_limit()
+        } else {
+            0
+        };
+        let output = collect_run_output(handle, capture_limit, |stream, chunk| {
+            let event = TerminalOutputEvent {
+                workspace_id: self.labels.workspace_id.clone(),
+                agent_id: self.labels.agent_id.clone(),
+                operation_id: operation_id.clone(),
+                terminal_id: terminal_id.clone(),
+                stream,
+                chunk,
+            };
+            if let Err(e) = self.app.emit(EVENT_TERMINAL_OUTPUT, &event) {
+                log::error!(
+                    "Failed to emit terminal/output: {e} (workspace={}, agent={}, terminal={terminal_id})",
+                    self.labels.workspace_id,
+                    self.labels.agent_id
+                );
             }
-        }
+        })
+        .await;
+        let TerminalExit {
+            exit_code,
+            signal,
+            user_stopped,
+            timed_out,
+        } = output.exit.unwrap_or(TerminalExit {
+            exit_code: None,
+            signal: None,
+            user_stopped: false,
+            timed_out: false,
+        });
 
         let exited_event = TerminalExitedEvent {
-            workspace_id: self.workspace_id.clone(),
-            agent_id: self.agent_id.clone(),
+            workspace_id: self.labels.workspace_id.clone(),
+            agent_id: self.labels.agent_id.clone(),
             operation_id: operation_id.clone(),
             terminal_id: terminal_id.clone(),
             exit_code,
+            signal,
             user_stopped,
+            timed_out,
         };
 
         if let Err(e) = self.app.emit(EVENT_TERMINAL_EXITED, &exited_event) {
             log::error!(
                 "Failed to emit terminal/exited: {e} (workspace={}, agent={}, terminal={terminal_id})",
-                self.workspace_id,
-                self.agent_id
+                self.labels.workspace_id,
+                self.labels.agent_id
             );
         }
 
+        self.in_flight.finish_terminal(&terminal_id);
         Ok(TerminalRunResult {
             terminal_id,
             exit_code,
-            stdout: stdout_buffer,
-            stderr: stderr_buffer,
+            timed_out,
+            stdout: output.stdout,
+            stderr: output.stderr,
+        })
+    }
+
+    async fn terminal_create(
+        &self,
+        request: TerminalCreateRequest,
+    ) -> Result<TerminalId, ApiError> {
+        self.check_command_rules(
+            request.operation_id.clone(),
+            rule_command(&request.command, &request.args),
+        )?;
+
+        let observer = Arc::new(TerminalEventEmitter {
+            app: self.app.clone(),
+            workspace_id: self.labels.workspace_id.clone(),
+            agent_id: self.labels.agent_id.clone(),
+            operation_id: request.operation_id,
+        });
+
+        let terminal_id = self
+            .terminal_manager
+            .create(
+                command_line(&request.command, &request.args),
+                request.env,
+                request.cwd,
+                request.output_byte_limit,
+                Some(observer),
+            )
+            .await?;
+        self.in_flight.track_terminal(&terminal_id);
+        Ok(terminal_id)
+    }
+
+    async fn terminal_output(
+        &self,
+        terminal_id: TerminalId,
+    ) -> Result<TerminalOutputResult, ApiError> {
+        let output = self.terminal_manager.output(&terminal_id).await?;
+
+        Ok(TerminalOutputResult {
+            output: output.output,
+            truncated: output.truncated,
+            exit_status: output.exit.map(exit_status),
         })
     }
 
+    async fn terminal_wait_for_exit(
+        &self,
+        terminal_id: TerminalId,
+    ) -> Result<TerminalExitStatus, ApiError> {
+        self.terminal_manager
+            .wait_for_exit(&terminal_id)
+            .await
+            .map(exit_status)
+    }
+
+    async fn terminal_kill(&self, terminal_id: TerminalId) -> Result<(), ApiError> {
+        self.terminal_manager.kill(terminal_id).await
+    }
+
+    async fn terminal_release(&self, terminal_id: TerminalId) -> Result<(), ApiError> {
+        self.in_flight.finish_terminal(&terminal_id);
+        self.terminal_manager.release(terminal_id).await
+    }
+
     async fn fs_read_text_file(
         &self,
         request: FsReadTextFileRequest,
@@ -322,30 +547,62 @@ impl AgentHost for RuntimeAgentHost {
             .clone()
             .unwrap_or_else(|| Uuid::new_v4().to_string());
 
+        let cancel = self.turn_token(request.session_id.as_deref());
+
         let origin = PermissionOrigin {
-            workspace_id: Some(self.workspace_id.clone()),
-            agent_id: Some(self.agent_id.clone()),
+            workspace_id: Some(self.labels.workspace_id.clone()),
+            agent_id: Some(self.labels.agent_id.clone()),
             session_id: request.session_id,
             tool_call_id: request.tool_call_id,
         };
 
+        // Resolve before prompting so traversal is rejected up front and the
+        // user sees the canonical location rather than the raw agent path
+        let resolved_path = self.fs_manager.resolve_read_path(&request.path)?;
+
         let decision = self
-            .permission_hub
-            .request(
+            .decide(
                 operation_id.clone(),
                 PermissionSource::FsReadTextFile {
                     path: request.path.clone(),
+                    resolved_path,
                 },
-                Some(origin),
+                origin,
             )
             .await?;
 
-        if decision != PermissionDecision::AllowOnce {
+        if !decision.is_allowed() {
             return Err(ApiError::PermissionDenied { operation_id });
         }
 
-        let content = self.fs_manager.read_text_file(request.path).await?;
-        Ok(FsReadTextFileResult { content })
+        // Text reads of binary files fall back to base64 rather than failing
+        let read = match request.encoding {
+            FsReadEncoding::Text => {
+                self.fs_manager
+                    .read_file_auto(request.path, request.line, request.limit, &cancel)
+                    .await?
+            }
+            FsReadEncoding::Base64 => {
+                let read = self
+                    .fs_manager
+                    .read_file_base64(request.path, &cancel)
+                    .await?;
+                FileRead {
+                    content: FileContent::Base64(read.content),
+                    path: read.path,
+                }
+            }
+        };
+        let (content, encoding) = match read.content {
+            FileContent::Text(content) => (content, FsContentEncoding::Text),
+            FileContent::Base64(content) => (content, FsContentEncoding::Base64),
+        };
+        Ok(FsReadTextFileResult {
+            content,
+            encoding,
+            path: read.path.relative,
+            absolute_path: read.path.absolute,
+        })
     }
 
     async fn fs_write_text_file(
@@ -357,9 +614,11 @@ impl AgentHost for RuntimeAgentHost {
             .clone()
             .unwrap_or_else(|| Uuid::new_v4().to_string());
 
+        let cancel = self.turn_token(request.session_id.as_deref());
+
         let origin = PermissionOrigin {
-            workspace_id: Some(self.workspace_id.clone()),
-            agent_id: Some(self.agent_id.clone()),
+            workspace_id: Some(self.labels.workspace_id.clone()),
+            agent_id: Some(self.labels.agent_id.clone()),
             session_id: request.session_id,
             tool_call_id: request.tool_call_id,
         };
@@ -367,94 +626,310 @@ impl AgentHost for RuntimeAgentHost {
         let (content_preview, content_truncated, content_len) =
             summarize_content(&request.content, CONTENT_PREVIEW_LIMIT);
 
+        let resolved_path = self.fs_manager.resolve_write_path(&request.path)?;
+        let source = PermissionSource::FsWriteTextFile {
+            path: request.path.clone(),
+            resolved_path,
+            content_preview,
+            content_truncated,
+            append: request.append,
+        };
+        // Denied paths stay denied even for preapproved operations
+        if self.denied_by_rule(&operation_id, &source, &origin) {
+            return Err(ApiError::PermissionDenied { operation_id });
+        }
+
         if let Some(op_id) = request.operation_id.as_ref() {
             if self.consume_preapproval(op_id).await {
                 log::debug!(
                     "Using preapproved permission: operation_id={op_id}, content_len={content_len}"
                 );
-                let bytes_written = self
-                    .fs_manager
-                    .write_text_file(request.path, request.content)
-                    .await?;
+                let written = self.write_or_append(request, &cancel).await?;
 
-                log::debug!("Write completed: bytes_written={bytes_written}");
-                return Ok(FsWriteTextFileResult);
+                log::debug!("Write completed: bytes_written={}", written.bytes_written);
+                return Ok(write_result(written));
             }
         }
 
-        let decision = self
-            .permission_hub
-            .request(
-                operation_id.clone(),
-                PermissionSource::FsWriteTextFile {
-                    path: request.path.clone(),
-                    content_preview,
-                    content_truncated,
-                },
-                Some(origin),
-            )
-            .await?;
+        let decision = self.decide(operation_id.clone(), source, origin).await?;
 
         log::debug!(
             "Permission requested for write: operation_id={operation_id}, content_len={content_len}"
         );
 
-        if decision != PermissionDecision::AllowOnce {
+        if !decision.is_allowed() {
             return Err(ApiError::PermissionDenied { operation_id });
         }
 
-        let bytes_written = self
-            .fs_manager
-            .write_text_file(request.path, request.content)
+        let written = self.write_or_append(request, &cancel).await?;
+
+        log::debug!("Write completed: bytes_written={}", written.bytes_written);
+        Ok(write_result(written))
+    }
+
+    async fn fs_delete(&self, request: FsDeleteRequest) -> Result<(), ApiError> {
+        let operation_id = request
+            .operation_id
+            .clone()
+            .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+        let origin = PermissionOrigin {
+            workspace_id: Some(self.labels.workspace_id.clone()),
+            agent_id: Some(self.labels.agent_id.clone()),
+            session_id: request.session_id,
+            tool_call_id: request.tool_call_id,
+        };
+        let resolved_path = self.fs_manager.resolve_write_path(&request.path)?;
+        let source = PermissionSource::FsDelete {
+            path: request.path.clone(),
+            resolved_path,
+            recursive: request.recursive,
+        };
+        // Denied paths stay denied even for preapproved operations
+        if self.denied_by_rule(&operation_id, &source, &origin) {
+            return Err(ApiError::PermissionDenied { operation_id });
+        }
+
+        let preapproved = match request.operation_id.as_ref() {
+            Some(op_id) => self.consume_preapproval(op_id).await,
+            None => false,
+        };
+
+        if !preapproved {
+            let decision = self.decide(operation_id.clone(), source, origin).await?;
+
+            if !decision.is_allowed() {
+                return Err(ApiError::PermissionDenied { operation_id });
+            }
+        }
+
+        self.fs_manager
+            .delete_file(request.path, request.recursive)
             .await?;
+        log::debug!("Delete completed: operation_id={operation_id}");
+        Ok(())
+    }
+
+    async fn fs_rename(&self, request: FsRenameRequest) -> Result<(), ApiError> {
+        let operation_id = request
+            .operation_id
+            .clone()
+            .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+        let origin = PermissionOrigin {
+            workspace_id: Some(self.labels.workspace_id.clone()),
+            agent_id: Some(self.labels.agent_id.clone()),
+            session_id: request.session_id,
+            tool_call_id: request.tool_call_id,
+        };
+        let resolved_from = self.fs_manager.resolve_write_path(&request.from)?;
+        let resolved_to = self.fs_manager.resolve_write_path(&request.to)?;
+        let source = PermissionSource::FsRename {
+            from: request.from.clone(),
+            to: request.to.clone(),
+            resolved_from,
+            resolved_to,
+        };
+        // Denied paths stay denied even for preapproved operations
+        if self.denied_by_rule(&operation_id, &source, &origin) {
+            return Err(ApiError::PermissionDenied { operation_id });
+        }
+
+        let preapproved = match request.operation_id.as_ref() {
+            Some(op_id) => self.consume_preapproval(op_id).await,
+            None => false,
+        };
+
+        if !preapproved {
+            let decision = self.decide(operation_id.clone(), source, origin).await?;
 
-        log::debug!("Write completed: bytes_written={bytes_written}");
-        Ok(FsWriteTextFileResult)
+            if !decision.is_allowed() {
+                return Err(ApiError::PermissionDenied { operation_id });
+            }
+        }
+
+        self.fs_manager
+            .rename(request.from, request.to, request.recursive)
+            .await?;
+        log::debug!("Rename completed: operation_id={operation_id}");
+        Ok(())
+    }
+}
+
+/// Keep the parts of a session update the UI may need to re-fetch later.
+fn cache_session_update(runtime: &AgentRuntime, session_id: &SessionId, update: &AcpSessionUpdate) {
+    match update {
+        AcpSessionUpdate::AvailableCommandsUpdate { available_commands } => {
+            runtime.record_available_commands(session_id.clone(), available_commands);
+        }
+        AcpSessionUpdate::CurrentModeUpdate { current_mode_id } => {
+            runtime.record_current_mode(session_id.clone(), current_mode_id);
+        }
+        _ => {}
+    }
+}
+
+/// Output of a finished `terminal/run` command.
+struct RunOutput {
+    stdout: String,
+    stderr: String,
+    /// `None` if the exit status was lost
+    exit: Option<TerminalExit>,
+}
+
+/// Drain a `terminal/run` command until it exits and its streams close.
+///
+/// Every chunk is passed to `on_output`; up to `capture_limit` bytes of each
+/// stream are also kept for the result (none when the limit is 0).
+async fn collect_run_output(
+    handle: TerminalRunHandle,
+    capture_limit: usize,
+    mut on_output: impl FnMut(TerminalStream, String),
+) -> RunOutput {
+    let TerminalRunHandle {
+        mut stdout_rx,
+        mut stderr_rx,
+        mut exit_rx,
+        ..
+    } = handle;
+    let mut output = RunOutput {
+        stdout: String::new(),
+        stderr: String::new(),
+        exit: None,
+    };
+    let mut stdout_closed = false;
+    let mut stderr_closed = false;
+    let mut exit_received = false;
+
+    while !(stdout_closed && stderr_closed && exit_received) {
+        tokio::select! {
+            stdout = stdout_rx.recv(), if !stdout_closed => match stdout {
+                Some(chunk) => {
+                    append_capped(&mut output.stdout, &chunk, capture_limit);
+                    on_output(TerminalStream::Stdout, chunk);
+                }
+                None => stdout_closed = true,
+            },
+            stderr = stderr_rx.recv(), if !stderr_closed => match stderr {
+                Some(chunk) => {
+                    append_capped(&mut output.stderr, &chunk, capture_limit);
+                    on_output(TerminalStream::Stderr, chunk);
+                }
+                None => stderr_closed = true,
+            },
+            exit = &mut exit_rx, if !exit_received => {
+                exit_received = true;
+                output.exit = exit.ok();
+            }
+        }
     }
+    output
 }
 
 fn append_capped(target: &mut String, chunk: &str, cap: usize) {
     if target.len() >= cap {
         return;
     }
-    let remaining = cap.saturating_sub(target.len());
-    if chunk.len() <= remaining {
-        target.push_str(chunk);
-    } else {
-        let mut end = 0;
-        for (idx, ch) in chunk.char_indices() {
-            let next = idx + ch.len_utf8();
-            if next > remaining {
-                break;
-            }
-            end = next;
-        }
-        target.push_str(&chunk[..end]);
+    let remaining = cap - target.len();
+    let kept = truncate_on_char_boundary(chunk, remaining);
+    target.push_str(kept);
+    if kept.len() < chunk.len() {
         target.push_str("\n...[truncated]");
     }
 }
 
-fn summarize_content(content: &str, max_chars: usize) -> (String, bool, usize) {
-    let total_len = content.len();
-    if content.len() <= max_chars {
-        return (content.to_string(), false, total_len);
+fn write_result(written: FileWrite) -> FsWriteTextFileResult {
+    FsWriteTextFileResult {
+        bytes_written: written.bytes_written,
+        path: written.path.relative,
+        absolute_path: written.path.absolute,
     }
+}
 
-    let mut end = 0;
-    for (idx, ch) in content.char_indices() {
-        let next = idx + ch.len_utf8();
-        if next > max_chars {
-            break;
-        }
-        end = next;
+fn summarize_content(content: &str, max_bytes: usize) -> (String, bool, usize) {
+    let preview = truncate_on_char_boundary(content, max_bytes);
+    if preview.len() == content.len() {
+        return (content.to_string(), false, content.len());
     }
-
-    let mut preview = content[..end].to_string();
-    preview.push_str("\n...[truncated]");
-    (preview, true, total_len)
+    (format!("{preview}\n...[truncated]"), true, content.len())
 }
 
 impl RuntimeAgentHost {
+    /// Perform an approved write, appending when the request asks for it.
+    async fn write_or_append(
+        &self,
+        request: FsWriteTextFileRequest,
+        cancel: &CancellationToken,
+    ) -> Result<FileWrite, ApiError> {
+        if request.append {
+            self.fs_manager
+                .append_text_file(request.path, request.content)
+                .await
+        } else {
+            self.fs_manager
+                .write_text_file(request.path, request.content, cancel)
+                .await
+        }
+    }
+
+    /// Decide a file access request: by the workspace's path rules if one
+    /// applies, otherwise by asking the user.
+    async fn decide(
+        &self,
+        operation_id: OperationId,
+        source: PermissionSource,
+        origin: PermissionOrigin,
+    ) -> Result<PermissionDecision, ApiError> {
+        match self
+            .permission_hub
+            .apply_policy(&operation_id, &source, &origin, &[])
+        {
+            Some(outcome) => Ok(outcome.decision),
+            None => {
+                self.permission_hub
+                    .request(operation_id, source, Some(origin))
+                    .await
+            }
+        }
+    }
+
+    /// Refuse a terminal command the workspace's deny rules cover. Checked
+    /// when spawning, so a command the agent was granted earlier (or never
+    /// asked about) can't slip past a deny rule.
+    fn check_command_rules(
+        &self,
+        operation_id: Option<OperationId>,
+        command: String,
+    ) -> Result<(), ApiError> {
+        let operation_id = operation_id.unwrap_or_else(|| Uuid::new_v4().to_string());
+        let origin = PermissionOrigin {
+            workspace_id: Some(self.labels.workspace_id.clone()),
+            agent_id: Some(self.labels.agent_id.clone()),
+            session_id: None,
+            tool_call_id: None,
+        };
+        let source = PermissionSource::TerminalRun { command };
+        if self
+            .permission_hub
+            .command_denied(&operation_id, &source, &origin)
+        {
+            return Err(ApiError::PermissionDenied { operation_id });
+        }
+        Ok(())
+    }
+
+    /// Whether the workspace's path rules refuse this request outright.
+    fn denied_by_rule(
+        &self,
+        operation_id: &OperationId,
+        source: &PermissionSource,
+        origin: &PermissionOrigin,
+    ) -> bool {
+        self.permission_hub
+            .apply_policy(operation_id, source, origin, &[])
+            .is_some_and(|outcome| !outcome.decision.is_allowed())
+    }
+
     async fn consume_preapproval(&self, operation_id: &OperationId) -> bool {
         let mut preapproved = self.preapproved_ops.lock().await;
         prune_preapprovals(&mut preapproved);
@@ -462,15 +937,319 @@ impl RuntimeAgentHost {
     }
 }
 
-/// Helper function to get current time in milliseconds since UNIX epoch
-fn now_ms() -> f64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map(|d| d.as_secs_f64() * 1000.0)
-        .unwrap_or(0.0)
+/// The command line a terminal request is checked against the command
+/// rules: arguments joined unquoted, the way a rule author writes them
+/// (`rm -rf` rather than `rm '-rf'`).
+fn rule_command(command: &str, args: &[String]) -> String {
+    std::iter::once(command)
+        .chain(args.iter().map(String::as_str))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Emitter that sends an agent's session updates to the frontend in order.
+/// Payload of `agent/status_changed` for the agent `labels` describes.
+fn status_changed_event(
+    labels: &AgentLabels,
+    status: AgentRuntimeStatus,
+) -> AgentStatusChangedEvent {
+    AgentStatusChangedEvent {
+        workspace_id: labels.workspace_id.clone(),
+        agent_id: labels.agent_id.clone(),
+        plugin_id: labels.plugin_id.clone(),
+        display_name: labels.display_name.clone(),
+        status,
+    }
+}
+
+fn session_update_emitter(app: tauri::AppHandle, labels: AgentLabels) -> SessionUpdateEmitter {
+    let transcripts = app
+        .try_state::<Arc<SessionTranscripts>>()
+        .map(|transcripts| transcripts.inner().clone());
+    let coalescing = app
+        .try_state::<Arc<ChunkCoalescing>>()
+        .map(|coalescing| coalescing.inner().clone())
+        .unwrap_or_default();
+    let emit = move |event: &AcpSessionUpdateEvent| {
+        if let Some(transcripts) = &transcripts {
+            transcripts.append(event);
+        }
+        if let Err(e) = app.emit(EVENT_ACP_SESSION_UPDATE, event) {
+            log::error!(
+                "Failed to emit acp/session_update event: {} (workspace={}, agent={})",
+                e,
+                event.workspace_id,
+                event.agent_id
+            );
+        } else {
+            log::trace!(
+                "Emitted acp/session_update: workspace={}, agent={}",
+                event.workspace_id,
+                event.agent_id
+            );
+        }
+    };
+    SessionUpdateEmitter::spawn_coalescing(labels, coalescing, emit)
 }
 
 fn prune_preapprovals(preapproved: &mut HashMap<OperationId, Instant>) {
     let now = Instant::now();
     preapproved.retain(|_, timestamp| now.duration_since(*timestamp) <= PREAPPROVAL_TTL);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_available_commands_update_is_cached_per_session() {
+        let runtime = AgentRuntime::new(
+            "agent-1".to_string(),
+            "workspace-1".to_string(),
+            "claude-code".to_string(),
+        );
+        let session_id = "session-1".to_string();
+        assert!(runtime.available_commands(&session_id).is_empty());
+
+        let commands = serde_json::json!([
+            { "name": "review", "description": "Review the current diff" },
+            { "name": "init", "description": "Create an AGENTS.md file" }
+        ]);
+        cache_session_update(
+            &runtime,
+            &session_id,
+            &AcpSessionUpdate::AvailableCommandsUpdate {
+                available_commands: commands.clone(),
+            },
+        );
+        // Other updates leave the cache alone
+        cache_session_update(
+            &runtime,
+            &session_id,
+            &AcpSessionUpdate::AgentMessageChunk {
+                content: serde_json::json!({ "type": "text", "text": "hi" }),
+            },
+        );
+
+        assert_eq!(
+            runtime.available_commands(&session_id),
+            commands.as_array().unwrap().clone()
+        );
+        assert!(runtime
+            .available_commands(&"session-2".to_string())
+            .is_empty());
+
+        // A later update replaces the list
+        cache_session_update(
+            &runtime,
+            &session_id,
+            &AcpSessionUpdate::AvailableCommandsUpdate {
+                available_commands: serde_json::json!([]),
+            },
+        );
+        assert!(runtime.available_commands(&session_id).is_empty());
+    }
+
+    #[test]
+    fn test_current_mode_update_is_cached_per_session() {
+        let runtime = AgentRuntime::new(
+            "agent-1".to_string(),
+            "workspace-1".to_string(),
+            "claude-code".to_string(),
+        );
+        let session_id = "session-1".to_string();
+        assert_eq!(runtime.current_mode(&session_id), None);
+
+        for mode_id in ["ask", "code"] {
+            cache_session_update(
+                &runtime,
+                &session_id,
+                &AcpSessionUpdate::CurrentModeUpdate {
+                    current_mode_id: serde_json::json!(mode_id),
+                },
+            );
+        }
+
+        assert_eq!(runtime.current_mode(&session_id).as_deref(), Some("code"));
+        assert_eq!(runtime.current_mode(&"session-2".to_string()), None);
+        // Commands and mode are cached side by side
+        assert!(runtime.available_commands(&session_id).is_empty());
+    }
+
+    #[test]
+    fn test_status_changed_event_carries_agent_labels() {
+        let labels = AgentLabels {
+            workspace_id: "workspace-1".to_string(),
+            agent_id: "agent-1".to_string(),
+            plugin_id: "claude-code".to_string(),
+            display_name: Some("Reviewer".to_string()),
+        };
+
+        let event = status_changed_event(&labels, AgentRuntimeStatus::Running);
+
+        assert_eq!(event.workspace_id, "workspace-1");
+        assert_eq!(event.agent_id, "agent-1");
+        assert_eq!(event.plugin_id, "claude-code");
+        assert_eq!(event.display_name.as_deref(), Some("Reviewer"));
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["displayName"], "Reviewer");
+        assert_eq!(json["pluginId"], "claude-code");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_in_flight_terminal_is_killed_on_shutdown() {
+        let manager = TerminalManager::new(
+            std::env::temp_dir(),
+            crate::runtime::terminal::DEFAULT_OUTPUT_CAPTURE_LIMIT,
+            None,
+        );
+        let in_flight = InFlightOperations::default();
+        let pending = manager
+            .spawn_run("sleep 30".to_string(), None, None)
+            .await
+            .unwrap();
+        in_flight.track_terminal(&pending.terminal_id);
+        let finished = manager
+            .spawn_run("true".to_string(), None, None)
+            .await
+            .unwrap();
+        in_flight.track_terminal(&finished.terminal_id);
+        in_flight.finish_terminal(&finished.terminal_id);
+        in_flight.track_permission(&"op-1".to_string());
+
+        in_flight.kill_terminals(&manager).await;
+
+        let exit = tokio::time::timeout(Duration::from_secs(5), pending.exit_rx)
+            .await
+            .expect("terminal was not killed")
+            .unwrap();
+        assert!(exit.user_stopped);
+        assert!(lock_unpoisoned(&in_flight.terminals).is_empty());
+        assert_eq!(in_flight.take_permissions(), vec!["op-1".to_string()]);
+        assert!(in_flight.take_permissions().is_empty());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_run_output_streams_without_capture() {
+        let manager = TerminalManager::new(
+            std::env::temp_dir(),
+            crate::runtime::terminal::DEFAULT_OUTPUT_CAPTURE_LIMIT,
+            None,
+        );
+        let command = "echo out; echo err 1>&2; exit 3".to_string();
+
+        let mut streamed = Vec::new();
+        let handle = manager
+            .spawn_run(command.clone(), None, None)
+            .await
+            .unwrap();
+        let output = collect_run_output(handle, 0, |stream, chunk| {
+            streamed.push((stream, chunk));
+        })
+        .await;
+        assert_eq!(output.exit.unwrap().exit_code, Some(3));
+        assert!(output.stdout.is_empty());
+        assert!(output.stderr.is_empty());
+        let text = |wanted: TerminalStream| {
+            streamed
+                .iter()
+                .filter(|(stream, _)| *stream == wanted)
+                .map(|(_, chunk)| chunk.as_str())
+                .collect::<String>()
+        };
+        assert_eq!(text(TerminalStream::Stdout).trim(), "out");
+        assert_eq!(text(TerminalStream::Stderr).trim(), "err");
+
+        // With capture on, the same output is also returned
+        let handle = manager.spawn_run(command, None, None).await.unwrap();
+        let output = collect_run_output(handle, manager.output_capture_limit(), |_, _| {}).await;
+        assert_eq!(output.stdout.trim(), "out");
+        assert_eq!(output.stderr.trim(), "err");
+    }
+
+    #[test]
+    fn test_terminal_create_is_checked_against_deny_rules_unquoted() {
+        use crate::runtime::command_policy::CommandPolicy;
+        use crate::types::{PolicyAction, TerminalCommandRule};
+
+        let policy = CommandPolicy::new();
+        let ws = "ws-1".to_string();
+        policy.set_rules(HashMap::from([(
+            ws.clone(),
+            vec![TerminalCommandRule {
+                action: PolicyAction::Deny,
+                pattern: "rm -rf".to_string(),
+            }],
+        )]));
+        let args = vec!["-rf".to_string(), "/".to_string()];
+
+        assert_eq!(rule_command("rm", &args), "rm -rf /");
+        assert_eq!(
+            policy.evaluate(&ws, &rule_command("rm", &args)),
+            Some(PolicyAction::Deny)
+        );
+        assert_eq!(policy.evaluate(&ws, &rule_command("ls", &args)), None);
+        // The quoted line handed to the shell would slip past the rule
+        if cfg!(unix) {
+            assert_eq!(policy.evaluate(&ws, &command_line("rm", &args)), None);
+        }
+    }
+
+    #[test]
+    fn test_summarize_content_truncates_past_limit() {
+        let exact = "a".repeat(CONTENT_PREVIEW_LIMIT);
+        let (preview, truncated, len) = summarize_content(&exact, CONTENT_PREVIEW_LIMIT);
+        assert_eq!(preview, exact);
+        assert!(!truncated);
+        assert_eq!(len, CONTENT_PREVIEW_LIMIT);
+
+        let over = "a".repeat(CONTENT_PREVIEW_LIMIT + 1);
+        let (preview, truncated, len) = summarize_content(&over, CONTENT_PREVIEW_LIMIT);
+        assert_eq!(preview, format!("{exact}\n...[truncated]"));
+        assert!(truncated);
+        assert_eq!(len, CONTENT_PREVIEW_LIMIT + 1);
+    }
+
+    #[test]
+    fn test_summarize_content_keeps_char_boundaries() {
+        // "é" is two bytes; a 3-byte limit must not split the second one
+        let (preview, truncated, _) = summarize_content("éé", 3);
+        assert_eq!(preview, "é\n...[truncated]");
+        assert!(truncated);
+    }
+
+    #[test]
+    fn test_summarize_content_with_multibyte_char_at_limit() {
+        // "日" is three bytes: a limit at its end keeps it, one byte short drops it
+        let (preview, truncated, len) = summarize_content("日本", 6);
+        assert_eq!(preview, "日本");
+        assert!(!truncated);
+        assert_eq!(len, 6);
+
+        let (preview, truncated, len) = summarize_content("日本語", 6);
+        assert_eq!(preview, "日本\n...[truncated]");
+        assert!(truncated);
+        assert_eq!(len, 9);
+
+        let (preview, truncated, _) = summarize_content("日本語", 5);
+        assert_eq!(preview, "日\n...[truncated]");
+        assert!(truncated);
+    }
+
+    #[test]
+    fn test_append_capped_keeps_char_boundaries() {
+        let mut captured = String::from("ab");
+        append_capped(&mut captured, "日本", 6);
+        assert_eq!(captured, "ab日\n...[truncated]");
+
+        // Already at the cap: later chunks are dropped
+        append_capped(&mut captured, "more", 6);
+        assert_eq!(captured, "ab日\n...[truncated]");
+
+        let mut captured = String::new();
+        append_capped(&mut captured, "日本", 6);
+        assert_eq!(captured, "日本");
+    }
+}
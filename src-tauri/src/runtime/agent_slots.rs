@@ -0,0 +1,84 @@
+//! Global cap on running adapter processes.
+//!
+//! Every started agent spawns a heavyweight adapter subprocess. An agent takes
+//! a slot before spawning and holds it until it is stopped or its connection
+//! is lost, so clicking through many agents (in any number of workspaces)
+//! can't exhaust the machine. Starting beyond the cap fails right away with
+//! `ApiError::TooManyAgents` instead of waiting for a slot.
+
+use std::sync::Arc;
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::api::types::ApiError;
+
+/// How many agents may run at once unless configured otherwise
+pub const DEFAULT_MAX_RUNNING_AGENTS: u32 = 8;
+
+/// A held slot; dropping it frees the slot.
+pub type AgentSlot = OwnedSemaphorePermit;
+
+/// Slots shared by all agents of all workspaces.
+pub struct AgentSlots {
+    semaphore: Arc<Semaphore>,
+    limit: u32,
+}
+
+impl AgentSlots {
+    /// Allow at most `limit` agents to run at once (at least one).
+    pub fn new(limit: u32) -> Self {
+        let limit = limit.max(1);
+        Self {
+            semaphore: Arc::new(Semaphore::new(limit as usize)),
+            limit,
+        }
+    }
+
+    /// The configured cap.
+    pub fn limit(&self) -> u32 {
+        self.limit
+    }
+
+    /// Slots not held by a running agent.
+    pub fn available(&self) -> usize {
+        self.semaphore.available_permits()
+    }
+
+    /// Take a slot without waiting.
+    ///
+    /// # Errors
+    /// * `ApiError::TooManyAgents` - If every slot is held
+    pub fn try_acquire(&self) -> Result<AgentSlot, ApiError> {
+        self.semaphore
+            .clone()
+            .try_acquire_owned()
+            .map_err(|_| ApiError::TooManyAgents { limit: self.limit })
+    }
+}
+
+impl Default for AgentSlots {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_RUNNING_AGENTS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slots_are_capped_and_freed_on_drop() {
+        let slots = AgentSlots::new(2);
+        let first = slots.try_acquire().unwrap();
+        let _second = slots.try_acquire().unwrap();
+        assert_eq!(slots.available(), 0);
+        assert!(matches!(
+            slots.try_acquire(),
+            Err(ApiError::TooManyAgents { limit: 2 })
+        ));
+
+        drop(first);
+        assert_eq!(slots.available(), 1);
+        assert!(slots.try_acquire().is_ok());
+    }
+}
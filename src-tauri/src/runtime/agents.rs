@@ -5,33 +5,48 @@
 //! - `AgentRuntime`: manages the runtime state of a started agent (US-06+)
 //!
 //! Agents are created as entities first (not started), and lazily started
-//! when the first prompt is sent (US-06).
+//! when the first prompt is sent (US-06). A started agent can hold several
+//! sessions on its one adapter process; the session it started with is its
+//! primary session, used when no session is named.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicU8, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
 use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
 use uuid::Uuid;
 
 use crate::api::types::{
-    AgentId, AgentRuntimeStatus, AgentSummary, ApiError, SessionId, WorkspaceId,
+    AgentId, AgentRuntimeStatus, AgentSummary, ApiError, BusyPromptPolicy, McpServerConfig, Page,
+    PromptContentBlock, ProtocolErrorKind, SendPromptAck, SessionId, TurnId, WorkspaceId,
 };
 use crate::plugins::manager::PluginManager;
-use crate::protocols::acp::AcpAgent;
+use crate::protocols::acp::{AcpAgent, AcpConnectOptions, AcpKeepalive};
 use crate::protocols::agent_connection::AgentConnection;
 use crate::protocols::host::AgentHost;
 use crate::runtime::agent_host::RuntimeAgentHost;
+use crate::runtime::agent_slots::{AgentSlot, AgentSlots};
+use crate::runtime::cancellation::TurnCancellations;
+use crate::runtime::env_file;
 use crate::runtime::fs::FsManager;
+use crate::runtime::path;
 use crate::runtime::permissions::PermissionHub;
+use crate::runtime::session_updates::AgentLabels;
 use crate::runtime::terminal::TerminalManager;
+use crate::utils::adapter_pids::AdapterPids;
 
 /// Internal record for an agent entity (not yet started).
 ///
 /// This represents the static configuration of an agent within a workspace.
 /// The actual runtime state (process, session, etc.) is handled separately
-/// in `AgentRuntime` (US-06+).
-#[derive(Debug, Clone)]
+/// in `AgentRuntime` (US-06+). Records are saved with their workspace.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct AgentRecord {
     /// Unique identifier for this agent
     pub agent_id: AgentId,
@@ -39,6 +54,21 @@ pub struct AgentRecord {
     pub plugin_id: String,
     /// Optional display name for the agent
     pub display_name: Option<String>,
+    /// Last session issued by the adapter, resumed via `session/load` on restart
+    #[serde(default)]
+    pub session_id: Option<SessionId>,
+    /// MCP servers passed to the adapter when a session is created or loaded
+    #[serde(default)]
+    pub mcp_servers: Vec<McpServerConfig>,
+    /// Restart the adapter automatically when it exits unexpectedly
+    #[serde(default)]
+    pub auto_restart: bool,
+    /// What to do with a prompt sent while a turn is in progress
+    #[serde(default)]
+    pub busy_prompt_policy: BusyPromptPolicy,
+    /// When the agent was created (milliseconds since epoch; 0 if unknown)
+    #[serde(default)]
+    pub created_at_ms: f64,
 }
 
 impl AgentRecord {
@@ -49,6 +79,8 @@ impl AgentRecord {
             workspace_id: workspace_id.clone(),
             plugin_id: self.plugin_id.clone(),
             display_name: self.display_name.clone(),
+            auto_restart: self.auto_restart,
+            busy_prompt_policy: self.busy_prompt_policy,
         }
     }
 }
@@ -57,6 +89,172 @@ impl AgentRecord {
 // AgentRuntime (US-06+)
 // ============================================================================
 
+/// Restart attempts made after an adapter dies unexpectedly
+const AUTO_RESTART_MAX_ATTEMPTS: u32 = 5;
+/// Delay before the first restart attempt; doubles with each failure
+const AUTO_RESTART_BASE_DELAY: Duration = Duration::from_secs(1);
+/// Upper bound on the delay between restart attempts
+const AUTO_RESTART_MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// Longest display name accepted, in characters after trimming
+const MAX_DISPLAY_NAME_CHARS: usize = 128;
+
+/// Prompts a session may have outstanding (running plus queued) unless
+/// configured otherwise
+pub const DEFAULT_MAX_OUTSTANDING_PROMPTS: u32 = 8;
+
+/// Upper bound on resolving the plugin and connecting to its adapter. Each
+/// handshake step has its own, shorter timeout; this one also catches a slow
+/// resolve and handshakes that keep making progress without finishing.
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// How long a keepalive ping may go unanswered unless configured otherwise
+pub const DEFAULT_KEEPALIVE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Keepalive pings for adapters (off by default).
+///
+/// Shared by every runtime, so changes apply to adapters started afterwards.
+#[derive(Debug, Default)]
+pub struct AdapterKeepalive(std::sync::Mutex<Option<AcpKeepalive>>);
+
+impl AdapterKeepalive {
+    /// Keepalive with the given settings; `None` leaves it off.
+    pub fn new(keepalive: Option<AcpKeepalive>) -> Self {
+        Self(std::sync::Mutex::new(keepalive))
+    }
+
+    /// Change the settings; `None` turns keepalive off.
+    pub fn set(&self, keepalive: Option<AcpKeepalive>) {
+        *self.0.lock().unwrap_or_else(|e| e.into_inner()) = keepalive;
+    }
+
+    /// The current settings, or `None` while keepalive is off.
+    pub fn get(&self) -> Option<AcpKeepalive> {
+        *self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Deadline shared by the steps of one agent startup.
+#[derive(Debug, Clone, Copy)]
+struct StartupDeadline {
+    at: tokio::time::Instant,
+    limit: Duration,
+}
+
+impl StartupDeadline {
+    /// A deadline `limit` from now.
+    fn after(limit: Duration) -> Self {
+        Self {
+            at: tokio::time::Instant::now() + limit,
+            limit,
+        }
+    }
+
+    /// Run a startup step, failing with `HandshakeTimeout` if the deadline
+    /// passes first. `step` describes what was in progress, for the error.
+    /// Dropping a timed-out connect kills the adapter it spawned.
+    async fn run<T>(
+        self,
+        step: &str,
+        future: impl std::future::Future<Output = Result<T, ApiError>>,
+    ) -> Result<T, ApiError> {
+        tokio::time::timeout_at(self.at, future)
+            .await
+            .unwrap_or_else(|_| {
+                Err(ApiError::ProtocolError {
+                    kind: ProtocolErrorKind::HandshakeTimeout,
+                    message: format!(
+                        "Agent startup timed out after {:?} while {step}",
+                        self.limit
+                    ),
+                })
+            })
+    }
+}
+
+/// Dependencies of the last successful `ensure_started`, kept so a crashed
+/// adapter can be restarted without a prompt.
+#[derive(Clone)]
+struct StartContext {
+    app: tauri::AppHandle,
+    workspace_root: PathBuf,
+    plugin_manager: Arc<PluginManager>,
+    permission_hub: Arc<PermissionHub>,
+    terminal_manager: Arc<TerminalManager>,
+    fs_manager: Arc<FsManager>,
+    mcp_servers: Vec<McpServerConfig>,
+}
+
+/// Where a session is in its current turn.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+enum TurnPhase {
+    /// No prompt in flight
+    #[default]
+    Idle,
+    /// A prompt is being sent; its turn id isn't known yet
+    Starting,
+    /// The adapter is working on this turn
+    Active(TurnId),
+}
+
+/// A prompt and how long the turn it starts may run (no limit if `None`).
+struct TurnPrompt {
+    blocks: Vec<PromptContentBlock>,
+    timeout: Option<Duration>,
+}
+
+/// Turn bookkeeping of one session: one turn at a time, later prompts wait
+/// in line.
+#[derive(Default)]
+struct SessionState {
+    phase: TurnPhase,
+    queue: VecDeque<TurnPrompt>,
+}
+
+impl SessionState {
+    /// Take the next queued prompt, marking its turn as starting.
+    fn next_queued(&mut self) -> Option<TurnPrompt> {
+        let prompt = self.queue.pop_front()?;
+        self.phase = TurnPhase::Starting;
+        Some(prompt)
+    }
+}
+
+/// What the adapter last announced about a session, for the UI to re-fetch.
+#[derive(Default)]
+struct SessionAnnouncements {
+    available_commands: Vec<serde_json::Value>,
+    current_mode_id: Option<String>,
+}
+
+/// Sessions open on the agent's connection.
+#[derive(Default)]
+struct Sessions {
+    /// Session the agent started with (or its replacement); `None` while
+    /// the agent isn't running
+    primary: Option<SessionId>,
+    states: HashMap<SessionId, SessionState>,
+}
+
+impl Sessions {
+    /// Only the session a fresh connection started with.
+    fn started(primary: SessionId) -> Self {
+        Self {
+            states: HashMap::from([(primary.clone(), SessionState::default())]),
+            primary: Some(primary),
+        }
+    }
+
+    /// Forget every session and drop queued prompts (the adapter is gone).
+    fn clear(&mut self) {
+        let queued: usize = self.states.values().map(|state| state.queue.len()).sum();
+        if queued > 0 {
+            log::warn!("Dropping {queued} queued prompt(s)");
+        }
+        *self = Self::default();
+    }
+}
+
 /// Runtime state of a started agent.
 ///
 /// Created when an agent is lazily started (first prompt sent).
@@ -68,16 +266,41 @@ pub struct AgentRuntime {
     workspace_id: WorkspaceId,
     /// Plugin identifier
     plugin_id: String,
+    /// Display name from the agent record, included in emitted events
+    display_name: std::sync::Mutex<Option<String>>,
     /// Current runtime status
     status: Mutex<AgentRuntimeStatus>,
-    /// Active session ID (if running)
-    session_id: Mutex<Option<SessionId>>,
+    /// Open sessions and their turns (empty unless running)
+    sessions: Mutex<Sessions>,
     /// Protocol connection (if running)
     connection: Mutex<Option<Arc<dyn AgentConnection>>>,
     /// Lock to prevent concurrent startup attempts
     start_lock: Mutex<()>,
     /// App handle for emitting events (set during ensure_started)
     app: Mutex<Option<tauri::AppHandle>>,
+    /// Host used for status callbacks (set during ensure_started)
+    host: Mutex<Option<Arc<dyn AgentHost>>>,
+    /// Cancellation tokens for host operations of the current turn
+    turn_cancellations: Arc<TurnCancellations>,
+    /// Bumped on every start so a dead connection's late loss notice can't
+    /// tear down its replacement
+    connection_generation: AtomicU64,
+    /// Restart the adapter automatically if it exits unexpectedly
+    auto_restart: AtomicBool,
+    /// What the last successful start used, for automatic restarts
+    start_context: Mutex<Option<StartContext>>,
+    /// Pending automatic restart, aborted by a deliberate `stop()`
+    restart_task: Mutex<Option<JoinHandle<()>>>,
+    /// What to do with a prompt sent mid-turn (a `BusyPromptPolicy` as u8)
+    busy_prompt_policy: AtomicU8,
+    /// Cap on the running turn plus queued prompts
+    max_outstanding_prompts: AtomicU32,
+    /// Emit every protocol frame as an `acp/protocol_trace` event
+    protocol_trace: AtomicBool,
+    /// Global running-agent slot, held from startup until stop or connection loss
+    slot: Mutex<Option<AgentSlot>>,
+    /// Latest commands and mode the adapter announced, per session
+    announcements: std::sync::Mutex<HashMap<SessionId, SessionAnnouncements>>,
 }
 
 impl AgentRuntime {
@@ -87,14 +310,185 @@ impl AgentRuntime {
             agent_id,
             workspace_id,
             plugin_id,
+            display_name: std::sync::Mutex::new(None),
             status: Mutex::new(AgentRuntimeStatus::Stopped),
-            session_id: Mutex::new(None),
+            sessions: Mutex::new(Sessions::default()),
             connection: Mutex::new(None),
             start_lock: Mutex::new(()),
             app: Mutex::new(None),
+            host: Mutex::new(None),
+            turn_cancellations: Arc::new(TurnCancellations::new()),
+            connection_generation: AtomicU64::new(0),
+            auto_restart: AtomicBool::new(false),
+            start_context: Mutex::new(None),
+            restart_task: Mutex::new(None),
+            busy_prompt_policy: AtomicU8::new(BusyPromptPolicy::Queue as u8),
+            max_outstanding_prompts: AtomicU32::new(DEFAULT_MAX_OUTSTANDING_PROMPTS),
+            protocol_trace: AtomicBool::new(false),
+            slot: Mutex::new(None),
+            announcements: std::sync::Mutex::new(HashMap::new()),
         })
     }
 
+    /// Plugin this agent runs on.
+    pub fn plugin_id(&self) -> &str {
+        &self.plugin_id
+    }
+
+    /// Set the display name used in events from the next start on.
+    pub fn set_display_name(&self, display_name: Option<String>) {
+        *self.display_name.lock().unwrap_or_else(|e| e.into_inner()) = display_name;
+    }
+
+    /// Choose what happens to prompts sent while a turn is in progress.
+    pub fn set_busy_prompt_policy(&self, policy: BusyPromptPolicy) {
+        self.busy_prompt_policy
+            .store(policy as u8, Ordering::SeqCst);
+    }
+
+    fn busy_prompt_policy(&self) -> BusyPromptPolicy {
+        if self.busy_prompt_policy.load(Ordering::SeqCst) == BusyPromptPolicy::Reject as u8 {
+            BusyPromptPolicy::Reject
+        } else {
+            BusyPromptPolicy::Queue
+        }
+    }
+
+    /// Cap how many prompts may be outstanding (the running turn plus queued
+    /// ones); further prompts fail with `ApiError::TooManyPrompts`.
+    pub fn set_max_outstanding_prompts(&self, limit: u32) {
+        self.max_outstanding_prompts
+            .store(limit.max(1), Ordering::SeqCst);
+    }
+
+    /// Enable or disable automatic restarts after unexpected exits.
+    pub fn set_auto_restart(&self, enabled: bool) {
+        self.auto_restart.store(enabled, Ordering::SeqCst);
+    }
+
+    /// Turn protocol frame tracing on or off; takes effect immediately.
+    pub fn set_protocol_trace(&self, enabled: bool) {
+        self.protocol_trace.store(enabled, Ordering::SeqCst);
+    }
+
+    /// Whether protocol frames are being traced.
+    pub fn protocol_trace_enabled(&self) -> bool {
+        self.protocol_trace.load(Ordering::SeqCst)
+    }
+
+    /// Remember the slash commands a session offers, replacing earlier ones.
+    ///
+    /// Anything but a list (e.g. `null` from an unserializable update) clears
+    /// the session's commands.
+    pub fn record_available_commands(&self, session_id: SessionId, commands: &serde_json::Value) {
+        let commands = match commands {
+            serde_json::Value::Array(commands) => commands.clone(),
+            other => {
+                log::warn!(
+                    "Ignoring available commands that aren't a list: agent={}, session={session_id}, value={other}",
+                    self.agent_id
+                );
+                Vec::new()
+            }
+        };
+        self.announcements()
+            .entry(session_id)
+            .or_default()
+            .available_commands = commands;
+    }
+
+    /// Slash commands last announced for a session (empty if none yet).
+    pub fn available_commands(&self, session_id: &SessionId) -> Vec<serde_json::Value> {
+        self.announcements()
+            .get(session_id)
+            .map(|announced| announced.available_commands.clone())
+            .unwrap_or_default()
+    }
+
+    /// Remember the mode a session is in.
+    pub fn record_current_mode(&self, session_id: SessionId, mode_id: &serde_json::Value) {
+        let mode_id = match mode_id {
+            serde_json::Value::String(mode_id) => Some(mode_id.clone()),
+            other => {
+                log::warn!(
+                    "Ignoring current mode that isn't a string: agent={}, session={session_id}, value={other}",
+                    self.agent_id
+                );
+                None
+            }
+        };
+        self.announcements()
+            .entry(session_id)
+            .or_default()
+            .current_mode_id = mode_id;
+    }
+
+    /// Mode a session was last reported in (`None` if never reported).
+    pub fn current_mode(&self, session_id: &SessionId) -> Option<String> {
+        self.announcements()
+            .get(session_id)
+            .and_then(|announced| announced.current_mode_id.clone())
+    }
+
+    fn announcements(&self) -> std::sync::MutexGuard<'_, HashMap<SessionId, SessionAnnouncements>> {
+        self.announcements.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Whether the agent currently has a live connection to its adapter.
+    pub async fn is_running(&self) -> bool {
+        self.connection.lock().await.is_some()
+    }
+
+    /// The agent's current runtime status (as last emitted).
+    pub async fn current_status(&self) -> AgentRuntimeStatus {
+        self.status.lock().await.clone()
+    }
+
+    /// Primary session of the live connection, if the agent is running.
+    async fn current_session(&self) -> Option<SessionId> {
+        self.sessions.lock().await.primary.clone()
+    }
+
+    /// Connection of the running agent.
+    async fn running_connection(&self) -> Result<Arc<dyn AgentConnection>, ApiError> {
+        self.connection
+            .lock()
+            .await
+            .clone()
+            .ok_or_else(|| ApiError::ProtocolError {
+                kind: ProtocolErrorKind::NotConnected,
+                message: "Agent not running".to_string(),
+            })
+    }
+
+    /// Take a running-agent slot unless this agent already holds one.
+    ///
+    /// # Errors
+    /// * `ApiError::TooManyAgents` - If the global cap is reached
+    pub(crate) async fn acquire_slot(&self, slots: &AgentSlots) -> Result<(), ApiError> {
+        let mut slot = self.slot.lock().await;
+        if slot.is_none() {
+            *slot = Some(slots.try_acquire()?);
+        }
+        Ok(())
+    }
+
+    /// Free this agent's running-agent slot so another agent can start.
+    async fn release_slot(&self) {
+        self.slot.lock().await.take();
+    }
+
+    /// Install a connection as if the agent had been started.
+    #[cfg(test)]
+    pub(crate) async fn attach_connection_for_test(
+        &self,
+        session_id: SessionId,
+        connection: Arc<dyn AgentConnection>,
+    ) {
+        *self.sessions.lock().await = Sessions::started(session_id);
+        *self.connection.lock().await = Some(connection);
+    }
+
     /// Ensure the agent is started and return the session ID.
     ///
     /// This method is idempotent: if already started, returns the existing session ID.
@@ -111,10 +505,13 @@ impl AgentRuntime {
     /// * `permission_hub` - Permission hub for approval flow
     /// * `terminal_manager` - Terminal manager scoped to the workspace
     /// * `fs_manager` - File system manager scoped to the workspace
+    /// * `resume_session_id` - Session to resume instead of creating a new one
+    /// * `mcp_servers` - MCP servers to hand to the adapter's session
     ///
     /// # Returns
-    /// * `Ok(SessionId)` - The session ID (existing or newly created)
+    /// * `Ok(SessionId)` - The session ID (existing, resumed, or newly created)
     /// * `Err(ApiError)` - Plugin not installed, spawn failed, etc.
+    #[allow(clippy::too_many_arguments)]
     pub async fn ensure_started(
         self: &Arc<Self>,
         app: tauri::AppHandle,
@@ -123,34 +520,37 @@ impl AgentRuntime {
         permission_hub: Arc<PermissionHub>,
         terminal_manager: Arc<TerminalManager>,
         fs_manager: Arc<FsManager>,
+        resume_session_id: Option<SessionId>,
+        mcp_servers: Vec<McpServerConfig>,
     ) -> Result<SessionId, ApiError> {
         // Fast path: already running
-        {
-            let session_id_guard = self.session_id.lock().await;
-            if let Some(ref session_id) = *session_id_guard {
-                log::debug!(
-                    "Agent already started: agent={}, session={}",
-                    self.agent_id,
-                    session_id
-                );
-                return Ok(session_id.clone());
-            }
+        if let Some(session_id) = self.current_session().await {
+            log::debug!(
+                "Agent already started: agent={}, session={}",
+                self.agent_id,
+                session_id
+            );
+            return Ok(session_id);
         }
 
         // Acquire start lock to prevent concurrent startup
         let _start_guard = self.start_lock.lock().await;
 
         // Double-check after acquiring lock
-        {
-            let session_id_guard = self.session_id.lock().await;
-            if let Some(ref session_id) = *session_id_guard {
-                log::debug!(
-                    "Agent started by another task: agent={}, session={}",
-                    self.agent_id,
-                    session_id
-                );
-                return Ok(session_id.clone());
-            }
+        if let Some(session_id) = self.current_session().await {
+            log::debug!(
+                "Agent started by another task: agent={}, session={}",
+                self.agent_id,
+                session_id
+            );
+            return Ok(session_id);
+        }
+
+        // Fail fast when too many adapters are running already
+        let slots = app.state::<Arc<AgentSlots>>().inner().clone();
+        if let Err(e) = self.acquire_slot(&slots).await {
+            log::warn!("Agent start rejected: agent={}, error={}", self.agent_id, e);
+            return Err(e);
         }
 
         // Update status to Starting
@@ -159,21 +559,75 @@ impl AgentRuntime {
             *status = AgentRuntimeStatus::Starting;
         }
 
+        let start_context = StartContext {
+            app: app.clone(),
+            workspace_root: workspace_root.clone(),
+            plugin_manager: plugin_manager.clone(),
+            permission_hub: permission_hub.clone(),
+            terminal_manager: terminal_manager.clone(),
+            fs_manager: fs_manager.clone(),
+            mcp_servers: mcp_servers.clone(),
+        };
+
         // Create host for callbacks
+        let labels = AgentLabels {
+            workspace_id: self.workspace_id.clone(),
+            agent_id: self.agent_id.clone(),
+            plugin_id: self.plugin_id.clone(),
+            display_name: self
+                .display_name
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .clone(),
+        };
         let host = RuntimeAgentHost::new(
             app.clone(),
-            self.workspace_id.clone(),
-            self.agent_id.clone(),
+            labels,
             permission_hub,
             terminal_manager,
             fs_manager,
+            self.turn_cancellations.clone(),
+            Arc::downgrade(self),
+            self.connection_generation.fetch_add(1, Ordering::SeqCst) + 1,
         );
 
         // Emit Starting status
         host.set_status(AgentRuntimeStatus::Starting);
 
+        // The adapter runs in, and is told about, the workspace root; make sure
+        // it is still there and in a form the adapter can resolve
+        let adapter_cwd = match path::adapter_cwd(&workspace_root) {
+            Ok(cwd) => cwd,
+            Err(e) => {
+                log::error!(
+                    "Invalid adapter working directory: agent={}, cwd={:?}, error={}",
+                    self.agent_id,
+                    workspace_root,
+                    e
+                );
+                self.release_slot().await;
+                let error_status = AgentRuntimeStatus::Errored {
+                    message: e.to_string(),
+                };
+                *self.status.lock().await = error_status.clone();
+                host.set_status(error_status);
+                return Err(e);
+            }
+        };
+
+        // Resolving and connecting share one deadline, so a stuck resolve or an
+        // adapter that never finishes its handshake can't leave the agent in
+        // Starting
+        let deadline = StartupDeadline::after(STARTUP_TIMEOUT);
+
         // Resolve plugin binary
-        let mut plugin_command = match plugin_manager.resolve_bin(self.plugin_id.clone()).await {
+        let mut plugin_command = match deadline
+            .run(
+                "resolving the plugin binary",
+                plugin_manager.resolve_bin(self.plugin_id.clone()),
+            )
+            .await
+        {
             Ok(cmd) => cmd,
             Err(e) => {
                 log::error!(
@@ -182,6 +636,7 @@ impl AgentRuntime {
                     self.plugin_id,
                     e
                 );
+                self.release_slot().await;
                 let error_status = AgentRuntimeStatus::Errored {
                     message: e.to_string(),
                 };
@@ -191,36 +646,15 @@ impl AgentRuntime {
             }
         };
 
-        // Pass essential environment variables to the child process
-        // These may not be inherited if the app is launched from Finder/GUI
-        let essential_env_vars = [
-            // System paths (Claude Code needs these to find config files)
-            "HOME",
-            "USER",
-            "PATH",
-            "SHELL",
-            "TMPDIR",
-            // XDG specification paths (Linux/macOS config file locations)
-            "XDG_CONFIG_HOME",
-            "XDG_DATA_HOME",
-            "XDG_CACHE_HOME",
-            // Anthropic-specific variables
-            "ANTHROPIC_API_KEY",
-            "ANTHROPIC_BASE_URL",
-            "ANTHROPIC_AUTH_TOKEN",
-        ];
-
-        for var_name in essential_env_vars {
-            // Only add if not already set and exists in parent env
-            let already_set = plugin_command.env.iter().any(|(k, _)| k == var_name);
-            if !already_set {
-                if let Ok(value) = std::env::var(var_name) {
-                    // Show full value for debugging (TODO: redact in production)
-                    log::debug!("Passing env var to adapter: {}={}", var_name, value);
-                    plugin_command.env.push((var_name.to_string(), value));
-                }
-            }
+        // Variables from the workspace `.env`, if the user opted in
+        let workspace_env = env_file::load_workspace_env(&workspace_root).await;
+        if !workspace_env.is_empty() {
+            log::debug!(
+                "Passing workspace .env variables to adapter: count={}",
+                workspace_env.len()
+            );
         }
+        add_inherited_env(&mut plugin_command.env, workspace_env);
 
         log::debug!(
             "Adapter process environment prepared: {} variables total",
@@ -245,23 +679,40 @@ impl AgentRuntime {
         );
 
         // Connect via ACP
-        let (connection, session_id) =
-            match AcpAgent::connect(plugin_command, workspace_root, host.clone()).await {
-                Ok(result) => result,
-                Err(e) => {
-                    log::error!(
-                        "Failed to connect to agent: agent={}, error={}",
-                        self.agent_id,
-                        e
-                    );
-                    let error_status = AgentRuntimeStatus::Errored {
-                        message: e.to_string(),
-                    };
-                    *self.status.lock().await = error_status.clone();
-                    host.set_status(error_status);
-                    return Err(e);
-                }
-            };
+        let options = connect_options(
+            &app.state::<Arc<AdapterKeepalive>>(),
+            app.state::<Arc<AdapterPids>>().inner().clone(),
+        );
+        let (connection, session_id) = match deadline
+            .run(
+                "connecting to the adapter",
+                AcpAgent::connect_with_options(
+                    plugin_command,
+                    adapter_cwd,
+                    host.clone(),
+                    resume_session_id,
+                    mcp_servers,
+                    options,
+                ),
+            )
+            .await
+        {
+            Ok(result) => result,
+            Err(e) => {
+                log::error!(
+                    "Failed to connect to agent: agent={}, error={}",
+                    self.agent_id,
+                    e
+                );
+                self.release_slot().await;
+                let error_status = AgentRuntimeStatus::Errored {
+                    message: e.to_string(),
+                };
+                *self.status.lock().await = error_status.clone();
+                host.set_status(error_status);
+                return Err(e);
+            }
+        };
 
         // Store connection, session, and app handle
         {
@@ -269,13 +720,21 @@ impl AgentRuntime {
             *conn_guard = Some(connection);
         }
         {
-            let mut session_guard = self.session_id.lock().await;
-            *session_guard = Some(session_id.clone());
+            let mut sessions_guard = self.sessions.lock().await;
+            *sessions_guard = Sessions::started(session_id.clone());
         }
         {
             let mut app_guard = self.app.lock().await;
             *app_guard = Some(app);
         }
+        {
+            let mut host_guard = self.host.lock().await;
+            *host_guard = Some(host.clone());
+        }
+        {
+            let mut context_guard = self.start_context.lock().await;
+            *context_guard = Some(start_context);
+        }
 
         // Update status to Running
         let running_status = AgentRuntimeStatus::Running {
@@ -296,84 +755,728 @@ impl AgentRuntime {
         Ok(session_id)
     }
 
-    /// Send a prompt to the running agent.
+    /// Send a prompt to one of the running agent's sessions, by default its
+    /// primary session.
     ///
     /// US-07: Sends the user's prompt text to the agent via the protocol connection.
     /// The agent must already be started (call ensure_started first).
     /// Streaming responses will arrive asynchronously via AgentHost callbacks.
     ///
+    /// If the adapter reports that the session is unknown (e.g., it restarted
+    /// without us noticing), a fresh session is created and the prompt is
+    /// retried once on it.
+    ///
     /// # Arguments
-    /// * `prompt` - The user's prompt text
+    /// * `session_id` - Open session to send to (e.g. one opened with
+    ///   [`Self::new_session`]); `None` for the primary session
+    /// * `prompt` - The prompt content (use `PromptContentBlock::text` for plain text)
+    ///
+    /// Only one turn runs at a time per session. A prompt sent while a turn
+    /// is in progress is queued and sent when that turn completes, or rejected
+    /// with `TurnInProgress` if the agent's busy policy says so.
     ///
     /// # Returns
-    /// * `Ok(())` - Prompt sent successfully
+    /// * `Ok(SendPromptAck)` - The session the prompt was sent to and its turn
+    ///   id, or `queued: true` if it waits behind the active turn
+    /// * `Err(ApiError::InvalidInput)` - If `session_id` isn't an open session
+    /// * `Err(ApiError::TurnInProgress)` - If a turn is active and the policy rejects
+    /// * `Err(ApiError::TooManyPrompts)` - If the queue behind the active turn is full
     /// * `Err(ApiError::ProtocolError)` - If agent is not running or connection unavailable
+    /// * `Err(ApiError::SessionNotFound)` - If the retried prompt is also rejected
     /// * `Err(ApiError::IoError)` - If writing to the protocol fails
-    pub async fn send_prompt(self: &Arc<Self>, prompt: String) -> Result<(), ApiError> {
-        // Get session_id (fail if agent not running)
-        let session_id = {
-            let session_guard = self.session_id.lock().await;
-            session_guard
-                .clone()
+    pub async fn send_prompt(
+        self: &Arc<Self>,
+        session_id: Option<SessionId>,
+        prompt: Vec<PromptContentBlock>,
+    ) -> Result<SendPromptAck, ApiError> {
+        self.send_prompt_with_timeout(session_id, prompt, None)
+            .await
+    }
+
+    /// Like [`Self::send_prompt`], but the turn may run for at most `timeout`
+    /// once it starts (a queued prompt's wait doesn't count).
+    ///
+    /// A turn still running when the timeout passes is cancelled and ends
+    /// with a `TurnComplete` error; if the adapter hadn't even accepted the
+    /// prompt, this fails with a `RequestTimeout` protocol error instead.
+    pub async fn send_prompt_with_timeout(
+        self: &Arc<Self>,
+        session_id: Option<SessionId>,
+        prompt: Vec<PromptContentBlock>,
+        timeout: Option<Duration>,
+    ) -> Result<SendPromptAck, ApiError> {
+        let session_id = match session_id {
+            Some(session_id) => {
+                let sessions = self.sessions.lock().await;
+                // Not running is reported by start_or_queue_turn below
+                if sessions.primary.is_some() && !sessions.states.contains_key(&session_id) {
+                    return Err(ApiError::InvalidInput {
+                        message: "Session ID does not match an open session".to_string(),
+                    });
+                }
+                session_id
+            }
+            None => self
+                .current_session()
+                .await
                 .ok_or_else(|| ApiError::ProtocolError {
+                    kind: ProtocolErrorKind::NotConnected,
                     message: "Agent not running".to_string(),
-                })?
+                })?,
         };
+        self.start_or_queue_turn(
+            session_id,
+            TurnPrompt {
+                blocks: prompt,
+                timeout,
+            },
+        )
+        .await
+    }
+
+    /// Send `prompt` on `session_id`, or queue it behind the active turn.
+    async fn start_or_queue_turn(
+        self: &Arc<Self>,
+        session_id: SessionId,
+        prompt: TurnPrompt,
+    ) -> Result<SendPromptAck, ApiError> {
+        {
+            let mut sessions = self.sessions.lock().await;
+            if sessions.primary.is_none() {
+                return Err(ApiError::ProtocolError {
+                    kind: ProtocolErrorKind::NotConnected,
+                    message: "Agent not running".to_string(),
+                });
+            }
+            let Some(state) = sessions.states.get_mut(&session_id) else {
+                return Err(ApiError::SessionNotFound { session_id });
+            };
+            if state.phase != TurnPhase::Idle {
+                if self.busy_prompt_policy() == BusyPromptPolicy::Reject {
+                    return Err(ApiError::TurnInProgress { session_id });
+                }
+                // The running turn counts as outstanding too
+                let limit = self.max_outstanding_prompts.load(Ordering::SeqCst);
+                if state.queue.len() + 1 >= limit as usize {
+                    log::warn!(
+                        "Prompt rejected, too many outstanding: agent={}, session={session_id}, limit={limit}",
+                        self.agent_id
+                    );
+                    return Err(ApiError::TooManyPrompts { session_id, limit });
+                }
+                state.queue.push_back(prompt);
+                log::info!(
+                    "Prompt queued behind active turn: agent={}, session={session_id}, queued={}",
+                    self.agent_id,
+                    state.queue.len()
+                );
+                return Ok(SendPromptAck {
+                    session_id,
+                    turn_id: None,
+                    queued: true,
+                });
+            }
+            state.phase = TurnPhase::Starting;
+        }
+
+        let (session_id, result) = self.dispatch_prompt(session_id, prompt).await;
+        self.turn_started(&session_id, &result).await;
+        result
+    }
 
+    /// Send a prompt on the connection right away (see [`Self::send_prompt`]).
+    ///
+    /// Also returns the session the turn ended up on, which differs from
+    /// `session_id` if the adapter lost it and it was replaced.
+    async fn dispatch_prompt(
+        self: &Arc<Self>,
+        session_id: SessionId,
+        prompt: TurnPrompt,
+    ) -> (SessionId, Result<SendPromptAck, ApiError>) {
+        let TurnPrompt {
+            blocks: prompt,
+            timeout,
+        } = prompt;
         // Get connection (fail if connection unavailable)
         let connection = {
             let conn_guard = self.connection.lock().await;
-            conn_guard.clone().ok_or_else(|| ApiError::ProtocolError {
-                message: "Agent connection not available".to_string(),
-            })?
+            match conn_guard.clone() {
+                Some(connection) => connection,
+                None => {
+                    let error = ApiError::ProtocolError {
+                        kind: ProtocolErrorKind::NotConnected,
+                        message: "Agent connection not available".to_string(),
+                    };
+                    return (session_id, Err(error));
+                }
+            }
         };
 
         // Call the trait method to send prompt
-        connection.send_prompt(session_id, prompt).await
+        let (session_id, result) = match connection
+            .send_prompt_with_timeout(session_id.clone(), prompt.clone(), timeout)
+            .await
+        {
+            Ok(turn_id) => (
+                session_id.clone(),
+                Ok(SendPromptAck {
+                    session_id,
+                    turn_id: Some(turn_id),
+                    queued: false,
+                }),
+            ),
+            Err(ApiError::SessionNotFound { .. }) => {
+                log::warn!(
+                    "Adapter lost session, creating a new one: agent={}, session={}",
+                    self.agent_id,
+                    session_id
+                );
+                let new_session_id =
+                    match self.replace_session(connection.as_ref(), &session_id).await {
+                        Ok(new_session_id) => new_session_id,
+                        Err(e) => return (session_id, Err(e)),
+                    };
+                log::info!(
+                    "Retrying prompt on new session: agent={}, previous={}, session={}",
+                    self.agent_id,
+                    session_id,
+                    new_session_id
+                );
+                let result = connection
+                    .send_prompt_with_timeout(new_session_id.clone(), prompt, timeout)
+                    .await
+                    .map(|turn_id| SendPromptAck {
+                        session_id: new_session_id.clone(),
+                        turn_id: Some(turn_id),
+                        queued: false,
+                    });
+                (new_session_id, result)
+            }
+            Err(e) => (session_id, Err(e)),
+        };
+
+        // A dead adapter (e.g. it closed its stdin) can't be retried; drop the
+        // connection so the next prompt restarts it
+        if result.is_err() && !connection.is_connected() {
+            self.mark_connection_lost().await;
+        }
+
+        (session_id, result)
     }
 
-    /// Stop the current turn for the given session.
-    ///
-    /// US-12: Cancels the active turn via the protocol connection.
-    /// The agent must already be started (call ensure_started first).
-    ///
-    /// # Arguments
-    /// * `session_id` - The session to cancel the current turn for
+    /// Forget the current connection and session after the adapter went away.
     ///
-    /// # Returns
-    /// * `Ok(())` - Cancel request sent successfully
-    /// * `Err(ApiError::ProtocolError)` - If agent is not running or connection unavailable
-    /// * `Err(ApiError::IoError)` - If writing to the protocol fails
-    pub async fn stop_turn(self: &Arc<Self>, session_id: SessionId) -> Result<(), ApiError> {
-        // Ensure agent is running and the session matches
-        let current_session_id = {
-            let session_guard = self.session_id.lock().await;
-            session_guard
-                .clone()
-                .ok_or_else(|| ApiError::ProtocolError {
-                    message: "Agent not running".to_string(),
-                })?
+    /// The next `ensure_started` spawns a fresh adapter process.
+    async fn mark_connection_lost(&self) {
+        log::warn!(
+            "Agent connection lost, will restart on next prompt: agent={}",
+            self.agent_id
+        );
+        *self.connection.lock().await = None;
+        self.sessions.lock().await.clear();
+        *self.status.lock().await = AgentRuntimeStatus::Stopped;
+        self.release_slot().await;
+    }
+
+    /// Record the outcome of sending a turn's prompt on `session_id`.
+    async fn turn_started(&self, session_id: &SessionId, result: &Result<SendPromptAck, ApiError>) {
+        let mut sessions = self.sessions.lock().await;
+        // Gone if the session was closed meanwhile
+        let Some(state) = sessions.states.get_mut(session_id) else {
+            return;
         };
+        // Already Idle if the turn completed before the send returned
+        if state.phase == TurnPhase::Starting {
+            state.phase = match result {
+                Ok(SendPromptAck {
+                    turn_id: Some(turn_id),
+                    ..
+                }) => TurnPhase::Active(turn_id.clone()),
+                _ => TurnPhase::Idle,
+            };
+        }
+    }
 
-        if current_session_id != session_id {
-            return Err(ApiError::InvalidInput {
-                message: "Session ID does not match active session".to_string(),
-            });
+    /// Advance past a finished turn and send the session's next queued
+    /// prompt, if any.
+    ///
+    /// Called when the adapter's `TurnComplete` for `session_id` arrives on
+    /// connection `generation`; completions of other turns are ignored.
+    pub(crate) async fn handle_turn_complete(
+        self: &Arc<Self>,
+        generation: u64,
+        session_id: SessionId,
+        turn_id: Option<TurnId>,
+    ) {
+        if generation != self.connection_generation.load(Ordering::SeqCst) {
+            return;
         }
 
-        // Get connection (fail if connection unavailable)
-        let connection = {
-            let conn_guard = self.connection.lock().await;
-            conn_guard.clone().ok_or_else(|| ApiError::ProtocolError {
-                message: "Agent connection not available".to_string(),
-            })?
+        let mut next = {
+            let mut sessions = self.sessions.lock().await;
+            let Some(state) = sessions.states.get_mut(&session_id) else {
+                return;
+            };
+            let finished = match (&state.phase, &turn_id) {
+                (TurnPhase::Active(active), Some(turn_id)) => active == turn_id,
+                (TurnPhase::Idle, _) => false,
+                _ => true,
+            };
+            if !finished {
+                return;
+            }
+            state.phase = TurnPhase::Idle;
+            state.next_queued()
         };
 
-        // Call the trait method to cancel turn
-        connection.cancel_turn(session_id).await
-    }
-}
-
+        let mut session_id = session_id;
+        while let Some(prompt) = next {
+            log::info!(
+                "Sending queued prompt: agent={}, session={session_id}",
+                self.agent_id
+            );
+            let (sent_on, result) = self.dispatch_prompt(session_id, prompt).await;
+            self.turn_started(&sent_on, &result).await;
+            session_id = sent_on;
+            match result {
+                Ok(_) => return,
+                Err(e) => {
+                    log::warn!("Queued prompt failed: agent={}, error={e}", self.agent_id);
+                    next = self
+                        .sessions
+                        .lock()
+                        .await
+                        .states
+                        .get_mut(&session_id)
+                        .and_then(SessionState::next_queued);
+                }
+            }
+        }
+    }
+
+    /// React to the adapter process going away on its own.
+    ///
+    /// Called by the host of connection `generation`; notices from an older
+    /// connection (e.g. one already stopped and replaced) are ignored.
+    /// With auto-restart enabled, a reconnect is scheduled with exponential
+    /// backoff, resuming the lost session.
+    pub(crate) async fn handle_connection_lost(self: &Arc<Self>, generation: u64) {
+        let _start_guard = self.start_lock.lock().await;
+        if generation != self.connection_generation.load(Ordering::SeqCst) {
+            log::debug!(
+                "Ignoring connection loss of a replaced connection: agent={}, generation={generation}",
+                self.agent_id
+            );
+            return;
+        }
+        if self.connection.lock().await.is_none() {
+            // Already stopped deliberately
+            return;
+        }
+
+        let lost_session_id = self.current_session().await;
+        self.mark_connection_lost().await;
+        let host = self.host.lock().await.clone();
+        if let Some(host) = host {
+            host.set_status(AgentRuntimeStatus::Stopped);
+        }
+
+        if self.auto_restart.load(Ordering::SeqCst) {
+            let runtime = self.clone();
+            let task = tokio::spawn(async move {
+                runtime.restart_with_backoff(lost_session_id).await;
+            });
+            *self.restart_task.lock().await = Some(task);
+        }
+    }
+
+    /// Try to start the adapter again, waiting longer after each failure.
+    async fn restart_with_backoff(self: &Arc<Self>, resume_session_id: Option<SessionId>) {
+        let Some(context) = self.start_context.lock().await.clone() else {
+            return;
+        };
+
+        for attempt in 1..=AUTO_RESTART_MAX_ATTEMPTS {
+            tokio::time::sleep(restart_delay(attempt)).await;
+            log::info!(
+                "Restarting agent after unexpected exit: agent={}, attempt={attempt}",
+                self.agent_id
+            );
+
+            let context = context.clone();
+            match self
+                .ensure_started(
+                    context.app,
+                    context.workspace_root,
+                    context.plugin_manager,
+                    context.permission_hub,
+                    context.terminal_manager,
+                    context.fs_manager,
+                    resume_session_id.clone(),
+                    context.mcp_servers,
+                )
+                .await
+            {
+                Ok(session_id) => {
+                    log::info!(
+                        "Agent restarted: agent={}, session={session_id}",
+                        self.agent_id
+                    );
+                    return;
+                }
+                Err(e) => {
+                    log::warn!(
+                        "Agent restart failed: agent={}, attempt={attempt}, error={e}",
+                        self.agent_id
+                    );
+                }
+            }
+        }
+
+        log::error!(
+            "Giving up restarting agent after {AUTO_RESTART_MAX_ATTEMPTS} attempts: agent={}",
+            self.agent_id
+        );
+    }
+
+    /// Create a new session on the connection to take over from `lost`,
+    /// keeping its queued prompts.
+    async fn replace_session(
+        &self,
+        connection: &dyn AgentConnection,
+        lost: &SessionId,
+    ) -> Result<SessionId, ApiError> {
+        let new_session_id = connection.replace_session(lost.clone()).await?;
+
+        let was_primary = {
+            let mut sessions = self.sessions.lock().await;
+            let state = sessions.states.remove(lost).unwrap_or_default();
+            sessions.states.insert(new_session_id.clone(), state);
+            let was_primary = sessions.primary.as_ref() == Some(lost);
+            if was_primary {
+                sessions.primary = Some(new_session_id.clone());
+            }
+            was_primary
+        };
+        // The status reports the primary session only
+        if !was_primary {
+            return Ok(new_session_id);
+        }
+
+        let running_status = AgentRuntimeStatus::Running {
+            session_id: new_session_id.clone(),
+        };
+        {
+            let mut status = self.status.lock().await;
+            *status = running_status.clone();
+        }
+        let host = self.host.lock().await.clone();
+        if let Some(host) = host {
+            host.set_status(running_status);
+        }
+
+        Ok(new_session_id)
+    }
+
+    /// Stop the current turn for the given session.
+    ///
+    /// US-12: Cancels the active turn via the protocol connection.
+    /// The agent must already be started (call ensure_started first).
+    ///
+    /// # Arguments
+    /// * `session_id` - The session to cancel the current turn for
+    ///
+    /// # Returns
+    /// * `Ok(())` - Cancel request sent successfully
+    /// * `Err(ApiError::ProtocolError)` - If agent is not running or connection unavailable
+    /// * `Err(ApiError::InvalidInput)` - If the session isn't open on this agent
+    /// * `Err(ApiError::IoError)` - If writing to the protocol fails
+    pub async fn stop_turn(self: &Arc<Self>, session_id: SessionId) -> Result<(), ApiError> {
+        // Ensure agent is running and the session is one of its own
+        {
+            let sessions = self.sessions.lock().await;
+            if sessions.primary.is_none() {
+                return Err(ApiError::ProtocolError {
+                    kind: ProtocolErrorKind::NotConnected,
+                    message: "Agent not running".to_string(),
+                });
+            }
+            if !sessions.states.contains_key(&session_id) {
+                return Err(ApiError::InvalidInput {
+                    message: "Session ID does not match an open session".to_string(),
+                });
+            }
+        }
+
+        // Get connection (fail if connection unavailable)
+        let connection = {
+            let conn_guard = self.connection.lock().await;
+            conn_guard.clone().ok_or_else(|| ApiError::ProtocolError {
+                kind: ProtocolErrorKind::NotConnected,
+                message: "Agent connection not available".to_string(),
+            })?
+        };
+
+        // Abort in-flight file operations first; the adapter may take a while
+        // to react to the cancel notification
+        self.turn_cancellations.cancel(&session_id);
+
+        // Call the trait method to cancel turn
+        connection.cancel_turn(session_id).await
+    }
+
+    /// Switch an open session to another of its adapter's modes.
+    ///
+    /// The new mode is reported back through the host once the adapter
+    /// accepts it, like any other `CurrentModeUpdate`.
+    ///
+    /// # Returns
+    /// * `Ok(())` - Mode changed
+    /// * `Err(ApiError::ProtocolError)` - If the agent is not running, has no
+    ///   modes, or the adapter rejects the change
+    /// * `Err(ApiError::InvalidInput)` - If the session isn't open on this
+    ///   agent or the adapter doesn't offer `mode_id`
+    pub async fn set_mode(&self, session_id: SessionId, mode_id: String) -> Result<(), ApiError> {
+        let connection = self.running_connection().await?;
+        if !self.sessions.lock().await.states.contains_key(&session_id) {
+            return Err(ApiError::InvalidInput {
+                message: "Session ID does not match an open session".to_string(),
+            });
+        }
+        connection.set_mode(session_id, mode_id).await
+    }
+
+    /// Open another session on the running agent's connection.
+    ///
+    /// The session gets its own turn and prompt queue; send to it with
+    /// [`Self::send_prompt`]. The primary session is unchanged.
+    ///
+    /// # Returns
+    /// * `Ok(SessionId)` - The new session
+    /// * `Err(ApiError::ProtocolError)` - If the agent is not running or the
+    ///   adapter can't create the session
+    pub async fn new_session(&self) -> Result<SessionId, ApiError> {
+        let connection = self.running_connection().await?;
+        let session_id = connection.new_session().await?;
+
+        let mut sessions = self.sessions.lock().await;
+        if sessions.primary.is_none() {
+            // Stopped while the session was being created
+            return Err(ApiError::ProtocolError {
+                kind: ProtocolErrorKind::NotConnected,
+                message: "Agent not running".to_string(),
+            });
+        }
+        sessions
+            .states
+            .insert(session_id.clone(), SessionState::default());
+        log::info!(
+            "Session opened: agent={}, session={session_id}, open_sessions={}",
+            self.agent_id,
+            sessions.states.len()
+        );
+        Ok(session_id)
+    }
+
+    /// Close a session opened with [`Self::new_session`].
+    ///
+    /// Its running turn is cancelled and queued prompts are dropped. The
+    /// session is forgotten even if the adapter fails to close it. The
+    /// primary session can't be closed; stop the agent instead.
+    ///
+    /// # Returns
+    /// * `Ok(())` - Session closed
+    /// * `Err(ApiError::ProtocolError)` - If the agent is not running or the
+    ///   adapter fails to close the session
+    /// * `Err(ApiError::InvalidInput)` - If the session is the primary one or
+    ///   isn't open on this agent
+    pub async fn close_session(&self, session_id: SessionId) -> Result<(), ApiError> {
+        let connection = self.running_connection().await?;
+        {
+            let mut sessions = self.sessions.lock().await;
+            if sessions.primary.as_ref() == Some(&session_id) {
+                return Err(ApiError::InvalidInput {
+                    message: "The agent's primary session can't be closed".to_string(),
+                });
+            }
+            let Some(state) = sessions.states.remove(&session_id) else {
+                return Err(ApiError::InvalidInput {
+                    message: "Session ID does not match an open session".to_string(),
+                });
+            };
+            if !state.queue.is_empty() {
+                log::warn!(
+                    "Dropping {} queued prompt(s) of closed session: agent={}, session={session_id}",
+                    state.queue.len(),
+                    self.agent_id
+                );
+            }
+        }
+
+        self.turn_cancellations.cancel(&session_id);
+        self.announcements().remove(&session_id);
+        connection.close_session(session_id.clone()).await?;
+        log::info!(
+            "Session closed: agent={}, session={session_id}",
+            self.agent_id
+        );
+        Ok(())
+    }
+
+    /// Stop the agent: shut down its adapter process and forget its sessions.
+    ///
+    /// Idempotent: stopping an agent that isn't running is a no-op. The next
+    /// `ensure_started` starts a fresh adapter (resuming the recorded session
+    /// where supported).
+    ///
+    /// # Returns
+    /// * `Ok(())` - Agent stopped (or was not running)
+    /// * `Err(ApiError)` - If the connection failed to shut down
+    pub async fn stop(self: &Arc<Self>) -> Result<(), ApiError> {
+        // A deliberate stop wins over a scheduled automatic restart
+        let restart_pending = match self.restart_task.lock().await.take() {
+            Some(task) if !task.is_finished() => {
+                task.abort();
+                true
+            }
+            _ => false,
+        };
+
+        // Don't race a concurrent startup
+        let _start_guard = self.start_lock.lock().await;
+
+        let Some(connection) = self.connection.lock().await.take() else {
+            if restart_pending {
+                log::info!("Agent restart cancelled: agent={}", self.agent_id);
+                self.set_stopped().await;
+            } else {
+                log::debug!("Agent stop ignored, not running: agent={}", self.agent_id);
+            }
+            return Ok(());
+        };
+        let mut sessions = std::mem::take(&mut *self.sessions.lock().await);
+
+        log::info!(
+            "Stopping agent: agent={}, session={:?}, open_sessions={}",
+            self.agent_id,
+            sessions.primary,
+            sessions.states.len()
+        );
+
+        for session_id in sessions.states.keys() {
+            self.turn_cancellations.cancel(session_id);
+        }
+
+        let result = connection.shutdown().await;
+        sessions.clear();
+        self.release_slot().await;
+        self.set_stopped().await;
+
+        result
+    }
+
+    /// Restart the agent: shut down its adapter and start a fresh one.
+    ///
+    /// Takes the same parameters as [`AgentRuntime::ensure_started`]. The new
+    /// adapter resumes the live session (else `resume_session_id`) via
+    /// `session/load` so the history survives, and falls back to a new
+    /// session if the adapter can't load it. Emits `Stopped`, then `Starting`
+    /// and `Running`.
+    ///
+    /// # Returns
+    /// * `Ok(SessionId)` - The resumed or new session
+    /// * `Err(ApiError)` - Plugin not installed, spawn failed, etc.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn restart(
+        self: &Arc<Self>,
+        app: tauri::AppHandle,
+        workspace_root: PathBuf,
+        plugin_manager: Arc<PluginManager>,
+        permission_hub: Arc<PermissionHub>,
+        terminal_manager: Arc<TerminalManager>,
+        fs_manager: Arc<FsManager>,
+        resume_session_id: Option<SessionId>,
+        mcp_servers: Vec<McpServerConfig>,
+    ) -> Result<SessionId, ApiError> {
+        let resume_session_id = self.current_session().await.or(resume_session_id);
+        let runtime = self.clone();
+        self.restart_with(resume_session_id, move |resume_session_id| async move {
+            runtime
+                .ensure_started(
+                    app,
+                    workspace_root,
+                    plugin_manager,
+                    permission_hub,
+                    terminal_manager,
+                    fs_manager,
+                    resume_session_id,
+                    mcp_servers,
+                )
+                .await
+        })
+        .await
+    }
+
+    /// Stop the agent, then run `start` with the session to resume.
+    async fn restart_with<F, Fut>(
+        self: &Arc<Self>,
+        resume_session_id: Option<SessionId>,
+        start: F,
+    ) -> Result<SessionId, ApiError>
+    where
+        F: FnOnce(Option<SessionId>) -> Fut,
+        Fut: std::future::Future<Output = Result<SessionId, ApiError>>,
+    {
+        log::info!(
+            "Restarting agent: agent={}, resume={resume_session_id:?}",
+            self.agent_id
+        );
+
+        let was_running = self.is_running().await;
+        if let Err(e) = self.stop().await {
+            // The connection is dropped either way; start the new one regardless
+            log::warn!(
+                "Agent shutdown failed during restart: agent={}, error={e}",
+                self.agent_id
+            );
+        }
+        if !was_running {
+            self.set_stopped().await;
+        }
+
+        start(resume_session_id).await
+    }
+
+    /// Record and announce the Stopped status.
+    async fn set_stopped(&self) {
+        *self.status.lock().await = AgentRuntimeStatus::Stopped;
+        let host = self.host.lock().await.clone();
+        if let Some(host) = host {
+            host.set_status(AgentRuntimeStatus::Stopped);
+        }
+    }
+}
+
+/// Options an adapter is connected with: the keepalive currently configured,
+/// and the registry its pid goes in.
+fn connect_options(
+    keepalive: &AdapterKeepalive,
+    adapter_pids: Arc<AdapterPids>,
+) -> AcpConnectOptions {
+    AcpConnectOptions {
+        keepalive: keepalive.get(),
+        adapter_pids: Some(adapter_pids),
+        ..AcpConnectOptions::default()
+    }
+}
+
+/// Delay before restart `attempt` (1-based): doubles each time, capped.
+fn restart_delay(attempt: u32) -> Duration {
+    AUTO_RESTART_BASE_DELAY
+        .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+        .min(AUTO_RESTART_MAX_DELAY)
+}
+
 /// Registry of agent entities within a single workspace.
 ///
 /// Thread-safe: Uses tokio::sync::Mutex for concurrent access.
@@ -383,31 +1486,100 @@ pub struct AgentRegistry {
     agents: Mutex<HashMap<AgentId, AgentRecord>>,
     /// Map of agent ID to agent runtime (lazily created on first prompt)
     runtimes: Mutex<HashMap<AgentId, Arc<AgentRuntime>>>,
+    /// Outstanding prompt cap applied to every runtime
+    max_outstanding_prompts: AtomicU32,
 }
 
 impl AgentRegistry {
     /// Creates a new empty AgentRegistry.
     pub fn new() -> Self {
+        Self::with_agents(Vec::new())
+    }
+
+    /// Creates a registry holding agents saved in an earlier session.
+    ///
+    /// No runtimes are created; each agent still starts on its first prompt.
+    /// Records with an invalid plugin ID are dropped with a warning.
+    pub fn with_agents(records: Vec<AgentRecord>) -> Self {
+        let agents = records
+            .into_iter()
+            .filter(
+                |record| match PluginManager::validate_plugin_id(&record.plugin_id) {
+                    Ok(()) => true,
+                    Err(e) => {
+                        log::warn!(
+                            "Dropping saved agent: id={}, plugin={}, error={e}",
+                            record.agent_id,
+                            record.plugin_id
+                        );
+                        false
+                    }
+                },
+            )
+            .map(|record| (record.agent_id.clone(), record))
+            .collect();
         Self {
-            agents: Mutex::new(HashMap::new()),
+            agents: Mutex::new(agents),
             runtimes: Mutex::new(HashMap::new()),
+            max_outstanding_prompts: AtomicU32::new(DEFAULT_MAX_OUTSTANDING_PROMPTS),
         }
     }
 
-    /// Lists all agents in the registry.
+    /// Lists agents in the registry, oldest first.
+    ///
+    /// # Arguments
+    /// * `offset` - Agents to skip (default 0)
+    /// * `limit` - Most agents to return (default: all)
     ///
     /// # Returns
-    /// * `Vec<AgentRecord>` - List of all agent records
-    pub async fn list_agents(&self) -> Vec<AgentRecord> {
-        let agents = self.agents.lock().await;
-        agents.values().cloned().collect()
+    /// * `Page<AgentRecord>` - The requested agent records and the total count
+    pub async fn list_agents(&self, offset: Option<u32>, limit: Option<u32>) -> Page<AgentRecord> {
+        let mut records: Vec<AgentRecord> = self.agents.lock().await.values().cloned().collect();
+        // Ties (e.g. agents saved before creation times were kept) by ID, so
+        // pages stay stable
+        records.sort_by(|a, b| {
+            a.created_at_ms
+                .partial_cmp(&b.created_at_ms)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.agent_id.cmp(&b.agent_id))
+        });
+        Page::slice(records, offset, limit)
+    }
+
+    /// Validate a display name and return it trimmed.
+    ///
+    /// Valid display names:
+    /// - Are 1-128 characters after trimming
+    /// - Contain no control characters (tabs, newlines, escapes, ...)
+    pub fn validate_display_name(display_name: &str) -> Result<String, ApiError> {
+        let name = display_name.trim();
+        if name.is_empty() {
+            return Err(ApiError::InvalidInput {
+                message: "Display name cannot be empty".to_string(),
+            });
+        }
+
+        if name.chars().count() > MAX_DISPLAY_NAME_CHARS {
+            return Err(ApiError::InvalidInput {
+                message: format!("Display name cannot exceed {MAX_DISPLAY_NAME_CHARS} characters"),
+            });
+        }
+
+        if name.chars().any(char::is_control) {
+            return Err(ApiError::InvalidInput {
+                message: "Display name cannot contain control characters".to_string(),
+            });
+        }
+
+        Ok(name.to_string())
     }
 
     /// Creates a new agent entity in the registry.
     ///
     /// # Arguments
     /// * `plugin_id` - Plugin identifier (validated for format)
-    /// * `display_name` - Optional display name (if Some, must be non-empty after trim)
+    /// * `display_name` - Optional display name (see `validate_display_name`),
+    ///   stored trimmed
     ///
     /// # Returns
     /// * `Ok(AgentRecord)` - The created agent record
@@ -421,13 +1593,10 @@ impl AgentRegistry {
         PluginManager::validate_plugin_id(&plugin_id)?;
 
         // Validate display_name if provided
-        if let Some(ref name) = display_name {
-            if name.trim().is_empty() {
-                return Err(ApiError::InvalidInput {
-                    message: "Display name cannot be empty".to_string(),
-                });
-            }
-        }
+        let display_name = display_name
+            .as_deref()
+            .map(Self::validate_display_name)
+            .transpose()?;
 
         // Generate unique agent ID
         let agent_id = Uuid::new_v4().to_string();
@@ -436,6 +1605,14 @@ impl AgentRegistry {
             agent_id: agent_id.clone(),
             plugin_id,
             display_name,
+            session_id: None,
+            mcp_servers: Vec::new(),
+            auto_restart: false,
+            busy_prompt_policy: BusyPromptPolicy::default(),
+            created_at_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as f64)
+                .unwrap_or(0.0),
         };
 
         // Insert into registry
@@ -469,44 +1646,312 @@ impl AgentRegistry {
             })
     }
 
-    /// Get or create an AgentRuntime for the given agent.
+    /// Remember the adapter session for an agent so it can be resumed later.
     ///
-    /// This is called during lazy startup to get the runtime handle.
-    /// The runtime is created if it doesn't exist.
+    /// # Returns
+    /// * `Ok(())` - Session recorded
+    /// * `Err(ApiError::AgentNotFound)` - If agent doesn't exist
+    pub async fn set_session_id(
+        &self,
+        agent_id: &AgentId,
+        session_id: SessionId,
+    ) -> Result<(), ApiError> {
+        let mut agents = self.agents.lock().await;
+        let record = agents
+            .get_mut(agent_id)
+            .ok_or_else(|| ApiError::AgentNotFound {
+                agent_id: agent_id.clone(),
+            })?;
+        record.session_id = Some(session_id);
+        Ok(())
+    }
+
+    /// Configure the MCP servers an agent's adapter should use.
     ///
-    /// # Arguments
-    /// * `workspace_id` - The workspace this agent belongs to
-    /// * `agent_id` - The agent to get runtime for
+    /// Takes effect the next time the agent starts a session.
     ///
     /// # Returns
-    /// * `Ok(Arc<AgentRuntime>)` - The agent runtime (existing or newly created)
-    /// * `Err(ApiError::AgentNotFound)` - If agent doesn't exist in registry
-    pub async fn ensure_runtime(
+    /// * `Ok(())` - Servers recorded
+    /// * `Err(ApiError::InvalidInput)` - If a server has an empty name or command
+    /// * `Err(ApiError::AgentNotFound)` - If agent doesn't exist
+    pub async fn set_mcp_servers(
         &self,
-        workspace_id: WorkspaceId,
-        agent_id: AgentId,
-    ) -> Result<Arc<AgentRuntime>, ApiError> {
-        // Verify agent exists
-        let record = self.get_agent(&agent_id).await?;
+        agent_id: &AgentId,
+        mcp_servers: Vec<McpServerConfig>,
+    ) -> Result<(), ApiError> {
+        validate_mcp_servers(&mcp_servers)?;
+
+        let mut agents = self.agents.lock().await;
+        let record = agents
+            .get_mut(agent_id)
+            .ok_or_else(|| ApiError::AgentNotFound {
+                agent_id: agent_id.clone(),
+            })?;
+        log::info!(
+            "Agent MCP servers configured: id={agent_id}, servers={}",
+            mcp_servers.len()
+        );
+        record.mcp_servers = mcp_servers;
+        Ok(())
+    }
 
-        // Check if runtime already exists
+    /// Turn automatic restarts after unexpected adapter exits on or off.
+    ///
+    /// Applies to a running agent immediately.
+    ///
+    /// # Returns
+    /// * `Ok(())` - Setting recorded
+    /// * `Err(ApiError::AgentNotFound)` - If agent doesn't exist
+    pub async fn set_auto_restart(
+        &self,
+        agent_id: &AgentId,
+        enabled: bool,
+    ) -> Result<(), ApiError> {
         {
-            let runtimes = self.runtimes.lock().await;
-            if let Some(runtime) = runtimes.get(&agent_id) {
-                return Ok(runtime.clone());
-            }
+            let mut agents = self.agents.lock().await;
+            let record = agents
+                .get_mut(agent_id)
+                .ok_or_else(|| ApiError::AgentNotFound {
+                    agent_id: agent_id.clone(),
+                })?;
+            record.auto_restart = enabled;
         }
+        log::info!("Agent auto-restart configured: id={agent_id}, enabled={enabled}");
 
-        // Create new runtime
-        let runtime = AgentRuntime::new(agent_id.clone(), workspace_id, record.plugin_id);
+        let runtime = self.runtimes.lock().await.get(agent_id).cloned();
+        if let Some(runtime) = runtime {
+            runtime.set_auto_restart(enabled);
+        }
+        Ok(())
+    }
 
-        // Insert into runtimes map
+    /// Choose whether prompts sent mid-turn are queued or rejected.
+    ///
+    /// Applies to a running agent immediately.
+    ///
+    /// # Returns
+    /// * `Ok(())` - Setting recorded
+    /// * `Err(ApiError::AgentNotFound)` - If agent doesn't exist
+    pub async fn set_busy_prompt_policy(
+        &self,
+        agent_id: &AgentId,
+        policy: BusyPromptPolicy,
+    ) -> Result<(), ApiError> {
         {
-            let mut runtimes = self.runtimes.lock().await;
-            // Double-check in case another task created it
-            if let Some(existing) = runtimes.get(&agent_id) {
+            let mut agents = self.agents.lock().await;
+            let record = agents
+                .get_mut(agent_id)
+                .ok_or_else(|| ApiError::AgentNotFound {
+                    agent_id: agent_id.clone(),
+                })?;
+            record.busy_prompt_policy = policy;
+        }
+        log::info!("Agent busy prompt policy configured: id={agent_id}, policy={policy:?}");
+
+        let runtime = self.runtimes.lock().await.get(agent_id).cloned();
+        if let Some(runtime) = runtime {
+            runtime.set_busy_prompt_policy(policy);
+        }
+        Ok(())
+    }
+
+    /// Cap outstanding prompts per session for all agents, including those
+    /// already running.
+    pub async fn set_max_outstanding_prompts(&self, limit: u32) {
+        self.max_outstanding_prompts.store(limit, Ordering::SeqCst);
+        for runtime in self.runtimes.lock().await.values() {
+            runtime.set_max_outstanding_prompts(limit);
+        }
+    }
+
+    /// Delete an agent, shutting down its adapter first if it is running.
+    ///
+    /// The record is kept if the adapter fails to shut down, so the delete
+    /// can be retried.
+    ///
+    /// # Returns
+    /// * `Ok(())` - Agent stopped and removed
+    /// * `Err(ApiError::AgentNotFound)` - If agent doesn't exist
+    /// * `Err(ApiError)` - If the running adapter failed to shut down
+    pub async fn delete_agent(&self, agent_id: &AgentId) -> Result<(), ApiError> {
+        self.get_agent(agent_id).await?;
+
+        let runtime = self.runtimes.lock().await.get(agent_id).cloned();
+        if let Some(runtime) = runtime {
+            runtime.stop().await?;
+        }
+
+        self.runtimes.lock().await.remove(agent_id);
+        self.agents.lock().await.remove(agent_id);
+        log::info!("Agent deleted: id={agent_id}");
+        Ok(())
+    }
+
+    /// Shut down every running agent in this registry.
+    ///
+    /// Agents are stopped concurrently, so one slow adapter doesn't hold up
+    /// the others. Failures are logged and don't stop the remaining agents.
+    ///
+    /// # Returns
+    /// * Number of agents that were running and were stopped
+    pub async fn shutdown_all(&self) -> usize {
+        // Snapshot the runtimes so the registry lock isn't held while stopping
+        let runtimes: Vec<Arc<AgentRuntime>> = {
+            let runtimes = self.runtimes.lock().await;
+            runtimes.values().cloned().collect()
+        };
+
+        let mut stops = tokio::task::JoinSet::new();
+        for runtime in runtimes {
+            if !runtime.is_running().await {
+                continue;
+            }
+            stops.spawn(async move {
+                if let Err(e) = runtime.stop().await {
+                    log::warn!(
+                        "Agent shutdown failed: agent={}, error={e}",
+                        runtime.agent_id
+                    );
+                }
+            });
+        }
+
+        let stopped = stops.len();
+        while let Some(result) = stops.join_next().await {
+            if let Err(e) = result {
+                log::warn!("Agent shutdown task failed: {e}");
+            }
+        }
+        stopped
+    }
+
+    /// Current runtime status of an agent.
+    ///
+    /// An agent that has never been started has no runtime and reports `Stopped`.
+    ///
+    /// # Returns
+    /// * `Ok(AgentRuntimeStatus)` - The agent's status
+    /// * `Err(ApiError::AgentNotFound)` - If agent doesn't exist
+    pub async fn agent_status(&self, agent_id: &AgentId) -> Result<AgentRuntimeStatus, ApiError> {
+        self.get_agent(agent_id).await?;
+
+        let runtime = self.runtimes.lock().await.get(agent_id).cloned();
+        match runtime {
+            Some(runtime) => Ok(runtime.current_status().await),
+            None => Ok(AgentRuntimeStatus::Stopped),
+        }
+    }
+
+    /// Slash commands an agent's session last announced.
+    ///
+    /// # Returns
+    /// * `Ok(Vec)` - The cached commands (empty if none were received yet)
+    /// * `Err(ApiError::AgentNotFound)` - If the agent does not exist
+    pub async fn available_commands(
+        &self,
+        agent_id: &AgentId,
+        session_id: &SessionId,
+    ) -> Result<Vec<serde_json::Value>, ApiError> {
+        self.get_agent(agent_id).await?;
+
+        let runtime = self.runtimes.lock().await.get(agent_id).cloned();
+        Ok(runtime
+            .map(|runtime| runtime.available_commands(session_id))
+            .unwrap_or_default())
+    }
+
+    /// Mode an agent's session was last reported in.
+    ///
+    /// # Returns
+    /// * `Ok(Option)` - The mode ID (`None` if none was reported yet)
+    /// * `Err(ApiError::AgentNotFound)` - If the agent does not exist
+    pub async fn current_mode(
+        &self,
+        agent_id: &AgentId,
+        session_id: &SessionId,
+    ) -> Result<Option<String>, ApiError> {
+        self.get_agent(agent_id).await?;
+
+        let runtime = self.runtimes.lock().await.get(agent_id).cloned();
+        Ok(runtime.and_then(|runtime| runtime.current_mode(session_id)))
+    }
+
+    /// Whether any agent in this registry is running on `plugin_id`.
+    pub async fn has_running_plugin(&self, plugin_id: &str) -> bool {
+        // Snapshot the runtimes so the registry lock isn't held while probing
+        let runtimes: Vec<Arc<AgentRuntime>> = {
+            let runtimes = self.runtimes.lock().await;
+            runtimes.values().cloned().collect()
+        };
+
+        for runtime in runtimes {
+            if runtime.plugin_id() == plugin_id && runtime.is_running().await {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Number of agents in this registry whose adapter is running.
+    pub async fn running_count(&self) -> usize {
+        let runtimes: Vec<Arc<AgentRuntime>> = {
+            let runtimes = self.runtimes.lock().await;
+            runtimes.values().cloned().collect()
+        };
+
+        let mut running = 0;
+        for runtime in runtimes {
+            if runtime.is_running().await {
+                running += 1;
+            }
+        }
+        running
+    }
+
+    /// Get or create an AgentRuntime for the given agent.
+    ///
+    /// This is called during lazy startup to get the runtime handle.
+    /// The runtime is created if it doesn't exist.
+    ///
+    /// # Arguments
+    /// * `workspace_id` - The workspace this agent belongs to
+    /// * `agent_id` - The agent to get runtime for
+    ///
+    /// # Returns
+    /// * `Ok(Arc<AgentRuntime>)` - The agent runtime (existing or newly created)
+    /// * `Err(ApiError::AgentNotFound)` - If agent doesn't exist in registry
+    pub async fn ensure_runtime(
+        &self,
+        workspace_id: WorkspaceId,
+        agent_id: AgentId,
+    ) -> Result<Arc<AgentRuntime>, ApiError> {
+        // Verify agent exists
+        let record = self.get_agent(&agent_id).await?;
+
+        // Check if runtime already exists
+        {
+            let runtimes = self.runtimes.lock().await;
+            if let Some(runtime) = runtimes.get(&agent_id) {
+                return Ok(runtime.clone());
+            }
+        }
+
+        // Create new runtime
+        let runtime = AgentRuntime::new(agent_id.clone(), workspace_id, record.plugin_id);
+        runtime.set_display_name(record.display_name);
+        runtime.set_auto_restart(record.auto_restart);
+        runtime.set_busy_prompt_policy(record.busy_prompt_policy);
+
+        // Insert into runtimes map
+        {
+            let mut runtimes = self.runtimes.lock().await;
+            // Double-check in case another task created it
+            if let Some(existing) = runtimes.get(&agent_id) {
                 return Ok(existing.clone());
             }
+            // Under the lock, so a concurrent limit change can't be missed
+            runtime
+                .set_max_outstanding_prompts(self.max_outstanding_prompts.load(Ordering::SeqCst));
             runtimes.insert(agent_id.clone(), runtime.clone());
             log::debug!(
                 "Created agent runtime: agent={}, total_runtimes={}",
@@ -525,6 +1970,47 @@ impl Default for AgentRegistry {
     }
 }
 
+/// Complete an adapter's environment after the plugin's own variables.
+///
+/// The essential variables come next, then the workspace `.env`, and
+/// nothing overrides a variable already set. So a `.env` can't redirect the
+/// adapter's `PATH` or `HOME`.
+fn add_inherited_env(env: &mut Vec<(String, String)>, workspace_env: Vec<(String, String)>) {
+    for var_name in env_file::ESSENTIAL_ENV_VARS {
+        if env.iter().any(|(k, _)| k == var_name) {
+            continue;
+        }
+        if let Ok(value) = std::env::var(var_name) {
+            log::debug!("Passing env var to adapter: {var_name}");
+            env.push((var_name.to_string(), value));
+        }
+    }
+    for (key, value) in workspace_env {
+        if env.iter().any(|(k, _)| *k == key) {
+            log::debug!("Ignoring workspace .env variable already set: {key}");
+        } else {
+            env.push((key, value));
+        }
+    }
+}
+
+/// Reject MCP server entries the adapter could never launch.
+fn validate_mcp_servers(mcp_servers: &[McpServerConfig]) -> Result<(), ApiError> {
+    for server in mcp_servers {
+        if server.name.trim().is_empty() {
+            return Err(ApiError::InvalidInput {
+                message: "MCP server name cannot be empty".to_string(),
+            });
+        }
+        if server.command.trim().is_empty() {
+            return Err(ApiError::InvalidInput {
+                message: format!("MCP server '{}' has an empty command", server.name),
+            });
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -540,9 +2026,9 @@ mod tests {
         async fn send_prompt(
             &self,
             _session_id: SessionId,
-            _prompt: String,
-        ) -> Result<(), ApiError> {
-            Ok(())
+            _prompt: Vec<PromptContentBlock>,
+        ) -> Result<TurnId, ApiError> {
+            Ok("turn-1".to_string())
         }
 
         async fn cancel_turn(&self, session_id: SessionId) -> Result<(), ApiError> {
@@ -551,6 +2037,47 @@ mod tests {
             Ok(())
         }
 
+        async fn new_session(&self) -> Result<SessionId, ApiError> {
+            Ok("new-session".to_string())
+        }
+
+        async fn shutdown(&self) -> Result<(), ApiError> {
+            Ok(())
+        }
+    }
+
+    /// Connection that rejects prompts for any session other than the
+    /// one it created, mimicking an adapter that restarted.
+    struct RestartedAdapterConnection {
+        known_session: std::sync::Mutex<Option<SessionId>>,
+        prompts: std::sync::Mutex<Vec<SessionId>>,
+    }
+
+    #[async_trait]
+    impl AgentConnection for RestartedAdapterConnection {
+        async fn send_prompt(
+            &self,
+            session_id: SessionId,
+            _prompt: Vec<PromptContentBlock>,
+        ) -> Result<TurnId, ApiError> {
+            self.prompts.lock().unwrap().push(session_id.clone());
+            if self.known_session.lock().unwrap().as_ref() == Some(&session_id) {
+                Ok(format!("turn-{}", self.prompts.lock().unwrap().len()))
+            } else {
+                Err(ApiError::SessionNotFound { session_id })
+            }
+        }
+
+        async fn cancel_turn(&self, _session_id: SessionId) -> Result<(), ApiError> {
+            Ok(())
+        }
+
+        async fn new_session(&self) -> Result<SessionId, ApiError> {
+            let session_id = "fresh-session".to_string();
+            *self.known_session.lock().unwrap() = Some(session_id.clone());
+            Ok(session_id)
+        }
+
         async fn shutdown(&self) -> Result<(), ApiError> {
             Ok(())
         }
@@ -619,6 +2146,48 @@ mod tests {
         assert!(matches!(result, Err(ApiError::InvalidInput { .. })));
     }
 
+    #[test]
+    fn test_validate_display_name_too_long() {
+        assert!(AgentRegistry::validate_display_name(&"a".repeat(128)).is_ok());
+        // Multi-byte characters count once
+        assert!(AgentRegistry::validate_display_name(&"é".repeat(128)).is_ok());
+
+        let result = AgentRegistry::validate_display_name(&"a".repeat(129));
+        match result.unwrap_err() {
+            ApiError::InvalidInput { message } => {
+                assert!(message.contains("128 characters"));
+            }
+            _ => panic!("Expected InvalidInput error"),
+        }
+    }
+
+    #[test]
+    fn test_validate_display_name_control_chars() {
+        for name in ["My\nAgent", "My\tAgent", "Agent\u{1b}[31m", "Agent\u{7f}"] {
+            match AgentRegistry::validate_display_name(name).unwrap_err() {
+                ApiError::InvalidInput { message } => {
+                    assert!(message.contains("control characters"));
+                }
+                _ => panic!("Expected InvalidInput error"),
+            }
+        }
+        assert!(AgentRegistry::validate_display_name("My Agent").is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_create_agent_trims_display_name() {
+        let registry = AgentRegistry::new();
+
+        let record = registry
+            .create_agent("claude-code".to_string(), Some("  My Agent \n".to_string()))
+            .await
+            .unwrap();
+
+        assert_eq!(record.display_name.as_deref(), Some("My Agent"));
+        let stored = registry.get_agent(&record.agent_id).await.unwrap();
+        assert_eq!(stored.display_name.as_deref(), Some("My Agent"));
+    }
+
     #[tokio::test]
     async fn test_create_multiple_agents() {
         let registry = AgentRegistry::new();
@@ -637,12 +2206,192 @@ mod tests {
         assert_ne!(record1.agent_id, record2.agent_id);
     }
 
+    #[tokio::test]
+    async fn test_list_agents_pages_oldest_first() {
+        let record = |agent_id: &str, created_at_ms: f64| AgentRecord {
+            agent_id: agent_id.to_string(),
+            plugin_id: "claude-code".to_string(),
+            display_name: None,
+            session_id: None,
+            mcp_servers: Vec::new(),
+            auto_restart: false,
+            busy_prompt_policy: BusyPromptPolicy::default(),
+            created_at_ms,
+        };
+        let registry = AgentRegistry::with_agents(vec![
+            record("agent-c", 3.0),
+            record("agent-a", 1.0),
+            record("agent-d", 4.0),
+            record("agent-b", 2.0),
+        ]);
+        let ids = |page: Page<AgentRecord>| -> Vec<String> {
+            page.items.into_iter().map(|r| r.agent_id).collect()
+        };
+
+        let all = registry.list_agents(None, None).await;
+        assert_eq!(all.total, 4);
+        assert_eq!(ids(all), vec!["agent-a", "agent-b", "agent-c", "agent-d"]);
+        assert_eq!(
+            ids(registry.list_agents(Some(1), Some(2)).await),
+            vec!["agent-b", "agent-c"]
+        );
+        assert_eq!(
+            ids(registry.list_agents(Some(3), Some(5)).await),
+            vec!["agent-d"]
+        );
+
+        let beyond = registry.list_agents(Some(4), Some(1)).await;
+        assert!(beyond.items.is_empty());
+        assert_eq!(beyond.total, 4);
+    }
+
+    #[tokio::test]
+    async fn test_set_session_id_is_remembered() {
+        let registry = AgentRegistry::new();
+        let record = registry
+            .create_agent("claude-code".to_string(), None)
+            .await
+            .unwrap();
+        assert_eq!(record.session_id, None);
+
+        registry
+            .set_session_id(&record.agent_id, "session-1".to_string())
+            .await
+            .unwrap();
+
+        let stored = registry.get_agent(&record.agent_id).await.unwrap();
+        assert_eq!(stored.session_id.as_deref(), Some("session-1"));
+
+        let missing = registry
+            .set_session_id(&"missing".to_string(), "session-2".to_string())
+            .await;
+        assert!(matches!(missing, Err(ApiError::AgentNotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_agent_status_defaults_to_stopped_without_runtime() {
+        let registry = AgentRegistry::new();
+        let record = registry
+            .create_agent("claude-code".to_string(), None)
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            registry.agent_status(&record.agent_id).await,
+            Ok(AgentRuntimeStatus::Stopped)
+        ));
+
+        let runtime = registry
+            .ensure_runtime("workspace-1".to_string(), record.agent_id.clone())
+            .await
+            .unwrap();
+        *runtime.status.lock().await = AgentRuntimeStatus::Running {
+            session_id: "session-1".to_string(),
+        };
+        assert!(matches!(
+            registry.agent_status(&record.agent_id).await,
+            Ok(AgentRuntimeStatus::Running { session_id }) if session_id == "session-1"
+        ));
+
+        let missing = registry.agent_status(&"missing".to_string()).await;
+        assert!(matches!(missing, Err(ApiError::AgentNotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_delete_agent_shuts_down_runtime() {
+        struct ShutdownFlagConnection {
+            shut_down: Arc<AtomicBool>,
+        }
+
+        #[async_trait]
+        impl AgentConnection for ShutdownFlagConnection {
+            async fn send_prompt(
+                &self,
+                _session_id: SessionId,
+                _prompt: Vec<PromptContentBlock>,
+            ) -> Result<TurnId, ApiError> {
+                Ok("turn-1".to_string())
+            }
+
+            async fn cancel_turn(&self, _session_id: SessionId) -> Result<(), ApiError> {
+                Ok(())
+            }
+
+            async fn new_session(&self) -> Result<SessionId, ApiError> {
+                Ok("unused".to_string())
+            }
+
+            async fn shutdown(&self) -> Result<(), ApiError> {
+                self.shut_down.store(true, Ordering::SeqCst);
+                Ok(())
+            }
+        }
+
+        let registry = AgentRegistry::new();
+        let record = registry
+            .create_agent("claude-code".to_string(), None)
+            .await
+            .unwrap();
+        let runtime = registry
+            .ensure_runtime("workspace-1".to_string(), record.agent_id.clone())
+            .await
+            .unwrap();
+        let shut_down = Arc::new(AtomicBool::new(false));
+        runtime
+            .attach_connection_for_test(
+                "session-1".to_string(),
+                Arc::new(ShutdownFlagConnection {
+                    shut_down: shut_down.clone(),
+                }),
+            )
+            .await;
+
+        registry.delete_agent(&record.agent_id).await.unwrap();
+
+        assert!(shut_down.load(Ordering::SeqCst));
+        assert!(!runtime.is_running().await);
+        assert_eq!(registry.list_agents(None, None).await.total, 0);
+        assert!(registry.runtimes.lock().await.is_empty());
+        assert!(matches!(
+            registry.delete_agent(&record.agent_id).await,
+            Err(ApiError::AgentNotFound { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_set_mcp_servers_rejects_empty_command() {
+        let registry = AgentRegistry::new();
+        let record = registry
+            .create_agent("claude-code".to_string(), None)
+            .await
+            .unwrap();
+        let server = McpServerConfig {
+            name: "filesystem".to_string(),
+            command: "  ".to_string(),
+            args: Vec::new(),
+            env: Vec::new(),
+        };
+
+        let result = registry
+            .set_mcp_servers(&record.agent_id, vec![server])
+            .await;
+
+        assert!(matches!(result, Err(ApiError::InvalidInput { .. })));
+        let stored = registry.get_agent(&record.agent_id).await.unwrap();
+        assert!(stored.mcp_servers.is_empty());
+    }
+
     #[test]
     fn test_agent_record_to_summary() {
         let record = AgentRecord {
             agent_id: "test-agent-id".to_string(),
             plugin_id: "claude-code".to_string(),
             display_name: Some("Test Agent".to_string()),
+            session_id: None,
+            mcp_servers: Vec::new(),
+            auto_restart: false,
+            busy_prompt_policy: BusyPromptPolicy::default(),
+            created_at_ms: 0.0,
         };
 
         let workspace_id = "test-workspace-id".to_string();
@@ -679,14 +2428,9 @@ mod tests {
             canceled_session: canceled_session.clone(),
         });
 
-        {
-            let mut session_guard = runtime.session_id.lock().await;
-            *session_guard = Some("session-123".to_string());
-        }
-        {
-            let mut conn_guard = runtime.connection.lock().await;
-            *conn_guard = Some(connection);
-        }
+        runtime
+            .attach_connection_for_test("session-123".to_string(), connection)
+            .await;
 
         let result = runtime.stop_turn("session-123".to_string()).await;
         assert!(result.is_ok());
@@ -696,31 +2440,891 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_agent_runtime_stop_turn_session_mismatch() {
+    async fn test_agent_runtime_stop_is_idempotent() {
         let runtime = AgentRuntime::new(
             "agent-123".to_string(),
             "workspace-123".to_string(),
             "claude-code".to_string(),
         );
+        runtime
+            .attach_connection_for_test(
+                "session-123".to_string(),
+                Arc::new(MockConnection {
+                    canceled_session: Arc::new(TokioMutex::new(None)),
+                }),
+            )
+            .await;
+        assert!(runtime.is_running().await);
 
-        let canceled_session = Arc::new(TokioMutex::new(None));
-        let connection = Arc::new(MockConnection {
-            canceled_session: canceled_session.clone(),
-        });
+        runtime.stop().await.unwrap();
 
-        {
-            let mut session_guard = runtime.session_id.lock().await;
-            *session_guard = Some("session-123".to_string());
+        assert!(!runtime.is_running().await);
+        assert!(runtime.current_session().await.is_none());
+        assert!(matches!(
+            *runtime.status.lock().await,
+            AgentRuntimeStatus::Stopped
+        ));
+
+        // Stopping again is a no-op
+        runtime.stop().await.unwrap();
+    }
+
+    /// Connection whose shutdown is recorded in a shared log.
+    struct LoggingConnection {
+        log: Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl AgentConnection for LoggingConnection {
+        async fn send_prompt(
+            &self,
+            _session_id: SessionId,
+            _prompt: Vec<PromptContentBlock>,
+        ) -> Result<TurnId, ApiError> {
+            Ok("turn-1".to_string())
         }
-        {
-            let mut conn_guard = runtime.connection.lock().await;
-            *conn_guard = Some(connection);
+
+        async fn cancel_turn(&self, _session_id: SessionId) -> Result<(), ApiError> {
+            Ok(())
         }
 
-        let result = runtime.stop_turn("session-999".to_string()).await;
-        assert!(matches!(result, Err(ApiError::InvalidInput { .. })));
+        async fn new_session(&self) -> Result<SessionId, ApiError> {
+            Ok("new-session".to_string())
+        }
+
+        async fn shutdown(&self) -> Result<(), ApiError> {
+            self.log.lock().unwrap().push("shutdown".to_string());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_restart_shuts_down_before_starting_and_resumes_session() {
+        let runtime = AgentRuntime::new(
+            "agent-123".to_string(),
+            "workspace-123".to_string(),
+            "claude-code".to_string(),
+        );
+        let log = Arc::new(std::sync::Mutex::new(Vec::new()));
+        runtime
+            .attach_connection_for_test(
+                "session-123".to_string(),
+                Arc::new(LoggingConnection { log: log.clone() }),
+            )
+            .await;
+
+        let start_runtime = runtime.clone();
+        let start_log = log.clone();
+        let session_id = runtime
+            .restart_with(Some("session-123".to_string()), |resume| async move {
+                // The old connection is gone by the time the new one starts
+                assert!(!start_runtime.is_running().await);
+                assert!(matches!(
+                    start_runtime.current_status().await,
+                    AgentRuntimeStatus::Stopped
+                ));
+                start_log
+                    .lock()
+                    .unwrap()
+                    .push(format!("start resume={}", resume.as_deref().unwrap_or("-")));
+                start_runtime
+                    .attach_connection_for_test(
+                        "session-123".to_string(),
+                        Arc::new(LoggingConnection { log: start_log }),
+                    )
+                    .await;
+                Ok("session-123".to_string())
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(session_id, "session-123");
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![
+                "shutdown".to_string(),
+                "start resume=session-123".to_string()
+            ]
+        );
+        assert!(runtime.is_running().await);
+    }
+
+    #[tokio::test]
+    async fn test_restart_of_stopped_agent_degrades_to_new_session() {
+        let runtime = AgentRuntime::new(
+            "agent-123".to_string(),
+            "workspace-123".to_string(),
+            "claude-code".to_string(),
+        );
+
+        // The adapter couldn't load the recorded session and made a new one
+        let session_id = runtime
+            .restart_with(Some("lost-session".to_string()), |resume| async move {
+                assert_eq!(resume.as_deref(), Some("lost-session"));
+                Ok("new-session".to_string())
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(session_id, "new-session");
+    }
+
+    #[tokio::test]
+    async fn test_connection_lost_clears_stale_session() {
+        let runtime = AgentRuntime::new(
+            "agent-123".to_string(),
+            "workspace-123".to_string(),
+            "claude-code".to_string(),
+        );
+        runtime
+            .attach_connection_for_test(
+                "session-123".to_string(),
+                Arc::new(MockConnection {
+                    canceled_session: Arc::new(TokioMutex::new(None)),
+                }),
+            )
+            .await;
+
+        // A notice from an older connection leaves the live one alone
+        runtime.handle_connection_lost(7).await;
+        assert_eq!(
+            runtime.current_session().await.as_deref(),
+            Some("session-123")
+        );
+
+        runtime.handle_connection_lost(0).await;
+
+        // ensure_started's fast path no longer sees a session, so it restarts
+        assert!(runtime.current_session().await.is_none());
+        assert!(!runtime.is_running().await);
+        assert!(matches!(
+            *runtime.status.lock().await,
+            AgentRuntimeStatus::Stopped
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_agent_slots_reject_excess_start_until_one_stops() {
+        let slots = AgentSlots::new(1);
+        let first = AgentRuntime::new(
+            "agent-1".to_string(),
+            "workspace-1".to_string(),
+            "claude-code".to_string(),
+        );
+        let second = AgentRuntime::new(
+            "agent-2".to_string(),
+            "workspace-2".to_string(),
+            "claude-code".to_string(),
+        );
+
+        first.acquire_slot(&slots).await.unwrap();
+        first
+            .attach_connection_for_test(
+                "session-1".to_string(),
+                Arc::new(MockConnection {
+                    canceled_session: Arc::new(TokioMutex::new(None)),
+                }),
+            )
+            .await;
+        // Re-acquiring while running doesn't take a second slot
+        first.acquire_slot(&slots).await.unwrap();
+
+        assert!(matches!(
+            second.acquire_slot(&slots).await,
+            Err(ApiError::TooManyAgents { limit: 1 })
+        ));
+
+        first.stop().await.unwrap();
+        assert_eq!(slots.available(), 1);
+        second.acquire_slot(&slots).await.unwrap();
+
+        // A lost connection frees the slot too
+        second
+            .attach_connection_for_test(
+                "session-2".to_string(),
+                Arc::new(MockConnection {
+                    canceled_session: Arc::new(TokioMutex::new(None)),
+                }),
+            )
+            .await;
+        second.handle_connection_lost(0).await;
+        assert_eq!(slots.available(), 1);
+    }
+
+    #[test]
+    fn test_restart_delay_backs_off_exponentially_with_cap() {
+        assert_eq!(restart_delay(1), Duration::from_secs(1));
+        assert_eq!(restart_delay(2), Duration::from_secs(2));
+        assert_eq!(restart_delay(4), Duration::from_secs(8));
+        assert_eq!(restart_delay(10), AUTO_RESTART_MAX_DELAY);
+    }
+
+    #[tokio::test]
+    async fn test_stop_cancels_pending_restart() {
+        let runtime = AgentRuntime::new(
+            "agent-123".to_string(),
+            "workspace-123".to_string(),
+            "claude-code".to_string(),
+        );
+        runtime.set_auto_restart(true);
+        let pending = tokio::spawn(tokio::time::sleep(Duration::from_secs(60)));
+        let abort_handle = pending.abort_handle();
+        *runtime.restart_task.lock().await = Some(pending);
+
+        runtime.stop().await.unwrap();
+
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while !abort_handle.is_finished() {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .expect("pending restart was not aborted");
+        assert!(runtime.restart_task.lock().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_agent_runtime_stop_turn_session_mismatch() {
+        let runtime = AgentRuntime::new(
+            "agent-123".to_string(),
+            "workspace-123".to_string(),
+            "claude-code".to_string(),
+        );
+
+        let canceled_session = Arc::new(TokioMutex::new(None));
+        let connection = Arc::new(MockConnection {
+            canceled_session: canceled_session.clone(),
+        });
+
+        runtime
+            .attach_connection_for_test("session-123".to_string(), connection)
+            .await;
+
+        let result = runtime.stop_turn("session-999".to_string()).await;
+        assert!(matches!(result, Err(ApiError::InvalidInput { .. })));
 
         let canceled = canceled_session.lock().await;
         assert!(canceled.is_none());
     }
+
+    #[tokio::test]
+    async fn test_agent_runtime_send_prompt_recovers_from_session_not_found() {
+        let runtime = AgentRuntime::new(
+            "agent-123".to_string(),
+            "workspace-123".to_string(),
+            "claude-code".to_string(),
+        );
+
+        let connection = Arc::new(RestartedAdapterConnection {
+            known_session: std::sync::Mutex::new(None),
+            prompts: std::sync::Mutex::new(Vec::new()),
+        });
+
+        runtime
+            .attach_connection_for_test("stale-session".to_string(), connection.clone())
+            .await;
+
+        let result = runtime
+            .send_prompt(None, vec![PromptContentBlock::text("hello")])
+            .await;
+        let ack = result.unwrap();
+        assert_eq!(ack.session_id, "fresh-session");
+        assert_eq!(ack.turn_id.as_deref(), Some("turn-2"));
+        assert!(!ack.queued);
+
+        assert_eq!(
+            *connection.prompts.lock().unwrap(),
+            vec!["stale-session".to_string(), "fresh-session".to_string()]
+        );
+        assert_eq!(
+            runtime.current_session().await.as_deref(),
+            Some("fresh-session")
+        );
+        assert!(matches!(
+            &*runtime.status.lock().await,
+            AgentRuntimeStatus::Running { session_id } if session_id == "fresh-session"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_agent_runtime_send_prompt_retries_only_once() {
+        let runtime = AgentRuntime::new(
+            "agent-123".to_string(),
+            "workspace-123".to_string(),
+            "claude-code".to_string(),
+        );
+
+        // Never learns a session, so every prompt is rejected
+        struct ForgetfulConnection {
+            attempts: std::sync::Mutex<usize>,
+        }
+
+        #[async_trait]
+        impl AgentConnection for ForgetfulConnection {
+            async fn send_prompt(
+                &self,
+                session_id: SessionId,
+                _prompt: Vec<PromptContentBlock>,
+            ) -> Result<TurnId, ApiError> {
+                *self.attempts.lock().unwrap() += 1;
+                Err(ApiError::SessionNotFound { session_id })
+            }
+
+            async fn cancel_turn(&self, _session_id: SessionId) -> Result<(), ApiError> {
+                Ok(())
+            }
+
+            async fn new_session(&self) -> Result<SessionId, ApiError> {
+                Ok("fresh-session".to_string())
+            }
+
+            async fn shutdown(&self) -> Result<(), ApiError> {
+                Ok(())
+            }
+        }
+
+        let connection = Arc::new(ForgetfulConnection {
+            attempts: std::sync::Mutex::new(0),
+        });
+
+        runtime
+            .attach_connection_for_test("stale-session".to_string(), connection.clone())
+            .await;
+
+        let result = runtime
+            .send_prompt(None, vec![PromptContentBlock::text("hello")])
+            .await;
+        assert!(matches!(result, Err(ApiError::SessionNotFound { .. })));
+        assert_eq!(*connection.attempts.lock().unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_agent_runtime_send_prompt_broken_pipe_flags_restart() {
+        // Adapter that closed its stdin: every write fails and the
+        // connection reports itself as gone
+        struct ClosedPipeConnection;
+
+        #[async_trait]
+        impl AgentConnection for ClosedPipeConnection {
+            async fn send_prompt(
+                &self,
+                _session_id: SessionId,
+                _prompt: Vec<PromptContentBlock>,
+            ) -> Result<TurnId, ApiError> {
+                Err(ApiError::ProtocolError {
+                    kind: ProtocolErrorKind::ConnectionClosed,
+                    message: "Adapter closed its input; restart required".to_string(),
+                })
+            }
+
+            async fn cancel_turn(&self, _session_id: SessionId) -> Result<(), ApiError> {
+                Ok(())
+            }
+
+            async fn new_session(&self) -> Result<SessionId, ApiError> {
+                Ok("unused".to_string())
+            }
+
+            async fn shutdown(&self) -> Result<(), ApiError> {
+                Ok(())
+            }
+
+            fn is_connected(&self) -> bool {
+                false
+            }
+        }
+
+        let runtime = AgentRuntime::new(
+            "agent-123".to_string(),
+            "workspace-123".to_string(),
+            "claude-code".to_string(),
+        );
+        runtime
+            .attach_connection_for_test("session-1".to_string(), Arc::new(ClosedPipeConnection))
+            .await;
+        *runtime.status.lock().await = AgentRuntimeStatus::Running {
+            session_id: "session-1".to_string(),
+        };
+
+        let result = runtime
+            .send_prompt(None, vec![PromptContentBlock::text("hello")])
+            .await;
+
+        assert!(matches!(result, Err(ApiError::ProtocolError { .. })));
+        // Connection and session are dropped so ensure_started respawns the adapter
+        assert!(!runtime.is_running().await);
+        assert!(runtime.current_session().await.is_none());
+        assert!(matches!(
+            &*runtime.status.lock().await,
+            AgentRuntimeStatus::Stopped
+        ));
+    }
+
+    /// Accepts every prompt, numbering turns and recording what was sent.
+    struct TurnCountingConnection {
+        prompts: std::sync::Mutex<Vec<Vec<PromptContentBlock>>>,
+    }
+
+    #[async_trait]
+    impl AgentConnection for TurnCountingConnection {
+        async fn send_prompt(
+            &self,
+            _session_id: SessionId,
+            prompt: Vec<PromptContentBlock>,
+        ) -> Result<TurnId, ApiError> {
+            let mut prompts = self.prompts.lock().unwrap();
+            prompts.push(prompt);
+            Ok(format!("turn-{}", prompts.len()))
+        }
+
+        async fn cancel_turn(&self, _session_id: SessionId) -> Result<(), ApiError> {
+            Ok(())
+        }
+
+        async fn new_session(&self) -> Result<SessionId, ApiError> {
+            Ok("unused".to_string())
+        }
+
+        async fn shutdown(&self) -> Result<(), ApiError> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_prompt_sent_mid_turn_is_queued_until_turn_completes() {
+        let runtime = AgentRuntime::new(
+            "agent-123".to_string(),
+            "workspace-123".to_string(),
+            "claude-code".to_string(),
+        );
+        let connection = Arc::new(TurnCountingConnection {
+            prompts: std::sync::Mutex::new(Vec::new()),
+        });
+        runtime
+            .attach_connection_for_test("session-1".to_string(), connection.clone())
+            .await;
+
+        let first = runtime
+            .send_prompt(None, vec![PromptContentBlock::text("first")])
+            .await
+            .unwrap();
+        assert_eq!(first.turn_id.as_deref(), Some("turn-1"));
+
+        let second = runtime
+            .send_prompt(None, vec![PromptContentBlock::text("second")])
+            .await
+            .unwrap();
+        assert!(second.queued);
+        assert!(second.turn_id.is_none());
+        assert_eq!(connection.prompts.lock().unwrap().len(), 1);
+
+        // A completion for some other turn doesn't release the queue
+        runtime
+            .handle_turn_complete(0, "session-1".to_string(), Some("turn-9".to_string()))
+            .await;
+        assert_eq!(connection.prompts.lock().unwrap().len(), 1);
+
+        runtime
+            .handle_turn_complete(0, "session-1".to_string(), Some("turn-1".to_string()))
+            .await;
+        assert_eq!(
+            *connection.prompts.lock().unwrap(),
+            vec![
+                vec![PromptContentBlock::text("first")],
+                vec![PromptContentBlock::text("second")]
+            ]
+        );
+        assert_eq!(
+            runtime.sessions.lock().await.states["session-1"].phase,
+            TurnPhase::Active("turn-2".to_string())
+        );
+
+        // With the queue drained, the next completion leaves the agent idle
+        runtime
+            .handle_turn_complete(0, "session-1".to_string(), Some("turn-2".to_string()))
+            .await;
+        assert_eq!(
+            runtime.sessions.lock().await.states["session-1"].phase,
+            TurnPhase::Idle
+        );
+        let third = runtime
+            .send_prompt(None, vec![PromptContentBlock::text("third")])
+            .await
+            .unwrap();
+        assert_eq!(third.turn_id.as_deref(), Some("turn-3"));
+    }
+
+    #[tokio::test]
+    async fn test_prompt_sent_mid_turn_is_rejected_by_policy() {
+        let runtime = AgentRuntime::new(
+            "agent-123".to_string(),
+            "workspace-123".to_string(),
+            "claude-code".to_string(),
+        );
+        runtime.set_busy_prompt_policy(BusyPromptPolicy::Reject);
+        let connection = Arc::new(TurnCountingConnection {
+            prompts: std::sync::Mutex::new(Vec::new()),
+        });
+        runtime
+            .attach_connection_for_test("session-1".to_string(), connection.clone())
+            .await;
+
+        runtime
+            .send_prompt(None, vec![PromptContentBlock::text("first")])
+            .await
+            .unwrap();
+        let result = runtime
+            .send_prompt(None, vec![PromptContentBlock::text("second")])
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(ApiError::TurnInProgress { session_id }) if session_id == "session-1"
+        ));
+        assert_eq!(connection.prompts.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_prompts_beyond_outstanding_cap_are_rejected() {
+        let runtime = AgentRuntime::new(
+            "agent-123".to_string(),
+            "workspace-123".to_string(),
+            "claude-code".to_string(),
+        );
+        runtime.set_max_outstanding_prompts(3);
+        let connection = Arc::new(TurnCountingConnection {
+            prompts: std::sync::Mutex::new(Vec::new()),
+        });
+        runtime
+            .attach_connection_for_test("session-1".to_string(), connection.clone())
+            .await;
+
+        // One running turn and two queued prompts fill the cap
+        let mut results = Vec::new();
+        for i in 0..5 {
+            results.push(
+                runtime
+                    .send_prompt(None, vec![PromptContentBlock::text(format!("prompt {i}"))])
+                    .await,
+            );
+        }
+
+        assert_eq!(
+            results[0].as_ref().unwrap().turn_id.as_deref(),
+            Some("turn-1")
+        );
+        assert!(results[1].as_ref().unwrap().queued);
+        assert!(results[2].as_ref().unwrap().queued);
+        for result in &results[3..] {
+            assert!(matches!(
+                result,
+                Err(ApiError::TooManyPrompts { session_id, limit: 3 }) if session_id == "session-1"
+            ));
+        }
+        assert_eq!(
+            runtime.sessions.lock().await.states["session-1"]
+                .queue
+                .len(),
+            2
+        );
+
+        // Finishing the turn frees a place
+        runtime
+            .handle_turn_complete(0, "session-1".to_string(), Some("turn-1".to_string()))
+            .await;
+        assert!(
+            runtime
+                .send_prompt(None, vec![PromptContentBlock::text("after")])
+                .await
+                .unwrap()
+                .queued
+        );
+    }
+
+    /// Opens numbered sessions and records which session each prompt and
+    /// close went to.
+    struct MultiSessionConnection {
+        opened: std::sync::Mutex<u32>,
+        prompts: std::sync::Mutex<Vec<(SessionId, Vec<PromptContentBlock>)>>,
+        closed: std::sync::Mutex<Vec<SessionId>>,
+    }
+
+    impl MultiSessionConnection {
+        fn new() -> Self {
+            Self {
+                opened: std::sync::Mutex::new(1),
+                prompts: std::sync::Mutex::new(Vec::new()),
+                closed: std::sync::Mutex::new(Vec::new()),
+            }
+        }
+
+        fn sessions_prompted(&self) -> Vec<SessionId> {
+            let prompts = self.prompts.lock().unwrap();
+            prompts
+                .iter()
+                .map(|(session_id, _)| session_id.clone())
+                .collect()
+        }
+    }
+
+    #[async_trait]
+    impl AgentConnection for MultiSessionConnection {
+        async fn send_prompt(
+            &self,
+            session_id: SessionId,
+            prompt: Vec<PromptContentBlock>,
+        ) -> Result<TurnId, ApiError> {
+            let mut prompts = self.prompts.lock().unwrap();
+            prompts.push((session_id, prompt));
+            Ok(format!("turn-{}", prompts.len()))
+        }
+
+        async fn cancel_turn(&self, _session_id: SessionId) -> Result<(), ApiError> {
+            Ok(())
+        }
+
+        async fn new_session(&self) -> Result<SessionId, ApiError> {
+            let mut opened = self.opened.lock().unwrap();
+            *opened += 1;
+            Ok(format!("session-{opened}"))
+        }
+
+        async fn close_session(&self, session_id: SessionId) -> Result<(), ApiError> {
+            self.closed.lock().unwrap().push(session_id);
+            Ok(())
+        }
+
+        async fn shutdown(&self) -> Result<(), ApiError> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_prompt_routes_to_named_or_primary_session() {
+        let runtime = AgentRuntime::new(
+            "agent-123".to_string(),
+            "workspace-123".to_string(),
+            "claude-code".to_string(),
+        );
+        let connection = Arc::new(MultiSessionConnection::new());
+        runtime
+            .attach_connection_for_test("session-1".to_string(), connection.clone())
+            .await;
+        let second = runtime.new_session().await.unwrap();
+
+        let named = runtime
+            .send_prompt(Some(second.clone()), vec![PromptContentBlock::text("a")])
+            .await
+            .unwrap();
+        assert_eq!(named.session_id, "session-2");
+        let primary = runtime
+            .send_prompt(None, vec![PromptContentBlock::text("b")])
+            .await
+            .unwrap();
+        assert_eq!(primary.session_id, "session-1");
+        assert_eq!(
+            connection.sessions_prompted(),
+            vec!["session-2".to_string(), "session-1".to_string()]
+        );
+
+        let unknown = runtime
+            .send_prompt(
+                Some("session-9".to_string()),
+                vec![PromptContentBlock::text("c")],
+            )
+            .await;
+        assert!(matches!(unknown, Err(ApiError::InvalidInput { .. })));
+        assert_eq!(connection.sessions_prompted().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_two_sessions_route_prompts_independently() {
+        let runtime = AgentRuntime::new(
+            "agent-123".to_string(),
+            "workspace-123".to_string(),
+            "claude-code".to_string(),
+        );
+        let connection = Arc::new(MultiSessionConnection::new());
+        runtime
+            .attach_connection_for_test("session-1".to_string(), connection.clone())
+            .await;
+
+        let second = runtime.new_session().await.unwrap();
+        assert_eq!(second, "session-2");
+        // The single-session API keeps using the session the agent started with
+        assert_eq!(
+            runtime.current_session().await.as_deref(),
+            Some("session-1")
+        );
+
+        let on_second = runtime
+            .send_prompt(Some(second.clone()), vec![PromptContentBlock::text("a")])
+            .await
+            .unwrap();
+        assert_eq!(on_second.session_id, "session-2");
+        assert_eq!(on_second.turn_id.as_deref(), Some("turn-1"));
+
+        // A turn on one session doesn't hold up the other
+        let on_first = runtime
+            .send_prompt(None, vec![PromptContentBlock::text("b")])
+            .await
+            .unwrap();
+        assert_eq!(on_first.session_id, "session-1");
+        assert!(!on_first.queued);
+
+        let queued = runtime
+            .send_prompt(Some(second.clone()), vec![PromptContentBlock::text("c")])
+            .await
+            .unwrap();
+        assert!(queued.queued);
+
+        // Finishing session-1's turn leaves session-2's queue alone
+        runtime
+            .handle_turn_complete(0, "session-1".to_string(), Some("turn-2".to_string()))
+            .await;
+        assert_eq!(connection.prompts.lock().unwrap().len(), 2);
+
+        runtime
+            .handle_turn_complete(0, second.clone(), Some("turn-1".to_string()))
+            .await;
+        assert_eq!(
+            connection.sessions_prompted(),
+            vec!["session-2", "session-1", "session-2"]
+        );
+        assert_eq!(
+            connection.prompts.lock().unwrap()[2].1,
+            vec![PromptContentBlock::text("c")]
+        );
+
+        // Stopping a turn is routed by session too
+        assert!(runtime.stop_turn(second).await.is_ok());
+        assert!(matches!(
+            runtime.stop_turn("session-9".to_string()).await,
+            Err(ApiError::InvalidInput { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_close_session_forgets_it_but_keeps_the_primary() {
+        let runtime = AgentRuntime::new(
+            "agent-123".to_string(),
+            "workspace-123".to_string(),
+            "claude-code".to_string(),
+        );
+        let connection = Arc::new(MultiSessionConnection::new());
+        runtime
+            .attach_connection_for_test("session-1".to_string(), connection.clone())
+            .await;
+        let second = runtime.new_session().await.unwrap();
+
+        assert!(matches!(
+            runtime.close_session("session-1".to_string()).await,
+            Err(ApiError::InvalidInput { .. })
+        ));
+
+        runtime.close_session(second.clone()).await.unwrap();
+        assert_eq!(*connection.closed.lock().unwrap(), vec![second.clone()]);
+        assert!(matches!(
+            runtime
+                .send_prompt(Some(second.clone()), vec![PromptContentBlock::text("late")])
+                .await,
+            Err(ApiError::InvalidInput { .. })
+        ));
+        assert!(matches!(
+            runtime.close_session(second).await,
+            Err(ApiError::InvalidInput { .. })
+        ));
+
+        // The primary session still works
+        assert!(runtime
+            .send_prompt(None, vec![PromptContentBlock::text("hello")])
+            .await
+            .is_ok());
+
+        runtime.stop().await.unwrap();
+        assert!(matches!(
+            runtime.new_session().await,
+            Err(ApiError::ProtocolError {
+                kind: ProtocolErrorKind::NotConnected,
+                ..
+            })
+        ));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_startup_times_out_when_adapter_never_completes_handshake() {
+        // `sleep` never reads its stdin, so `initialize` is never answered
+        let command = crate::plugins::manager::PluginCommand {
+            path: PathBuf::from("sleep"),
+            args: vec!["30".to_string()],
+            env: Vec::new(),
+        };
+        let started = std::time::Instant::now();
+        let result = StartupDeadline::after(Duration::from_millis(200))
+            .run(
+                "connecting to the adapter",
+                AcpAgent::connect_with_options(
+                    command,
+                    std::env::temp_dir(),
+                    Arc::new(crate::protocols::acp::NoopHost),
+                    None,
+                    Vec::new(),
+                    AcpConnectOptions::default(),
+                ),
+            )
+            .await;
+
+        match result {
+            Err(ApiError::ProtocolError {
+                kind: ProtocolErrorKind::HandshakeTimeout,
+                message,
+            }) => assert_eq!(
+                message,
+                "Agent startup timed out after 200ms while connecting to the adapter"
+            ),
+            Err(other) => panic!("unexpected error: {other}"),
+            Ok(_) => panic!("startup should time out"),
+        }
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_workspace_env_cannot_override_adapter_essentials() {
+        let parent_path = std::env::var("PATH").unwrap();
+        let mut env = vec![("FOO".to_string(), "plugin".to_string())];
+        let workspace_env = vec![
+            ("PATH".to_string(), "/tmp/evil".to_string()),
+            ("FOO".to_string(), "dotenv".to_string()),
+            ("BAR".to_string(), "dotenv".to_string()),
+        ];
+
+        add_inherited_env(&mut env, workspace_env);
+
+        let value = |key: &str| {
+            let values: Vec<_> = env.iter().filter(|(k, _)| k == key).collect();
+            assert_eq!(values.len(), 1, "{key} set once");
+            values[0].1.clone()
+        };
+        assert_eq!(value("PATH"), parent_path);
+        assert_eq!(value("FOO"), "plugin");
+        assert_eq!(value("BAR"), "dotenv");
+    }
+
+    #[test]
+    fn test_keepalive_preference_reaches_connect_options() {
+        let keepalive = AdapterKeepalive::default();
+        let pids = Arc::new(AdapterPids::new());
+        assert_eq!(connect_options(&keepalive, pids.clone()).keepalive, None);
+
+        let settings = AcpKeepalive {
+            interval: Duration::from_secs(30),
+            timeout: DEFAULT_KEEPALIVE_TIMEOUT,
+        };
+        keepalive.set(Some(settings));
+
+        let options = connect_options(&keepalive, pids);
+        assert_eq!(options.keepalive, Some(settings));
+        assert!(options.adapter_pids.is_some());
+    }
 }
@@ -0,0 +1,84 @@
+//! Turn-scoped cancellation for long-running host operations.
+//!
+//! `terminal/run` is cancelled by killing the process; file operations have no
+//! process to kill, so they poll a [`CancellationToken`] between chunks instead.
+//! Tokens are handed out per session by [`TurnCancellations`] and tripped when
+//! the user stops the turn.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::api::types::SessionId;
+
+/// Cheap, clonable cancellation flag.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Create a token that is not cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mark the token (and all its clones) as cancelled.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    /// Whether `cancel` has been called on this token or any clone.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// Per-session registry of tokens for the current turn.
+#[derive(Debug, Default)]
+pub struct TurnCancellations {
+    tokens: Mutex<HashMap<SessionId, CancellationToken>>,
+}
+
+impl TurnCancellations {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Token for the session's current turn (created on first use).
+    pub fn token(&self, session_id: &str) -> CancellationToken {
+        let mut tokens = self.tokens.lock().unwrap_or_else(|e| e.into_inner());
+        tokens.entry(session_id.to_string()).or_default().clone()
+    }
+
+    /// Cancel every operation holding the session's current token.
+    ///
+    /// The token is retired, so operations started by the next turn get a
+    /// fresh one.
+    pub fn cancel(&self, session_id: &str) {
+        let token = {
+            let mut tokens = self.tokens.lock().unwrap_or_else(|e| e.into_inner());
+            tokens.remove(session_id)
+        };
+        if let Some(token) = token {
+            token.cancel();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancel_trips_current_token_and_issues_fresh_one() {
+        let registry = TurnCancellations::new();
+        let first = registry.token("session-1");
+        let other_session = registry.token("session-2");
+
+        registry.cancel("session-1");
+
+        assert!(first.is_cancelled());
+        assert!(!other_session.is_cancelled());
+        assert!(!registry.token("session-1").is_cancelled());
+    }
+}
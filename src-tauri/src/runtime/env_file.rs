@@ -0,0 +1,320 @@
+//! `.env` parsing for workspace-scoped adapter environment variables.
+//!
+//! Supports the common dotenv subset: `KEY=value`, optional `export ` prefix,
+//! single/double-quoted values, `#` comments, and blank lines. Lines that
+//! can't be parsed are skipped and reported as warnings rather than failing
+//! the whole file.
+//!
+//! When the user opts in, the variables are passed to the workspace's
+//! adapters and terminal commands. Values are never logged.
+
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::api::types::EnvFileWarning;
+
+/// File name looked up in the workspace root.
+pub const ENV_FILE_NAME: &str = ".env";
+
+/// Environment variables passed on from the app even when it was launched
+/// from Finder/GUI and the adapter wouldn't inherit them. A workspace `.env`
+/// never overrides them.
+pub const ESSENTIAL_ENV_VARS: [&str; 11] = [
+    // System paths (Claude Code needs these to find config files)
+    "HOME",
+    "USER",
+    "PATH",
+    "SHELL",
+    "TMPDIR",
+    // XDG specification paths (Linux/macOS config file locations)
+    "XDG_CONFIG_HOME",
+    "XDG_DATA_HOME",
+    "XDG_CACHE_HOME",
+    // Anthropic-specific variables
+    "ANTHROPIC_API_KEY",
+    "ANTHROPIC_BASE_URL",
+    "ANTHROPIC_AUTH_TOKEN",
+];
+
+/// Whether `.env` variables are passed on (the `workspaceEnvFile` preference)
+static ENV_FILE_ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Turn passing workspace `.env` variables on or off (driven by preferences).
+pub fn set_env_file_enabled(enabled: bool) {
+    ENV_FILE_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Variables from `<root>/.env` to set for adapters and terminal commands.
+///
+/// Empty while the preference is off or if the file is missing. The file is
+/// read on every call, so edits apply to the next spawned process.
+pub async fn load_workspace_env(root: &Path) -> Vec<(String, String)> {
+    if !ENV_FILE_ENABLED.load(Ordering::Relaxed) {
+        return Vec::new();
+    }
+    read_env_file(root).await
+}
+
+/// Drop `.env` variables that would replace an essential variable the app
+/// has, so a workspace can't redirect `PATH` or `HOME` for processes that
+/// inherit the app's environment.
+pub fn without_essential_overrides(vars: Vec<(String, String)>) -> Vec<(String, String)> {
+    vars.into_iter()
+        .filter(|(key, _)| {
+            let protected =
+                ESSENTIAL_ENV_VARS.contains(&key.as_str()) && std::env::var_os(key).is_some();
+            if protected {
+                log::debug!("Ignoring workspace .env variable already set: {key}");
+            }
+            !protected
+        })
+        .collect()
+}
+
+/// Parse `<root>/.env`, logging (by line number only) what had to be skipped.
+async fn read_env_file(root: &Path) -> Vec<(String, String)> {
+    let env_path = root.join(ENV_FILE_NAME);
+    let content = match tokio::fs::read_to_string(&env_path).await {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Vec::new(),
+        Err(e) => {
+            log::warn!("Failed to read {}: {e}", env_path.display());
+            return Vec::new();
+        }
+    };
+
+    let parsed = parse_env_file(&content);
+    for warning in &parsed.warnings {
+        log::warn!(
+            "{} line {}: {}",
+            env_path.display(),
+            warning.line,
+            warning.message
+        );
+    }
+    parsed.vars
+}
+
+/// Result of parsing a `.env` file.
+#[derive(Debug, Default)]
+pub struct ParsedEnvFile {
+    /// Variables in file order (later duplicates replace earlier ones)
+    pub vars: Vec<(String, String)>,
+    /// Problems found while parsing, with 1-based line numbers
+    pub warnings: Vec<EnvFileWarning>,
+}
+
+impl ParsedEnvFile {
+    /// Variable names only (for previews that must not expose values).
+    pub fn keys(&self) -> Vec<String> {
+        self.vars.iter().map(|(key, _)| key.clone()).collect()
+    }
+}
+
+/// Parse the contents of a `.env` file.
+pub fn parse_env_file(content: &str) -> ParsedEnvFile {
+    let mut parsed = ParsedEnvFile::default();
+
+    for (index, raw_line) in content.lines().enumerate() {
+        let line_number = (index + 1) as u32;
+        let line = raw_line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let line = line.strip_prefix("export ").unwrap_or(line).trim_start();
+
+        let Some((key, value)) = line.split_once('=') else {
+            parsed.warnings.push(EnvFileWarning {
+                line: line_number,
+                message: "Expected KEY=value".to_string(),
+            });
+            continue;
+        };
+
+        let key = key.trim();
+        if !is_valid_key(key) {
+            parsed.warnings.push(EnvFileWarning {
+                line: line_number,
+                message: format!("Invalid variable name: '{key}'"),
+            });
+            continue;
+        }
+
+        let value = match parse_value(value.trim()) {
+            Ok(value) => value,
+            Err(message) => {
+                parsed.warnings.push(EnvFileWarning {
+                    line: line_number,
+                    message,
+                });
+                continue;
+            }
+        };
+
+        if let Some(existing) = parsed.vars.iter_mut().find(|(k, _)| k == key) {
+            parsed.warnings.push(EnvFileWarning {
+                line: line_number,
+                message: format!("Duplicate variable '{key}' overrides an earlier value"),
+            });
+            existing.1 = value;
+        } else {
+            parsed.vars.push((key.to_string(), value));
+        }
+    }
+
+    parsed
+}
+
+/// Variable names must look like shell identifiers.
+fn is_valid_key(key: &str) -> bool {
+    let mut chars = key.chars();
+    match chars.next() {
+        Some(first) if first.is_ascii_alphabetic() || first == '_' => {}
+        _ => return false,
+    }
+    chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Unquote a value, stripping trailing comments from unquoted values.
+///
+/// Single-quoted values are taken literally; double-quoted values accept
+/// the escapes `\n`, `\t`, `\"` and `\\`.
+fn parse_value(value: &str) -> Result<String, String> {
+    if let Some(rest) = value.strip_prefix('\'') {
+        let end = rest
+            .find('\'')
+            .ok_or_else(|| "Unterminated ' quote".to_string())?;
+        return Ok(rest[..end].to_string());
+    }
+    if let Some(rest) = value.strip_prefix('"') {
+        let mut unquoted = String::new();
+        let mut chars = rest.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => return Ok(unquoted),
+                '\\' => match chars.next() {
+                    Some('n') => unquoted.push('\n'),
+                    Some('t') => unquoted.push('\t'),
+                    Some(other @ ('"' | '\\')) => unquoted.push(other),
+                    Some(other) => {
+                        unquoted.push('\\');
+                        unquoted.push(other);
+                    }
+                    None => break,
+                },
+                c => unquoted.push(c),
+            }
+        }
+        return Err("Unterminated \" quote".to_string());
+    }
+
+    let unquoted = match value.find(" #") {
+        Some(index) => &value[..index],
+        None => value,
+    };
+    Ok(unquoted.trim_end().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_env_file_comments_blank_and_malformed_lines() {
+        let content =
+            "# API credentials\n\nAPI_KEY=secret\nnot a variable\nexport REGION=\"us-east-1\"\n";
+
+        let parsed = parse_env_file(content);
+
+        assert_eq!(parsed.keys(), vec!["API_KEY", "REGION"]);
+        assert_eq!(parsed.vars[1].1, "us-east-1");
+        assert_eq!(parsed.warnings.len(), 1);
+        assert_eq!(parsed.warnings[0].line, 4);
+    }
+
+    #[test]
+    fn test_parse_env_file_values() {
+        let parsed =
+            parse_env_file("A=plain # trailing comment\nB='single # kept'\nC=\nD=x=y\nB=again\n");
+
+        assert_eq!(
+            parsed.vars,
+            vec![
+                ("A".to_string(), "plain".to_string()),
+                ("B".to_string(), "again".to_string()),
+                ("C".to_string(), String::new()),
+                ("D".to_string(), "x=y".to_string()),
+            ]
+        );
+        // Duplicate B is reported
+        assert_eq!(parsed.warnings.len(), 1);
+        assert_eq!(parsed.warnings[0].line, 5);
+    }
+
+    #[test]
+    fn test_parse_env_file_quoting_and_spacing_edge_cases() {
+        let content = concat!(
+            "export   SPACED = \"a b\"  # comment\r\n",
+            "ESCAPED=\"say \\\"hi\\\"\\nnext\\\\path\"\n",
+            "LITERAL='no \\n escapes'\n",
+            "HASH=a#b\n",
+            "export=1\n",
+            "  # indented comment\n",
+        );
+
+        let parsed = parse_env_file(content);
+
+        assert_eq!(
+            parsed.vars,
+            vec![
+                ("SPACED".to_string(), "a b".to_string()),
+                ("ESCAPED".to_string(), "say \"hi\"\nnext\\path".to_string()),
+                ("LITERAL".to_string(), "no \\n escapes".to_string()),
+                ("HASH".to_string(), "a#b".to_string()),
+                ("export".to_string(), "1".to_string()),
+            ]
+        );
+        assert!(parsed.warnings.is_empty(), "{:?}", parsed.warnings);
+    }
+
+    #[tokio::test]
+    async fn test_read_env_file_from_workspace_root() {
+        let root = std::env::temp_dir().join(format!("env_file_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        assert!(read_env_file(&root).await.is_empty());
+
+        std::fs::write(root.join(ENV_FILE_NAME), "TOKEN=abc\nbroken line\n").unwrap();
+        assert_eq!(
+            read_env_file(&root).await,
+            vec![("TOKEN".to_string(), "abc".to_string())]
+        );
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_essential_variables_are_not_overridden() {
+        assert!(std::env::var_os("PATH").is_some());
+        let vars = without_essential_overrides(vec![
+            ("PATH".to_string(), "/tmp/evil".to_string()),
+            ("API_URL".to_string(), "http://localhost".to_string()),
+        ]);
+        assert_eq!(
+            vars,
+            vec![("API_URL".to_string(), "http://localhost".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_parse_env_file_rejects_bad_keys_and_quotes() {
+        let parsed = parse_env_file("1BAD=x\nGOOD-NAME=y\nOPEN=\"unterminated\n");
+
+        assert!(parsed.vars.is_empty());
+        assert_eq!(
+            parsed.warnings.iter().map(|w| w.line).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+    }
+}
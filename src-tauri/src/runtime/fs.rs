@@ -1,32 +1,183 @@
 //! FsManager - reads files scoped to a workspace root.
 //!
-//! US-10: Provides read_text_file with workspace boundary validation.
+//! US-10: Provides read_text_file with workspace boundary validation; binary
+//! files can be read as base64 instead.
+//! Reads and writes are chunked so a cancelled turn aborts them promptly.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::UNIX_EPOCH;
 
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine as _;
 use tokio::fs;
-use tokio::io::AsyncWriteExt;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use uuid::Uuid;
 
-use crate::api::types::ApiError;
-use crate::runtime::path::{resolve_path_in_workspace, resolve_write_target_in_workspace};
+use crate::api::types::{ApiError, DirEntryInfo, FileMetadata};
+use crate::runtime::cancellation::CancellationToken;
+use crate::runtime::path::{
+    resolve_path_in_workspace, resolve_write_target_in_workspace, workspace_relative_path,
+};
+use crate::utils::blocking::BlockingFsLimiter;
 
-const MAX_READ_BYTES: u64 = 1024 * 1024;
+/// Default cap on the size of a file an agent may read
+pub const DEFAULT_MAX_READ_BYTES: u64 = 1024 * 1024;
+/// Granularity of cancellation checks for reads and writes
+const IO_CHUNK_BYTES: usize = 64 * 1024;
+/// Name prefix of the temp files `write_text_file` renames into place
+const TEMP_WRITE_PREFIX: &str = ".tmp_write_";
+
+/// File content as returned to an agent: UTF-8 text, or base64 for binary data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileContent {
+    Text(String),
+    Base64(String),
+}
+
+/// Where a read or write took place, so it can be cited precisely.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedFilePath {
+    /// Relative to the workspace root
+    pub relative: String,
+    /// Absolute location on disk, with the root and parents canonicalized
+    pub absolute: PathBuf,
+}
+
+impl ResolvedFilePath {
+    fn new(workspace_root: &Path, resolved: PathBuf) -> Result<Self, ApiError> {
+        Ok(Self {
+            relative: workspace_relative_path(workspace_root, &resolved)?,
+            absolute: resolved,
+        })
+    }
+}
+
+/// File content together with the file it was read from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileRead<T> {
+    pub content: T,
+    pub path: ResolvedFilePath,
+}
+
+/// Outcome of a write or append.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileWrite {
+    pub bytes_written: u64,
+    pub path: ResolvedFilePath,
+}
 
 /// Per-workspace file system manager.
 pub struct FsManager {
     workspace_root: PathBuf,
+    /// Largest file an agent may read
+    max_read_bytes: AtomicU64,
+    /// Bounds concurrent `tokio::fs` work (shared with plugin installs)
+    blocking_fs: BlockingFsLimiter,
 }
 
 impl FsManager {
     /// Create a new FsManager scoped to a workspace root.
-    pub fn new(workspace_root: PathBuf) -> Self {
-        Self { workspace_root }
+    pub fn new(workspace_root: PathBuf, max_read_bytes: u64) -> Self {
+        Self {
+            workspace_root,
+            max_read_bytes: AtomicU64::new(max_read_bytes),
+            blocking_fs: BlockingFsLimiter::shared(),
+        }
+    }
+
+    /// Change the largest file an agent may read.
+    pub fn set_max_read_bytes(&self, max_read_bytes: u64) {
+        self.max_read_bytes.store(max_read_bytes, Ordering::Relaxed);
+    }
+
+    /// Resolve a read target and return it relative to the workspace root.
+    ///
+    /// Fails (before any prompt is shown) if the path escapes the workspace.
+    pub fn resolve_read_path(&self, path: &str) -> Result<String, ApiError> {
+        let resolved = resolve_path_in_workspace(&self.workspace_root, path)?;
+        workspace_relative_path(&self.workspace_root, &resolved)
+    }
+
+    /// Resolve a write target and return it relative to the workspace root.
+    ///
+    /// Neither the file nor its parent directories need to exist yet;
+    /// nothing is created until the write itself.
+    pub fn resolve_write_path(&self, path: &str) -> Result<String, ApiError> {
+        let resolved = resolve_write_target_in_workspace(&self.workspace_root, path, false)?;
+        workspace_relative_path(&self.workspace_root, &resolved)
     }
 
     /// Read a text file within the workspace boundary.
-    pub async fn read_text_file(&self, path: String) -> Result<String, ApiError> {
-        let resolved = resolve_path_in_workspace(&self.workspace_root, &path)?;
+    ///
+    /// With `line` (1-based) and/or `limit`, only that window of lines is
+    /// returned; a start past the end of the file yields empty content.
+    /// Aborts with `ApiError::Cancelled` if `cancel` trips between chunks.
+    pub async fn read_text_file(
+        &self,
+        path: String,
+        line: Option<u32>,
+        limit: Option<u32>,
+        cancel: &CancellationToken,
+    ) -> Result<FileRead<String>, ApiError> {
+        let (bytes, resolved) = self.read_bytes(&path, cancel).await?;
+
+        let content = String::from_utf8(bytes).map_err(|e| ApiError::IoError {
+            message: format!("Failed to read file '{path}': {e}"),
+        })?;
+
+        Ok(FileRead {
+            content: select_lines(content, line, limit),
+            path: resolved,
+        })
+    }
+
+    /// Read any file within the workspace boundary as base64.
+    ///
+    /// Subject to the same size limit as text reads.
+    pub async fn read_file_base64(
+        &self,
+        path: String,
+        cancel: &CancellationToken,
+    ) -> Result<FileRead<String>, ApiError> {
+        let (bytes, resolved) = self.read_bytes(&path, cancel).await?;
+        Ok(FileRead {
+            content: BASE64.encode(bytes),
+            path: resolved,
+        })
+    }
+
+    /// Read a file as text if it is valid UTF-8, otherwise as base64.
+    ///
+    /// `line` and `limit` apply only when the file is returned as text.
+    pub async fn read_file_auto(
+        &self,
+        path: String,
+        line: Option<u32>,
+        limit: Option<u32>,
+        cancel: &CancellationToken,
+    ) -> Result<FileRead<FileContent>, ApiError> {
+        let (bytes, resolved) = self.read_bytes(&path, cancel).await?;
+
+        let content = match String::from_utf8(bytes) {
+            Ok(text) => FileContent::Text(select_lines(text, line, limit)),
+            Err(e) => FileContent::Base64(BASE64.encode(e.into_bytes())),
+        };
+        Ok(FileRead {
+            content,
+            path: resolved,
+        })
+    }
+
+    /// Read a file's raw bytes, enforcing the workspace boundary and size
+    /// limit, along with where the file was resolved to.
+    async fn read_bytes(
+        &self,
+        path: &str,
+        cancel: &CancellationToken,
+    ) -> Result<(Vec<u8>, ResolvedFilePath), ApiError> {
+        let resolved = resolve_path_in_workspace(&self.workspace_root, path)?;
+        let _permit = self.blocking_fs.acquire().await;
 
         let metadata = fs::metadata(&resolved)
             .await
@@ -40,91 +191,387 @@ impl FsManager {
             });
         }
 
-        if metadata.len() > MAX_READ_BYTES {
+        let max_read_bytes = self.max_read_bytes.load(Ordering::Relaxed);
+        if metadata.len() > max_read_bytes {
             return Err(ApiError::InvalidInput {
-                message: format!("File too large to read: {path} (max {MAX_READ_BYTES} bytes)"),
+                message: format!("File too large to read: {path} (max {max_read_bytes} bytes)"),
             });
         }
 
-        fs::read_to_string(&resolved)
+        let mut file = fs::File::open(&resolved)
             .await
             .map_err(|e| ApiError::IoError {
                 message: format!("Failed to read file '{path}': {e}"),
-            })
+            })?;
+
+        let mut bytes = Vec::with_capacity(metadata.len() as usize);
+        let mut chunk = vec![0u8; IO_CHUNK_BYTES];
+        loop {
+            if cancel.is_cancelled() {
+                return Err(cancelled(path));
+            }
+            let read = file.read(&mut chunk).await.map_err(|e| ApiError::IoError {
+                message: format!("Failed to read file '{path}': {e}"),
+            })?;
+            if read == 0 {
+                break;
+            }
+            bytes.extend_from_slice(&chunk[..read]);
+        }
+
+        Ok((
+            bytes,
+            ResolvedFilePath::new(&self.workspace_root, resolved)?,
+        ))
     }
 
     /// Write a text file within the workspace boundary.
-    pub async fn write_text_file(&self, path: String, content: String) -> Result<u64, ApiError> {
-        let resolved = resolve_write_target_in_workspace(&self.workspace_root, &path)?;
+    ///
+    /// Missing parent directories are created. Content goes to a temp file
+    /// that is renamed into place; if `cancel` trips between chunks (or any
+    /// write fails) the temp file is removed.
+    pub async fn write_text_file(
+        &self,
+        path: String,
+        content: String,
+        cancel: &CancellationToken,
+    ) -> Result<FileWrite, ApiError> {
+        let resolved = resolve_write_target_in_workspace(&self.workspace_root, &path, true)?;
+        let _permit = self.blocking_fs.acquire().await;
 
-        let parent = resolved.parent().ok_or_else(|| ApiError::InvalidInput {
-            message: format!("Path must include a parent directory: {path}"),
-        })?;
+        let parent = check_write_target(&resolved, &path).await?;
+        let temp_path = parent.join(format!("{TEMP_WRITE_PREFIX}{}", Uuid::new_v4()));
+        let mut temp_file = fs::OpenOptions::new()
+            .create_new(true)
+            .write(true)
+            .open(&temp_path)
+            .await
+            .map_err(|e| ApiError::IoError {
+                message: format!("Failed to create temp file for '{path}': {e}"),
+            })?;
 
-        let parent_metadata = fs::metadata(&parent).await.map_err(|e| ApiError::IoError {
-            message: format!("Failed to read metadata for '{path}': {e}"),
-        })?;
+        let written = write_chunks(&mut temp_file, &content, &path, cancel).await;
+        drop(temp_file);
+        if let Err(e) = written {
+            let _ = fs::remove_file(&temp_path).await;
+            return Err(e);
+        }
 
-        if !parent_metadata.is_dir() {
-            return Err(ApiError::InvalidInput {
-                message: format!("Parent is not a directory: {path}"),
-            });
+        if let Err(e) = replace_file(&temp_path, &resolved).await {
+            let _ = fs::remove_file(&temp_path).await;
+            return Err(e);
         }
 
-        match fs::symlink_metadata(&resolved).await {
-            Ok(metadata) => {
-                if metadata.file_type().is_symlink() {
-                    return Err(ApiError::InvalidInput {
-                        message: format!("Path is a symlink: {path}"),
-                    });
-                }
-                if metadata.is_dir() {
-                    return Err(ApiError::InvalidInput {
-                        message: format!("Path is a directory: {path}"),
-                    });
-                }
+        Ok(FileWrite {
+            bytes_written: content.len() as u64,
+            path: ResolvedFilePath::new(&self.workspace_root, resolved)?,
+        })
+    }
+
+    /// Append text to a file within the workspace boundary, creating it (and
+    /// its parent directories) if missing.
+    ///
+    /// Unlike `write_text_file` this is not atomic: a failure part-way can
+    /// leave a partial append behind. The data is flushed and synced before
+    /// returning. Symlinks and directories are rejected the same way.
+    pub async fn append_text_file(
+        &self,
+        path: String,
+        content: String,
+    ) -> Result<FileWrite, ApiError> {
+        let resolved = resolve_write_target_in_workspace(&self.workspace_root, &path, true)?;
+        let _permit = self.blocking_fs.acquire().await;
+
+        check_write_target(&resolved, &path).await?;
+
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&resolved)
+            .await
+            .map_err(|e| ApiError::IoError {
+                message: format!("Failed to open '{path}' for append: {e}"),
+            })?;
+
+        file.write_all(content.as_bytes())
+            .await
+            .map_err(|e| ApiError::IoError {
+                message: format!("Failed to append to '{path}': {e}"),
+            })?;
+        file.flush().await.map_err(|e| ApiError::IoError {
+            message: format!("Failed to flush '{path}': {e}"),
+        })?;
+        file.sync_all().await.map_err(|e| ApiError::IoError {
+            message: format!("Failed to sync '{path}': {e}"),
+        })?;
+
+        Ok(FileWrite {
+            bytes_written: content.len() as u64,
+            path: ResolvedFilePath::new(&self.workspace_root, resolved)?,
+        })
+    }
+
+    /// Delete a file within the workspace boundary.
+    ///
+    /// Directories are only removed (with their contents) when `recursive`
+    /// is set. Symlinks and targets outside the workspace are rejected.
+    pub async fn delete_file(&self, path: String, recursive: bool) -> Result<(), ApiError> {
+        let resolved = resolve_write_target_in_workspace(&self.workspace_root, &path, false)?;
+        let _permit = self.blocking_fs.acquire().await;
+
+        let is_dir = existing_target_is_dir(&resolved, &path, recursive).await?;
+        let removed = if is_dir {
+            fs::remove_dir_all(&resolved).await
+        } else {
+            fs::remove_file(&resolved).await
+        };
+        removed.map_err(|e| ApiError::IoError {
+            message: format!("Failed to delete '{path}': {e}"),
+        })
+    }
+
+    /// Rename (move) a file within the workspace boundary.
+    ///
+    /// Both ends must stay inside the workspace and the destination must not
+    /// exist yet. Directories are only moved when `recursive` is set.
+    pub async fn rename(&self, from: String, to: String, recursive: bool) -> Result<(), ApiError> {
+        let resolved_from = resolve_write_target_in_workspace(&self.workspace_root, &from, false)?;
+        let resolved_to = resolve_write_target_in_workspace(&self.workspace_root, &to, false)?;
+        let _permit = self.blocking_fs.acquire().await;
+
+        existing_target_is_dir(&resolved_from, &from, recursive).await?;
+
+        match fs::symlink_metadata(&resolved_to).await {
+            Ok(_) => {
+                return Err(ApiError::InvalidInput {
+                    message: format!("Destination already exists: {to}"),
+                });
             }
             Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
             Err(e) => {
                 return Err(ApiError::IoError {
-                    message: format!("Failed to read metadata for '{path}': {e}"),
+                    message: format!("Failed to read metadata for '{to}': {e}"),
                 });
             }
         }
 
-        let temp_path = parent.join(format!(".tmp_write_{}", Uuid::new_v4()));
-        let mut temp_file = fs::OpenOptions::new()
-            .create_new(true)
-            .write(true)
-            .open(&temp_path)
+        fs::rename(&resolved_from, &resolved_to)
             .await
             .map_err(|e| ApiError::IoError {
-                message: format!("Failed to create temp file for '{path}': {e}"),
+                message: format!("Failed to rename '{from}' to '{to}': {e}"),
+            })
+    }
+
+    /// List a directory within the workspace boundary.
+    ///
+    /// An empty path lists the workspace root. Directories come first, then
+    /// files, each sorted by name. Symlinks that point outside the workspace
+    /// (or nowhere) are left out.
+    pub async fn list_dir(&self, path: String) -> Result<Vec<DirEntryInfo>, ApiError> {
+        let path = if path.trim().is_empty() {
+            ".".to_string()
+        } else {
+            path
+        };
+        let resolved = resolve_path_in_workspace(&self.workspace_root, &path)?;
+        let root = self
+            .workspace_root
+            .canonicalize()
+            .map_err(|e| ApiError::IoError {
+                message: format!("Failed to canonicalize workspace root: {e}"),
+            })?;
+        let _permit = self.blocking_fs.acquire().await;
+
+        let metadata = fs::metadata(&resolved)
+            .await
+            .map_err(|e| ApiError::IoError {
+                message: format!("Failed to read metadata for '{path}': {e}"),
             })?;
+        if !metadata.is_dir() {
+            return Err(ApiError::PathNotDirectory { path });
+        }
 
-        temp_file
-            .write_all(content.as_bytes())
+        let mut reader = fs::read_dir(&resolved)
             .await
             .map_err(|e| ApiError::IoError {
-                message: format!("Failed to write temp file for '{path}': {e}"),
+                message: format!("Failed to list directory '{path}': {e}"),
             })?;
 
-        temp_file.flush().await.map_err(|e| ApiError::IoError {
-            message: format!("Failed to flush temp file for '{path}': {e}"),
-        })?;
+        let mut entries = Vec::new();
+        while let Some(entry) = reader.next_entry().await.map_err(|e| ApiError::IoError {
+            message: format!("Failed to list directory '{path}': {e}"),
+        })? {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let file_type = entry.file_type().await.map_err(|e| ApiError::IoError {
+                message: format!("Failed to read '{name}' in '{path}': {e}"),
+            })?;
 
-        temp_file.sync_all().await.map_err(|e| ApiError::IoError {
-            message: format!("Failed to sync temp file for '{path}': {e}"),
-        })?;
+            let metadata = if file_type.is_symlink() {
+                match fs::canonicalize(entry.path()).await {
+                    Ok(target) if target.starts_with(&root) => fs::metadata(&target).await,
+                    _ => {
+                        log::debug!("Skipping symlink outside workspace: {path}/{name}");
+                        continue;
+                    }
+                }
+            } else {
+                entry.metadata().await
+            };
+            let metadata = metadata.map_err(|e| ApiError::IoError {
+                message: format!("Failed to read metadata for '{name}' in '{path}': {e}"),
+            })?;
 
-        drop(temp_file);
+            entries.push(DirEntryInfo {
+                name,
+                is_dir: metadata.is_dir(),
+                size: if metadata.is_dir() {
+                    0.0
+                } else {
+                    metadata.len() as f64
+                },
+            });
+        }
 
-        if let Err(e) = replace_file(&temp_path, &resolved).await {
-            let _ = fs::remove_file(&temp_path).await;
-            return Err(e);
+        entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
+        Ok(entries)
+    }
+
+    /// Size, modification time and type of a path inside the workspace.
+    ///
+    /// The type comes from the entry itself, so a symlink is reported as
+    /// one; its size and time are those of its target, which (like any
+    /// path) must stay inside the workspace.
+    pub async fn metadata(&self, path: String) -> Result<FileMetadata, ApiError> {
+        let resolved = resolve_path_in_workspace(&self.workspace_root, &path)?;
+        let _permit = self.blocking_fs.acquire().await;
+
+        let entry = if Path::new(&path).is_absolute() {
+            PathBuf::from(&path)
+        } else {
+            self.workspace_root.join(&path)
+        };
+        let entry_metadata = fs::symlink_metadata(&entry)
+            .await
+            .map_err(|e| ApiError::IoError {
+                message: format!("Failed to read metadata for '{path}': {e}"),
+            })?;
+        let target_metadata = fs::metadata(&resolved)
+            .await
+            .map_err(|e| ApiError::IoError {
+                message: format!("Failed to read metadata for '{path}': {e}"),
+            })?;
+
+        let file_type = entry_metadata.file_type();
+        Ok(FileMetadata {
+            is_file: file_type.is_file(),
+            is_dir: file_type.is_dir(),
+            is_symlink: file_type.is_symlink(),
+            len: target_metadata.len() as f64,
+            modified_ms: target_metadata
+                .modified()
+                .ok()
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_millis() as f64),
+        })
+    }
+}
+
+/// Write `content` in chunks, checking `cancel` before each one, then sync.
+/// Check that a write target's parent is a directory and the target itself,
+/// if it exists, is a regular file. Returns the parent directory.
+async fn check_write_target<'a>(resolved: &'a Path, path: &str) -> Result<&'a Path, ApiError> {
+    let parent = resolved.parent().ok_or_else(|| ApiError::InvalidInput {
+        message: format!("Path must include a parent directory: {path}"),
+    })?;
+
+    let parent_metadata = fs::metadata(&parent).await.map_err(|e| ApiError::IoError {
+        message: format!("Failed to read metadata for '{path}': {e}"),
+    })?;
+
+    if !parent_metadata.is_dir() {
+        return Err(ApiError::InvalidInput {
+            message: format!("Parent is not a directory: {path}"),
+        });
+    }
+
+    match fs::symlink_metadata(resolved).await {
+        Ok(metadata) => {
+            if metadata.file_type().is_symlink() {
+                return Err(ApiError::InvalidInput {
+                    message: format!("Path is a symlink: {path}"),
+                });
+            }
+            if metadata.is_dir() {
+                return Err(ApiError::InvalidInput {
+                    message: format!("Path is a directory: {path}"),
+                });
+            }
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => {
+            return Err(ApiError::IoError {
+                message: format!("Failed to read metadata for '{path}': {e}"),
+            });
+        }
+    }
+
+    Ok(parent)
+}
+
+/// Check that a delete/rename source exists and return whether it is a
+/// directory, rejecting directories unless `recursive` is set.
+async fn existing_target_is_dir(
+    resolved: &Path,
+    path: &str,
+    recursive: bool,
+) -> Result<bool, ApiError> {
+    let metadata = fs::symlink_metadata(resolved).await.map_err(|e| {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            ApiError::PathNotFound {
+                path: path.to_string(),
+            }
+        } else {
+            ApiError::IoError {
+                message: format!("Failed to read metadata for '{path}': {e}"),
+            }
         }
+    })?;
 
-        Ok(content.len() as u64)
+    if metadata.is_dir() && !recursive {
+        return Err(ApiError::InvalidInput {
+            message: format!("Path is a directory (pass recursive to include it): {path}"),
+        });
+    }
+    Ok(metadata.is_dir())
+}
+
+async fn write_chunks(
+    file: &mut fs::File,
+    content: &str,
+    path: &str,
+    cancel: &CancellationToken,
+) -> Result<(), ApiError> {
+    for chunk in content.as_bytes().chunks(IO_CHUNK_BYTES) {
+        if cancel.is_cancelled() {
+            return Err(cancelled(path));
+        }
+        file.write_all(chunk).await.map_err(|e| ApiError::IoError {
+            message: format!("Failed to write temp file for '{path}': {e}"),
+        })?;
+    }
+
+    file.flush().await.map_err(|e| ApiError::IoError {
+        message: format!("Failed to flush temp file for '{path}': {e}"),
+    })?;
+
+    file.sync_all().await.map_err(|e| ApiError::IoError {
+        message: format!("Failed to sync temp file for '{path}': {e}"),
+    })
+}
+
+fn cancelled(path: &str) -> ApiError {
+    ApiError::Cancelled {
+        message: format!("File operation on '{path}' was cancelled"),
     }
 }
 
@@ -145,6 +592,62 @@ async fn replace_file(from: &PathBuf, to: &PathBuf) -> Result<(), ApiError> {
     }
 }
 
+/// Remove the temp files that interrupted `write_text_file` calls left
+/// under `root`, returning how many were removed and their total size.
+///
+/// Blocking. Symlinks aren't followed, and `.git` and `node_modules` are
+/// skipped.
+pub fn remove_temp_write_files(root: &Path) -> (u32, u64) {
+    let mut removed = (0, 0);
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return removed;
+    };
+    for entry in entries.flatten() {
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if file_type.is_dir() {
+            if name != ".git" && name != "node_modules" {
+                let (count, bytes) = remove_temp_write_files(&entry.path());
+                removed.0 += count;
+                removed.1 += bytes;
+            }
+        } else if file_type.is_file() && name.starts_with(TEMP_WRITE_PREFIX) {
+            let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            match std::fs::remove_file(entry.path()) {
+                Ok(()) => {
+                    removed.0 += 1;
+                    removed.1 += size;
+                }
+                Err(e) => log::warn!("Failed to remove temp file {:?}: {e}", entry.path()),
+            }
+        }
+    }
+    removed
+}
+
+/// Apply an optional line window to a whole file's content.
+fn select_lines(content: String, line: Option<u32>, limit: Option<u32>) -> String {
+    if line.is_none() && limit.is_none() {
+        return content;
+    }
+    slice_lines(&content, line, limit)
+}
+
+/// Lines `line..line + limit` (1-based, line endings kept) of `content`.
+fn slice_lines(content: &str, line: Option<u32>, limit: Option<u32>) -> String {
+    let start = line.unwrap_or(1).max(1) as usize - 1;
+    let limit = limit.map_or(usize::MAX, |limit| limit as usize);
+
+    content
+        .split_inclusive('\n')
+        .skip(start)
+        .take(limit)
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -163,13 +666,24 @@ mod tests {
             .await
             .expect("failed to write file");
 
-        let manager = FsManager::new(root.clone());
-        let content = manager
-            .read_text_file("hello.txt".to_string())
+        let manager = FsManager::new(root.clone(), DEFAULT_MAX_READ_BYTES);
+        let read = manager
+            .read_text_file(
+                "./hello.txt".to_string(),
+                None,
+                None,
+                &CancellationToken::new(),
+            )
             .await
             .unwrap();
 
-        assert_eq!(content, "hello world");
+        assert_eq!(read.content, "hello world");
+        // The returned location is the resolved file inside the root
+        assert_eq!(read.path.relative, "hello.txt");
+        assert_eq!(
+            read.path.absolute,
+            root.canonicalize().unwrap().join("hello.txt")
+        );
 
         fs::remove_file(&file_path)
             .await
@@ -179,6 +693,188 @@ mod tests {
             .expect("failed to remove root dir");
     }
 
+    #[tokio::test]
+    async fn test_read_text_file_line_window() {
+        let root = env::temp_dir().join(format!("fs_root_{}", Uuid::new_v4()));
+        fs::create_dir_all(&root)
+            .await
+            .expect("failed to create root dir");
+        fs::write(root.join("lines.txt"), "one\ntwo\nthree\nfour")
+            .await
+            .expect("failed to write file");
+
+        let manager = FsManager::new(root.clone(), DEFAULT_MAX_READ_BYTES);
+        let cancel = CancellationToken::new();
+        let read =
+            |line, limit| manager.read_text_file("lines.txt".to_string(), line, limit, &cancel);
+
+        assert_eq!(
+            read(Some(2), Some(2)).await.unwrap().content,
+            "two\nthree\n"
+        );
+        assert_eq!(read(Some(3), None).await.unwrap().content, "three\nfour");
+        assert_eq!(read(None, Some(1)).await.unwrap().content, "one\n");
+        // Start past EOF and a zero limit both yield empty content
+        assert_eq!(read(Some(10), Some(5)).await.unwrap().content, "");
+        assert_eq!(read(Some(1), Some(0)).await.unwrap().content, "");
+
+        fs::remove_dir_all(&root)
+            .await
+            .expect("failed to remove root dir");
+    }
+
+    #[tokio::test]
+    async fn test_read_binary_file_as_base64() {
+        let root = env::temp_dir().join(format!("fs_root_{}", Uuid::new_v4()));
+        fs::create_dir_all(&root)
+            .await
+            .expect("failed to create root dir");
+        // Not valid UTF-8: 0xff never appears in UTF-8
+        let binary = [0xffu8, 0xfe, 0x00, 0x01];
+        fs::write(root.join("image.bin"), binary)
+            .await
+            .expect("failed to write file");
+        fs::write(root.join("notes.txt"), "one\ntwo\n")
+            .await
+            .expect("failed to write file");
+        assert!(std::str::from_utf8(&binary).is_err());
+
+        let manager = FsManager::new(root.clone(), DEFAULT_MAX_READ_BYTES);
+        let cancel = CancellationToken::new();
+
+        let as_text = manager
+            .read_text_file("image.bin".to_string(), None, None, &cancel)
+            .await;
+        assert!(matches!(as_text, Err(ApiError::IoError { .. })));
+
+        let encoded = manager
+            .read_file_base64("image.bin".to_string(), &cancel)
+            .await
+            .unwrap();
+        assert_eq!(encoded.content, "//4AAQ==");
+        assert_eq!(encoded.path.relative, "image.bin");
+
+        assert_eq!(
+            manager
+                .read_file_auto("image.bin".to_string(), None, None, &cancel)
+                .await
+                .unwrap()
+                .content,
+            FileContent::Base64("//4AAQ==".to_string())
+        );
+        // Text files stay text, honoring the line window
+        assert_eq!(
+            manager
+                .read_file_auto("notes.txt".to_string(), Some(2), None, &cancel)
+                .await
+                .unwrap()
+                .content,
+            FileContent::Text("two\n".to_string())
+        );
+
+        fs::remove_dir_all(&root)
+            .await
+            .expect("failed to remove root dir");
+    }
+
+    #[tokio::test]
+    async fn test_list_dir_stays_inside_workspace() {
+        let base = env::temp_dir().join(format!("fs_list_{}", Uuid::new_v4()));
+        let root = base.join("root");
+        fs::create_dir_all(root.join("src"))
+            .await
+            .expect("failed to create root dir");
+        fs::write(root.join("README.md"), "hello")
+            .await
+            .expect("failed to write file");
+        fs::write(base.join("secret.txt"), "outside")
+            .await
+            .expect("failed to write file");
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(base.join("secret.txt"), root.join("escape"))
+                .expect("failed to create symlink");
+            std::os::unix::fs::symlink(root.join("README.md"), root.join("readme-link"))
+                .expect("failed to create symlink");
+        }
+
+        let manager = FsManager::new(root.clone(), DEFAULT_MAX_READ_BYTES);
+        let entries = manager.list_dir(String::new()).await.unwrap();
+
+        let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+        #[cfg(unix)]
+        assert_eq!(names, vec!["src", "README.md", "readme-link"]);
+        #[cfg(not(unix))]
+        assert_eq!(names, vec!["src", "README.md"]);
+        assert!(entries[0].is_dir);
+        assert_eq!(entries[1].size, 5.0);
+
+        // Files and paths outside the root are rejected
+        assert!(matches!(
+            manager.list_dir("README.md".to_string()).await,
+            Err(ApiError::PathNotDirectory { .. })
+        ));
+        assert!(manager.list_dir("..".to_string()).await.is_err());
+
+        fs::remove_dir_all(&base)
+            .await
+            .expect("failed to remove base dir");
+    }
+
+    #[tokio::test]
+    async fn test_metadata_reports_file_dir_and_symlink() {
+        let base = env::temp_dir().join(format!("fs_meta_{}", Uuid::new_v4()));
+        let root = base.join("root");
+        fs::create_dir_all(root.join("src"))
+            .await
+            .expect("failed to create root dir");
+        fs::write(root.join("README.md"), "hello")
+            .await
+            .expect("failed to write file");
+
+        let manager = FsManager::new(root.clone(), DEFAULT_MAX_READ_BYTES);
+
+        let file = manager.metadata("README.md".to_string()).await.unwrap();
+        assert!(file.is_file && !file.is_dir && !file.is_symlink);
+        assert_eq!(file.len, 5.0);
+        assert!(file.modified_ms.is_some_and(|ms| ms > 0.0));
+
+        let dir = manager
+            .metadata(root.join("src").to_string_lossy().to_string())
+            .await
+            .unwrap();
+        assert!(dir.is_dir && !dir.is_file && !dir.is_symlink);
+
+        #[cfg(unix)]
+        {
+            fs::write(base.join("secret.txt"), "outside")
+                .await
+                .expect("failed to write file");
+            std::os::unix::fs::symlink(root.join("README.md"), root.join("readme-link"))
+                .expect("failed to create symlink");
+            std::os::unix::fs::symlink(base.join("secret.txt"), root.join("escape"))
+                .expect("failed to create symlink");
+
+            let link = manager.metadata("readme-link".to_string()).await.unwrap();
+            assert!(link.is_symlink && !link.is_file && !link.is_dir);
+            assert_eq!(link.len, 5.0);
+
+            assert!(matches!(
+                manager.metadata("escape".to_string()).await,
+                Err(ApiError::InvalidInput { .. })
+            ));
+        }
+
+        assert!(matches!(
+            manager.metadata("..".to_string()).await,
+            Err(ApiError::InvalidInput { .. })
+        ));
+
+        fs::remove_dir_all(&base)
+            .await
+            .expect("failed to remove base dir");
+    }
+
     #[tokio::test]
     async fn test_read_text_file_rejects_directory() {
         let root = env::temp_dir().join(format!("fs_root_{}", Uuid::new_v4()));
@@ -191,8 +887,10 @@ mod tests {
             .await
             .expect("failed to create nested dir");
 
-        let manager = FsManager::new(root.clone());
-        let result = manager.read_text_file("nested".to_string()).await;
+        let manager = FsManager::new(root.clone(), DEFAULT_MAX_READ_BYTES);
+        let result = manager
+            .read_text_file("nested".to_string(), None, None, &CancellationToken::new())
+            .await;
 
         assert!(matches!(result, Err(ApiError::InvalidInput { .. })));
 
@@ -209,16 +907,35 @@ mod tests {
             .expect("failed to create root dir");
 
         let file_path = root.join("large.txt");
-        let content = vec![b'a'; (MAX_READ_BYTES as usize) + 1];
+        let content = vec![b'a'; (DEFAULT_MAX_READ_BYTES as usize) + 1];
         fs::write(&file_path, content)
             .await
             .expect("failed to write file");
 
-        let manager = FsManager::new(root.clone());
-        let result = manager.read_text_file("large.txt".to_string()).await;
+        let manager = FsManager::new(root.clone(), DEFAULT_MAX_READ_BYTES);
+        let result = manager
+            .read_text_file(
+                "large.txt".to_string(),
+                None,
+                None,
+                &CancellationToken::new(),
+            )
+            .await;
 
         assert!(matches!(result, Err(ApiError::InvalidInput { .. })));
 
+        // A raised workspace limit lets the same file through
+        manager.set_max_read_bytes(DEFAULT_MAX_READ_BYTES * 2);
+        let result = manager
+            .read_text_file(
+                "large.txt".to_string(),
+                None,
+                None,
+                &CancellationToken::new(),
+            )
+            .await;
+        assert!(result.is_ok());
+
         fs::remove_file(&file_path)
             .await
             .expect("failed to remove file");
@@ -234,13 +951,18 @@ mod tests {
             .await
             .expect("failed to create root dir");
 
-        let manager = FsManager::new(root.clone());
-        let bytes = manager
-            .write_text_file("hello.txt".to_string(), "hello world".to_string())
+        let manager = FsManager::new(root.clone(), DEFAULT_MAX_READ_BYTES);
+        let written = manager
+            .write_text_file(
+                "hello.txt".to_string(),
+                "hello world".to_string(),
+                &CancellationToken::new(),
+            )
             .await
             .unwrap();
 
-        assert_eq!(bytes, "hello world".as_bytes().len() as u64);
+        assert_eq!(written.bytes_written, "hello world".as_bytes().len() as u64);
+        assert_eq!(written.path.relative, "hello.txt");
 
         let content = fs::read_to_string(root.join("hello.txt"))
             .await
@@ -255,6 +977,44 @@ mod tests {
             .expect("failed to remove root dir");
     }
 
+    #[tokio::test]
+    async fn test_write_text_file_creates_missing_parents() {
+        let root = env::temp_dir().join(format!("fs_root_{}", Uuid::new_v4()));
+        fs::create_dir_all(&root)
+            .await
+            .expect("failed to create root dir");
+
+        let manager = FsManager::new(root.clone(), DEFAULT_MAX_READ_BYTES);
+        assert_eq!(
+            manager.resolve_write_path("src/new/mod.rs").unwrap(),
+            "src/new/mod.rs"
+        );
+        // Resolving for the permission prompt doesn't touch the disk
+        assert!(!root.join("src").exists());
+
+        let written = manager
+            .write_text_file(
+                "src/new/mod.rs".to_string(),
+                "pub mod x;".to_string(),
+                &CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        // Both forms of the path point at the new file inside the root
+        let root_dir = root.canonicalize().unwrap();
+        assert_eq!(written.path.relative, "src/new/mod.rs");
+        assert!(written.path.absolute.starts_with(&root_dir));
+        assert_eq!(written.path.absolute, root_dir.join("src/new/mod.rs"));
+        let content = fs::read_to_string(root.join("src/new/mod.rs"))
+            .await
+            .expect("failed to read written file");
+        assert_eq!(content, "pub mod x;");
+
+        fs::remove_dir_all(&root)
+            .await
+            .expect("failed to remove root dir");
+    }
+
     #[tokio::test]
     async fn test_write_text_file_rejects_directory() {
         let root = env::temp_dir().join(format!("fs_root_{}", Uuid::new_v4()));
@@ -267,9 +1027,13 @@ mod tests {
             .await
             .expect("failed to create nested dir");
 
-        let manager = FsManager::new(root.clone());
+        let manager = FsManager::new(root.clone(), DEFAULT_MAX_READ_BYTES);
         let result = manager
-            .write_text_file("nested".to_string(), "data".to_string())
+            .write_text_file(
+                "nested".to_string(),
+                "data".to_string(),
+                &CancellationToken::new(),
+            )
             .await;
 
         assert!(matches!(result, Err(ApiError::InvalidInput { .. })));
@@ -278,4 +1042,231 @@ mod tests {
             .await
             .expect("failed to remove root dir");
     }
+
+    #[tokio::test]
+    async fn test_append_text_file_creates_then_appends() {
+        let root = env::temp_dir().join(format!("fs_root_{}", Uuid::new_v4()));
+        fs::create_dir_all(root.join("nested"))
+            .await
+            .expect("failed to create root dir");
+
+        let manager = FsManager::new(root.clone(), DEFAULT_MAX_READ_BYTES);
+        let appended = manager
+            .append_text_file("log.txt".to_string(), "first\n".to_string())
+            .await
+            .unwrap();
+        assert_eq!(appended.bytes_written, 6);
+        assert_eq!(appended.path.relative, "log.txt");
+        manager
+            .append_text_file("log.txt".to_string(), "second\n".to_string())
+            .await
+            .unwrap();
+
+        let content = fs::read_to_string(root.join("log.txt"))
+            .await
+            .expect("failed to read appended file");
+        assert_eq!(content, "first\nsecond\n");
+
+        assert!(matches!(
+            manager
+                .append_text_file("nested".to_string(), "x".to_string())
+                .await,
+            Err(ApiError::InvalidInput { .. })
+        ));
+
+        fs::remove_dir_all(&root)
+            .await
+            .expect("failed to remove root dir");
+    }
+
+    #[tokio::test]
+    async fn test_delete_file_success() {
+        let root = env::temp_dir().join(format!("fs_root_{}", Uuid::new_v4()));
+        fs::create_dir_all(&root)
+            .await
+            .expect("failed to create root dir");
+        fs::write(root.join("old.txt"), "bye")
+            .await
+            .expect("failed to write file");
+
+        let manager = FsManager::new(root.clone(), DEFAULT_MAX_READ_BYTES);
+        manager
+            .delete_file("old.txt".to_string(), false)
+            .await
+            .unwrap();
+        assert!(!root.join("old.txt").exists());
+
+        // Deleting it again reports the missing path
+        assert!(matches!(
+            manager.delete_file("old.txt".to_string(), false).await,
+            Err(ApiError::PathNotFound { .. })
+        ));
+
+        fs::remove_dir_all(&root)
+            .await
+            .expect("failed to remove root dir");
+    }
+
+    #[tokio::test]
+    async fn test_delete_and_rename_reject_directory_unless_recursive() {
+        let root = env::temp_dir().join(format!("fs_root_{}", Uuid::new_v4()));
+        fs::create_dir_all(root.join("nested"))
+            .await
+            .expect("failed to create nested dir");
+        fs::write(root.join("nested").join("a.txt"), "a")
+            .await
+            .expect("failed to write file");
+
+        let manager = FsManager::new(root.clone(), DEFAULT_MAX_READ_BYTES);
+        assert!(matches!(
+            manager.delete_file("nested".to_string(), false).await,
+            Err(ApiError::InvalidInput { .. })
+        ));
+        assert!(matches!(
+            manager
+                .rename("nested".to_string(), "moved".to_string(), false)
+                .await,
+            Err(ApiError::InvalidInput { .. })
+        ));
+        assert!(root.join("nested").join("a.txt").exists());
+
+        manager
+            .rename("nested".to_string(), "moved".to_string(), true)
+            .await
+            .unwrap();
+        assert!(root.join("moved").join("a.txt").exists());
+
+        manager
+            .delete_file("moved".to_string(), true)
+            .await
+            .unwrap();
+        assert!(!root.join("moved").exists());
+
+        fs::remove_dir_all(&root)
+            .await
+            .expect("failed to remove root dir");
+    }
+
+    #[tokio::test]
+    async fn test_delete_and_rename_reject_out_of_root_targets() {
+        let base = env::temp_dir().join(format!("fs_base_{}", Uuid::new_v4()));
+        let root = base.join("root");
+        fs::create_dir_all(&root)
+            .await
+            .expect("failed to create root dir");
+        fs::write(base.join("outside.txt"), "keep")
+            .await
+            .expect("failed to write file");
+        fs::write(root.join("inside.txt"), "data")
+            .await
+            .expect("failed to write file");
+
+        let manager = FsManager::new(root.clone(), DEFAULT_MAX_READ_BYTES);
+        assert!(matches!(
+            manager
+                .delete_file("../outside.txt".to_string(), false)
+                .await,
+            Err(ApiError::InvalidInput { .. })
+        ));
+        assert!(matches!(
+            manager
+                .rename("inside.txt".to_string(), "../stolen.txt".to_string(), false)
+                .await,
+            Err(ApiError::InvalidInput { .. })
+        ));
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(base.join("outside.txt"), root.join("link.txt"))
+                .expect("failed to create symlink");
+            assert!(matches!(
+                manager.delete_file("link.txt".to_string(), false).await,
+                Err(ApiError::InvalidInput { .. })
+            ));
+        }
+
+        assert!(base.join("outside.txt").exists());
+        assert!(root.join("inside.txt").exists());
+        assert!(!base.join("stolen.txt").exists());
+
+        fs::remove_dir_all(&base)
+            .await
+            .expect("failed to remove base dir");
+    }
+
+    #[tokio::test]
+    async fn test_resolve_paths_show_canonical_location() {
+        let root = env::temp_dir().join(format!("fs_root_{}", Uuid::new_v4()));
+        fs::create_dir_all(root.join("nested"))
+            .await
+            .expect("failed to create nested dir");
+        fs::write(root.join("notes.txt"), "notes")
+            .await
+            .expect("failed to write file");
+
+        let manager = FsManager::new(root.clone(), DEFAULT_MAX_READ_BYTES);
+
+        assert_eq!(
+            manager.resolve_read_path("nested/../notes.txt").unwrap(),
+            "notes.txt"
+        );
+        assert_eq!(
+            manager.resolve_write_path("nested/../new.txt").unwrap(),
+            "new.txt"
+        );
+        assert!(matches!(
+            manager.resolve_write_path("../outside.txt"),
+            Err(ApiError::InvalidInput { .. })
+        ));
+
+        fs::remove_dir_all(&root)
+            .await
+            .expect("failed to remove root dir");
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_write_removes_temp_file() {
+        let root = env::temp_dir().join(format!("fs_root_{}", Uuid::new_v4()));
+        fs::create_dir_all(&root)
+            .await
+            .expect("failed to create root dir");
+
+        let manager = FsManager::new(root.clone(), DEFAULT_MAX_READ_BYTES);
+        let content = "x".repeat(IO_CHUNK_BYTES * 16);
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+
+        let result = manager
+            .write_text_file("large.txt".to_string(), content, &cancel)
+            .await;
+
+        assert!(matches!(result, Err(ApiError::Cancelled { .. })));
+        assert!(!root.join("large.txt").exists());
+        let leftovers = std::fs::read_dir(&root)
+            .expect("failed to list root dir")
+            .count();
+        assert_eq!(leftovers, 0, "temp file was not cleaned up");
+
+        fs::remove_dir_all(&root)
+            .await
+            .expect("failed to remove root dir");
+    }
+
+    #[test]
+    fn test_remove_temp_write_files_spares_other_files() {
+        let root = env::temp_dir().join(format!("fs_root_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::create_dir_all(root.join(".git")).unwrap();
+        std::fs::write(root.join(".tmp_write_a"), "abc").unwrap();
+        std::fs::write(root.join("src/.tmp_write_b"), "de").unwrap();
+        std::fs::write(root.join("src/main.rs"), "fn main() {}").unwrap();
+        std::fs::write(root.join(".git/.tmp_write_c"), "f").unwrap();
+
+        assert_eq!(remove_temp_write_files(&root), (2, 5));
+
+        assert!(!root.join(".tmp_write_a").exists());
+        assert!(!root.join("src/.tmp_write_b").exists());
+        assert!(root.join("src/main.rs").exists());
+        assert!(root.join(".git/.tmp_write_c").exists());
+        let _ = std::fs::remove_dir_all(&root);
+    }
 }
@@ -0,0 +1,209 @@
+//! FsWatcher - opt-in file watching for a workspace root.
+//!
+//! Raw `notify` events are coalesced per path over a short debounce window
+//! so a burst of writes (e.g. an agent regenerating a directory) reaches the
+//! frontend as one `fs/changed` event per path.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use notify::event::{ModifyKind, RenameMode};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::api::types::{ApiError, FsChangeKind};
+
+/// Event name for workspace file changes
+pub const EVENT_FS_CHANGED: &str = "fs/changed";
+
+/// How long to wait for more events before flushing a batch
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Watches a workspace root until dropped.
+pub struct FsWatcher {
+    _watcher: RecommendedWatcher,
+    task: JoinHandle<()>,
+}
+
+impl FsWatcher {
+    /// Start watching `root` recursively.
+    ///
+    /// `on_change` receives each debounced change with its path relative to
+    /// the canonical root; events for paths outside the root are dropped.
+    pub fn start<F>(root: &Path, on_change: F) -> Result<Self, ApiError>
+    where
+        F: Fn(String, FsChangeKind) + Send + 'static,
+    {
+        let root = root.canonicalize().map_err(|e| ApiError::IoError {
+            message: format!("Failed to canonicalize workspace root: {e}"),
+        })?;
+
+        let (tx, mut rx) = mpsc::unbounded_channel::<(PathBuf, FsChangeKind)>();
+        let mut watcher =
+            notify::recommended_watcher(move |res: notify::Result<Event>| match res {
+                Ok(event) => {
+                    for change in classify_event(&event) {
+                        let _ = tx.send(change);
+                    }
+                }
+                Err(e) => log::warn!("File watcher error: {e}"),
+            })
+            .map_err(|e| ApiError::IoError {
+                message: format!("Failed to create file watcher: {e}"),
+            })?;
+        watcher
+            .watch(&root, RecursiveMode::Recursive)
+            .map_err(|e| ApiError::IoError {
+                message: format!("Failed to watch '{}': {e}", root.display()),
+            })?;
+
+        let task = tokio::spawn(async move {
+            while let Some(first) = rx.recv().await {
+                let mut pending: HashMap<PathBuf, FsChangeKind> = HashMap::new();
+                merge_change(&mut pending, first);
+                while let Ok(Some(next)) = tokio::time::timeout(DEBOUNCE, rx.recv()).await {
+                    merge_change(&mut pending, next);
+                }
+
+                for (path, kind) in pending {
+                    if let Some(relative) = relative_event_path(&root, &path) {
+                        on_change(relative, kind);
+                    }
+                }
+            }
+        });
+
+        Ok(Self {
+            _watcher: watcher,
+            task,
+        })
+    }
+}
+
+impl Drop for FsWatcher {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Map a raw `notify` event to the changes it represents.
+fn classify_event(event: &Event) -> Vec<(PathBuf, FsChangeKind)> {
+    let kinds: Vec<FsChangeKind> = match event.kind {
+        EventKind::Create(_) => vec![FsChangeKind::Created],
+        EventKind::Remove(_) => vec![FsChangeKind::Removed],
+        EventKind::Modify(ModifyKind::Name(RenameMode::From)) => vec![FsChangeKind::Removed],
+        EventKind::Modify(ModifyKind::Name(RenameMode::To)) => vec![FsChangeKind::Created],
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => {
+            vec![FsChangeKind::Removed, FsChangeKind::Created]
+        }
+        EventKind::Modify(_) => vec![FsChangeKind::Modified],
+        _ => return Vec::new(),
+    };
+
+    event
+        .paths
+        .iter()
+        .enumerate()
+        .map(|(i, path)| (path.clone(), kinds[i.min(kinds.len() - 1)]))
+        .collect()
+}
+
+/// Fold a change into the pending batch; a later event wins, except that a
+/// modification doesn't hide that the file was just created.
+fn merge_change(
+    pending: &mut HashMap<PathBuf, FsChangeKind>,
+    (path, kind): (PathBuf, FsChangeKind),
+) {
+    match (pending.get(&path), kind) {
+        (Some(FsChangeKind::Created), FsChangeKind::Modified) => {}
+        _ => {
+            pending.insert(path, kind);
+        }
+    }
+}
+
+/// Express an event path relative to the root, or `None` if it lies outside.
+fn relative_event_path(root: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?;
+    if relative.as_os_str().is_empty() {
+        return None;
+    }
+    Some(
+        relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_relative_event_path_drops_outside_paths() {
+        let root = Path::new("/work/project");
+        assert_eq!(
+            relative_event_path(root, Path::new("/work/project/src/main.rs")),
+            Some("src/main.rs".to_string())
+        );
+        assert_eq!(relative_event_path(root, Path::new("/work/project")), None);
+        assert_eq!(
+            relative_event_path(root, Path::new("/work/other/a.txt")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_merge_keeps_created_over_modified() {
+        let mut pending = HashMap::new();
+        let path = PathBuf::from("/work/a.txt");
+        merge_change(&mut pending, (path.clone(), FsChangeKind::Created));
+        merge_change(&mut pending, (path.clone(), FsChangeKind::Modified));
+        assert_eq!(pending[&path], FsChangeKind::Created);
+
+        merge_change(&mut pending, (path.clone(), FsChangeKind::Removed));
+        assert_eq!(pending[&path], FsChangeKind::Removed);
+    }
+
+    #[tokio::test]
+    async fn test_watcher_reports_debounced_change() {
+        let root = std::env::temp_dir().join(format!("fs_watch_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).expect("failed to create root dir");
+
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        let sink = changes.clone();
+        let watcher = FsWatcher::start(&root, move |path, kind| {
+            sink.lock().unwrap().push((path, kind));
+        })
+        .unwrap();
+
+        std::fs::write(root.join("a.txt"), "one").expect("failed to write file");
+        std::fs::write(root.join("a.txt"), "two").expect("failed to write file");
+
+        let reported = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                if !changes.lock().unwrap().is_empty() {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+        })
+        .await;
+        assert!(reported.is_ok(), "no change reported");
+
+        // Give the debounce window time to close, then check the burst coalesced
+        tokio::time::sleep(DEBOUNCE * 2).await;
+        let changes = changes.lock().unwrap().clone();
+        let for_file: Vec<_> = changes.iter().filter(|(p, _)| p == "a.txt").collect();
+        assert_eq!(for_file.len(), 1, "changes: {changes:?}");
+        assert_eq!(for_file[0].1, FsChangeKind::Created);
+
+        drop(watcher);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -4,11 +4,25 @@
 //! Workspace, Agent, Operation, and Permission.
 
 pub mod agent_host;
+pub mod agent_slots;
 pub mod agents;
+pub mod cancellation;
+pub mod command_policy;
+pub mod env_file;
 pub mod fs;
+pub mod fs_watch;
+pub mod notifier;
 pub mod path;
+pub mod path_policy;
+pub mod permission_audit;
+pub mod permission_rules;
 pub mod permissions;
 pub mod plugin_installer;
+pub mod session_updates;
 pub mod terminal;
+#[cfg(test)]
+pub(crate) mod test_support;
+pub mod transcripts;
 pub mod workspace;
 pub mod workspace_manager;
+pub mod workspace_store;
@@ -0,0 +1,80 @@
+//! User-facing alerts (install finished, agent failed, ...).
+//!
+//! Alerts are shown as native OS notifications. Where that isn't possible
+//! (mobile, or the notification plugin fails) they are emitted as a
+//! `notification/show` event for the frontend to render instead. Nothing is
+//! shown while the "notifications enabled" preference is off.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use tauri::{AppHandle, Emitter};
+
+use crate::api::types::{NotificationLevel, NotificationShowEvent};
+
+/// Event name for alerts the frontend should render itself
+pub const EVENT_NOTIFICATION_SHOW: &str = "notification/show";
+
+/// Shows user-facing alerts, honoring the notifications preference.
+///
+/// This singleton is injected via `app.manage(Arc::new(Notifier::new(...)))`.
+pub struct Notifier {
+    app: AppHandle,
+    enabled: AtomicBool,
+}
+
+impl Notifier {
+    /// Create a notifier; `enabled` is the saved preference.
+    pub fn new(app: AppHandle, enabled: bool) -> Self {
+        Self {
+            app,
+            enabled: AtomicBool::new(enabled),
+        }
+    }
+
+    /// Apply a changed preference.
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Whether alerts are currently shown.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Show an alert. Returns false if notifications are disabled.
+    pub fn notify(&self, title: &str, body: Option<&str>, level: NotificationLevel) -> bool {
+        if !self.is_enabled() {
+            log::debug!("Notification suppressed (disabled): title={title}");
+            return false;
+        }
+
+        if let Err(e) = self.show_native(title, body) {
+            log::debug!("Native notification unavailable, emitting event: {e}");
+            let event = NotificationShowEvent {
+                title: title.to_string(),
+                body: body.map(str::to_string),
+                level,
+            };
+            if let Err(e) = self.app.emit(EVENT_NOTIFICATION_SHOW, &event) {
+                log::error!("Failed to emit notification/show: {e}");
+            }
+        }
+        true
+    }
+
+    #[cfg(not(mobile))]
+    fn show_native(&self, title: &str, body: Option<&str>) -> Result<(), String> {
+        use tauri_plugin_notification::NotificationExt;
+
+        let mut notification = self.app.notification().builder().title(title);
+        if let Some(body) = body {
+            notification = notification.body(body);
+        }
+        notification.show().map_err(|e| e.to_string())
+    }
+
+    #[cfg(mobile)]
+    fn show_native(&self, _title: &str, _body: Option<&str>) -> Result<(), String> {
+        Err("Native notifications not supported on mobile".to_string())
+    }
+}
@@ -4,7 +4,7 @@
 
 use std::fs;
 use std::io::ErrorKind;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 
 use crate::api::types::ApiError;
 
@@ -45,6 +45,61 @@ pub fn canonicalize_workspace_root(root: &str) -> Result<PathBuf, ApiError> {
     })
 }
 
+/// Working directory to launch an adapter in and send with `session/new`.
+///
+/// Re-checks that the workspace root still exists and is a directory, and
+/// returns it canonicalized. On Windows the `\\?\` verbatim prefix that
+/// `canonicalize` adds is removed, since node-based adapters can't resolve
+/// such paths. The path must be valid Unicode to survive the JSON handshake.
+///
+/// # Errors
+/// * `ApiError::PathNotFound` - If the root no longer exists
+/// * `ApiError::PathNotDirectory` - If the root is not a directory
+/// * `ApiError::InvalidInput` - If the path isn't valid Unicode
+pub fn adapter_cwd(root: &Path) -> Result<PathBuf, ApiError> {
+    let display = root.display().to_string();
+    let canonical = match root.canonicalize() {
+        Ok(path) => path,
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            return Err(ApiError::PathNotFound { path: display });
+        }
+        Err(e) => {
+            return Err(ApiError::IoError {
+                message: format!("Failed to canonicalize path '{display}': {e}"),
+            });
+        }
+    };
+    if !canonical.is_dir() {
+        return Err(ApiError::PathNotDirectory { path: display });
+    }
+
+    let Some(cwd) = canonical.to_str() else {
+        return Err(ApiError::InvalidInput {
+            message: format!("Workspace path is not valid Unicode: {display}"),
+        });
+    };
+    Ok(PathBuf::from(strip_verbatim_prefix(cwd)))
+}
+
+/// Turn a verbatim path (`\\?\C:\dir`, `\\?\UNC\server\share`) into its
+/// ordinary form (`C:\dir`, `\\server\share`).
+#[cfg(windows)]
+fn strip_verbatim_prefix(path: &str) -> String {
+    if let Some(unc) = path.strip_prefix(r"\\?\UNC\") {
+        format!(r"\\{unc}")
+    } else if let Some(rest) = path.strip_prefix(r"\\?\") {
+        rest.to_string()
+    } else {
+        path.to_string()
+    }
+}
+
+/// Paths have no verbatim prefix outside Windows.
+#[cfg(not(windows))]
+fn strip_verbatim_prefix(path: &str) -> String {
+    path.to_string()
+}
+
 /// Resolve a file path within a workspace root.
 ///
 /// This performs a canonicalization-based boundary check to ensure
@@ -116,9 +171,16 @@ pub fn resolve_path_in_workspace(root: &Path, input: &str) -> Result<PathBuf, Ap
 
 /// Resolve a write target within a workspace root.
 ///
-/// This allows the target file to be missing, while still enforcing that the
-/// parent directory exists and is inside the workspace root.
-pub fn resolve_write_target_in_workspace(root: &Path, input: &str) -> Result<PathBuf, ApiError> {
+/// The target file and any of its parent directories may be missing: the
+/// deepest existing ancestor is canonicalized, the missing components are
+/// appended, and the result must stay inside the workspace root. `..` is not
+/// allowed among the missing components. With `create_dirs`, missing parent
+/// directories are created once the target has been validated.
+pub fn resolve_write_target_in_workspace(
+    root: &Path,
+    input: &str,
+    create_dirs: bool,
+) -> Result<PathBuf, ApiError> {
     if input.trim().is_empty() {
         return Err(ApiError::InvalidInput {
             message: "Path cannot be empty".to_string(),
@@ -154,17 +216,7 @@ pub fn resolve_write_target_in_workspace(root: &Path, input: &str) -> Result<Pat
         message: format!("Path must include a parent directory: {input}"),
     })?;
 
-    let canonical_parent = parent.canonicalize().map_err(|e| {
-        if e.kind() == ErrorKind::NotFound {
-            ApiError::PathNotFound {
-                path: parent.display().to_string(),
-            }
-        } else {
-            ApiError::IoError {
-                message: format!("Failed to canonicalize path '{input}': {e}"),
-            }
-        }
-    })?;
+    let (canonical_parent, parent_exists) = resolve_missing_ancestors(parent, input)?;
 
     if !canonical_parent.starts_with(&root) {
         return Err(ApiError::InvalidInput {
@@ -172,6 +224,18 @@ pub fn resolve_write_target_in_workspace(root: &Path, input: &str) -> Result<Pat
         });
     }
 
+    if !parent_exists {
+        if !create_dirs {
+            return Ok(canonical_parent.join(file_name));
+        }
+        fs::create_dir_all(&canonical_parent).map_err(|e| ApiError::IoError {
+            message: format!(
+                "Failed to create directory '{}': {e}",
+                canonical_parent.display()
+            ),
+        })?;
+    }
+
     match fs::symlink_metadata(&candidate) {
         Ok(metadata) => {
             if metadata.file_type().is_symlink() {
@@ -199,6 +263,84 @@ pub fn resolve_write_target_in_workspace(root: &Path, input: &str) -> Result<Pat
     }
 }
 
+/// Canonicalize the deepest existing ancestor of `dir` and append the rest.
+///
+/// Returns the resolved directory and whether it exists. Missing components
+/// can't contain `..` (it would be resolved against a directory that isn't
+/// there), and an existing-but-unresolvable entry such as a dangling symlink
+/// is rejected rather than treated as missing.
+fn resolve_missing_ancestors(dir: &Path, input: &str) -> Result<(PathBuf, bool), ApiError> {
+    for ancestor in dir.ancestors() {
+        let canonical = match ancestor.canonicalize() {
+            Ok(canonical) => canonical,
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                if fs::symlink_metadata(ancestor).is_ok() {
+                    return Err(ApiError::InvalidInput {
+                        message: format!("Path goes through a broken symlink: {input}"),
+                    });
+                }
+                continue;
+            }
+            Err(e) => {
+                return Err(ApiError::IoError {
+                    message: format!("Failed to canonicalize path '{input}': {e}"),
+                });
+            }
+        };
+
+        let missing = dir.strip_prefix(ancestor).unwrap_or(Path::new(""));
+        let mut resolved = canonical;
+        let mut exists = true;
+        for component in missing.components() {
+            match component {
+                Component::Normal(name) => {
+                    resolved.push(name);
+                    exists = false;
+                }
+                Component::CurDir => {}
+                _ => {
+                    return Err(ApiError::InvalidInput {
+                        message: format!("Path escapes workspace root: {input}"),
+                    });
+                }
+            }
+        }
+        return Ok((resolved, exists));
+    }
+
+    Err(ApiError::PathNotFound {
+        path: dir.display().to_string(),
+    })
+}
+
+/// Express an already-resolved path relative to the workspace root.
+///
+/// Used to show users the canonical location an agent operation will touch,
+/// which may differ from the raw path the agent supplied (e.g. `a/../b`).
+/// Returns `"."` for the root itself and uses `/` separators on all platforms.
+pub fn workspace_relative_path(root: &Path, resolved: &Path) -> Result<String, ApiError> {
+    let root_display = root.display().to_string();
+    let root = root.canonicalize().map_err(|e| ApiError::IoError {
+        message: format!("Failed to canonicalize workspace root '{root_display}': {e}"),
+    })?;
+
+    let relative = resolved
+        .strip_prefix(&root)
+        .map_err(|_| ApiError::InvalidInput {
+            message: format!("Path escapes workspace root: {}", resolved.display()),
+        })?;
+
+    if relative.as_os_str().is_empty() {
+        return Ok(".".to_string());
+    }
+
+    Ok(relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -226,6 +368,37 @@ mod tests {
         assert!(matches!(result, Err(ApiError::InvalidInput { .. })));
     }
 
+    #[test]
+    fn test_adapter_cwd_checks_root_still_exists() {
+        let root = env::temp_dir().join(format!("adapter_cwd_{}", Uuid::new_v4()));
+        fs::create_dir_all(&root).unwrap();
+
+        let cwd = adapter_cwd(&root).unwrap();
+        assert!(cwd.is_absolute());
+        assert!(cwd.is_dir());
+
+        fs::remove_dir_all(&root).unwrap();
+        assert!(matches!(
+            adapter_cwd(&root),
+            Err(ApiError::PathNotFound { .. })
+        ));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_strip_verbatim_prefix() {
+        assert_eq!(strip_verbatim_prefix(r"\\?\C:\work\repo"), r"C:\work\repo");
+        assert_eq!(
+            strip_verbatim_prefix(r"\\?\UNC\server\share\repo"),
+            r"\\server\share\repo"
+        );
+        assert_eq!(strip_verbatim_prefix(r"C:\work"), r"C:\work");
+
+        // canonicalize adds the prefix; adapter_cwd removes it again
+        let cwd = adapter_cwd(&env::temp_dir()).unwrap();
+        assert!(!cwd.to_str().unwrap().starts_with(r"\\?\"));
+    }
+
     #[test]
     fn test_canonicalize_file_not_directory() {
         // Create a temp file to test with
@@ -312,19 +485,57 @@ mod tests {
         let root = env::temp_dir().join(format!("ws_root_{}", Uuid::new_v4()));
         std::fs::create_dir_all(&root).expect("failed to create root dir");
 
-        let resolved = resolve_write_target_in_workspace(&root, "new.txt").unwrap();
+        let resolved = resolve_write_target_in_workspace(&root, "new.txt", false).unwrap();
         assert_eq!(resolved, root.canonicalize().unwrap().join("new.txt"));
 
         std::fs::remove_dir_all(&root).expect("failed to remove root dir");
     }
 
     #[test]
-    fn test_resolve_write_target_rejects_missing_parent() {
+    fn test_resolve_write_target_allows_new_subdir_without_creating_it() {
         let root = env::temp_dir().join(format!("ws_root_{}", Uuid::new_v4()));
         std::fs::create_dir_all(&root).expect("failed to create root dir");
 
-        let result = resolve_write_target_in_workspace(&root, "missing/new.txt");
-        assert!(matches!(result, Err(ApiError::PathNotFound { .. })));
+        let resolved =
+            resolve_write_target_in_workspace(&root, "missing/nested/new.txt", false).unwrap();
+        assert_eq!(
+            resolved,
+            root.canonicalize().unwrap().join("missing/nested/new.txt")
+        );
+        assert!(!root.join("missing").exists());
+
+        std::fs::remove_dir_all(&root).expect("failed to remove root dir");
+    }
+
+    #[test]
+    fn test_resolve_write_target_creates_new_subdir_with_flag() {
+        let root = env::temp_dir().join(format!("ws_root_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&root).expect("failed to create root dir");
+
+        let absolute = root.join("missing/./nested/new.txt");
+        let resolved =
+            resolve_write_target_in_workspace(&root, absolute.to_str().unwrap(), true).unwrap();
+        assert_eq!(
+            resolved,
+            root.canonicalize().unwrap().join("missing/nested/new.txt")
+        );
+        assert!(root.join("missing/nested").is_dir());
+        assert!(!resolved.exists());
+
+        std::fs::remove_dir_all(&root).expect("failed to remove root dir");
+    }
+
+    #[test]
+    fn test_resolve_write_target_rejects_escape_through_missing_dir() {
+        let root = env::temp_dir().join(format!("ws_root_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&root).expect("failed to create root dir");
+
+        for create_dirs in [false, true] {
+            let result =
+                resolve_write_target_in_workspace(&root, "missing/../../escape.txt", create_dirs);
+            assert!(matches!(result, Err(ApiError::InvalidInput { .. })));
+        }
+        assert!(!root.join("missing").exists());
 
         std::fs::remove_dir_all(&root).expect("failed to remove root dir");
     }
@@ -338,7 +549,8 @@ mod tests {
         std::fs::create_dir_all(&outside_dir).expect("failed to create outside dir");
         let outside_file = outside_dir.join("secret.txt");
 
-        let result = resolve_write_target_in_workspace(&root, outside_file.to_str().unwrap());
+        let result =
+            resolve_write_target_in_workspace(&root, outside_file.to_str().unwrap(), false);
         assert!(matches!(result, Err(ApiError::InvalidInput { .. })));
 
         std::fs::remove_dir_all(&outside_dir).expect("failed to remove outside dir");
@@ -357,7 +569,7 @@ mod tests {
             "../{}/secret.txt",
             outside_dir.file_name().unwrap().to_string_lossy()
         );
-        let result = resolve_write_target_in_workspace(&root, &escape_path);
+        let result = resolve_write_target_in_workspace(&root, &escape_path, false);
         assert!(matches!(result, Err(ApiError::InvalidInput { .. })));
 
         std::fs::remove_dir_all(&outside_dir).expect("failed to remove outside dir");
@@ -381,7 +593,8 @@ mod tests {
         symlink(&outside_file, &symlink_path).expect("failed to create symlink");
 
         let result =
-            resolve_write_target_in_workspace(&root, symlink_path.to_str().unwrap()).unwrap_err();
+            resolve_write_target_in_workspace(&root, symlink_path.to_str().unwrap(), false)
+                .unwrap_err();
         assert!(matches!(result, ApiError::InvalidInput { .. }));
 
         std::fs::remove_file(&symlink_path).expect("failed to remove symlink");
@@ -389,4 +602,26 @@ mod tests {
         std::fs::remove_dir_all(&outside_dir).expect("failed to remove outside dir");
         std::fs::remove_dir_all(&root).expect("failed to remove root dir");
     }
+
+    #[test]
+    fn test_workspace_relative_path() {
+        let root = env::temp_dir().join(format!("ws_root_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("nested")).expect("failed to create nested dir");
+
+        let canonical_root = root.canonicalize().unwrap();
+        assert_eq!(
+            workspace_relative_path(&root, &canonical_root.join("nested").join("a.txt")).unwrap(),
+            "nested/a.txt"
+        );
+        assert_eq!(
+            workspace_relative_path(&root, &canonical_root).unwrap(),
+            "."
+        );
+        assert!(matches!(
+            workspace_relative_path(&root, &env::temp_dir().join("elsewhere.txt")),
+            Err(ApiError::InvalidInput { .. })
+        ));
+
+        std::fs::remove_dir_all(&root).expect("failed to remove root dir");
+    }
 }
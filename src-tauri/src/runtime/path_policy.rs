@@ -0,0 +1,253 @@
+//! Allow/deny rules for agent file access, set in preferences.
+//!
+//! Rules match workspace-relative resolved paths (what will actually be read
+//! or written). Deny rules refuse reads and writes and win over allow rules;
+//! allow rules only approve reads. `DEFAULT_FS_DENY_PATTERNS` are denied in
+//! every workspace on top of its own rules, unless turned off in preferences.
+//! Requests matching no rule are left to the usual permission prompt.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use crate::api::types::WorkspaceId;
+use crate::runtime::command_policy::glob_matches;
+use crate::types::{FsPathRule, PolicyAction, DEFAULT_FS_DENY_PATTERNS};
+
+/// Kind of file access being requested
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathAccess {
+    Read,
+    /// Writes, deletes and renames
+    Write,
+}
+
+/// File path rules of every workspace.
+pub struct PathPolicy {
+    rules: Mutex<HashMap<WorkspaceId, Vec<FsPathRule>>>,
+    defaults: Vec<FsPathRule>,
+    /// Whether `defaults` are denied alongside each workspace's rules
+    defaults_enabled: AtomicBool,
+}
+
+impl PathPolicy {
+    /// Create a policy with only the default deny rules.
+    pub fn new() -> Self {
+        Self {
+            rules: Mutex::new(HashMap::new()),
+            defaults: DEFAULT_FS_DENY_PATTERNS
+                .iter()
+                .map(|pattern| FsPathRule {
+                    action: PolicyAction::Deny,
+                    pattern: pattern.to_string(),
+                })
+                .collect(),
+            defaults_enabled: AtomicBool::new(true),
+        }
+    }
+
+    /// Replace all rules, keyed by workspace.
+    pub fn set_rules(&self, rules: HashMap<WorkspaceId, Vec<FsPathRule>>) {
+        *self.rules.lock().unwrap_or_else(|e| e.into_inner()) = rules;
+    }
+
+    /// Turn the default deny rules on or off for every workspace.
+    pub fn set_defaults_enabled(&self, enabled: bool) {
+        self.defaults_enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// What the workspace's rules say about accessing `resolved_path`, or
+    /// `None` if no rule applies and the user should be asked.
+    pub fn evaluate(
+        &self,
+        workspace_id: &WorkspaceId,
+        resolved_path: &str,
+        access: PathAccess,
+    ) -> Option<PolicyAction> {
+        let rules = self.rules.lock().unwrap_or_else(|e| e.into_inner());
+        let rules = rules
+            .get(workspace_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let defaults: &[FsPathRule] = if self.defaults_enabled.load(Ordering::Relaxed) {
+            &self.defaults
+        } else {
+            &[]
+        };
+        let path = resolved_path.trim_start_matches("./");
+
+        let matching = |action: PolicyAction| {
+            rules
+                .iter()
+                .chain(defaults)
+                .filter(move |rule| rule.action == action)
+                .any(|rule| path_matches(&rule.pattern, path))
+        };
+        if matching(PolicyAction::Deny) {
+            return Some(PolicyAction::Deny);
+        }
+        (access == PathAccess::Read && matching(PolicyAction::Allow)).then_some(PolicyAction::Allow)
+    }
+}
+
+impl Default for PathPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Whether a rule pattern covers a workspace-relative path.
+///
+/// Globs without a `/` match any single component (like `.gitignore`);
+/// other globs match the whole path. Plain patterns are prefixes on whole
+/// components.
+fn path_matches(pattern: &str, path: &str) -> bool {
+    let pattern = pattern.trim_start_matches("./").trim_matches('/');
+    if pattern.is_empty() {
+        return false;
+    }
+    if pattern.contains(['*', '?']) {
+        let glob: Vec<char> = pattern.chars().collect();
+        let matches = |text: &str| glob_matches(&glob, &text.chars().collect::<Vec<_>>());
+        return if pattern.contains('/') {
+            matches(path)
+        } else {
+            path.split('/').any(matches)
+        };
+    }
+    path == pattern || path.starts_with(&format!("{pattern}/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy_with(rules: Vec<(PolicyAction, &str)>) -> PathPolicy {
+        let policy = PathPolicy::new();
+        policy.set_rules(HashMap::from([(
+            "ws-1".to_string(),
+            rules
+                .into_iter()
+                .map(|(action, pattern)| FsPathRule {
+                    action,
+                    pattern: pattern.to_string(),
+                })
+                .collect(),
+        )]));
+        policy
+    }
+
+    #[test]
+    fn test_allow_rules_approve_reads_only() {
+        let policy = policy_with(vec![(PolicyAction::Allow, "src/")]);
+        let ws = "ws-1".to_string();
+
+        assert_eq!(
+            policy.evaluate(&ws, "src/main.rs", PathAccess::Read),
+            Some(PolicyAction::Allow)
+        );
+        assert_eq!(
+            policy.evaluate(&ws, "src/nested/mod.rs", PathAccess::Read),
+            Some(PolicyAction::Allow)
+        );
+        // Writes under an allowed prefix still prompt
+        assert_eq!(policy.evaluate(&ws, "src/main.rs", PathAccess::Write), None);
+    }
+
+    #[test]
+    fn test_deny_rules_refuse_reads_and_writes() {
+        let policy = policy_with(vec![
+            (PolicyAction::Allow, "config"),
+            (PolicyAction::Deny, "config/secrets.json"),
+            (PolicyAction::Deny, "*.key"),
+        ]);
+        let ws = "ws-1".to_string();
+
+        for access in [PathAccess::Read, PathAccess::Write] {
+            assert_eq!(
+                policy.evaluate(&ws, "config/secrets.json", access),
+                Some(PolicyAction::Deny)
+            );
+            assert_eq!(
+                policy.evaluate(&ws, "deploy/server.key", access),
+                Some(PolicyAction::Deny)
+            );
+        }
+    }
+
+    #[test]
+    fn test_unmatched_paths_fall_through_to_prompt() {
+        let policy = policy_with(vec![(PolicyAction::Allow, "src")]);
+        let ws = "ws-1".to_string();
+
+        for access in [PathAccess::Read, PathAccess::Write] {
+            assert_eq!(policy.evaluate(&ws, "srcx/main.rs", access), None);
+            assert_eq!(policy.evaluate(&ws, "README.md", access), None);
+        }
+    }
+
+    #[test]
+    fn test_default_rules_still_apply_alongside_workspace_rules() {
+        let policy = policy_with(vec![(PolicyAction::Deny, "build")]);
+        let policy_allowing = policy_with(vec![
+            (PolicyAction::Allow, ".env"),
+            (PolicyAction::Allow, "src"),
+        ]);
+        let ws = "ws-1".to_string();
+
+        for path in [".env", ".git/HEAD", "credentials.json"] {
+            assert_eq!(
+                policy.evaluate(&ws, path, PathAccess::Read),
+                Some(PolicyAction::Deny),
+                "{path}"
+            );
+        }
+        // Deny wins, so an allow rule can't reopen a default deny
+        assert_eq!(
+            policy_allowing.evaluate(&ws, ".env", PathAccess::Read),
+            Some(PolicyAction::Deny)
+        );
+        assert_eq!(
+            policy_allowing.evaluate(&ws, "src/main.rs", PathAccess::Read),
+            Some(PolicyAction::Allow)
+        );
+
+        // Only turning the defaults off lifts them
+        policy_allowing.set_defaults_enabled(false);
+        assert_eq!(
+            policy_allowing.evaluate(&ws, ".env", PathAccess::Read),
+            Some(PolicyAction::Allow)
+        );
+        assert_eq!(
+            policy_allowing.evaluate(&ws, ".git/HEAD", PathAccess::Write),
+            None
+        );
+    }
+
+    #[test]
+    fn test_default_rules_deny_git_env_and_credentials() {
+        let policy = PathPolicy::new();
+        let ws = "ws-without-rules".to_string();
+
+        for path in [
+            ".git/config",
+            ".env",
+            "app/.env.local",
+            "credentials.json",
+            "certs/server.pem",
+        ] {
+            for access in [PathAccess::Read, PathAccess::Write] {
+                assert_eq!(
+                    policy.evaluate(&ws, path, access),
+                    Some(PolicyAction::Deny),
+                    "{path}"
+                );
+            }
+        }
+        assert_eq!(
+            policy.evaluate(&ws, ".github/ci.yml", PathAccess::Read),
+            None
+        );
+        assert_eq!(policy.evaluate(&ws, "src/env.rs", PathAccess::Write), None);
+    }
+}
@@ -0,0 +1,177 @@
+//! Audit trail of permission decisions.
+//!
+//! Every resolved permission request (answered, remembered, timed out or
+//! cancelled) is kept in a bounded in-memory ring buffer for display. When
+//! the user opts in via preferences, entries are also appended to a JSONL
+//! file so there's a durable record.
+
+use std::collections::VecDeque;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::api::types::{
+    OperationId, PermissionAuditDecision, PermissionAuditEntry, PermissionOrigin, PermissionSource,
+};
+
+/// How many decisions are kept in memory
+pub const AUDIT_CAPACITY: usize = 500;
+
+/// Bounded log of permission decisions.
+pub struct PermissionAudit {
+    entries: Mutex<VecDeque<PermissionAuditEntry>>,
+    capacity: usize,
+    /// JSONL file entries are appended to while file logging is enabled
+    file: Option<PathBuf>,
+    file_enabled: AtomicBool,
+}
+
+impl PermissionAudit {
+    /// Create an in-memory audit log keeping the last `capacity` decisions.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: Mutex::new(VecDeque::with_capacity(capacity)),
+            capacity,
+            file: None,
+            file_enabled: AtomicBool::new(false),
+        }
+    }
+
+    /// Set the JSONL file used when file logging is enabled.
+    pub fn with_file(mut self, path: PathBuf) -> Self {
+        self.file = Some(path);
+        self
+    }
+
+    /// Turn appending to the JSONL file on or off.
+    pub fn set_file_logging(&self, enabled: bool) {
+        self.file_enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Record a resolved permission request.
+    pub fn record(
+        &self,
+        operation_id: &OperationId,
+        source: PermissionSource,
+        origin: Option<PermissionOrigin>,
+        decision: PermissionAuditDecision,
+    ) {
+        let entry = PermissionAuditEntry {
+            operation_id: operation_id.clone(),
+            source,
+            origin,
+            decision,
+            decided_at_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as f64)
+                .unwrap_or(0.0),
+        };
+
+        if self.file_enabled.load(Ordering::Relaxed) {
+            if let Some(path) = self.file.as_ref() {
+                if let Err(e) = append_line(path, &entry) {
+                    log::warn!("Failed to append permission audit entry: {e}");
+                }
+            }
+        }
+
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if entries.len() >= self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+
+    /// Recent decisions, newest first, at most `limit` of them.
+    pub fn recent(&self, limit: usize) -> Vec<PermissionAuditEntry> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.iter().rev().take(limit).cloned().collect()
+    }
+}
+
+fn append_line(path: &Path, entry: &PermissionAuditEntry) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let line = serde_json::to_string(entry)?;
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    writeln!(file, "{line}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(command: &str) -> PermissionSource {
+        PermissionSource::TerminalRun {
+            command: command.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_audit_buffer_is_bounded_and_newest_first() {
+        let audit = PermissionAudit::new(2);
+        for (i, decision) in [
+            PermissionAuditDecision::AllowOnce,
+            PermissionAuditDecision::Deny,
+            PermissionAuditDecision::TimedOut,
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            audit.record(&format!("op-{i}"), command("ls"), None, decision);
+        }
+
+        let recent = audit.recent(10);
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].operation_id, "op-2");
+        assert_eq!(recent[0].decision, PermissionAuditDecision::TimedOut);
+        assert_eq!(recent[1].operation_id, "op-1");
+        assert_eq!(audit.recent(1).len(), 1);
+    }
+
+    #[test]
+    fn test_audit_file_logging_is_opt_in() {
+        let dir = std::env::temp_dir().join(format!("permission_audit_{}", uuid::Uuid::new_v4()));
+        let path = dir.join("permission_audit.jsonl");
+        let audit = PermissionAudit::new(AUDIT_CAPACITY).with_file(path.clone());
+
+        audit.record(
+            &"op-1".to_string(),
+            command("ls"),
+            None,
+            PermissionAuditDecision::AllowOnce,
+        );
+        assert!(!path.exists());
+
+        audit.set_file_logging(true);
+        audit.record(
+            &"op-2".to_string(),
+            command("ls"),
+            None,
+            PermissionAuditDecision::Deny,
+        );
+        audit.record(
+            &"op-3".to_string(),
+            command("ls"),
+            None,
+            PermissionAuditDecision::Cancelled,
+        );
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<PermissionAuditEntry> = contents
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].operation_id, "op-2");
+        assert_eq!(lines[1].decision, PermissionAuditDecision::Cancelled);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -12,8 +12,21 @@
 //! 4. User responds via `permission_respond` command
 //! 5. PermissionHub calls `respond()` which sends decision through oneshot
 //! 6. Background task receives decision and proceeds accordingly
+//!
+//! A request that times out or is cancelled emits `acp/permission_timed_out`
+//! so the frontend can dismiss its prompt.
+//!
+//! Answering `AllowAlways` also records a remembered rule (see
+//! `permission_rules`); matching requests from the same workspace/agent are
+//! then approved immediately without emitting an event.
+//!
+//! Terminal commands and file access may also be approved or refused up
+//! front by the workspace's rules from preferences (see `command_policy` and
+//! `path_policy`).
 
 use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex as StdMutex;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use tauri::{AppHandle, Emitter};
@@ -23,18 +36,72 @@ use tokio::time::timeout;
 /// Default timeout for permission requests (5 minutes)
 const PERMISSION_TIMEOUT: Duration = Duration::from_secs(300);
 
+/// How long requests wait for an answer before being denied.
+///
+/// `None` means wait indefinitely. Overrides apply to one kind of source
+/// (e.g. a longer window for plugin installs).
+#[derive(Debug, Clone)]
+struct PermissionTimeouts {
+    default: Option<Duration>,
+    overrides: HashMap<PermissionSourceKind, Option<Duration>>,
+}
+
+impl Default for PermissionTimeouts {
+    fn default() -> Self {
+        Self {
+            default: Some(PERMISSION_TIMEOUT),
+            overrides: HashMap::new(),
+        }
+    }
+}
+
+impl PermissionTimeouts {
+    fn for_source(&self, kind: PermissionSourceKind) -> Option<Duration> {
+        self.overrides.get(&kind).copied().unwrap_or(self.default)
+    }
+}
+
 use crate::api::types::{
-    AcpPermissionRequestedEvent, ApiError, OperationId, PermissionDecision, PermissionOrigin,
-    PermissionSource,
+    AcpPermissionRequestedEvent, AcpPermissionTimedOutEvent, ApiError, OperationId,
+    PermissionAuditDecision, PermissionAuditEntry, PermissionDecision, PermissionExpiryReason,
+    PermissionOption, PermissionOptionKind, PermissionOrigin, PermissionOutcome, PermissionRule,
+    PermissionSource, PermissionSourceKind, WorkspaceId,
 };
+use crate::runtime::command_policy::CommandPolicy;
+use crate::runtime::path_policy::{PathAccess, PathPolicy};
+use crate::runtime::permission_audit::{PermissionAudit, AUDIT_CAPACITY};
+use crate::runtime::permission_rules::PermissionRules;
+use crate::types::{FsPathRule, PolicyAction, TerminalCommandRule};
 
 /// Event name for permission requests
 pub const EVENT_PERMISSION_REQUESTED: &str = "acp/permission_requested";
 
+/// Event name for permission requests that timed out or were cancelled
+pub const EVENT_PERMISSION_TIMED_OUT: &str = "acp/permission_timed_out";
+
 /// A pending permission request awaiting user decision
 struct PendingPermission {
     /// Oneshot sender to deliver the decision
-    tx: oneshot::Sender<PermissionDecision>,
+    tx: oneshot::Sender<PermissionOutcome>,
+    /// Agent-defined options the user may pick from (empty for allow/deny)
+    options: Vec<PermissionOption>,
+    /// What was asked, kept for the audit log and for re-listing
+    source: PermissionSource,
+    origin: Option<PermissionOrigin>,
+    requested_at_ms: f64,
+}
+
+impl PendingPermission {
+    /// Rebuild the event the frontend was sent for this request.
+    fn to_event(&self, operation_id: &OperationId) -> AcpPermissionRequestedEvent {
+        AcpPermissionRequestedEvent {
+            operation_id: operation_id.clone(),
+            source: self.source.clone(),
+            requested_at_ms: self.requested_at_ms,
+            origin: self.origin.clone(),
+            options: self.options.clone(),
+        }
+    }
 }
 
 /// Global permission hub for managing permission requests and responses.
@@ -45,6 +112,16 @@ pub struct PermissionHub {
     app: AppHandle,
     /// Pending permission requests keyed by operation ID
     pending: Mutex<HashMap<OperationId, PendingPermission>>,
+    /// Remembered "Allow Always" grants
+    rules: PermissionRules,
+    /// Terminal command rules from preferences
+    command_policy: CommandPolicy,
+    /// File path rules from preferences
+    path_policy: PathPolicy,
+    /// Record of resolved requests
+    audit: PermissionAudit,
+    /// How long requests wait for an answer
+    timeouts: StdMutex<PermissionTimeouts>,
 }
 
 impl PermissionHub {
@@ -57,7 +134,182 @@ impl PermissionHub {
         Self {
             app,
             pending: Mutex::new(HashMap::new()),
+            rules: PermissionRules::new(),
+            command_policy: CommandPolicy::new(),
+            path_policy: PathPolicy::new(),
+            audit: PermissionAudit::new(AUDIT_CAPACITY),
+            timeouts: StdMutex::new(PermissionTimeouts::default()),
+        }
+    }
+
+    /// Set how long requests wait for an answer before being denied.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - Only this kind of request (`None`: the default for all kinds
+    ///   without an override)
+    /// * `timeout` - How long to wait (`None`: wait indefinitely)
+    pub fn set_timeout(&self, kind: Option<PermissionSourceKind>, timeout: Option<Duration>) {
+        let mut timeouts = self.timeouts.lock().unwrap_or_else(|e| e.into_inner());
+        match kind {
+            Some(kind) => {
+                timeouts.overrides.insert(kind, timeout);
+            }
+            None => timeouts.default = timeout,
+        }
+    }
+
+    /// Append audit entries to the JSONL file at `path` once file logging is enabled.
+    pub fn with_audit_file(mut self, path: PathBuf) -> Self {
+        self.audit = self.audit.with_file(path);
+        self
+    }
+
+    /// Turn the audit JSONL file on or off (driven by preferences).
+    pub fn set_audit_file_logging(&self, enabled: bool) {
+        self.audit.set_file_logging(enabled);
+    }
+
+    /// Recent permission decisions, newest first.
+    pub fn history(&self, limit: usize) -> Vec<PermissionAuditEntry> {
+        self.audit.recent(limit)
+    }
+
+    /// Keep remembered grants in the JSON file at `path` (loading any saved there).
+    pub fn with_rule_store(mut self, path: PathBuf) -> Self {
+        self.rules = PermissionRules::with_store(path);
+        self
+    }
+
+    /// All remembered "Allow Always" grants.
+    pub fn list_rules(&self) -> Vec<PermissionRule> {
+        self.rules.list()
+    }
+
+    /// Forget a remembered grant so matching requests prompt again.
+    pub fn revoke_rule(&self, rule_id: &str) -> Result<(), ApiError> {
+        self.rules.revoke(rule_id)?;
+        log::info!("Permission rule revoked: rule_id={rule_id}");
+        Ok(())
+    }
+
+    /// Replace the terminal command rules (driven by preferences), keyed by
+    /// workspace ID.
+    pub fn set_command_rules(&self, rules: HashMap<WorkspaceId, Vec<TerminalCommandRule>>) {
+        self.command_policy.set_rules(rules);
+    }
+
+    /// Replace the file path rules (driven by preferences), keyed by
+    /// workspace ID.
+    pub fn set_path_rules(&self, rules: HashMap<WorkspaceId, Vec<FsPathRule>>) {
+        self.path_policy.set_rules(rules);
+    }
+
+    /// Turn the default file deny rules on or off for every workspace
+    /// (driven by preferences).
+    pub fn set_default_path_denies(&self, enabled: bool) {
+        self.path_policy.set_defaults_enabled(enabled);
+    }
+
+    /// Answer a request from the workspace's terminal command or file path
+    /// rules.
+    ///
+    /// # Returns
+    /// * The outcome if a rule approves or refuses the request (recorded in
+    ///   the audit log), or `None` if no rule applies and the user should be
+    ///   asked via [`PermissionHub::request_with_options`]
+    pub fn apply_policy(
+        &self,
+        operation_id: &OperationId,
+        source: &PermissionSource,
+        origin: &PermissionOrigin,
+        options: &[PermissionOption],
+    ) -> Option<PermissionOutcome> {
+        let workspace_id = origin.workspace_id.as_ref()?;
+        let action = match source {
+            PermissionSource::TerminalRun { command } => {
+                self.command_policy.evaluate(workspace_id, command)?
+            }
+            PermissionSource::FsReadTextFile { resolved_path, .. } => {
+                self.path_policy
+                    .evaluate(workspace_id, resolved_path, PathAccess::Read)?
+            }
+            PermissionSource::FsWriteTextFile { resolved_path, .. }
+            | PermissionSource::FsDelete { resolved_path, .. } => {
+                self.path_policy
+                    .evaluate(workspace_id, resolved_path, PathAccess::Write)?
+            }
+            PermissionSource::FsRename {
+                resolved_from,
+                resolved_to,
+                ..
+            } => {
+                // Renames need both ends cleared; a deny on either refuses
+                [resolved_from, resolved_to]
+                    .into_iter()
+                    .filter_map(|path| {
+                        self.path_policy
+                            .evaluate(workspace_id, path, PathAccess::Write)
+                    })
+                    .next()?
+            }
+            PermissionSource::InstallPlugin { .. } => return None,
+        };
+        let (decision, audit_decision) = match action {
+            PolicyAction::Allow => (
+                PermissionDecision::AllowOnce,
+                PermissionAuditDecision::AllowedByRule,
+            ),
+            PolicyAction::Deny => (
+                PermissionDecision::Deny,
+                PermissionAuditDecision::DeniedByRule,
+            ),
+        };
+        log::info!(
+            "Permission {} by rule: operation_id={operation_id}, kind={:?}, workspace={workspace_id}",
+            if decision.is_allowed() {
+                "granted"
+            } else {
+                "denied"
+            },
+            source.kind()
+        );
+        self.audit.record(
+            operation_id,
+            source.clone(),
+            Some(origin.clone()),
+            audit_decision,
+        );
+        resolve_outcome(options, decision, None).ok()
+    }
+
+    /// Whether the workspace's terminal command rules refuse a command about
+    /// to be spawned, whatever was granted for it before. A refusal is
+    /// recorded in the audit log; allow rules are not consulted.
+    pub fn command_denied(
+        &self,
+        operation_id: &OperationId,
+        source: &PermissionSource,
+        origin: &PermissionOrigin,
+    ) -> bool {
+        let (Some(workspace_id), PermissionSource::TerminalRun { command }) =
+            (origin.workspace_id.as_ref(), source)
+        else {
+            return false;
+        };
+        if self.command_policy.evaluate(workspace_id, command) != Some(PolicyAction::Deny) {
+            return false;
         }
+        log::info!(
+            "Terminal command denied by rule: operation_id={operation_id}, workspace={workspace_id}"
+        );
+        self.audit.record(
+            operation_id,
+            source.clone(),
+            Some(origin.clone()),
+            PermissionAuditDecision::DeniedByRule,
+        );
+        true
     }
 
     /// Request permission for an operation.
@@ -76,37 +328,68 @@ impl PermissionHub {
     ///
     /// # Returns
     ///
-    /// The user's decision (AllowOnce or Deny), or an error if the channel was dropped.
+    /// The user's decision, or an error if the channel was dropped.
     pub async fn request(
         &self,
         operation_id: OperationId,
         source: PermissionSource,
         origin: Option<PermissionOrigin>,
     ) -> Result<PermissionDecision, ApiError> {
-        let (tx, rx) = oneshot::channel();
+        self.request_with_options(operation_id, source, origin, Vec::new())
+            .await
+            .map(|outcome| outcome.decision)
+    }
 
-        // Store the sender
-        {
-            let mut pending = self.pending.lock().await;
-            if pending.contains_key(&operation_id) {
-                return Err(ApiError::InvalidInput {
-                    message: format!("Operation already pending: {operation_id}"),
-                });
-            }
-            pending.insert(operation_id.clone(), PendingPermission { tx });
+    /// Request permission, offering the agent's own options to the user.
+    ///
+    /// Same flow as [`PermissionHub::request`]; the returned outcome carries
+    /// the chosen `option_id` when `options` is non-empty.
+    pub async fn request_with_options(
+        &self,
+        operation_id: OperationId,
+        source: PermissionSource,
+        origin: Option<PermissionOrigin>,
+        options: Vec<PermissionOption>,
+    ) -> Result<PermissionOutcome, ApiError> {
+        if let Some(rule) = self.rules.matching(&source, origin.as_ref()) {
+            log::debug!(
+                "Permission granted by remembered rule: operation_id={operation_id}, rule_id={}",
+                rule.rule_id
+            );
+            self.audit.record(
+                &operation_id,
+                source,
+                origin,
+                PermissionAuditDecision::Remembered,
+            );
+            return resolve_outcome(&options, PermissionDecision::AllowAlways, None);
         }
 
-        // Build event payload
+        let (tx, rx) = oneshot::channel();
         let requested_at_ms = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .map(|d| d.as_millis() as f64)
             .unwrap_or(0.0);
 
-        let event = AcpPermissionRequestedEvent {
-            operation_id: operation_id.clone(),
-            source,
-            requested_at_ms,
-            origin,
+        // Store the sender along with what the frontend needs to re-render the prompt
+        let event = {
+            let mut pending = self.pending.lock().await;
+            if pending.contains_key(&operation_id) {
+                return Err(ApiError::InvalidInput {
+                    message: format!("Operation already pending: {operation_id}"),
+                });
+            }
+            pending.insert(
+                operation_id.clone(),
+                PendingPermission {
+                    tx,
+                    options,
+                    source: source.clone(),
+                    origin: origin.clone(),
+                    requested_at_ms,
+                },
+            );
+            pending[&operation_id].to_event(&operation_id)
         };
 
         // Emit event to frontend
@@ -121,33 +404,69 @@ impl PermissionHub {
 
         log::debug!("Permission requested: operation_id={operation_id}");
 
-        // Await the decision with timeout to prevent indefinite hangs
-        match timeout(PERMISSION_TIMEOUT, rx).await {
-            Ok(Ok(decision)) => {
-                log::debug!("Permission decision received: operation_id={operation_id}, decision={decision:?}");
-                Ok(decision)
-            }
-            Ok(Err(_)) => {
-                // Channel was dropped without sending - this shouldn't happen in normal flow
-                log::warn!(
-                    "Permission channel dropped without response: operation_id={operation_id}"
-                );
-                Err(ApiError::IoError {
-                    message: "Permission request was cancelled".to_string(),
-                })
-            }
-            Err(_) => {
-                // Timeout - clean up pending entry and report error
-                {
-                    let mut pending = self.pending.lock().await;
-                    pending.remove(&operation_id);
-                }
-                log::warn!("Permission request timed out: operation_id={operation_id}");
-                Err(ApiError::IoError {
-                    message: "Permission request timed out".to_string(),
-                })
+        let wait = self
+            .timeouts
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .for_source(source.kind());
+        let outcome = await_outcome(
+            &self.pending,
+            &self.audit,
+            &operation_id,
+            rx,
+            wait,
+            |event| self.emit_timed_out(&event),
+        )
+        .await?;
+        if outcome.decision == PermissionDecision::AllowAlways {
+            match self.rules.remember(&source, origin.as_ref()) {
+                Some(rule) => log::info!(
+                    "Permission rule remembered: rule_id={}, pattern={}",
+                    rule.rule_id,
+                    rule.pattern
+                ),
+                None => log::debug!(
+                    "AllowAlways treated as AllowOnce (source can't be remembered): operation_id={operation_id}"
+                ),
             }
         }
+        Ok(outcome)
+    }
+
+    /// Requests still awaiting a decision, oldest first.
+    ///
+    /// Lets the frontend re-render prompts whose `acp/permission_requested`
+    /// event it missed (e.g. after a reload). Entries leave the list as soon
+    /// as they are answered, time out or are cancelled.
+    pub async fn list_pending(&self) -> Vec<AcpPermissionRequestedEvent> {
+        pending_events(&*self.pending.lock().await)
+    }
+
+    /// Cancel a pending permission request.
+    ///
+    /// Used when the operation that asked is abandoned (turn cancelled,
+    /// install aborted). The awaiting `request()` returns `ApiError::Cancelled`.
+    /// Racing with `respond` is safe: whichever removes the entry first wins
+    /// and the other gets `OperationNotFound`.
+    ///
+    /// # Errors
+    ///
+    /// - `OperationNotFound` if no pending request exists for this ID
+    pub async fn cancel(&self, operation_id: OperationId) -> Result<(), ApiError> {
+        cancel_pending(&self.pending, &self.audit, operation_id, |event| {
+            self.emit_timed_out(&event)
+        })
+        .await
+    }
+
+    /// Tell the frontend a prompt can no longer be answered.
+    fn emit_timed_out(&self, event: &AcpPermissionTimedOutEvent) {
+        if let Err(e) = self.app.emit(EVENT_PERMISSION_TIMED_OUT, event) {
+            log::error!(
+                "Failed to emit acp/permission_timed_out: operation_id={}, error={e}",
+                event.operation_id
+            );
+        }
     }
 
     /// Respond to a pending permission request.
@@ -158,27 +477,43 @@ impl PermissionHub {
     /// # Arguments
     ///
     /// * `operation_id` - The operation to respond to
-    /// * `decision` - The user's decision (AllowOnce or Deny)
+    /// * `decision` - The user's decision (AllowOnce, AllowAlways or Deny)
+    /// * `option_id` - The agent-defined option chosen, if the request offered any
     ///
     /// # Errors
     ///
     /// - `OperationNotFound` if no pending request exists for this ID
     /// - `OperationAlreadyResolved` if the request was already responded to
+    /// - `InvalidInput` if `option_id` is not one of the offered options
     pub async fn respond(
         &self,
         operation_id: OperationId,
         decision: PermissionDecision,
+        option_id: Option<String>,
     ) -> Result<(), ApiError> {
         let pending_op = {
             let mut pending = self.pending.lock().await;
-            pending.remove(&operation_id)
+            match pending.get(&operation_id) {
+                // Validate before removing so a bad option id can be retried
+                Some(entry) => {
+                    let outcome = resolve_outcome(&entry.options, decision, option_id)?;
+                    pending
+                        .remove(&operation_id)
+                        .map(|pending| (pending, outcome))
+                }
+                None => None,
+            }
         };
 
         match pending_op {
-            Some(pending) => {
+            Some((pending, outcome)) => {
+                let decision = outcome.decision;
+                let PendingPermission {
+                    tx, source, origin, ..
+                } = pending;
                 // Send the decision - if this fails, the receiver was already dropped
                 // (e.g., request timed out or was cancelled)
-                if pending.tx.send(decision).is_err() {
+                if tx.send(outcome).is_err() {
                     log::warn!(
                         "Failed to send permission decision (receiver dropped): operation_id={operation_id}"
                     );
@@ -187,6 +522,8 @@ impl PermissionHub {
                 log::info!(
                     "Permission responded: operation_id={operation_id}, decision={decision:?}"
                 );
+                self.audit
+                    .record(&operation_id, source, origin, decision.into());
                 Ok(())
             }
             None => {
@@ -198,6 +535,344 @@ impl PermissionHub {
     }
 }
 
+/// Await the decision for a pending request, giving up after `wait` (if set)
+/// so it can't hang forever. A dropped sender means the request was cancelled.
+/// `notify` is told when the request times out.
+async fn await_outcome(
+    pending: &Mutex<HashMap<OperationId, PendingPermission>>,
+    audit: &PermissionAudit,
+    operation_id: &OperationId,
+    rx: oneshot::Receiver<PermissionOutcome>,
+    wait: Option<Duration>,
+    notify: impl Fn(AcpPermissionTimedOutEvent),
+) -> Result<PermissionOutcome, ApiError> {
+    let received = match wait {
+        Some(wait) => timeout(wait, rx).await,
+        None => Ok(rx.await),
+    };
+    match received {
+        Ok(Ok(outcome)) => {
+            log::debug!(
+                "Permission decision received: operation_id={operation_id}, decision={:?}, option_id={:?}",
+                outcome.decision,
+                outcome.option_id
+            );
+            Ok(outcome)
+        }
+        Ok(Err(_)) => {
+            log::info!("Permission request cancelled: operation_id={operation_id}");
+            Err(ApiError::Cancelled {
+                message: format!("Permission request {operation_id} was cancelled"),
+            })
+        }
+        Err(_) => {
+            // Timeout - clean up pending entry and report error
+            // Already gone if it was answered or cancelled right at the deadline
+            if let Some(entry) = pending.lock().await.remove(operation_id) {
+                audit.record(
+                    operation_id,
+                    entry.source,
+                    entry.origin,
+                    PermissionAuditDecision::TimedOut,
+                );
+                notify(AcpPermissionTimedOutEvent {
+                    operation_id: operation_id.clone(),
+                    reason: PermissionExpiryReason::TimedOut,
+                });
+            }
+            log::warn!("Permission request timed out: operation_id={operation_id}");
+            Err(ApiError::IoError {
+                message: "Permission request timed out".to_string(),
+            })
+        }
+    }
+}
+
+/// Snapshot pending requests as request events, oldest first.
+fn pending_events(
+    pending: &HashMap<OperationId, PendingPermission>,
+) -> Vec<AcpPermissionRequestedEvent> {
+    let mut events: Vec<_> = pending
+        .iter()
+        .map(|(operation_id, entry)| entry.to_event(operation_id))
+        .collect();
+    events.sort_by(|a, b| a.requested_at_ms.total_cmp(&b.requested_at_ms));
+    events
+}
+
+/// Remove a pending request without answering it, dropping its sender.
+/// `notify` is told once the request is removed.
+async fn cancel_pending(
+    pending: &Mutex<HashMap<OperationId, PendingPermission>>,
+    audit: &PermissionAudit,
+    operation_id: OperationId,
+    notify: impl Fn(AcpPermissionTimedOutEvent),
+) -> Result<(), ApiError> {
+    let removed = pending.lock().await.remove(&operation_id);
+    match removed {
+        // Dropping the entry drops its sender, waking the requester
+        Some(entry) => {
+            audit.record(
+                &operation_id,
+                entry.source,
+                entry.origin,
+                PermissionAuditDecision::Cancelled,
+            );
+            notify(AcpPermissionTimedOutEvent {
+                operation_id,
+                reason: PermissionExpiryReason::Cancelled,
+            });
+            Ok(())
+        }
+        None => Err(ApiError::OperationNotFound { operation_id }),
+    }
+}
+
+/// Work out the outcome of a response against the options that were offered.
+///
+/// An explicit `option_id` decides on its own (its kind implies allow/deny).
+/// Without one, the first offered option matching `decision` is chosen,
+/// preferring the one-time kinds, so frontends that only know allow/deny
+/// still answer option-based requests.
+fn resolve_outcome(
+    options: &[PermissionOption],
+    decision: PermissionDecision,
+    option_id: Option<String>,
+) -> Result<PermissionOutcome, ApiError> {
+    if let Some(option_id) = option_id {
+        let option = options
+            .iter()
+            .find(|option| option.option_id == option_id)
+            .ok_or_else(|| ApiError::InvalidInput {
+                message: format!("Unknown permission option: {option_id}"),
+            })?;
+        return Ok(PermissionOutcome {
+            decision: option.kind.decision(),
+            option_id: Some(option_id),
+        });
+    }
+
+    let preferred = match decision {
+        PermissionDecision::AllowOnce => PermissionOptionKind::AllowOnce,
+        PermissionDecision::AllowAlways => PermissionOptionKind::AllowAlways,
+        PermissionDecision::Deny => PermissionOptionKind::RejectOnce,
+    };
+    let option = options
+        .iter()
+        .find(|option| option.kind == preferred)
+        .or_else(|| {
+            options
+                .iter()
+                .find(|option| option.kind.decision().is_allowed() == decision.is_allowed())
+        });
+
+    Ok(PermissionOutcome {
+        decision,
+        option_id: option.map(|option| option.option_id.clone()),
+    })
+}
+
 // Note: Testing PermissionHub requires a real AppHandle for event emission.
-// Unit testing this module would require refactoring to inject the event emitter
-// behind a trait. For now, integration tests should cover the permission flow.
+// Unit testing the hub would require refactoring to inject the event emitter
+// behind a trait; the pending-map helpers and option resolution logic are
+// covered here instead.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn option(option_id: &str, kind: PermissionOptionKind) -> PermissionOption {
+        PermissionOption {
+            option_id: option_id.to_string(),
+            name: option_id.to_string(),
+            kind,
+        }
+    }
+
+    /// Register a pending terminal request and return its receiver.
+    async fn insert_pending(
+        pending: &Mutex<HashMap<OperationId, PendingPermission>>,
+        operation_id: &str,
+    ) -> oneshot::Receiver<PermissionOutcome> {
+        let (tx, rx) = oneshot::channel();
+        pending.lock().await.insert(
+            operation_id.to_string(),
+            PendingPermission {
+                tx,
+                options: Vec::new(),
+                source: PermissionSource::TerminalRun {
+                    command: "ls".to_string(),
+                },
+                origin: None,
+                requested_at_ms: 1.0,
+            },
+        );
+        rx
+    }
+
+    /// Collects the events passed to `notify`.
+    fn recorder() -> std::sync::Mutex<Vec<AcpPermissionTimedOutEvent>> {
+        std::sync::Mutex::new(Vec::new())
+    }
+
+    #[tokio::test]
+    async fn test_cancel_resolves_pending_request_and_unlists_it() {
+        let pending = Mutex::new(HashMap::new());
+        let audit = PermissionAudit::new(AUDIT_CAPACITY);
+        let rx = insert_pending(&pending, "op-1").await;
+        let expired = recorder();
+        let notify = |event| expired.lock().unwrap().push(event);
+
+        let listed = pending_events(&*pending.lock().await);
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].operation_id, "op-1");
+
+        let waiting = await_outcome(
+            &pending,
+            &audit,
+            &"op-1".to_string(),
+            rx,
+            Some(PERMISSION_TIMEOUT),
+            notify,
+        );
+        let (outcome, cancelled) = tokio::join!(
+            waiting,
+            cancel_pending(&pending, &audit, "op-1".to_string(), notify)
+        );
+
+        assert!(cancelled.is_ok());
+        assert!(matches!(outcome, Err(ApiError::Cancelled { .. })));
+        assert!(pending_events(&*pending.lock().await).is_empty());
+        let history = audit.recent(10);
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].decision, PermissionAuditDecision::Cancelled);
+        {
+            let expired = expired.lock().unwrap();
+            assert_eq!(expired.len(), 1);
+            assert_eq!(expired[0].operation_id, "op-1");
+            assert_eq!(expired[0].reason, PermissionExpiryReason::Cancelled);
+        }
+
+        // Only one of cancel/respond can win; the entry is gone now
+        assert!(matches!(
+            cancel_pending(&pending, &audit, "op-1".to_string(), notify).await,
+            Err(ApiError::OperationNotFound { .. })
+        ));
+        assert_eq!(expired.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_short_timeout_fires() {
+        let pending = Mutex::new(HashMap::new());
+        let audit = PermissionAudit::new(AUDIT_CAPACITY);
+        let rx = insert_pending(&pending, "op-1").await;
+        let expired = recorder();
+
+        let outcome = await_outcome(
+            &pending,
+            &audit,
+            &"op-1".to_string(),
+            rx,
+            Some(Duration::from_millis(10)),
+            |event| expired.lock().unwrap().push(event),
+        )
+        .await;
+
+        assert!(matches!(outcome, Err(ApiError::IoError { .. })));
+        assert!(pending.lock().await.is_empty());
+        assert_eq!(
+            audit.recent(1)[0].decision,
+            PermissionAuditDecision::TimedOut
+        );
+        let expired = expired.lock().unwrap();
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].operation_id, "op-1");
+        assert_eq!(expired[0].reason, PermissionExpiryReason::TimedOut);
+    }
+
+    #[tokio::test]
+    async fn test_no_timeout_keeps_waiting() {
+        let pending = Mutex::new(HashMap::new());
+        let audit = PermissionAudit::new(AUDIT_CAPACITY);
+        let rx = insert_pending(&pending, "op-1").await;
+
+        let still_waiting = timeout(
+            Duration::from_millis(50),
+            await_outcome(&pending, &audit, &"op-1".to_string(), rx, None, |_| {
+                panic!("nothing should expire")
+            }),
+        )
+        .await;
+
+        assert!(still_waiting.is_err());
+        assert!(pending.lock().await.contains_key("op-1"));
+        assert!(audit.recent(1).is_empty());
+    }
+
+    #[test]
+    fn test_timeout_overrides_apply_per_source_kind() {
+        let mut timeouts = PermissionTimeouts::default();
+        assert_eq!(
+            timeouts.for_source(PermissionSourceKind::InstallPlugin),
+            Some(PERMISSION_TIMEOUT)
+        );
+
+        timeouts
+            .overrides
+            .insert(PermissionSourceKind::InstallPlugin, None);
+        timeouts.default = Some(Duration::from_secs(30));
+        assert_eq!(
+            timeouts.for_source(PermissionSourceKind::InstallPlugin),
+            None
+        );
+        assert_eq!(
+            timeouts.for_source(PermissionSourceKind::FsWriteTextFile),
+            Some(Duration::from_secs(30))
+        );
+    }
+
+    #[test]
+    fn test_resolve_outcome_uses_chosen_or_matching_option() {
+        let options = vec![
+            option("always", PermissionOptionKind::AllowAlways),
+            option("once", PermissionOptionKind::AllowOnce),
+            option("never", PermissionOptionKind::RejectAlways),
+        ];
+
+        // Explicit choice wins, and its kind decides allow/deny
+        let chosen = resolve_outcome(
+            &options,
+            PermissionDecision::AllowOnce,
+            Some("never".into()),
+        )
+        .unwrap();
+        assert_eq!(chosen.decision, PermissionDecision::Deny);
+        assert_eq!(chosen.option_id.as_deref(), Some("never"));
+
+        // Plain allow/deny maps onto the closest offered option
+        let allow = resolve_outcome(&options, PermissionDecision::AllowOnce, None).unwrap();
+        assert_eq!(allow.option_id.as_deref(), Some("once"));
+        let deny = resolve_outcome(&options, PermissionDecision::Deny, None).unwrap();
+        assert_eq!(deny.option_id.as_deref(), Some("never"));
+        let always = resolve_outcome(&options, PermissionDecision::AllowAlways, None).unwrap();
+        assert_eq!(always.option_id.as_deref(), Some("always"));
+
+        // Without an "always" option, a remembered grant answers with any allow option
+        let once_only = vec![option("once", PermissionOptionKind::AllowOnce)];
+        let remembered =
+            resolve_outcome(&once_only, PermissionDecision::AllowAlways, None).unwrap();
+        assert_eq!(remembered.option_id.as_deref(), Some("once"));
+
+        // No options: classic two-choice behavior
+        let plain = resolve_outcome(&[], PermissionDecision::AllowOnce, None).unwrap();
+        assert_eq!(plain.option_id, None);
+
+        assert!(matches!(
+            resolve_outcome(
+                &options,
+                PermissionDecision::AllowOnce,
+                Some("bogus".into())
+            ),
+            Err(ApiError::InvalidInput { .. })
+        ));
+    }
+}
@@ -8,22 +8,80 @@
 //! 5. Emits status change events
 
 use std::collections::HashSet;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
-use tauri::{AppHandle, Emitter};
-use tokio::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
 use uuid::Uuid;
 
 use crate::api::types::{
-    AcpPluginStatusChangedEvent, ApiError, OperationId, OperationStarted, PermissionDecision,
-    PermissionSource,
+    AcpPluginStatusChangedEvent, ApiError, NotificationLevel, OperationId, OperationStarted,
+    PermissionDecision, PermissionSource,
 };
 use crate::plugins::manager::PluginManager;
+use crate::plugins::package_manager::PackageManager;
+use crate::runtime::notifier::Notifier;
 use crate::runtime::permissions::PermissionHub;
+use crate::runtime::workspace_manager::WorkspaceManager;
 
 /// Event name for plugin status changes
 pub const EVENT_PLUGIN_STATUS_CHANGED: &str = "acp/plugin_status_changed";
 
+/// Set of plugin IDs with an install in flight.
+///
+/// Slots are released when the returned [`InstallSlot`] is dropped, so a plugin
+/// can never stay marked as installing once its task ends, whatever the outcome.
+#[derive(Clone, Default)]
+pub(crate) struct InstallingSet(Arc<Mutex<HashSet<String>>>);
+
+impl InstallingSet {
+    /// Mark `plugin_id` as installing, or return `None` if it already is.
+    pub(crate) fn try_acquire(&self, plugin_id: &str) -> Option<InstallSlot> {
+        let mut installing = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        if !installing.insert(plugin_id.to_string()) {
+            return None;
+        }
+        Some(InstallSlot {
+            set: self.clone(),
+            plugin_id: plugin_id.to_string(),
+        })
+    }
+
+    #[cfg(test)]
+    fn contains(&self, plugin_id: &str) -> bool {
+        self.0
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .contains(plugin_id)
+    }
+}
+
+/// Guard that removes its plugin from the [`InstallingSet`] on drop.
+pub(crate) struct InstallSlot {
+    set: InstallingSet,
+    plugin_id: String,
+}
+
+impl Drop for InstallSlot {
+    fn drop(&mut self) {
+        self.set
+            .0
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&self.plugin_id);
+    }
+}
+
+/// Work a background task performs once the user approves it.
+enum InstallAction {
+    /// Fresh install or upgrade
+    Install {
+        version: Option<String>,
+        package_manager: Option<PackageManager>,
+    },
+    /// Wipe install artifacts and reinstall the recorded version
+    Repair,
+}
+
 /// Orchestrator for plugin installation with permission flow.
 ///
 /// This singleton is injected via `app.manage(Arc::new(PluginInstaller::new(...)))`.
@@ -35,7 +93,7 @@ pub struct PluginInstaller {
     /// Plugin manager for actual installation
     plugin_manager: Arc<PluginManager>,
     /// Set of plugin IDs currently being installed (for serialization)
-    installing: Mutex<HashSet<String>>,
+    installing: InstallingSet,
 }
 
 impl PluginInstaller {
@@ -55,7 +113,7 @@ impl PluginInstaller {
             app,
             permission_hub,
             plugin_manager,
-            installing: Mutex::new(HashSet::new()),
+            installing: InstallingSet::default(),
         }
     }
 
@@ -72,12 +130,14 @@ impl PluginInstaller {
     /// - Request permission from the user
     /// - On approval: install the plugin and emit status change
     /// - On denial: exit cleanly
-    /// - Always: remove the plugin from the installing set
+    /// - Always: remove the plugin from the installing set (including on
+    ///   install timeout or panic)
     ///
     /// # Arguments
     ///
     /// * `plugin_id` - Plugin identifier (e.g., "claude-code")
     /// * `version` - Optional version to install
+    /// * `package_manager` - Optional package manager (auto-detected if None)
     ///
     /// # Returns
     ///
@@ -87,38 +147,80 @@ impl PluginInstaller {
         self: &Arc<Self>,
         plugin_id: String,
         version: Option<String>,
+        package_manager: Option<PackageManager>,
+    ) -> Result<OperationStarted, ApiError> {
+        log::info!("Starting plugin install: plugin_id={plugin_id}, version={version:?}");
+
+        self.start_task(
+            plugin_id,
+            InstallAction::Install {
+                version,
+                package_manager,
+            },
+        )
+    }
+
+    /// Start a plugin repair operation.
+    ///
+    /// Like `start_install`, but once approved it removes `node_modules`,
+    /// `package.json`, and `install.json` before reinstalling the previously
+    /// recorded version. Used to recover from interrupted installs.
+    ///
+    /// # Arguments
+    ///
+    /// * `plugin_id` - Plugin identifier (e.g., "claude-code")
+    ///
+    /// # Returns
+    ///
+    /// `OperationStarted` with the operation ID, or an error if validation fails
+    /// or the plugin is already being installed.
+    pub async fn start_repair(
+        self: &Arc<Self>,
+        plugin_id: String,
+    ) -> Result<OperationStarted, ApiError> {
+        log::info!("Starting plugin repair: plugin_id={plugin_id}");
+
+        self.start_task(plugin_id, InstallAction::Repair)
+    }
+
+    /// Plugins with an install or repair in flight.
+    ///
+    /// Holding a slot keeps a plugin from being installed meanwhile.
+    pub(crate) fn installing(&self) -> &InstallingSet {
+        &self.installing
+    }
+
+    /// Validate, reserve the plugin's install slot, and spawn the background task.
+    fn start_task(
+        self: &Arc<Self>,
+        plugin_id: String,
+        action: InstallAction,
     ) -> Result<OperationStarted, ApiError> {
         // Validate plugin ID early
         PluginManager::validate_plugin_id(&plugin_id)?;
 
-        // Check if already installing this plugin
-        {
-            let mut installing = self.installing.lock().await;
-            if installing.contains(&plugin_id) {
-                return Err(ApiError::PluginInstallInProgress {
-                    plugin_id: plugin_id.clone(),
-                });
+        // Mark as installing; the slot is released when the task finishes
+        let slot = self.installing.try_acquire(&plugin_id).ok_or_else(|| {
+            ApiError::PluginInstallInProgress {
+                plugin_id: plugin_id.clone(),
             }
-            // Mark as installing
-            installing.insert(plugin_id.clone());
-        }
+        })?;
 
         // Generate operation ID
         let operation_id = Uuid::new_v4().to_string();
 
-        log::info!(
-            "Starting plugin install: plugin_id={plugin_id}, version={version:?}, operation_id={operation_id}"
+        log::debug!(
+            "Plugin install task queued: plugin_id={plugin_id}, operation_id={operation_id}"
         );
 
         // Clone what we need for the background task
         let installer = Arc::clone(self);
         let op_id = operation_id.clone();
-        let pid = plugin_id.clone();
-        let ver = version.clone();
 
         // Spawn background task
         tauri::async_runtime::spawn(async move {
-            installer.run_install_task(op_id, pid, ver).await;
+            let _slot = slot;
+            installer.run_install_task(op_id, plugin_id, action).await;
         });
 
         Ok(OperationStarted { operation_id })
@@ -129,12 +231,21 @@ impl PluginInstaller {
         &self,
         operation_id: OperationId,
         plugin_id: String,
-        version: Option<String>,
+        action: InstallAction,
     ) {
-        // Build permission source
+        // Build permission source (a repair reinstalls the recorded version)
+        let version = match &action {
+            InstallAction::Install { version, .. } => version.clone(),
+            InstallAction::Repair => self
+                .plugin_manager
+                .get_status(plugin_id.clone(), false)
+                .await
+                .ok()
+                .and_then(|status| status.installed_version),
+        };
         let source = PermissionSource::InstallPlugin {
             plugin_id: plugin_id.clone(),
-            version: version.clone(),
+            version,
         };
 
         // Request permission (no origin context for user-initiated install)
@@ -144,16 +255,30 @@ impl PluginInstaller {
             .await;
 
         match decision {
-            Ok(PermissionDecision::AllowOnce) => {
+            Ok(PermissionDecision::AllowOnce | PermissionDecision::AllowAlways) => {
                 log::info!(
                     "Permission granted for plugin install: plugin_id={plugin_id}, operation_id={operation_id}"
                 );
 
-                // Perform installation
-                let install_result = self
-                    .plugin_manager
-                    .install(plugin_id.clone(), version)
-                    .await;
+                // An agent may have started on the plugin while the prompt
+                // was open, so check again right before touching its files
+                let is_repair = matches!(action, InstallAction::Repair);
+                let install_result = match self.ensure_not_in_use(&plugin_id).await {
+                    Err(e) => Err(e),
+                    Ok(()) => match action {
+                        InstallAction::Install {
+                            version,
+                            package_manager,
+                        } => {
+                            self.plugin_manager
+                                .install(plugin_id.clone(), version, package_manager)
+                                .await
+                        }
+                        InstallAction::Repair => {
+                            self.plugin_manager.repair(plugin_id.clone()).await
+                        }
+                    },
+                };
 
                 // Get current status for the event
                 let status = self
@@ -188,11 +313,13 @@ impl PluginInstaller {
                     );
                 }
 
-                if let Err(e) = install_result {
+                if let Err(e) = &install_result {
                     log::error!(
                         "Plugin installation failed: plugin_id={plugin_id}, error={e}, operation_id={operation_id}"
                     );
                 }
+
+                self.notify_install_finished(&plugin_id, is_repair, &install_result);
             }
             Ok(PermissionDecision::Deny) => {
                 log::info!(
@@ -208,16 +335,49 @@ impl PluginInstaller {
             }
         }
 
-        // Always remove from installing set
-        {
-            let mut installing = self.installing.lock().await;
-            installing.remove(&plugin_id);
-        }
-
         log::debug!(
             "Plugin install task completed: plugin_id={plugin_id}, operation_id={operation_id}"
         );
     }
+
+    /// Refuse to modify a plugin a running agent uses.
+    async fn ensure_not_in_use(&self, plugin_id: &str) -> Result<(), ApiError> {
+        let Some(workspace_manager) = self.app.try_state::<Arc<WorkspaceManager>>() else {
+            return Ok(());
+        };
+        let workspace_manager = workspace_manager.inner().clone();
+        workspace_manager.ensure_plugin_not_in_use(plugin_id).await
+    }
+
+    /// Alert the user that an install or repair finished (or failed).
+    fn notify_install_finished(
+        &self,
+        plugin_id: &str,
+        is_repair: bool,
+        result: &Result<(), ApiError>,
+    ) {
+        let Some(notifier) = self.app.try_state::<Arc<Notifier>>() else {
+            return;
+        };
+        let action = if is_repair { "repair" } else { "install" };
+        match result {
+            Ok(()) => {
+                let done = if is_repair { "repaired" } else { "installed" };
+                notifier.notify(
+                    &format!("{plugin_id} {done}"),
+                    None,
+                    NotificationLevel::Success,
+                );
+            }
+            Err(e) => {
+                notifier.notify(
+                    &format!("Failed to {action} {plugin_id}"),
+                    Some(&e.to_string()),
+                    NotificationLevel::Error,
+                );
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -234,4 +394,33 @@ mod tests {
         assert!(PluginManager::validate_plugin_id("").is_err());
         assert!(PluginManager::validate_plugin_id("../etc").is_err());
     }
+
+    #[test]
+    fn test_installing_set_rejects_concurrent_install() {
+        let set = InstallingSet::default();
+        let _slot = set.try_acquire("claude-code").expect("first acquire");
+
+        assert!(set.try_acquire("claude-code").is_none());
+        assert!(set.try_acquire("codex").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_installing_set_released_after_timed_out_task() {
+        let set = InstallingSet::default();
+        let slot = set.try_acquire("claude-code").expect("first acquire");
+
+        // Simulate an install task that hangs until its timeout fires
+        let task = tokio::spawn(async move {
+            let _slot = slot;
+            tokio::time::timeout(
+                std::time::Duration::from_millis(50),
+                std::future::pending::<()>(),
+            )
+            .await
+        });
+
+        assert!(task.await.unwrap().is_err());
+        assert!(!set.contains("claude-code"));
+        assert!(set.try_acquire("claude-code").is_some());
+    }
 }
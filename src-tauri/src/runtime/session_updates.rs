@@ -0,0 +1,461 @@
+//! Ordered delivery of ACP session updates to the frontend.
+//!
+//! Updates reach the agent host from several tasks at once (the adapter's
+//! stdout reader, concurrently handled adapter requests, `cancel_turn`).
+//! Numbering and emitting them at each call site lets a later `seq` overtake
+//! an earlier one. Instead every update is queued here and a single task
+//! assigns `seq` and emits in queue order, so emit order always matches `seq`.
+//!
+//! The queue is unbounded because `AgentHost::on_session_update` is
+//! synchronous and must not block or drop updates; the emitter task does no
+//! I/O besides emitting, so it keeps up with the adapter.
+//!
+//! With [`ChunkCoalescing`] turned on, consecutive text chunks of the same
+//! session, turn and kind (message or thought) arriving within the window are
+//! merged into one update before they get their `seq`. Any other update ends
+//! the batch, so it is emitted right after the chunks that preceded it.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+
+use crate::api::types::{
+    AcpSessionUpdate, AcpSessionUpdateEvent, AgentId, SessionId, TurnId, WorkspaceId,
+};
+
+/// Longest window chunks may be held back for merging
+pub const MAX_COALESCE_WINDOW: Duration = Duration::from_millis(250);
+
+/// The agent an emitter's events are about: its IDs and its labels.
+#[derive(Debug, Clone)]
+pub struct AgentLabels {
+    pub workspace_id: WorkspaceId,
+    pub agent_id: AgentId,
+    pub plugin_id: String,
+    pub display_name: Option<String>,
+}
+
+/// An update waiting for its `seq`.
+struct QueuedUpdate {
+    session_id: SessionId,
+    turn_id: Option<TurnId>,
+    update: AcpSessionUpdate,
+}
+
+/// How long message and thought chunks are held back to merge with the ones
+/// that follow (off by default).
+///
+/// Shared by every emitter, so changes apply to batches started afterwards.
+#[derive(Debug, Default)]
+pub struct ChunkCoalescing {
+    window_ms: AtomicU32,
+}
+
+impl ChunkCoalescing {
+    /// Coalescing with the given window; see [`Self::set_window`].
+    pub fn new(window: Option<Duration>) -> Self {
+        let coalescing = Self::default();
+        coalescing.set_window(window);
+        coalescing
+    }
+
+    /// Set the window, capped at [`MAX_COALESCE_WINDOW`]; `None` or zero
+    /// turns coalescing off.
+    pub fn set_window(&self, window: Option<Duration>) {
+        let millis = window
+            .unwrap_or_default()
+            .min(MAX_COALESCE_WINDOW)
+            .as_millis();
+        self.window_ms.store(millis as u32, Ordering::Relaxed);
+    }
+
+    /// The current window, or `None` while coalescing is off.
+    pub fn window(&self) -> Option<Duration> {
+        match self.window_ms.load(Ordering::Relaxed) {
+            0 => None,
+            millis => Some(Duration::from_millis(u64::from(millis))),
+        }
+    }
+}
+
+/// Sender side of an agent host's session update queue.
+///
+/// Dropping it ends the emitter task once the queued updates are emitted.
+pub struct SessionUpdateEmitter {
+    tx: mpsc::UnboundedSender<QueuedUpdate>,
+}
+
+impl SessionUpdateEmitter {
+    /// Start the emitter task; `emit` is called for each update, in order.
+    pub fn spawn<F>(labels: AgentLabels, emit: F) -> Self
+    where
+        F: Fn(&AcpSessionUpdateEvent) + Send + 'static,
+    {
+        Self::spawn_coalescing(labels, Arc::new(ChunkCoalescing::default()), emit)
+    }
+
+    /// Like [`Self::spawn`], merging text chunks as `coalescing` directs.
+    pub fn spawn_coalescing<F>(
+        labels: AgentLabels,
+        coalescing: Arc<ChunkCoalescing>,
+        emit: F,
+    ) -> Self
+    where
+        F: Fn(&AcpSessionUpdateEvent) + Send + 'static,
+    {
+        let (tx, mut rx) = mpsc::unbounded_channel::<QueuedUpdate>();
+        tokio::spawn(async move {
+            let mut seq: u64 = 0;
+            let mut emit_one = |queued: QueuedUpdate| {
+                let event = AcpSessionUpdateEvent {
+                    workspace_id: labels.workspace_id.clone(),
+                    agent_id: labels.agent_id.clone(),
+                    plugin_id: labels.plugin_id.clone(),
+                    display_name: labels.display_name.clone(),
+                    session_id: queued.session_id,
+                    seq,
+                    emitted_at_ms: now_ms(),
+                    turn_id: queued.turn_id,
+                    update: queued.update,
+                };
+                seq += 1;
+                emit(&event);
+            };
+
+            // Chunk held back for merging, and when it must be emitted
+            let mut held: Option<(QueuedUpdate, Instant)> = None;
+            loop {
+                let deadline = held.as_ref().map(|(_, deadline)| *deadline);
+                let next = match deadline {
+                    Some(deadline) => match tokio::time::timeout_at(deadline, rx.recv()).await {
+                        Ok(next) => next,
+                        Err(_) => {
+                            if let Some((chunk, _)) = held.take() {
+                                emit_one(chunk);
+                            }
+                            continue;
+                        }
+                    },
+                    None => rx.recv().await,
+                };
+                let Some(queued) = next else {
+                    break;
+                };
+
+                if let Some((chunk, _)) = &mut held {
+                    if merge_chunk(chunk, &queued) {
+                        continue;
+                    }
+                }
+                if let Some((chunk, _)) = held.take() {
+                    emit_one(chunk);
+                }
+                match coalescing.window() {
+                    Some(window) if text_chunk(&queued.update).is_some() => {
+                        held = Some((queued, Instant::now() + window));
+                    }
+                    _ => emit_one(queued),
+                }
+            }
+            if let Some((chunk, _)) = held {
+                emit_one(chunk);
+            }
+        });
+        Self { tx }
+    }
+
+    /// Queue an update for emission.
+    pub fn push(&self, session_id: SessionId, turn_id: Option<TurnId>, update: AcpSessionUpdate) {
+        let queued = QueuedUpdate {
+            session_id,
+            turn_id,
+            update,
+        };
+        if self.tx.send(queued).is_err() {
+            log::debug!("Session update emitter stopped, dropping update");
+        }
+    }
+}
+
+/// The content of a message or thought chunk that is plain text.
+fn text_chunk(update: &AcpSessionUpdate) -> Option<&serde_json::Value> {
+    let content = match update {
+        AcpSessionUpdate::AgentMessageChunk { content }
+        | AcpSessionUpdate::AgentThoughtChunk { content } => content,
+        _ => return None,
+    };
+    let is_text = content.get("type").and_then(|t| t.as_str()) == Some("text")
+        && content.get("text").is_some_and(|text| text.is_string());
+    is_text.then_some(content)
+}
+
+/// Append `next`'s text to `held` if both are text chunks of the same kind,
+/// session and turn with otherwise identical content (e.g. annotations).
+fn merge_chunk(held: &mut QueuedUpdate, next: &QueuedUpdate) -> bool {
+    if held.session_id != next.session_id
+        || held.turn_id != next.turn_id
+        || std::mem::discriminant(&held.update) != std::mem::discriminant(&next.update)
+    {
+        return false;
+    }
+    let (Some(_), Some(next_content)) = (text_chunk(&held.update), text_chunk(&next.update)) else {
+        return false;
+    };
+    let content = match &mut held.update {
+        AcpSessionUpdate::AgentMessageChunk { content }
+        | AcpSessionUpdate::AgentThoughtChunk { content } => content,
+        _ => return false,
+    };
+    let (Some(held_fields), Some(next_fields)) =
+        (content.as_object_mut(), next_content.as_object())
+    else {
+        return false;
+    };
+    let same_metadata = held_fields.len() == next_fields.len()
+        && next_fields
+            .iter()
+            .all(|(key, value)| key == "text" || held_fields.get(key) == Some(value));
+    if !same_metadata {
+        return false;
+    }
+    if let (Some(serde_json::Value::String(text)), Some(more)) = (
+        held_fields.get_mut("text"),
+        next_fields.get("text").and_then(|t| t.as_str()),
+    ) {
+        text.push_str(more);
+        return true;
+    }
+    false
+}
+
+fn now_ms() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs_f64() * 1000.0)
+        .unwrap_or(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tokio::time::{timeout, Duration};
+
+    fn labels() -> AgentLabels {
+        AgentLabels {
+            workspace_id: "ws-1".to_string(),
+            agent_id: "agent-1".to_string(),
+            plugin_id: "claude-code".to_string(),
+            display_name: Some("Reviewer".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_interleaved_updates_are_emitted_with_increasing_seq() {
+        let emitted: Arc<Mutex<Vec<(u64, String, u64)>>> = Arc::new(Mutex::new(Vec::new()));
+        let emitted_for_task = emitted.clone();
+        let emitter = Arc::new(SessionUpdateEmitter::spawn(labels(), move |event| {
+            let AcpSessionUpdate::Raw { json } = &event.update else {
+                panic!("unexpected update");
+            };
+            emitted_for_task.lock().unwrap().push((
+                event.seq,
+                event.session_id.clone(),
+                json["i"].as_u64().unwrap(),
+            ));
+        }));
+
+        // Several producers push at once, like the stdout reader and
+        // concurrently handled requests do
+        let mut producers = Vec::new();
+        for producer in 0..8 {
+            let emitter = emitter.clone();
+            producers.push(tokio::spawn(async move {
+                for i in 0..50 {
+                    emitter.push(
+                        format!("session-{producer}"),
+                        None,
+                        AcpSessionUpdate::Raw {
+                            json: serde_json::json!({ "i": i }),
+                        },
+                    );
+                    tokio::task::yield_now().await;
+                }
+            }));
+        }
+        for producer in producers {
+            producer.await.unwrap();
+        }
+
+        timeout(Duration::from_secs(2), async {
+            while emitted.lock().unwrap().len() < 400 {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        let emitted = emitted.lock().unwrap();
+        let seqs: Vec<u64> = emitted.iter().map(|(seq, _, _)| *seq).collect();
+        assert_eq!(seqs, (0..400).collect::<Vec<u64>>());
+        // Each producer's updates keep their relative order
+        for producer in 0..8 {
+            let session = format!("session-{producer}");
+            let order: Vec<u64> = emitted
+                .iter()
+                .filter(|(_, s, _)| *s == session)
+                .map(|(_, _, i)| *i)
+                .collect();
+            assert_eq!(order, (0..50).collect::<Vec<u64>>());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_emitted_events_carry_agent_labels() {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let emitter = SessionUpdateEmitter::spawn(labels(), move |event| {
+            let _ = tx.send(event.clone());
+        });
+
+        emitter.push(
+            "session-1".to_string(),
+            None,
+            AcpSessionUpdate::Raw {
+                json: serde_json::json!({}),
+            },
+        );
+
+        let event = timeout(Duration::from_secs(2), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(event.agent_id, "agent-1");
+        assert_eq!(event.plugin_id, "claude-code");
+        assert_eq!(event.display_name.as_deref(), Some("Reviewer"));
+    }
+
+    fn text(text: &str) -> serde_json::Value {
+        serde_json::json!({ "type": "text", "text": text })
+    }
+
+    fn describe(event: &AcpSessionUpdateEvent) -> String {
+        match &event.update {
+            AcpSessionUpdate::AgentMessageChunk { content } => {
+                format!(
+                    "{}:message:{}",
+                    event.session_id,
+                    content["text"].as_str().unwrap()
+                )
+            }
+            AcpSessionUpdate::AgentThoughtChunk { content } => {
+                format!(
+                    "{}:thought:{}",
+                    event.session_id,
+                    content["text"].as_str().unwrap()
+                )
+            }
+            AcpSessionUpdate::TurnComplete { .. } => format!("{}:turn_complete", event.session_id),
+            other => panic!("unexpected update {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rapid_chunks_coalesce_into_fewer_events() {
+        let emitted: Arc<Mutex<Vec<AcpSessionUpdateEvent>>> = Arc::new(Mutex::new(Vec::new()));
+        let emitted_for_task = emitted.clone();
+        let emitter = SessionUpdateEmitter::spawn_coalescing(
+            labels(),
+            Arc::new(ChunkCoalescing::new(Some(Duration::from_millis(50)))),
+            move |event| emitted_for_task.lock().unwrap().push(event.clone()),
+        );
+        let turn = Some("turn-1".to_string());
+        let message = |t: &str| AcpSessionUpdate::AgentMessageChunk { content: text(t) };
+
+        for t in ["Hel", "lo, ", "wor", "ld"] {
+            emitter.push("session-1".to_string(), turn.clone(), message(t));
+        }
+        // A different kind or session starts a new batch
+        emitter.push(
+            "session-1".to_string(),
+            turn.clone(),
+            AcpSessionUpdate::AgentThoughtChunk {
+                content: text("hmm"),
+            },
+        );
+        emitter.push("session-2".to_string(), None, message("other"));
+        emitter.push("session-1".to_string(), turn.clone(), message("!"));
+        emitter.push("session-1".to_string(), turn.clone(), message("!"));
+        // Anything else passes straight through, after the chunks before it
+        emitter.push(
+            "session-1".to_string(),
+            turn.clone(),
+            AcpSessionUpdate::TurnComplete {
+                stop_reason: crate::api::types::StopReason::EndTurn,
+                error: None,
+            },
+        );
+        // A lone chunk is emitted once the window ends
+        emitter.push("session-1".to_string(), None, message("late"));
+
+        timeout(Duration::from_secs(2), async {
+            while emitted.lock().unwrap().len() < 6 {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        let emitted = emitted.lock().unwrap();
+        let described: Vec<String> = emitted.iter().map(describe).collect();
+        assert_eq!(
+            described,
+            vec![
+                "session-1:message:Hello, world",
+                "session-1:thought:hmm",
+                "session-2:message:other",
+                "session-1:message:!!",
+                "session-1:turn_complete",
+                "session-1:message:late",
+            ]
+        );
+        let seqs: Vec<u64> = emitted.iter().map(|event| event.seq).collect();
+        assert_eq!(seqs, (0..6).collect::<Vec<u64>>());
+        assert_eq!(emitted[0].turn_id.as_deref(), Some("turn-1"));
+    }
+
+    #[test]
+    fn test_only_matching_text_chunks_merge() {
+        let chunk = |content: serde_json::Value| QueuedUpdate {
+            session_id: "session-1".to_string(),
+            turn_id: None,
+            update: AcpSessionUpdate::AgentMessageChunk { content },
+        };
+
+        let mut held = chunk(text("a"));
+        assert!(merge_chunk(&mut held, &chunk(text("b"))));
+        assert!(matches!(
+            &held.update,
+            AcpSessionUpdate::AgentMessageChunk { content } if content == &text("ab")
+        ));
+
+        // Images and differently annotated text stay separate
+        let image = serde_json::json!({ "type": "image", "data": "AAAA", "mimeType": "image/png" });
+        assert!(!merge_chunk(&mut held, &chunk(image)));
+        let annotated = serde_json::json!({
+            "type": "text",
+            "text": "c",
+            "annotations": { "priority": 1.0 }
+        });
+        assert!(!merge_chunk(&mut held, &chunk(annotated)));
+
+        assert_eq!(ChunkCoalescing::default().window(), None);
+        assert_eq!(ChunkCoalescing::new(Some(Duration::ZERO)).window(), None);
+        assert_eq!(
+            ChunkCoalescing::new(Some(Duration::from_secs(5))).window(),
+            Some(MAX_COALESCE_WINDOW)
+        );
+    }
+}
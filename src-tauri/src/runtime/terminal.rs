@@ -1,21 +1,34 @@
 //! TerminalManager - executes terminal commands scoped to a workspace root.
 //!
 //! US-08: Spawns a command, streams stdout/stderr, and reports exit status.
+//!
+//! Commands started with [`TerminalManager::create`] are retained by ID so the
+//! agent can poll their output and exit status (ACP `terminal/create` and
+//! friends) until it releases them.
 
 use std::collections::HashMap;
-use std::path::PathBuf;
-use std::process::Stdio;
+use std::path::{Path, PathBuf};
+use std::process::{ExitStatus, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use tokio::io::{AsyncRead, AsyncReadExt, BufReader};
-use tokio::process::Command;
-use tokio::sync::{mpsc, oneshot, Mutex};
+use tokio::process::{Child, Command};
+use tokio::sync::{mpsc, oneshot, watch, Mutex};
 use uuid::Uuid;
 
-use crate::api::types::{ApiError, TerminalId};
+use crate::api::types::{ApiError, TerminalId, TerminalInfo, TerminalShell, TerminalStream};
+use crate::runtime::env_file;
 
 const OUTPUT_CHANNEL_CAPACITY: usize = 128;
 const OUTPUT_BUFFER_SIZE: usize = 4096;
+/// Output retained for a created terminal when the agent sets no limit
+const DEFAULT_OUTPUT_BYTE_LIMIT: usize = 1024 * 1024;
+/// Default bytes of `terminal/run` output kept per stream for the agent
+pub const DEFAULT_OUTPUT_CAPTURE_LIMIT: usize = 64 * 1024;
+/// Default number of commands that may run at once in a workspace
+pub const DEFAULT_MAX_TERMINALS: usize = 16;
 
 /// Handle to a running terminal command.
 pub struct TerminalRunHandle {
@@ -26,37 +39,195 @@ pub struct TerminalRunHandle {
 }
 
 /// Exit metadata for a terminal run.
+#[derive(Debug, Clone, Copy)]
 pub struct TerminalExit {
     pub exit_code: Option<i32>,
+    /// Signal that terminated the process (Unix only)
+    pub signal: Option<i32>,
     pub user_stopped: bool,
+    /// Whether the command was killed for exceeding its timeout
+    pub timed_out: bool,
+}
+
+/// Receives output and exit notifications for a created terminal.
+pub trait TerminalObserver: Send + Sync {
+    fn on_output(&self, terminal_id: &TerminalId, stream: TerminalStream, chunk: &str);
+    fn on_exit(&self, terminal_id: &TerminalId, exit: &TerminalExit);
+}
+
+/// Output captured so far for a created terminal.
+#[derive(Debug, Clone)]
+pub struct TerminalOutput {
+    /// Interleaved stdout/stderr, trimmed from the front to the byte limit
+    pub output: String,
+    /// Whether earlier output was dropped to stay within the limit
+    pub truncated: bool,
+    /// Exit status, once the command has finished
+    pub exit: Option<TerminalExit>,
 }
 
 struct TerminalControl {
+    command: String,
+    started_at_ms: f64,
     kill_tx: Option<oneshot::Sender<()>>,
 }
 
+/// Output buffer that keeps the most recent `limit` bytes.
+struct OutputBuffer {
+    text: String,
+    truncated: bool,
+    limit: usize,
+}
+
+impl OutputBuffer {
+    fn new(limit: usize) -> Self {
+        Self {
+            text: String::new(),
+            truncated: false,
+            limit,
+        }
+    }
+
+    fn push(&mut self, chunk: &str) {
+        self.text.push_str(chunk);
+        if self.text.len() > self.limit {
+            let mut start = self.text.len() - self.limit;
+            while !self.text.is_char_boundary(start) {
+                start += 1;
+            }
+            self.text.drain(..start);
+            self.truncated = true;
+        }
+    }
+}
+
+/// A created terminal kept alive until released.
+struct RetainedTerminal {
+    buffer: Arc<std::sync::Mutex<OutputBuffer>>,
+    /// Set once the command has exited and its output is fully buffered
+    exit_rx: watch::Receiver<Option<TerminalExit>>,
+}
+
+/// Placeholder in a shell's arguments replaced by the command line
+const SHELL_COMMAND_PLACEHOLDER: &str = "{command}";
+
 /// Per-workspace terminal manager.
 pub struct TerminalManager {
     workspace_root: PathBuf,
+    /// Shell commands run under (`None`: `sh -c`, or `cmd /C` on Windows)
+    shell: std::sync::Mutex<Option<TerminalShell>>,
+    /// Bytes of `terminal/run` output kept per stream for the agent
+    output_capture_limit: AtomicUsize,
+    /// Commands allowed to run at once; further spawns are rejected
+    max_terminals: AtomicUsize,
     runs: Arc<Mutex<HashMap<TerminalId, TerminalControl>>>,
+    retained: Mutex<HashMap<TerminalId, RetainedTerminal>>,
 }
 
 impl TerminalManager {
     /// Create a new TerminalManager scoped to a workspace root.
-    pub fn new(workspace_root: PathBuf) -> Self {
+    pub fn new(
+        workspace_root: PathBuf,
+        output_capture_limit: usize,
+        shell: Option<TerminalShell>,
+    ) -> Self {
         Self {
             workspace_root,
+            shell: std::sync::Mutex::new(shell),
+            output_capture_limit: AtomicUsize::new(output_capture_limit),
+            max_terminals: AtomicUsize::new(DEFAULT_MAX_TERMINALS),
             runs: Arc::new(Mutex::new(HashMap::new())),
+            retained: Mutex::new(HashMap::new()),
         }
     }
 
+    /// Bytes of `terminal/run` output kept per stream for the agent.
+    pub fn output_capture_limit(&self) -> usize {
+        self.output_capture_limit.load(Ordering::Relaxed)
+    }
+
+    /// Change how much `terminal/run` output is kept per stream.
+    pub fn set_output_capture_limit(&self, limit: usize) {
+        self.output_capture_limit.store(limit, Ordering::Relaxed);
+    }
+
+    /// Number of commands allowed to run at once.
+    pub fn max_terminals(&self) -> usize {
+        self.max_terminals.load(Ordering::Relaxed)
+    }
+
+    /// Change how many commands may run at once; running commands are kept
+    /// even if they exceed the new limit.
+    pub fn set_max_terminals(&self, limit: usize) {
+        self.max_terminals.store(limit, Ordering::Relaxed);
+    }
+
+    /// Shell commands are run under, if one was configured.
+    pub fn shell(&self) -> Option<TerminalShell> {
+        self.shell
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// Change the shell used for the next command (`None`: platform default).
+    pub fn set_shell(&self, shell: Option<TerminalShell>) {
+        *self
+            .shell
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = shell;
+    }
+
     /// Spawn a terminal command and stream its output.
-    pub async fn spawn_run(&self, command: String) -> Result<TerminalRunHandle, ApiError> {
+    ///
+    /// The command inherits the app's environment, plus the workspace `.env`
+    /// variables if that preference is on (except those replacing one of
+    /// `ESSENTIAL_ENV_VARS`); entries in `env` are set on top, overriding
+    /// variables of the same name. When
+    /// `timeout` elapses before the command exits it is killed and its exit
+    /// is reported with `timed_out` set.
+    ///
+    /// # Errors
+    /// * `ApiError::TooManyTerminals` - If the workspace already runs
+    ///   `max_terminals` commands
+    pub async fn spawn_run(
+        &self,
+        command: String,
+        env: Option<HashMap<String, String>>,
+        timeout: Option<Duration>,
+    ) -> Result<TerminalRunHandle, ApiError> {
         if command.trim().is_empty() {
             return Err(ApiError::InvalidInput {
                 message: "Command cannot be empty".to_string(),
             });
         }
+        let env = env.unwrap_or_default();
+        if let Some(name) = env
+            .keys()
+            .find(|name| name.is_empty() || name.contains(['=', '\0']))
+        {
+            return Err(ApiError::InvalidInput {
+                message: format!("Invalid environment variable name: {name:?}"),
+            });
+        }
+
+        let workspace_env = env_file::without_essential_overrides(
+            env_file::load_workspace_env(&self.workspace_root).await,
+        );
+
+        // Held until the run is registered so concurrent spawns can't
+        // overshoot the limit
+        let mut runs = self.runs.lock().await;
+        let max_terminals = self.max_terminals();
+        if runs.len() >= max_terminals {
+            log::warn!(
+                "Terminal spawn rejected: running={}, limit={max_terminals}",
+                runs.len()
+            );
+            return Err(ApiError::TooManyTerminals {
+                limit: max_terminals.min(u32::MAX as usize) as u32,
+            });
+        }
 
         let terminal_id = Uuid::new_v4().to_string();
 
@@ -69,8 +240,22 @@ impl TerminalManager {
             command.len()
         );
 
-        let mut cmd = build_shell_command(&command);
-        cmd.current_dir(&self.workspace_root)
+        let mut cmd = match self.shell() {
+            Some(shell) => match find_program(Path::new(&shell.program)) {
+                Some(program) => build_custom_shell_command(&program, &shell.args, &command),
+                None => {
+                    log::warn!(
+                        "Configured shell not found, using the default: program={}",
+                        shell.program
+                    );
+                    build_shell_command(&command)
+                }
+            },
+            None => build_shell_command(&command),
+        };
+        cmd.envs(workspace_env)
+            .envs(&env)
+            .current_dir(&self.workspace_root)
             .stdin(Stdio::null())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
@@ -91,15 +276,18 @@ impl TerminalManager {
         let (stderr_tx, stderr_rx) = mpsc::channel(OUTPUT_CHANNEL_CAPACITY);
         let (exit_tx, exit_rx) = oneshot::channel();
         let (kill_tx, mut kill_rx) = oneshot::channel::<()>();
-        {
-            let mut runs = self.runs.lock().await;
-            runs.insert(
-                terminal_id.clone(),
-                TerminalControl {
-                    kill_tx: Some(kill_tx),
-                },
-            );
-        }
+        runs.insert(
+            terminal_id.clone(),
+            TerminalControl {
+                command,
+                started_at_ms: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_millis() as f64)
+                    .unwrap_or(0.0),
+                kill_tx: Some(kill_tx),
+            },
+        );
+        drop(runs);
 
         tokio::spawn(stream_to_channel(stdout, stdout_tx, "stdout"));
         tokio::spawn(stream_to_channel(stderr, stderr_tx, "stderr"));
@@ -108,41 +296,35 @@ impl TerminalManager {
         let terminal_id_for_task = terminal_id.clone();
         tokio::spawn(async move {
             let mut user_stopped = false;
+            let mut timed_out = false;
+            let deadline = async {
+                match timeout {
+                    Some(timeout) => tokio::time::sleep(timeout).await,
+                    None => std::future::pending::<()>().await,
+                }
+            };
             let status = tokio::select! {
                 status = child.wait() => status,
-                _ = &mut kill_rx => {
-                    match child.try_wait() {
-                        Ok(Some(status)) => Ok(status),
-                        Ok(None) => {
-                            user_stopped = true;
-                            if let Err(e) = child.kill().await {
-                                log::warn!("Terminal kill failed: {e}");
-                            }
-                            child.wait().await
-                        }
-                        Err(e) => {
-                            user_stopped = true;
-                            log::warn!("Terminal process try_wait failed: {e}");
-                            if let Err(e) = child.kill().await {
-                                log::warn!("Terminal kill failed: {e}");
-                            }
-                            child.wait().await
-                        }
-                    }
+                _ = &mut kill_rx => stop_child(&mut child, &mut user_stopped).await,
+                _ = deadline => {
+                    log::info!("Terminal command timed out: terminal_id={terminal_id_for_task}");
+                    stop_child(&mut child, &mut timed_out).await
                 }
             };
 
-            let exit_code = match status {
-                Ok(status) => status.code(),
+            let (exit_code, signal) = match status {
+                Ok(status) => (status.code(), exit_signal(&status)),
                 Err(e) => {
                     log::warn!("Terminal process wait failed: {e}");
-                    None
+                    (None, None)
                 }
             };
 
             let _ = exit_tx.send(TerminalExit {
                 exit_code,
+                signal,
                 user_stopped,
+                timed_out,
             });
 
             let mut runs = runs.lock().await;
@@ -177,6 +359,267 @@ impl TerminalManager {
 
         Ok(())
     }
+
+    /// Terminal commands that are still running, oldest first.
+    pub async fn list_active(&self) -> Vec<TerminalInfo> {
+        let runs = self.runs.lock().await;
+        let mut active: Vec<TerminalInfo> = runs
+            .iter()
+            .map(|(terminal_id, control)| TerminalInfo {
+                terminal_id: terminal_id.clone(),
+                command: control.command.clone(),
+                started_at_ms: control.started_at_ms,
+            })
+            .collect();
+        active.sort_by(|a, b| a.started_at_ms.total_cmp(&b.started_at_ms));
+        active
+    }
+
+    /// Drop the retained output of created terminals whose command has exited.
+    ///
+    /// # Returns
+    /// * Number of terminals released
+    pub async fn release_exited(&self) -> usize {
+        let mut retained = self.retained.lock().await;
+        let before = retained.len();
+        retained.retain(|_, terminal| terminal.exit_rx.borrow().is_none());
+        before - retained.len()
+    }
+
+    /// Refuse a working directory other than the workspace root (relative
+    /// paths are taken from the root).
+    async fn ensure_workspace_cwd(&self, cwd: &Path) -> Result<(), ApiError> {
+        let cwd = self.workspace_root.join(cwd);
+        let is_root = match (
+            tokio::fs::canonicalize(&self.workspace_root).await,
+            tokio::fs::canonicalize(&cwd).await,
+        ) {
+            (Ok(root), Ok(cwd)) => root == cwd,
+            _ => cwd == self.workspace_root,
+        };
+        if is_root {
+            return Ok(());
+        }
+        Err(ApiError::InvalidInput {
+            message: format!(
+                "Terminals run in the workspace root; cwd {} is not supported",
+                cwd.display()
+            ),
+        })
+    }
+
+    /// Kill every running terminal command.
+    pub async fn kill_all(&self) {
+        let mut runs = self.runs.lock().await;
+        for (terminal_id, control) in runs.iter_mut() {
+            if let Some(kill_tx) = control.kill_tx.take() {
+                log::debug!("Killing terminal: terminal_id={terminal_id}");
+                let _ = kill_tx.send(());
+            }
+        }
+    }
+
+    /// Spawn a terminal command and retain it for polling.
+    ///
+    /// `env` is applied as in [`TerminalManager::spawn_run`]. Commands run in
+    /// the workspace root; a `cwd` naming any other directory is refused.
+    /// Output is buffered (keeping the last `output_byte_limit` bytes) until
+    /// the terminal is released. `observer` is notified of every chunk and
+    /// of the exit, e.g. to mirror the terminal in the UI.
+    ///
+    /// # Errors
+    /// * `ApiError::InvalidInput` - If `cwd` is not the workspace root
+    pub async fn create(
+        &self,
+        command: String,
+        env: Option<HashMap<String, String>>,
+        cwd: Option<PathBuf>,
+        output_byte_limit: Option<usize>,
+        observer: Option<Arc<dyn TerminalObserver>>,
+    ) -> Result<TerminalId, ApiError> {
+        if let Some(cwd) = cwd {
+            self.ensure_workspace_cwd(&cwd).await?;
+        }
+        let TerminalRunHandle {
+            terminal_id,
+            mut stdout_rx,
+            mut stderr_rx,
+            exit_rx,
+        } = self.spawn_run(command, env, None).await?;
+
+        let buffer = Arc::new(std::sync::Mutex::new(OutputBuffer::new(
+            output_byte_limit.unwrap_or(DEFAULT_OUTPUT_BYTE_LIMIT),
+        )));
+        let (exit_tx, exit_watch) = watch::channel(None);
+        {
+            let mut retained = self.retained.lock().await;
+            retained.insert(
+                terminal_id.clone(),
+                RetainedTerminal {
+                    buffer: buffer.clone(),
+                    exit_rx: exit_watch,
+                },
+            );
+        }
+
+        let terminal_id_for_task = terminal_id.clone();
+        tokio::spawn(async move {
+            let terminal_id = terminal_id_for_task;
+            let mut stdout_closed = false;
+            let mut stderr_closed = false;
+
+            while !(stdout_closed && stderr_closed) {
+                let (stream, chunk) = tokio::select! {
+                    chunk = stdout_rx.recv(), if !stdout_closed => (TerminalStream::Stdout, chunk),
+                    chunk = stderr_rx.recv(), if !stderr_closed => (TerminalStream::Stderr, chunk),
+                };
+                let Some(chunk) = chunk else {
+                    match stream {
+                        TerminalStream::Stdout => stdout_closed = true,
+                        TerminalStream::Stderr => stderr_closed = true,
+                    }
+                    continue;
+                };
+                if let Some(observer) = &observer {
+                    observer.on_output(&terminal_id, stream, &chunk);
+                }
+                buffer
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .push(&chunk);
+            }
+
+            // Published after the output is drained so a finished terminal
+            // always reports its complete output
+            let exit = exit_rx.await.unwrap_or(TerminalExit {
+                exit_code: None,
+                signal: None,
+                user_stopped: false,
+                timed_out: false,
+            });
+            if let Some(observer) = &observer {
+                observer.on_exit(&terminal_id, &exit);
+            }
+            let _ = exit_tx.send(Some(exit));
+        });
+
+        Ok(terminal_id)
+    }
+
+    /// Output captured so far for a created terminal, plus its exit status
+    /// if it has finished.
+    pub async fn output(&self, terminal_id: &TerminalId) -> Result<TerminalOutput, ApiError> {
+        let retained = self.retained.lock().await;
+        let terminal = retained
+            .get(terminal_id)
+            .ok_or_else(|| unknown_terminal(terminal_id))?;
+
+        let buffer = terminal
+            .buffer
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        Ok(TerminalOutput {
+            output: buffer.text.clone(),
+            truncated: buffer.truncated,
+            exit: *terminal.exit_rx.borrow(),
+        })
+    }
+
+    /// Wait for a created terminal to exit.
+    pub async fn wait_for_exit(&self, terminal_id: &TerminalId) -> Result<TerminalExit, ApiError> {
+        let mut exit_rx = {
+            let retained = self.retained.lock().await;
+            retained
+                .get(terminal_id)
+                .ok_or_else(|| unknown_terminal(terminal_id))?
+                .exit_rx
+                .clone()
+        };
+
+        let exit = exit_rx
+            .wait_for(Option::is_some)
+            .await
+            .map_err(|_| ApiError::IoError {
+                message: format!("Terminal {terminal_id} was released before it exited"),
+            })?;
+        Ok(exit.unwrap_or(TerminalExit {
+            exit_code: None,
+            signal: None,
+            user_stopped: false,
+            timed_out: false,
+        }))
+    }
+
+    /// Kill a created terminal (if still running) and drop its output.
+    ///
+    /// Releasing an unknown or already released terminal is a no-op.
+    pub async fn release(&self, terminal_id: TerminalId) -> Result<(), ApiError> {
+        self.kill(terminal_id.clone()).await?;
+
+        let removed = {
+            let mut retained = self.retained.lock().await;
+            retained.remove(&terminal_id)
+        };
+        if removed.is_none() {
+            log::debug!("Terminal release ignored: terminal_id={terminal_id} not found");
+        }
+
+        Ok(())
+    }
+}
+
+/// Signal that terminated the process, if it didn't exit normally.
+#[cfg(unix)]
+fn exit_signal(status: &ExitStatus) -> Option<i32> {
+    use std::os::unix::process::ExitStatusExt;
+    status.signal()
+}
+
+#[cfg(not(unix))]
+fn exit_signal(_status: &ExitStatus) -> Option<i32> {
+    None
+}
+
+/// Kill a child that is still running and wait for it, setting `killed`
+/// only if it actually had to be killed.
+async fn stop_child(child: &mut Child, killed: &mut bool) -> std::io::Result<ExitStatus> {
+    match child.try_wait() {
+        Ok(Some(status)) => return Ok(status),
+        Ok(None) => {}
+        Err(e) => log::warn!("Terminal process try_wait failed: {e}"),
+    }
+    *killed = true;
+    if let Err(e) = child.kill().await {
+        log::warn!("Terminal kill failed: {e}");
+    }
+    child.wait().await
+}
+
+fn unknown_terminal(terminal_id: &TerminalId) -> ApiError {
+    ApiError::InvalidInput {
+        message: format!("Unknown terminal: {terminal_id}"),
+    }
+}
+
+/// Join a program and its arguments into a single shell command line,
+/// quoting each argument so the shell passes it through verbatim.
+pub fn command_line(command: &str, args: &[String]) -> String {
+    let mut line = command.to_string();
+    for arg in args {
+        line.push(' ');
+        line.push_str(&quote_arg(arg));
+    }
+    line
+}
+
+#[cfg(target_os = "windows")]
+fn quote_arg(arg: &str) -> String {
+    format!("\"{}\"", arg.replace('"', "\"\""))
+}
+
+#[cfg(not(target_os = "windows"))]
+fn quote_arg(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', "'\\''"))
 }
 
 #[cfg(target_os = "windows")]
@@ -193,6 +636,47 @@ fn build_shell_command(command: &str) -> Command {
     cmd
 }
 
+/// Build a command running `command` under a configured shell.
+fn build_custom_shell_command(program: &Path, args: &[String], command: &str) -> Command {
+    let mut cmd = Command::new(program);
+    if args.iter().any(|arg| arg == SHELL_COMMAND_PLACEHOLDER) {
+        cmd.args(args.iter().map(|arg| {
+            if arg == SHELL_COMMAND_PLACEHOLDER {
+                command
+            } else {
+                arg.as_str()
+            }
+        }));
+    } else {
+        cmd.args(args).arg(command);
+    }
+    cmd
+}
+
+/// Locate an executable: paths are checked directly, bare names are looked
+/// up on `PATH`.
+fn find_program(program: &Path) -> Option<PathBuf> {
+    if program.components().count() > 1 {
+        return program.is_file().then(|| program.to_path_buf());
+    }
+    let paths = std::env::var_os("PATH")?;
+    std::env::split_paths(&paths).find_map(|dir| {
+        let candidate = dir.join(program);
+        if candidate.is_file() {
+            return Some(candidate);
+        }
+        if cfg!(target_os = "windows") && candidate.extension().is_none() {
+            let exe = candidate.with_extension("exe");
+            return exe.is_file().then_some(exe);
+        }
+        None
+    })
+}
+
+/// Forward output to `tx`, waiting for room when the consumer falls behind
+/// (the pipe then fills up and the process blocks, rather than losing output).
+/// If the consumer goes away, the rest of the output is read and discarded so
+/// the process can still finish.
 async fn stream_to_channel<R: AsyncRead + Unpin>(
     reader: R,
     tx: mpsc::Sender<String>,
@@ -200,13 +684,25 @@ async fn stream_to_channel<R: AsyncRead + Unpin>(
 ) {
     let mut reader = BufReader::new(reader);
     let mut buf = vec![0u8; OUTPUT_BUFFER_SIZE];
+    // Bytes of a character split across reads, decoded with the next read
+    let mut pending: Vec<u8> = Vec::new();
+    let mut receiver_gone = false;
 
     loop {
         match reader.read(&mut buf).await {
             Ok(0) => break,
             Ok(n) => {
-                let chunk = String::from_utf8_lossy(&buf[..n]).to_string();
-                let _ = tx.try_send(chunk);
+                pending.extend_from_slice(&buf[..n]);
+                let complete = pending.len() - incomplete_utf8_tail(&pending);
+                if complete == 0 {
+                    continue;
+                }
+                let chunk = String::from_utf8_lossy(&pending[..complete]).to_string();
+                pending.drain(..complete);
+                if !receiver_gone && tx.send(chunk).await.is_err() {
+                    log::debug!("Terminal {label} receiver closed, discarding output");
+                    receiver_gone = true;
+                }
             }
             Err(e) => {
                 log::warn!("Terminal {label} stream read failed: {e}");
@@ -214,6 +710,30 @@ async fn stream_to_channel<R: AsyncRead + Unpin>(
             }
         }
     }
+
+    if !pending.is_empty() && !receiver_gone {
+        let _ = tx.send(String::from_utf8_lossy(&pending).to_string()).await;
+    }
+}
+
+/// Length of a multibyte UTF-8 sequence cut off at the end of `bytes`, or 0
+/// if the bytes end on a character boundary.
+fn incomplete_utf8_tail(bytes: &[u8]) -> usize {
+    for back in 1..=bytes.len().min(3) {
+        let byte = bytes[bytes.len() - back];
+        if byte & 0xC0 == 0x80 {
+            // Continuation byte; keep looking for the lead byte
+            continue;
+        }
+        let width = match byte {
+            0xC0..=0xDF => 2,
+            0xE0..=0xEF => 3,
+            0xF0..=0xF7 => 4,
+            _ => 1,
+        };
+        return if width > back { back } else { 0 };
+    }
+    0
 }
 
 #[cfg(test)]
@@ -223,14 +743,15 @@ mod tests {
 
     #[tokio::test]
     async fn test_spawn_run_captures_stdout() {
-        let manager = TerminalManager::new(std::env::temp_dir());
+        let manager =
+            TerminalManager::new(std::env::temp_dir(), DEFAULT_OUTPUT_CAPTURE_LIMIT, None);
 
         #[cfg(target_os = "windows")]
         let command = "echo hello".to_string();
         #[cfg(not(target_os = "windows"))]
         let command = "printf 'hello'".to_string();
 
-        let mut handle = manager.spawn_run(command).await.unwrap();
+        let mut handle = manager.spawn_run(command, None, None).await.unwrap();
         let mut stdout = String::new();
 
         while let Ok(Some(chunk)) = timeout(Duration::from_secs(1), handle.stdout_rx.recv()).await {
@@ -250,16 +771,46 @@ mod tests {
         assert!(stdout.contains("hello"));
     }
 
+    #[tokio::test]
+    async fn test_spawn_run_injects_env() {
+        let manager =
+            TerminalManager::new(std::env::temp_dir(), DEFAULT_OUTPUT_CAPTURE_LIMIT, None);
+
+        #[cfg(target_os = "windows")]
+        let command = "echo %ACP_TEST_VAR%".to_string();
+        #[cfg(not(target_os = "windows"))]
+        let command = "printf '%s' \"$ACP_TEST_VAR\"".to_string();
+
+        let env = HashMap::from([("ACP_TEST_VAR".to_string(), "injected".to_string())]);
+        let mut handle = manager.spawn_run(command, Some(env), None).await.unwrap();
+        let mut stdout = String::new();
+
+        while let Ok(Some(chunk)) = timeout(Duration::from_secs(1), handle.stdout_rx.recv()).await {
+            stdout.push_str(&chunk);
+            if stdout.contains("injected") {
+                break;
+            }
+        }
+        assert!(stdout.contains("injected"));
+
+        let bad = HashMap::from([("A=B".to_string(), "x".to_string())]);
+        assert!(matches!(
+            manager.spawn_run("true".to_string(), Some(bad), None).await,
+            Err(ApiError::InvalidInput { .. })
+        ));
+    }
+
     #[tokio::test]
     async fn test_kill_marks_user_stopped() {
-        let manager = TerminalManager::new(std::env::temp_dir());
+        let manager =
+            TerminalManager::new(std::env::temp_dir(), DEFAULT_OUTPUT_CAPTURE_LIMIT, None);
 
         #[cfg(target_os = "windows")]
         let command = "ping -n 10 127.0.0.1 >NUL".to_string();
         #[cfg(not(target_os = "windows"))]
         let command = "sleep 5".to_string();
 
-        let handle = manager.spawn_run(command).await.unwrap();
+        let handle = manager.spawn_run(command, None, None).await.unwrap();
         let terminal_id = handle.terminal_id.clone();
 
         manager.kill(terminal_id).await.unwrap();
@@ -272,9 +823,411 @@ mod tests {
         assert!(exit.user_stopped);
     }
 
+    #[tokio::test]
+    async fn test_stream_keeps_multibyte_chars_split_across_reads() {
+        // "héllo 世界" with both multibyte characters cut between reads
+        let text = "héllo 世界";
+        let bytes = text.as_bytes();
+        let reader = (&bytes[..2])
+            .chain(&bytes[2..8])
+            .chain(&bytes[8..9])
+            .chain(&bytes[9..]);
+        let (tx, mut rx) = mpsc::channel(OUTPUT_CHANNEL_CAPACITY);
+
+        stream_to_channel(reader, tx, "stdout").await;
+
+        let mut output = String::new();
+        while let Some(chunk) = rx.recv().await {
+            assert!(!chunk.contains('\u{FFFD}'));
+            output.push_str(&chunk);
+        }
+        assert_eq!(output, text);
+    }
+
+    #[tokio::test]
+    async fn test_stream_waits_for_slow_consumer_instead_of_dropping() {
+        // Far more chunks than the channel holds
+        let data = vec![b'x'; OUTPUT_BUFFER_SIZE * OUTPUT_CHANNEL_CAPACITY * 4];
+        let (tx, mut rx) = mpsc::channel(OUTPUT_CHANNEL_CAPACITY);
+        let producer = tokio::spawn(async move {
+            stream_to_channel(&data[..], tx, "stdout").await;
+        });
+
+        // Let the producer fill the channel before draining it
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let mut received = 0;
+        while let Some(chunk) = rx.recv().await {
+            received += chunk.len();
+        }
+        producer.await.unwrap();
+
+        assert_eq!(received, OUTPUT_BUFFER_SIZE * OUTPUT_CHANNEL_CAPACITY * 4);
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_spawn_run_keeps_all_high_volume_output() {
+        let manager =
+            TerminalManager::new(std::env::temp_dir(), DEFAULT_OUTPUT_CAPTURE_LIMIT, None);
+        let mut handle = manager
+            .spawn_run("seq 1 200000".to_string(), None, None)
+            .await
+            .unwrap();
+
+        // Fall behind while the command prints
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let mut stdout = String::new();
+        while let Ok(Some(chunk)) = timeout(Duration::from_secs(5), handle.stdout_rx.recv()).await {
+            stdout.push_str(&chunk);
+        }
+
+        let lines: Vec<&str> = stdout.lines().collect();
+        assert_eq!(lines.len(), 200000);
+        assert_eq!(lines.first(), Some(&"1"));
+        assert_eq!(lines.last(), Some(&"200000"));
+    }
+
+    #[test]
+    fn test_incomplete_utf8_tail() {
+        let bytes = "a世".as_bytes();
+        assert_eq!(incomplete_utf8_tail(bytes), 0);
+        assert_eq!(incomplete_utf8_tail(&bytes[..2]), 1);
+        assert_eq!(incomplete_utf8_tail(&bytes[..3]), 2);
+        assert_eq!(incomplete_utf8_tail(b"abc"), 0);
+        assert_eq!(incomplete_utf8_tail(b""), 0);
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_list_active_and_kill_all() {
+        let manager =
+            TerminalManager::new(std::env::temp_dir(), DEFAULT_OUTPUT_CAPTURE_LIMIT, None);
+
+        let first = manager
+            .spawn_run("sleep 5".to_string(), None, None)
+            .await
+            .unwrap();
+        let second = manager
+            .spawn_run("sleep 6".to_string(), None, None)
+            .await
+            .unwrap();
+
+        let active = manager.list_active().await;
+        assert_eq!(active.len(), 2);
+        for handle in [&first, &second] {
+            assert!(active
+                .iter()
+                .any(|info| info.terminal_id == handle.terminal_id));
+        }
+        assert!(active.iter().any(|info| info.command == "sleep 6"));
+
+        manager.kill_all().await;
+        for handle in [first, second] {
+            let exit = timeout(Duration::from_secs(5), handle.exit_rx)
+                .await
+                .unwrap()
+                .unwrap();
+            assert!(exit.user_stopped);
+        }
+
+        // Finished runs drop out of the list
+        timeout(Duration::from_secs(2), async {
+            while !manager.list_active().await.is_empty() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_exit_reports_terminating_signal() {
+        let manager =
+            TerminalManager::new(std::env::temp_dir(), DEFAULT_OUTPUT_CAPTURE_LIMIT, None);
+
+        let handle = manager
+            .spawn_run("kill -9 $$".to_string(), None, None)
+            .await
+            .unwrap();
+        let exit = timeout(Duration::from_secs(2), handle.exit_rx)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(exit.exit_code, None);
+        assert_eq!(exit.signal, Some(9));
+
+        let handle = manager
+            .spawn_run("exit 4".to_string(), None, None)
+            .await
+            .unwrap();
+        let exit = timeout(Duration::from_secs(2), handle.exit_rx)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(exit.exit_code, Some(4));
+        assert_eq!(exit.signal, None);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_configured_shell_is_used() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("terminal_shell_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let script = dir.join("myshell");
+        std::fs::write(
+            &script,
+            "#!/bin/sh\nprintf 'custom:'\nexec /bin/sh \"$@\"\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let manager = TerminalManager::new(
+            dir.clone(),
+            DEFAULT_OUTPUT_CAPTURE_LIMIT,
+            Some(TerminalShell {
+                program: script.display().to_string(),
+                args: vec!["-c".to_string(), "{command}".to_string()],
+            }),
+        );
+        assert_eq!(read_stdout(&manager, "printf 'ok'").await, "custom:ok");
+
+        // A missing shell falls back to the default
+        manager.set_shell(Some(TerminalShell {
+            program: dir.join("missing").display().to_string(),
+            args: vec!["-c".to_string()],
+        }));
+        assert_eq!(read_stdout(&manager, "printf 'ok'").await, "ok");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    async fn read_stdout(manager: &TerminalManager, command: &str) -> String {
+        let mut handle = manager
+            .spawn_run(command.to_string(), None, None)
+            .await
+            .unwrap();
+        let mut stdout = String::new();
+        while let Ok(Some(chunk)) = timeout(Duration::from_secs(2), handle.stdout_rx.recv()).await {
+            stdout.push_str(&chunk);
+        }
+        stdout
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_spawn_run_enforces_terminal_limit() {
+        let manager =
+            TerminalManager::new(std::env::temp_dir(), DEFAULT_OUTPUT_CAPTURE_LIMIT, None);
+        manager.set_max_terminals(2);
+
+        let first = manager
+            .spawn_run("sleep 5".to_string(), None, None)
+            .await
+            .unwrap();
+        let _second = manager
+            .spawn_run("sleep 5".to_string(), None, None)
+            .await
+            .unwrap();
+        assert!(matches!(
+            manager.spawn_run("sleep 5".to_string(), None, None).await,
+            Err(ApiError::TooManyTerminals { limit: 2 })
+        ));
+
+        // Once a run finishes its slot is free again
+        manager.kill(first.terminal_id.clone()).await.unwrap();
+        timeout(Duration::from_secs(5), first.exit_rx)
+            .await
+            .unwrap()
+            .unwrap();
+        let third = timeout(Duration::from_secs(2), async {
+            loop {
+                match manager.spawn_run("true".to_string(), None, None).await {
+                    Err(ApiError::TooManyTerminals { .. }) => {
+                        tokio::time::sleep(Duration::from_millis(10)).await
+                    }
+                    other => break other,
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert!(third.is_ok());
+
+        manager.kill_all().await;
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_spawn_run_kills_on_timeout() {
+        let manager =
+            TerminalManager::new(std::env::temp_dir(), DEFAULT_OUTPUT_CAPTURE_LIMIT, None);
+
+        let handle = manager
+            .spawn_run(
+                "sleep 5".to_string(),
+                None,
+                Some(Duration::from_millis(100)),
+            )
+            .await
+            .unwrap();
+
+        let exit = timeout(Duration::from_secs(2), handle.exit_rx)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert!(exit.timed_out);
+        assert!(!exit.user_stopped);
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_created_terminal_gets_env_and_runs_in_workspace_root() {
+        let root = std::env::temp_dir();
+        let manager = TerminalManager::new(root.clone(), DEFAULT_OUTPUT_CAPTURE_LIMIT, None);
+
+        let env = HashMap::from([("ACP_TEST_VAR".to_string(), "from-agent".to_string())]);
+        let terminal_id = manager
+            .create(
+                "printf '%s' \"$ACP_TEST_VAR\"".to_string(),
+                Some(env),
+                Some(root.clone()),
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        timeout(Duration::from_secs(2), manager.wait_for_exit(&terminal_id))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            manager.output(&terminal_id).await.unwrap().output,
+            "from-agent"
+        );
+
+        // "." is the root itself; anywhere else is refused before spawning
+        assert!(manager
+            .create(
+                "true".to_string(),
+                None,
+                Some(PathBuf::from(".")),
+                None,
+                None
+            )
+            .await
+            .is_ok());
+        let result = manager
+            .create(
+                "true".to_string(),
+                None,
+                Some(PathBuf::from("/")),
+                None,
+                None,
+            )
+            .await;
+        assert!(matches!(result, Err(ApiError::InvalidInput { .. })));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_created_terminal_can_be_polled_until_released() {
+        let manager =
+            TerminalManager::new(std::env::temp_dir(), DEFAULT_OUTPUT_CAPTURE_LIMIT, None);
+
+        let terminal_id = manager
+            .create(
+                "printf 'abcdef'; exit 3".to_string(),
+                None,
+                None,
+                Some(4),
+                None,
+            )
+            .await
+            .unwrap();
+
+        let exit = timeout(Duration::from_secs(2), manager.wait_for_exit(&terminal_id))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(exit.exit_code, Some(3));
+
+        // Output stays available after exit, trimmed to the last 4 bytes
+        let output = manager.output(&terminal_id).await.unwrap();
+        assert_eq!(output.output, "cdef");
+        assert!(output.truncated);
+        assert_eq!(output.exit.and_then(|exit| exit.exit_code), Some(3));
+
+        manager.release(terminal_id.clone()).await.unwrap();
+        assert!(matches!(
+            manager.output(&terminal_id).await,
+            Err(ApiError::InvalidInput { .. })
+        ));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_kill_created_terminal_keeps_it_until_release() {
+        let manager =
+            TerminalManager::new(std::env::temp_dir(), DEFAULT_OUTPUT_CAPTURE_LIMIT, None);
+
+        let terminal_id = manager
+            .create("sleep 5".to_string(), None, None, None, None)
+            .await
+            .unwrap();
+        assert!(manager.output(&terminal_id).await.unwrap().exit.is_none());
+
+        manager.kill(terminal_id.clone()).await.unwrap();
+
+        let exit = timeout(Duration::from_secs(5), manager.wait_for_exit(&terminal_id))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(exit.user_stopped);
+        assert!(manager.output(&terminal_id).await.is_ok());
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_release_exited_keeps_running_terminals() {
+        let manager =
+            TerminalManager::new(std::env::temp_dir(), DEFAULT_OUTPUT_CAPTURE_LIMIT, None);
+        let exited = manager
+            .create("true".to_string(), None, None, None, None)
+            .await
+            .unwrap();
+        let running = manager
+            .create("sleep 5".to_string(), None, None, None, None)
+            .await
+            .unwrap();
+        timeout(Duration::from_secs(2), manager.wait_for_exit(&exited))
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(manager.release_exited().await, 1);
+
+        assert!(manager.output(&exited).await.is_err());
+        assert!(manager.output(&running).await.is_ok());
+        manager.release(running).await.unwrap();
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_command_line_quotes_args() {
+        let line = command_line("echo", &["a b".to_string(), "it's".to_string()]);
+
+        assert_eq!(line, "echo 'a b' 'it'\\''s'");
+    }
+
     #[tokio::test]
     async fn test_kill_unknown_terminal_ok() {
-        let manager = TerminalManager::new(std::env::temp_dir());
+        let manager =
+            TerminalManager::new(std::env::temp_dir(), DEFAULT_OUTPUT_CAPTURE_LIMIT, None);
 
         let result = manager.kill("unknown-terminal".to_string()).await;
 
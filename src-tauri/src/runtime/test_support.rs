@@ -0,0 +1,30 @@
+//! Test doubles shared by the runtime and command tests.
+
+use crate::api::types::{ApiError, PromptContentBlock, SessionId};
+use crate::protocols::agent_connection::AgentConnection;
+
+/// Connection that accepts everything (stands in for a running adapter).
+pub(crate) struct IdleConnection;
+
+#[async_trait::async_trait]
+impl AgentConnection for IdleConnection {
+    async fn send_prompt(
+        &self,
+        _session_id: SessionId,
+        _prompt: Vec<PromptContentBlock>,
+    ) -> Result<String, ApiError> {
+        Ok("turn-1".to_string())
+    }
+
+    async fn cancel_turn(&self, _session_id: SessionId) -> Result<(), ApiError> {
+        Ok(())
+    }
+
+    async fn new_session(&self) -> Result<SessionId, ApiError> {
+        Ok("session-2".to_string())
+    }
+
+    async fn shutdown(&self) -> Result<(), ApiError> {
+        Ok(())
+    }
+}
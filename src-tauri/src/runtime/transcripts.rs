@@ -0,0 +1,359 @@
+//! Opt-in session transcripts.
+//!
+//! When enabled via preferences, every `acp/session_update` event is also
+//! appended as one JSON line to `<root>/<workspace>/<session>.jsonl`, so a
+//! conversation can be shown again after the UI reloads.
+//!
+//! Appending never blocks the emit path: events go through an unbounded
+//! queue to a single writer task, which writes them in batches on the
+//! blocking pool. A transcript file that would grow past the size cap is
+//! moved to `<session>.1.jsonl` (replacing the previous one) and a fresh file
+//! is started, so each session keeps at most about twice the cap on disk.
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+
+use tokio::sync::{mpsc, oneshot};
+
+use crate::api::types::{AcpSessionUpdateEvent, ApiError, SessionId, WorkspaceId};
+use crate::utils::blocking::BlockingFsLimiter;
+
+/// Size at which a transcript file is rotated (10 MiB)
+pub const DEFAULT_MAX_TRANSCRIPT_BYTES: u64 = 10 * 1024 * 1024;
+
+enum WriterMessage {
+    Append(Box<AcpSessionUpdateEvent>),
+    /// Answered once everything queued before it is written
+    Flush(oneshot::Sender<()>),
+}
+
+/// Writes and reads session transcripts under one root directory.
+pub struct SessionTranscripts {
+    root: PathBuf,
+    max_file_bytes: u64,
+    enabled: AtomicBool,
+    /// Queue to the writer task, started on first use
+    writer: OnceLock<mpsc::UnboundedSender<WriterMessage>>,
+}
+
+impl SessionTranscripts {
+    /// Create a disabled transcript store rooted at `root`, rotating files
+    /// once they reach `max_file_bytes`.
+    pub fn new(root: PathBuf, max_file_bytes: u64) -> Self {
+        Self {
+            root,
+            max_file_bytes: max_file_bytes.max(1),
+            enabled: AtomicBool::new(false),
+            writer: OnceLock::new(),
+        }
+    }
+
+    /// Turn transcript writing on or off (driven by preferences).
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Queue an emitted update for its session's transcript.
+    ///
+    /// Does nothing while transcripts are disabled. Must be called from
+    /// within a tokio runtime (the writer task starts on first use).
+    pub fn append(&self, event: &AcpSessionUpdateEvent) {
+        if !self.enabled.load(Ordering::Relaxed) {
+            return;
+        }
+        if self
+            .writer()
+            .send(WriterMessage::Append(Box::new(event.clone())))
+            .is_err()
+        {
+            log::debug!("Transcript writer stopped, dropping update");
+        }
+    }
+
+    /// Load a session's transcript, oldest update first.
+    ///
+    /// Waits for updates queued so far to be written. Lines that can't be
+    /// parsed are skipped with a warning.
+    ///
+    /// # Returns
+    /// * `Ok(Vec)` - The recorded updates (empty if there is no transcript)
+    /// * `Err(ApiError::IoError)` - If a transcript file can't be read
+    pub async fn read(
+        &self,
+        workspace_id: &WorkspaceId,
+        session_id: &SessionId,
+    ) -> Result<Vec<AcpSessionUpdateEvent>, ApiError> {
+        if let Some(writer) = self.writer.get() {
+            let (done_tx, done_rx) = oneshot::channel();
+            if writer.send(WriterMessage::Flush(done_tx)).is_ok() {
+                let _ = done_rx.await;
+            }
+        }
+
+        let path = transcript_path(&self.root, workspace_id, session_id);
+        BlockingFsLimiter::shared()
+            .run(move || read_transcript(&path))
+            .await
+            .map_err(|e| ApiError::IoError {
+                message: format!("Failed to spawn blocking task: {e}"),
+            })?
+    }
+
+    fn writer(&self) -> &mpsc::UnboundedSender<WriterMessage> {
+        self.writer.get_or_init(|| {
+            let (tx, rx) = mpsc::unbounded_channel();
+            tokio::spawn(run_writer(self.root.clone(), self.max_file_bytes, rx));
+            tx
+        })
+    }
+}
+
+/// Write queued updates in batches until every sender is gone.
+async fn run_writer(
+    root: PathBuf,
+    max_file_bytes: u64,
+    mut rx: mpsc::UnboundedReceiver<WriterMessage>,
+) {
+    while let Some(first) = rx.recv().await {
+        let mut events = Vec::new();
+        let mut flushes = Vec::new();
+        let mut next = Some(first);
+        while let Some(message) = next {
+            match message {
+                WriterMessage::Append(event) => events.push(*event),
+                WriterMessage::Flush(done) => flushes.push(done),
+            }
+            next = rx.try_recv().ok();
+        }
+
+        if !events.is_empty() {
+            let root = root.clone();
+            let written = BlockingFsLimiter::shared()
+                .run(move || write_batch(&root, max_file_bytes, events))
+                .await;
+            match written {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => log::warn!("Failed to write session transcript: {e}"),
+                Err(e) => log::warn!("Session transcript write task failed: {e}"),
+            }
+        }
+        for done in flushes {
+            let _ = done.send(());
+        }
+    }
+}
+
+/// Append a batch of updates, grouped by transcript file in arrival order.
+fn write_batch(
+    root: &Path,
+    max_file_bytes: u64,
+    events: Vec<AcpSessionUpdateEvent>,
+) -> std::io::Result<()> {
+    let mut order: Vec<PathBuf> = Vec::new();
+    let mut lines: HashMap<PathBuf, String> = HashMap::new();
+    for event in events {
+        let path = transcript_path(root, &event.workspace_id, &event.session_id);
+        let line = serde_json::to_string(&event)?;
+        let text = lines.entry(path.clone()).or_insert_with(|| {
+            order.push(path);
+            String::new()
+        });
+        text.push_str(&line);
+        text.push('\n');
+    }
+
+    for path in order {
+        append_with_rotation(&path, &lines[&path], max_file_bytes)?;
+    }
+    Ok(())
+}
+
+fn append_with_rotation(path: &Path, text: &str, max_file_bytes: u64) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let len = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    if len > 0 && len + text.len() as u64 > max_file_bytes {
+        std::fs::rename(path, rotated_path(path))?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    file.write_all(text.as_bytes())
+}
+
+fn read_transcript(path: &Path) -> Result<Vec<AcpSessionUpdateEvent>, ApiError> {
+    let mut events = Vec::new();
+    for file in [rotated_path(path), path.to_path_buf()] {
+        let contents = match std::fs::read_to_string(&file) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => {
+                return Err(ApiError::IoError {
+                    message: format!("Failed to read transcript {}: {e}", file.display()),
+                });
+            }
+        };
+        for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+            match serde_json::from_str(line) {
+                Ok(event) => events.push(event),
+                Err(e) => log::warn!(
+                    "Skipping unreadable transcript line: file={}, error={e}",
+                    file.display()
+                ),
+            }
+        }
+    }
+    Ok(events)
+}
+
+/// `<root>/<workspace>/<session>.jsonl`, with IDs reduced to safe file names.
+fn transcript_path(root: &Path, workspace_id: &WorkspaceId, session_id: &SessionId) -> PathBuf {
+    root.join(file_name_part(workspace_id))
+        .join(format!("{}.jsonl", file_name_part(session_id)))
+}
+
+/// Where a transcript goes when it is rotated out.
+fn rotated_path(path: &Path) -> PathBuf {
+    path.with_extension("1.jsonl")
+}
+
+/// Adapter-issued session IDs may contain anything; keep only characters
+/// that can't escape the directory or collide with the rotation suffix.
+fn file_name_part(id: &str) -> String {
+    let part: String = id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if part.is_empty() {
+        "_".to_string()
+    } else {
+        part
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::types::AcpSessionUpdate;
+
+    fn event(session_id: &str, seq: u64) -> AcpSessionUpdateEvent {
+        AcpSessionUpdateEvent {
+            workspace_id: "ws-1".to_string(),
+            agent_id: "agent-1".to_string(),
+            plugin_id: "claude-code".to_string(),
+            display_name: Some("Reviewer".to_string()),
+            session_id: session_id.to_string(),
+            seq,
+            emitted_at_ms: 1_700_000_000_000.0 + seq as f64,
+            turn_id: Some("turn-1".to_string()),
+            update: AcpSessionUpdate::AgentMessageChunk {
+                content: serde_json::json!({ "type": "text", "text": format!("chunk {seq}") }),
+            },
+        }
+    }
+
+    fn temp_root() -> PathBuf {
+        std::env::temp_dir().join(format!("transcripts_{}", uuid::Uuid::new_v4()))
+    }
+
+    fn seqs(events: &[AcpSessionUpdateEvent]) -> Vec<u64> {
+        events.iter().map(|event| event.seq).collect()
+    }
+
+    #[tokio::test]
+    async fn test_updates_are_persisted_and_reloaded_in_order() {
+        let root = temp_root();
+        let transcripts = SessionTranscripts::new(root.clone(), DEFAULT_MAX_TRANSCRIPT_BYTES);
+        let workspace_id = "ws-1".to_string();
+
+        // Nothing is written until transcripts are enabled
+        transcripts.append(&event("session-1", 0));
+        transcripts.set_enabled(true);
+        for seq in 1..=5 {
+            transcripts.append(&event("session-1", seq));
+            transcripts.append(&event("session-2", 100 + seq));
+        }
+
+        let first = transcripts
+            .read(&workspace_id, &"session-1".to_string())
+            .await
+            .unwrap();
+        assert_eq!(seqs(&first), vec![1, 2, 3, 4, 5]);
+        assert_eq!(first[0].turn_id.as_deref(), Some("turn-1"));
+        assert!(matches!(
+            &first[4].update,
+            AcpSessionUpdate::AgentMessageChunk { content } if content["text"] == "chunk 5"
+        ));
+        let second = transcripts
+            .read(&workspace_id, &"session-2".to_string())
+            .await
+            .unwrap();
+        assert_eq!(seqs(&second), vec![101, 102, 103, 104, 105]);
+        assert!(transcripts
+            .read(&workspace_id, &"unknown".to_string())
+            .await
+            .unwrap()
+            .is_empty());
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[tokio::test]
+    async fn test_transcripts_rotate_past_the_size_cap() {
+        let root = temp_root();
+        let line_len = serde_json::to_string(&event("session-1", 10))
+            .unwrap()
+            .len() as u64
+            + 1;
+        // Room for three updates per file
+        let transcripts = SessionTranscripts::new(root.clone(), line_len * 3);
+        transcripts.set_enabled(true);
+        let workspace_id = "ws-1".to_string();
+        let session_id = "session-1".to_string();
+
+        for seq in 10..20 {
+            transcripts.append(&event("session-1", seq));
+            // One write per update, so each append checks the cap
+            transcripts.read(&workspace_id, &session_id).await.unwrap();
+        }
+
+        let events = transcripts.read(&workspace_id, &session_id).await.unwrap();
+        // Only the current and the previous file are kept
+        assert_eq!(seqs(&events), vec![16, 17, 18, 19]);
+        let path = transcript_path(&root, &workspace_id, &session_id);
+        for file in [rotated_path(&path), path] {
+            assert!(std::fs::metadata(&file).unwrap().len() <= line_len * 3);
+        }
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_transcript_path_sanitizes_ids() {
+        let root = Path::new("/data/transcripts");
+
+        assert_eq!(
+            transcript_path(root, &"ws-1".to_string(), &"../../etc/passwd".to_string()),
+            root.join("ws-1").join("______etc_passwd.jsonl")
+        );
+        assert_eq!(
+            transcript_path(root, &"ws-1".to_string(), &String::new()),
+            root.join("ws-1").join("_.jsonl")
+        );
+        assert_eq!(
+            rotated_path(&root.join("ws-1").join("s_1.jsonl")),
+            root.join("ws-1").join("s_1.1.jsonl")
+        );
+    }
+}
@@ -4,18 +4,86 @@
 //! - AgentRegistry (agents within this workspace)
 //! - TerminalManager (terminals for this workspace)
 //! - FsManager (file system operations scoped to this workspace)
+//! - FsWatcher (optional, emits `fs/changed` while the UI is watching)
 
 use std::path::PathBuf;
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+
+use tauri::Emitter;
 
 use crate::api::types::{
-    AgentId, AgentSummary, ApiError, SessionId, WorkspaceId, WorkspaceSummary,
+    AgentId, AgentRuntimeStatus, AgentSummary, ApiError, BusyPromptPolicy, EnvPreview,
+    FsChangedEvent, McpServerConfig, Page, SessionId, TerminalShell, WorkspaceId, WorkspaceLimits,
+    WorkspaceSummary,
+};
+use crate::runtime::agents::{
+    AgentRecord, AgentRegistry, AgentRuntime, DEFAULT_MAX_OUTSTANDING_PROMPTS,
+};
+use crate::runtime::env_file::{parse_env_file, ENV_FILE_NAME};
+use crate::runtime::fs::{FsManager, DEFAULT_MAX_READ_BYTES};
+use crate::runtime::fs_watch::{FsWatcher, EVENT_FS_CHANGED};
+use crate::runtime::terminal::{
+    TerminalManager, DEFAULT_MAX_TERMINALS, DEFAULT_OUTPUT_CAPTURE_LIMIT,
 };
-use crate::runtime::agents::{AgentRegistry, AgentRuntime};
-use crate::runtime::fs::FsManager;
-use crate::runtime::terminal::TerminalManager;
+
+/// Hard ceiling on `max_read_bytes`; whole files are held in memory
+const MAX_READ_BYTES_CEILING: u32 = 64 * 1024 * 1024;
+/// Hard ceiling on `output_capture_limit`, per output stream
+const OUTPUT_CAPTURE_LIMIT_CEILING: u32 = 16 * 1024 * 1024;
+/// Hard ceiling on `max_terminals`
+const MAX_TERMINALS_CEILING: u32 = 256;
+/// Hard ceiling on `max_outstanding_prompts`
+const MAX_OUTSTANDING_PROMPTS_CEILING: u32 = 64;
+
+impl Default for WorkspaceLimits {
+    fn default() -> Self {
+        Self {
+            max_read_bytes: DEFAULT_MAX_READ_BYTES as u32,
+            output_capture_limit: DEFAULT_OUTPUT_CAPTURE_LIMIT as u32,
+            max_terminals: DEFAULT_MAX_TERMINALS as u32,
+            max_outstanding_prompts: DEFAULT_MAX_OUTSTANDING_PROMPTS,
+        }
+    }
+}
+
+impl WorkspaceLimits {
+    /// Reject zero limits and limits above the safety ceilings.
+    pub fn validate(&self) -> Result<(), ApiError> {
+        if self.max_read_bytes == 0 || self.max_read_bytes > MAX_READ_BYTES_CEILING {
+            return Err(ApiError::InvalidInput {
+                message: format!(
+                    "maxReadBytes must be between 1 and {MAX_READ_BYTES_CEILING} bytes"
+                ),
+            });
+        }
+        if self.output_capture_limit == 0
+            || self.output_capture_limit > OUTPUT_CAPTURE_LIMIT_CEILING
+        {
+            return Err(ApiError::InvalidInput {
+                message: format!(
+                    "outputCaptureLimit must be between 1 and {OUTPUT_CAPTURE_LIMIT_CEILING} bytes"
+                ),
+            });
+        }
+        if self.max_terminals == 0 || self.max_terminals > MAX_TERMINALS_CEILING {
+            return Err(ApiError::InvalidInput {
+                message: format!("maxTerminals must be between 1 and {MAX_TERMINALS_CEILING}"),
+            });
+        }
+        if self.max_outstanding_prompts == 0
+            || self.max_outstanding_prompts > MAX_OUTSTANDING_PROMPTS_CEILING
+        {
+            return Err(ApiError::InvalidInput {
+                message: format!(
+                    "maxOutstandingPrompts must be between 1 and {MAX_OUTSTANDING_PROMPTS_CEILING}"
+                ),
+            });
+        }
+        Ok(())
+    }
+}
 
 /// Runtime state for a single workspace.
 ///
@@ -26,6 +94,8 @@ pub struct WorkspaceRuntime {
     workspace_id: WorkspaceId,
     /// Canonicalized absolute path to workspace root
     root_dir: PathBuf,
+    /// Name given by the user (`None`: use the directory name)
+    name: Mutex<Option<String>>,
     /// Timestamp when workspace was created (milliseconds since epoch)
     created_at_ms: f64,
     /// Registry of agent entities within this workspace
@@ -34,6 +104,10 @@ pub struct WorkspaceRuntime {
     terminal_manager: Arc<TerminalManager>,
     /// File system manager scoped to this workspace
     fs_manager: Arc<FsManager>,
+    /// Size limits applied by the terminal and file system managers
+    limits: Mutex<WorkspaceLimits>,
+    /// File watcher, present only while the frontend has opted in
+    watcher: Mutex<Option<FsWatcher>>,
     // Future additions for subsequent user stories.
 }
 
@@ -49,21 +123,50 @@ impl WorkspaceRuntime {
             .map(|d| d.as_millis() as f64)
             .unwrap_or(0.0);
 
+        Self::restore(workspace_id, root_dir, None, created_at_ms, Vec::new())
+    }
+
+    /// Recreates a WorkspaceRuntime saved in an earlier session.
+    ///
+    /// # Arguments
+    /// * `workspace_id` - The workspace's original identifier
+    /// * `root_dir` - Canonicalized absolute path to workspace root
+    /// * `name` - Name the user gave the workspace, if any
+    /// * `created_at_ms` - When the workspace was first created
+    /// * `agents` - Agents saved with the workspace (restored stopped)
+    pub fn restore(
+        workspace_id: WorkspaceId,
+        root_dir: PathBuf,
+        name: Option<String>,
+        created_at_ms: f64,
+        agents: Vec<AgentRecord>,
+    ) -> Self {
         log::info!(
             "Creating workspace runtime: id={workspace_id}, root={}",
             root_dir.display()
         );
 
-        let terminal_manager = Arc::new(TerminalManager::new(root_dir.clone()));
-        let fs_manager = Arc::new(FsManager::new(root_dir.clone()));
+        let limits = WorkspaceLimits::default();
+        let terminal_manager = Arc::new(TerminalManager::new(
+            root_dir.clone(),
+            limits.output_capture_limit as usize,
+            None,
+        ));
+        let fs_manager = Arc::new(FsManager::new(
+            root_dir.clone(),
+            limits.max_read_bytes as u64,
+        ));
 
         Self {
             workspace_id,
             root_dir,
+            name: Mutex::new(name),
             created_at_ms,
-            agent_registry: AgentRegistry::new(),
+            agent_registry: AgentRegistry::with_agents(agents),
             terminal_manager,
             fs_manager,
+            limits: Mutex::new(limits),
+            watcher: Mutex::new(None),
         }
     }
 
@@ -72,10 +175,104 @@ impl WorkspaceRuntime {
         WorkspaceSummary {
             workspace_id: self.workspace_id.clone(),
             root_dir: self.root_dir.display().to_string(),
+            name: self.custom_name().or_else(|| {
+                self.root_dir
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+            }),
             created_at_ms: self.created_at_ms,
         }
     }
 
+    /// Current size limits for agent file reads and command output.
+    pub fn limits(&self) -> WorkspaceLimits {
+        *self.limits.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Apply new size limits; they take effect for the next read or command.
+    ///
+    /// # Returns
+    /// * `Ok(())` - Limits applied
+    /// * `Err(ApiError::InvalidInput)` - If a limit is zero or above its ceiling
+    pub async fn set_limits(&self, limits: WorkspaceLimits) -> Result<(), ApiError> {
+        limits.validate()?;
+
+        {
+            let mut current = self.limits.lock().unwrap_or_else(|e| e.into_inner());
+            self.fs_manager
+                .set_max_read_bytes(limits.max_read_bytes as u64);
+            self.terminal_manager
+                .set_output_capture_limit(limits.output_capture_limit as usize);
+            self.terminal_manager
+                .set_max_terminals(limits.max_terminals as usize);
+            *current = limits;
+        }
+        self.agent_registry
+            .set_max_outstanding_prompts(limits.max_outstanding_prompts)
+            .await;
+        Ok(())
+    }
+
+    /// Set the shell terminal commands run under (`None`: platform default).
+    ///
+    /// # Returns
+    /// * `Ok(())` - Shell applied to the next command
+    /// * `Err(ApiError::InvalidInput)` - If the shell program is empty
+    pub fn set_terminal_shell(&self, shell: Option<TerminalShell>) -> Result<(), ApiError> {
+        if shell
+            .as_ref()
+            .is_some_and(|shell| shell.program.trim().is_empty())
+        {
+            return Err(ApiError::InvalidInput {
+                message: "Shell program cannot be empty".to_string(),
+            });
+        }
+        self.terminal_manager.set_shell(shell);
+        Ok(())
+    }
+
+    /// The name the user gave this workspace, if they renamed it.
+    pub fn custom_name(&self) -> Option<String> {
+        self.name.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Rename the workspace.
+    pub fn set_name(&self, name: String) {
+        *self.name.lock().unwrap_or_else(|e| e.into_inner()) = Some(name);
+    }
+
+    /// Preview the variables the workspace `.env` would pass to adapters.
+    ///
+    /// Parses `<root>/.env` without injecting anything. Only variable names
+    /// are returned; values stay on the backend. A missing file yields an
+    /// empty preview with `exists: false`.
+    pub async fn preview_env(&self) -> Result<EnvPreview, ApiError> {
+        let env_path = self.root_dir.join(ENV_FILE_NAME);
+
+        let content = match tokio::fs::read_to_string(&env_path).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(EnvPreview {
+                    exists: false,
+                    keys: Vec::new(),
+                    warnings: Vec::new(),
+                });
+            }
+            Err(e) => {
+                return Err(ApiError::IoError {
+                    message: format!("Failed to read {}: {e}", env_path.display()),
+                });
+            }
+        };
+
+        let parsed = parse_env_file(&content);
+        Ok(EnvPreview {
+            exists: true,
+            keys: parsed.keys(),
+            warnings: parsed.warnings,
+        })
+    }
+
     /// Creates an agent entity within this workspace.
     ///
     /// # Arguments
@@ -97,16 +294,33 @@ impl WorkspaceRuntime {
         Ok(record.to_summary(&self.workspace_id))
     }
 
-    /// Lists all agents within this workspace.
+    /// Lists agents within this workspace, oldest first.
+    ///
+    /// # Arguments
+    /// * `offset` - Agents to skip (default 0)
+    /// * `limit` - Most agents to return (default: all)
     ///
     /// # Returns
-    /// * `Vec<AgentSummary>` - List of all agent summaries
-    pub async fn list_agents(&self) -> Vec<AgentSummary> {
-        let records = self.agent_registry.list_agents().await;
-        records
-            .into_iter()
+    /// * `Page<AgentSummary>` - The requested agent summaries and the total count
+    pub async fn list_agents(&self, offset: Option<u32>, limit: Option<u32>) -> Page<AgentSummary> {
+        self.agent_registry
+            .list_agents(offset, limit)
+            .await
             .map(|r| r.to_summary(&self.workspace_id))
-            .collect()
+    }
+
+    /// Records of all agents in this workspace, for saving.
+    pub async fn agent_records(&self) -> Vec<AgentRecord> {
+        self.agent_registry.list_agents(None, None).await.items
+    }
+
+    /// Deletes an agent, shutting down its adapter if it is running.
+    ///
+    /// # Returns
+    /// * `Ok(())` - Agent removed
+    /// * `Err(ApiError::AgentNotFound)` - If agent doesn't exist
+    pub async fn delete_agent(&self, agent_id: &AgentId) -> Result<(), ApiError> {
+        self.agent_registry.delete_agent(agent_id).await
     }
 
     /// Get or create an AgentRuntime for the given agent.
@@ -129,6 +343,154 @@ impl WorkspaceRuntime {
             .await
     }
 
+    /// The adapter session last used by an agent, if any (for `session/load`).
+    pub async fn agent_session_id(
+        &self,
+        agent_id: &AgentId,
+    ) -> Result<Option<SessionId>, ApiError> {
+        Ok(self.agent_registry.get_agent(agent_id).await?.session_id)
+    }
+
+    /// Remember the adapter session used by an agent so a restart can resume it.
+    pub async fn record_agent_session(
+        &self,
+        agent_id: &AgentId,
+        session_id: SessionId,
+    ) -> Result<(), ApiError> {
+        self.agent_registry
+            .set_session_id(agent_id, session_id)
+            .await
+    }
+
+    /// MCP servers configured for an agent's adapter sessions.
+    pub async fn agent_mcp_servers(
+        &self,
+        agent_id: &AgentId,
+    ) -> Result<Vec<McpServerConfig>, ApiError> {
+        Ok(self.agent_registry.get_agent(agent_id).await?.mcp_servers)
+    }
+
+    /// Configure the MCP servers for an agent (used from its next session on).
+    pub async fn set_agent_mcp_servers(
+        &self,
+        agent_id: &AgentId,
+        mcp_servers: Vec<McpServerConfig>,
+    ) -> Result<(), ApiError> {
+        self.agent_registry
+            .set_mcp_servers(agent_id, mcp_servers)
+            .await
+    }
+
+    /// Turn automatic restarts after unexpected exits on or off for an agent.
+    pub async fn set_agent_auto_restart(
+        &self,
+        agent_id: &AgentId,
+        enabled: bool,
+    ) -> Result<(), ApiError> {
+        self.agent_registry
+            .set_auto_restart(agent_id, enabled)
+            .await
+    }
+
+    /// Turn protocol frame tracing on or off for an agent.
+    ///
+    /// Tracing lives on the agent's runtime (created if needed) and isn't
+    /// saved, so it is off again after the app restarts.
+    pub async fn set_agent_protocol_trace(
+        &self,
+        agent_id: &AgentId,
+        enabled: bool,
+    ) -> Result<(), ApiError> {
+        let runtime = self
+            .agent_registry
+            .ensure_runtime(self.workspace_id.clone(), agent_id.clone())
+            .await?;
+        runtime.set_protocol_trace(enabled);
+        log::info!("Agent protocol trace configured: id={agent_id}, enabled={enabled}");
+        Ok(())
+    }
+
+    /// Choose whether an agent queues or rejects prompts sent mid-turn.
+    pub async fn set_agent_busy_prompt_policy(
+        &self,
+        agent_id: &AgentId,
+        policy: BusyPromptPolicy,
+    ) -> Result<(), ApiError> {
+        self.agent_registry
+            .set_busy_prompt_policy(agent_id, policy)
+            .await
+    }
+
+    /// Current runtime status of an agent (`Stopped` if never started).
+    pub async fn agent_status(&self, agent_id: &AgentId) -> Result<AgentRuntimeStatus, ApiError> {
+        self.agent_registry.agent_status(agent_id).await
+    }
+
+    /// Slash commands an agent's session last announced (empty if none yet).
+    pub async fn available_commands(
+        &self,
+        agent_id: &AgentId,
+        session_id: &SessionId,
+    ) -> Result<Vec<serde_json::Value>, ApiError> {
+        self.agent_registry
+            .available_commands(agent_id, session_id)
+            .await
+    }
+
+    /// Mode an agent's session was last reported in (`None` if none yet).
+    pub async fn current_mode(
+        &self,
+        agent_id: &AgentId,
+        session_id: &SessionId,
+    ) -> Result<Option<String>, ApiError> {
+        self.agent_registry.current_mode(agent_id, session_id).await
+    }
+
+    /// Switch an agent's session to another mode.
+    pub async fn set_mode(
+        &self,
+        agent_id: AgentId,
+        session_id: SessionId,
+        mode_id: String,
+    ) -> Result<(), ApiError> {
+        let agent_runtime = self.ensure_agent_runtime(agent_id).await?;
+        agent_runtime.set_mode(session_id, mode_id).await
+    }
+
+    /// Open another session on an agent, starting it if needed.
+    pub async fn new_session(&self, agent_id: AgentId) -> Result<SessionId, ApiError> {
+        let agent_runtime = self.ensure_agent_runtime(agent_id).await?;
+        agent_runtime.new_session().await
+    }
+
+    /// Close a session opened with [`Self::new_session`].
+    pub async fn close_session(
+        &self,
+        agent_id: AgentId,
+        session_id: SessionId,
+    ) -> Result<(), ApiError> {
+        let agent_runtime = self.ensure_agent_runtime(agent_id).await?;
+        agent_runtime.close_session(session_id).await
+    }
+
+    /// Shut down the adapter process of every running agent in this workspace.
+    ///
+    /// # Returns
+    /// * Number of agents that were stopped
+    pub async fn shutdown_all_agents(&self) -> usize {
+        self.agent_registry.shutdown_all().await
+    }
+
+    /// Whether an agent in this workspace is running on `plugin_id`.
+    pub async fn is_plugin_in_use(&self, plugin_id: &str) -> bool {
+        self.agent_registry.has_running_plugin(plugin_id).await
+    }
+
+    /// Number of agents in this workspace whose adapter is running.
+    pub async fn running_agent_count(&self) -> usize {
+        self.agent_registry.running_count().await
+    }
+
     /// Stop the current turn for a given agent/session.
     ///
     /// US-12: Routes to the agent runtime to cancel the current turn.
@@ -141,6 +503,50 @@ impl WorkspaceRuntime {
         agent_runtime.stop_turn(session_id).await
     }
 
+    /// Stop an agent's adapter process (no-op if it isn't running).
+    pub async fn stop_agent(&self, agent_id: AgentId) -> Result<(), ApiError> {
+        let agent_runtime = self.ensure_agent_runtime(agent_id).await?;
+        agent_runtime.stop().await
+    }
+
+    /// Start emitting `fs/changed` events for this workspace's root.
+    ///
+    /// Idempotent: a workspace that is already watched keeps its watcher.
+    pub fn start_watch(&self, app: tauri::AppHandle) -> Result<(), ApiError> {
+        let mut watcher = self.watcher.lock().unwrap_or_else(|e| e.into_inner());
+        if watcher.is_some() {
+            return Ok(());
+        }
+
+        let workspace_id = self.workspace_id.clone();
+        *watcher = Some(FsWatcher::start(&self.root_dir, move |path, kind| {
+            let event = FsChangedEvent {
+                workspace_id: workspace_id.clone(),
+                path,
+                kind,
+            };
+            if let Err(e) = app.emit(EVENT_FS_CHANGED, &event) {
+                log::warn!("Failed to emit fs/changed event: {e}");
+            }
+        })?);
+
+        log::info!("Watching workspace: id={}", self.workspace_id);
+        Ok(())
+    }
+
+    /// Stop watching this workspace's root (no-op if not watching).
+    pub fn stop_watch(&self) {
+        let stopped = self
+            .watcher
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take()
+            .is_some();
+        if stopped {
+            log::info!("Stopped watching workspace: id={}", self.workspace_id);
+        }
+    }
+
     /// Get the workspace ID.
     #[allow(dead_code)]
     pub fn workspace_id(&self) -> &WorkspaceId {
@@ -211,4 +617,37 @@ mod tests {
         assert_eq!(summary.plugin_id, "claude-code");
         assert_eq!(summary.display_name, Some("Test Agent".to_string()));
     }
+
+    #[tokio::test]
+    async fn test_workspace_agent_protocol_trace_toggle() {
+        let runtime = WorkspaceRuntime::new("test-workspace-trace".to_string(), env::temp_dir());
+        let agent = runtime
+            .create_agent("claude-code".to_string(), None)
+            .await
+            .unwrap();
+
+        runtime
+            .set_agent_protocol_trace(&agent.agent_id, true)
+            .await
+            .unwrap();
+        let agent_runtime = runtime
+            .agent_registry
+            .ensure_runtime(runtime.workspace_id.clone(), agent.agent_id.clone())
+            .await
+            .unwrap();
+        assert!(agent_runtime.protocol_trace_enabled());
+
+        runtime
+            .set_agent_protocol_trace(&agent.agent_id, false)
+            .await
+            .unwrap();
+        assert!(!agent_runtime.protocol_trace_enabled());
+
+        assert!(matches!(
+            runtime
+                .set_agent_protocol_trace(&"missing".to_string(), true)
+                .await,
+            Err(ApiError::AgentNotFound { .. })
+        ));
+    }
 }
@@ -4,17 +4,36 @@
 //! thread-safe access to workspace runtimes.
 
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
 use tokio::sync::Mutex;
 use uuid::Uuid;
 
 use crate::api::types::{
-    AgentId, AgentSummary, ApiError, SessionId, WorkspaceId, WorkspaceSummary,
+    AgentId, AgentRuntimeStatus, AgentSummary, ApiError, BusyPromptPolicy, McpServerConfig, Page,
+    SessionId, TerminalShell, WorkspaceId, WorkspaceLimits, WorkspaceSummary,
 };
 use crate::runtime::agents::AgentRuntime;
 use crate::runtime::path::canonicalize_workspace_root;
 use crate::runtime::workspace::WorkspaceRuntime;
+use crate::runtime::workspace_store::{PersistedWorkspace, PersistedWorkspaces, WorkspaceStore};
+
+/// Upper bound on [`WorkspaceManager::shutdown_all`] when the app exits.
+///
+/// Covers an adapter's cancel and exit grace periods; adapters still running
+/// afterwards are killed when their process handles are dropped.
+pub const APP_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(8);
+
+/// Totals across every workspace, for diagnostics.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RuntimeCounts {
+    pub workspaces: usize,
+    pub agents: usize,
+    pub running_agents: usize,
+    pub active_terminals: usize,
+}
 
 /// Global manager for all workspaces.
 ///
@@ -25,25 +44,144 @@ pub struct WorkspaceManager {
     workspaces: Mutex<HashMap<String, Arc<WorkspaceRuntime>>>,
     /// Currently focused workspace ID (UI state)
     focused_workspace_id: Mutex<Option<WorkspaceId>>,
+    /// Where the workspace list is saved (none: in-memory only)
+    store: Option<WorkspaceStore>,
+    /// Serializes writes so an older snapshot never overwrites a newer one
+    persist_lock: Mutex<()>,
 }
 
 impl WorkspaceManager {
-    /// Creates a new WorkspaceManager.
+    /// Creates a new WorkspaceManager that keeps workspaces in memory only.
     pub fn new() -> Self {
         log::debug!("Initializing WorkspaceManager");
         Self {
             workspaces: Mutex::new(HashMap::new()),
             focused_workspace_id: Mutex::new(None),
+            store: None,
+            persist_lock: Mutex::new(()),
+        }
+    }
+
+    /// Creates a WorkspaceManager that saves its workspaces to `store`.
+    ///
+    /// Call [`Self::load_from_disk`] to restore previously saved workspaces.
+    pub fn with_store(store: WorkspaceStore) -> Self {
+        Self {
+            store: Some(store),
+            ..Self::new()
+        }
+    }
+
+    /// Restores the workspaces saved by a previous session.
+    ///
+    /// Workspaces whose root directory no longer exists are dropped with a
+    /// warning. Saved agents are restored stopped, without emitting events;
+    /// they start on their first prompt as usual.
+    ///
+    /// # Returns
+    /// * `Ok(usize)` - Number of workspaces restored
+    /// * `Err(ApiError::IoError)` - If the workspace file can't be read
+    pub async fn load_from_disk(&self) -> Result<usize, ApiError> {
+        let Some(store) = &self.store else {
+            return Ok(0);
+        };
+        let saved = store.load()?;
+        let saved_count = saved.workspaces.len();
+
+        let mut restored = 0;
+        {
+            let mut workspaces = self.workspaces.lock().await;
+            for entry in saved.workspaces {
+                let root_dir = match canonicalize_workspace_root(&entry.root_dir) {
+                    Ok(root_dir) => root_dir,
+                    Err(e) => {
+                        log::warn!(
+                            "Dropping saved workspace: id={}, root={}, error={e}",
+                            entry.workspace_id,
+                            entry.root_dir
+                        );
+                        continue;
+                    }
+                };
+                if workspaces
+                    .values()
+                    .any(|existing| existing.root_dir() == &root_dir)
+                {
+                    log::warn!(
+                        "Dropping duplicate saved workspace: id={}, root={}",
+                        entry.workspace_id,
+                        root_dir.display()
+                    );
+                    continue;
+                }
+                let runtime = WorkspaceRuntime::restore(
+                    entry.workspace_id.clone(),
+                    root_dir,
+                    entry.name,
+                    entry.created_at_ms,
+                    entry.agents,
+                );
+                workspaces.insert(entry.workspace_id, Arc::new(runtime));
+                restored += 1;
+            }
+
+            if let Some(focused) = saved.focused_workspace_id {
+                if workspaces.contains_key(&focused) {
+                    *self.focused_workspace_id.lock().await = Some(focused);
+                }
+            }
+        }
+
+        log::info!("Workspaces loaded: restored={restored}, saved={saved_count}");
+        if restored != saved_count {
+            self.persist().await;
+        }
+        Ok(restored)
+    }
+
+    /// Save the current workspace list and focus, if a store is configured.
+    ///
+    /// Failures are logged; the in-memory state stays authoritative.
+    async fn persist(&self) {
+        let Some(store) = &self.store else {
+            return;
+        };
+        let _guard = self.persist_lock.lock().await;
+
+        let data = {
+            let workspaces = self.workspaces.lock().await;
+            let mut saved = Vec::with_capacity(workspaces.len());
+            for runtime in workspaces.values() {
+                let summary = runtime.summary();
+                saved.push(PersistedWorkspace {
+                    workspace_id: summary.workspace_id,
+                    root_dir: summary.root_dir,
+                    name: runtime.custom_name(),
+                    created_at_ms: summary.created_at_ms,
+                    agents: runtime.agent_records().await,
+                });
+            }
+            PersistedWorkspaces {
+                workspaces: saved,
+                focused_workspace_id: self.focused_workspace_id.lock().await.clone(),
+            }
+        };
+
+        if let Err(e) = store.save(&data) {
+            log::error!("Failed to save workspaces: {e}");
         }
     }
 
     /// Creates a new workspace with the given root directory.
     ///
+    /// Adding a directory that already has a workspace (after
+    /// canonicalization) returns the existing workspace instead of a duplicate.
+    ///
     /// # Arguments
     /// * `root_dir` - Path to workspace root (will be canonicalized)
     ///
     /// # Returns
-    /// * `Ok(WorkspaceSummary)` - Summary of created workspace
+    /// * `Ok(WorkspaceSummary)` - Summary of the created (or existing) workspace
     /// * `Err(ApiError)` - If path validation fails
     pub async fn create_workspace(&self, root_dir: &str) -> Result<WorkspaceSummary, ApiError> {
         if root_dir.trim().is_empty() {
@@ -67,15 +205,28 @@ impl WorkspaceManager {
         // Get summary before inserting (avoids holding lock during summary creation)
         let summary = runtime.summary();
 
-        // Insert into map
+        // Insert into map, unless the directory already has a workspace
         {
             let mut workspaces = self.workspaces.lock().await;
+            if let Some(existing) = workspaces
+                .values()
+                .find(|existing| existing.root_dir() == &canonical_root)
+            {
+                let existing = existing.summary();
+                log::info!(
+                    "Workspace already exists for root: id={}, root={}",
+                    existing.workspace_id,
+                    existing.root_dir
+                );
+                return Ok(existing);
+            }
             workspaces.insert(workspace_id.clone(), runtime);
             log::info!(
                 "Workspace created: id={workspace_id}, total_workspaces={}",
                 workspaces.len()
             );
         }
+        self.persist().await;
 
         Ok(summary)
     }
@@ -112,6 +263,7 @@ impl WorkspaceManager {
             *focused = Some(workspace_id.clone());
             log::info!("Workspace focus set: {workspace_id}");
         }
+        self.persist().await;
 
         Ok(())
     }
@@ -126,11 +278,20 @@ impl WorkspaceManager {
         focused.clone()
     }
 
-    /// Lists all workspaces with their summaries.
+    /// Lists workspaces with their summaries, newest first.
+    ///
+    /// # Arguments
+    /// * `offset` - Workspaces to skip (default 0)
+    /// * `limit` - Most workspaces to return (default: all)
     ///
     /// # Returns
-    /// * `Vec<WorkspaceSummary>` - List of workspace summaries, sorted by creation time (newest first)
-    pub async fn list_workspaces(&self) -> Vec<WorkspaceSummary> {
+    /// * `Page<WorkspaceSummary>` - The requested workspace summaries, sorted by
+    ///   creation time (newest first), and the total count
+    pub async fn list_workspaces(
+        &self,
+        offset: Option<u32>,
+        limit: Option<u32>,
+    ) -> Page<WorkspaceSummary> {
         let workspaces = self.workspaces.lock().await;
         let mut summaries: Vec<WorkspaceSummary> = workspaces
             .values()
@@ -142,11 +303,14 @@ impl WorkspaceManager {
                 .partial_cmp(&a.created_at_ms)
                 .unwrap_or(std::cmp::Ordering::Equal)
         });
-        summaries
+        Page::slice(summaries, offset, limit)
     }
 
     /// Deletes a workspace by ID.
     ///
+    /// Running agents in the workspace are shut down first so their adapter
+    /// processes don't outlive it.
+    ///
     /// # Arguments
     /// * `workspace_id` - ID of the workspace to delete
     ///
@@ -167,11 +331,15 @@ impl WorkspaceManager {
             workspaces.remove(workspace_id)
         };
 
-        if removed.is_none() {
+        let Some(workspace) = removed else {
             return Err(ApiError::WorkspaceNotFound {
                 workspace_id: workspace_id.clone(),
             });
-        }
+        };
+
+        // Don't leave adapter processes running behind a deleted workspace
+        let stopped = workspace.shutdown_all_agents().await;
+        workspace.stop_watch();
 
         // Clear focus if this was the focused workspace
         {
@@ -181,7 +349,120 @@ impl WorkspaceManager {
             }
         }
 
-        log::info!("Workspace deleted: {workspace_id}");
+        self.persist().await;
+
+        log::info!("Workspace deleted: {workspace_id}, agents_stopped={stopped}");
+        Ok(())
+    }
+
+    /// Stops every agent and kills every terminal in all workspaces.
+    ///
+    /// Called when the app exits so adapters get the graceful cancel and
+    /// shutdown path instead of being killed with the process. Workspaces are
+    /// shut down concurrently; callers should bound the wait with
+    /// [`APP_SHUTDOWN_TIMEOUT`].
+    ///
+    /// # Returns
+    /// * Number of agents that were running and were stopped
+    pub async fn shutdown_all(&self) -> usize {
+        let workspaces: Vec<Arc<WorkspaceRuntime>> = {
+            let workspaces = self.workspaces.lock().await;
+            workspaces.values().cloned().collect()
+        };
+        log::info!("Shutting down all workspaces: count={}", workspaces.len());
+
+        let mut shutdowns = tokio::task::JoinSet::new();
+        for workspace in workspaces {
+            shutdowns.spawn(async move {
+                workspace.stop_watch();
+                let terminal_manager = workspace.terminal_manager();
+                let (stopped, ()) =
+                    tokio::join!(workspace.shutdown_all_agents(), terminal_manager.kill_all());
+                stopped
+            });
+        }
+
+        let mut stopped = 0;
+        while let Some(result) = shutdowns.join_next().await {
+            match result {
+                Ok(count) => stopped += count,
+                Err(e) => log::warn!("Workspace shutdown task failed: {e}"),
+            }
+        }
+        log::info!("All workspaces shut down: agents_stopped={stopped}");
+        stopped
+    }
+
+    /// Renames a workspace.
+    ///
+    /// # Arguments
+    /// * `workspace_id` - ID of the workspace to rename
+    /// * `name` - New display name (surrounding whitespace is trimmed)
+    ///
+    /// # Returns
+    /// * `Ok(WorkspaceSummary)` - Summary with the new name
+    /// * `Err(ApiError::InvalidInput)` - If the name is empty
+    /// * `Err(ApiError::WorkspaceNotFound)` - If workspace does not exist
+    pub async fn rename_workspace(
+        &self,
+        workspace_id: &WorkspaceId,
+        name: &str,
+    ) -> Result<WorkspaceSummary, ApiError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(ApiError::InvalidInput {
+                message: "Workspace name cannot be empty".to_string(),
+            });
+        }
+
+        let workspace = self.get_workspace(workspace_id).await?;
+        workspace.set_name(name.to_string());
+        self.persist().await;
+
+        log::info!("Workspace renamed: id={workspace_id}, name={name}");
+        Ok(workspace.summary())
+    }
+
+    /// Sets the size limits for agent file reads and command output.
+    ///
+    /// # Returns
+    /// * `Ok(WorkspaceLimits)` - The limits now in effect
+    /// * `Err(ApiError::InvalidInput)` - If a limit is zero or above its ceiling
+    /// * `Err(ApiError::WorkspaceNotFound)` - If workspace does not exist
+    pub async fn set_workspace_limits(
+        &self,
+        workspace_id: &WorkspaceId,
+        limits: WorkspaceLimits,
+    ) -> Result<WorkspaceLimits, ApiError> {
+        let workspace = self.get_workspace(workspace_id).await?;
+        workspace.set_limits(limits).await?;
+
+        log::info!(
+            "Workspace limits set: id={workspace_id}, max_read_bytes={}, output_capture_limit={}, max_terminals={}, max_outstanding_prompts={}",
+            limits.max_read_bytes,
+            limits.output_capture_limit,
+            limits.max_terminals,
+            limits.max_outstanding_prompts
+        );
+        Ok(workspace.limits())
+    }
+
+    /// Sets the shell terminal commands run under in a workspace.
+    ///
+    /// # Returns
+    /// * `Ok(())` - Shell applied to the next command
+    /// * `Err(ApiError::InvalidInput)` - If the shell program is empty
+    /// * `Err(ApiError::WorkspaceNotFound)` - If workspace does not exist
+    pub async fn set_workspace_shell(
+        &self,
+        workspace_id: &WorkspaceId,
+        shell: Option<TerminalShell>,
+    ) -> Result<(), ApiError> {
+        let workspace = self.get_workspace(workspace_id).await?;
+        let program = shell.as_ref().map(|shell| shell.program.clone());
+        workspace.set_terminal_shell(shell)?;
+
+        log::info!("Workspace shell set: id={workspace_id}, program={program:?}");
         Ok(())
     }
 
@@ -227,23 +508,157 @@ impl WorkspaceManager {
         let workspace = self.get_workspace(&workspace_id).await?;
 
         // Delegate to workspace runtime
-        workspace.create_agent(plugin_id, display_name).await
+        let summary = workspace.create_agent(plugin_id, display_name).await?;
+        self.persist().await;
+        Ok(summary)
     }
 
-    /// Lists all agents within a workspace.
+    /// Lists agents within a workspace, oldest first.
     ///
     /// # Arguments
     /// * `workspace_id` - ID of the workspace to list agents from
+    /// * `offset` - Agents to skip (default 0)
+    /// * `limit` - Most agents to return (default: all)
     ///
     /// # Returns
-    /// * `Ok(Vec<AgentSummary>)` - List of all agent summaries
+    /// * `Ok(Page<AgentSummary>)` - The requested agent summaries and the total count
     /// * `Err(ApiError::WorkspaceNotFound)` - If the workspace does not exist
     pub async fn list_agents(
         &self,
         workspace_id: WorkspaceId,
-    ) -> Result<Vec<AgentSummary>, ApiError> {
+        offset: Option<u32>,
+        limit: Option<u32>,
+    ) -> Result<Page<AgentSummary>, ApiError> {
         let workspace = self.get_workspace(&workspace_id).await?;
-        Ok(workspace.list_agents().await)
+        Ok(workspace.list_agents(offset, limit).await)
+    }
+
+    /// Deletes an agent from a workspace.
+    ///
+    /// A running agent's adapter process is shut down before the agent is
+    /// removed.
+    ///
+    /// # Arguments
+    /// * `workspace_id` - ID of the workspace the agent belongs to
+    /// * `agent_id` - ID of the agent to delete
+    ///
+    /// # Returns
+    /// * `Ok(())` - Agent deleted
+    /// * `Err(ApiError::WorkspaceNotFound)` - If the workspace does not exist
+    /// * `Err(ApiError::AgentNotFound)` - If the agent does not exist
+    pub async fn delete_agent(
+        &self,
+        workspace_id: WorkspaceId,
+        agent_id: AgentId,
+    ) -> Result<(), ApiError> {
+        let workspace = self.get_workspace(&workspace_id).await?;
+        workspace.delete_agent(&agent_id).await?;
+        self.persist().await;
+        Ok(())
+    }
+
+    /// Remembers the adapter session an agent is using so it can be resumed
+    /// after a restart of the adapter or the app.
+    ///
+    /// # Returns
+    /// * `Ok(())` - Session recorded (saved only when it changed)
+    /// * `Err(ApiError::WorkspaceNotFound)` - If the workspace does not exist
+    /// * `Err(ApiError::AgentNotFound)` - If the agent does not exist
+    pub async fn record_agent_session(
+        &self,
+        workspace_id: WorkspaceId,
+        agent_id: AgentId,
+        session_id: SessionId,
+    ) -> Result<(), ApiError> {
+        let workspace = self.get_workspace(&workspace_id).await?;
+        if workspace.agent_session_id(&agent_id).await?.as_ref() == Some(&session_id) {
+            return Ok(());
+        }
+        workspace
+            .record_agent_session(&agent_id, session_id)
+            .await?;
+        self.persist().await;
+        Ok(())
+    }
+
+    /// Configures the MCP servers an agent's adapter is given.
+    ///
+    /// # Arguments
+    /// * `workspace_id` - ID of the workspace the agent belongs to
+    /// * `agent_id` - ID of the agent to configure
+    /// * `mcp_servers` - Servers to pass with `session/new` from the next start on
+    ///
+    /// # Returns
+    /// * `Ok(())` - Servers recorded
+    /// * `Err(ApiError::WorkspaceNotFound)` - If the workspace does not exist
+    /// * `Err(ApiError::AgentNotFound)` - If the agent does not exist
+    /// * `Err(ApiError::InvalidInput)` - If a server has an empty name or command
+    pub async fn set_agent_mcp_servers(
+        &self,
+        workspace_id: WorkspaceId,
+        agent_id: AgentId,
+        mcp_servers: Vec<McpServerConfig>,
+    ) -> Result<(), ApiError> {
+        let workspace = self.get_workspace(&workspace_id).await?;
+        workspace
+            .set_agent_mcp_servers(&agent_id, mcp_servers)
+            .await?;
+        self.persist().await;
+        Ok(())
+    }
+
+    /// Turn automatic restarts after unexpected adapter exits on or off.
+    ///
+    /// # Returns
+    /// * `Ok(())` - Setting recorded
+    /// * `Err(ApiError::WorkspaceNotFound)` - If the workspace does not exist
+    /// * `Err(ApiError::AgentNotFound)` - If the agent does not exist
+    pub async fn set_agent_auto_restart(
+        &self,
+        workspace_id: WorkspaceId,
+        agent_id: AgentId,
+        enabled: bool,
+    ) -> Result<(), ApiError> {
+        let workspace = self.get_workspace(&workspace_id).await?;
+        workspace.set_agent_auto_restart(&agent_id, enabled).await?;
+        self.persist().await;
+        Ok(())
+    }
+
+    /// Turn protocol frame tracing on or off for an agent.
+    ///
+    /// # Returns
+    /// * `Ok(())` - Setting applied
+    /// * `Err(ApiError::WorkspaceNotFound)` - If the workspace does not exist
+    /// * `Err(ApiError::AgentNotFound)` - If the agent does not exist
+    pub async fn set_agent_protocol_trace(
+        &self,
+        workspace_id: WorkspaceId,
+        agent_id: AgentId,
+        enabled: bool,
+    ) -> Result<(), ApiError> {
+        let workspace = self.get_workspace(&workspace_id).await?;
+        workspace.set_agent_protocol_trace(&agent_id, enabled).await
+    }
+
+    /// Choose whether an agent queues or rejects prompts sent mid-turn.
+    ///
+    /// # Returns
+    /// * `Ok(())` - Setting recorded
+    /// * `Err(ApiError::WorkspaceNotFound)` - If the workspace does not exist
+    /// * `Err(ApiError::AgentNotFound)` - If the agent does not exist
+    pub async fn set_agent_busy_prompt_policy(
+        &self,
+        workspace_id: WorkspaceId,
+        agent_id: AgentId,
+        policy: BusyPromptPolicy,
+    ) -> Result<(), ApiError> {
+        let workspace = self.get_workspace(&workspace_id).await?;
+        workspace
+            .set_agent_busy_prompt_policy(&agent_id, policy)
+            .await?;
+        self.persist().await;
+        Ok(())
     }
 
     /// Get or create an AgentRuntime for the given agent.
@@ -274,6 +689,165 @@ impl WorkspaceManager {
         workspace.ensure_agent_runtime(agent_id).await
     }
 
+    /// Whether any workspace has a running agent on `plugin_id`.
+    pub async fn is_plugin_in_use(&self, plugin_id: &str) -> bool {
+        let workspaces: Vec<Arc<WorkspaceRuntime>> = {
+            let workspaces = self.workspaces.lock().await;
+            workspaces.values().cloned().collect()
+        };
+
+        for workspace in workspaces {
+            if workspace.is_plugin_in_use(plugin_id).await {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Counts of workspaces, agents and terminals across all workspaces.
+    pub async fn runtime_counts(&self) -> RuntimeCounts {
+        let workspaces: Vec<Arc<WorkspaceRuntime>> = {
+            let workspaces = self.workspaces.lock().await;
+            workspaces.values().cloned().collect()
+        };
+
+        let mut counts = RuntimeCounts {
+            workspaces: workspaces.len(),
+            ..RuntimeCounts::default()
+        };
+        for workspace in workspaces {
+            counts.agents += workspace.list_agents(None, None).await.total as usize;
+            counts.running_agents += workspace.running_agent_count().await;
+            counts.active_terminals += workspace.terminal_manager().list_active().await.len();
+        }
+        counts
+    }
+
+    /// Root directories of all workspaces.
+    pub async fn workspace_roots(&self) -> Vec<PathBuf> {
+        let workspaces = self.workspaces.lock().await;
+        workspaces
+            .values()
+            .map(|workspace| workspace.root_dir().clone())
+            .collect()
+    }
+
+    /// Drop the retained output of exited terminals in every workspace.
+    ///
+    /// # Returns
+    /// * Number of terminals released
+    pub async fn release_exited_terminals(&self) -> usize {
+        let workspaces: Vec<Arc<WorkspaceRuntime>> = {
+            let workspaces = self.workspaces.lock().await;
+            workspaces.values().cloned().collect()
+        };
+
+        let mut released = 0;
+        for workspace in workspaces {
+            released += workspace.terminal_manager().release_exited().await;
+        }
+        released
+    }
+
+    /// Refuse to touch a plugin's files while an agent is running on it.
+    ///
+    /// Called before operations that replace or remove the plugin install
+    /// (install, repair, cache clearing). Installs and repairs check again
+    /// once the user has approved them.
+    ///
+    /// # Returns
+    /// * `Ok(())` - No running agent uses the plugin
+    /// * `Err(ApiError::PluginInUse)` - An agent process is running on it
+    pub async fn ensure_plugin_not_in_use(&self, plugin_id: &str) -> Result<(), ApiError> {
+        if self.is_plugin_in_use(plugin_id).await {
+            log::warn!("Refusing to modify plugin in use: plugin_id={plugin_id}");
+            return Err(ApiError::PluginInUse {
+                plugin_id: plugin_id.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Current runtime status of an agent in a workspace.
+    ///
+    /// # Returns
+    /// * `Ok(AgentRuntimeStatus)` - The agent's status (`Stopped` if never started)
+    /// * `Err(ApiError::WorkspaceNotFound)` - If the workspace does not exist
+    /// * `Err(ApiError::AgentNotFound)` - If the agent does not exist
+    pub async fn agent_status(
+        &self,
+        workspace_id: WorkspaceId,
+        agent_id: AgentId,
+    ) -> Result<AgentRuntimeStatus, ApiError> {
+        let workspace = self.get_workspace(&workspace_id).await?;
+        workspace.agent_status(&agent_id).await
+    }
+
+    /// Slash commands an agent's session last announced.
+    ///
+    /// # Returns
+    /// * `Ok(Vec)` - The cached commands (empty if none were received yet)
+    /// * `Err(ApiError::WorkspaceNotFound)` - If the workspace does not exist
+    /// * `Err(ApiError::AgentNotFound)` - If the agent does not exist
+    pub async fn available_commands(
+        &self,
+        workspace_id: WorkspaceId,
+        agent_id: AgentId,
+        session_id: SessionId,
+    ) -> Result<Vec<serde_json::Value>, ApiError> {
+        let workspace = self.get_workspace(&workspace_id).await?;
+        workspace.available_commands(&agent_id, &session_id).await
+    }
+
+    /// Mode an agent's session was last reported in.
+    ///
+    /// # Returns
+    /// * `Ok(Option)` - The mode ID (`None` if none was reported yet)
+    /// * `Err(ApiError::WorkspaceNotFound)` - If the workspace does not exist
+    /// * `Err(ApiError::AgentNotFound)` - If the agent does not exist
+    pub async fn current_mode(
+        &self,
+        workspace_id: WorkspaceId,
+        agent_id: AgentId,
+        session_id: SessionId,
+    ) -> Result<Option<String>, ApiError> {
+        let workspace = self.get_workspace(&workspace_id).await?;
+        workspace.current_mode(&agent_id, &session_id).await
+    }
+
+    /// Switch an agent's session to another mode.
+    pub async fn set_mode(
+        &self,
+        workspace_id: WorkspaceId,
+        agent_id: AgentId,
+        session_id: SessionId,
+        mode_id: String,
+    ) -> Result<(), ApiError> {
+        let workspace = self.get_workspace(&workspace_id).await?;
+        workspace.set_mode(agent_id, session_id, mode_id).await
+    }
+
+    /// Open another session on an agent.
+    pub async fn new_session(
+        &self,
+        workspace_id: WorkspaceId,
+        agent_id: AgentId,
+    ) -> Result<SessionId, ApiError> {
+        let workspace = self.get_workspace(&workspace_id).await?;
+        workspace.new_session(agent_id).await
+    }
+
+    /// Close a session opened with [`Self::new_session`].
+    pub async fn close_session(
+        &self,
+        workspace_id: WorkspaceId,
+        agent_id: AgentId,
+        session_id: SessionId,
+    ) -> Result<(), ApiError> {
+        let workspace = self.get_workspace(&workspace_id).await?;
+        workspace.close_session(agent_id, session_id).await
+    }
+
     /// Stop the current turn for a given agent/session in a workspace.
     ///
     /// US-12: Routes to workspace runtime for cancellation.
@@ -286,6 +860,21 @@ impl WorkspaceManager {
         let workspace = self.get_workspace(&workspace_id).await?;
         workspace.stop_turn(agent_id, session_id).await
     }
+
+    /// Stop a running agent and free its adapter process.
+    ///
+    /// # Returns
+    /// * `Ok(())` - Agent stopped, or it was not running
+    /// * `Err(ApiError::WorkspaceNotFound)` - If the workspace does not exist
+    /// * `Err(ApiError::AgentNotFound)` - If the agent does not exist
+    pub async fn stop_agent(
+        &self,
+        workspace_id: WorkspaceId,
+        agent_id: AgentId,
+    ) -> Result<(), ApiError> {
+        let workspace = self.get_workspace(&workspace_id).await?;
+        workspace.stop_agent(agent_id).await
+    }
 }
 
 impl Default for WorkspaceManager {
@@ -297,6 +886,7 @@ impl Default for WorkspaceManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::runtime::test_support::IdleConnection;
     use std::env;
 
     #[tokio::test]
@@ -490,33 +1080,98 @@ mod tests {
     async fn test_list_workspaces_empty() {
         let manager = WorkspaceManager::new();
 
-        let result = manager.list_workspaces().await;
+        let result = manager.list_workspaces(None, None).await;
 
-        assert!(result.is_empty());
+        assert!(result.items.is_empty());
+        assert_eq!(result.total, 0);
     }
 
     #[tokio::test]
     async fn test_list_workspaces_multiple() {
         let manager = WorkspaceManager::new();
-        let temp_dir = env::temp_dir();
+        let base = env::temp_dir().join(format!("workspace_list_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(base.join("a")).unwrap();
+        std::fs::create_dir_all(base.join("b")).unwrap();
 
         // Create multiple workspaces
         let summary_a = manager
-            .create_workspace(temp_dir.to_str().unwrap())
+            .create_workspace(base.join("a").to_str().unwrap())
             .await
             .unwrap();
         let summary_b = manager
-            .create_workspace(temp_dir.to_str().unwrap())
+            .create_workspace(base.join("b").to_str().unwrap())
             .await
             .unwrap();
 
-        let list = manager.list_workspaces().await;
+        let list = manager.list_workspaces(None, None).await.items;
 
         assert_eq!(list.len(), 2);
         // Verify both workspaces are in the list
         let ids: Vec<&str> = list.iter().map(|s| s.workspace_id.as_str()).collect();
         assert!(ids.contains(&summary_a.workspace_id.as_str()));
         assert!(ids.contains(&summary_b.workspace_id.as_str()));
+        let _ = std::fs::remove_dir_all(&base);
+    }
+
+    #[tokio::test]
+    async fn test_list_workspaces_pages_newest_first() {
+        let manager = WorkspaceManager::new();
+        let base = env::temp_dir().join(format!("workspace_pages_{}", Uuid::new_v4()));
+        let mut created = Vec::new();
+        for name in ["a", "b", "c"] {
+            std::fs::create_dir_all(base.join(name)).unwrap();
+            let summary = manager
+                .create_workspace(base.join(name).to_str().unwrap())
+                .await
+                .unwrap();
+            created.push(summary.workspace_id);
+            // Distinct creation times, so the order is defined
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        let ids = |page: Page<WorkspaceSummary>| -> Vec<WorkspaceId> {
+            page.items.into_iter().map(|s| s.workspace_id).collect()
+        };
+
+        let newest_first: Vec<WorkspaceId> = created.iter().rev().cloned().collect();
+        assert_eq!(ids(manager.list_workspaces(None, None).await), newest_first);
+        assert_eq!(
+            ids(manager.list_workspaces(Some(1), Some(1)).await),
+            vec![created[1].clone()]
+        );
+        assert_eq!(
+            ids(manager.list_workspaces(Some(2), Some(10)).await),
+            vec![created[0].clone()]
+        );
+        assert_eq!(
+            ids(manager.list_workspaces(None, Some(2)).await),
+            newest_first[..2].to_vec()
+        );
+
+        let beyond = manager.list_workspaces(Some(3), None).await;
+        assert!(beyond.items.is_empty());
+        assert_eq!(beyond.total, 3);
+        let _ = std::fs::remove_dir_all(&base);
+    }
+
+    #[tokio::test]
+    async fn test_create_workspace_same_root_returns_existing() {
+        let manager = WorkspaceManager::new();
+        let root = env::temp_dir().join(format!("workspace_dedupe_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("sub")).unwrap();
+
+        let first = manager
+            .create_workspace(root.to_str().unwrap())
+            .await
+            .unwrap();
+        // A different spelling of the same directory canonicalizes to it too
+        let again = manager
+            .create_workspace(root.join("sub/..").to_str().unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(again.workspace_id, first.workspace_id);
+        assert_eq!(manager.list_workspaces(None, None).await.total, 1);
+        let _ = std::fs::remove_dir_all(&root);
     }
 
     #[tokio::test]
@@ -535,8 +1190,8 @@ mod tests {
         assert!(result.is_ok());
 
         // Verify it's gone
-        let list = manager.list_workspaces().await;
-        assert!(list.is_empty());
+        let list = manager.list_workspaces(None, None).await;
+        assert!(list.items.is_empty());
     }
 
     #[tokio::test]
@@ -590,4 +1245,316 @@ mod tests {
         // Focus should be cleared
         assert!(manager.get_focus().await.is_none());
     }
+
+    #[tokio::test]
+    async fn test_workspaces_are_restored_from_disk() {
+        let base = env::temp_dir().join(format!("workspace_persist_{}", Uuid::new_v4()));
+        let kept_dir = base.join("kept");
+        let gone_dir = base.join("gone");
+        std::fs::create_dir_all(&kept_dir).unwrap();
+        std::fs::create_dir_all(&gone_dir).unwrap();
+        let store_path = base.join("config/workspaces.json");
+
+        let manager = WorkspaceManager::with_store(WorkspaceStore::new(store_path.clone()));
+        let kept = manager
+            .create_workspace(kept_dir.to_str().unwrap())
+            .await
+            .unwrap();
+        manager
+            .create_workspace(gone_dir.to_str().unwrap())
+            .await
+            .unwrap();
+        manager.set_focus(kept.workspace_id.clone()).await.unwrap();
+        manager
+            .rename_workspace(&kept.workspace_id, "Kept project")
+            .await
+            .unwrap();
+        std::fs::remove_dir_all(&gone_dir).unwrap();
+
+        // A fresh manager (next app launch) sees only the surviving workspace
+        let reloaded = WorkspaceManager::with_store(WorkspaceStore::new(store_path.clone()));
+        assert_eq!(reloaded.load_from_disk().await.unwrap(), 1);
+
+        let workspaces = reloaded.list_workspaces(None, None).await.items;
+        assert_eq!(workspaces.len(), 1);
+        assert_eq!(workspaces[0].workspace_id, kept.workspace_id);
+        assert_eq!(workspaces[0].root_dir, kept.root_dir);
+        assert_eq!(workspaces[0].name.as_deref(), Some("Kept project"));
+        assert_eq!(workspaces[0].created_at_ms, kept.created_at_ms);
+        assert_eq!(reloaded.get_focus().await, Some(kept.workspace_id.clone()));
+
+        // The dropped entry is pruned from the file as well
+        let saved = WorkspaceStore::new(store_path).load().unwrap();
+        assert_eq!(saved.workspaces.len(), 1);
+        let _ = std::fs::remove_dir_all(&base);
+    }
+
+    #[tokio::test]
+    async fn test_agents_are_restored_with_their_workspace() {
+        let base = env::temp_dir().join(format!("agent_persist_{}", Uuid::new_v4()));
+        let root = base.join("project");
+        std::fs::create_dir_all(&root).unwrap();
+        let store_path = base.join("config/workspaces.json");
+
+        let manager = WorkspaceManager::with_store(WorkspaceStore::new(store_path.clone()));
+        let workspace = manager
+            .create_workspace(root.to_str().unwrap())
+            .await
+            .unwrap();
+        let ws = workspace.workspace_id.clone();
+        let reviewer = manager
+            .create_agent(
+                ws.clone(),
+                "claude-code".to_string(),
+                Some("Reviewer".to_string()),
+            )
+            .await
+            .unwrap();
+        let writer = manager
+            .create_agent(ws.clone(), "codex".to_string(), None)
+            .await
+            .unwrap();
+        let removed = manager
+            .create_agent(ws.clone(), "gemini".to_string(), None)
+            .await
+            .unwrap();
+        manager
+            .record_agent_session(
+                ws.clone(),
+                reviewer.agent_id.clone(),
+                "session-1".to_string(),
+            )
+            .await
+            .unwrap();
+        manager
+            .set_agent_auto_restart(ws.clone(), writer.agent_id.clone(), true)
+            .await
+            .unwrap();
+        manager
+            .set_agent_busy_prompt_policy(
+                ws.clone(),
+                writer.agent_id.clone(),
+                BusyPromptPolicy::Reject,
+            )
+            .await
+            .unwrap();
+        manager
+            .delete_agent(ws.clone(), removed.agent_id.clone())
+            .await
+            .unwrap();
+
+        let reloaded = WorkspaceManager::with_store(WorkspaceStore::new(store_path));
+        assert_eq!(reloaded.load_from_disk().await.unwrap(), 1);
+
+        let mut agents = reloaded
+            .list_agents(ws.clone(), None, None)
+            .await
+            .unwrap()
+            .items;
+        agents.sort_by_key(|agent| agent.plugin_id.clone());
+        assert_eq!(agents.len(), 2);
+        assert_eq!(agents[0].agent_id, reviewer.agent_id);
+        assert_eq!(agents[0].display_name.as_deref(), Some("Reviewer"));
+        assert!(!agents[0].auto_restart);
+        assert_eq!(agents[1].agent_id, writer.agent_id);
+        assert_eq!(agents[1].plugin_id, "codex");
+        assert!(agents[1].auto_restart);
+        assert_eq!(agents[1].busy_prompt_policy, BusyPromptPolicy::Reject);
+
+        let restored = reloaded.get_workspace(&ws).await.unwrap();
+        assert_eq!(
+            restored.agent_session_id(&reviewer.agent_id).await.unwrap(),
+            Some("session-1".to_string())
+        );
+        // Restored agents are not started until their first prompt
+        assert!(!restored.is_plugin_in_use("claude-code").await);
+        let _ = std::fs::remove_dir_all(&base);
+    }
+
+    /// Connection that counts how often it is shut down.
+    struct CountingShutdownConnection {
+        shutdowns: Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    #[async_trait::async_trait]
+    impl crate::protocols::agent_connection::AgentConnection for CountingShutdownConnection {
+        async fn send_prompt(
+            &self,
+            _session_id: SessionId,
+            _prompt: Vec<crate::api::types::PromptContentBlock>,
+        ) -> Result<String, ApiError> {
+            Ok("turn-1".to_string())
+        }
+
+        async fn cancel_turn(&self, _session_id: SessionId) -> Result<(), ApiError> {
+            Ok(())
+        }
+
+        async fn new_session(&self) -> Result<SessionId, ApiError> {
+            Ok("session-2".to_string())
+        }
+
+        async fn shutdown(&self) -> Result<(), ApiError> {
+            self.shutdowns
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_delete_workspace_shuts_down_running_agents() {
+        let manager = WorkspaceManager::new();
+        let temp_dir = env::temp_dir();
+        let ws_summary = manager
+            .create_workspace(temp_dir.to_str().unwrap())
+            .await
+            .unwrap();
+        let shutdowns = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+
+        let mut runtimes = Vec::new();
+        for plugin_id in ["claude-code", "codex"] {
+            let agent = manager
+                .create_agent(ws_summary.workspace_id.clone(), plugin_id.to_string(), None)
+                .await
+                .unwrap();
+            let runtime = manager
+                .ensure_agent_runtime(ws_summary.workspace_id.clone(), agent.agent_id)
+                .await
+                .unwrap();
+            runtime
+                .attach_connection_for_test(
+                    format!("session-{plugin_id}"),
+                    Arc::new(CountingShutdownConnection {
+                        shutdowns: shutdowns.clone(),
+                    }),
+                )
+                .await;
+            runtimes.push(runtime);
+        }
+        // A created but never started agent has nothing to shut down
+        manager
+            .create_agent(ws_summary.workspace_id.clone(), "gemini".to_string(), None)
+            .await
+            .unwrap();
+
+        manager
+            .delete_workspace(&ws_summary.workspace_id)
+            .await
+            .unwrap();
+
+        assert_eq!(shutdowns.load(std::sync::atomic::Ordering::SeqCst), 2);
+        for runtime in runtimes {
+            assert!(!runtime.is_running().await);
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_shutdown_all_stops_agents_and_terminals_in_every_workspace() {
+        let manager = WorkspaceManager::new();
+        let roots: Vec<_> = (0..2)
+            .map(|_| env::temp_dir().join(format!("ws_shutdown_{}", uuid::Uuid::new_v4())))
+            .collect();
+        let shutdowns = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+
+        let mut runtimes = Vec::new();
+        let mut runs = Vec::new();
+        for root in &roots {
+            std::fs::create_dir_all(root).unwrap();
+            let ws_summary = manager
+                .create_workspace(root.to_str().unwrap())
+                .await
+                .unwrap();
+            let agent = manager
+                .create_agent(
+                    ws_summary.workspace_id.clone(),
+                    "claude-code".to_string(),
+                    None,
+                )
+                .await
+                .unwrap();
+            let runtime = manager
+                .ensure_agent_runtime(ws_summary.workspace_id.clone(), agent.agent_id)
+                .await
+                .unwrap();
+            runtime
+                .attach_connection_for_test(
+                    "session-1".to_string(),
+                    Arc::new(CountingShutdownConnection {
+                        shutdowns: shutdowns.clone(),
+                    }),
+                )
+                .await;
+            runtimes.push(runtime);
+
+            let run = manager
+                .get_workspace(&ws_summary.workspace_id)
+                .await
+                .unwrap()
+                .terminal_manager()
+                .spawn_run("sleep 30".to_string(), None, None)
+                .await
+                .unwrap();
+            runs.push(run);
+        }
+
+        let stopped = manager.shutdown_all().await;
+
+        assert_eq!(stopped, 2);
+        assert_eq!(shutdowns.load(std::sync::atomic::Ordering::SeqCst), 2);
+        for runtime in runtimes {
+            assert!(!runtime.is_running().await);
+        }
+        for run in runs {
+            let exit = tokio::time::timeout(Duration::from_secs(5), run.exit_rx)
+                .await
+                .expect("terminal should be killed")
+                .unwrap();
+            assert_eq!(exit.exit_code, None);
+        }
+        for root in roots {
+            let _ = std::fs::remove_dir_all(root);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_running_agent_blocks_plugin_changes() {
+        let manager = WorkspaceManager::new();
+        let temp_dir = env::temp_dir();
+        let ws_summary = manager
+            .create_workspace(temp_dir.to_str().unwrap())
+            .await
+            .unwrap();
+        let agent = manager
+            .create_agent(
+                ws_summary.workspace_id.clone(),
+                "claude-code".to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+
+        // Created but not started: nothing blocks the plugin
+        let runtime = manager
+            .ensure_agent_runtime(ws_summary.workspace_id.clone(), agent.agent_id)
+            .await
+            .unwrap();
+        assert!(!manager.is_plugin_in_use("claude-code").await);
+        assert!(manager
+            .ensure_plugin_not_in_use("claude-code")
+            .await
+            .is_ok());
+
+        runtime
+            .attach_connection_for_test("session-1".to_string(), Arc::new(IdleConnection))
+            .await;
+
+        assert!(manager.is_plugin_in_use("claude-code").await);
+        assert!(matches!(
+            manager.ensure_plugin_not_in_use("claude-code").await,
+            Err(ApiError::PluginInUse { plugin_id }) if plugin_id == "claude-code"
+        ));
+        // Other plugins are unaffected
+        assert!(manager.ensure_plugin_not_in_use("codex").await.is_ok());
+    }
 }
@@ -0,0 +1,136 @@
+//! On-disk persistence for the workspace list.
+//!
+//! Workspaces are saved as a small JSON file so they survive app restarts,
+//! together with the agents defined in them. Agent processes are not
+//! restored; agents start lazily on their first prompt as usual.
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::api::types::{ApiError, WorkspaceId};
+use crate::runtime::agents::AgentRecord;
+
+/// A workspace as saved on disk.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PersistedWorkspace {
+    pub workspace_id: WorkspaceId,
+    pub root_dir: String,
+    /// Name the user gave the workspace (absent: use the directory name)
+    #[serde(default)]
+    pub name: Option<String>,
+    pub created_at_ms: f64,
+    /// Agents defined in the workspace
+    #[serde(default)]
+    pub agents: Vec<AgentRecord>,
+}
+
+/// Contents of the workspace file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PersistedWorkspaces {
+    pub workspaces: Vec<PersistedWorkspace>,
+    pub focused_workspace_id: Option<WorkspaceId>,
+}
+
+/// Reads and writes the workspace file.
+pub struct WorkspaceStore {
+    path: PathBuf,
+}
+
+impl WorkspaceStore {
+    /// Create a store backed by the JSON file at `path`.
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Load the saved workspaces; a missing file means none were saved.
+    ///
+    /// # Errors
+    /// * `ApiError::IoError` - If the file can't be read or parsed
+    pub fn load(&self) -> Result<PersistedWorkspaces, ApiError> {
+        if !self.path.exists() {
+            return Ok(PersistedWorkspaces::default());
+        }
+
+        let contents = std::fs::read_to_string(&self.path).map_err(|e| ApiError::IoError {
+            message: format!("Failed to read workspace file: {e}"),
+        })?;
+        serde_json::from_str(&contents).map_err(|e| ApiError::IoError {
+            message: format!("Failed to parse workspace file: {e}"),
+        })
+    }
+
+    /// Save the workspaces, replacing the file atomically (temp file + rename).
+    ///
+    /// # Errors
+    /// * `ApiError::IoError` - If the file can't be written
+    pub fn save(&self, data: &PersistedWorkspaces) -> Result<(), ApiError> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| ApiError::IoError {
+                message: format!("Failed to create workspace file directory: {e}"),
+            })?;
+        }
+
+        let content = serde_json::to_string_pretty(data).map_err(|e| ApiError::IoError {
+            message: format!("Failed to serialize workspaces: {e}"),
+        })?;
+
+        let temp_path = self.path.with_extension("tmp");
+        std::fs::write(&temp_path, content).map_err(|e| ApiError::IoError {
+            message: format!("Failed to write workspace file: {e}"),
+        })?;
+
+        if let Err(rename_err) = std::fs::rename(&temp_path, &self.path) {
+            // Don't leave an orphaned temp file behind
+            if let Err(remove_err) = std::fs::remove_file(&temp_path) {
+                log::warn!("Failed to remove temp file after rename failure: {remove_err}");
+            }
+            return Err(ApiError::IoError {
+                message: format!("Failed to finalize workspace file: {rename_err}"),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_then_load_round_trips() {
+        let dir = std::env::temp_dir().join(format!("workspace_store_{}", uuid::Uuid::new_v4()));
+        let store = WorkspaceStore::new(dir.join("workspaces.json"));
+
+        // Nothing saved yet
+        assert_eq!(store.load().unwrap(), PersistedWorkspaces::default());
+
+        let data = PersistedWorkspaces {
+            workspaces: vec![PersistedWorkspace {
+                workspace_id: "ws-1".to_string(),
+                root_dir: "/tmp/project".to_string(),
+                name: Some("Project".to_string()),
+                created_at_ms: 1_700_000_000_000.0,
+                agents: vec![AgentRecord {
+                    agent_id: "agent-1".to_string(),
+                    plugin_id: "claude-code".to_string(),
+                    display_name: Some("Reviewer".to_string()),
+                    session_id: Some("session-1".to_string()),
+                    mcp_servers: Vec::new(),
+                    auto_restart: true,
+                    busy_prompt_policy: Default::default(),
+                    created_at_ms: 1_700_000_000_001.0,
+                }],
+            }],
+            focused_workspace_id: Some("ws-1".to_string()),
+        };
+        store.save(&data).unwrap();
+
+        assert_eq!(store.load().unwrap(), data);
+        assert!(!dir.join("workspaces.tmp").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -3,11 +3,20 @@
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use specta::Type;
+use std::collections::HashMap;
 use std::sync::LazyLock;
 
 /// Default shortcut for the quick pane
 pub const DEFAULT_QUICK_PANE_SHORTCUT: &str = "CommandOrControl+Shift+.";
 
+/// Default plugin for the agent that answers quick pane prompts
+pub const DEFAULT_QUICK_PANE_PLUGIN: &str = "claude-code";
+
+/// Paths agents may never read or write, whatever a workspace's own file
+/// path rules say (version control internals, env files, credentials),
+/// unless `fs_default_deny_rules` is turned off
+pub const DEFAULT_FS_DENY_PATTERNS: &[&str] = &[".git", ".env*", "credentials*", "*.pem"];
+
 /// Maximum size for recovery data files (10MB)
 pub const MAX_RECOVERY_DATA_BYTES: u32 = 10_485_760;
 
@@ -33,9 +42,85 @@ pub struct AppPreferences {
     /// Global shortcut for quick pane (e.g., "CommandOrControl+Shift+.")
     /// If None, uses the default shortcut
     pub quick_pane_shortcut: Option<String>,
+    /// Plugin of the agent that answers quick pane prompts (e.g., "codex")
+    /// If None, uses the default plugin
+    #[serde(default)]
+    pub quick_pane_plugin_id: Option<String>,
     /// User's preferred language (e.g., "en", "es", "de")
     /// If None, uses system locale detection
     pub language: Option<String>,
+    /// Also append permission decisions to a JSONL audit file in the app data dir
+    #[serde(default)]
+    pub permission_audit_log: bool,
+    /// Show alerts such as finished installs and agent failures
+    #[serde(default = "default_notifications_enabled")]
+    pub notifications_enabled: bool,
+    /// Keep a JSONL transcript of every session in the app data dir
+    #[serde(default)]
+    pub session_transcripts: bool,
+    /// Pass the variables in each workspace's `.env` to its agents and
+    /// terminal commands
+    #[serde(default)]
+    pub workspace_env_file: bool,
+    /// Merge agent message and thought chunks arriving within this many
+    /// milliseconds into one update (0 turns merging off; capped at 250)
+    #[serde(default)]
+    pub message_chunk_coalesce_ms: u32,
+    /// Ping each adapter this often, in milliseconds, and treat it as lost
+    /// once a ping goes unanswered (0 turns keepalive off; at least 1000
+    /// otherwise)
+    #[serde(default)]
+    pub adapter_keepalive_interval_ms: u32,
+    /// How long a keepalive ping may go unanswered, in milliseconds (0 uses
+    /// the default of 10 seconds)
+    #[serde(default)]
+    pub adapter_keepalive_timeout_ms: u32,
+    /// Rules that approve or refuse agent terminal commands without asking,
+    /// keyed by workspace ID
+    #[serde(default)]
+    pub terminal_command_rules: HashMap<String, Vec<TerminalCommandRule>>,
+    /// Rules that approve agent file reads or refuse file access without
+    /// asking, keyed by workspace ID
+    #[serde(default)]
+    pub fs_path_rules: HashMap<String, Vec<FsPathRule>>,
+    /// Refuse agent access to `DEFAULT_FS_DENY_PATTERNS` in every workspace,
+    /// on top of its own file path rules
+    #[serde(default = "default_fs_default_deny_rules")]
+    pub fs_default_deny_rules: bool,
+}
+
+/// What a terminal command or file path rule does with matching requests
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum PolicyAction {
+    /// Approve without asking
+    Allow,
+    /// Refuse without asking (wins over allow rules)
+    Deny,
+}
+
+/// A terminal command rule.
+///
+/// `pattern` is a glob over the whole command if it contains `*` or `?`, and
+/// otherwise a prefix matched on whole words (`git status` covers
+/// `git status -s`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub struct TerminalCommandRule {
+    pub action: PolicyAction,
+    pub pattern: String,
+}
+
+/// A file path rule, matched against workspace-relative paths.
+///
+/// Allow rules approve reads only; writes, deletes and renames still prompt.
+/// Deny rules refuse reads and writes alike. `pattern` is a glob if it
+/// contains `*` or `?` (matched against each path component when it has no
+/// `/`, so `.env*` covers `app/.env.local`), and otherwise a path prefix
+/// matched on whole components (`src` covers `src/main.rs`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub struct FsPathRule {
+    pub action: PolicyAction,
+    pub pattern: String,
 }
 
 impl Default for AppPreferences {
@@ -43,8 +128,19 @@ impl Default for AppPreferences {
         Self {
             theme: "system".to_string(),
             color_theme: default_color_theme(),
-            quick_pane_shortcut: None, // None means use default
-            language: None,            // None means use system locale
+            quick_pane_shortcut: None,  // None means use default
+            quick_pane_plugin_id: None, // None means use default
+            language: None,             // None means use system locale
+            permission_audit_log: false,
+            notifications_enabled: default_notifications_enabled(),
+            session_transcripts: false,
+            workspace_env_file: false,
+            message_chunk_coalesce_ms: 0,
+            adapter_keepalive_interval_ms: 0,
+            adapter_keepalive_timeout_ms: 0,
+            terminal_command_rules: HashMap::new(),
+            fs_path_rules: HashMap::new(),
+            fs_default_deny_rules: default_fs_default_deny_rules(),
         }
     }
 }
@@ -53,6 +149,14 @@ fn default_color_theme() -> String {
     "default".to_string()
 }
 
+fn default_notifications_enabled() -> bool {
+    true
+}
+
+fn default_fs_default_deny_rules() -> bool {
+    true
+}
+
 // ============================================================================
 // Recovery Errors
 // ============================================================================
@@ -129,6 +233,38 @@ pub fn validate_theme(theme: &str) -> Result<(), String> {
     }
 }
 
+/// Validates terminal command rules (non-empty, bounded patterns).
+pub fn validate_terminal_command_rules(
+    rules: &HashMap<String, Vec<TerminalCommandRule>>,
+) -> Result<(), String> {
+    for rule in rules.values().flatten() {
+        if rule.pattern.trim().is_empty() {
+            return Err("Terminal command rule pattern cannot be empty".to_string());
+        }
+        validate_string_input(&rule.pattern, 500, "Terminal command rule pattern")?;
+    }
+    Ok(())
+}
+
+/// Validates file path rules (non-empty, bounded patterns).
+pub fn validate_fs_path_rules(rules: &HashMap<String, Vec<FsPathRule>>) -> Result<(), String> {
+    for rule in rules.values().flatten() {
+        if rule.pattern.trim_matches(['/', ' ']).is_empty() {
+            return Err("File path rule pattern cannot be empty".to_string());
+        }
+        validate_string_input(&rule.pattern, 500, "File path rule pattern")?;
+    }
+    Ok(())
+}
+
+/// Validates the adapter keepalive interval (off, or at least a second).
+pub fn validate_adapter_keepalive(interval_ms: u32) -> Result<(), String> {
+    if interval_ms != 0 && interval_ms < 1000 {
+        return Err("Adapter keepalive interval must be 0 (off) or at least 1000 ms".to_string());
+    }
+    Ok(())
+}
+
 /// Validates color theme value.
 pub fn validate_color_theme(color_theme: &str) -> Result<(), String> {
     if color_theme.is_empty() {
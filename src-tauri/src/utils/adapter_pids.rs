@@ -0,0 +1,345 @@
+//! Tracking of adapter processes spawned by this app.
+//!
+//! Every adapter spawned by `AcpAgent` is registered here until its
+//! connection is dropped. The live set is mirrored to a JSON file so that,
+//! after a crash, the next launch can kill adapters the previous run left
+//! behind. Only pids recorded here are ever killed, and a pid from an earlier
+//! run is only killed if it still runs the same program (pids get reused).
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::api::types::AdapterProcessInfo;
+
+/// A registered process and the token of the registration that owns it.
+#[derive(Debug)]
+struct Entry {
+    token: u64,
+    info: AdapterProcessInfo,
+}
+
+/// Registry of adapter pids this app created.
+#[derive(Debug, Default)]
+pub struct AdapterPids {
+    /// Where the live set is mirrored, if anywhere
+    file: Option<PathBuf>,
+    live: Mutex<HashMap<u32, Entry>>,
+    next_token: AtomicU64,
+}
+
+/// Unregisters its pid when dropped (held by the connection that owns the
+/// child process).
+#[derive(Debug)]
+pub struct AdapterPidGuard {
+    pids: Arc<AdapterPids>,
+    pid: u32,
+    token: u64,
+}
+
+impl Drop for AdapterPidGuard {
+    fn drop(&mut self) {
+        self.pids.unregister(self.pid, self.token);
+    }
+}
+
+impl AdapterPids {
+    /// A registry that is not persisted.
+    #[allow(dead_code)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A registry mirrored to `file`.
+    pub fn with_file(file: PathBuf) -> Self {
+        Self {
+            file: Some(file),
+            ..Self::default()
+        }
+    }
+
+    /// Record a freshly spawned adapter. The pid stays registered until the
+    /// returned guard is dropped or the process is killed via [`Self::kill`].
+    pub fn register(self: &Arc<Self>, pid: u32, program: &Path, cwd: &Path) -> AdapterPidGuard {
+        let token = self.next_token.fetch_add(1, Ordering::Relaxed);
+        let info = AdapterProcessInfo {
+            pid,
+            program: program.display().to_string(),
+            cwd: cwd.display().to_string(),
+            started_at_ms: now_ms(),
+        };
+        {
+            let mut live = self.live.lock().unwrap_or_else(|e| e.into_inner());
+            live.insert(pid, Entry { token, info });
+            self.persist(&live);
+        }
+        log::debug!("Registered adapter process: pid={pid}");
+        AdapterPidGuard {
+            pids: self.clone(),
+            pid,
+            token,
+        }
+    }
+
+    /// Drop `pid` if it is still owned by registration `token` (a killed and
+    /// reused pid may have been registered again since).
+    fn unregister(&self, pid: u32, token: u64) {
+        let mut live = self.live.lock().unwrap_or_else(|e| e.into_inner());
+        if live.get(&pid).is_some_and(|entry| entry.token == token) {
+            live.remove(&pid);
+            self.persist(&live);
+            log::debug!("Unregistered adapter process: pid={pid}");
+        }
+    }
+
+    /// Registered processes, ordered by pid.
+    pub fn list(&self) -> Vec<AdapterProcessInfo> {
+        let live = self.live.lock().unwrap_or_else(|e| e.into_inner());
+        let mut processes: Vec<_> = live.values().map(|entry| entry.info.clone()).collect();
+        processes.sort_by_key(|info| info.pid);
+        processes
+    }
+
+    /// Whether `pid` is a registered adapter.
+    pub fn contains(&self, pid: u32) -> bool {
+        self.live
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .contains_key(&pid)
+    }
+
+    /// Kill a registered adapter and forget it.
+    ///
+    /// # Errors
+    /// * If `pid` was not spawned by this app, or the kill failed
+    pub fn kill(&self, pid: u32) -> Result<(), String> {
+        if !self.contains(pid) {
+            return Err(format!(
+                "Process {pid} is not an adapter started by this app"
+            ));
+        }
+        kill_pid(pid)?;
+        let mut live = self.live.lock().unwrap_or_else(|e| e.into_inner());
+        live.remove(&pid);
+        self.persist(&live);
+        log::info!("Killed adapter process: pid={pid}");
+        Ok(())
+    }
+
+    /// Kill adapters recorded by a previous run that are still running the
+    /// same program, then clear the record. Call before any adapter is
+    /// registered. Returns the pids that were killed.
+    pub fn sweep_previous_run(&self) -> Vec<u32> {
+        let Some(file) = &self.file else {
+            return Vec::new();
+        };
+        let previous: Vec<AdapterProcessInfo> = match std::fs::read_to_string(file) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                log::warn!("Ignoring unreadable adapter pid file: path={file:?}, error={e}");
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        };
+
+        let mut killed = Vec::new();
+        for info in previous {
+            if !runs_program(info.pid, &info.program) {
+                continue;
+            }
+            match kill_pid(info.pid) {
+                Ok(()) => {
+                    log::warn!(
+                        "Killed adapter left over from a previous run: pid={}, program={}",
+                        info.pid,
+                        info.program
+                    );
+                    killed.push(info.pid);
+                }
+                Err(e) => log::warn!(
+                    "Failed to kill leftover adapter: pid={}, error={e}",
+                    info.pid
+                ),
+            }
+        }
+
+        let live = self.live.lock().unwrap_or_else(|e| e.into_inner());
+        self.persist(&live);
+        killed
+    }
+
+    /// Mirror the live set to disk (best effort).
+    fn persist(&self, live: &HashMap<u32, Entry>) {
+        let Some(file) = &self.file else {
+            return;
+        };
+        let infos: Vec<&AdapterProcessInfo> = live.values().map(|entry| &entry.info).collect();
+        let result = serde_json::to_string(&infos)
+            .map_err(|e| e.to_string())
+            .and_then(|json| {
+                if let Some(parent) = file.parent() {
+                    std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+                }
+                let temp = file.with_extension("tmp");
+                std::fs::write(&temp, json).map_err(|e| e.to_string())?;
+                std::fs::rename(&temp, file).map_err(|e| e.to_string())
+            });
+        if let Err(e) = result {
+            log::warn!("Failed to save adapter pid file: path={file:?}, error={e}");
+        }
+    }
+}
+
+fn now_ms() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as f64)
+        .unwrap_or(0.0)
+}
+
+/// Whether `pid` is alive and its command line mentions `program`.
+#[cfg(target_os = "linux")]
+fn runs_program(pid: u32, program: &str) -> bool {
+    // Arguments are NUL separated; scripts show up as `node <program> ...`
+    std::fs::read(format!("/proc/{pid}/cmdline"))
+        .map(|raw| {
+            String::from_utf8_lossy(&raw)
+                .replace('\0', " ")
+                .contains(program)
+        })
+        .unwrap_or(false)
+}
+
+/// Whether `pid` is alive and its command line mentions `program`.
+#[cfg(all(unix, not(target_os = "linux")))]
+fn runs_program(pid: u32, program: &str) -> bool {
+    Command::new("ps")
+        .args(["-p", &pid.to_string(), "-o", "command="])
+        .stderr(Stdio::null())
+        .output()
+        .map(|out| out.status.success() && String::from_utf8_lossy(&out.stdout).contains(program))
+        .unwrap_or(false)
+}
+
+/// Whether `pid` is alive and its image is `program`.
+#[cfg(windows)]
+fn runs_program(pid: u32, program: &str) -> bool {
+    // tasklist only reports the image name, not the full path
+    let Some(image) = Path::new(program).file_stem().and_then(|s| s.to_str()) else {
+        return false;
+    };
+    Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {pid}"), "/FO", "CSV", "/NH"])
+        .stderr(Stdio::null())
+        .output()
+        .map(|out| {
+            String::from_utf8_lossy(&out.stdout)
+                .to_lowercase()
+                .contains(&format!("\"{}.", image.to_lowercase()))
+        })
+        .unwrap_or(false)
+}
+
+#[cfg(unix)]
+fn kill_pid(pid: u32) -> Result<(), String> {
+    let status = Command::new("kill")
+        .args(["-KILL", &pid.to_string()])
+        .stderr(Stdio::null())
+        .status()
+        .map_err(|e| format!("Failed to run kill: {e}"))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("kill exited with status {:?}", status.code()))
+    }
+}
+
+#[cfg(windows)]
+fn kill_pid(pid: u32) -> Result<(), String> {
+    let status = Command::new("taskkill")
+        .args(["/PID", &pid.to_string(), "/T", "/F"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map_err(|e| format!("Failed to run taskkill: {e}"))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("taskkill exited with status {:?}", status.code()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn temp_pid_file() -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("adapter_pids_{}", Uuid::new_v4()))
+            .join("adapter_pids.json")
+    }
+
+    fn read_file(path: &Path) -> Vec<AdapterProcessInfo> {
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_register_and_drop_guard_track_pids_on_disk() {
+        let file = temp_pid_file();
+        let pids = Arc::new(AdapterPids::with_file(file.clone()));
+
+        let first = pids.register(101, Path::new("/bin/adapter-a"), Path::new("/ws"));
+        let _second = pids.register(202, Path::new("/bin/adapter-b"), Path::new("/ws"));
+        assert!(pids.contains(101));
+        let listed: Vec<u32> = pids.list().iter().map(|info| info.pid).collect();
+        assert_eq!(listed, vec![101, 202]);
+        assert_eq!(read_file(&file).len(), 2);
+
+        drop(first);
+        assert!(!pids.contains(101));
+        let on_disk = read_file(&file);
+        assert_eq!(on_disk.len(), 1);
+        assert_eq!(on_disk[0].pid, 202);
+        assert_eq!(on_disk[0].program, "/bin/adapter-b");
+        let _ = std::fs::remove_dir_all(file.parent().unwrap());
+    }
+
+    #[test]
+    fn test_stale_guard_does_not_remove_reused_pid() {
+        let pids = Arc::new(AdapterPids::new());
+        let stale = pids.register(7, Path::new("/bin/old"), Path::new("/ws"));
+        let _fresh = pids.register(7, Path::new("/bin/new"), Path::new("/ws"));
+
+        drop(stale);
+        assert!(pids.contains(7));
+        assert_eq!(pids.list()[0].program, "/bin/new");
+    }
+
+    #[test]
+    fn test_kill_refuses_unregistered_pid() {
+        let pids = AdapterPids::new();
+        assert!(pids.kill(std::process::id()).is_err());
+    }
+
+    #[test]
+    fn test_sweep_skips_pids_running_another_program_and_clears_file() {
+        let file = temp_pid_file();
+        std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+        // Our own pid is alive, but it isn't running this program
+        let leftover = AdapterProcessInfo {
+            pid: std::process::id(),
+            program: "/no/such/adapter-binary".to_string(),
+            cwd: "/ws".to_string(),
+            started_at_ms: 0.0,
+        };
+        std::fs::write(&file, serde_json::to_string(&vec![leftover]).unwrap()).unwrap();
+
+        let pids = AdapterPids::with_file(file.clone());
+        assert!(pids.sweep_previous_run().is_empty());
+        assert!(read_file(&file).is_empty());
+        let _ = std::fs::remove_dir_all(file.parent().unwrap());
+    }
+}
@@ -0,0 +1,112 @@
+//! Bounded execution of blocking filesystem work.
+//!
+//! `spawn_blocking` (and `tokio::fs`, which is built on it) shares one pool
+//! with every other blocking task in the app. Filesystem-heavy callers route
+//! their work through a [`BlockingFsLimiter`] so bursts (batch plugin installs,
+//! many agent fs requests) occupy at most [`DEFAULT_MAX_BLOCKING_FS_TASKS`]
+//! threads and can't stall unrelated blocking work.
+
+use std::sync::{Arc, OnceLock};
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::task::JoinError;
+
+/// Default cap on concurrent blocking filesystem tasks across the app.
+///
+/// Far below tokio's blocking pool size (512), so the pool always has room
+/// for non-fs blocking work.
+pub const DEFAULT_MAX_BLOCKING_FS_TASKS: usize = 16;
+
+/// Semaphore-bounded gate for blocking filesystem work.
+///
+/// Cloning shares the same permits.
+#[derive(Debug, Clone)]
+pub struct BlockingFsLimiter {
+    semaphore: Arc<Semaphore>,
+}
+
+impl BlockingFsLimiter {
+    /// Create a limiter allowing `max_concurrent` tasks at once (minimum 1).
+    pub fn new(max_concurrent: usize) -> Self {
+        Self {
+            semaphore: Arc::new(Semaphore::new(max_concurrent.max(1))),
+        }
+    }
+
+    /// The app-wide limiter, capped at [`DEFAULT_MAX_BLOCKING_FS_TASKS`].
+    pub fn shared() -> Self {
+        static SHARED: OnceLock<BlockingFsLimiter> = OnceLock::new();
+        SHARED
+            .get_or_init(|| Self::new(DEFAULT_MAX_BLOCKING_FS_TASKS))
+            .clone()
+    }
+
+    /// Wait for a slot; the slot is held until the permit is dropped.
+    ///
+    /// Use this around async `tokio::fs` calls, which spawn blocking tasks
+    /// internally.
+    pub async fn acquire(&self) -> OwnedSemaphorePermit {
+        self.semaphore
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("blocking fs semaphore is never closed")
+    }
+
+    /// Run `f` on the blocking pool once a slot is free.
+    ///
+    /// Drop-in replacement for `tokio::task::spawn_blocking(f).await`.
+    pub async fn run<F, T>(&self, f: F) -> Result<T, JoinError>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let permit = self.acquire().await;
+        tokio::task::spawn_blocking(move || {
+            let _permit = permit;
+            f()
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_run_never_exceeds_configured_concurrency() {
+        const LIMIT: usize = 2;
+        let limiter = BlockingFsLimiter::new(LIMIT);
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        // One more task than the limit, all started at once
+        let handles: Vec<_> = (0..=LIMIT)
+            .map(|_| {
+                let limiter = limiter.clone();
+                let running = running.clone();
+                let peak = peak.clone();
+                tokio::spawn(async move {
+                    limiter
+                        .run(move || {
+                            let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                            peak.fetch_max(now, Ordering::SeqCst);
+                            std::thread::sleep(Duration::from_millis(50));
+                            running.fetch_sub(1, Ordering::SeqCst);
+                        })
+                        .await
+                        .unwrap();
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.await.unwrap();
+        }
+
+        let peak = peak.load(Ordering::SeqCst);
+        assert!((1..=LIMIT).contains(&peak), "peak concurrency was {peak}");
+    }
+}
@@ -1,3 +1,6 @@
 //! Utility modules for cross-platform support and common operations.
 
+pub mod adapter_pids;
+pub mod blocking;
 pub mod platform;
+pub mod text;
@@ -0,0 +1,46 @@
+//! String helpers shared by output capture and previews.
+
+/// Longest prefix of `text` that fits in `max_bytes` without splitting a
+/// UTF-8 character.
+pub fn truncate_on_char_boundary(text: &str, max_bytes: usize) -> &str {
+    if text.len() <= max_bytes {
+        return text;
+    }
+    let mut end = max_bytes;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_text_is_returned_whole() {
+        assert_eq!(truncate_on_char_boundary("", 0), "");
+        assert_eq!(truncate_on_char_boundary("abc", 3), "abc");
+        assert_eq!(truncate_on_char_boundary("日本", 6), "日本");
+    }
+
+    #[test]
+    fn test_cut_at_a_boundary_keeps_the_whole_char() {
+        // "ab" + "é" (2 bytes) ends exactly at 4
+        assert_eq!(truncate_on_char_boundary("abéz", 4), "abé");
+        // "日" is 3 bytes
+        assert_eq!(truncate_on_char_boundary("日本語", 3), "日");
+        assert_eq!(truncate_on_char_boundary("日本語", 6), "日本");
+    }
+
+    #[test]
+    fn test_cut_inside_a_char_backs_off_to_its_start() {
+        assert_eq!(truncate_on_char_boundary("abéz", 3), "ab");
+        assert_eq!(truncate_on_char_boundary("日本語", 4), "日");
+        assert_eq!(truncate_on_char_boundary("日本語", 5), "日");
+        assert_eq!(truncate_on_char_boundary("日本語", 2), "");
+        // 4-byte emoji
+        assert_eq!(truncate_on_char_boundary("a😀b", 4), "a");
+        assert_eq!(truncate_on_char_boundary("a😀b", 5), "a😀");
+    }
+}
@@ -9,6 +9,10 @@
 import { useEffect } from 'react'
 import { listen } from '@tauri-apps/api/event'
 import { logger } from '@/lib/logger'
+import type {
+  AcpSessionUpdateEvent,
+  AgentRuntimeStatus,
+} from '@/lib/tauri-bindings'
 import {
   useChatStore,
   type AgentStatusLike,
@@ -16,59 +20,22 @@ import {
 } from '@/store/chat-store'
 
 // ============================================================================
-// Event Payload Types (matching Rust types in api/types.rs)
+// Event Payload Types (session updates come from the generated bindings)
 // ============================================================================
 
-/**
- * Agent runtime status.
- * Matches Rust AgentRuntimeStatus enum.
- */
-type AgentRuntimeStatus =
-  | { type: 'stopped' }
-  | { type: 'starting' }
-  | { type: 'running'; sessionId: string }
-  | { type: 'errored'; message: string }
-
 /**
  * Agent status changed event payload.
- * Matches Rust AgentStatusChangedEvent.
+ * Matches Rust AgentStatusChangedEvent, which no command returns and so has
+ * no generated binding.
  */
 interface AgentStatusChangedEvent {
   workspaceId: string
   agentId: string
+  pluginId: string
+  displayName: string | null
   status: AgentRuntimeStatus
 }
 
-/**
- * ACP session update types.
- * Matches Rust AcpSessionUpdate enum.
- */
-type AcpSessionUpdate =
-  | { type: 'userMessageChunk'; content: unknown }
-  | { type: 'agentMessageChunk'; content: unknown }
-  | { type: 'agentThoughtChunk'; content: unknown }
-  | { type: 'toolCall'; toolCall: unknown }
-  | { type: 'toolCallUpdate'; toolCallUpdate: unknown }
-  | { type: 'plan'; plan: unknown }
-  | { type: 'availableCommandsUpdate'; availableCommands: unknown }
-  | { type: 'currentModeUpdate'; currentModeId: unknown }
-  | { type: 'configOptionUpdate'; configOptions: unknown }
-  | { type: 'turnComplete'; stopReason: unknown }
-  | { type: 'raw'; json: unknown }
-
-/**
- * ACP session update event payload.
- * Matches Rust AcpSessionUpdateEvent.
- */
-interface AcpSessionUpdateEvent {
-  workspaceId: string
-  agentId: string
-  sessionId: string
-  seq?: number
-  emittedAtMs?: number
-  update: AcpSessionUpdate
-}
-
 // ============================================================================
 // Helper Functions
 // ============================================================================
@@ -553,22 +520,30 @@ export function useAgentChatEvents() {
             break
           }
 
+          case 'turnStarted': {
+            // The adapter accepted the prompt; turnComplete clears this
+            store.setSending(key, true)
+            logger.debug('Turn started', { promptId: update.promptId })
+            break
+          }
+
           case 'turnComplete': {
             store.endAssistantStreaming(key)
 
+            // A prompt rejected (or timed out) after its turn started
+            if (update.error) {
+              store.addSystemMessage(key, `Turn failed: ${update.error}`, meta)
+            }
+
             // Per Zed's pattern: only tool_use is an intermediate stop reason.
             // All other stop reasons (end_turn, max_tokens, refusal, cancelled, etc.)
             // are terminal and should clear the sending state.
             // This ensures unknown stop reasons default to terminal (safe behavior)
             // rather than leaving the UI stuck in "sending" state.
             const stopReason = update.stopReason
-            const stopStr =
-              typeof stopReason === 'string'
-                ? stopReason
-                : (stopReason as { type?: string })?.type
 
             // Normalize to lowercase for comparison
-            const normalizedStop = stopStr?.toLowerCase()
+            const normalizedStop = stopReason.toLowerCase()
 
             // Only tool_use is intermediate - agent continues after tool results
             const isIntermediate = normalizedStop === 'tool_use'
@@ -55,6 +55,19 @@ async sendNativeNotification(title: string, body: string | null) : Promise<Resul
     else return { status: "error", error: e  as any };
 }
 },
+/**
+ * Shows a user-facing alert unless notifications are disabled in preferences.
+ * Falls back to a `notification/show` event where native notifications are
+ * unavailable. Returns whether the alert was shown.
+ */
+async notify(title: string, body: string | null, level: NotificationLevel) : Promise<Result<boolean, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("notify", { title, body, level }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 /**
  * Saves emergency data to a JSON file for later recovery.
  * Validates filename and enforces a 10MB size limit.
@@ -91,6 +104,29 @@ async cleanupOldRecoveryFiles() : Promise<Result<number, RecoveryError>> {
     else return { status: "error", error: e  as any };
 }
 },
+/**
+ * Lists the adapter processes this app has spawned and not yet released.
+ */
+async recoveryListAdapterProcesses() : Promise<Result<AdapterProcessInfo[], RecoveryError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("recovery_list_adapter_processes") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Kills an adapter process spawned by this app.
+ * Refuses pids this app did not create.
+ */
+async recoveryKillAdapterProcess(pid: number) : Promise<Result<null, RecoveryError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("recovery_kill_adapter_process", { pid }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 /**
  * Shows the quick pane window and makes it the key window (for keyboard input).
  */
@@ -143,6 +179,25 @@ async updateQuickPaneShortcut(shortcut: string | null) : Promise<Result<null, st
     else return { status: "error", error: e  as any };
 }
 },
+/**
+ * Sends a one-off prompt from the quick pane to the focused workspace.
+ * 
+ * The prompt goes to the workspace's quick pane agent (created on first use)
+ * using the plugin chosen in preferences, and otherwise behaves like
+ * `chat_send_prompt`.
+ * 
+ * # Errors
+ * * `ApiError::NoFocusedWorkspace` - If no workspace is focused
+ * * Any error of `chat_send_prompt`
+ */
+async quickPaneSend(prompt: string) : Promise<Result<SendPromptAck, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("quick_pane_send", { prompt }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 /**
  * Creates a new workspace with the specified root directory.
  * 
@@ -167,14 +222,19 @@ async workspaceCreate(rootDir: string) : Promise<Result<WorkspaceSummary, ApiErr
 }
 },
 /**
- * Lists all workspaces.
+ * Lists workspaces, one page at a time.
+ * 
+ * # Arguments
+ * * `offset` - Workspaces to skip (default 0)
+ * * `limit` - Most workspaces to return (default: all)
  * 
  * # Returns
- * * `Vec<WorkspaceSummary>` - List of all workspace summaries, sorted by creation time (newest first)
+ * * `Page<WorkspaceSummary>` - The requested workspace summaries, sorted by
+ * creation time (newest first), and the total number of workspaces
  */
-async workspaceList() : Promise<Result<WorkspaceSummary[], ApiError>> {
+async workspaceList(offset: number | null, limit: number | null) : Promise<Result<Page<WorkspaceSummary>, ApiError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("workspace_list") };
+    return { status: "ok", data: await TAURI_INVOKE("workspace_list", { offset, limit }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
@@ -201,6 +261,117 @@ async workspaceDelete(workspaceId: string) : Promise<Result<null, ApiError>> {
     else return { status: "error", error: e  as any };
 }
 },
+/**
+ * Renames a workspace.
+ * 
+ * The name is shown instead of the full root path and survives restarts.
+ * 
+ * # Arguments
+ * * `workspace_id` - ID of the workspace to rename
+ * * `name` - New display name (trimmed)
+ * 
+ * # Returns
+ * * `WorkspaceSummary` - The workspace with its new name
+ * 
+ * # Errors
+ * * `ApiError::InvalidInput` - If the name is empty after trimming
+ * * `ApiError::WorkspaceNotFound` - If the workspace does not exist
+ */
+async workspaceRename(workspaceId: string, name: string) : Promise<Result<WorkspaceSummary, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("workspace_rename", { workspaceId, name }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Sets how large a file agents may read, how much command output they get back
+ * and how many commands may run at once.
+ * 
+ * Limits have fixed upper bounds (64 MiB reads, 16 MiB output per stream,
+ * 256 concurrent terminal commands) so a workspace can't be configured to
+ * exhaust memory or processes.
+ * 
+ * # Arguments
+ * * `workspace_id` - ID of the workspace to configure
+ * * `limits` - New `maxReadBytes`, `outputCaptureLimit` and `maxTerminals`
+ * 
+ * # Returns
+ * * `WorkspaceLimits` - The limits now in effect
+ * 
+ * # Errors
+ * * `ApiError::InvalidInput` - If a limit is zero or above its upper bound
+ * * `ApiError::WorkspaceNotFound` - If the workspace does not exist
+ */
+async workspaceSetLimits(workspaceId: string, limits: WorkspaceLimits) : Promise<Result<WorkspaceLimits, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("workspace_set_limits", { workspaceId, limits }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Sets the shell terminal commands in a workspace run under.
+ * 
+ * A shell that can't be found when a command starts is skipped (with a
+ * logged warning) in favor of the default `sh -c` / `cmd /C`.
+ * 
+ * # Arguments
+ * * `workspace_id` - ID of the workspace to configure
+ * * `shell` - Shell program and arguments, or `None` for the default
+ * 
+ * # Errors
+ * * `ApiError::InvalidInput` - If the shell program is empty
+ * * `ApiError::WorkspaceNotFound` - If the workspace does not exist
+ */
+async workspaceSetShell(workspaceId: string, shell: TerminalShell | null) : Promise<Result<null, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("workspace_set_shell", { workspaceId, shell }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Starts emitting `fs/changed` events for files under the workspace root.
+ * 
+ * Watching is opt-in so idle workspaces don't hold OS watch handles.
+ * Calling it again while already watching is a no-op.
+ * 
+ * # Arguments
+ * * `workspace_id` - ID of the workspace to watch
+ * 
+ * # Errors
+ * * `ApiError::WorkspaceNotFound` - If the workspace does not exist
+ * * `ApiError::IoError` - If the OS watcher can't be set up
+ */
+async workspaceStartWatch(workspaceId: string) : Promise<Result<null, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("workspace_start_watch", { workspaceId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Stops emitting `fs/changed` events for the workspace (no-op if not watching).
+ * 
+ * # Arguments
+ * * `workspace_id` - ID of the workspace to stop watching
+ * 
+ * # Errors
+ * * `ApiError::WorkspaceNotFound` - If the workspace does not exist
+ */
+async workspaceStopWatch(workspaceId: string) : Promise<Result<null, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("workspace_stop_watch", { workspaceId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 /**
  * Sets the currently focused workspace.
  * 
@@ -237,6 +408,30 @@ async workspaceGetFocus() : Promise<Result<string | null, ApiError>> {
     else return { status: "error", error: e  as any };
 }
 },
+/**
+ * Previews the variables a workspace `.env` file would pass to adapters.
+ * 
+ * Parses `<root>/.env` without injecting anything. Values are redacted:
+ * only variable names and parse warnings are returned.
+ * 
+ * # Arguments
+ * * `workspace_id` - ID of the workspace whose `.env` to preview
+ * 
+ * # Returns
+ * * `EnvPreview` - Whether the file exists, its variable names, and any warnings
+ * 
+ * # Errors
+ * * `ApiError::WorkspaceNotFound` - If the workspace does not exist
+ * * `ApiError::IoError` - If the file exists but cannot be read
+ */
+async workspacePreviewEnv(workspaceId: string) : Promise<Result<EnvPreview, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("workspace_preview_env", { workspaceId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 /**
  * Get the installation and update status of a plugin.
  * 
@@ -280,6 +475,7 @@ async pluginGetStatus(pluginId: string, checkUpdates: boolean) : Promise<Result<
  * 
  * * `plugin_id` - Plugin identifier (e.g., "claude-code", "codex", "gemini")
  * * `version` - Optional version to install
+ * * `package_manager` - Optional package manager (npm, pnpm, yarn); auto-detected if omitted
  * 
  * # Returns
  * 
@@ -289,268 +485,1468 @@ async pluginGetStatus(pluginId: string, checkUpdates: boolean) : Promise<Result<
  * 
  * Returns `ApiError::InvalidInput` if the plugin ID is invalid.
  * Returns `ApiError::PluginInstallInProgress` if the plugin is already being installed.
+ * Returns `ApiError::PluginInUse` if a running agent uses the plugin.
  */
-async pluginInstall(pluginId: string, version: string | null) : Promise<Result<OperationStarted, ApiError>> {
+async pluginInstall(pluginId: string, version: string | null, packageManager: PackageManager | null) : Promise<Result<OperationStarted, ApiError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("plugin_install", { pluginId, version }) };
+    return { status: "ok", data: await TAURI_INVOKE("plugin_install", { pluginId, version, packageManager }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
 /**
- * Respond to a pending permission request.
+ * Check whether the Node.js toolchain needed for plugin installs is available.
  * 
- * This command is called by the frontend when the user makes a decision
- * about a permission request (allow or deny).
+ * Lets the frontend disable the install button and explain what is missing
+ * before any permission prompt or filesystem work begins.
+ * 
+ * # Returns
+ * 
+ * Returns `ToolchainInfo` with:
+ * - `nodeVersion` / `npmVersion`: Detected versions, or null if missing
+ * - `minNodeVersion`: Minimum Node.js version the adapters require
+ * - `ready`: Whether installation can proceed
+ * - `message`: Explanation when `ready` is false
+ */
+async pluginCheckToolchain() : Promise<Result<ToolchainInfo, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("plugin_check_toolchain") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Repair a plugin whose installation is broken or incomplete.
+ * 
+ * Like `plugin_install`, this returns immediately and goes through the
+ * permission prompt. Once approved, `node_modules`, `package.json`, and
+ * `install.json` are removed and the previously recorded version is
+ * reinstalled, emitting `acp/plugin_status_changed` when done.
  * 
  * # Arguments
  * 
- * * `operation_id` - The operation ID from the permission request event
- * * `decision` - The user's decision (AllowOnce or Deny)
+ * * `plugin_id` - Plugin identifier (e.g., "claude-code")
  * 
  * # Returns
  * 
- * Ok(()) on success, or an error if the operation is not found or already resolved.
+ * Returns `OperationStarted` with the operation ID for tracking.
+ * 
+ * # Errors
+ * 
+ * Returns `ApiError::InvalidInput` if the plugin ID is invalid.
+ * Returns `ApiError::PluginInstallInProgress` if the plugin is already being installed.
+ * Returns `ApiError::PluginInUse` if a running agent uses the plugin.
  */
-async permissionRespond(operationId: string, decision: PermissionDecision) : Promise<Result<null, ApiError>> {
+async pluginRepair(pluginId: string) : Promise<Result<OperationStarted, ApiError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("permission_respond", { operationId, decision }) };
+    return { status: "ok", data: await TAURI_INVOKE("plugin_repair", { pluginId }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
 /**
- * Creates a new agent entity within a workspace.
+ * Check whether any running agent uses a plugin.
+ * 
+ * The frontend uses this to disable reinstall/repair while an adapter
+ * process for the plugin is alive.
  * 
  * # Arguments
- * * `workspace_id` - ID of the workspace to create the agent in
- * * `plugin_id` - Plugin identifier (e.g., "claude-code", "codex", "gemini")
- * * `display_name` - Optional display name for the agent
+ * 
+ * * `plugin_id` - Plugin identifier (e.g., "claude-code")
  * 
  * # Returns
- * * `AgentSummary` - Summary of the created agent including ID
+ * 
+ * Returns `true` if an agent in any workspace is running on the plugin.
+ */
+async pluginIsInUse(pluginId: string) : Promise<Result<boolean, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("plugin_is_in_use", { pluginId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Report where the plugin cache lives and how much space it uses.
+ * 
+ * # Returns
+ * 
+ * Returns `CacheInfo` with:
+ * - `pluginsRoot`: Absolute path to the plugins cache directory
+ * - `pluginIds`: Plugins currently installed in the cache
+ * - `totalBytes`: Total size on disk
  * 
  * # Errors
- * * `ApiError::WorkspaceNotFound` - If the workspace does not exist
- * * `ApiError::InvalidInput` - If plugin_id or display_name is invalid
+ * 
+ * Returns `ApiError::IoError` if the cache directory can't be resolved or read.
  */
-async agentCreate(workspaceId: string, pluginId: string, displayName: string | null) : Promise<Result<AgentSummary, ApiError>> {
+async cacheInfo() : Promise<Result<CacheInfo, ApiError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("agent_create", { workspaceId, pluginId, displayName }) };
+    return { status: "ok", data: await TAURI_INVOKE("cache_info") };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
 /**
- * Lists all agents within a workspace.
+ * Remove every installed plugin from the cache, plus transient data: the
+ * retained output of exited terminals and temp files of interrupted writes
+ * in workspaces.
+ * 
+ * Plugins are reinstalled through the usual install flow when needed.
+ * Nothing is removed if any cached plugin has a running agent or an install
+ * in progress.
  * 
  * # Arguments
- * * `workspace_id` - ID of the workspace to list agents from
+ * 
+ * * `confirm` - Must be `true`; guards against accidental wipes
  * 
  * # Returns
- * * `Vec<AgentSummary>` - List of all agent summaries
+ * 
+ * Returns `CacheClearResult` listing the removed plugins and bytes freed.
  * 
  * # Errors
- * * `ApiError::WorkspaceNotFound` - If the workspace does not exist
+ * 
+ * Returns `ApiError::InvalidInput` if `confirm` is not `true`.
+ * Returns `ApiError::PluginInUse` if a running agent uses a cached plugin.
+ * Returns `ApiError::PluginInstallInProgress` if a cached plugin is being
+ * installed or repaired.
+ * Returns `ApiError::IoError` if a plugin directory can't be removed.
  */
-async agentList(workspaceId: string) : Promise<Result<AgentSummary[], ApiError>> {
+async cacheClear(confirm: boolean) : Promise<Result<CacheClearResult, ApiError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("agent_list", { workspaceId }) };
+    return { status: "ok", data: await TAURI_INVOKE("cache_clear", { confirm }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
 /**
- * Send a prompt to an agent, triggering lazy startup if needed.
- * 
- * US-06: This command triggers agent lazy startup on first call.
- * The agent is started (spawn/initialize/new_session) and session ID is returned.
- * 
- * US-07: Sends the user's prompt to the agent via JSON-RPC over stdin.
- * Streaming responses arrive asynchronously via `acp/session_update` events.
+ * Report app version, runtime counts and installed plugin versions.
  * 
- * # Arguments
- * * `workspace_id` - ID of the workspace containing the agent
- * * `agent_id` - ID of the agent to send the prompt to
- * * `prompt` - The user's prompt text
+ * Cheap to compute; meant to be attached to bug reports.
  * 
  * # Returns
- * * `SendPromptAck` - Contains the session ID for tracking responses
  * 
- * # Events Emitted
- * * `agent/status_changed` - When agent starts (Starting → Running) or errors
- * * `acp/session_update` - Streaming session updates from the agent
+ * Returns `Diagnostics` with:
+ * - `appVersion`: Backend version
+ * - `workspaceCount`, `agentCount`, `runningAgentCount`: Across all workspaces
+ * - `activeTerminalCount`: Terminal commands still running
+ * - `pendingPermissionCount`: Permission requests awaiting a decision
+ * - `plugins`: Cached plugins and their installed versions
  * 
  * # Errors
- * * `ApiError::WorkspaceNotFound` - If workspace doesn't exist
- * * `ApiError::AgentNotFound` - If agent doesn't exist in workspace
- * * `ApiError::PluginNotInstalled` - If the agent's plugin is not installed
- * * `ApiError::PluginMissingBinPath` - If plugin has no binary path
- * * `ApiError::ProtocolError` - If ACP communication fails
- * * `ApiError::IoError` - If writing to stdin fails
+ * 
+ * Returns `ApiError::IoError` if the plugin cache can't be read.
  */
-async chatSendPrompt(workspaceId: string, agentId: string, prompt: string) : Promise<Result<SendPromptAck, ApiError>> {
+async diagnostics() : Promise<Result<Diagnostics, ApiError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("chat_send_prompt", { workspaceId, agentId, prompt }) };
+    return { status: "ok", data: await TAURI_INVOKE("diagnostics") };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
 /**
- * Stop the current turn for a session.
+ * Respond to a pending permission request.
  * 
- * US-12: Cancels the active turn for the specified session.
+ * This command is called by the frontend when the user makes a decision
+ * about a permission request (allow or deny).
  * 
  * # Arguments
- * * `workspace_id` - ID of the workspace containing the agent
- * * `agent_id` - ID of the agent to stop
- * * `session_id` - Session identifier to cancel the current turn for
+ * 
+ * * `operation_id` - The operation ID from the permission request event
+ * * `decision` - The user's decision (AllowOnce, AllowAlways or Deny);
+ * AllowAlways also remembers the grant for matching requests
+ * * `option_id` - The agent-defined option the user picked, when the request
+ * event listed `options`; if omitted, the closest option to `decision` is used
  * 
  * # Returns
- * * `()` - Stop request accepted
  * 
- * # Errors
- * * `ApiError::InvalidInput` - If any ID is empty
- * * `ApiError::WorkspaceNotFound` - If workspace doesn't exist
- * * `ApiError::AgentNotFound` - If agent doesn't exist in workspace
- * * `ApiError::ProtocolError` - If agent is not running or connection unavailable
+ * Ok(()) on success, or an error if the operation is not found or already resolved.
  */
-async chatStopTurn(workspaceId: string, agentId: string, sessionId: string) : Promise<Result<null, ApiError>> {
+async permissionRespond(operationId: string, decision: PermissionDecision, optionId: string | null) : Promise<Result<null, ApiError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("chat_stop_turn", { workspaceId, agentId, sessionId }) };
+    return { status: "ok", data: await TAURI_INVOKE("permission_respond", { operationId, decision, optionId }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
 /**
- * Stop a running terminal command by ID.
+ * Cancel a pending permission request.
+ * 
+ * Called when the operation that asked for permission is abandoned; the
+ * waiting operation fails with a cancelled error and the prompt can be
+ * dismissed.
  * 
  * # Arguments
- * * `workspace_id` - ID of the workspace containing the terminal
- * * `terminal_id` - ID of the terminal run to stop
+ * 
+ * * `operation_id` - The operation ID from the permission request event
  * 
  * # Returns
- * * `Ok(())` - Stop request accepted (idempotent)
- * * `Err(ApiError)` - If workspace is missing or inputs are invalid
+ * 
+ * Ok(()) on success, or `OperationNotFound` if it was already answered,
+ * cancelled or timed out.
  */
-async terminalKill(workspaceId: string, terminalId: string) : Promise<Result<null, ApiError>> {
+async permissionCancel(operationId: string) : Promise<Result<null, ApiError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("terminal_kill", { workspaceId, terminalId }) };
+    return { status: "ok", data: await TAURI_INVOKE("permission_cancel", { operationId }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
-}
-}
-
-/** user-defined events **/
-
-
-
-/** user-defined constants **/
-
-
-
-/** user-defined types **/
-
+},
 /**
- * Summary of an agent returned to the frontend
+ * List permission requests still awaiting a decision, oldest first.
+ * 
+ * Each entry has the same shape as the `acp/permission_requested` event, so
+ * the frontend can re-render prompts it missed (e.g. after a reload).
  */
-export type AgentSummary = { 
+async permissionListPending() : Promise<Result<AcpPermissionRequestedEvent[], ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("permission_list_pending") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 /**
- * Unique agent identifier
+ * List remembered "Allow Always" grants.
+ * 
+ * # Returns
+ * 
+ * Every remembered rule with the workspace (and agent) it is scoped to.
+ */
+async permissionRulesList() : Promise<Result<PermissionRule[], ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("permission_rules_list") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Revoke a remembered grant so matching requests prompt again.
+ * 
+ * # Arguments
+ * 
+ * * `rule_id` - The rule ID from `permission_rules_list`
+ * 
+ * # Returns
+ * 
+ * Ok(()) on success, or `InvalidInput` if no rule has this ID.
+ */
+async permissionRuleRevoke(ruleId: string) : Promise<Result<null, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("permission_rule_revoke", { ruleId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Recent permission decisions for display, newest first.
+ * 
+ * # Arguments
+ * 
+ * * `limit` - Maximum number of entries (defaults to everything kept in memory)
+ * 
+ * # Returns
+ * 
+ * Each resolved request with what was asked, by whom, and how it was resolved.
+ */
+async permissionHistory(limit: number | null) : Promise<Result<PermissionAuditEntry[], ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("permission_history", { limit }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Set how long permission requests wait for an answer before being denied.
+ * 
+ * # Arguments
+ * 
+ * * `source_kind` - Only this kind of request (omit to change the default,
+ * which is 5 minutes out of the box)
+ * * `timeout_secs` - Seconds to wait; omit to wait indefinitely
+ * 
+ * # Returns
+ * 
+ * Ok(()) on success, or `InvalidInput` if `timeout_secs` is zero.
+ */
+async permissionSetTimeout(sourceKind: PermissionSourceKind | null, timeoutSecs: number | null) : Promise<Result<null, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("permission_set_timeout", { sourceKind, timeoutSecs }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Creates a new agent entity within a workspace.
+ * 
+ * # Arguments
+ * * `workspace_id` - ID of the workspace to create the agent in
+ * * `plugin_id` - Plugin identifier (e.g., "claude-code", "codex", "gemini")
+ * * `display_name` - Optional display name for the agent
+ * 
+ * # Returns
+ * * `AgentSummary` - Summary of the created agent including ID
+ * 
+ * # Errors
+ * * `ApiError::WorkspaceNotFound` - If the workspace does not exist
+ * * `ApiError::InvalidInput` - If plugin_id or display_name is invalid
+ */
+async agentCreate(workspaceId: string, pluginId: string, displayName: string | null) : Promise<Result<AgentSummary, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("agent_create", { workspaceId, pluginId, displayName }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Lists agents within a workspace, one page at a time.
+ * 
+ * # Arguments
+ * * `workspace_id` - ID of the workspace to list agents from
+ * * `offset` - Agents to skip (default 0)
+ * * `limit` - Most agents to return (default: all)
+ * 
+ * # Returns
+ * * `Page<AgentSummary>` - The requested agent summaries, oldest first, and
+ * the total number of agents
+ * 
+ * # Errors
+ * * `ApiError::WorkspaceNotFound` - If the workspace does not exist
+ */
+async agentList(workspaceId: string, offset: number | null, limit: number | null) : Promise<Result<Page<AgentSummary>, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("agent_list", { workspaceId, offset, limit }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Enables or disables automatic restarts for an agent.
+ * 
+ * When enabled, an adapter that exits unexpectedly is restarted with
+ * exponential backoff (a limited number of attempts), resuming its session.
+ * `agent_stop` never triggers a restart.
+ * 
+ * # Arguments
+ * * `workspace_id` - ID of the workspace containing the agent
+ * * `agent_id` - ID of the agent to configure
+ * * `enabled` - Whether to restart automatically
+ * 
+ * # Errors
+ * * `ApiError::WorkspaceNotFound` - If the workspace does not exist
+ * * `ApiError::AgentNotFound` - If the agent does not exist
+ */
+async agentSetAutoRestart(workspaceId: string, agentId: string, enabled: boolean) : Promise<Result<null, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("agent_set_auto_restart", { workspaceId, agentId, enabled }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Enables or disables protocol tracing for an agent.
+ * 
+ * While on, every JSON-RPC frame exchanged with the adapter is emitted as an
+ * `acp/protocol_trace` event, with large content such as image data
+ * redacted. Off by default since frames include prompt content; the
+ * setting isn't saved.
+ * 
+ * # Arguments
+ * * `workspace_id` - ID of the workspace containing the agent
+ * * `agent_id` - ID of the agent to trace
+ * * `enabled` - Whether to emit trace events
+ * 
+ * # Errors
+ * * `ApiError::WorkspaceNotFound` - If the workspace does not exist
+ * * `ApiError::AgentNotFound` - If the agent does not exist
+ */
+async agentSetProtocolTrace(workspaceId: string, agentId: string, enabled: boolean) : Promise<Result<null, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("agent_set_protocol_trace", { workspaceId, agentId, enabled }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Chooses what happens to prompts sent while an agent is mid-turn.
+ * 
+ * `queue` (the default) holds them and sends each once the previous turn
+ * completes; `reject` fails `chat_send_prompt` with `TurnInProgress`.
+ * 
+ * # Arguments
+ * * `workspace_id` - ID of the workspace containing the agent
+ * * `agent_id` - ID of the agent to configure
+ * * `policy` - Queue or reject busy prompts
+ * 
+ * # Errors
+ * * `ApiError::WorkspaceNotFound` - If the workspace does not exist
+ * * `ApiError::AgentNotFound` - If the agent does not exist
+ */
+async agentSetBusyPromptPolicy(workspaceId: string, agentId: string, policy: BusyPromptPolicy) : Promise<Result<null, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("agent_set_busy_prompt_policy", { workspaceId, agentId, policy }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Returns an agent's current runtime status.
+ * 
+ * Lets the UI recover the status after a reload or a missed
+ * `agent/status_changed` event. Agents that were never started are `stopped`.
+ * 
+ * # Arguments
+ * * `workspace_id` - ID of the workspace containing the agent
+ * * `agent_id` - ID of the agent to query
+ * 
+ * # Errors
+ * * `ApiError::WorkspaceNotFound` - If the workspace does not exist
+ * * `ApiError::AgentNotFound` - If the agent does not exist
+ */
+async agentStatus(workspaceId: string, agentId: string) : Promise<Result<AgentRuntimeStatus, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("agent_status", { workspaceId, agentId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Returns the slash commands an agent's session last announced.
+ * 
+ * Adapters send `availableCommandsUpdate` session updates as their command
+ * list changes; this lets the UI recover the latest list after a reload.
+ * 
+ * # Arguments
+ * * `workspace_id` - ID of the workspace containing the agent
+ * * `agent_id` - ID of the agent to query
+ * * `session_id` - Session whose commands to return
+ * 
+ * # Returns
+ * * The commands as the adapter sent them (empty if none were received yet)
+ * 
+ * # Errors
+ * * `ApiError::WorkspaceNotFound` - If the workspace does not exist
+ * * `ApiError::AgentNotFound` - If the agent does not exist
+ */
+async agentAvailableCommands(workspaceId: string, agentId: string, sessionId: string) : Promise<Result<JsonValue[], ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("agent_available_commands", { workspaceId, agentId, sessionId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Returns the mode an agent's session was last reported in.
+ * 
+ * Sessions report their mode when created and on every
+ * `currentModeUpdate`; this lets the UI recover it after a reload.
+ * 
+ * # Arguments
+ * * `workspace_id` - ID of the workspace containing the agent
+ * * `agent_id` - ID of the agent to query
+ * * `session_id` - Session whose mode to return
+ * 
+ * # Returns
+ * * The mode ID, or `null` if the adapter hasn't reported one
+ * 
+ * # Errors
+ * * `ApiError::WorkspaceNotFound` - If the workspace does not exist
+ * * `ApiError::AgentNotFound` - If the agent does not exist
+ */
+async agentCurrentMode(workspaceId: string, agentId: string, sessionId: string) : Promise<Result<string | null, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("agent_current_mode", { workspaceId, agentId, sessionId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Switches an agent's session to another mode via ACP `session/set_mode`.
+ * 
+ * Once the adapter accepts, the change is emitted as a `currentModeUpdate`
+ * session update.
+ * 
+ * # Arguments
+ * * `workspace_id` - ID of the workspace containing the agent
+ * * `agent_id` - ID of the agent
+ * * `session_id` - Session to switch
+ * * `mode_id` - One of the modes the adapter offers for the session
+ * 
+ * # Errors
+ * * `ApiError::WorkspaceNotFound` - If the workspace does not exist
+ * * `ApiError::AgentNotFound` - If the agent does not exist
+ * * `ApiError::InvalidInput` - If the session isn't open or the mode is unknown
+ * * `ApiError::ProtocolError` - If the agent isn't running, has no modes, or
+ * the adapter rejects the change
+ */
+async agentSetMode(workspaceId: string, agentId: string, sessionId: string, modeId: string) : Promise<Result<null, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("agent_set_mode", { workspaceId, agentId, sessionId, modeId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Opens another session on an agent, starting the agent if needed.
+ * 
+ * The session has its own turn and prompt queue; pass its ID to
+ * `chat_send_prompt` to prompt it. The agent's primary session is unchanged.
+ * 
+ * # Arguments
+ * * `workspace_id` - ID of the workspace containing the agent
+ * * `agent_id` - ID of the agent
+ * 
+ * # Returns
+ * * `Ok(SessionId)` - The new session
+ * 
+ * # Errors
+ * * `ApiError::WorkspaceNotFound` - If the workspace does not exist
+ * * `ApiError::AgentNotFound` - If the agent does not exist
+ * * `ApiError::ProtocolError` - If the adapter can't create the session
+ */
+async agentNewSession(workspaceId: string, agentId: string) : Promise<Result<string, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("agent_new_session", { workspaceId, agentId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Closes a session opened with `agent_new_session`.
+ * 
+ * Its running turn is cancelled and queued prompts are dropped.
+ * 
+ * # Arguments
+ * * `workspace_id` - ID of the workspace containing the agent
+ * * `agent_id` - ID of the agent
+ * * `session_id` - Session to close
+ * 
+ * # Errors
+ * * `ApiError::WorkspaceNotFound` - If the workspace does not exist
+ * * `ApiError::AgentNotFound` - If the agent does not exist
+ * * `ApiError::InvalidInput` - If the session is the agent's primary one or
+ * isn't open
+ * * `ApiError::ProtocolError` - If the adapter fails to close the session
+ */
+async agentCloseSession(workspaceId: string, agentId: string, sessionId: string) : Promise<Result<null, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("agent_close_session", { workspaceId, agentId, sessionId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Deletes an agent from a workspace.
+ * 
+ * A running agent is stopped first so its adapter process doesn't outlive it.
+ * 
+ * # Arguments
+ * * `workspace_id` - ID of the workspace containing the agent
+ * * `agent_id` - ID of the agent to delete
+ * 
+ * # Errors
+ * * `ApiError::WorkspaceNotFound` - If the workspace does not exist
+ * * `ApiError::AgentNotFound` - If the agent does not exist
+ */
+async agentDelete(workspaceId: string, agentId: string) : Promise<Result<null, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("agent_delete", { workspaceId, agentId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Configures the MCP servers passed to an agent's adapter.
+ * 
+ * The servers are sent with `session/new` (and `session/load`) the next
+ * time the agent starts; a running adapter keeps its current servers.
+ * 
+ * # Arguments
+ * * `workspace_id` - ID of the workspace containing the agent
+ * * `agent_id` - ID of the agent to configure
+ * * `mcp_servers` - Stdio MCP servers (name, command, args, env)
+ * 
+ * # Errors
+ * * `ApiError::WorkspaceNotFound` - If the workspace does not exist
+ * * `ApiError::AgentNotFound` - If the agent does not exist
+ * * `ApiError::InvalidInput` - If a server has an empty name or command
+ */
+async agentSetMcpServers(workspaceId: string, agentId: string, mcpServers: McpServerConfig[]) : Promise<Result<null, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("agent_set_mcp_servers", { workspaceId, agentId, mcpServers }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Stops a running agent, shutting down its adapter process.
+ * 
+ * Unlike `chat_stop_turn`, this ends the agent itself; the next prompt
+ * starts it again. Stopping an agent that isn't running succeeds.
+ * 
+ * # Arguments
+ * * `workspace_id` - ID of the workspace containing the agent
+ * * `agent_id` - ID of the agent to stop
+ * 
+ * # Errors
+ * * `ApiError::WorkspaceNotFound` - If the workspace does not exist
+ * * `ApiError::AgentNotFound` - If the agent does not exist
+ */
+async agentStop(workspaceId: string, agentId: string) : Promise<Result<null, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("agent_stop", { workspaceId, agentId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Restarts an agent, shutting down its adapter and starting a fresh one.
+ * 
+ * The new adapter resumes the agent's session where the adapter supports
+ * `session/load`, so the conversation survives; otherwise a new session is
+ * created. Restarting a stopped agent starts it.
+ * 
+ * # Arguments
+ * * `workspace_id` - ID of the workspace containing the agent
+ * * `agent_id` - ID of the agent to restart
+ * 
+ * # Returns
+ * * `SessionId` - The resumed or new session
+ * 
+ * # Events Emitted
+ * * `agent/status_changed` - Stopped, then Starting and Running (or Errored)
+ * 
+ * # Errors
+ * * `ApiError::WorkspaceNotFound` - If the workspace does not exist
+ * * `ApiError::AgentNotFound` - If the agent does not exist
+ * * `ApiError::PluginNotInstalled` - If the agent's plugin is not installed
+ * * `ApiError::ProtocolError` - If the new adapter fails to start
+ */
+async agentRestart(workspaceId: string, agentId: string) : Promise<Result<string, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("agent_restart", { workspaceId, agentId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Send a prompt to an agent, triggering lazy startup if needed.
+ * 
+ * US-06: This command triggers agent lazy startup on first call.
+ * The agent is started (spawn/initialize/new_session) and session ID is returned.
+ * 
+ * US-07: Sends the user's prompt to the agent via JSON-RPC over stdin.
+ * Streaming responses arrive asynchronously via `acp/session_update` events.
+ * 
+ * # Arguments
+ * * `workspace_id` - ID of the workspace containing the agent
+ * * `agent_id` - ID of the agent to send the prompt to
+ * * `session_id` - Open session to send to; `None` for the agent's primary session
+ * * `prompt` - The user's prompt text
+ * * `attachments` - Optional images or resource links sent after the text
+ * * `timeout_ms` - Optional limit on how long this turn may run once started;
+ * a turn still running then is cancelled and completes with an error
+ * 
+ * # Returns
+ * * `SendPromptAck` - Contains the session ID and turn ID for tracking responses,
+ * or `queued: true` if the prompt waits for the active turn to complete
+ * 
+ * # Events Emitted
+ * * `agent/status_changed` - When agent starts (Starting → Running) or errors
+ * * `acp/session_update` - Streaming session updates from the agent
+ * 
+ * # Errors
+ * * `ApiError::WorkspaceNotFound` - If workspace doesn't exist
+ * * `ApiError::AgentNotFound` - If agent doesn't exist in workspace
+ * * `ApiError::InvalidInput` - If `session_id` isn't an open session of the agent,
+ * or `timeout_ms` is zero or above one hour
+ * * `ApiError::PluginNotInstalled` - If the agent's plugin is not installed
+ * * `ApiError::PluginMissingBinPath` - If plugin has no binary path
+ * * `ApiError::TurnInProgress` - If a turn is active and the agent rejects busy prompts
+ * * `ApiError::TooManyPrompts` - If the session already has as many prompts outstanding as allowed
+ * * `ApiError::ProtocolError` - If ACP communication fails, or the adapter
+ * didn't accept the prompt within `timeout_ms`
+ * * `ApiError::IoError` - If writing to stdin fails
+ */
+async chatSendPrompt(workspaceId: string, agentId: string, sessionId: string | null, prompt: string, attachments: PromptContentBlock[] | null, timeoutMs: number | null) : Promise<Result<SendPromptAck, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("chat_send_prompt", { workspaceId, agentId, sessionId, prompt, attachments, timeoutMs }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Load the recorded transcript of a session, oldest update first.
+ * 
+ * Transcripts are only written while the `sessionTranscripts` preference is
+ * on; each entry is an `acp/session_update` event as it was emitted.
+ * 
+ * # Arguments
+ * * `workspace_id` - ID of the workspace the session belongs to
+ * * `session_id` - Session whose transcript to load
+ * 
+ * # Returns
+ * * The recorded updates (empty if none were recorded)
+ * 
+ * # Errors
+ * * `ApiError::InvalidInput` - If an ID is empty
+ * * `ApiError::IoError` - If the transcript can't be read
+ */
+async sessionTranscriptRead(workspaceId: string, sessionId: string) : Promise<Result<AcpSessionUpdateEvent[], ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("session_transcript_read", { workspaceId, sessionId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Stop the current turn for a session.
+ * 
+ * US-12: Cancels the active turn for the specified session.
+ * 
+ * # Arguments
+ * * `workspace_id` - ID of the workspace containing the agent
+ * * `agent_id` - ID of the agent to stop
+ * * `session_id` - Session identifier to cancel the current turn for
+ * 
+ * # Returns
+ * * `()` - Stop request accepted
+ * 
+ * # Errors
+ * * `ApiError::InvalidInput` - If any ID is empty
+ * * `ApiError::WorkspaceNotFound` - If workspace doesn't exist
+ * * `ApiError::AgentNotFound` - If agent doesn't exist in workspace
+ * * `ApiError::ProtocolError` - If agent is not running or connection unavailable
+ */
+async chatStopTurn(workspaceId: string, agentId: string, sessionId: string) : Promise<Result<null, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("chat_stop_turn", { workspaceId, agentId, sessionId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Stop a running terminal command by ID.
+ * 
+ * # Arguments
+ * * `workspace_id` - ID of the workspace containing the terminal
+ * * `terminal_id` - ID of the terminal run to stop
+ * 
+ * # Returns
+ * * `Ok(())` - Stop request accepted (idempotent)
+ * * `Err(ApiError)` - If workspace is missing or inputs are invalid
+ */
+async terminalKill(workspaceId: string, terminalId: string) : Promise<Result<null, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("terminal_kill", { workspaceId, terminalId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * List the terminal commands still running in a workspace.
+ * 
+ * # Arguments
+ * * `workspace_id` - ID of the workspace to list
+ * 
+ * # Returns
+ * * `Ok(Vec<TerminalInfo>)` - Running terminals, oldest first
+ * * `Err(ApiError)` - If workspace is missing or inputs are invalid
+ */
+async terminalList(workspaceId: string) : Promise<Result<TerminalInfo[], ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("terminal_list", { workspaceId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Stop every running terminal command in a workspace.
+ * 
+ * # Arguments
+ * * `workspace_id` - ID of the workspace whose terminals to stop
+ * 
+ * # Returns
+ * * `Ok(())` - Stop requests sent (idempotent)
+ * * `Err(ApiError)` - If workspace is missing or inputs are invalid
+ */
+async terminalKillAll(workspaceId: string) : Promise<Result<null, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("terminal_kill_all", { workspaceId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * List a directory inside a workspace.
+ * 
+ * # Arguments
+ * * `workspace_id` - ID of the workspace to browse
+ * * `path` - Directory path relative to the workspace root (empty: the root)
+ * 
+ * # Returns
+ * * `Ok(Vec<DirEntryInfo>)` - Entries, directories first, then by name
+ * * `Err(ApiError)` - If the workspace is missing, the path escapes the
+ * workspace, or the path is not a directory
+ */
+async fsListDir(workspaceId: string, path: string) : Promise<Result<DirEntryInfo[], ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("fs_list_dir", { workspaceId, path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Size, modification time and type of a path inside a workspace.
+ * 
+ * # Arguments
+ * * `workspace_id` - ID of the workspace the path belongs to
+ * * `path` - File or directory path (relative to the workspace root or absolute)
+ * 
+ * # Returns
+ * * `Ok(FileMetadata)` - Entry info; symlinks are reported as such
+ * * `Err(ApiError)` - If the workspace is missing, the path escapes the
+ * workspace, or it doesn't exist
+ */
+async fsMetadata(workspaceId: string, path: string) : Promise<Result<FileMetadata, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("fs_metadata", { workspaceId, path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+}
+}
+
+/** user-defined events **/
+
+
+
+/** user-defined constants **/
+
+
+
+/** user-defined types **/
+
+/**
+ * Event payload: permission requested (acp/permission_requested)
+ */
+export type AcpPermissionRequestedEvent = { 
+/**
+ * Operation identifier for responding
+ */
+operationId: string; 
+/**
+ * What is being requested
+ */
+source: PermissionSource; 
+/**
+ * When the request was made (ms since epoch)
+ */
+requestedAtMs: number; 
+/**
+ * Optional origin context
+ */
+origin: PermissionOrigin | null; 
+/**
+ * Agent-defined choices; empty means a plain allow/deny prompt
+ */
+options: PermissionOption[] }
+/**
+ * ACP session update types
+ * 
+ * US-07: Represents different types of updates that can be received from
+ * an ACP adapter during a session. Tool calls are typed; other payloads
+ * use `serde_json::Value` for flexibility.
+ * 
+ * Variants are inspired by Zed's SessionUpdate enum but kept flexible
+ * for compatibility with various ACP adapters.
+ * 
+ * Field names match Claude Code ACP adapter format:
+ * - agentMessageChunk: { content: [...] }
+ * - availableCommandsUpdate: { availableCommands: [...] }
+ * - toolCall: { toolCall: {...} } or inline fields
+ */
+export type AcpSessionUpdate = 
+/**
+ * User message content chunk
+ */
+{ type: "userMessageChunk"; content: JsonValue } | 
+/**
+ * Agent message content chunk
+ */
+{ type: "agentMessageChunk"; content: JsonValue } | 
+/**
+ * Agent thought/reasoning chunk
+ */
+{ type: "agentThoughtChunk"; content: JsonValue } | 
+/**
+ * Tool call initiated
+ */
+{ type: "toolCall"; toolCall: AcpToolCall } | 
+/**
+ * Tool call progress update
+ */
+{ type: "toolCallUpdate"; toolCallUpdate: AcpToolCallUpdate } | 
+/**
+ * Implementation plan
+ */
+{ type: "plan"; plan: JsonValue } | 
+/**
+ * Available commands update
+ */
+{ type: "availableCommandsUpdate"; availableCommands: JsonValue } | 
+/**
+ * Current mode update
+ */
+{ type: "currentModeUpdate"; currentModeId: JsonValue } | 
+/**
+ * Configuration option update
+ */
+{ type: "configOptionUpdate"; configOptions: JsonValue } | 
+/**
+ * Turn start signal, sent once the adapter accepts a prompt
+ */
+{ type: "turnStarted"; 
+/**
+ * Request ID of the prompt, which is also the turn ID
+ */
+promptId: string } | 
+/**
+ * Turn completion signal with stop reason
+ */
+{ type: "turnComplete"; stopReason: string; 
+/**
+ * Why the turn failed, if the adapter rejected its prompt after the
+ * turn had started or it timed out
+ */
+error?: string } | 
+/**
+ * Raw/unknown update (fallback for unrecognized formats)
+ */
+{ type: "raw"; json: JsonValue }
+/**
+ * Event payload: session update (acp/session_update)
+ * 
+ * US-07: Emitted when the ACP adapter sends session updates (message chunks,
+ * tool calls, plans, etc.). The frontend subscribes to this event to display
+ * streaming responses.
+ */
+export type AcpSessionUpdateEvent = { 
+/**
+ * Workspace this agent belongs to
+ */
+workspaceId: string; 
+/**
+ * Agent identifier
+ */
+agentId: string; 
+/**
+ * Plugin the agent runs on, for labels (`agent_id` identifies the agent)
+ */
+pluginId?: string; 
+/**
+ * Agent's display name, if it has one, for labels
+ */
+displayName?: string; 
+/**
+ * Session identifier
+ */
+sessionId: string; 
+/**
+ * Monotonic sequence number (per agent host) for deterministic ordering
+ */
+seq: number; 
+/**
+ * Timestamp when the host emitted this update (ms since UNIX epoch)
+ */
+emittedAtMs: number; 
+/**
+ * Prompt turn this update belongs to, when the adapter identifies it
+ */
+turnId: string | null; 
+/**
+ * The update payload
+ */
+update: AcpSessionUpdate }
+/**
+ * A tool call the agent started
+ * 
+ * Mirrors the ACP `ToolCall` schema. `raw` keeps the payload exactly as
+ * the adapter sent it, for fields this type doesn't know about yet.
+ */
+export type AcpToolCall = { toolCallId: string; title?: string; kind?: ToolCallKind; status?: ToolCallStatus; content?: ToolCallContent[]; locations?: ToolCallLocation[]; rawInput?: JsonValue; rawOutput?: JsonValue; 
+/**
+ * The payload as received
+ */
+raw?: JsonValue }
+/**
+ * Changes to a tool call; absent fields are unchanged
+ * 
+ * Mirrors the ACP `ToolCallUpdate` schema, with the same `raw` escape
+ * hatch as [`AcpToolCall`].
+ */
+export type AcpToolCallUpdate = { toolCallId: string; title?: string; kind?: ToolCallKind; status?: ToolCallStatus; content?: ToolCallContent[]; locations?: ToolCallLocation[]; rawInput?: JsonValue; rawOutput?: JsonValue; 
+/**
+ * The payload as received
+ */
+raw?: JsonValue }
+/**
+ * An adapter process spawned by this app that hasn't been released yet
+ */
+export type AdapterProcessInfo = { 
+/**
+ * OS process id
+ */
+pid: number; 
+/**
+ * Executable the adapter was launched from
+ */
+program: string; 
+/**
+ * Working directory (workspace root) it was launched in
+ */
+cwd: string; 
+/**
+ * Spawn time (milliseconds since epoch)
+ */
+startedAtMs: number }
+/**
+ * Runtime status of an agent
+ */
+export type AgentRuntimeStatus = 
+/**
+ * Agent is not running
+ */
+{ type: "stopped" } | 
+/**
+ * Agent is starting up (spawn/initialize/new_session in progress)
+ */
+{ type: "starting" } | 
+/**
+ * Agent is running with an active session
+ */
+{ type: "running"; sessionId: string } | 
+/**
+ * Agent encountered an error
+ */
+{ type: "errored"; message: string }
+/**
+ * Summary of an agent returned to the frontend
+ */
+export type AgentSummary = { 
+/**
+ * Unique agent identifier
  */
 agentId: string; 
 /**
- * Workspace this agent belongs to
+ * Workspace this agent belongs to
+ */
+workspaceId: string; 
+/**
+ * Plugin identifier (e.g., "claude-code", "codex", "gemini")
+ */
+pluginId: string; 
+/**
+ * Optional display name for the agent
+ */
+displayName: string | null; 
+/**
+ * Whether the agent is restarted automatically after an unexpected exit
+ */
+autoRestart: boolean; 
+/**
+ * What happens to a prompt sent while a turn is in progress
+ */
+busyPromptPolicy: BusyPromptPolicy }
+/**
+ * API errors for frontend consumption
+ */
+export type ApiError = 
+/**
+ * Invalid input parameter
+ */
+{ type: "invalidInput"; message: string } | 
+/**
+ * Path does not exist or is not accessible
+ */
+{ type: "pathNotFound"; path: string } | 
+/**
+ * Path is not a directory
+ */
+{ type: "pathNotDirectory"; path: string } | 
+/**
+ * IO error during file system operation
+ */
+{ type: "ioError"; message: string } | 
+/**
+ * Workspace not found by ID
+ */
+{ type: "workspaceNotFound"; workspaceId: string } | 
+/**
+ * Agent not found by ID
+ */
+{ type: "agentNotFound"; agentId: string } | 
+/**
+ * Operation not found by ID (e.g., permission already resolved, expired, or never existed)
+ */
+{ type: "operationNotFound"; operationId: string } | 
+/**
+ * Permission was denied by the user
+ */
+{ type: "permissionDenied"; operationId: string } | 
+/**
+ * Plugin installation is already in progress
+ */
+{ type: "pluginInstallInProgress"; pluginId: string } | 
+/**
+ * Plugin is not installed (required for lazy startup)
+ */
+{ type: "pluginNotInstalled"; pluginId: string } | 
+/**
+ * Plugin is installed but missing binary path in metadata
+ */
+{ type: "pluginMissingBinPath"; pluginId: string } | 
+/**
+ * Plugin files can't be changed while an agent is running on it
+ */
+{ type: "pluginInUse"; pluginId: string } | 
+/**
+ * Protocol error during ACP communication
+ */
+{ type: "protocolError"; kind: ProtocolErrorKind; message: string } | 
+/**
+ * The adapter no longer knows the session (e.g., it restarted)
+ */
+{ type: "sessionNotFound"; sessionId: string } | 
+/**
+ * The operation was aborted because its turn was cancelled
+ */
+{ type: "cancelled"; message: string } | 
+/**
+ * The agent is still working on a turn and won't take another prompt
+ */
+{ type: "turnInProgress"; sessionId: string } | 
+/**
+ * The workspace already runs as many terminal commands as it allows
+ */
+{ type: "tooManyTerminals"; limit: number } | 
+/**
+ * As many agents are running (across all workspaces) as the app allows
+ */
+{ type: "tooManyAgents"; limit: number } | 
+/**
+ * The session already has as many prompts outstanding as allowed
+ */
+{ type: "tooManyPrompts"; sessionId: string; limit: number } | 
+/**
+ * The action needs a focused workspace, but none is focused
+ */
+{ type: "noFocusedWorkspace" }
+/**
+ * Application preferences that persist to disk.
+ * Only contains settings that should be saved between sessions.
+ */
+export type AppPreferences = { theme: string; 
+/**
+ * Color theme name (e.g., "default", "claude")
+ */
+color_theme?: string; 
+/**
+ * Global shortcut for quick pane (e.g., "CommandOrControl+Shift+.")
+ * If None, uses the default shortcut
+ */
+quick_pane_shortcut: string | null; 
+/**
+ * Plugin of the agent that answers quick pane prompts (e.g., "codex")
+ * If None, uses the default plugin
+ */
+quick_pane_plugin_id?: string; 
+/**
+ * User's preferred language (e.g., "en", "es", "de")
+ * If None, uses system locale detection
+ */
+language: string | null; 
+/**
+ * Also append permission decisions to a JSONL audit file in the app data dir
+ */
+permission_audit_log?: boolean; 
+/**
+ * Show alerts such as finished installs and agent failures
+ */
+notifications_enabled?: boolean; 
+/**
+ * Keep a JSONL transcript of every session in the app data dir
+ */
+session_transcripts?: boolean; 
+/**
+ * Pass the variables in each workspace's `.env` to its agents and
+ * terminal commands
+ */
+workspace_env_file?: boolean; 
+/**
+ * Merge agent message and thought chunks arriving within this many
+ * milliseconds into one update (0 turns merging off; capped at 250)
+ */
+message_chunk_coalesce_ms?: number; 
+/**
+ * Ping each adapter this often, in milliseconds, and treat it as lost
+ * once a ping goes unanswered (0 turns keepalive off; at least 1000
+ * otherwise)
+ */
+adapter_keepalive_interval_ms?: number; 
+/**
+ * How long a keepalive ping may go unanswered, in milliseconds (0 uses
+ * the default of 10 seconds)
+ */
+adapter_keepalive_timeout_ms?: number; 
+/**
+ * Rules that approve or refuse agent terminal commands without asking,
+ * keyed by workspace ID
+ */
+terminal_command_rules?: Partial<{ [key in string]: TerminalCommandRule[] }>; 
+/**
+ * Rules that approve agent file reads or refuse file access without
+ * asking, keyed by workspace ID
+ */
+fs_path_rules?: Partial<{ [key in string]: FsPathRule[] }>; 
+/**
+ * Refuse agent access to `DEFAULT_FS_DENY_PATTERNS` in every workspace,
+ * on top of its own file path rules
+ */
+fs_default_deny_rules?: boolean }
+/**
+ * How an agent handles a prompt sent while it is still working on a turn
+ */
+export type BusyPromptPolicy = 
+/**
+ * Hold the prompt and send it once the current turn completes
+ */
+"queue" | 
+/**
+ * Refuse the prompt with `ApiError::TurnInProgress`
+ */
+"reject"
+/**
+ * What `cache_clear` removed
+ */
+export type CacheClearResult = { 
+/**
+ * Plugin IDs whose installation was removed
+ */
+removedPlugins: string[]; 
+/**
+ * Temp files of interrupted agent writes removed from workspaces
+ */
+removedTempFiles: number; 
+/**
+ * Exited terminals whose retained output was dropped
+ */
+releasedTerminals: number; 
+/**
+ * Bytes freed on disk
+ * Using f64 for JavaScript number compatibility
+ */
+freedBytes: number }
+/**
+ * Disk usage of the app cache returned to the frontend
+ */
+export type CacheInfo = { 
+/**
+ * Absolute path to the plugins cache directory
+ */
+pluginsRoot: string; 
+/**
+ * Installed plugin IDs found in the cache
+ */
+pluginIds: string[]; 
+/**
+ * Total size of the cache in bytes
+ * Using f64 for JavaScript number compatibility
+ */
+totalBytes: number }
+/**
+ * Snapshot of app and runtime state for bug reports
+ */
+export type Diagnostics = { 
+/**
+ * Version of the app backend (`CARGO_PKG_VERSION`)
+ */
+appVersion: string; workspaceCount: number; 
+/**
+ * Agents across all workspaces, running or not
+ */
+agentCount: number; 
+/**
+ * Agents whose adapter process is running
+ */
+runningAgentCount: number; 
+/**
+ * Terminal commands still running
+ */
+activeTerminalCount: number; 
+/**
+ * Permission requests awaiting a decision
+ */
+pendingPermissionCount: number; 
+/**
+ * Plugins in the local cache, sorted by ID
+ */
+plugins: InstalledPluginVersion[] }
+/**
+ * One entry of a workspace directory listing
+ */
+export type DirEntryInfo = { 
+/**
+ * File or directory name (no path)
+ */
+name: string; 
+/**
+ * Whether the entry is a directory (symlinks report their target)
+ */
+isDir: boolean; 
+/**
+ * Size in bytes (0 for directories)
+ */
+size: number }
+/**
+ * A problem found while parsing a workspace `.env` file
+ */
+export type EnvFileWarning = { 
+/**
+ * 1-based line number
+ */
+line: number; 
+/**
+ * Description of the problem
  */
-workspaceId: string; 
+message: string }
 /**
- * Plugin identifier (e.g., "claude-code", "codex", "gemini")
+ * Preview of the variables a workspace `.env` would pass to adapters.
+ * 
+ * Values are never included; only variable names are exposed.
  */
-pluginId: string; 
+export type EnvPreview = { 
 /**
- * Optional display name for the agent
+ * Whether a `.env` file exists in the workspace root
  */
-displayName: string | null }
+exists: boolean; 
 /**
- * API errors for frontend consumption
+ * Variable names that would be injected, in file order
  */
-export type ApiError = 
+keys: string[]; 
 /**
- * Invalid input parameter
+ * Lines that were skipped or overridden
  */
-{ type: "invalidInput"; message: string } | 
+warnings: EnvFileWarning[] }
 /**
- * Path does not exist or is not accessible
+ * Size, modification time and type of a workspace path
  */
-{ type: "pathNotFound"; path: string } | 
+export type FileMetadata = { 
 /**
- * Path is not a directory
+ * The entry is a regular file (false for a symlink itself)
  */
-{ type: "pathNotDirectory"; path: string } | 
+isFile: boolean; 
 /**
- * IO error during file system operation
+ * The entry is a directory (false for a symlink itself)
  */
-{ type: "ioError"; message: string } | 
+isDir: boolean; 
 /**
- * Workspace not found by ID
+ * The entry is a symlink; size and time then describe its target
  */
-{ type: "workspaceNotFound"; workspaceId: string } | 
+isSymlink: boolean; 
 /**
- * Agent not found by ID
+ * Size in bytes
  */
-{ type: "agentNotFound"; agentId: string } | 
+len: number; 
 /**
- * Operation not found by ID (e.g., permission already resolved, expired, or never existed)
+ * Last modification (milliseconds since epoch), if the platform reports it
  */
-{ type: "operationNotFound"; operationId: string } | 
+modifiedMs: number | null }
 /**
- * Permission was denied by the user
+ * A file path rule, matched against workspace-relative paths.
+ * 
+ * Allow rules approve reads only; writes, deletes and renames still prompt.
+ * Deny rules refuse reads and writes alike. `pattern` is a glob if it
+ * contains `*` or `?` (matched against each path component when it has no
+ * `/`, so `.env*` covers `app/.env.local`), and otherwise a path prefix
+ * matched on whole components (`src` covers `src/main.rs`).
  */
-{ type: "permissionDenied"; operationId: string } | 
+export type FsPathRule = { action: PolicyAction; pattern: string }
 /**
- * Plugin installation is already in progress
+ * Installed plugin and its version, as reported by `diagnostics`
  */
-{ type: "pluginInstallInProgress"; pluginId: string } | 
+export type InstalledPluginVersion = { pluginId: string; 
 /**
- * Plugin is not installed (required for lazy startup)
+ * None if the install metadata is missing or unreadable
  */
-{ type: "pluginNotInstalled"; pluginId: string } | 
+installedVersion: string | null }
+export type JsonValue = null | boolean | number | string | JsonValue[] | Partial<{ [key in string]: JsonValue }>
 /**
- * Plugin is installed but missing binary path in metadata
+ * Environment variable passed to an MCP server process
  */
-{ type: "pluginMissingBinPath"; pluginId: string } | 
+export type McpEnvVariable = { name: string; value: string }
 /**
- * Protocol error during ACP communication
+ * Stdio MCP server an agent's adapter should connect to.
+ * 
+ * Sent with `session/new` and `session/load` so the agent can use the
+ * server's tools.
  */
-{ type: "protocolError"; message: string }
+export type McpServerConfig = { 
 /**
- * Application preferences that persist to disk.
- * Only contains settings that should be saved between sessions.
+ * Human-readable server name (e.g., "filesystem")
  */
-export type AppPreferences = { theme: string; 
+name: string; 
 /**
- * Color theme name (e.g., "default", "claude")
+ * Executable that launches the server
  */
-color_theme?: string; 
+command: string; 
 /**
- * Global shortcut for quick pane (e.g., "CommandOrControl+Shift+.")
- * If None, uses the default shortcut
+ * Command-line arguments for the server
  */
-quick_pane_shortcut: string | null; 
+args?: string[]; 
 /**
- * User's preferred language (e.g., "en", "es", "de")
- * If None, uses system locale detection
+ * Environment variables for the server process
  */
-language: string | null }
-export type JsonValue = null | boolean | number | string | JsonValue[] | Partial<{ [key in string]: JsonValue }>
+env?: McpEnvVariable[] }
+/**
+ * Severity of a user-facing notification
+ */
+export type NotificationLevel = "info" | "success" | "warning" | "error"
 /**
  * Response when an async operation is started
  */
@@ -559,6 +1955,52 @@ export type OperationStarted = {
  * Unique operation identifier for tracking
  */
 operationId: string }
+/**
+ * Package manager used to install a plugin.
+ * 
+ * Persisted in `install.json` so later updates use the same tool.
+ */
+export type PackageManager = "npm" | "pnpm" | "yarn"
+/**
+ * One page of a list, plus the length of the whole list
+ */
+export type Page<T> = { 
+/**
+ * Items on this page, in list order
+ */
+items: T[]; 
+/**
+ * Number of items in the whole list
+ */
+total: number }
+/**
+ * How a permission request was resolved, as recorded in the audit log
+ */
+export type PermissionAuditDecision = "allowOnce" | "allowAlways" | "deny" | 
+/**
+ * Approved without asking by a remembered "Allow Always" grant
+ */
+"remembered" | 
+/**
+ * Approved without asking by a terminal command or file path rule from preferences
+ */
+"allowedByRule" | 
+/**
+ * Refused without asking by a terminal command or file path rule from preferences
+ */
+"deniedByRule" | 
+/**
+ * Nobody answered before the request timed out
+ */
+"timedOut" | 
+/**
+ * The operation was abandoned before anyone answered
+ */
+"cancelled"
+/**
+ * One resolved permission request in the audit log
+ */
+export type PermissionAuditEntry = { operationId: string; source: PermissionSource; origin: PermissionOrigin | null; decision: PermissionAuditDecision; decidedAtMs: number }
 /**
  * User decision for a permission request
  */
@@ -567,10 +2009,168 @@ export type PermissionDecision =
  * Allow this operation once
  */
 "allowOnce" | 
+/**
+ * Allow this operation and remember the grant for matching requests
+ * from the same workspace/agent
+ */
+"allowAlways" | 
 /**
  * Deny this operation
  */
 "deny"
+/**
+ * A choice offered by the agent for a permission request
+ */
+export type PermissionOption = { 
+/**
+ * Agent-defined identifier echoed back when chosen
+ */
+optionId: string; 
+/**
+ * Label to show the user (e.g., "Always Allow")
+ */
+name: string; 
+/**
+ * What choosing this option means
+ */
+kind: PermissionOptionKind }
+/**
+ * What choosing a permission option means (mirrors ACP `PermissionOptionKind`)
+ */
+export type PermissionOptionKind = 
+/**
+ * Allow this operation only this time
+ */
+"allowOnce" | 
+/**
+ * Allow this operation and remember the choice
+ */
+"allowAlways" | 
+/**
+ * Reject this operation only this time
+ */
+"rejectOnce" | 
+/**
+ * Reject this operation and remember the choice
+ */
+"rejectAlways"
+/**
+ * Origin context for a permission request (optional scoping)
+ */
+export type PermissionOrigin = { 
+/**
+ * Workspace context (if applicable)
+ */
+workspaceId: string | null; 
+/**
+ * Agent context (if applicable)
+ */
+agentId: string | null; 
+/**
+ * Session context (if applicable)
+ */
+sessionId: string | null; 
+/**
+ * Tool call context (if applicable)
+ */
+toolCallId: string | null }
+/**
+ * A remembered "Allow Always" grant, scoped to the workspace (and agent)
+ * that requested it
+ */
+export type PermissionRule = { ruleId: string; kind: PermissionRuleKind; 
+/**
+ * Command prefix, or directory relative to the workspace root (`.`: all)
+ */
+pattern: string; workspaceId: string; 
+/**
+ * Agent the grant applies to (`None`: any agent in the workspace)
+ */
+agentId: string | null; createdAtMs: number }
+/**
+ * What a remembered permission grant covers
+ */
+export type PermissionRuleKind = 
+/**
+ * Terminal commands starting with the pattern
+ */
+"terminalCommand" | 
+/**
+ * File reads inside the pattern directory
+ */
+"fsRead" | 
+/**
+ * File writes inside the pattern directory
+ */
+"fsWrite"
+/**
+ * Source of a permission request
+ */
+export type PermissionSource = 
+/**
+ * User-initiated plugin installation
+ */
+{ type: "installPlugin"; plugin_id: string; version: string | null } | 
+/**
+ * Agent-requested terminal execution
+ */
+{ type: "terminalRun"; command: string } | 
+/**
+ * Agent-requested file read
+ */
+{ type: "fsReadTextFile"; 
+/**
+ * Path exactly as supplied by the agent
+ */
+path: string; 
+/**
+ * Canonical path relative to the workspace root (what will be read)
+ */
+resolved_path: string } | 
+/**
+ * Agent-requested file write
+ */
+{ type: "fsWriteTextFile"; 
+/**
+ * Path exactly as supplied by the agent
+ */
+path: string; 
+/**
+ * Canonical path relative to the workspace root (what will be written)
+ */
+resolved_path: string; content_preview: string; content_truncated: boolean; 
+/**
+ * Content is appended rather than replacing the file
+ */
+append: boolean } | 
+/**
+ * Agent-requested file (or recursive directory) delete
+ */
+{ type: "fsDelete"; 
+/**
+ * Path exactly as supplied by the agent
+ */
+path: string; 
+/**
+ * Canonical path relative to the workspace root (what will be deleted)
+ */
+resolved_path: string; recursive: boolean } | 
+/**
+ * Agent-requested file rename (move)
+ */
+{ type: "fsRename"; 
+/**
+ * Paths exactly as supplied by the agent
+ */
+from: string; to: string; 
+/**
+ * Canonical paths relative to the workspace root
+ */
+resolved_from: string; resolved_to: string }
+/**
+ * The variant of a `PermissionSource`, without its details
+ */
+export type PermissionSourceKind = "installPlugin" | "terminalRun" | "fsReadTextFile" | "fsWriteTextFile" | "fsDelete" | "fsRename"
 /**
  * Plugin installation and update status returned to the frontend
  */
@@ -599,6 +2199,86 @@ updateAvailable: boolean | null;
  * Path to the plugin binary/entry point (if installed)
  */
 binPath: string | null }
+/**
+ * What a terminal command or file path rule does with matching requests
+ */
+export type PolicyAction = 
+/**
+ * Approve without asking
+ */
+"allow" | 
+/**
+ * Refuse without asking (wins over allow rules)
+ */
+"deny"
+/**
+ * One block of prompt content sent to an agent.
+ * 
+ * Mirrors the subset of ACP `ContentBlock`s the app can produce; the
+ * protocol layer converts each variant to the adapter's wire shape.
+ */
+export type PromptContentBlock = 
+/**
+ * Plain text
+ */
+{ type: "text"; text: string } | 
+/**
+ * Base64-encoded image (e.g. a screenshot)
+ */
+{ type: "image"; mimeType: string; data: string } | 
+/**
+ * Reference to a file or other resource by URI
+ */
+{ type: "resource"; uri: string }
+/**
+ * What went wrong in an ACP protocol error, so callers can react without
+ * parsing the message
+ */
+export type ProtocolErrorKind = 
+/**
+ * The adapter didn't answer initialize or session setup in time
+ */
+"handshakeTimeout" | 
+/**
+ * The adapter rejected initialize or speaks an unsupported version
+ */
+"initializeRejected" | 
+/**
+ * session/new failed or returned no session
+ */
+"sessionCreateFailed" | 
+/**
+ * session/load failed
+ */
+"sessionLoadFailed" | 
+/**
+ * The adapter process or its stdio pipes went away
+ */
+"connectionClosed" | 
+/**
+ * A JSON-RPC message couldn't be serialized
+ */
+"serializationFailed" | 
+/**
+ * The adapter sent a message larger than the frame limit
+ */
+"frameTooLarge" | 
+/**
+ * The adapter answered a request with a JSON-RPC error
+ */
+"requestFailed" | 
+/**
+ * A request wasn't answered within its timeout
+ */
+"requestTimeout" | 
+/**
+ * The agent isn't running or has no connection
+ */
+"notConnected" | 
+/**
+ * The adapter doesn't support the requested operation
+ */
+"unsupported"
 /**
  * Error types for recovery operations (typed for frontend matching)
  */
@@ -630,7 +2310,141 @@ export type SendPromptAck = {
 /**
  * Session identifier for tracking responses
  */
-sessionId: string }
+sessionId: string; 
+/**
+ * Turn started by this prompt; matches `turnId` on its session updates.
+ * `None` while the prompt is queued behind the active turn.
+ */
+turnId: string | null; 
+/**
+ * Whether the prompt waits for the active turn to complete
+ */
+queued: boolean }
+/**
+ * A terminal command rule.
+ * 
+ * `pattern` is a glob over the whole command if it contains `*` or `?`, and
+ * otherwise a prefix matched on whole words (`git status` covers
+ * `git status -s`).
+ */
+export type TerminalCommandRule = { action: PolicyAction; pattern: string }
+/**
+ * A terminal command that is still running
+ */
+export type TerminalInfo = { 
+/**
+ * Terminal identifier
+ */
+terminalId: string; 
+/**
+ * Shell command line being run
+ */
+command: string; 
+/**
+ * When the command was started (ms since epoch)
+ */
+startedAtMs: number }
+/**
+ * Shell used to run terminal commands in a workspace
+ */
+export type TerminalShell = { 
+/**
+ * Shell executable: a path, or a name looked up on `PATH`
+ */
+program: string; 
+/**
+ * Arguments for the shell; an argument equal to `{command}` is replaced
+ * by the command line, which is otherwise appended last (e.g. `["-c"]`)
+ */
+args: string[] }
+/**
+ * Output produced by a tool call
+ */
+export type ToolCallContent = 
+/**
+ * A regular content block (text, image, resource...)
+ */
+{ type: "content"; content: JsonValue } | 
+/**
+ * A file modification
+ */
+{ type: "diff"; path: string; 
+/**
+ * Previous text (`None` for a new file)
+ */
+oldText?: string; newText: string } | 
+/**
+ * Live output of a terminal started by the agent
+ */
+{ type: "terminal"; terminalId: string }
+/**
+ * What kind of tool a tool call runs (ACP `ToolKind`)
+ */
+export type ToolCallKind = "read" | "edit" | "delete" | "move" | "search" | "execute" | "think" | "fetch" | "switch_mode" | 
+/**
+ * Anything else, including kinds added to ACP later
+ */
+"other"
+/**
+ * A file a tool call is working on
+ */
+export type ToolCallLocation = { path: string; 
+/**
+ * 1-based line, when the tool targets a specific spot
+ */
+line?: number }
+/**
+ * Progress of a tool call (ACP `ToolCallStatus`)
+ */
+export type ToolCallStatus = "pending" | "in_progress" | "completed" | "failed"
+/**
+ * Availability of the Node.js toolchain needed to install plugins.
+ * 
+ * Returned by the install preflight so the UI can explain what is missing
+ * before any permission prompt or filesystem work happens.
+ */
+export type ToolchainInfo = { 
+/**
+ * Detected Node.js version without the leading "v" (None if `node` is missing)
+ */
+nodeVersion: string | null; 
+/**
+ * Detected npm version (None if `npm` is missing)
+ */
+npmVersion: string | null; 
+/**
+ * Minimum Node.js version the adapters require
+ */
+minNodeVersion: string; 
+/**
+ * Whether plugins can be installed with the detected toolchain
+ */
+ready: boolean; 
+/**
+ * Human-readable explanation when `ready` is false
+ */
+message: string | null }
+/**
+ * Size limits applied to agent file reads and command output in a workspace
+ */
+export type WorkspaceLimits = { 
+/**
+ * Largest file (in bytes) an agent may read
+ */
+maxReadBytes: number; 
+/**
+ * Bytes of `terminal/run` output kept per stream for the agent
+ */
+outputCaptureLimit: number; 
+/**
+ * Terminal commands that may run at once
+ */
+maxTerminals: number; 
+/**
+ * Prompts an agent may have outstanding per session: the running turn
+ * plus those queued behind it
+ */
+maxOutstandingPrompts: number }
 /**
  * Summary of a workspace returned to the frontend
  */
@@ -643,6 +2457,10 @@ workspaceId: string;
  * Canonicalized absolute path to workspace root
  */
 rootDir: string; 
+/**
+ * Display name: the user's chosen name, else the root directory's name
+ */
+name: string | null; 
 /**
  * Timestamp when workspace was created (milliseconds since epoch)
  * Using f64 for JavaScript number compatibility
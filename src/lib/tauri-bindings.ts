@@ -23,6 +23,9 @@
 
 export { commands, type Result } from './bindings'
 export type {
+  AcpSessionUpdate,
+  AcpSessionUpdateEvent,
+  AgentRuntimeStatus,
   AgentSummary,
   AppPreferences,
   JsonValue,
@@ -48,7 +48,7 @@ export function useAgentsList(workspaceId: string | null) {
       }
 
       logger.debug('Loading agents list from backend', { workspaceId })
-      const result = await commands.agentList(workspaceId)
+      const result = await commands.agentList(workspaceId, null, null)
 
       if (result.status === 'error') {
         logger.error('Failed to load agents list', { error: result.error })
@@ -57,9 +57,9 @@ export function useAgentsList(workspaceId: string | null) {
 
       logger.debug('Agents list loaded', {
         workspaceId,
-        count: result.data.length,
+        count: result.data.total,
       })
-      return result.data
+      return result.data.items
     },
     enabled: !!workspaceId,
     staleTime: 1000 * 30, // 30 seconds
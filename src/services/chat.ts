@@ -78,7 +78,14 @@ export function useChatSendPrompt() {
         promptLength: prompt.length,
       })
 
-      const result = await commands.chatSendPrompt(workspaceId, agentId, prompt)
+      const result = await commands.chatSendPrompt(
+        workspaceId,
+        agentId,
+        null,
+        prompt,
+        null,
+        null
+      )
 
       if (result.status === 'error') {
         logger.error('Failed to send prompt', {
@@ -74,7 +74,11 @@ export function useInstallPlugin() {
       version?: string
     }) => {
       logger.info('Starting plugin installation', { pluginId, version })
-      const result = await commands.pluginInstall(pluginId, version ?? null)
+      const result = await commands.pluginInstall(
+        pluginId,
+        version ?? null,
+        null
+      )
 
       if (result.status === 'error') {
         logger.error('Failed to start plugin installation', {
@@ -111,7 +115,11 @@ export function usePermissionRespond() {
       decision: PermissionDecision
     }) => {
       logger.info('Responding to permission request', { operationId, decision })
-      const result = await commands.permissionRespond(operationId, decision)
+      const result = await commands.permissionRespond(
+        operationId,
+        decision,
+        null
+      )
 
       if (result.status === 'error') {
         logger.error('Failed to respond to permission', {
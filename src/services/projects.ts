@@ -43,15 +43,15 @@ export function useProjectsList() {
     queryKey: projectsQueryKeys.list(),
     queryFn: async (): Promise<WorkspaceSummary[]> => {
       logger.debug('Loading projects list from backend')
-      const result = await commands.workspaceList()
+      const result = await commands.workspaceList(null, null)
 
       if (result.status === 'error') {
         logger.error('Failed to load projects list', { error: result.error })
         throw result.error
       }
 
-      logger.debug('Projects list loaded', { count: result.data.length })
-      return result.data
+      logger.debug('Projects list loaded', { count: result.data.total })
+      return result.data.items
     },
     staleTime: 1000 * 30, // 30 seconds
     gcTime: 1000 * 60 * 5, // 5 minutes